@@ -457,7 +457,7 @@ pub enum ServerCommands {
     /// Perform Healthcheck
     Healthcheck {
         /// Status `ready` (default) or `live` to check for
-        check: Option<String>
+        check: Option<String>,
     },
 }
 
@@ -492,6 +492,9 @@ pub enum QueueCommands {
         /// Number of items to show per page
         #[clap(short, long)]
         page_size: Option<usize>,
+        /// Only show messages held for review
+        #[clap(long)]
+        held: bool,
     },
 
     /// Displays details about a queued message
@@ -543,6 +546,43 @@ pub enum QueueCommands {
         // Cancel one or multiple message ids
         ids: Vec<String>,
     },
+
+    /// Hold messages for review
+    Hold {
+        /// Reason for holding the messages
+        #[clap(long)]
+        reason: Option<String>,
+        // Hold one or multiple message ids
+        #[clap(required = true)]
+        ids: Vec<String>,
+    },
+
+    /// Release held messages for delivery
+    Release {
+        /// Apply to held messages matching a sender address
+        #[clap(short, long)]
+        sender: Option<String>,
+        /// Apply to specific recipients or domains
+        #[clap(short, long)]
+        rcpt: Option<String>,
+        // Release one or multiple message ids
+        ids: Vec<String>,
+    },
+
+    /// Reject held messages and notify the sender
+    Reject {
+        /// Apply to held messages matching a sender address
+        #[clap(short, long)]
+        sender: Option<String>,
+        /// Apply to specific recipients or domains
+        #[clap(short, long)]
+        rcpt: Option<String>,
+        /// Reason included in the delivery status notification
+        #[clap(long)]
+        reason: Option<String>,
+        // Reject one or multiple message ids
+        ids: Vec<String>,
+    },
 }
 
 #[derive(Subcommand)]
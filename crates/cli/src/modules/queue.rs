@@ -44,6 +44,8 @@ pub struct Recipient {
     pub expires: Option<DateTime>,
     #[serde(default)]
     pub orcpt: Option<String>,
+    #[serde(default)]
+    pub held: bool,
 }
 
 #[derive(Debug, PartialEq, Eq, Deserialize)]
@@ -67,9 +69,12 @@ impl QueueCommands {
                 before,
                 after,
                 page_size,
+                held,
             } => {
                 let stdout = Term::buffered_stdout();
-                let ids = client.query_messages(&sender, &rcpt, &before, &after).await;
+                let ids = client
+                    .query_messages(&sender, &rcpt, &before, &after, held)
+                    .await;
                 let ids_len = ids.len();
                 let page_size = page_size.map(|p| std::cmp::max(p, 1)).unwrap_or(20);
                 let pages_total = (ids_len as f64 / page_size as f64).ceil() as usize;
@@ -108,7 +113,11 @@ impl QueueCommands {
                             }
                             rcpts.push_str(&rcpt.address);
                             rcpts.push_str(" (");
-                            rcpts.push_str(rcpt.status.status_short());
+                            rcpts.push_str(if rcpt.held {
+                                "held"
+                            } else {
+                                rcpt.status.status_short()
+                            });
                             rcpts.push(')');
                         }
 
@@ -208,7 +217,11 @@ impl QueueCommands {
                             ]));
                             table.add_row(Row::new(vec![
                                 Cell::new("Status").with_style(Attr::Bold),
-                                Cell::new(rcpt.status.status()),
+                                Cell::new(if rcpt.held {
+                                    "Held"
+                                } else {
+                                    rcpt.status.status()
+                                }),
                             ]));
                             table.add_row(Row::new(vec![
                                 Cell::new("Details").with_style(Attr::Bold),
@@ -261,7 +274,7 @@ impl QueueCommands {
                 let (parsed_ids, ids) = if ids.is_empty() {
                     if sender.is_some() || domain.is_some() || before.is_some() || after.is_some() {
                         let parsed_ids = client
-                            .query_messages(&sender, &domain, &before, &after)
+                            .query_messages(&sender, &domain, &before, &after, false)
                             .await;
                         let ids = parsed_ids.iter().map(|id| format!("{id:X}")).collect();
                         (parsed_ids, ids)
@@ -317,8 +330,9 @@ impl QueueCommands {
             } => {
                 let (parsed_ids, ids) = if ids.is_empty() {
                     if sender.is_some() || rcpt.is_some() || before.is_some() || after.is_some() {
-                        let parsed_ids =
-                            client.query_messages(&sender, &rcpt, &before, &after).await;
+                        let parsed_ids = client
+                            .query_messages(&sender, &rcpt, &before, &after, false)
+                            .await;
                         let ids = parsed_ids.iter().map(|id| format!("{id:X}")).collect();
                         (parsed_ids, ids)
                     } else {
@@ -364,6 +378,62 @@ impl QueueCommands {
                 }
                 eprintln!();
             }
+            QueueCommands::Hold { reason, ids } => {
+                let mut success_count = 0;
+                let mut failed_list = vec![];
+
+                for id in parse_ids(&ids) {
+                    let mut query =
+                        form_urlencoded::Serializer::new(format!("/api/queue/held/{id}"));
+
+                    if let Some(reason) = &reason {
+                        query.append_pair("reason", reason);
+                    }
+
+                    if client
+                        .try_http_request::<bool, String>(Method::POST, &query.finish(), None)
+                        .await
+                        .unwrap_or(false)
+                    {
+                        success_count += 1;
+                    } else {
+                        failed_list.push(format!("{id:X}"));
+                    }
+                }
+
+                eprint!("\nHeld {success_count} message(s) for review.");
+                if !failed_list.is_empty() {
+                    eprint!(" Unable to hold id(s): {}.", failed_list.join(", "));
+                }
+                eprintln!();
+            }
+            QueueCommands::Release { sender, rcpt, ids } => {
+                let (success_count, failed_list) = client
+                    .update_held_messages(Method::PATCH, sender, rcpt, None, ids)
+                    .await;
+
+                eprint!("\nReleased {success_count} message(s) for delivery.");
+                if !failed_list.is_empty() {
+                    eprint!(" Unable to release id(s): {}.", failed_list.join(", "));
+                }
+                eprintln!();
+            }
+            QueueCommands::Reject {
+                sender,
+                rcpt,
+                reason,
+                ids,
+            } => {
+                let (success_count, failed_list) = client
+                    .update_held_messages(Method::DELETE, sender, rcpt, reason, ids)
+                    .await;
+
+                eprint!("\nRejected {success_count} message(s).");
+                if !failed_list.is_empty() {
+                    eprint!(" Unable to reject id(s): {}.", failed_list.join(", "));
+                }
+                eprintln!();
+            }
         }
     }
 }
@@ -375,9 +445,13 @@ impl Client {
         rcpt: &Option<String>,
         before: &Option<DateTime>,
         after: &Option<DateTime>,
+        held: bool,
     ) -> Vec<u64> {
         let mut query = form_urlencoded::Serializer::new("/api/queue/messages".to_string());
 
+        if held {
+            query.append_pair("held", "true");
+        }
         if let Some(sender) = from {
             query.append_pair("from", sender);
         }
@@ -395,6 +469,57 @@ impl Client {
             .await
             .items
     }
+
+    async fn update_held_messages(
+        &self,
+        method: Method,
+        sender: Option<String>,
+        rcpt: Option<String>,
+        reason: Option<String>,
+        ids: Vec<String>,
+    ) -> (usize, Vec<String>) {
+        let parsed_ids = if ids.is_empty() {
+            if sender.is_some() || rcpt.is_some() {
+                self.query_messages(&sender, &rcpt, &None, &None, true)
+                    .await
+            } else {
+                vec![]
+            }
+        } else {
+            parse_ids(&ids)
+        };
+
+        if parsed_ids.is_empty() {
+            eprintln!("No messages were found.");
+            std::process::exit(1);
+        }
+
+        let mut success_count = 0;
+        let mut failed_list = vec![];
+
+        for id in parsed_ids {
+            let mut query = form_urlencoded::Serializer::new(format!("/api/queue/held/{id}"));
+
+            if let Some(filter) = &rcpt {
+                query.append_pair("filter", filter);
+            }
+            if let Some(reason) = &reason {
+                query.append_pair("reason", reason);
+            }
+
+            if self
+                .try_http_request::<bool, String>(method.clone(), &query.finish(), None)
+                .await
+                .unwrap_or(false)
+            {
+                success_count += 1;
+            } else {
+                failed_list.push(format!("{id:X}"));
+            }
+        }
+
+        (success_count, failed_list)
+    }
 }
 
 fn deserialize_maybe_datetime<'de, D>(deserializer: D) -> Result<Option<DateTime>, D::Error>
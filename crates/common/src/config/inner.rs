@@ -73,6 +73,8 @@ impl Data {
                 .unwrap_or_default(),
             logos: Default::default(),
             smtp_connectors: TlsConnectors::default(),
+            smtp_connection_pool: Default::default(),
+            smtp_throttled_domains: Default::default(),
             asn_geo_data: Default::default(),
         }
     }
@@ -231,6 +233,8 @@ impl Default for Data {
             webadmin: Default::default(),
             logos: Default::default(),
             smtp_connectors: Default::default(),
+            smtp_connection_pool: Default::default(),
+            smtp_throttled_domains: Default::default(),
             asn_geo_data: Default::default(),
         }
     }
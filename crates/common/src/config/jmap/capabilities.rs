@@ -15,7 +15,7 @@ use jmap_proto::{
         BlobCapabilities, CalendarCapabilities, Capabilities, Capability, ContactsCapabilities,
         CoreCapabilities, EmptyCapabilities, FileNodeCapabilities, MailCapabilities,
         PrincipalAvailabilityCapabilities, PrincipalCapabilities, SieveAccountCapabilities,
-        SieveSessionCapabilities, SubmissionCapabilities,
+        SieveSessionCapabilities, SubmissionCapabilities, TasksCapabilities,
     },
     types::date::UTCDate,
 };
@@ -99,6 +99,20 @@ impl JmapConfig {
             Capabilities::Empty(EmptyCapabilities::default()),
         );
 
+        // Add tasks capabilities
+        self.capabilities.session.append(
+            Capability::Tasks,
+            Capabilities::Empty(EmptyCapabilities::default()),
+        );
+        self.capabilities.account.insert(
+            Capability::Tasks,
+            Capabilities::Tasks(TasksCapabilities {
+                min_date_time: UTCDate::from_timestamp(DateTime::<Utc>::MIN_UTC.timestamp()),
+                max_date_time: UTCDate::from_timestamp(DateTime::<Utc>::MAX_UTC.timestamp()),
+                may_create_task_list: true,
+            }),
+        );
+
         // Add contacts capabilities
         self.capabilities.session.append(
             Capability::Contacts,
@@ -135,6 +149,46 @@ impl JmapConfig {
             }),
         );
 
+        // Add MDN capabilities
+        self.capabilities.session.append(
+            Capability::Mdn,
+            Capabilities::Empty(EmptyCapabilities::default()),
+        );
+        self.capabilities.account.insert(
+            Capability::Mdn,
+            Capabilities::Empty(EmptyCapabilities::default()),
+        );
+
+        // Add S/MIME verification capabilities
+        self.capabilities.session.append(
+            Capability::SmimeVerify,
+            Capabilities::Empty(EmptyCapabilities::default()),
+        );
+        self.capabilities.account.insert(
+            Capability::SmimeVerify,
+            Capabilities::Empty(EmptyCapabilities::default()),
+        );
+
+        // Add mailbox metadata capabilities
+        self.capabilities.session.append(
+            Capability::Metadata,
+            Capabilities::Empty(EmptyCapabilities::default()),
+        );
+        self.capabilities.account.insert(
+            Capability::Metadata,
+            Capabilities::Empty(EmptyCapabilities::default()),
+        );
+
+        // Add virtual mailbox capabilities
+        self.capabilities.session.append(
+            Capability::VirtualMailbox,
+            Capabilities::Empty(EmptyCapabilities::default()),
+        );
+        self.capabilities.account.insert(
+            Capability::VirtualMailbox,
+            Capabilities::Empty(EmptyCapabilities::default()),
+        );
+
         // Add principal capabilities
         self.capabilities.session.append(
             Capability::Principals,
@@ -8,7 +8,9 @@ use crate::config::groupware::GroupwareConfig;
 use ahash::{AHashMap, AHashSet};
 use jmap_proto::request::capability::BaseCapabilities;
 use nlp::language::Language;
-use std::{str::FromStr, time::Duration};
+use rustls_pemfile::certs;
+use rustls_pki_types::CertificateDer;
+use std::{io::Cursor, str::FromStr, time::Duration};
 use store::{search::SearchField, write::SearchIndex};
 use types::{collection::Collection, special_use::SpecialUse};
 use utils::{
@@ -42,6 +44,7 @@ pub struct JmapConfig {
 
     pub mailbox_max_depth: usize,
     pub mailbox_name_max_len: usize,
+    pub mailbox_max_virtual: usize,
     pub mail_attachments_max_size: usize,
     pub mail_parse_max_items: usize,
     pub mail_max_size: usize,
@@ -80,6 +83,7 @@ pub struct JmapConfig {
 
     pub encrypt: bool,
     pub encrypt_append: bool,
+    pub smime_trust_anchors: Vec<CertificateDer<'static>>,
 
     pub index_batch_size: usize,
     pub index_fields: AHashMap<SearchIndex, AHashSet<SearchField>>,
@@ -216,6 +220,27 @@ impl JmapConfig {
             ));
         }
 
+        // Parse S/MIME trust anchors, without them signatures are reported as
+        // signed since the certificate chain of the signer cannot be validated
+        let mut smime_trust_anchors = Vec::new();
+        for (key, value) in config
+            .values("email.smime.trust-anchors")
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<Vec<_>>()
+        {
+            match certs(&mut Cursor::new(value.as_bytes())).collect::<Result<Vec<_>, _>>() {
+                Ok(anchors) if !anchors.is_empty() => {
+                    smime_trust_anchors.extend(anchors);
+                }
+                Ok(_) => {
+                    config.new_parse_error(key, "No certificates found.");
+                }
+                Err(err) => {
+                    config.new_parse_error(key, format!("Failed to read certificates: {err}"));
+                }
+            }
+        }
+
         let mut jmap = JmapConfig {
             default_language: Language::from_iso_639(
                 config
@@ -273,6 +298,7 @@ impl JmapConfig {
             mailbox_name_max_len: config
                 .property("jmap.mailbox.max-name-length")
                 .unwrap_or(255),
+            mailbox_max_virtual: config.property("jmap.mailbox.max-virtual").unwrap_or(25),
             mail_attachments_max_size: config
                 .property("jmap.email.max-attachment-size")
                 .unwrap_or(50000000),
@@ -315,6 +341,7 @@ impl JmapConfig {
             encrypt_append: config
                 .property_or_default("email.encryption.append", "false")
                 .unwrap_or(false),
+            smime_trust_anchors,
             http_use_forwarded: config.property("http.use-x-forwarded").unwrap_or(false),
             http_headers,
             push_attempt_interval: config
@@ -5,8 +5,8 @@
  */
 
 use self::{
-    imap::ImapConfig, jmap::settings::JmapConfig, scripts::Scripting, smtp::SmtpConfig,
-    storage::Storage,
+    imap::ImapConfig, jmap::settings::JmapConfig, pop3::Pop3Config, scripts::Scripting,
+    server::dns::DnsProviders, smtp::SmtpConfig, storage::Storage,
 };
 use crate::{
     Core, Network, Security, auth::oauth::config::OAuthConfig, expr::*,
@@ -28,6 +28,7 @@ pub mod imap;
 pub mod inner;
 pub mod jmap;
 pub mod network;
+pub mod pop3;
 pub mod scripts;
 pub mod server;
 pub mod smtp;
@@ -197,8 +198,10 @@ impl Core {
             smtp: SmtpConfig::parse(config).await,
             jmap: JmapConfig::parse(config, &groupware),
             imap: ImapConfig::parse(config),
+            pop3: Pop3Config::parse(config),
             oauth: OAuthConfig::parse(config),
             acme: AcmeProviders::parse(config),
+            dns: DnsProviders::parse(config),
             metrics: Metrics::parse(config),
             spam: SpamFilterConfig::parse(config).await,
             groupware,
@@ -45,8 +45,11 @@ pub struct ClusterRoles {
     pub spam_training: ClusterRole,
     pub imip_processing: ClusterRole,
     pub merge_threads: ClusterRole,
+    pub unsnooze_emails: ClusterRole,
     pub calendar_alerts: ClusterRole,
     pub renew_acme: ClusterRole,
+    pub sync_dns: ClusterRole,
+    pub rotate_dkim: ClusterRole,
     pub calculate_metrics: ClusterRole,
     pub push_metrics: ClusterRole,
 }
@@ -229,6 +232,8 @@ impl Network {
                 "cluster.roles.purge.accounts",
             ),
             (&mut network.roles.renew_acme, "cluster.roles.acme.renew"),
+            (&mut network.roles.sync_dns, "cluster.roles.dns.sync"),
+            (&mut network.roles.rotate_dkim, "cluster.roles.dkim.rotate"),
             (
                 &mut network.roles.calculate_metrics,
                 "cluster.roles.metrics.calculate",
@@ -261,6 +266,10 @@ impl Network {
                 &mut network.roles.merge_threads,
                 "cluster.roles.merge-threads",
             ),
+            (
+                &mut network.roles.unsnooze_emails,
+                "cluster.roles.unsnooze-emails",
+            ),
         ] {
             let shards = config
                 .properties::<NodeList>(key)
@@ -0,0 +1,65 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::Duration;
+
+use utils::config::Config;
+
+#[derive(Default, Clone)]
+pub struct Pop3Config {
+    pub policy: Pop3Policy,
+}
+
+#[derive(
+    rkyv::Archive,
+    rkyv::Deserialize,
+    rkyv::Serialize,
+    serde::Serialize,
+    serde::Deserialize,
+    Debug,
+    Default,
+    Clone,
+    PartialEq,
+    Eq,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct Pop3Policy {
+    // Seconds after the first download when a message is removed from the server
+    #[serde(default)]
+    pub expire_after: Option<u64>,
+    // Mailbox exposed over POP3, defaults to INBOX
+    #[serde(default)]
+    pub mailbox: Option<String>,
+    // Hide messages that were downloaded in a previous session
+    #[serde(default)]
+    pub hide_downloaded: bool,
+}
+
+impl Pop3Policy {
+    pub fn is_tracking(&self) -> bool {
+        self.hide_downloaded || self.expire_after.is_some()
+    }
+}
+
+impl Pop3Config {
+    pub fn parse(config: &mut Config) -> Self {
+        Pop3Config {
+            policy: Pop3Policy {
+                expire_after: config
+                    .property::<Option<Duration>>("pop3.retention.expire-after")
+                    .unwrap_or_default()
+                    .map(|d| d.as_secs()),
+                mailbox: config
+                    .value("pop3.mailbox")
+                    .filter(|v| !v.is_empty() && !v.eq_ignore_ascii_case("inbox"))
+                    .map(|v| v.to_string()),
+                hide_downloaded: config
+                    .property_or_default("pop3.retention.hide-downloaded", "false")
+                    .unwrap_or(false),
+            },
+        }
+    }
+}
@@ -0,0 +1,146 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+
+use ahash::AHashMap;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use dns_update::{DnsUpdater, TsigAlgorithm, providers::rfc2136::DnsAddress};
+use hickory_client::{client::Signer, proto::rr::dnssec::tsig::TSigner, rr::Name};
+use utils::config::Config;
+
+use super::tls::build_dns_updater;
+
+#[derive(Default, Clone)]
+pub struct DnsProviders {
+    pub providers: AHashMap<String, Arc<DnsProvider>>,
+    pub sync_frequency: Duration,
+}
+
+#[derive(Clone)]
+pub struct DnsProvider {
+    pub id: String,
+    pub updater: DnsUpdater,
+    pub tlsa_updater: Option<TlsaUpdater>,
+    pub domains: Vec<String>,
+    pub origin: Option<String>,
+    pub ttl: u32,
+}
+
+// DnsUpdater has no support for TLSA records, these are
+// published using RFC 2136 directly when the provider supports it.
+#[derive(Clone)]
+pub struct TlsaUpdater {
+    pub addr: DnsAddress,
+    pub signer: Arc<Signer>,
+}
+
+impl DnsProviders {
+    pub fn parse(config: &mut Config) -> Self {
+        let mut providers = AHashMap::new();
+
+        for provider_id in config.sub_keys("dns.provider", ".provider") {
+            let provider_id = provider_id.as_str();
+            let Some(updater) = build_dns_updater(config, "dns.provider", provider_id) else {
+                continue;
+            };
+
+            // Domains managed by this provider
+            let domains = config
+                .values(("dns.provider", provider_id, "domains"))
+                .map(|(_, s)| s.trim().trim_end_matches('.').to_lowercase())
+                .filter(|s| !s.is_empty())
+                .collect::<Vec<_>>();
+            if domains.is_empty() {
+                config
+                    .new_parse_error(("dns.provider", provider_id, "domains"), "Missing property");
+                continue;
+            }
+
+            providers.insert(
+                provider_id.to_string(),
+                Arc::new(DnsProvider {
+                    id: provider_id.to_string(),
+                    tlsa_updater: build_tlsa_updater(config, provider_id),
+                    updater,
+                    domains,
+                    origin: config
+                        .value(("dns.provider", provider_id, "origin"))
+                        .map(|s| s.trim().to_string()),
+                    ttl: config
+                        .property_or_default(("dns.provider", provider_id, "ttl"), "1h")
+                        .unwrap_or_else(|| Duration::from_secs(3600))
+                        .as_secs() as u32,
+                }),
+            );
+        }
+
+        DnsProviders {
+            providers,
+            sync_frequency: config
+                .property_or_default("dns.managed.frequency", "1h")
+                .unwrap_or_else(|| Duration::from_secs(3600)),
+        }
+    }
+
+    pub fn get(&self, domain: &str) -> Option<&Arc<DnsProvider>> {
+        self.providers
+            .values()
+            .find(|provider| provider.domains.iter().any(|d| d == domain))
+    }
+}
+
+impl DnsProvider {
+    pub fn zone_origin(&self, domain: &str) -> String {
+        self.origin
+            .as_deref()
+            .or_else(|| psl::domain_str(domain))
+            .unwrap_or(domain)
+            .to_string()
+    }
+}
+
+fn build_tlsa_updater(config: &mut Config, id: &str) -> Option<TlsaUpdater> {
+    // Errors have been already reported while building the DnsUpdater
+    if config.value(("dns.provider", id, "provider")) != Some("rfc2136-tsig") {
+        return None;
+    }
+    let algorithm = config
+        .value(("dns.provider", id, "tsig-algorithm"))?
+        .parse::<TsigAlgorithm>()
+        .ok()?;
+    let key = STANDARD
+        .decode(config.value(("dns.provider", id, "secret"))?.trim())
+        .ok()?;
+    let key_name = Name::from_ascii(config.value(("dns.provider", id, "key"))?.trim()).ok()?;
+    let host = config.property::<IpAddr>(("dns.provider", id, "host"))?;
+    let port = config
+        .property_or_default::<u16>(("dns.provider", id, "port"), "53")
+        .unwrap_or(53);
+    let addr = if config.value(("dns.provider", id, "protocol")) == Some("tcp") {
+        DnsAddress::Tcp(SocketAddr::new(host, port))
+    } else {
+        DnsAddress::Udp(SocketAddr::new(host, port))
+    };
+
+    match TSigner::new(key, algorithm.into(), key_name, 60) {
+        Ok(signer) => Some(TlsaUpdater {
+            addr,
+            signer: Arc::new(Signer::from(signer)),
+        }),
+        Err(err) => {
+            config.new_build_error(
+                ("dns.provider", id, "provider"),
+                format!("Failed to create TSIG signer: {err}"),
+            );
+            None
+        }
+    }
+}
@@ -13,6 +13,7 @@ use utils::{config::ipmask::IpAddrMask, snowflake::SnowflakeIdGenerator};
 
 use crate::listener::TcpAcceptor;
 
+pub mod dns;
 pub mod listener;
 pub mod tls;
 
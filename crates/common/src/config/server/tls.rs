@@ -83,7 +83,7 @@ impl AcmeProviders {
             {
                 "tls-alpn-01" => ChallengeSettings::TlsAlpn01,
                 "http-01" => ChallengeSettings::Http01,
-                "dns-01" => match build_dns_updater(config, acme_id) {
+                "dns-01" => match build_dns_updater(config, "acme", acme_id) {
                     Some(updater) => ChallengeSettings::Dns01 {
                         updater,
                         origin: config
@@ -185,34 +185,31 @@ impl AcmeProviders {
 }
 
 #[allow(clippy::unnecessary_to_owned)]
-fn build_dns_updater(config: &mut Config, acme_id: &str) -> Option<DnsUpdater> {
+pub(crate) fn build_dns_updater(config: &mut Config, prefix: &str, id: &str) -> Option<DnsUpdater> {
     let timeout = config
-        .property_or_default(("acme", acme_id, "timeout"), "30s")
+        .property_or_default((prefix, id, "timeout"), "30s")
         .unwrap_or_else(|| Duration::from_secs(30));
 
-    match config.value_require(("acme", acme_id, "provider"))? {
+    match config.value_require((prefix, id, "provider"))? {
         "rfc2136-tsig" => {
             let algorithm: TsigAlgorithm = config
-                .value_require(("acme", acme_id, "tsig-algorithm"))?
+                .value_require((prefix, id, "tsig-algorithm"))?
                 .parse()
                 .map_err(|_| {
-                    config.new_parse_error(("acme", acme_id, "tsig-algorithm"), "Invalid algorithm")
+                    config.new_parse_error((prefix, id, "tsig-algorithm"), "Invalid algorithm")
                 })
                 .ok()?;
             let key = STANDARD
-                .decode(config.value_require(("acme", acme_id, "secret"))?.trim())
+                .decode(config.value_require((prefix, id, "secret"))?.trim())
                 .map_err(|_| {
-                    config.new_parse_error(
-                        ("acme", acme_id, "secret"),
-                        "Failed to base64 decode secret",
-                    )
+                    config.new_parse_error((prefix, id, "secret"), "Failed to base64 decode secret")
                 })
                 .ok()?;
-            let host = config.property_require::<IpAddr>(("acme", acme_id, "host"))?;
+            let host = config.property_require::<IpAddr>((prefix, id, "host"))?;
             let port = config
-                .property_or_default::<u16>(("acme", acme_id, "port"), "53")
+                .property_or_default::<u16>((prefix, id, "port"), "53")
                 .unwrap_or(53);
-            let addr = if config.value(("acme", acme_id, "protocol")) == Some("tcp") {
+            let addr = if config.value((prefix, id, "protocol")) == Some("tcp") {
                 DnsAddress::Tcp(SocketAddr::new(host, port))
             } else {
                 DnsAddress::Udp(SocketAddr::new(host, port))
@@ -221,7 +218,7 @@ fn build_dns_updater(config: &mut Config, acme_id: &str) -> Option<DnsUpdater> {
             DnsUpdater::new_rfc2136_tsig(
                 addr,
                 config
-                    .value_require(("acme", acme_id, "key"))?
+                    .value_require((prefix, id, "key"))?
                     .trim()
                     .to_string(),
                 key,
@@ -229,7 +226,7 @@ fn build_dns_updater(config: &mut Config, acme_id: &str) -> Option<DnsUpdater> {
             )
             .map_err(|err| {
                 config.new_build_error(
-                    ("acme", acme_id, "provider"),
+                    (prefix, id, "provider"),
                     format!("Failed to create RFC2136-TSIG DNS updater: {err}"),
                 )
             })
@@ -237,79 +234,78 @@ fn build_dns_updater(config: &mut Config, acme_id: &str) -> Option<DnsUpdater> {
         }
         "cloudflare" => DnsUpdater::new_cloudflare(
             config
-                .value_require(("acme", acme_id, "secret"))?
+                .value_require((prefix, id, "secret"))?
                 .trim()
                 .to_string(),
-            config.value(("acme", acme_id, "user")).map(|s| s.trim()),
+            config.value((prefix, id, "user")).map(|s| s.trim()),
             timeout.into(),
         )
         .map_err(|err| {
             config.new_build_error(
-                ("acme", acme_id, "provider"),
+                (prefix, id, "provider"),
                 format!("Failed to create Cloudflare DNS updater: {err}"),
             )
         })
         .ok(),
         "digitalocean" => DnsUpdater::new_digitalocean(
             config
-                .value_require(("acme", acme_id, "secret"))?
+                .value_require((prefix, id, "secret"))?
                 .trim()
                 .to_string(),
             timeout.into(),
         )
         .map_err(|err| {
             config.new_build_error(
-                ("acme", acme_id, "provider"),
+                (prefix, id, "provider"),
                 format!("Failed to create DigitalOcean DNS updater: {err}"),
             )
         })
         .ok(),
         "desec" => DnsUpdater::new_desec(
             config
-                .value_require(("acme", acme_id, "secret"))?
+                .value_require((prefix, id, "secret"))?
                 .trim()
                 .to_string(),
             timeout.into(),
         )
         .map_err(|err| {
             config.new_build_error(
-                ("acme", acme_id, "provider"),
+                (prefix, id, "provider"),
                 format!("Failed to create Desec DNS updater: {err}"),
             )
         })
         .ok(),
         "ovh" => DnsUpdater::new_ovh(
             config
-                .value_require(("acme", acme_id, "key"))
+                .value_require((prefix, id, "key"))
                 .map(|s| s.trim())?
                 .to_string(),
             config
-                .value_require(("acme", acme_id, "secret"))?
+                .value_require((prefix, id, "secret"))?
                 .trim()
                 .to_string(),
             config
-                .value_require(("acme", acme_id, "consumer-key"))?
+                .value_require((prefix, id, "consumer-key"))?
                 .trim()
                 .to_string(),
             config
-                .value_require(("acme", acme_id, "ovh-endpoint"))?
+                .value_require((prefix, id, "ovh-endpoint"))?
                 .parse()
                 .map_err(|_| {
-                    config
-                        .new_parse_error(("acme", acme_id, "ovh-endpoint"), "Invalid OVH endpoint")
+                    config.new_parse_error((prefix, id, "ovh-endpoint"), "Invalid OVH endpoint")
                 })
                 .ok()?,
             timeout.into(),
         )
         .map_err(|err| {
             config.new_build_error(
-                ("acme", acme_id, "provider"),
+                (prefix, id, "provider"),
                 format!("Failed to create OVH DNS updater: {err}"),
             )
         })
         .ok(),
         _ => {
-            config.new_parse_error(("acme", acme_id, "provider"), "Unsupported provider");
+            config.new_parse_error((prefix, id, "provider"), "Unsupported provider");
             None
         }
     }
@@ -4,14 +4,19 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use std::{sync::Arc, time::Duration};
+use std::{str::FromStr, sync::Arc, time::Duration};
 
-use ahash::AHashMap;
+use ahash::{AHashMap, AHashSet};
+use directory::backend::internal::manage;
 use mail_auth::{
     common::crypto::{Algorithm, Ed25519Key, HashAlgorithm, RsaKey, Sha256, SigningKey},
+    dkim::generate::DkimKeyPair,
     dkim::{Canonicalization, Done},
 };
+use mail_builder::encoders::base64::base64_encode;
 use mail_parser::decoders::base64::base64_decode;
+use rsa::{pkcs1::DecodeRsaPublicKey, pkcs8::Document};
+use rustls_pki_types::{PrivateKeyDer, PrivatePkcs1KeyDer, PrivatePkcs8KeyDer};
 use utils::config::{
     Config,
     utils::{AsKey, ParseValue},
@@ -52,12 +57,23 @@ pub struct DkimAuthConfig {
     pub verify: IfBlock,
     pub sign: IfBlock,
     pub strict: bool,
+    pub rotation: Option<DkimRotation>,
+}
+
+const MIN_ROTATION_INTERVAL: Duration = Duration::from_secs(86400);
+
+#[derive(Clone)]
+pub struct DkimRotation {
+    pub interval: Duration,
+    pub grace_period: Duration,
+    pub frequency: Duration,
 }
 
 #[derive(Clone)]
 pub struct ArcAuthConfig {
     pub verify: IfBlock,
     pub seal: IfBlock,
+    pub trusted_sealers: AHashSet<String>,
 }
 
 #[derive(Clone)]
@@ -114,6 +130,7 @@ impl Default for MailAuthConfig {
                     "false",
                 ),
                 strict: true,
+                rotation: None,
             },
             arc: ArcAuthConfig {
                 verify: IfBlock::new::<VerifyStrategy>("auth.arc.verify", [], "relaxed"),
@@ -122,6 +139,7 @@ impl Default for MailAuthConfig {
                     [],
                     "'rsa-' + config_get('report.domain')",
                 ),
+                trusted_sealers: AHashSet::new(),
             },
             spf: SpfAuthConfig {
                 verify_ehlo: IfBlock::new::<VerifyStrategy>(
@@ -201,6 +219,35 @@ impl MailAuthConfig {
         mail_auth.dkim.strict = config
             .property_or_default("auth.dkim.strict", "true")
             .unwrap_or(true);
+        if config
+            .property_or_default("auth.dkim.rotation.enable", "false")
+            .unwrap_or(false)
+        {
+            let mut interval = config
+                .property_or_default("auth.dkim.rotation.interval", "90d")
+                .unwrap_or_else(|| Duration::from_secs(90 * 86400));
+            if interval < MIN_ROTATION_INTERVAL {
+                config.new_build_error(
+                    "auth.dkim.rotation.interval",
+                    "Rotation interval must be at least one day",
+                );
+                interval = MIN_ROTATION_INTERVAL;
+            }
+            mail_auth.dkim.rotation = Some(DkimRotation {
+                interval,
+                grace_period: config
+                    .property_or_default("auth.dkim.rotation.grace-period", "7d")
+                    .unwrap_or_else(|| Duration::from_secs(7 * 86400)),
+                frequency: config
+                    .property_or_default("auth.dkim.rotation.frequency", "1h")
+                    .unwrap_or_else(|| Duration::from_secs(3600)),
+            });
+        }
+        mail_auth.arc.trusted_sealers = config
+            .values("auth.arc.trusted-sealers")
+            .map(|(_, domain)| domain.trim().to_lowercase())
+            .filter(|domain| !domain.is_empty())
+            .collect();
 
         // Parse signatures
         let mut signatures: AHashMap<&str, Config> = AHashMap::new();
@@ -309,6 +356,115 @@ fn parse_pem(config: &mut Config, key: impl AsKey) -> Option<Vec<u8>> {
     }
 }
 
+#[derive(Debug, serde::Serialize, serde::Deserialize, Copy, Clone, PartialEq, Eq)]
+pub enum DkimAlgorithm {
+    Rsa,
+    Ed25519,
+}
+
+pub fn obtain_dkim_public_key(algo: DkimAlgorithm, pk: &str) -> trc::Result<String> {
+    match simple_pem_parse(pk) {
+        Some(der) => match algo {
+            DkimAlgorithm::Rsa => match RsaKey::<Sha256>::from_key_der(PrivateKeyDer::Pkcs1(
+                PrivatePkcs1KeyDer::from(der.as_slice()),
+            ))
+            .or_else(|_| {
+                RsaKey::<Sha256>::from_key_der(PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(
+                    der.as_slice(),
+                )))
+            })
+            .and_then(|key| {
+                Document::from_pkcs1_der(&key.public_key())
+                    .map_err(|err| mail_auth::Error::CryptoError(err.to_string()))
+            }) {
+                Ok(pk) => Ok(
+                    String::from_utf8(base64_encode(pk.as_bytes()).unwrap_or_default())
+                        .unwrap_or_default(),
+                ),
+                Err(err) => Err(manage::error(
+                    "Failed to read RSA DER",
+                    err.to_string().into(),
+                )),
+            },
+            DkimAlgorithm::Ed25519 => {
+                match Ed25519Key::from_pkcs8_maybe_unchecked_der(&der)
+                    .map_err(|err| mail_auth::Error::CryptoError(err.to_string()))
+                {
+                    Ok(pk) => Ok(String::from_utf8(
+                        base64_encode(&pk.public_key()).unwrap_or_default(),
+                    )
+                    .unwrap_or_default()),
+                    Err(err) => Err(manage::error("Crypto error", err.to_string().into())),
+                }
+            }
+        },
+        None => Err(manage::error("Failed to decode private key", None::<u32>)),
+    }
+}
+
+pub fn generate_dkim_private_key(algo: DkimAlgorithm) -> trc::Result<String> {
+    let pk_type = match algo {
+        DkimAlgorithm::Rsa => "RSA PRIVATE KEY",
+        DkimAlgorithm::Ed25519 => "PRIVATE KEY",
+    };
+    let mut pk = format!("-----BEGIN {pk_type}-----\n").into_bytes();
+    let mut lf_count = 65;
+    for ch in base64_encode(
+        match algo {
+            DkimAlgorithm::Rsa => DkimKeyPair::generate_rsa(2048),
+            DkimAlgorithm::Ed25519 => DkimKeyPair::generate_ed25519(),
+        }
+        .map_err(|err| {
+            manage::error("Failed to generate key", err.to_string().into())
+                .caused_by(trc::location!())
+        })?
+        .private_key(),
+    )
+    .unwrap_or_default()
+    {
+        pk.push(ch);
+        lf_count -= 1;
+        if lf_count == 0 {
+            pk.push(b'\n');
+            lf_count = 65;
+        }
+    }
+    if lf_count != 65 {
+        pk.push(b'\n');
+    }
+    pk.extend_from_slice(format!("-----END {pk_type}-----\n").as_bytes());
+
+    Ok(String::from_utf8(pk).unwrap())
+}
+
+pub fn dkim_dns_record(algo: DkimAlgorithm, public_key: &str) -> String {
+    match algo {
+        DkimAlgorithm::Rsa => format!("v=DKIM1; k=rsa; h=sha256; p={public_key}"),
+        DkimAlgorithm::Ed25519 => format!("v=DKIM1; k=ed25519; h=sha256; p={public_key}"),
+    }
+}
+
+impl DkimAlgorithm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DkimAlgorithm::Rsa => "rsa-sha256",
+            DkimAlgorithm::Ed25519 => "ed25519-sha256",
+        }
+    }
+}
+
+impl FromStr for DkimAlgorithm {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('-').map(|(algo, _)| algo) {
+            Some("rsa") => Ok(DkimAlgorithm::Rsa),
+            Some("ed25519") => Ok(DkimAlgorithm::Ed25519),
+            _ => Err(()),
+        }
+    }
+}
+
 pub fn simple_pem_parse(contents: &str) -> Option<Vec<u8>> {
     let mut contents = contents.as_bytes().iter().copied();
     let mut base64 = vec![];
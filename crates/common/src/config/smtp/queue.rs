@@ -17,10 +17,18 @@ use std::{
     fmt::Display,
     hash::{Hash, Hasher},
     net::IpAddr,
+    str::FromStr,
     time::Duration,
 };
 use throttle::parse_queue_rate_limiter_key;
-use utils::config::{Config, utils::ParseValue};
+use utils::{
+    config::{
+        Config,
+        http::parse_http_headers,
+        utils::{AsKey, ParseValue},
+    },
+    template::Template,
+};
 
 #[derive(
     Debug,
@@ -55,6 +63,7 @@ pub struct QueueConfig {
     pub inbound_limiters: QueueRateLimiters,
     pub outbound_limiters: QueueRateLimiters,
     pub quota: QueueQuotas,
+    pub adaptive_throttle: Option<AdaptiveThrottle>,
 
     // Strategies
     pub queue_strategy: AHashMap<String, QueueStrategy>,
@@ -62,6 +71,9 @@ pub struct QueueConfig {
     pub routing_strategy: AHashMap<String, RoutingStrategy>,
     pub tls_strategy: AHashMap<String, TlsStrategy>,
     pub virtual_queues: AHashMap<QueueName, VirtualQueue>,
+
+    // Sender Rewriting Scheme
+    pub srs: Option<SrsConfig>,
 }
 
 #[derive(Clone, Hash, PartialEq, Eq, Debug)]
@@ -69,6 +81,8 @@ pub enum RoutingStrategy {
     Local,
     Mx(MxConfig),
     Relay(RelayConfig),
+    Http(HttpRouteConfig),
+    Hold,
 }
 
 #[derive(Clone, Debug)]
@@ -78,13 +92,85 @@ pub struct MxConfig {
     pub ip_lookup_strategy: IpLookupStrategy,
 }
 
+#[derive(Clone)]
+pub struct HttpRouteConfig {
+    pub id: String,
+    pub url: String,
+    pub format: HttpRouteFormat,
+    pub client: reqwest::Client,
+    pub signature_key: Option<String>,
+    pub blob_url: String,
+    pub blob_expiry: Duration,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HttpRouteFormat {
+    Raw,
+    Json,
+}
+
+#[derive(Clone, Debug)]
+pub struct AdaptiveThrottle {
+    pub concurrency: u64,
+    pub min_concurrency: u64,
+    pub rate: Rate,
+    pub min_rate: u64,
+    pub decrease_factor: f64,
+    pub recovery_interval: Duration,
+}
+
+#[derive(Clone, Debug)]
+pub struct SrsConfig {
+    pub domain: String,
+    pub secrets: Vec<String>,
+    pub max_age: u64,
+}
+
 #[derive(Clone)]
 pub struct Dsn {
     pub name: IfBlock,
     pub address: IfBlock,
     pub sign: IfBlock,
+    pub template: IfBlock,
+    pub templates: AHashMap<String, DsnTemplate>,
+    pub default_template: DsnTemplate,
+}
+
+#[derive(Clone, Debug)]
+pub struct DsnTemplate {
+    pub locale: Option<String>,
+    pub subject: Option<Template<DsnTemplateVariable>>,
+    pub text: Template<DsnTemplateVariable>,
+    pub html: Option<Template<DsnTemplateVariable>>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DsnTemplateVariable {
+    Subject,
+    Header,
+    Sender,
+    ReportingMta,
+    IsMixed,
+    Delivered,
+    Delayed,
+    Failed,
+    DeliveredTitle,
+    DelayedTitle,
+    FailedTitle,
+    Address,
+    Details,
+}
+
+const DEFAULT_DSN_TEMPLATE: &str = concat!(
+    "{{!header}}\r\n\r\n",
+    "{{#if delivered}}{{#if is_mixed}}    ----- {{!delivered_title}} -----\r\n{{/if is_mixed}}",
+    "{{#each delivered}}<{{!address}}> ({{!details}})\r\n{{/each delivered}}\r\n{{/if delivered}}",
+    "{{#if delayed}}{{#if is_mixed}}    ----- {{!delayed_title}} -----\r\n{{/if is_mixed}}",
+    "{{#each delayed}}<{{!address}}> ({{!details}})\r\n{{/each delayed}}\r\n{{/if delayed}}",
+    "{{#if failed}}{{#if is_mixed}}    ----- {{!failed_title}} -----\r\n{{/if is_mixed}}",
+    "{{#each failed}}<{{!address}}> ({{!details}})\r\n{{/each failed}}\r\n{{/if failed}}",
+);
+
 #[derive(Clone, Debug)]
 pub struct VirtualQueue {
     pub threads: usize,
@@ -137,6 +223,9 @@ pub struct ConnectionStrategy {
     pub timeout_mail: Duration,
     pub timeout_rcpt: Duration,
     pub timeout_data: Duration,
+    pub timeout_idle: Duration,
+
+    pub max_messages: usize,
 }
 
 #[derive(Clone, Debug)]
@@ -233,15 +322,20 @@ impl Default for QueueConfig {
                     [],
                     "['rsa-' + config_get('report.domain'), 'ed25519-' + config_get('report.domain')]",
                 ),
+                template: IfBlock::new::<()>("report.dsn.template", [], "'default'"),
+                templates: AHashMap::new(),
+                default_template: DsnTemplate::default(),
             },
             inbound_limiters: QueueRateLimiters::default(),
             outbound_limiters: QueueRateLimiters::default(),
             quota: QueueQuotas::default(),
+            adaptive_throttle: None,
             queue_strategy: Default::default(),
             virtual_queues: Default::default(),
             connection_strategy: Default::default(),
             routing_strategy: Default::default(),
             tls_strategy: Default::default(),
+            srs: None,
         }
     }
 }
@@ -269,6 +363,7 @@ impl QueueConfig {
                 &sender_vars,
             ),
             (&mut queue.dsn.sign, "report.dsn.sign", &sender_vars),
+            (&mut queue.dsn.template, "report.dsn.template", &sender_vars),
         ] {
             if let Some(if_block) = IfBlock::try_parse(config, key, token_map) {
                 *value = if_block;
@@ -281,15 +376,168 @@ impl QueueConfig {
         queue.connection_strategy = parse_connection_strategies(config);
         queue.routing_strategy = parse_routing_strategies(config);
         queue.tls_strategy = parse_tls_strategies(config);
+        queue.dsn.templates = parse_dsn_templates(config);
 
         // Parse rate limiters
         queue.inbound_limiters = parse_inbound_rate_limiters(config);
         queue.outbound_limiters = parse_outbound_rate_limiters(config);
         queue.quota = parse_queue_quota(config);
+        queue.adaptive_throttle = parse_adaptive_throttle(config);
+
+        // Parse SRS
+        queue.srs = parse_srs(config);
         queue
     }
 }
 
+fn parse_adaptive_throttle(config: &mut Config) -> Option<AdaptiveThrottle> {
+    if !config
+        .property_or_default::<bool>("queue.adaptive-throttle.enable", "false")
+        .unwrap_or_default()
+    {
+        return None;
+    }
+
+    let concurrency = config
+        .property_or_default::<u64>("queue.adaptive-throttle.concurrency", "16")
+        .unwrap_or(16)
+        .max(1);
+    let rate = config
+        .property_or_default::<Rate>("queue.adaptive-throttle.rate", "120/1m")
+        .unwrap_or(Rate {
+            requests: 120,
+            period: Duration::from_secs(60),
+        });
+    if rate.requests == 0 || rate.period.is_zero() {
+        config.new_build_error(
+            "queue.adaptive-throttle.rate",
+            "Rate must be greater than zero",
+        );
+        return None;
+    }
+    let decrease_factor = config
+        .property_or_default::<f64>("queue.adaptive-throttle.decrease-factor", "0.5")
+        .unwrap_or(0.5);
+    if decrease_factor <= 0.0 || decrease_factor >= 1.0 {
+        config.new_build_error(
+            "queue.adaptive-throttle.decrease-factor",
+            "Decrease factor must be between 0 and 1",
+        );
+        return None;
+    }
+
+    Some(AdaptiveThrottle {
+        min_concurrency: config
+            .property_or_default::<u64>("queue.adaptive-throttle.min-concurrency", "1")
+            .unwrap_or(1)
+            .clamp(1, concurrency),
+        min_rate: config
+            .property_or_default::<u64>("queue.adaptive-throttle.min-rate", "1")
+            .unwrap_or(1)
+            .clamp(1, rate.requests),
+        concurrency,
+        rate,
+        decrease_factor,
+        recovery_interval: config
+            .property_or_default::<Duration>("queue.adaptive-throttle.recovery-interval", "5m")
+            .unwrap_or_else(|| Duration::from_secs(300)),
+    })
+}
+
+fn parse_srs(config: &mut Config) -> Option<SrsConfig> {
+    if !config
+        .property_or_default::<bool>("queue.srs.enable", "false")
+        .unwrap_or_default()
+    {
+        return None;
+    }
+
+    let domain = config
+        .value("queue.srs.domain")
+        .or_else(|| config.value("report.domain"))
+        .or_else(|| config.value("lookup.default.domain"))
+        .or_else(|| config.value("server.hostname"))
+        .map(|v| v.trim().to_lowercase())
+        .filter(|v| !v.is_empty());
+    let Some(domain) = domain else {
+        config.new_build_error("queue.srs.domain", "Missing SRS domain");
+        return None;
+    };
+
+    // The first secret is used for signing, the remaining ones are only used
+    // to validate addresses signed before a key rotation.
+    let secrets = config
+        .values("queue.srs.secret")
+        .map(|(_, secret)| secret.to_string())
+        .filter(|secret| !secret.is_empty())
+        .collect::<Vec<_>>();
+    if secrets.is_empty() {
+        config.new_build_error("queue.srs.secret", "At least one SRS secret is required");
+        return None;
+    }
+
+    Some(SrsConfig {
+        domain,
+        secrets,
+        max_age: config
+            .property_or_default::<Duration>("queue.srs.max-age", "21d")
+            .map(|d| d.as_secs())
+            .unwrap_or(21 * 86400),
+    })
+}
+
+fn parse_dsn_templates(config: &mut Config) -> AHashMap<String, DsnTemplate> {
+    let mut entries = AHashMap::new();
+    for key in config.sub_keys_with_suffixes(
+        "report.dsn-template",
+        &[".subject", ".text", ".html", ".locale"],
+    ) {
+        if let Some(template) = parse_dsn_template(config, &key) {
+            entries.insert(key, template);
+        }
+    }
+    entries
+}
+
+fn parse_dsn_template(config: &mut Config, id: &str) -> Option<DsnTemplate> {
+    let mut template = DsnTemplate {
+        locale: config
+            .value(("report.dsn-template", id, "locale"))
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty()),
+        ..Default::default()
+    };
+
+    for (value, key) in [
+        (&mut template.subject, "subject"),
+        (&mut template.html, "html"),
+    ] {
+        let key = ("report.dsn-template", id, key).as_key();
+        if let Some(text) = config.value(&key) {
+            match Template::parse(text) {
+                Ok(text) => *value = Some(text),
+                Err(err) => {
+                    config.new_build_error(key, format!("Invalid template: {err}"));
+                    return None;
+                }
+            }
+        }
+    }
+
+    let key = ("report.dsn-template", id, "text").as_key();
+    if let Some(text) = config.value(&key) {
+        match Template::parse(text) {
+            Ok(text) => template.text = text,
+            Err(err) => {
+                config.new_build_error(key, format!("Invalid template: {err}"));
+                return None;
+            }
+        }
+    }
+
+    Some(template)
+}
+
 fn parse_queue_strategies(
     config: &mut Config,
     queues: &AHashMap<QueueName, VirtualQueue>,
@@ -432,6 +680,8 @@ fn parse_route(config: &mut Config, id: &str) -> Option<RoutingStrategy> {
         })
         .into(),
         "local" => RoutingStrategy::Local.into(),
+        "hold" => RoutingStrategy::Hold.into(),
+        "http" => RoutingStrategy::Http(parse_http_route(config, id)?).into(),
         "mx" => RoutingStrategy::Mx(MxConfig {
             max_mx: config
                 .property(("queue.route", id, "limits.mx"))
@@ -445,14 +695,119 @@ fn parse_route(config: &mut Config, id: &str) -> Option<RoutingStrategy> {
         })
         .into(),
         invalid => {
-            let details =
-                format!("Invalid route type: {invalid:?}. Expected 'relay', 'local', or 'mx'.");
+            let details = format!(
+                "Invalid route type: {invalid:?}. Expected 'relay', 'local', 'mx', 'http' or 'hold'."
+            );
             config.new_parse_error(("queue.route", id, "type"), details);
             None
         }
     }
 }
 
+fn parse_http_route(config: &mut Config, id: &str) -> Option<HttpRouteConfig> {
+    let url = config
+        .value_require_non_empty(("queue.route", id, "url"))?
+        .trim()
+        .to_string();
+    let format = match config.value(("queue.route", id, "format")).unwrap_or("raw") {
+        "raw" => HttpRouteFormat::Raw,
+        "json" => HttpRouteFormat::Json,
+        invalid => {
+            config.new_parse_error(
+                ("queue.route", id, "format"),
+                format!("Invalid format: {invalid:?}. Expected 'raw' or 'json'."),
+            );
+            return None;
+        }
+    };
+
+    let headers = parse_http_headers(config, ("queue.route", id));
+    let mut builder = reqwest::Client::builder()
+        .timeout(
+            config
+                .property_or_default::<Duration>(("queue.route", id, "timeout"), "30s")
+                .unwrap_or(Duration::from_secs(30)),
+        )
+        .danger_accept_invalid_certs(
+            config
+                .property_or_default::<bool>(
+                    ("queue.route", id, "tls.allow-invalid-certs"),
+                    "false",
+                )
+                .unwrap_or(false),
+        )
+        .default_headers(headers);
+
+    // Client certificate used for mutual TLS
+    if let Some(cert) = config.value(("queue.route", id, "tls.certificate")) {
+        let Some(key) = config.value(("queue.route", id, "tls.private-key")) else {
+            config.new_parse_error(("queue.route", id, "tls.private-key"), "Missing property");
+            return None;
+        };
+        match reqwest::Identity::from_pem(format!("{cert}\n{key}").as_bytes()) {
+            Ok(identity) => {
+                builder = builder.identity(identity);
+            }
+            Err(err) => {
+                config.new_build_error(
+                    ("queue.route", id, "tls.certificate"),
+                    format!("Failed to parse client certificate: {err}"),
+                );
+                return None;
+            }
+        }
+    }
+    if let Some(ca) = config.value(("queue.route", id, "tls.ca-certificate")) {
+        match reqwest::Certificate::from_pem(ca.as_bytes()) {
+            Ok(ca) => {
+                builder = builder.add_root_certificate(ca);
+            }
+            Err(err) => {
+                config.new_build_error(
+                    ("queue.route", id, "tls.ca-certificate"),
+                    format!("Failed to parse CA certificate: {err}"),
+                );
+                return None;
+            }
+        }
+    }
+
+    let client = match builder.build() {
+        Ok(client) => client,
+        Err(err) => {
+            config.new_build_error(
+                ("queue.route", id, "url"),
+                format!("Failed to build HTTP client: {err}"),
+            );
+            return None;
+        }
+    };
+
+    Some(HttpRouteConfig {
+        id: id.to_string(),
+        url,
+        format,
+        client,
+        signature_key: config
+            .value(("queue.route", id, "signature-key"))
+            .filter(|key| !key.is_empty())
+            .map(|key| key.to_string()),
+        blob_url: config
+            .value(("queue.route", id, "blob.url"))
+            .map(|v| v.trim().trim_end_matches('/').to_string())
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| {
+                format!(
+                    "https://{}/webhook/blob",
+                    config.value("server.hostname").unwrap_or("localhost")
+                )
+            }),
+        blob_expiry: config
+            .property_or_default::<Duration>(("queue.route", id, "blob.expiry"), "7d")
+            .unwrap_or(Duration::from_secs(7 * 86400)),
+    })
+}
+
 fn parse_tls_strategies(config: &mut Config) -> AHashMap<String, TlsStrategy> {
     let mut entries = AHashMap::new();
     for key in config.sub_keys_with_suffixes(
@@ -555,6 +910,13 @@ fn parse_connection(config: &mut Config, id: &str) -> Option<ConnectionStrategy>
         timeout_data: config
             .property::<Duration>(("queue.connection", id, "timeout.data"))
             .unwrap_or(Duration::from_secs(10 * 60)),
+        timeout_idle: config
+            .property::<Duration>(("queue.connection", id, "timeout.idle"))
+            .unwrap_or(Duration::from_secs(30)),
+        max_messages: config
+            .property::<usize>(("queue.connection", id, "max-messages"))
+            .unwrap_or(1)
+            .max(1),
     })
 }
 
@@ -893,6 +1255,32 @@ impl TlsStrategy {
     }
 }
 
+impl Hash for HttpRouteConfig {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl PartialEq for HttpRouteConfig {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for HttpRouteConfig {}
+
+impl std::fmt::Debug for HttpRouteConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HttpRouteConfig")
+            .field("id", &self.id)
+            .field("url", &self.url)
+            .field("format", &self.format)
+            .field("blob_url", &self.blob_url)
+            .field("blob_expiry", &self.blob_expiry)
+            .finish()
+    }
+}
+
 impl Hash for MxConfig {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.max_mx.hash(state);
@@ -986,3 +1374,37 @@ impl AsRef<[u8]> for QueueName {
         &self.0
     }
 }
+
+impl Default for DsnTemplate {
+    fn default() -> Self {
+        Self {
+            locale: None,
+            subject: None,
+            text: Template::parse(DEFAULT_DSN_TEMPLATE).expect("Failed to parse DSN template"),
+            html: None,
+        }
+    }
+}
+
+impl FromStr for DsnTemplateVariable {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "subject" => Ok(DsnTemplateVariable::Subject),
+            "header" => Ok(DsnTemplateVariable::Header),
+            "sender" => Ok(DsnTemplateVariable::Sender),
+            "reporting_mta" => Ok(DsnTemplateVariable::ReportingMta),
+            "is_mixed" => Ok(DsnTemplateVariable::IsMixed),
+            "delivered" => Ok(DsnTemplateVariable::Delivered),
+            "delayed" => Ok(DsnTemplateVariable::Delayed),
+            "failed" => Ok(DsnTemplateVariable::Failed),
+            "delivered_title" => Ok(DsnTemplateVariable::DeliveredTitle),
+            "delayed_title" => Ok(DsnTemplateVariable::DelayedTitle),
+            "failed_title" => Ok(DsnTemplateVariable::FailedTitle),
+            "address" => Ok(DsnTemplateVariable::Address),
+            "details" => Ok(DsnTemplateVariable::Details),
+            _ => Err(format!("Unknown DSN template variable: {}", s)),
+        }
+    }
+}
@@ -74,6 +74,10 @@ pub struct Extensions {
     pub future_release: IfBlock,
     pub deliver_by: IfBlock,
     pub mt_priority: IfBlock,
+    pub etrn: IfBlock,
+    pub etrn_rate: IfBlock,
+    pub atrn: IfBlock,
+    pub burl: IfBlock,
 }
 
 #[derive(Clone)]
@@ -124,6 +128,7 @@ pub enum AddressMapping {
 pub struct Data {
     pub script: IfBlock,
     pub spam_filter: IfBlock,
+    pub hold: IfBlock,
 
     // Limits
     pub max_messages: IfBlock,
@@ -286,6 +291,26 @@ impl SessionConfig {
                 "session.extensions.mt-priority",
                 &mt_priority_vars,
             ),
+            (
+                &mut session.extensions.etrn,
+                "session.extensions.etrn",
+                &has_sender_vars,
+            ),
+            (
+                &mut session.extensions.etrn_rate,
+                "session.extensions.etrn-rate",
+                &has_sender_vars,
+            ),
+            (
+                &mut session.extensions.atrn,
+                "session.extensions.atrn",
+                &has_sender_vars,
+            ),
+            (
+                &mut session.extensions.burl,
+                "session.extensions.burl",
+                &has_sender_vars,
+            ),
             (
                 &mut session.ehlo.script,
                 "session.ehlo.script",
@@ -406,6 +431,7 @@ impl SessionConfig {
                 "session.data.spam-filter",
                 &has_rcpt_vars,
             ),
+            (&mut session.data.hold, "session.data.hold", &has_rcpt_vars),
             (
                 &mut session.data.add_received,
                 "session.data.add-headers.received",
@@ -717,6 +743,7 @@ impl Default for SessionConfig {
             data: Data {
                 script: IfBlock::empty("session.data.script"),
                 spam_filter: IfBlock::new::<()>("session.data.spam-filter", [], "true"),
+                hold: IfBlock::new::<()>("session.data.hold", [], "false"),
                 max_messages: IfBlock::new::<()>("session.data.limits.messages", [], "10"),
                 max_message_size: IfBlock::new::<()>("session.data.limits.size", [], "104857600"),
                 max_received_headers: IfBlock::new::<()>(
@@ -791,6 +818,14 @@ impl Default for SessionConfig {
                     [("!is_empty(authenticated_as)", "mixer")],
                     "false",
                 ),
+                etrn: IfBlock::new::<()>("session.extensions.etrn", [], "false"),
+                etrn_rate: IfBlock::new::<()>("session.extensions.etrn-rate", [], "[5, 1m]"),
+                atrn: IfBlock::new::<()>("session.extensions.atrn", [], "false"),
+                burl: IfBlock::new::<()>(
+                    "session.extensions.burl",
+                    [("!is_empty(authenticated_as)", "true")],
+                    "false",
+                ),
             },
             mta_sts_policy: None,
             milters: Default::default(),
@@ -52,6 +52,7 @@ pub struct SpamFilterConfig {
 pub struct SpamFilterScoreConfig {
     pub reject_threshold: f32,
     pub discard_threshold: f32,
+    pub hold_threshold: f32,
     pub spam_threshold: f32,
 }
 
@@ -569,6 +570,9 @@ impl SpamFilterScoreConfig {
             discard_threshold: config
                 .property("spam-filter.score.discard")
                 .unwrap_or_default(),
+            hold_threshold: config
+                .property("spam-filter.score.hold")
+                .unwrap_or_default(),
             spam_threshold: config
                 .property_or_default("spam-filter.score.spam", "5.0")
                 .unwrap_or(5.0),
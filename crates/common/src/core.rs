@@ -317,6 +317,8 @@ impl Server {
             timeout_mail: Duration::from_secs(5 * 60),
             timeout_rcpt: Duration::from_secs(5 * 60),
             timeout_data: Duration::from_secs(10 * 60),
+            timeout_idle: Duration::from_secs(30),
+            max_messages: 1,
         };
 
         self.core
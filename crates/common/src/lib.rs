@@ -15,7 +15,9 @@ use config::{
     imap::ImapConfig,
     jmap::settings::JmapConfig,
     network::Network,
+    pop3::Pop3Config,
     scripts::Scripting,
+    server::dns::DnsProviders,
     smtp::{
         SmtpConfig,
         resolver::{Policy, Tlsa},
@@ -28,6 +30,7 @@ use ipc::{BroadcastEvent, HousekeeperEvent, PushEvent, QueueEvent, ReportingEven
 use listener::{asn::AsnGeoLookupData, blocked::Security, tls::AcmeProviders};
 use mail_auth::{MX, Txt};
 use manager::webadmin::{Resource, WebAdminManager};
+use outbound::{ConnectionPool, ThrottledDomains};
 use parking_lot::{Mutex, RwLock};
 use rustls::sign::CertifiedKey;
 use std::{
@@ -56,6 +59,7 @@ pub mod i18n;
 pub mod ipc;
 pub mod listener;
 pub mod manager;
+pub mod outbound;
 pub mod scripts;
 pub mod sharing;
 pub mod storage;
@@ -119,6 +123,8 @@ pub const KV_LOCK_TASK: u8 = 23;
 pub const KV_LOCK_HOUSEKEEPER: u8 = 24;
 pub const KV_LOCK_DAV: u8 = 25;
 pub const KV_SIEVE_ID: u8 = 26;
+pub const KV_TLSA_ROLLOVER: u8 = 27;
+pub const KV_HTTP_ROUTE_BLOBS: u8 = 28;
 
 #[derive(Clone)]
 pub struct Server {
@@ -152,6 +158,8 @@ pub struct Data {
     pub logos: Mutex<AHashMap<String, Option<Resource<Vec<u8>>>>>,
 
     pub smtp_connectors: TlsConnectors,
+    pub smtp_connection_pool: ConnectionPool,
+    pub smtp_throttled_domains: ThrottledDomains,
 }
 
 pub struct Caches {
@@ -301,11 +309,13 @@ pub enum DavResourceMetadata {
         name: String,
         acls: TinyVec<[AclGrant; 2]>,
         preferences: TinyVec<[TinyCalendarPreferences; 2]>,
+        supports_tasks: bool,
     },
     CalendarEvent {
         names: TinyVec<[DavName; 2]>,
         start: i64,
         duration: u32,
+        is_task: bool,
     },
     CalendarEventNotification {
         names: TinyVec<[DavName; 2]>,
@@ -341,12 +351,14 @@ pub struct Core {
     pub sieve: Scripting,
     pub network: Network,
     pub acme: AcmeProviders,
+    pub dns: DnsProviders,
     pub oauth: OAuthConfig,
     pub smtp: SmtpConfig,
     pub jmap: JmapConfig,
     pub groupware: GroupwareConfig,
     pub spam: SpamFilterConfig,
     pub imap: ImapConfig,
+    pub pop3: Pop3Config,
     pub metrics: Metrics,
 
     // SPDX-SnippetBegin
@@ -808,6 +820,23 @@ impl DavResource {
         }
     }
 
+    pub fn is_task(&self) -> bool {
+        matches!(
+            &self.data,
+            DavResourceMetadata::CalendarEvent { is_task: true, .. }
+        )
+    }
+
+    pub fn is_task_list(&self) -> bool {
+        matches!(
+            &self.data,
+            DavResourceMetadata::Calendar {
+                supports_tasks: true,
+                ..
+            }
+        )
+    }
+
     pub fn is_container(&self) -> bool {
         match &self.data {
             DavResourceMetadata::File { size, .. } => size.is_none(),
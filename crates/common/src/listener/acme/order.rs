@@ -31,6 +31,12 @@ impl Server {
     ) -> trc::Result<Duration> {
         let (cert, validity) = parse_cert(&pem)?;
 
+        // Publish the TLSA records of renewed certificates before serving them
+        if !cached && !self.core.dns.providers.is_empty() {
+            self.publish_tlsa_rollover(&provider.domains, &cert.cert)
+                .await;
+        }
+
         self.set_cert(provider, Arc::new(cert));
 
         let renew_at = (validity[1] - provider.renew_before - Utc::now())
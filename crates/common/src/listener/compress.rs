@@ -0,0 +1,293 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::{
+    borrow::Cow,
+    io,
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+
+use flate2::{Compress, Compression, Decompress, FlushCompress, FlushDecompress, Status};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use super::SessionStream;
+
+const READ_BUF_SIZE: usize = 8192;
+const MAX_PENDING_WRITE: usize = 64 * 1024;
+
+// Raw DEFLATE stream (RFC 1951) as used by IMAP COMPRESS=DEFLATE (RFC 4978)
+pub struct DeflateStream<T: SessionStream> {
+    inner: T,
+    compress: Compress,
+    decompress: Decompress,
+    read_buf: Box<[u8]>,
+    read_pos: usize,
+    read_len: usize,
+    write_buf: Vec<u8>,
+    write_pos: usize,
+    needs_flush: bool,
+}
+
+impl<T: SessionStream> DeflateStream<T> {
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            compress: Compress::new(Compression::default(), false),
+            decompress: Decompress::new(false),
+            read_buf: vec![0; READ_BUF_SIZE].into_boxed_slice(),
+            read_pos: 0,
+            read_len: 0,
+            write_buf: Vec::with_capacity(READ_BUF_SIZE),
+            write_pos: 0,
+            needs_flush: false,
+        }
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    fn deflate(&mut self, mut input: &[u8], flush: FlushCompress) -> io::Result<()> {
+        loop {
+            if self.write_buf.capacity() - self.write_buf.len() < 1024 {
+                self.write_buf.reserve(input.len().max(1024));
+            }
+            let total_in = self.compress.total_in();
+            self.compress
+                .compress_vec(input, &mut self.write_buf, flush)
+                .map_err(io::Error::other)?;
+            input = &input[(self.compress.total_in() - total_in) as usize..];
+
+            // Compression is complete once all input was consumed without filling the output buffer
+            if input.is_empty() && self.write_buf.len() < self.write_buf.capacity() {
+                return Ok(());
+            }
+        }
+    }
+
+    fn poll_write_pending(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while self.write_pos < self.write_buf.len() {
+            let bytes_written = ready!(
+                Pin::new(&mut self.inner).poll_write(cx, &self.write_buf[self.write_pos..])
+            )?;
+            if bytes_written == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            self.write_pos += bytes_written;
+        }
+        self.write_buf.clear();
+        self.write_pos = 0;
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<T: SessionStream> AsyncRead for DeflateStream<T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+
+        loop {
+            if buf.remaining() == 0 {
+                return Poll::Ready(Ok(()));
+            }
+
+            // Inflate any buffered input, the decompressor might also hold pending output
+            let total_in = this.decompress.total_in();
+            let total_out = this.decompress.total_out();
+            let status = this
+                .decompress
+                .decompress(
+                    &this.read_buf[this.read_pos..this.read_len],
+                    buf.initialize_unfilled(),
+                    FlushDecompress::Sync,
+                )
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            let bytes_in = (this.decompress.total_in() - total_in) as usize;
+            let bytes_out = (this.decompress.total_out() - total_out) as usize;
+            this.read_pos += bytes_in;
+            buf.advance(bytes_out);
+
+            if bytes_out > 0 || matches!(status, Status::StreamEnd) {
+                return Poll::Ready(Ok(()));
+            } else if bytes_in > 0 && this.read_pos < this.read_len {
+                continue;
+            }
+
+            // Read more compressed data
+            if this.read_pos == this.read_len {
+                this.read_pos = 0;
+                this.read_len = 0;
+            } else if this.read_pos > 0 {
+                this.read_buf.copy_within(this.read_pos..this.read_len, 0);
+                this.read_len -= this.read_pos;
+                this.read_pos = 0;
+            } else if this.read_len == this.read_buf.len() {
+                return Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Failed to inflate compressed stream",
+                )));
+            }
+
+            let mut read_buf = ReadBuf::new(&mut this.read_buf[this.read_len..]);
+            ready!(Pin::new(&mut this.inner).poll_read(cx, &mut read_buf))?;
+            let bytes_read = read_buf.filled().len();
+            if bytes_read == 0 {
+                return Poll::Ready(Ok(()));
+            }
+            this.read_len += bytes_read;
+        }
+    }
+}
+
+impl<T: SessionStream> AsyncWrite for DeflateStream<T> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+
+        if this.write_buf.len() - this.write_pos >= MAX_PENDING_WRITE {
+            ready!(this.poll_write_pending(cx))?;
+        }
+
+        this.deflate(buf, FlushCompress::None)?;
+        this.needs_flush = true;
+
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+
+        if this.needs_flush {
+            this.deflate(&[], FlushCompress::Sync)?;
+            this.needs_flush = false;
+        }
+        ready!(this.poll_write_pending(cx))?;
+
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.as_mut().poll_flush(cx))?;
+
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+impl<T: SessionStream> SessionStream for DeflateStream<T> {
+    fn is_tls(&self) -> bool {
+        self.inner.is_tls()
+    }
+
+    fn tls_version_and_cipher(&self) -> (Cow<'static, str>, Cow<'static, str>) {
+        self.inner.tls_version_and_cipher()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+
+    use super::DeflateStream;
+    use crate::listener::SessionStream;
+
+    struct TestStream(DuplexStream);
+
+    #[test]
+    fn deflate_roundtrip() {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(async {
+                let (client, server) = tokio::io::duplex(1024);
+                let mut client = DeflateStream::new(TestStream(client));
+                let mut server = DeflateStream::new(TestStream(server));
+
+                let messages = [
+                    b"A001 OK DEFLATE active\r\n".to_vec(),
+                    b"* 1 FETCH (BODY[] {12}\r\nHello world!)\r\n".repeat(2000),
+                    b"A002 LOGOUT\r\n".to_vec(),
+                ];
+
+                let writer = tokio::spawn(async move {
+                    for message in messages {
+                        client.write_all(&message).await.unwrap();
+                        client.flush().await.unwrap();
+                    }
+                    client.shutdown().await.unwrap();
+                });
+
+                let mut received = Vec::new();
+                let mut buf = vec![0; 4096];
+                loop {
+                    let bytes_read = server.read(&mut buf).await.unwrap();
+                    if bytes_read == 0 {
+                        break;
+                    }
+                    received.extend_from_slice(&buf[..bytes_read]);
+                }
+                writer.await.unwrap();
+
+                let mut expected = b"A001 OK DEFLATE active\r\n".to_vec();
+                expected.extend(b"* 1 FETCH (BODY[] {12}\r\nHello world!)\r\n".repeat(2000));
+                expected.extend(b"A002 LOGOUT\r\n");
+                assert_eq!(received, expected);
+            });
+    }
+
+    impl tokio::io::AsyncRead for TestStream {
+        fn poll_read(
+            mut self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+            buf: &mut tokio::io::ReadBuf<'_>,
+        ) -> std::task::Poll<std::io::Result<()>> {
+            std::pin::Pin::new(&mut self.0).poll_read(cx, buf)
+        }
+    }
+
+    impl tokio::io::AsyncWrite for TestStream {
+        fn poll_write(
+            mut self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+            buf: &[u8],
+        ) -> std::task::Poll<std::io::Result<usize>> {
+            std::pin::Pin::new(&mut self.0).poll_write(cx, buf)
+        }
+
+        fn poll_flush(
+            mut self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<std::io::Result<()>> {
+            std::pin::Pin::new(&mut self.0).poll_flush(cx)
+        }
+
+        fn poll_shutdown(
+            mut self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<std::io::Result<()>> {
+            std::pin::Pin::new(&mut self.0).poll_shutdown(cx)
+        }
+    }
+
+    impl SessionStream for TestStream {
+        fn is_tls(&self) -> bool {
+            false
+        }
+
+        fn tls_version_and_cipher(&self) -> (Cow<'static, str>, Cow<'static, str>) {
+            (Cow::Borrowed(""), Cow::Borrowed(""))
+        }
+    }
+}
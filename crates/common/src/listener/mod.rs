@@ -28,6 +28,7 @@ use self::limiter::{ConcurrencyLimiter, InFlight};
 pub mod acme;
 pub mod asn;
 pub mod blocked;
+pub mod compress;
 pub mod limiter;
 pub mod listen;
 pub mod stream;
@@ -86,6 +87,7 @@ pub enum SessionResult {
     Continue,
     Close,
     UpgradeTls,
+    UpgradeCompression,
 }
 
 pub trait SessionManager: Sync + Send + 'static + Clone {
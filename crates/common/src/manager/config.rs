@@ -240,6 +240,56 @@ impl ConfigManager {
         }
     }
 
+    pub async fn clear_all<I>(&self, keys: I) -> trc::Result<()>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        self.update(Vec::<ConfigKey>::new(), keys).await
+    }
+
+    /// Sets and clears keys in a single write
+    pub async fn update<I, T, C>(&self, set: I, clear: C) -> trc::Result<()>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<ConfigKey>,
+        C: IntoIterator<Item = String>,
+    {
+        let mut batch = BatchBuilder::new();
+        let mut local = self.cfg_local.load().as_ref().clone();
+        let mut has_local_changes = false;
+
+        for key in set {
+            let key = key.into();
+
+            if self.cfg_local_patterns.is_local_key(&key.key) {
+                if local.get(&key.key) != Some(&key.value) {
+                    local.insert(key.key, key.value);
+                    has_local_changes = true;
+                }
+            } else {
+                batch.set(ValueClass::Config(key.key.into_bytes()), key.value);
+            }
+        }
+
+        for key in clear {
+            if self.cfg_local_patterns.is_local_key(&key) {
+                has_local_changes |= local.remove(&key).is_some();
+            } else {
+                batch.clear(ValueClass::Config(key.into_bytes()));
+            }
+        }
+
+        if !batch.is_empty() {
+            self.cfg_store.write(batch.build_all()).await?;
+        }
+
+        if has_local_changes {
+            self.update_local(local).await?;
+        }
+
+        Ok(())
+    }
+
     pub async fn clear_prefix(&self, key: impl AsRef<str>) -> trc::Result<()> {
         let key = key.as_ref();
 
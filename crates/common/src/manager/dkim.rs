@@ -0,0 +1,793 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use ahash::AHashMap;
+use dns_update::{DnsRecord, DnsRecordType};
+use mail_parser::DateTime;
+use serde::{Deserialize, Serialize};
+use store::write::now;
+
+use crate::{
+    Server,
+    config::smtp::auth::{
+        DkimAlgorithm, DkimRotation, dkim_dns_record, generate_dkim_private_key,
+        obtain_dkim_public_key,
+    },
+};
+
+const REVOKED_RECORD: &str = "v=DKIM1; p=";
+const NEXT_KEYS: &[&str] = &[
+    "next-private-key",
+    "next-selector",
+    "next-created",
+    "next-published",
+];
+const RETIRED_KEYS: &[&str] = &["retired-selector", "retired-since"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DkimKeyStage {
+    Active,
+    Pending,
+    Retiring,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DkimKeyLifecycle {
+    pub id: String,
+    pub domain: String,
+    pub algorithm: DkimAlgorithm,
+    pub selector: String,
+    pub stage: DkimKeyStage,
+    pub rotatable: bool,
+    pub created: Option<u64>,
+    pub rotate_at: Option<u64>,
+    pub next_selector: Option<String>,
+    pub next_record: Option<String>,
+    pub next_created: Option<u64>,
+    pub retired_selector: Option<String>,
+    pub revoke_at: Option<u64>,
+}
+
+struct DkimKeyEntry {
+    lifecycle: DkimKeyLifecycle,
+    next_private_key: Option<String>,
+    next_published: bool,
+    retired_since: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum DkimRotationStep<'x> {
+    Start,
+    Cleanup(&'static [&'static str]),
+    Revoke(&'x str),
+    Activate {
+        selector: &'x str,
+        private_key: &'x str,
+        record: &'x str,
+    },
+    Generate,
+    Wait,
+}
+
+impl Server {
+    pub async fn dkim_key_lifecycles(&self) -> trc::Result<Vec<DkimKeyLifecycle>> {
+        let mut lifecycles = self
+            .dkim_key_entries()
+            .await?
+            .into_iter()
+            .map(|entry| entry.lifecycle)
+            .collect::<Vec<_>>();
+        lifecycles.sort_unstable_by(|a, b| a.id.cmp(&b.id));
+        Ok(lifecycles)
+    }
+
+    pub async fn rotate_dkim_keys(&self) -> trc::Result<bool> {
+        let Some(rotation) = &self.core.smtp.mail_auth.dkim.rotation else {
+            return Ok(false);
+        };
+        let mut has_changes = false;
+
+        for entry in self.dkim_key_entries().await? {
+            if entry.lifecycle.rotatable {
+                match self.rotate_dkim_key(rotation, entry).await {
+                    Ok(changed) => has_changes |= changed,
+                    Err(err) => {
+                        trc::error!(err.details("Failed to rotate DKIM key."));
+                    }
+                }
+            }
+        }
+
+        Ok(has_changes)
+    }
+
+    async fn rotate_dkim_key(
+        &self,
+        rotation: &DkimRotation,
+        entry: DkimKeyEntry,
+    ) -> trc::Result<bool> {
+        let config = &self.core.storage.config;
+        let key = &entry.lifecycle;
+        let id = key.id.as_str();
+        let now = now();
+
+        match entry.rotation_step(rotation, now) {
+            DkimRotationStep::Start => {
+                config
+                    .set(
+                        [(format!("signature.{id}.rotation.created"), now.to_string())],
+                        true,
+                    )
+                    .await?;
+            }
+            DkimRotationStep::Cleanup(suffixes) => {
+                config
+                    .clear_all(
+                        suffixes
+                            .iter()
+                            .map(|suffix| format!("signature.{id}.rotation.{suffix}")),
+                    )
+                    .await?;
+            }
+            DkimRotationStep::Revoke(retired_selector) => {
+                if self
+                    .revoke_dkim_selector(id, &key.domain, retired_selector)
+                    .await
+                {
+                    config
+                        .clear_all(
+                            RETIRED_KEYS
+                                .iter()
+                                .map(|suffix| format!("signature.{id}.rotation.{suffix}")),
+                        )
+                        .await?;
+                }
+            }
+            DkimRotationStep::Activate {
+                selector: next_selector,
+                record: next_record,
+                private_key: next_private_key,
+            } => {
+                let name = format!("{next_selector}._domainkey.{}", key.domain);
+                if self.is_dkim_record_published(&name, next_record).await {
+                    // Switch keys in a single write so an interrupted activation
+                    // never leaves the next key equal to the active one
+                    let (set, clear) = entry.activation_changes(next_private_key, now);
+                    config.update(set, clear).await?;
+
+                    trc::event!(
+                        Dkim(trc::DkimEvent::KeyActivated),
+                        Id = id.to_string(),
+                        Domain = key.domain.clone(),
+                        Details = next_selector.to_string(),
+                        Value = key.selector.clone(),
+                    );
+
+                    return Ok(true);
+                } else if !entry.next_published {
+                    self.publish_dkim_record(id, &key.domain, next_selector, next_record)
+                        .await?;
+                } else {
+                    trc::event!(
+                        Dkim(trc::DkimEvent::KeyNotPropagated),
+                        Id = id.to_string(),
+                        Domain = key.domain.clone(),
+                        Hostname = name,
+                    );
+                }
+            }
+            DkimRotationStep::Generate => {
+                let private_key = generate_dkim_private_key(key.algorithm)?;
+                let record = dkim_dns_record(
+                    key.algorithm,
+                    &obtain_dkim_public_key(key.algorithm, &private_key)?,
+                );
+                let selector = next_dkim_selector(key.algorithm, &[key.selector.as_str()], now);
+                config
+                    .set(
+                        [
+                            (
+                                format!("signature.{id}.rotation.next-private-key"),
+                                private_key,
+                            ),
+                            (
+                                format!("signature.{id}.rotation.next-selector"),
+                                selector.clone(),
+                            ),
+                            (
+                                format!("signature.{id}.rotation.next-created"),
+                                now.to_string(),
+                            ),
+                        ],
+                        true,
+                    )
+                    .await?;
+
+                trc::event!(
+                    Dkim(trc::DkimEvent::KeyGenerated),
+                    Id = id.to_string(),
+                    Domain = key.domain.clone(),
+                    Details = selector.clone(),
+                );
+
+                self.publish_dkim_record(id, &key.domain, &selector, &record)
+                    .await?;
+            }
+            DkimRotationStep::Wait => {}
+        }
+
+        Ok(false)
+    }
+
+    async fn publish_dkim_record(
+        &self,
+        id: &str,
+        domain: &str,
+        selector: &str,
+        record: &str,
+    ) -> trc::Result<()> {
+        let name = format!("{selector}._domainkey.{domain}");
+
+        if let Some(provider) = self.core.dns.get(domain) {
+            match provider
+                .updater
+                .create(
+                    &name,
+                    DnsRecord::TXT {
+                        content: record.to_string(),
+                    },
+                    provider.ttl,
+                    provider.zone_origin(domain),
+                )
+                .await
+            {
+                Ok(_) => {
+                    self.core
+                        .storage
+                        .config
+                        .set(
+                            [(
+                                format!("signature.{id}.rotation.next-published"),
+                                "true".to_string(),
+                            )],
+                            true,
+                        )
+                        .await?;
+
+                    trc::event!(
+                        Dkim(trc::DkimEvent::KeyPublished),
+                        Id = id.to_string(),
+                        Domain = domain.to_string(),
+                        Hostname = name,
+                    );
+                }
+                Err(err) => {
+                    trc::event!(
+                        Dkim(trc::DkimEvent::KeyRotationFailed),
+                        Id = id.to_string(),
+                        Domain = domain.to_string(),
+                        Hostname = name,
+                        Reason = err.to_string(),
+                    );
+                }
+            }
+        } else {
+            trc::event!(
+                Dkim(trc::DkimEvent::KeyPublicationRequired),
+                Id = id.to_string(),
+                Domain = domain.to_string(),
+                Hostname = name,
+                Details = record.to_string(),
+            );
+        }
+
+        Ok(())
+    }
+
+    async fn revoke_dkim_selector(&self, id: &str, domain: &str, selector: &str) -> bool {
+        let name = format!("{selector}._domainkey.{domain}");
+
+        if let Some(provider) = self.core.dns.get(domain) {
+            let origin = provider.zone_origin(domain);
+            let result = match provider
+                .updater
+                .delete(&name, &origin, DnsRecordType::TXT)
+                .await
+            {
+                Ok(_) => {
+                    provider
+                        .updater
+                        .create(
+                            &name,
+                            DnsRecord::TXT {
+                                content: REVOKED_RECORD.to_string(),
+                            },
+                            provider.ttl,
+                            &origin,
+                        )
+                        .await
+                }
+                Err(err) => Err(err),
+            };
+
+            match result {
+                Ok(_) => {
+                    trc::event!(
+                        Dkim(trc::DkimEvent::KeyRevoked),
+                        Id = id.to_string(),
+                        Domain = domain.to_string(),
+                        Hostname = name,
+                    );
+                    true
+                }
+                Err(err) => {
+                    trc::event!(
+                        Dkim(trc::DkimEvent::KeyRotationFailed),
+                        Id = id.to_string(),
+                        Domain = domain.to_string(),
+                        Hostname = name,
+                        Reason = err.to_string(),
+                    );
+                    false
+                }
+            }
+        } else if self.is_dkim_record_revoked(&name).await {
+            trc::event!(
+                Dkim(trc::DkimEvent::KeyRevoked),
+                Id = id.to_string(),
+                Domain = domain.to_string(),
+                Hostname = name,
+            );
+            true
+        } else {
+            trc::event!(
+                Dkim(trc::DkimEvent::KeyRevocationRequired),
+                Id = id.to_string(),
+                Domain = domain.to_string(),
+                Hostname = name,
+                Details = REVOKED_RECORD,
+            );
+            false
+        }
+    }
+
+    async fn is_dkim_record_published(&self, name: &str, record: &str) -> bool {
+        let Some(public_key) = dkim_public_key_tag(record) else {
+            return false;
+        };
+        match self.core.smtp.resolvers.dns.txt_raw_lookup(name).await {
+            Ok(result) => dkim_public_key_tag(std::str::from_utf8(&result).unwrap_or_default())
+                .is_some_and(|published| published == public_key),
+            Err(_) => false,
+        }
+    }
+
+    async fn is_dkim_record_revoked(&self, name: &str) -> bool {
+        match self.core.smtp.resolvers.dns.txt_raw_lookup(name).await {
+            Ok(result) => {
+                dkim_public_key_tag(std::str::from_utf8(&result).unwrap_or_default()).is_none()
+            }
+            Err(mail_auth::Error::DnsRecordNotFound(_)) => true,
+            Err(_) => false,
+        }
+    }
+
+    async fn dkim_key_entries(&self) -> trc::Result<Vec<DkimKeyEntry>> {
+        let rotation = self.core.smtp.mail_auth.dkim.rotation.as_ref();
+        let mut entries = Vec::new();
+
+        for (id, values) in self
+            .core
+            .storage
+            .config
+            .group("signature.", ".algorithm")
+            .await?
+        {
+            if let Some(entry) = DkimKeyEntry::parse(id, &values, rotation) {
+                entries.push(entry);
+            }
+        }
+
+        Ok(entries)
+    }
+}
+
+impl DkimKeyEntry {
+    fn parse(
+        id: String,
+        values: &AHashMap<String, String>,
+        rotation: Option<&DkimRotation>,
+    ) -> Option<Self> {
+        let (Some(algorithm), Some(domain), Some(selector)) = (
+            values
+                .get("algorithm")
+                .and_then(|algo| algo.parse::<DkimAlgorithm>().ok()),
+            values.get("domain"),
+            values.get("selector"),
+        ) else {
+            return None;
+        };
+        let get_u64 = |key: &str| values.get(key).and_then(|v| v.parse::<u64>().ok());
+        let created = get_u64("rotation.created");
+        let next_selector = values.get("rotation.next-selector").cloned();
+        let next_private_key = values.get("rotation.next-private-key").cloned();
+        let retired_selector = values.get("rotation.retired-selector").cloned();
+        let retired_since = get_u64("rotation.retired-since");
+
+        // Keys loaded from files or environment variables cannot be rotated
+        let rotatable = values
+            .get("private-key")
+            .is_some_and(|pk| !pk.contains("%{"))
+            && [domain.as_str(), selector.as_str()]
+                .iter()
+                .all(|v| !v.contains("%{"));
+
+        Some(DkimKeyEntry {
+            lifecycle: DkimKeyLifecycle {
+                stage: if retired_selector.is_some() {
+                    DkimKeyStage::Retiring
+                } else if next_selector.is_some() {
+                    DkimKeyStage::Pending
+                } else {
+                    DkimKeyStage::Active
+                },
+                rotate_at: rotation
+                    .filter(|_| rotatable)
+                    .and_then(|r| created.map(|created| created + r.interval.as_secs())),
+                revoke_at: rotation
+                    .and_then(|r| retired_since.map(|since| since + r.grace_period.as_secs())),
+                next_record: next_private_key
+                    .as_deref()
+                    .and_then(|pk| obtain_dkim_public_key(algorithm, pk).ok())
+                    .map(|public_key| dkim_dns_record(algorithm, &public_key)),
+                next_created: get_u64("rotation.next-created"),
+                id,
+                domain: domain.to_string(),
+                algorithm,
+                selector: selector.to_string(),
+                rotatable,
+                created,
+                next_selector,
+                retired_selector,
+            },
+            next_published: values
+                .get("rotation.next-published")
+                .is_some_and(|v| v == "true"),
+            next_private_key,
+            retired_since,
+        })
+    }
+
+    fn rotation_step(&self, rotation: &DkimRotation, now: u64) -> DkimRotationStep<'_> {
+        let key = &self.lifecycle;
+
+        // Keys created before rotation was enabled start their lifecycle now
+        let Some(created) = key.created else {
+            return DkimRotationStep::Start;
+        };
+
+        // Discard leftovers of transitions that were interrupted or applied twice
+        if key.next_selector.as_deref() == Some(key.selector.as_str())
+            || key.next_selector.is_none() != self.next_private_key.is_none()
+            || (key.next_selector.is_some() && key.next_record.is_none())
+        {
+            return DkimRotationStep::Cleanup(NEXT_KEYS);
+        } else if key.retired_selector.as_deref() == Some(key.selector.as_str())
+            || key.retired_selector.is_none() != self.retired_since.is_none()
+        {
+            return DkimRotationStep::Cleanup(RETIRED_KEYS);
+        }
+
+        // Revoke the retired selector once the grace period is over, and wait
+        // until it is revoked before starting a new rotation
+        if let (Some(retired_selector), Some(retired_since)) =
+            (&key.retired_selector, self.retired_since)
+        {
+            return if now >= retired_since + rotation.grace_period.as_secs() {
+                DkimRotationStep::Revoke(retired_selector)
+            } else {
+                DkimRotationStep::Wait
+            };
+        }
+
+        // Switch to the next key once its record is visible in DNS
+        if let (Some(selector), Some(private_key), Some(record)) =
+            (&key.next_selector, &self.next_private_key, &key.next_record)
+        {
+            return DkimRotationStep::Activate {
+                selector,
+                private_key,
+                record,
+            };
+        }
+
+        // Generate the next key ahead of time
+        if now >= created + rotation.interval.as_secs() {
+            DkimRotationStep::Generate
+        } else {
+            DkimRotationStep::Wait
+        }
+    }
+
+    fn activation_changes(
+        &self,
+        next_private_key: &str,
+        now: u64,
+    ) -> (Vec<(String, String)>, Vec<String>) {
+        let key = &self.lifecycle;
+        let id = key.id.as_str();
+        let next_selector = key.next_selector.as_deref().unwrap_or_default();
+
+        (
+            vec![
+                (
+                    format!("signature.{id}.private-key"),
+                    next_private_key.to_string(),
+                ),
+                (
+                    format!("signature.{id}.selector"),
+                    next_selector.to_string(),
+                ),
+                (format!("signature.{id}.rotation.created"), now.to_string()),
+                (
+                    format!("signature.{id}.rotation.retired-selector"),
+                    key.selector.to_string(),
+                ),
+                (
+                    format!("signature.{id}.rotation.retired-since"),
+                    now.to_string(),
+                ),
+            ],
+            NEXT_KEYS
+                .iter()
+                .map(|suffix| format!("signature.{id}.rotation.{suffix}"))
+                .collect(),
+        )
+    }
+}
+
+fn next_dkim_selector(algo: DkimAlgorithm, taken: &[&str], now: u64) -> String {
+    let dt = DateTime::from_timestamp(now as i64);
+    let suffix = match algo {
+        DkimAlgorithm::Rsa => "r",
+        DkimAlgorithm::Ed25519 => "e",
+    };
+    let monthly = format!("{:04}{:02}{suffix}", dt.year, dt.month);
+    if !taken.contains(&monthly.as_str()) {
+        return monthly;
+    }
+    let daily = format!("{:04}{:02}{:02}{suffix}", dt.year, dt.month, dt.day);
+    if !taken.contains(&daily.as_str()) {
+        return daily;
+    }
+
+    // Never reuse the active selector, as that would replace its published key
+    (2..)
+        .map(|n| format!("{daily}{n}"))
+        .find(|selector| !taken.contains(&selector.as_str()))
+        .unwrap()
+}
+
+fn dkim_public_key_tag(record: &str) -> Option<String> {
+    record.split(';').find_map(|tag| {
+        let (name, value) = tag.split_once('=')?;
+        if name.trim() == "p" {
+            let value = value
+                .chars()
+                .filter(|ch| !ch.is_ascii_whitespace())
+                .collect::<String>();
+            (!value.is_empty()).then_some(value)
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use ahash::AHashMap;
+
+    use super::{DkimKeyEntry, DkimKeyStage, DkimRotationStep, next_dkim_selector};
+    use crate::config::smtp::auth::{DkimAlgorithm, DkimRotation, generate_dkim_private_key};
+
+    const DAY: u64 = 86400;
+    // 2026-01-15T12:00:00Z
+    const NOW: u64 = 1768478400;
+
+    fn rotation() -> DkimRotation {
+        DkimRotation {
+            interval: Duration::from_secs(30 * DAY),
+            grace_period: Duration::from_secs(7 * DAY),
+            frequency: Duration::from_secs(3600),
+        }
+    }
+
+    fn parse(values: &AHashMap<String, String>) -> DkimKeyEntry {
+        DkimKeyEntry::parse("test".to_string(), values, Some(&rotation())).unwrap()
+    }
+
+    fn apply(
+        values: &mut AHashMap<String, String>,
+        set: Vec<(String, String)>,
+        clear: Vec<String>,
+    ) {
+        for (key, value) in set {
+            values.insert(
+                key.strip_prefix("signature.test.").unwrap().to_string(),
+                value,
+            );
+        }
+        for key in clear {
+            values.remove(key.strip_prefix("signature.test.").unwrap());
+        }
+    }
+
+    #[test]
+    fn dkim_selector_generation() {
+        assert_eq!(
+            next_dkim_selector(DkimAlgorithm::Rsa, &["default"], NOW),
+            "202601r"
+        );
+        assert_eq!(
+            next_dkim_selector(DkimAlgorithm::Ed25519, &["default"], NOW),
+            "202601e"
+        );
+
+        // Rotating twice in the same month or day never returns the active selector
+        assert_eq!(
+            next_dkim_selector(DkimAlgorithm::Rsa, &["202601r"], NOW),
+            "20260115r"
+        );
+        assert_eq!(
+            next_dkim_selector(DkimAlgorithm::Rsa, &["20260115r", "202601r"], NOW),
+            "20260115r2"
+        );
+        assert_eq!(
+            next_dkim_selector(
+                DkimAlgorithm::Rsa,
+                &["202601r", "20260115r", "20260115r2"],
+                NOW
+            ),
+            "20260115r3"
+        );
+    }
+
+    #[test]
+    fn dkim_rotation_lifecycle() {
+        let rotation = rotation();
+        let mut values = AHashMap::from_iter(
+            [
+                ("algorithm", "ed25519-sha256".to_string()),
+                ("domain", "example.org".to_string()),
+                ("selector", "default".to_string()),
+                (
+                    "private-key",
+                    generate_dkim_private_key(DkimAlgorithm::Ed25519).unwrap(),
+                ),
+            ]
+            .map(|(key, value)| (key.to_string(), value)),
+        );
+
+        // Keys without a creation date start their lifecycle
+        assert_eq!(
+            parse(&values).rotation_step(&rotation, NOW),
+            DkimRotationStep::Start
+        );
+        values.insert("rotation.created".to_string(), NOW.to_string());
+
+        // Keys are not rotated before the interval is over
+        let entry = parse(&values);
+        assert_eq!(entry.lifecycle.stage, DkimKeyStage::Active);
+        assert_eq!(entry.lifecycle.rotate_at, Some(NOW + 30 * DAY));
+        assert_eq!(
+            entry.rotation_step(&rotation, NOW + 29 * DAY),
+            DkimRotationStep::Wait
+        );
+        let now = NOW + 30 * DAY;
+        assert_eq!(
+            entry.rotation_step(&rotation, now),
+            DkimRotationStep::Generate
+        );
+
+        // Generate the next key
+        let next_private_key = generate_dkim_private_key(DkimAlgorithm::Ed25519).unwrap();
+        values.insert(
+            "rotation.next-private-key".to_string(),
+            next_private_key.clone(),
+        );
+        values.insert("rotation.next-selector".to_string(), "202602e".to_string());
+        values.insert("rotation.next-created".to_string(), now.to_string());
+        let entry = parse(&values);
+        assert_eq!(entry.lifecycle.stage, DkimKeyStage::Pending);
+        let next_record = entry.lifecycle.next_record.clone().unwrap();
+        assert_eq!(
+            entry.rotation_step(&rotation, now),
+            DkimRotationStep::Activate {
+                selector: "202602e",
+                private_key: &next_private_key,
+                record: &next_record,
+            }
+        );
+
+        // Switch to the next key
+        let (set, clear) = entry.activation_changes(&next_private_key, now);
+        apply(&mut values, set.clone(), clear.clone());
+        assert_eq!(values["selector"], "202602e");
+        assert_eq!(values["private-key"], next_private_key);
+        assert_eq!(values["rotation.retired-selector"], "default");
+        assert!(!values.keys().any(|key| key.starts_with("rotation.next-")));
+
+        // Replaying the switch is a no-op
+        let snapshot = values.clone();
+        apply(&mut values, set, clear);
+        assert_eq!(values, snapshot);
+
+        // The retired selector is revoked after the grace period, never the active one
+        let entry = parse(&values);
+        assert_eq!(entry.lifecycle.stage, DkimKeyStage::Retiring);
+        assert_eq!(entry.lifecycle.revoke_at, Some(now + 7 * DAY));
+        assert_eq!(
+            entry.rotation_step(&rotation, now + 6 * DAY),
+            DkimRotationStep::Wait
+        );
+        assert_eq!(
+            entry.rotation_step(&rotation, now + 7 * DAY),
+            DkimRotationStep::Revoke("default")
+        );
+
+        // A new rotation starts once the retired selector was revoked
+        values.remove("rotation.retired-selector");
+        values.remove("rotation.retired-since");
+        let entry = parse(&values);
+        assert_eq!(entry.lifecycle.stage, DkimKeyStage::Active);
+        assert_eq!(
+            entry.rotation_step(&rotation, now + 30 * DAY),
+            DkimRotationStep::Generate
+        );
+    }
+
+    #[test]
+    fn dkim_rotation_recovery() {
+        let rotation = rotation();
+        let private_key = generate_dkim_private_key(DkimAlgorithm::Ed25519).unwrap();
+        let mut values = AHashMap::from_iter(
+            [
+                ("algorithm", "ed25519-sha256".to_string()),
+                ("domain", "example.org".to_string()),
+                ("selector", "202602e".to_string()),
+                ("private-key", private_key.clone()),
+                ("rotation.created", NOW.to_string()),
+                ("rotation.retired-selector", "default".to_string()),
+                ("rotation.retired-since", NOW.to_string()),
+                ("rotation.next-selector", "202602e".to_string()),
+                ("rotation.next-private-key", private_key),
+            ]
+            .map(|(key, value)| (key.to_string(), value)),
+        );
+
+        // A next key equal to the active one is discarded instead of activated
+        assert_eq!(
+            parse(&values).rotation_step(&rotation, NOW),
+            DkimRotationStep::Cleanup(super::NEXT_KEYS)
+        );
+        values.remove("rotation.next-selector");
+        values.remove("rotation.next-private-key");
+
+        // The active selector is never revoked
+        values.insert(
+            "rotation.retired-selector".to_string(),
+            "202602e".to_string(),
+        );
+        assert_eq!(
+            parse(&values).rotation_step(&rotation, NOW + 7 * DAY),
+            DkimRotationStep::Cleanup(super::RETIRED_KEYS)
+        );
+    }
+}
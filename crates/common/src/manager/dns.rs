@@ -0,0 +1,1116 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use ahash::AHashMap;
+use directory::backend::internal::manage;
+use hickory_client::{
+    client::{AsyncClient, ClientConnection, ClientHandle},
+    op::ResponseCode,
+    rr::{
+        Name, RData, Record, RecordSet, RecordType,
+        rdata::tlsa::{CertUsage, Matching, Selector, TLSA},
+    },
+    tcp::TcpClientConnection,
+    udp::UdpClientConnection,
+};
+use mail_auth::hickory_resolver::proto::rr::{RData as LookupRData, RecordType as LookupType};
+use rustls_pki_types::CertificateDer;
+use serde::{Deserialize, Serialize};
+use sha1::Digest;
+use std::time::Duration;
+use store::dispatch::lookup::KeyValue;
+use utils::config::Config;
+use x509_parser::parse_x509_certificate;
+
+use crate::{
+    KV_TLSA_ROLLOVER, Server,
+    config::{
+        server::dns::{DnsProvider, TlsaUpdater},
+        smtp::auth::{DkimAlgorithm, dkim_dns_record, obtain_dkim_public_key},
+    },
+};
+use dns_update::providers::rfc2136::DnsAddress;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DnsRecord {
+    #[serde(rename = "type")]
+    pub typ: String,
+    pub name: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DnsRecordStatus {
+    Ok,
+    Missing,
+    Mismatch,
+    Created,
+    Updated,
+    Unsupported,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DnsRecordReport {
+    #[serde(flatten)]
+    pub record: DnsRecord,
+    pub status: DnsRecordStatus,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub actual: Vec<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Server {
+    pub async fn build_dns_records(&self, domain_name: &str) -> trc::Result<Vec<DnsRecord>> {
+        // Obtain server name
+        let server_name = &self.core.network.server_name;
+        let mut records = Vec::new();
+
+        // Obtain DKIM keys
+        let mut keys = Config::default();
+        let mut signature_ids = Vec::new();
+        let mut has_macros = false;
+        for (key, value) in self.core.storage.config.list("signature.", true).await? {
+            match key.strip_suffix(".domain") {
+                Some(key_id) if value == domain_name => {
+                    signature_ids.push(key_id.to_string());
+                }
+                _ => (),
+            }
+            if !has_macros && value.contains("%{") {
+                has_macros = true;
+            }
+            keys.keys.insert(key, value);
+        }
+
+        // Add MX and CNAME records
+        records.push(DnsRecord {
+            typ: "MX".to_string(),
+            name: format!("{domain_name}."),
+            content: format!("10 {server_name}."),
+        });
+        if server_name.strip_prefix("mail.") != Some(domain_name) {
+            records.push(DnsRecord {
+                typ: "CNAME".to_string(),
+                name: format!("mail.{domain_name}."),
+                content: format!("{server_name}."),
+            });
+        }
+
+        // Process DKIM keys
+        if has_macros {
+            keys.resolve_macros(&["env", "file", "cfg"]).await;
+            keys.log_errors();
+        }
+        for signature_id in signature_ids {
+            if let (Some(algo), Some(pk), Some(selector)) = (
+                keys.value(format!("{signature_id}.algorithm"))
+                    .and_then(|algo| algo.parse::<DkimAlgorithm>().ok()),
+                keys.value(format!("{signature_id}.private-key")),
+                keys.value(format!("{signature_id}.selector")),
+            ) {
+                match obtain_dkim_public_key(algo, pk) {
+                    Ok(public) => {
+                        records.push(DnsRecord {
+                            typ: "TXT".to_string(),
+                            name: format!("{selector}._domainkey.{domain_name}.",),
+                            content: dkim_dns_record(algo, &public),
+                        });
+                    }
+                    Err(err) => {
+                        trc::error!(err);
+                    }
+                }
+            }
+        }
+
+        // Add SPF records
+        if server_name.ends_with(&format!(".{domain_name}")) || server_name == domain_name {
+            records.push(DnsRecord {
+                typ: "TXT".to_string(),
+                name: format!("{server_name}."),
+                content: "v=spf1 a ra=postmaster -all".to_string(),
+            });
+        }
+        records.push(DnsRecord {
+            typ: "TXT".to_string(),
+            name: format!("{domain_name}."),
+            content: "v=spf1 mx ra=postmaster -all".to_string(),
+        });
+
+        let mut has_https = false;
+        for (protocol, port, is_tls) in self
+            .core
+            .storage
+            .config
+            .get_services()
+            .await
+            .unwrap_or_default()
+        {
+            match (protocol.as_str(), port) {
+                ("smtp", port @ 26..=u16::MAX) => {
+                    records.push(DnsRecord {
+                        typ: "SRV".to_string(),
+                        name: format!(
+                            "_submission{}._tcp.{domain_name}.",
+                            if is_tls { "s" } else { "" }
+                        ),
+                        content: format!("0 1 {port} {server_name}."),
+                    });
+                }
+                ("imap" | "pop3", port @ 1..=u16::MAX) => {
+                    records.push(DnsRecord {
+                        typ: "SRV".to_string(),
+                        name: format!(
+                            "_{protocol}{}._tcp.{domain_name}.",
+                            if is_tls { "s" } else { "" }
+                        ),
+                        content: format!("0 1 {port} {server_name}."),
+                    });
+                }
+                ("http", _) if is_tls => {
+                    has_https = true;
+                    for service in ["jmap", "caldavs", "carddavs"] {
+                        records.push(DnsRecord {
+                            typ: "SRV".to_string(),
+                            name: format!("_{service}._tcp.{domain_name}.",),
+                            content: format!("0 1 {port} {server_name}."),
+                        });
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        if has_https {
+            // Add autoconfig and autodiscover records
+            records.push(DnsRecord {
+                typ: "CNAME".to_string(),
+                name: format!("autoconfig.{domain_name}."),
+                content: format!("{server_name}."),
+            });
+            records.push(DnsRecord {
+                typ: "CNAME".to_string(),
+                name: format!("autodiscover.{domain_name}."),
+                content: format!("{server_name}."),
+            });
+
+            // Add MTA-STS records
+            if let Some(policy) = self.build_mta_sts_policy() {
+                records.push(DnsRecord {
+                    typ: "CNAME".to_string(),
+                    name: format!("mta-sts.{domain_name}."),
+                    content: format!("{server_name}."),
+                });
+                records.push(DnsRecord {
+                    typ: "TXT".to_string(),
+                    name: format!("_mta-sts.{domain_name}."),
+                    content: format!("v=STSv1; id={}", policy.id),
+                });
+            }
+        }
+
+        // Add DMARC record
+        records.push(DnsRecord {
+            typ: "TXT".to_string(),
+            name: format!("_dmarc.{domain_name}."),
+            content: format!("v=DMARC1; p=reject; rua=mailto:postmaster@{domain_name}; ruf=mailto:postmaster@{domain_name}",),
+        });
+
+        // Add TLS reporting record
+        records.push(DnsRecord {
+            typ: "TXT".to_string(),
+            name: format!("_smtp._tls.{domain_name}."),
+            content: format!("v=TLSRPTv1; rua=mailto:postmaster@{domain_name}",),
+        });
+
+        // Add TLSA records
+        for (name, key) in self.inner.data.tls_certificates.load().iter() {
+            if is_tlsa_name(name, domain_name) {
+                records.extend(tlsa_records(name, &key.cert));
+            }
+        }
+
+        // Keep publishing the records of a certificate rollover in progress
+        match self
+            .in_memory_store()
+            .key_get::<String>(KeyValue::<()>::build_key(KV_TLSA_ROLLOVER, domain_name))
+            .await
+        {
+            Ok(Some(rollover)) => merge_tlsa_rollover(&mut records, &rollover),
+            Ok(None) => {}
+            Err(err) => {
+                trc::error!(err.details("Failed to obtain TLSA rollover records."));
+            }
+        }
+
+        Ok(records)
+    }
+
+    pub async fn check_dns_records(&self, domain_name: &str) -> trc::Result<Vec<DnsRecordReport>> {
+        let records = self.build_dns_records(domain_name).await?;
+        let mut reports = Vec::with_capacity(records.len());
+        let mut lookups: AHashMap<_, Result<Vec<String>, String>> = AHashMap::new();
+
+        for record in &records {
+            // Records sharing the same name and type are looked up once
+            let key = (record.name.as_str(), record.typ.as_str());
+            let actual = if let Some(actual) = lookups.get(&key) {
+                actual.clone()
+            } else {
+                let actual = self.lookup_dns_record(record).await;
+                lookups.insert(key, actual.clone());
+                actual
+            };
+
+            reports.push(match actual {
+                Ok(actual) => compare_record(record, actual),
+                Err(err) => DnsRecordReport {
+                    record: record.clone(),
+                    status: DnsRecordStatus::Failed,
+                    actual: vec![],
+                    error: err.into(),
+                },
+            });
+        }
+
+        // Stale TLSA records are reported as drift of the whole record set
+        mark_stale_tlsa(&mut reports);
+
+        Ok(reports)
+    }
+
+    pub async fn sync_dns_records(
+        &self,
+        provider: &DnsProvider,
+        domain_name: &str,
+    ) -> trc::Result<Vec<DnsRecordReport>> {
+        let mut reports = self.check_dns_records(domain_name).await?;
+        let origin = provider.zone_origin(domain_name);
+
+        // Replace the TLSA record sets that are not up to date
+        let mut tlsa_names = Vec::new();
+        for report in &reports {
+            if report.record.typ == "TLSA"
+                && report.status != DnsRecordStatus::Ok
+                && !tlsa_names.contains(&report.record.name)
+            {
+                tlsa_names.push(report.record.name.clone());
+            }
+        }
+        for tlsa_name in tlsa_names {
+            let group = reports
+                .iter_mut()
+                .filter(|report| report.record.typ == "TLSA" && report.record.name == tlsa_name)
+                .collect::<Vec<_>>();
+            let is_new = group
+                .iter()
+                .all(|report| report.status == DnsRecordStatus::Missing);
+
+            let Some(tlsa_updater) = &provider.tlsa_updater else {
+                for report in group {
+                    if report.status != DnsRecordStatus::Ok {
+                        report.status = DnsRecordStatus::Unsupported;
+                    }
+                }
+                continue;
+            };
+
+            let result = tlsa_updater
+                .replace(
+                    &tlsa_name,
+                    group.iter().map(|report| report.record.content.as_str()),
+                    provider.ttl,
+                    &origin,
+                )
+                .await;
+            for report in group {
+                set_sync_status(report, &result, is_new, provider);
+            }
+        }
+
+        // Create missing records, existing records that differ from the expected
+        // value might be managed by other services so drift is only reported.
+        for report in reports.iter_mut() {
+            if !matches!(report.record.typ.as_str(), "MX" | "CNAME" | "TXT" | "SRV") {
+                continue;
+            }
+
+            match report.status {
+                DnsRecordStatus::Missing => {
+                    let result = provider
+                        .updater
+                        .create(
+                            report.record.name.trim_end_matches('.'),
+                            convert_record(&report.record)?,
+                            provider.ttl,
+                            &origin,
+                        )
+                        .await
+                        .map_err(|err| err.to_string());
+                    set_sync_status(report, &result, true, provider);
+                }
+                DnsRecordStatus::Mismatch => {
+                    trc::event!(
+                        Dns(trc::DnsEvent::RecordDrift),
+                        Id = provider.id.clone(),
+                        Domain = domain_name.to_string(),
+                        Hostname = report.record.name.clone(),
+                        Type = report.record.typ.clone(),
+                        Details = report.record.content.clone(),
+                        Value = report.actual.clone(),
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        trc::event!(
+            Dns(trc::DnsEvent::SyncCompleted),
+            Id = provider.id.clone(),
+            Domain = domain_name.to_string(),
+            Total = reports.len(),
+        );
+
+        Ok(reports)
+    }
+
+    // Publishes the TLSA records of a renewed certificate next to the current ones
+    // before it is served (RFC 7671, section 8.1). Both sets are kept in the rollover
+    // entry for two TTLs: one until the new certificate is served and one more for
+    // resolvers caching the old record set, after which the next sync drops the old hashes.
+    pub async fn publish_tlsa_rollover(&self, names: &[String], certs: &[CertificateDer<'_>]) {
+        let current = self.inner.data.tls_certificates.load();
+        let mut wait = 0;
+
+        for provider in self.core.dns.providers.values() {
+            if provider.tlsa_updater.is_none() {
+                continue;
+            }
+
+            for domain_name in &provider.domains {
+                let mut rollover = String::new();
+                let mut has_current = false;
+                for name in names {
+                    let name = name.strip_prefix("*.").unwrap_or(name.as_str());
+                    if !is_tlsa_name(name, domain_name) {
+                        continue;
+                    }
+
+                    let current = current.get(name).map(|key| tlsa_records(name, &key.cert));
+                    has_current |= current.is_some();
+                    for record in current
+                        .unwrap_or_default()
+                        .into_iter()
+                        .chain(tlsa_records(name, certs))
+                    {
+                        rollover.push_str(&record.name);
+                        rollover.push(' ');
+                        rollover.push_str(&record.content);
+                        rollover.push('\n');
+                    }
+                }
+                if rollover.is_empty() {
+                    continue;
+                }
+
+                if let Err(err) = self
+                    .in_memory_store()
+                    .key_set(
+                        KeyValue::with_prefix(KV_TLSA_ROLLOVER, domain_name, rollover.into_bytes())
+                            .expires(2 * provider.ttl as u64),
+                    )
+                    .await
+                {
+                    trc::error!(err.details("Failed to store TLSA rollover records."));
+                    continue;
+                }
+
+                match self.sync_dns_records(provider, domain_name).await {
+                    Ok(reports) => {
+                        // First issuances are served right away
+                        if has_current
+                            && reports.iter().any(|report| {
+                                report.record.typ == "TLSA"
+                                    && matches!(
+                                        report.status,
+                                        DnsRecordStatus::Created | DnsRecordStatus::Updated
+                                    )
+                            })
+                        {
+                            wait = wait.max(provider.ttl);
+                        }
+                    }
+                    Err(err) => {
+                        trc::error!(
+                            err.details("Failed to publish TLSA rollover records.")
+                                .ctx(trc::Key::Domain, domain_name.to_string())
+                        );
+                    }
+                }
+            }
+        }
+
+        // Wait for the cached record sets to expire before serving the new certificate
+        if wait > 0 {
+            tokio::time::sleep(Duration::from_secs(wait as u64)).await;
+        }
+    }
+
+    pub async fn sync_managed_dns(&self) {
+        for provider in self.core.dns.providers.values() {
+            for domain in &provider.domains {
+                if let Err(err) = self.sync_dns_records(provider, domain).await {
+                    trc::error!(
+                        err.details("Failed to synchronize DNS records.")
+                            .ctx(trc::Key::Domain, domain.to_string())
+                    );
+                }
+            }
+        }
+    }
+
+    async fn lookup_dns_record(&self, record: &DnsRecord) -> Result<Vec<String>, String> {
+        let record_type = match record.typ.as_str() {
+            "MX" => LookupType::MX,
+            "CNAME" => LookupType::CNAME,
+            "TXT" => LookupType::TXT,
+            "SRV" => LookupType::SRV,
+            "TLSA" => LookupType::TLSA,
+            _ => return Err(format!("Unsupported record type {}", record.typ)),
+        };
+
+        match self
+            .core
+            .smtp
+            .resolvers
+            .dns
+            .resolver()
+            .lookup(record.name.as_str(), record_type)
+            .await
+        {
+            Ok(lookup) => Ok(lookup
+                .record_iter()
+                .filter_map(|record| match record.data() {
+                    LookupRData::TXT(txt) => txt
+                        .txt_data()
+                        .iter()
+                        .map(|part| String::from_utf8_lossy(part))
+                        .collect::<String>()
+                        .into(),
+                    LookupRData::MX(_)
+                    | LookupRData::CNAME(_)
+                    | LookupRData::SRV(_)
+                    | LookupRData::TLSA(_)
+                        if record.record_type() == record_type =>
+                    {
+                        normalize_value(&record.data().to_string()).into()
+                    }
+                    _ => None,
+                })
+                .collect()),
+            Err(err) if err.is_no_records_found() || err.is_nx_domain() => Ok(vec![]),
+            Err(err) => {
+                trc::event!(
+                    Dns(trc::DnsEvent::LookupFailed),
+                    Hostname = record.name.clone(),
+                    Type = record.typ.clone(),
+                    Reason = err.to_string(),
+                );
+
+                Err(err.to_string())
+            }
+        }
+    }
+}
+
+impl TlsaUpdater {
+    async fn connect(&self) -> Result<AsyncClient, String> {
+        match &self.addr {
+            DnsAddress::Udp(addr) => {
+                let conn = UdpClientConnection::new(*addr)
+                    .map_err(|err| err.to_string())?
+                    .new_stream(Some(self.signer.clone()));
+                let (client, bg) = AsyncClient::connect(conn)
+                    .await
+                    .map_err(|err| err.to_string())?;
+                tokio::spawn(bg);
+                Ok(client)
+            }
+            DnsAddress::Tcp(addr) => {
+                let conn = TcpClientConnection::new(*addr)
+                    .map_err(|err| err.to_string())?
+                    .new_stream(Some(self.signer.clone()));
+                let (client, bg) = AsyncClient::connect(conn)
+                    .await
+                    .map_err(|err| err.to_string())?;
+                tokio::spawn(bg);
+                Ok(client)
+            }
+        }
+    }
+
+    async fn replace(
+        &self,
+        name: &str,
+        records: impl Iterator<Item = &str>,
+        ttl: u32,
+        origin: &str,
+    ) -> Result<(), String> {
+        let name = Name::from_str_relaxed(name).map_err(|err| err.to_string())?;
+        let origin = Name::from_str_relaxed(format!("{}.", origin.trim_end_matches('.')))
+            .map_err(|err| err.to_string())?;
+        if !origin.zone_of(&name) {
+            return Err(format!("{name} is not part of zone {origin}"));
+        }
+
+        let rrset = tlsa_rrset(&name, records, ttl)?;
+        let mut client = self.connect().await?;
+        for result in [
+            client
+                .delete_rrset(Record::with(name, RecordType::TLSA, 0), origin.clone())
+                .await,
+            client.append(rrset, origin, false).await,
+        ] {
+            let response = result.map_err(|err| err.to_string())?;
+            if response.response_code() != ResponseCode::NoError {
+                return Err(response.response_code().to_string());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn tlsa_rrset<'x>(
+    name: &Name,
+    records: impl Iterator<Item = &'x str>,
+    ttl: u32,
+) -> Result<RecordSet, String> {
+    let mut rrset = RecordSet::with_ttl(name.clone(), RecordType::TLSA, ttl);
+    for record in records {
+        let mut parts = record.split_ascii_whitespace();
+        let (Some(usage), Some(selector), Some(matching), Some(data)) = (
+            parts.next().and_then(|v| v.parse::<u8>().ok()),
+            parts.next().and_then(|v| v.parse::<u8>().ok()),
+            parts.next().and_then(|v| v.parse::<u8>().ok()),
+            parts.next().and_then(decode_hex),
+        ) else {
+            return Err(format!("Invalid TLSA record {record:?}"));
+        };
+        rrset.add_rdata(RData::TLSA(TLSA::new(
+            CertUsage::from(usage),
+            Selector::from(selector),
+            Matching::from(matching),
+            data,
+        )));
+    }
+
+    Ok(rrset)
+}
+
+fn is_tlsa_name(name: &str, domain_name: &str) -> bool {
+    name.ends_with(domain_name)
+        && !name.starts_with("mta-sts.")
+        && !name.starts_with("autoconfig.")
+        && !name.starts_with("autodiscover.")
+}
+
+fn tlsa_records(name: &str, certs: &[CertificateDer<'_>]) -> Vec<DnsRecord> {
+    let mut records = Vec::new();
+    let name = if !name.starts_with('.') {
+        format!("_25._tcp.{name}.")
+    } else {
+        format!("_25._tcp.mail.{name}.")
+    };
+
+    for (cert_num, cert) in certs.iter().enumerate() {
+        let parsed_cert = match parse_x509_certificate(cert) {
+            Ok((_, parsed_cert)) => parsed_cert,
+            Err(err) => {
+                trc::error!(manage::error(
+                    "Failed to parse certificate",
+                    err.to_string().into()
+                ));
+                continue;
+            }
+        };
+
+        let cu = if cert_num == 0 { 3 } else { 2 };
+
+        for (s, cert) in [&**cert, parsed_cert.subject_pki.raw]
+            .into_iter()
+            .enumerate()
+        {
+            for (m, hash) in [
+                format!("{:x}", sha2::Sha256::digest(cert)),
+                format!("{:x}", sha2::Sha512::digest(cert)),
+            ]
+            .into_iter()
+            .enumerate()
+            {
+                records.push(DnsRecord {
+                    typ: "TLSA".to_string(),
+                    name: name.clone(),
+                    content: format!("{} {} {} {}", cu, s, m + 1, hash),
+                });
+            }
+        }
+    }
+
+    records
+}
+
+fn merge_tlsa_rollover(records: &mut Vec<DnsRecord>, rollover: &str) {
+    for (name, content) in rollover.lines().filter_map(|line| line.split_once(' ')) {
+        if !records
+            .iter()
+            .any(|record| record.typ == "TLSA" && record.name == name && record.content == content)
+        {
+            records.push(DnsRecord {
+                typ: "TLSA".to_string(),
+                name: name.to_string(),
+                content: content.to_string(),
+            });
+        }
+    }
+}
+
+fn mark_stale_tlsa(reports: &mut [DnsRecordReport]) {
+    for idx in 0..reports.len() {
+        let report = &reports[idx];
+        if report.record.typ == "TLSA"
+            && report.status == DnsRecordStatus::Ok
+            && report.actual.iter().any(|value| {
+                !reports.iter().any(|expected| {
+                    expected.record.typ == "TLSA"
+                        && expected.record.name == report.record.name
+                        && &normalize_value(&expected.record.content) == value
+                })
+            })
+        {
+            reports[idx].status = DnsRecordStatus::Mismatch;
+        }
+    }
+}
+
+fn compare_record(record: &DnsRecord, actual: Vec<String>) -> DnsRecordReport {
+    let (status, actual) = if record.typ == "TXT" {
+        // Only compare TXT records of the same kind (i.e. v=spf1, v=DKIM1, etc.)
+        let version = txt_version(&record.content);
+        let actual = actual
+            .into_iter()
+            .filter(|value| txt_version(value) == version)
+            .collect::<Vec<_>>();
+        let expected = normalize_txt(&record.content);
+        if actual.iter().any(|value| normalize_txt(value) == expected) {
+            (DnsRecordStatus::Ok, actual)
+        } else if actual.is_empty() {
+            (DnsRecordStatus::Missing, actual)
+        } else {
+            (DnsRecordStatus::Mismatch, actual)
+        }
+    } else {
+        let expected = normalize_value(&record.content);
+        if actual.is_empty() {
+            (DnsRecordStatus::Missing, actual)
+        } else if actual.contains(&expected) {
+            (DnsRecordStatus::Ok, actual)
+        } else {
+            (DnsRecordStatus::Mismatch, actual)
+        }
+    };
+
+    DnsRecordReport {
+        record: record.clone(),
+        status,
+        actual,
+        error: None,
+    }
+}
+
+fn set_sync_status(
+    report: &mut DnsRecordReport,
+    result: &Result<(), String>,
+    is_new: bool,
+    provider: &DnsProvider,
+) {
+    match result {
+        Ok(_) => {
+            report.status = if is_new {
+                DnsRecordStatus::Created
+            } else {
+                DnsRecordStatus::Updated
+            };
+
+            trc::event!(
+                Dns(if is_new {
+                    trc::DnsEvent::RecordCreated
+                } else {
+                    trc::DnsEvent::RecordUpdated
+                }),
+                Id = provider.id.clone(),
+                Hostname = report.record.name.clone(),
+                Type = report.record.typ.clone(),
+                Details = report.record.content.clone(),
+            );
+        }
+        Err(err) => {
+            report.status = DnsRecordStatus::Failed;
+            report.error = err.clone().into();
+
+            trc::event!(
+                Dns(trc::DnsEvent::RecordUpdateFailed),
+                Id = provider.id.clone(),
+                Hostname = report.record.name.clone(),
+                Type = report.record.typ.clone(),
+                Details = report.record.content.clone(),
+                Reason = err.clone(),
+            );
+        }
+    }
+}
+
+fn convert_record(record: &DnsRecord) -> trc::Result<dns_update::DnsRecord> {
+    let content = record.content.as_str();
+    let mut parts = content.split_ascii_whitespace();
+    let record = match record.typ.as_str() {
+        "TXT" => Some(dns_update::DnsRecord::TXT {
+            content: content.to_string(),
+        }),
+        "CNAME" => Some(dns_update::DnsRecord::CNAME {
+            content: content.trim_end_matches('.').to_string(),
+        }),
+        "MX" => match (parts.next().and_then(|v| v.parse().ok()), parts.next()) {
+            (Some(priority), Some(host)) => Some(dns_update::DnsRecord::MX {
+                content: host.trim_end_matches('.').to_string(),
+                priority,
+            }),
+            _ => None,
+        },
+        "SRV" => match (
+            parts.next().and_then(|v| v.parse().ok()),
+            parts.next().and_then(|v| v.parse().ok()),
+            parts.next().and_then(|v| v.parse().ok()),
+            parts.next(),
+        ) {
+            (Some(priority), Some(weight), Some(port), Some(host)) => {
+                Some(dns_update::DnsRecord::SRV {
+                    content: host.trim_end_matches('.').to_string(),
+                    priority,
+                    weight,
+                    port,
+                })
+            }
+            _ => None,
+        },
+        _ => None,
+    };
+
+    record.ok_or_else(|| {
+        trc::EventType::Dns(trc::DnsEvent::RecordUpdateFailed)
+            .into_err()
+            .details("Invalid DNS record")
+            .ctx(trc::Key::Value, content.to_string())
+    })
+}
+
+fn txt_version(value: &str) -> &str {
+    value
+        .split(|ch: char| ch == ';' || ch.is_ascii_whitespace())
+        .next()
+        .unwrap_or_default()
+}
+
+fn normalize_txt(value: &str) -> String {
+    value
+        .split(';')
+        .map(|part| part.trim())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn normalize_value(value: &str) -> String {
+    value
+        .split_ascii_whitespace()
+        .map(|part| part.trim_end_matches('.').to_lowercase())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(value.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use hickory_client::rr::{Name, RData, rdata::tlsa::CertUsage};
+    use rcgen::generate_simple_self_signed;
+    use rustls_pki_types::CertificateDer;
+
+    use super::{
+        DnsRecord, DnsRecordReport, DnsRecordStatus, compare_record, mark_stale_tlsa,
+        merge_tlsa_rollover, normalize_txt, normalize_value, tlsa_records, tlsa_rrset,
+    };
+
+    fn record(typ: &str, name: &str, content: &str) -> DnsRecord {
+        DnsRecord {
+            typ: typ.to_string(),
+            name: name.to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    fn certificate() -> CertificateDer<'static> {
+        CertificateDer::from(
+            generate_simple_self_signed(vec!["mail.example.org".to_string()])
+                .unwrap()
+                .serialize_der()
+                .unwrap(),
+        )
+    }
+
+    fn tlsa_report(expected: &DnsRecord, actual: &[DnsRecord]) -> DnsRecordReport {
+        compare_record(
+            expected,
+            actual
+                .iter()
+                .map(|record| normalize_value(&record.content))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn dns_normalize() {
+        for (value, expected) in [
+            (
+                "v=DMARC1;p=reject; rua=mailto:postmaster@example.org;",
+                "v=DMARC1; p=reject; rua=mailto:postmaster@example.org",
+            ),
+            (
+                "  v=TLSRPTv1 ;  rua=mailto:postmaster@example.org  ",
+                "v=TLSRPTv1; rua=mailto:postmaster@example.org",
+            ),
+            (
+                "v=spf1 mx ra=postmaster -all",
+                "v=spf1 mx ra=postmaster -all",
+            ),
+        ] {
+            assert_eq!(normalize_txt(value), expected, "{value:?}");
+        }
+
+        for (value, expected) in [
+            ("10 MAIL.Example.org.", "10 mail.example.org"),
+            ("0   1 993 mail.example.org.", "0 1 993 mail.example.org"),
+            ("3 1 1 ABCDEF", "3 1 1 abcdef"),
+        ] {
+            assert_eq!(normalize_value(value), expected, "{value:?}");
+        }
+    }
+
+    #[test]
+    fn dns_record_comparison() {
+        // TXT records are only compared with records of the same kind
+        let dmarc = record(
+            "TXT",
+            "_dmarc.example.org.",
+            "v=DMARC1; p=reject; rua=mailto:postmaster@example.org",
+        );
+        let report = compare_record(
+            &dmarc,
+            vec![
+                "v=spf1 -all".to_string(),
+                "v=DMARC1;p=reject;rua=mailto:postmaster@example.org".to_string(),
+            ],
+        );
+        assert_eq!(report.status, DnsRecordStatus::Ok);
+        assert_eq!(report.actual.len(), 1);
+
+        let report = compare_record(&dmarc, vec!["v=spf1 -all".to_string()]);
+        assert_eq!(report.status, DnsRecordStatus::Missing);
+        assert!(report.actual.is_empty());
+
+        let report = compare_record(&dmarc, vec!["v=DMARC1; p=none".to_string()]);
+        assert_eq!(report.status, DnsRecordStatus::Mismatch);
+        assert_eq!(report.actual, vec!["v=DMARC1; p=none".to_string()]);
+
+        // Other records are compared after normalization
+        let mx = record("MX", "example.org.", "10 mail.example.org.");
+        assert_eq!(compare_record(&mx, vec![]).status, DnsRecordStatus::Missing);
+        assert_eq!(
+            compare_record(
+                &mx,
+                vec![
+                    "20 backup.example.org".to_string(),
+                    "10 mail.example.org".to_string()
+                ]
+            )
+            .status,
+            DnsRecordStatus::Ok
+        );
+        let report = compare_record(&mx, vec!["10 mx.other.org".to_string()]);
+        assert_eq!(report.status, DnsRecordStatus::Mismatch);
+        assert_eq!(report.actual, vec!["10 mx.other.org".to_string()]);
+    }
+
+    #[test]
+    fn dns_tlsa_drift() {
+        let current = tlsa_records("mail.example.org", &[certificate()]);
+        let next = tlsa_records("mail.example.org", &[certificate()]);
+        assert_eq!(current.len(), 4);
+        for (record, prefix) in current.iter().zip(["3 0 1 ", "3 0 2 ", "3 1 1 ", "3 1 2 "]) {
+            assert_eq!(record.typ, "TLSA");
+            assert_eq!(record.name, "_25._tcp.mail.example.org.");
+            assert!(record.content.starts_with(prefix), "{record:?}");
+        }
+        assert_ne!(current, next);
+
+        // Published record set matches the expected one
+        let mut reports = current
+            .iter()
+            .map(|expected| tlsa_report(expected, &current))
+            .collect::<Vec<_>>();
+        mark_stale_tlsa(&mut reports);
+        assert!(
+            reports
+                .iter()
+                .all(|report| report.status == DnsRecordStatus::Ok)
+        );
+
+        // Hashes of the renewed certificate differ from the published ones and the
+        // stale hashes turn the matching records into a mismatch of the whole set
+        let mut expected = current.clone();
+        expected.truncate(2);
+        expected.extend(next.iter().cloned());
+        let mut reports = expected
+            .iter()
+            .map(|expected| tlsa_report(expected, &current))
+            .collect::<Vec<_>>();
+        mark_stale_tlsa(&mut reports);
+        assert_eq!(
+            reports
+                .iter()
+                .map(|report| report.status)
+                .collect::<Vec<_>>(),
+            [
+                DnsRecordStatus::Mismatch,
+                DnsRecordStatus::Mismatch,
+                DnsRecordStatus::Mismatch,
+                DnsRecordStatus::Mismatch,
+                DnsRecordStatus::Mismatch,
+                DnsRecordStatus::Mismatch,
+            ]
+        );
+
+        // Nothing published yet
+        let mut reports = current
+            .iter()
+            .map(|expected| tlsa_report(expected, &[]))
+            .collect::<Vec<_>>();
+        mark_stale_tlsa(&mut reports);
+        assert!(
+            reports
+                .iter()
+                .all(|report| report.status == DnsRecordStatus::Missing)
+        );
+    }
+
+    #[test]
+    fn dns_tlsa_rollover() {
+        let current = tlsa_records("mail.example.org", &[certificate()]);
+        let next = tlsa_records("mail.example.org", &[certificate()]);
+        let rollover = current
+            .iter()
+            .chain(next.iter())
+            .map(|record| format!("{} {}\n", record.name, record.content))
+            .collect::<String>();
+
+        // Before the switch both certificates are expected, duplicates are skipped
+        let mut records = vec![record("MX", "example.org.", "10 mail.example.org.")];
+        records.extend(current.iter().cloned());
+        merge_tlsa_rollover(&mut records, &rollover);
+        assert_eq!(records.len(), 9);
+        for record in current.iter().chain(next.iter()) {
+            assert!(records.contains(record), "{record:?}");
+        }
+
+        // Publishing the hashes of both certificates keeps the record set in sync
+        let mut reports = records
+            .iter()
+            .filter(|record| record.typ == "TLSA")
+            .map(|expected| {
+                tlsa_report(
+                    expected,
+                    &current
+                        .iter()
+                        .chain(next.iter())
+                        .cloned()
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        mark_stale_tlsa(&mut reports);
+        assert!(
+            reports
+                .iter()
+                .all(|report| report.status == DnsRecordStatus::Ok)
+        );
+
+        // Once the rollover expires the old hashes are stale
+        let mut reports = next
+            .iter()
+            .map(|expected| {
+                tlsa_report(
+                    expected,
+                    &current
+                        .iter()
+                        .chain(next.iter())
+                        .cloned()
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        mark_stale_tlsa(&mut reports);
+        assert!(
+            reports
+                .iter()
+                .all(|report| report.status == DnsRecordStatus::Mismatch)
+        );
+
+        // The record set update contains every expected record
+        let name = Name::from_str_relaxed("_25._tcp.mail.example.org.").unwrap();
+        let rrset = tlsa_rrset(
+            &name,
+            records
+                .iter()
+                .filter(|record| record.typ == "TLSA")
+                .map(|record| record.content.as_str()),
+            3600,
+        )
+        .unwrap();
+        assert_eq!(rrset.name(), &name);
+        assert_eq!(rrset.ttl(), 3600);
+        assert_eq!(rrset.records_without_rrsigs().count(), 8);
+        for record in rrset.records_without_rrsigs() {
+            let Some(RData::TLSA(tlsa)) = record.data() else {
+                panic!("Unexpected record {record:?}");
+            };
+            assert_eq!(tlsa.cert_usage(), CertUsage::DomainIssued);
+        }
+        assert!(tlsa_rrset(&name, ["3 1 1 xyz", "3 1"].into_iter(), 3600).is_err());
+    }
+}
@@ -14,6 +14,8 @@ pub mod backup;
 pub mod boot;
 pub mod config;
 pub mod console;
+pub mod dkim;
+pub mod dns;
 pub mod reload;
 pub mod restore;
 pub mod webadmin;
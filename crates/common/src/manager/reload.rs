@@ -10,7 +10,7 @@ use store::Stores;
 use utils::config::Config;
 
 use crate::{
-    Core, Server,
+    Core, Inner, Server,
     config::{
         server::{Listeners, tls::parse_certificates},
         telemetry::Telemetry,
@@ -140,6 +140,15 @@ impl Server {
     }
 }
 
+impl Inner {
+    pub fn replace_core(&self, core: Core) {
+        self.shared_core.store(core.into());
+
+        // Pooled SMTP connections were established using the previous settings
+        self.data.smtp_connection_pool.lock().clear();
+    }
+}
+
 impl From<Config> for ReloadResult {
     fn from(config: Config) -> Self {
         Self {
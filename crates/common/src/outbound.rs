@@ -0,0 +1,146 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use crate::config::smtp::queue::AdaptiveThrottle;
+use ahash::AHashMap;
+use mail_send::Credentials;
+use parking_lot::Mutex;
+use smtp_proto::EhloResponse;
+use std::{net::IpAddr, sync::Arc, time::Instant};
+use store::write::now;
+use tokio::net::TcpStream;
+use tokio_rustls::client::TlsStream;
+use trc::DeliveryEvent;
+
+pub type ConnectionPool = Mutex<AHashMap<PoolKey, Vec<PooledConnection>>>;
+
+// Only domains that returned temporary failures are tracked, entries are
+// removed once the domain has recovered its full concurrency and rate.
+pub type ThrottledDomains = Arc<Mutex<AHashMap<String, DomainThrottle>>>;
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct PoolKey {
+    pub hostname: String,
+    pub remote_ip: IpAddr,
+    pub remote_port: u16,
+    pub local_ip: IpAddr,
+    pub credentials: Option<Credentials<String>>,
+    pub is_smtp: bool,
+    pub is_dane: bool,
+    pub is_pki_verified: bool,
+}
+
+pub enum PooledStream {
+    Plain(TcpStream),
+    Tls(Box<TlsStream<TcpStream>>),
+}
+
+pub struct PooledConnection {
+    pub stream: PooledStream,
+    pub capabilities: EhloResponse<String>,
+    pub session_id: u64,
+    pub messages: usize,
+    pub idle_since: Instant,
+    pub throttle: ThrottleSlot,
+}
+
+pub struct DomainThrottle {
+    pub concurrency: u64,
+    pub rate: u64,
+    pub in_flight: u64,
+    pub window_start: Instant,
+    pub window_count: u64,
+    pub deferrals: u64,
+    pub deliveries: u64,
+    pub last_change: Instant,
+    pub last_deferral: Instant,
+    pub throttled_since: u64,
+    pub last_deferral_at: u64,
+}
+
+// Concurrency slot held by an idle pooled connection to a throttled domain
+pub struct ThrottleSlot {
+    domains: ThrottledDomains,
+    domain: String,
+    is_tracked: bool,
+}
+
+impl DomainThrottle {
+    pub fn new(config: &AdaptiveThrottle, last_change: Instant) -> Self {
+        DomainThrottle {
+            concurrency: config.concurrency,
+            rate: config.rate.requests,
+            in_flight: 0,
+            window_start: Instant::now(),
+            window_count: 0,
+            deferrals: 0,
+            deliveries: 0,
+            last_change,
+            last_deferral: Instant::now(),
+            throttled_since: now(),
+            last_deferral_at: now(),
+        }
+    }
+
+    // Additive increase after each recovery interval without deferrals,
+    // returns true when the domain is no longer throttled.
+    pub fn recover(&mut self, domain: &str, config: &AdaptiveThrottle, span_id: u64) -> bool {
+        if self.last_change.elapsed() >= config.recovery_interval
+            && self.last_deferral.elapsed() >= config.recovery_interval
+        {
+            let rate_step = (config.rate.requests / 10).max(1);
+            self.concurrency = (self.concurrency + 1).min(config.concurrency);
+            self.rate = (self.rate + rate_step).min(config.rate.requests);
+            self.last_change = Instant::now();
+
+            trc::event!(
+                Delivery(DeliveryEvent::ThrottleRecovered),
+                SpanId = span_id,
+                Domain = domain.to_string(),
+                Limit = vec![
+                    trc::Value::from(self.concurrency),
+                    trc::Value::from(self.rate)
+                ],
+            );
+        }
+
+        self.in_flight == 0
+            && self.concurrency >= config.concurrency
+            && self.rate >= config.rate.requests
+    }
+}
+
+impl ThrottleSlot {
+    // The caller still holds the permit of the delivery that used the connection,
+    // so the slot is available as long as the domain is not over its concurrency.
+    pub fn acquire(domains: &ThrottledDomains, domain: String) -> Option<Self> {
+        let mut slot = ThrottleSlot {
+            domains: domains.clone(),
+            domain,
+            is_tracked: false,
+        };
+
+        if let Some(throttle) = domains.lock().get_mut(&slot.domain) {
+            if throttle.in_flight > throttle.concurrency {
+                return None;
+            }
+            throttle.in_flight += 1;
+            slot.is_tracked = true;
+        }
+
+        Some(slot)
+    }
+}
+
+impl Drop for ThrottleSlot {
+    fn drop(&mut self) {
+        if self.is_tracked
+            && let Some(throttle) = self.domains.lock().get_mut(&self.domain)
+        {
+            throttle.in_flight = throttle.in_flight.saturating_sub(1);
+        }
+    }
+}
@@ -0,0 +1,27 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use sieve::{FunctionMap, runtime::Variable};
+
+use crate::scripts::ScriptModification;
+
+use super::PluginContext;
+
+pub fn register(plugin_id: u32, fnc_map: &mut FunctionMap) {
+    fnc_map.set_external_function("hold", plugin_id, 1);
+}
+
+pub fn exec(ctx: PluginContext<'_>) -> trc::Result<Variable> {
+    ctx.modifications.push(ScriptModification::Hold {
+        reason: match &ctx.arguments[0] {
+            Variable::String(reason) => reason.clone(),
+            other => Arc::new(other.to_string().into_owned()),
+        },
+    });
+    Ok(true.into())
+}
@@ -7,6 +7,7 @@
 pub mod dns;
 pub mod exec;
 pub mod headers;
+pub mod hold;
 pub mod http;
 pub mod llm_prompt;
 pub mod lookup;
@@ -31,7 +32,7 @@ pub struct PluginContext<'x> {
     pub arguments: Vec<Variable>,
 }
 
-const PLUGINS_REGISTER: [RegisterPluginFnc; 13] = [
+const PLUGINS_REGISTER: [RegisterPluginFnc; 14] = [
     query::register,
     exec::register,
     lookup::register,
@@ -45,6 +46,7 @@ const PLUGINS_REGISTER: [RegisterPluginFnc; 13] = [
     text::register_tokenize,
     text::register_domain_part,
     llm_prompt::register,
+    hold::register,
 ];
 
 pub trait RegisterSievePlugins {
@@ -93,6 +95,7 @@ impl Core {
             10 => text::exec_tokenize(ctx),
             11 => text::exec_domain_part(ctx),
             12 => llm_prompt::exec(ctx).await,
+            13 => hold::exec(ctx),
             _ => unreachable!(),
         };
 
@@ -127,4 +127,30 @@ impl DavResources {
             .iter()
             .any(|r| r.document_id == *id && !r.is_container())
     }
+
+    pub fn task_list_ids(&self) -> impl Iterator<Item = u32> {
+        self.resources
+            .iter()
+            .filter(|r| r.is_task_list())
+            .map(|r| r.document_id)
+    }
+
+    pub fn task_ids(&self) -> impl Iterator<Item = u32> {
+        self.resources
+            .iter()
+            .filter(|r| r.is_task())
+            .map(|r| r.document_id)
+    }
+
+    pub fn has_task_list_id(&self, id: &u32) -> bool {
+        self.resources
+            .iter()
+            .any(|r| r.document_id == *id && r.is_task_list())
+    }
+
+    pub fn has_task_id(&self, id: &u32) -> bool {
+        self.resources
+            .iter()
+            .any(|r| r.document_id == *id && r.is_task())
+    }
 }
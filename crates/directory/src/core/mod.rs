@@ -319,6 +319,28 @@ impl Permission {
             Permission::JmapParticipantIdentityChanges => {
                 "Track participant identity changes via JMAP"
             }
+            Permission::ImapMetadataGet => "Retrieve mailbox and server metadata via IMAP",
+            Permission::ImapMetadataSet => "Modify mailbox and server metadata via IMAP",
+            Permission::ImapNotify => "Use IMAP NOTIFY command",
+            Permission::ImapGenUrlAuth => "Use IMAP GENURLAUTH command",
+            Permission::ImapResetKey => "Use IMAP RESETKEY command",
+            Permission::ImapUrlFetch => "Use IMAP URLFETCH command",
+            Permission::JmapVirtualMailboxGet => "Retrieve virtual mailboxes via JMAP",
+            Permission::JmapVirtualMailboxSet => "Modify virtual mailboxes via JMAP",
+            Permission::ManageVirtualMailboxes => "Manage virtual mailboxes",
+            Permission::ManagePop3Policy => "Manage POP3 retention policies",
+            Permission::JmapMdnSend => "Send message disposition notifications via JMAP",
+            Permission::JmapMdnParse => "Parse message disposition notifications via JMAP",
+            Permission::JmapTaskListGet => "Retrieve task lists via JMAP",
+            Permission::JmapTaskListSet => "Create or update task lists via JMAP",
+            Permission::JmapTaskListChanges => "Track task list changes via JMAP",
+            Permission::JmapTaskGet => "Retrieve tasks via JMAP",
+            Permission::JmapTaskSet => "Create or update tasks via JMAP",
+            Permission::JmapTaskChanges => "Track task changes via JMAP",
+            Permission::JmapTaskQuery => "Search for tasks matching criteria via JMAP",
+            Permission::JmapTaskQueryChanges => "Track task query changes via JMAP",
+            Permission::SmtpAtrn => "Dequeue mail for own domains with SMTP ATRN",
+            Permission::JmapVirtualMailboxChanges => "Track virtual mailbox changes via JMAP",
         }
     }
 }
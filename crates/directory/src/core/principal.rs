@@ -1665,6 +1665,27 @@ impl Permission {
                 | Permission::JmapParticipantIdentityGet
                 | Permission::JmapParticipantIdentitySet
                 | Permission::JmapParticipantIdentityChanges
+                | Permission::ImapMetadataGet
+                | Permission::ImapMetadataSet
+                | Permission::ImapNotify
+                | Permission::ImapGenUrlAuth
+                | Permission::ImapResetKey
+                | Permission::ImapUrlFetch
+                | Permission::JmapVirtualMailboxGet
+                | Permission::JmapVirtualMailboxSet
+                | Permission::ManageVirtualMailboxes
+                | Permission::ManagePop3Policy
+                | Permission::JmapMdnSend
+                | Permission::JmapMdnParse
+                | Permission::JmapTaskListGet
+                | Permission::JmapTaskListSet
+                | Permission::JmapTaskListChanges
+                | Permission::JmapTaskGet
+                | Permission::JmapTaskSet
+                | Permission::JmapTaskChanges
+                | Permission::JmapTaskQuery
+                | Permission::JmapTaskQueryChanges
+                | Permission::JmapVirtualMailboxChanges
         )
     }
 
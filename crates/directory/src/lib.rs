@@ -423,6 +423,29 @@ pub enum Permission {
     JmapParticipantIdentityGet,
     JmapParticipantIdentitySet,
     JmapParticipantIdentityChanges,
+
+    ImapMetadataGet,
+    ImapMetadataSet,
+    ImapNotify,
+    ImapGenUrlAuth,
+    ImapResetKey,
+    ImapUrlFetch,
+    JmapVirtualMailboxGet,
+    JmapVirtualMailboxSet,
+    ManageVirtualMailboxes,
+    ManagePop3Policy,
+    JmapMdnSend,
+    JmapMdnParse,
+    JmapTaskListGet,
+    JmapTaskListSet,
+    JmapTaskListChanges,
+    JmapTaskGet,
+    JmapTaskSet,
+    JmapTaskChanges,
+    JmapTaskQuery,
+    JmapTaskQueryChanges,
+    SmtpAtrn,
+    JmapVirtualMailboxChanges,
     // TODO: Reuse _ suffixes for new permissions
     // WARNING: add new ids at the end (TODO: use static ids)
 }
@@ -8,6 +8,7 @@ pub mod cache;
 pub mod identity;
 pub mod mailbox;
 pub mod message;
+pub mod pop3;
 pub mod push;
 pub mod sieve;
 pub mod submission;
@@ -7,7 +7,7 @@
 use super::*;
 use crate::{
     cache::{MessageCacheFetch, email::MessageCacheAccess},
-    message::metadata::MessageData,
+    message::{metadata::MessageData, savedate::SaveDateBatch},
 };
 use common::{
     Server, auth::AccessToken, sharing::EffectiveAcl, storage::index::ObjectIndexBuilder,
@@ -97,26 +97,29 @@ impl MailboxDestroy for Server {
                         let prev_message_data = message_data_
                             .to_unarchived::<MessageData>()
                             .caused_by(trc::location!())?;
-                        if !prev_message_data
-                            .inner
-                            .mailboxes
-                            .iter()
-                            .any(|id| id.mailbox_id == document_id)
-                        {
+                        let Some(message_uid) = prev_message_data.inner.message_uid(document_id)
+                        else {
                             return Ok(true);
-                        }
+                        };
+
+                        batch
+                            .with_collection(Collection::Email)
+                            .with_document(message_id);
 
                         if prev_message_data.inner.mailboxes.len() == 1 {
                             // Delete message
                             for mailbox in prev_message_data.inner.mailboxes.iter() {
-                                batch.log_vanished_item(
-                                    VanishedCollection::Email,
-                                    (mailbox.mailbox_id.to_native(), mailbox.uid.to_native()),
-                                );
+                                batch
+                                    .clear_save_date(
+                                        mailbox.mailbox_id.to_native(),
+                                        mailbox.uid.to_native(),
+                                    )
+                                    .log_vanished_item(
+                                        VanishedCollection::Email,
+                                        (mailbox.mailbox_id.to_native(), mailbox.uid.to_native()),
+                                    );
                             }
                             batch
-                                .with_collection(Collection::Email)
-                                .with_document(message_id)
                                 .custom(
                                     ObjectIndexBuilder::<_, ()>::new()
                                         .with_access_token(access_token)
@@ -153,8 +156,7 @@ impl MailboxDestroy for Server {
 
                             // Untag message from mailbox
                             batch
-                                .with_collection(Collection::Email)
-                                .with_document(message_id)
+                                .clear_save_date(document_id, message_uid)
                                 .custom(
                                     ObjectIndexBuilder::new()
                                         .with_access_token(access_token)
@@ -202,6 +204,8 @@ impl MailboxDestroy for Server {
                 .with_collection(Collection::Mailbox)
                 .with_document(document_id)
                 .clear(MailboxField::UidCounter)
+                .clear(MailboxField::Metadata)
+                .clear(MailboxField::UrlAuthKey)
                 .custom(ObjectIndexBuilder::<_, ()>::new().with_current(mailbox))
                 .caused_by(trc::location!())?;
         } else {
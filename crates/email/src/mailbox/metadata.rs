@@ -0,0 +1,85 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+// Computed from the mailbox role, never stored
+pub const SPECIAL_USE_ENTRY: &str = "/private/specialuse";
+
+#[derive(
+    rkyv::Archive, rkyv::Deserialize, rkyv::Serialize, Default, Debug, Clone, PartialEq, Eq,
+)]
+pub struct MailboxMetadata {
+    pub entries: Vec<MetadataEntry>,
+}
+
+#[derive(rkyv::Archive, rkyv::Deserialize, rkyv::Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct MetadataEntry {
+    pub name: String,
+    // Set for /private entries, which are only visible to their owner
+    pub owner_id: Option<u32>,
+    pub value: Vec<u8>,
+}
+
+impl MailboxMetadata {
+    pub fn visible_to(&self, account_id: u32) -> impl Iterator<Item = &MetadataEntry> {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.owner_id.is_none_or(|owner_id| owner_id == account_id))
+    }
+
+    pub fn get(&self, name: &str, owner_id: Option<u32>) -> Option<&MetadataEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.name == name && entry.owner_id == owner_id)
+    }
+
+    pub fn set(&mut self, name: String, owner_id: Option<u32>, value: Option<Vec<u8>>) -> bool {
+        let pos = self
+            .entries
+            .iter()
+            .position(|entry| entry.name == name && entry.owner_id == owner_id);
+
+        match (pos, value) {
+            (Some(pos), Some(value)) => {
+                if self.entries[pos].value != value {
+                    self.entries[pos].value = value;
+                    true
+                } else {
+                    false
+                }
+            }
+            (Some(pos), None) => {
+                self.entries.swap_remove(pos);
+                true
+            }
+            (None, Some(value)) => {
+                self.entries.push(MetadataEntry {
+                    name,
+                    owner_id,
+                    value,
+                });
+                true
+            }
+            (None, None) => false,
+        }
+    }
+
+    pub fn count(&self, owner_id: Option<u32>) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.owner_id == owner_id)
+            .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+pub fn parse_entry_name(name: &str) -> Option<String> {
+    imap_proto::parser::metadata::parse_entry(name.as_bytes().to_vec())
+        .ok()
+        .filter(|name| name != SPECIAL_USE_ENTRY)
+}
@@ -0,0 +1,475 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use crate::cache::{email::MessageCacheAccess, mailbox::MailboxCacheAccess};
+use common::{MessageStoreCache, Server};
+use imap_proto::{parser::search::parse_search_criteria, protocol::search::Filter};
+use std::{borrow::Cow, future::Future};
+use store::{
+    Serialize, ValueKey,
+    roaring::RoaringBitmap,
+    write::{
+        AlignedBytes, Archive, Archiver, BatchBuilder,
+        assert::{AssertValue, ToAssertValue},
+    },
+};
+use trc::AddContext;
+use types::{collection::Collection, field::PrincipalField, special_use::SpecialUse};
+
+// Virtual mailboxes are per-account saved searches that are exposed as
+// read-only mailboxes, their contents are evaluated on demand.
+#[derive(
+    rkyv::Archive, rkyv::Deserialize, rkyv::Serialize, Default, Debug, Clone, PartialEq, Eq,
+)]
+pub struct VirtualMailboxes {
+    pub revision: u64,
+    pub next_id: u32,
+    pub items: Vec<VirtualMailbox>,
+    // Recently deleted mailboxes, changes older than the truncated revision
+    // can no longer be calculated.
+    pub destroyed: Vec<DestroyedVirtualMailbox>,
+    pub truncated_revision: u64,
+}
+
+#[derive(rkyv::Archive, rkyv::Deserialize, rkyv::Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct VirtualMailbox {
+    pub id: u32,
+    pub name: String,
+    pub query: String,
+    pub uid_validity: u32,
+    pub created: u64,
+    pub modified: u64,
+}
+
+#[derive(rkyv::Archive, rkyv::Deserialize, rkyv::Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct DestroyedVirtualMailbox {
+    pub id: u32,
+    pub created: u64,
+    pub destroyed: u64,
+}
+
+const MAX_DESTROYED: usize = 100;
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct VirtualMailboxChanges {
+    pub created: Vec<u32>,
+    pub updated: Vec<u32>,
+    pub destroyed: Vec<u32>,
+}
+
+// UIDs are assigned from a per-mailbox counter as messages enter the result set,
+// they are stored apart from the definitions so both can be updated independently.
+#[derive(
+    rkyv::Archive, rkyv::Deserialize, rkyv::Serialize, Default, Debug, Clone, PartialEq, Eq,
+)]
+pub struct VirtualMailboxUids {
+    pub items: Vec<VirtualUidMap>,
+}
+
+#[derive(rkyv::Archive, rkyv::Deserialize, rkyv::Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct VirtualUidMap {
+    pub id: u32,
+    pub uid_validity: u32,
+    pub uid_next: u32,
+    pub messages: Vec<VirtualMessageUid>,
+}
+
+#[derive(rkyv::Archive, rkyv::Deserialize, rkyv::Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct VirtualMessageUid {
+    pub document_id: u32,
+    pub uid: u32,
+    // One of the message's mailbox UIDs, which tells apart reused document ids
+    pub mailbox_id: u32,
+    pub mailbox_uid: u32,
+}
+
+pub trait VirtualMailboxStore: Sync + Send {
+    fn get_virtual_mailboxes(
+        &self,
+        account_id: u32,
+    ) -> impl Future<Output = trc::Result<VirtualMailboxes>> + Send;
+
+    fn get_virtual_mailboxes_for_update(
+        &self,
+        account_id: u32,
+    ) -> impl Future<Output = trc::Result<(VirtualMailboxes, AssertValue)>> + Send;
+
+    fn set_virtual_mailboxes(
+        &self,
+        account_id: u32,
+        current: AssertValue,
+        mailboxes: VirtualMailboxes,
+    ) -> impl Future<Output = trc::Result<()>> + Send;
+
+    fn get_virtual_mailbox_uids(
+        &self,
+        account_id: u32,
+    ) -> impl Future<Output = trc::Result<(VirtualMailboxUids, AssertValue)>> + Send;
+
+    fn set_virtual_mailbox_uids(
+        &self,
+        account_id: u32,
+        current: AssertValue,
+        uids: VirtualMailboxUids,
+    ) -> impl Future<Output = trc::Result<()>> + Send;
+}
+
+impl VirtualMailboxStore for Server {
+    async fn get_virtual_mailboxes(&self, account_id: u32) -> trc::Result<VirtualMailboxes> {
+        self.get_virtual_mailboxes_for_update(account_id)
+            .await
+            .map(|(mailboxes, _)| mailboxes)
+    }
+
+    async fn get_virtual_mailboxes_for_update(
+        &self,
+        account_id: u32,
+    ) -> trc::Result<(VirtualMailboxes, AssertValue)> {
+        get_principal_value(self, account_id, PrincipalField::VirtualMailboxes).await
+    }
+
+    async fn set_virtual_mailboxes(
+        &self,
+        account_id: u32,
+        current: AssertValue,
+        mut mailboxes: VirtualMailboxes,
+    ) -> trc::Result<()> {
+        // The value is kept when the last mailbox is deleted so that the revision,
+        // which is the JMAP state, never goes back.
+        mailboxes.revision += 1;
+
+        set_principal_value(
+            self,
+            account_id,
+            PrincipalField::VirtualMailboxes,
+            current,
+            Some(mailboxes),
+        )
+        .await
+    }
+
+    async fn get_virtual_mailbox_uids(
+        &self,
+        account_id: u32,
+    ) -> trc::Result<(VirtualMailboxUids, AssertValue)> {
+        get_principal_value(self, account_id, PrincipalField::VirtualMailboxUids).await
+    }
+
+    async fn set_virtual_mailbox_uids(
+        &self,
+        account_id: u32,
+        current: AssertValue,
+        uids: VirtualMailboxUids,
+    ) -> trc::Result<()> {
+        let is_empty = uids.items.is_empty();
+
+        set_principal_value(
+            self,
+            account_id,
+            PrincipalField::VirtualMailboxUids,
+            current,
+            (!is_empty).then_some(uids),
+        )
+        .await
+    }
+}
+
+async fn get_principal_value<T>(
+    server: &Server,
+    account_id: u32,
+    field: PrincipalField,
+) -> trc::Result<(T, AssertValue)>
+where
+    T: rkyv::Archive + Default,
+    T::Archived: for<'a> rkyv::bytecheck::CheckBytes<rkyv::api::high::HighValidator<'a, rkyv::rancor::Error>>
+        + rkyv::Deserialize<T, rkyv::api::high::HighDeserializer<rkyv::rancor::Error>>,
+{
+    if let Some(archive) = server
+        .store()
+        .get_value::<Archive<AlignedBytes>>(ValueKey::property(
+            account_id,
+            Collection::Principal,
+            0,
+            field,
+        ))
+        .await
+        .caused_by(trc::location!())?
+    {
+        archive
+            .deserialize::<T>()
+            .caused_by(trc::location!())
+            .map(|value| (value, archive.to_assert_value()))
+    } else {
+        Ok((T::default(), AssertValue::None))
+    }
+}
+
+async fn set_principal_value<T>(
+    server: &Server,
+    account_id: u32,
+    field: PrincipalField,
+    current: AssertValue,
+    value: Option<T>,
+) -> trc::Result<()>
+where
+    T: rkyv::Archive
+        + for<'a> rkyv::Serialize<
+            rkyv::api::high::HighSerializer<
+                rkyv::util::AlignedVec,
+                rkyv::ser::allocator::ArenaHandle<'a>,
+                rkyv::rancor::Error,
+            >,
+        >,
+{
+    let mut batch = BatchBuilder::new();
+    batch
+        .with_account_id(account_id)
+        .with_collection(Collection::Principal)
+        .with_document(0)
+        .assert_value(field, current);
+    if let Some(value) = value {
+        batch.set(
+            field,
+            Archiver::new(value)
+                .serialize()
+                .caused_by(trc::location!())?,
+        );
+    } else {
+        batch.clear(field);
+    }
+
+    server
+        .store()
+        .write(batch.build_all())
+        .await
+        .caused_by(trc::location!())
+        .map(|_| ())
+}
+
+impl VirtualMailboxes {
+    pub fn get(&self, id: u32) -> Option<&VirtualMailbox> {
+        self.items.iter().find(|item| item.id == id)
+    }
+
+    // Mutable access marks the mailbox as modified in the revision being written
+    pub fn get_mut(&mut self, id: u32) -> Option<&mut VirtualMailbox> {
+        let revision = self.revision + 1;
+        self.items
+            .iter_mut()
+            .find(|item| item.id == id)
+            .map(|item| {
+                item.modified = revision;
+                item
+            })
+    }
+
+    pub fn insert(&mut self, name: String, query: String) -> u32 {
+        // Ids are never reused so that clients do not confuse a new mailbox with a deleted one
+        let id = self.next_id;
+        let revision = self.revision + 1;
+        self.next_id += 1;
+        self.items.push(VirtualMailbox {
+            id,
+            name,
+            query,
+            uid_validity: rand::random::<u32>(),
+            created: revision,
+            modified: revision,
+        });
+        id
+    }
+
+    pub fn remove(&mut self, id: u32) -> bool {
+        if let Some(pos) = self.items.iter().position(|item| item.id == id) {
+            let item = self.items.remove(pos);
+            if self.destroyed.len() >= MAX_DESTROYED {
+                let oldest = self.destroyed.remove(0);
+                self.truncated_revision = oldest.destroyed;
+            }
+            self.destroyed.push(DestroyedVirtualMailbox {
+                id,
+                created: item.created,
+                destroyed: self.revision + 1,
+            });
+            true
+        } else {
+            false
+        }
+    }
+
+    // Returns the mailboxes that changed after the given revision, or None if
+    // the revision is unknown or too old to calculate the changes.
+    pub fn changes_since(&self, revision: u64) -> Option<VirtualMailboxChanges> {
+        if revision > self.revision || revision < self.truncated_revision {
+            return None;
+        }
+
+        let mut changes = VirtualMailboxChanges::default();
+        for item in &self.items {
+            if item.created > revision {
+                changes.created.push(item.id);
+            } else if item.modified > revision {
+                changes.updated.push(item.id);
+            }
+        }
+
+        // Mailboxes created and deleted after the revision were never seen by the client
+        changes.destroyed = self
+            .destroyed
+            .iter()
+            .filter(|item| item.destroyed > revision && item.created <= revision)
+            .map(|item| item.id)
+            .collect();
+
+        Some(changes)
+    }
+
+    pub fn validate_name(
+        &self,
+        name: &str,
+        id: Option<u32>,
+        cache: &MessageStoreCache,
+        max_len: usize,
+    ) -> Result<(), Cow<'static, str>> {
+        if name.is_empty() || name.trim() != name {
+            Err(Cow::from("Invalid virtual mailbox name."))
+        } else if name.len() > max_len {
+            Err(Cow::from("Virtual mailbox name is too long."))
+        } else if name.contains('/') {
+            Err(Cow::from("Virtual mailbox names cannot contain '/'."))
+        } else if name.eq_ignore_ascii_case("INBOX")
+            || cache.mailbox_by_path(name).is_some()
+            || self
+                .items
+                .iter()
+                .any(|item| Some(item.id) != id && item.name.eq_ignore_ascii_case(name))
+        {
+            Err(Cow::from("A mailbox with this name already exists."))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl VirtualMailboxUids {
+    pub fn get(&self, id: u32) -> Option<&VirtualUidMap> {
+        self.items.iter().find(|item| item.id == id)
+    }
+
+    // Brings the UID map of a virtual mailbox up to date with its current result set,
+    // returns true if it was modified and needs to be written back.
+    pub fn synchronize(
+        &mut self,
+        mailboxes: &VirtualMailboxes,
+        mailbox: &VirtualMailbox,
+        message_ids: &RoaringBitmap,
+        cache: &MessageStoreCache,
+    ) -> bool {
+        // Discard maps of deleted mailboxes or previous queries
+        let num_items = self.items.len();
+        self.items.retain(|item| {
+            mailboxes
+                .get(item.id)
+                .is_some_and(|m| m.uid_validity == item.uid_validity)
+        });
+        let mut has_changes = self.items.len() != num_items;
+
+        let idx = if let Some(idx) = self.items.iter().position(|item| item.id == mailbox.id) {
+            idx
+        } else {
+            self.items.push(VirtualUidMap {
+                id: mailbox.id,
+                uid_validity: mailbox.uid_validity,
+                uid_next: 1,
+                messages: Vec::new(),
+            });
+            has_changes = true;
+            self.items.len() - 1
+        };
+
+        self.items[idx].synchronize(message_ids, cache) || has_changes
+    }
+}
+
+impl VirtualUidMap {
+    fn synchronize(&mut self, message_ids: &RoaringBitmap, cache: &MessageStoreCache) -> bool {
+        // Remove messages that left the result set or whose document id was reused
+        let num_messages = self.messages.len();
+        let mut current_ids = RoaringBitmap::new();
+        self.messages.retain(|message| {
+            message_ids.contains(message.document_id)
+                && cache.email_by_id(&message.document_id).is_some_and(|item| {
+                    item.mailboxes
+                        .iter()
+                        .any(|m| m.mailbox_id == message.mailbox_id && m.uid == message.mailbox_uid)
+                })
+                && current_ids.insert(message.document_id)
+        });
+        let mut has_changes = self.messages.len() != num_messages;
+
+        // Messages entering the result set are assigned the next UID
+        for document_id in message_ids {
+            if !current_ids.contains(document_id)
+                && let Some(m) = cache
+                    .email_by_id(&document_id)
+                    .and_then(|item| item.mailboxes.first())
+            {
+                self.messages.push(VirtualMessageUid {
+                    document_id,
+                    uid: self.uid_next,
+                    mailbox_id: m.mailbox_id,
+                    mailbox_uid: m.uid,
+                });
+                self.uid_next += 1;
+                has_changes = true;
+            }
+        }
+
+        has_changes
+    }
+}
+
+impl VirtualMailbox {
+    pub fn set_query(&mut self, query: String) {
+        // A new query invalidates the UIDs of the previous result set
+        if self.query != query {
+            self.query = query;
+            self.uid_validity = rand::random::<u32>();
+        }
+    }
+
+    pub fn filters(&self) -> Result<Vec<Filter>, Cow<'static, str>> {
+        parse_search_criteria(&self.query)
+    }
+}
+
+pub fn validate_virtual_query(query: &str) -> Result<(), Cow<'static, str>> {
+    parse_search_criteria(query).map(|_| ())
+}
+
+// Messages that are only in the Trash or Junk folders are excluded
+// from virtual mailboxes.
+pub fn virtual_mailbox_candidates(cache: &MessageStoreCache) -> RoaringBitmap {
+    let excluded_ids = cache
+        .mailboxes
+        .items
+        .iter()
+        .filter(|m| matches!(m.role, SpecialUse::Trash | SpecialUse::Junk))
+        .map(|m| m.document_id)
+        .collect::<Vec<_>>();
+
+    cache
+        .emails
+        .items
+        .iter()
+        .filter(|item| {
+            item.mailboxes
+                .iter()
+                .any(|m| !excluded_ids.contains(&m.mailbox_id))
+        })
+        .map(|item| item.document_id)
+        .collect()
+}
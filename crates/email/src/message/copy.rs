@@ -16,15 +16,17 @@ use crate::{
         metadata::{
             MESSAGE_HAS_ATTACHMENT, MESSAGE_RECEIVED_MASK, MetadataHeaderName, MetadataHeaderValue,
         },
+        savedate::SaveDateBatch,
+        smime::SmimeVerify,
     },
 };
 use common::{Server, auth::ResourceToken, storage::index::ObjectIndexBuilder};
 use mail_parser::parsers::fields::thread::thread_name;
 use store::write::{
-    BatchBuilder, IndexPropertyClass, SearchIndex, TaskEpoch, TaskQueueClass, ValueClass,
+    BatchBuilder, IndexPropertyClass, SearchIndex, TaskEpoch, TaskQueueClass, ValueClass, now,
 };
 use store::{
-    ValueKey,
+    SerializeInfallible, ValueKey,
     write::{AlignedBytes, Archive},
 };
 use trc::AddContext;
@@ -212,6 +214,21 @@ impl EmailCopy for Server {
                 vec![],
             );
 
+        // Copy S/MIME status at delivery
+        if let Some(smime_status) = self
+            .get_smime_status(from_account_id, from_message_id)
+            .await
+            .caused_by(trc::location!())?
+        {
+            batch.set(EmailField::SmimeStatus, smime_status.serialize());
+        }
+
+        // Store save dates
+        let saved_at = now();
+        for (mailbox_id, uid) in mailboxes.iter().zip(email.imap_uids.iter()) {
+            batch.set_save_date(*mailbox_id, *uid, saved_at);
+        }
+
         // Merge threads if necessary
         if let Some(merge_threads) = MergeThreadIds::new(thread_result).serialize() {
             batch.set(
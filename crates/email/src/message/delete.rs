@@ -4,7 +4,7 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use super::metadata::MessageData;
+use super::{metadata::MessageData, savedate::SaveDateBatch};
 use common::{KV_LOCK_PURGE_ACCOUNT, Server, storage::index::ObjectIndexBuilder};
 use directory::backend::internal::manage::ManageDirectory;
 use groupware::calendar::storage::ItipAutoExpunge;
@@ -68,20 +68,24 @@ impl EmailDeletion for Server {
                 let metadata = data_
                     .to_unarchived::<MessageData>()
                     .caused_by(trc::location!())?;
+                batch.with_document(document_id);
                 for mailbox in metadata.inner.mailboxes.iter() {
-                    batch.log_vanished_item(
-                        VanishedCollection::Email,
-                        (mailbox.mailbox_id.to_native(), mailbox.uid.to_native()),
-                    );
+                    batch
+                        .clear_save_date(mailbox.mailbox_id.to_native(), mailbox.uid.to_native())
+                        .log_vanished_item(
+                            VanishedCollection::Email,
+                            (mailbox.mailbox_id.to_native(), mailbox.uid.to_native()),
+                        );
                 }
                 batch
-                    .with_document(document_id)
                     .custom(
                         ObjectIndexBuilder::<_, ()>::new()
                             .with_tenant_id(tenant_id)
                             .with_current(metadata),
                     )
                     .caused_by(trc::location!())?
+                    .clear(EmailField::Snooze)
+                    .clear(EmailField::SmimeStatus)
                     .set(
                         ValueClass::TaskQueue(TaskQueueClass::UpdateIndex {
                             index: SearchIndex::Email,
@@ -180,6 +180,8 @@ impl MailDelivery for Server {
                                     is_spam: rcpt.is_spam,
                                 },
                                 session_id: message.session_id,
+                                batch: None,
+                                released_quota: 0,
                             })
                             .await
                         }
@@ -12,6 +12,8 @@ use crate::{
         crypto::EncryptionParams,
         index::{IndexMessage, extractors::VisitText},
         metadata::{MessageData, MessageMetadata},
+        savedate::SaveDateBatch,
+        smime::verify_smime,
     },
 };
 use common::{Server, auth::AccessToken};
@@ -28,7 +30,7 @@ use std::{borrow::Cow, cmp::Ordering, fmt::Write, time::Instant};
 use std::{future::Future, hash::Hasher};
 use store::write::{AlignedBytes, Archive};
 use store::{
-    IndexKeyPrefix, IterateParams, U32_LEN, ValueKey,
+    IndexKeyPrefix, IterateParams, SerializeInfallible, U32_LEN, ValueKey,
     ahash::{AHashMap, AHashSet},
     write::{
         AssignedId, AssignedIds, BatchBuilder, BlobLink, BlobOp, IndexPropertyClass, SearchIndex,
@@ -66,6 +68,10 @@ pub struct IngestEmail<'x> {
     pub received_at: Option<u64>,
     pub source: IngestSource<'x>,
     pub session_id: u64,
+    // Changes to be committed in the same batch as the new message
+    pub batch: Option<BatchBuilder>,
+    // Quota freed by the changes in the batch, such as a replaced message
+    pub released_quota: u64,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -135,9 +141,12 @@ impl EmailIngest for Server {
         let tenant_id = params.access_token.tenant.map(|t| t.id);
         let mut raw_message_len = params.raw_message.len() as u64;
         let resource_token = params.access_token.as_resource_token();
-        self.has_available_quota(&resource_token, raw_message_len)
-            .await
-            .caused_by(trc::location!())?;
+        self.has_available_quota(
+            &resource_token,
+            raw_message_len.saturating_sub(params.released_quota),
+        )
+        .await
+        .caused_by(trc::location!())?;
 
         // Parse message
         let mut raw_message = Cow::from(params.raw_message);
@@ -476,6 +485,10 @@ impl EmailIngest for Server {
             _ => false,
         };
 
+        // Verify S/MIME signatures before the message is encrypted
+        let smime_status =
+            verify_smime(&message, &self.core.jmap.smime_trust_anchors, now()).map(|v| v.status);
+
         // Encrypt message
         let do_encrypt = match params.source {
             IngestSource::Jmap { .. } | IngestSource::Imap { .. } => {
@@ -576,7 +589,7 @@ impl EmailIngest for Server {
         }
 
         // Build write batch
-        let mut batch = BatchBuilder::new();
+        let mut batch = params.batch.take().unwrap_or_default();
         let mailbox_ids_event = mailbox_ids
             .iter()
             .map(|m| trc::Value::from(m.mailbox_id))
@@ -630,6 +643,17 @@ impl EmailIngest for Server {
                 vec![],
             );
 
+        // Store S/MIME status at delivery
+        if let Some(smime_status) = smime_status {
+            batch.set(EmailField::SmimeStatus, smime_status.serialize());
+        }
+
+        // Store save dates
+        let saved_at = now();
+        for (mailbox_id, uid) in params.mailbox_ids.iter().zip(imap_uids.iter()) {
+            batch.set_save_date(*mailbox_id, *uid, saved_at);
+        }
+
         if let Some(blob_hold) = blob_hold {
             batch.clear(blob_hold);
         }
@@ -11,3 +11,7 @@ pub mod delivery;
 pub mod index;
 pub mod ingest;
 pub mod metadata;
+pub mod savedate;
+pub mod smime;
+pub mod snooze;
+pub mod urlauth;
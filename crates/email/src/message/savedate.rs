@@ -0,0 +1,114 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use common::Server;
+use std::future::Future;
+use store::{
+    IterateParams, SerializeInfallible, U32_LEN, ValueKey,
+    ahash::AHashMap,
+    write::{BatchBuilder, IndexPropertyClass, ValueClass, key::DeserializeBigEndian},
+};
+use trc::AddContext;
+use types::{collection::Collection, field::EmailField};
+
+// The date a message was saved in a mailbox (RFC 8514) is stored
+// per mailbox UID, as messages can be added to mailboxes at different times.
+pub trait SaveDateBatch {
+    fn set_save_date(&mut self, mailbox_id: u32, uid: u32, saved_at: u64) -> &mut Self;
+    fn clear_save_date(&mut self, mailbox_id: u32, uid: u32) -> &mut Self;
+}
+
+pub trait SaveDate: Sync + Send {
+    fn get_save_date(
+        &self,
+        account_id: u32,
+        document_id: u32,
+        mailbox_id: u32,
+        uid: u32,
+    ) -> impl Future<Output = trc::Result<Option<u64>>> + Send;
+
+    fn get_save_dates(
+        &self,
+        account_id: u32,
+        mailbox_id: u32,
+    ) -> impl Future<Output = trc::Result<AHashMap<u32, u64>>> + Send;
+}
+
+impl SaveDateBatch for BatchBuilder {
+    fn set_save_date(&mut self, mailbox_id: u32, uid: u32, saved_at: u64) -> &mut Self {
+        self.set(save_date_class(mailbox_id, uid), saved_at.serialize())
+    }
+
+    fn clear_save_date(&mut self, mailbox_id: u32, uid: u32) -> &mut Self {
+        self.clear(save_date_class(mailbox_id, uid))
+    }
+}
+
+impl SaveDate for Server {
+    async fn get_save_date(
+        &self,
+        account_id: u32,
+        document_id: u32,
+        mailbox_id: u32,
+        uid: u32,
+    ) -> trc::Result<Option<u64>> {
+        self.store()
+            .get_value::<u64>(ValueKey {
+                account_id,
+                collection: Collection::Email.into(),
+                document_id,
+                class: save_date_class(mailbox_id, uid),
+            })
+            .await
+            .caused_by(trc::location!())
+    }
+
+    async fn get_save_dates(
+        &self,
+        account_id: u32,
+        mailbox_id: u32,
+    ) -> trc::Result<AHashMap<u32, u64>> {
+        let mut save_dates = AHashMap::new();
+
+        self.store()
+            .iterate(
+                IterateParams::new(
+                    ValueKey {
+                        account_id,
+                        collection: Collection::Email.into(),
+                        document_id: 0,
+                        class: save_date_class(mailbox_id, 0),
+                    },
+                    ValueKey {
+                        account_id,
+                        collection: Collection::Email.into(),
+                        document_id: u32::MAX,
+                        class: save_date_class(mailbox_id, u32::MAX),
+                    },
+                )
+                .ascending(),
+                |key, value| {
+                    save_dates.insert(
+                        key.deserialize_be_u32(key.len() - U32_LEN)?,
+                        value.deserialize_be_u64(0)?,
+                    );
+
+                    Ok(true)
+                },
+            )
+            .await
+            .caused_by(trc::location!())?;
+
+        Ok(save_dates)
+    }
+}
+
+fn save_date_class(mailbox_id: u32, uid: u32) -> ValueClass {
+    ValueClass::IndexProperty(IndexPropertyClass::Integer {
+        property: EmailField::SaveDate.into(),
+        value: ((mailbox_id as u64) << 32) | uid as u64,
+    })
+}
@@ -0,0 +1,722 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use super::metadata::MessageMetadata;
+use common::Server;
+use mail_parser::{Message, MessageParser, MessagePart, MimeHeaders, PartType};
+use rasn::types::{ObjectIdentifier, OctetString, Oid};
+use rasn_cms::{
+    CONTENT_ENVELOPED_DATA, CONTENT_SIGNED_DATA, ContentInfo, MESSAGE_DIGEST, SignedData,
+    SignerIdentifier, SignerInfo,
+};
+use rasn_pkix::{Certificate, GeneralName, Name, SubjectAltName};
+use ring::digest;
+use rustls_pki_types::{CertificateDer, SignatureVerificationAlgorithm, UnixTime};
+use std::{future::Future, time::Duration};
+use store::{
+    Deserialize, IterateParams, SerializeInfallible, U32_LEN, ValueKey,
+    write::{AlignedBytes, Archive, ValueClass, key::DeserializeBigEndian, now},
+};
+use trc::AddContext;
+use types::{collection::Collection, field::EmailField};
+use webpki::{EndEntityCert, KeyUsage, anchor_from_trusted_cert};
+
+// S/MIME signature status as defined in RFC 9219, section 3.1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmimeStatus {
+    Unknown,
+    Signed,
+    SignedVerified,
+    SignedFailed,
+    EncryptedSigned,
+    EncryptedSignedVerified,
+    EncryptedSignedFailed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmimeVerification {
+    pub status: SmimeStatus,
+    pub errors: Vec<String>,
+}
+
+const OID_SHA256: &Oid = Oid::const_new(&[2, 16, 840, 1, 101, 3, 4, 2, 1]);
+const OID_SHA384: &Oid = Oid::const_new(&[2, 16, 840, 1, 101, 3, 4, 2, 2]);
+const OID_SHA512: &Oid = Oid::const_new(&[2, 16, 840, 1, 101, 3, 4, 2, 3]);
+const OID_RSA: &Oid = Oid::const_new(&[1, 2, 840, 113549, 1, 1, 1]);
+const OID_RSA_SHA256: &Oid = Oid::const_new(&[1, 2, 840, 113549, 1, 1, 11]);
+const OID_RSA_SHA384: &Oid = Oid::const_new(&[1, 2, 840, 113549, 1, 1, 12]);
+const OID_RSA_SHA512: &Oid = Oid::const_new(&[1, 2, 840, 113549, 1, 1, 13]);
+const OID_EC_PUBLIC_KEY: &Oid = Oid::const_new(&[1, 2, 840, 10045, 2, 1]);
+const OID_ECDSA_SHA256: &Oid = Oid::const_new(&[1, 2, 840, 10045, 4, 3, 2]);
+const OID_ECDSA_SHA384: &Oid = Oid::const_new(&[1, 2, 840, 10045, 4, 3, 3]);
+const OID_ED25519: &Oid = Oid::const_new(&[1, 3, 101, 112]);
+const OID_EMAIL_ADDRESS: &Oid = Oid::const_new(&[1, 2, 840, 113549, 1, 9, 1]);
+const OID_SUBJECT_KEY_ID: &Oid = Oid::const_new(&[2, 5, 29, 14]);
+const OID_SUBJECT_ALT_NAME: &Oid = Oid::const_new(&[2, 5, 29, 17]);
+
+pub trait SmimeVerify: Sync + Send {
+    fn get_smime_status(
+        &self,
+        account_id: u32,
+        document_id: u32,
+    ) -> impl Future<Output = trc::Result<Option<SmimeStatus>>> + Send;
+
+    fn get_smime_statuses(
+        &self,
+        account_id: u32,
+    ) -> impl Future<Output = trc::Result<Vec<(u32, SmimeStatus)>>> + Send;
+
+    fn verify_smime_status(
+        &self,
+        account_id: u32,
+        document_id: u32,
+        at_delivery: Option<SmimeStatus>,
+    ) -> impl Future<Output = trc::Result<Option<SmimeVerification>>> + Send;
+}
+
+impl SmimeVerify for Server {
+    async fn get_smime_status(
+        &self,
+        account_id: u32,
+        document_id: u32,
+    ) -> trc::Result<Option<SmimeStatus>> {
+        self.store()
+            .get_value::<SmimeStatus>(ValueKey::property(
+                account_id,
+                Collection::Email,
+                document_id,
+                EmailField::SmimeStatus,
+            ))
+            .await
+            .caused_by(trc::location!())
+    }
+
+    async fn get_smime_statuses(&self, account_id: u32) -> trc::Result<Vec<(u32, SmimeStatus)>> {
+        let mut statuses = Vec::new();
+        self.store()
+            .iterate(
+                IterateParams::new(
+                    ValueKey {
+                        account_id,
+                        collection: Collection::Email.into(),
+                        document_id: 0,
+                        class: ValueClass::Property(EmailField::SmimeStatus.into()),
+                    },
+                    ValueKey {
+                        account_id,
+                        collection: Collection::Email.into(),
+                        document_id: u32::MAX,
+                        class: ValueClass::Property(EmailField::SmimeStatus.into()),
+                    },
+                )
+                .ascending(),
+                |key, value| {
+                    statuses.push((
+                        key.deserialize_be_u32(key.len() - U32_LEN)?,
+                        SmimeStatus::deserialize(value)?,
+                    ));
+
+                    Ok(true)
+                },
+            )
+            .await
+            .caused_by(trc::location!())
+            .map(|_| statuses)
+    }
+
+    async fn verify_smime_status(
+        &self,
+        account_id: u32,
+        document_id: u32,
+        at_delivery: Option<SmimeStatus>,
+    ) -> trc::Result<Option<SmimeVerification>> {
+        let Some(metadata_) = self
+            .store()
+            .get_value::<Archive<AlignedBytes>>(ValueKey::property(
+                account_id,
+                Collection::Email,
+                document_id,
+                EmailField::Metadata,
+            ))
+            .await
+            .caused_by(trc::location!())?
+        else {
+            return Ok(None);
+        };
+        let metadata = metadata_
+            .unarchive::<MessageMetadata>()
+            .caused_by(trc::location!())?;
+        let Some(raw_body) = self
+            .blob_store()
+            .get_blob(metadata.blob_hash.0.as_slice(), 0..usize::MAX)
+            .await
+            .caused_by(trc::location!())?
+        else {
+            return Ok(SmimeVerification::or_at_delivery(None, at_delivery));
+        };
+        let mut raw_message = metadata.raw_headers.to_vec();
+        raw_message.extend_from_slice(
+            raw_body
+                .get(metadata.blob_body_offset.to_native() as usize..)
+                .unwrap_or_default(),
+        );
+
+        Ok(SmimeVerification::or_at_delivery(
+            MessageParser::new()
+                .parse(&raw_message)
+                .and_then(|message| {
+                    verify_smime(&message, &self.core.jmap.smime_trust_anchors, now())
+                }),
+            at_delivery,
+        ))
+    }
+}
+
+// id-kp-emailProtection (1.3.6.1.5.5.7.3.4)
+const EKU_EMAIL_PROTECTION: &[u8] = &[0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x03, 0x04];
+
+// Verifies the S/MIME signature of a message against the provided trust anchors,
+// returns `None` when the message is neither signed nor encrypted with S/MIME.
+pub fn verify_smime(
+    message: &Message<'_>,
+    trust_anchors: &[CertificateDer<'_>],
+    now: u64,
+) -> Option<SmimeVerification> {
+    let root = message.root_part();
+    let content_type = root.content_type()?;
+    let (content, signature) = match (
+        content_type.ctype().to_ascii_lowercase().as_str(),
+        content_type.subtype()?.to_ascii_lowercase().as_str(),
+    ) {
+        ("multipart", "signed") => {
+            if !content_type
+                .attribute("protocol")
+                .is_some_and(is_pkcs7_signature)
+            {
+                return None;
+            }
+            let (content_id, signature_id) = match &root.body {
+                PartType::Multipart(part_ids) if part_ids.len() == 2 => {
+                    (part_ids[0] as usize, part_ids[1] as usize)
+                }
+                _ => {
+                    return SmimeVerification::failed("Malformed multipart/signed message").into();
+                }
+            };
+            let content = message.parts.get(content_id)?;
+            (
+                Some(
+                    message
+                        .raw_message
+                        .get(content.offset_header as usize..content.offset_end as usize)?,
+                ),
+                message.parts.get(signature_id)?.contents(),
+            )
+        }
+        ("application", "pkcs7-mime" | "x-pkcs7-mime") => (None, root.contents()),
+        _ => return None,
+    };
+
+    let content_info = match rasn::ber::decode::<ContentInfo>(signature) {
+        Ok(content_info) => content_info,
+        Err(_) => return SmimeVerification::failed("Failed to parse CMS structure").into(),
+    };
+    if content_info.content_type == *CONTENT_ENVELOPED_DATA && content.is_none() {
+        // Encrypted messages can't be verified without the recipient's private key
+        return Some(SmimeVerification {
+            status: SmimeStatus::Unknown,
+            errors: vec![],
+        });
+    } else if content_info.content_type != *CONTENT_SIGNED_DATA {
+        return SmimeVerification::failed("Unsupported CMS content type").into();
+    }
+
+    Some(
+        verify_signed_data(
+            content_info.content.as_bytes(),
+            content,
+            message
+                .from()
+                .and_then(|from| from.first())
+                .and_then(|addr| addr.address()),
+            trust_anchors,
+            now,
+        )
+        .unwrap_or_else(|err| err),
+    )
+}
+
+fn verify_signed_data(
+    signed_data_der: &[u8],
+    detached_content: Option<&[u8]>,
+    sender: Option<&str>,
+    trust_anchors: &[CertificateDer<'_>],
+    now: u64,
+) -> Result<SmimeVerification, SmimeVerification> {
+    let signed_data = rasn::ber::decode::<SignedData>(signed_data_der)
+        .map_err(|_| SmimeVerification::failed("Failed to parse CMS signed data"))?;
+    let raw = RawSignedData::parse(signed_data_der)
+        .ok_or_else(|| SmimeVerification::failed("Failed to parse CMS signed data"))?;
+    let content = match (detached_content, &signed_data.encap_content_info.content) {
+        (Some(content), _) => content,
+        (None, Some(content)) => content.as_ref(),
+        (None, None) => return Err(SmimeVerification::failed("Signed content is missing")),
+    };
+    let is_encrypted = signed_data.encap_content_info.content_type == *CONTENT_ENVELOPED_DATA
+        || MessageParser::new()
+            .parse(content)
+            .is_some_and(|message| is_enveloped_data(message.root_part()));
+
+    // Decode the certificates bundled with the signature
+    let certificates = raw
+        .certificates
+        .iter()
+        .filter_map(|der| {
+            rasn::der::decode::<Certificate>(der)
+                .ok()
+                .map(|cert| (CertificateDer::from(*der), cert))
+        })
+        .collect::<Vec<_>>();
+    let anchors = trust_anchors
+        .iter()
+        .filter_map(|cert| anchor_from_trusted_cert(cert).ok())
+        .collect::<Vec<_>>();
+    let intermediates = certificates
+        .iter()
+        .map(|(der, _)| der.clone())
+        .collect::<Vec<_>>();
+
+    let mut result = SmimeVerification {
+        status: SmimeStatus::SignedVerified,
+        errors: vec![],
+    };
+    if raw.signer_infos.is_empty() {
+        result.fail("No signers found");
+    }
+    for (signer_info_der, signed_attrs) in raw.signer_infos {
+        let Ok(signer_info) = rasn::ber::decode::<SignerInfo>(signer_info_der) else {
+            result.fail("Failed to parse signer information");
+            continue;
+        };
+        let Some((cert_der, cert)) = certificates
+            .iter()
+            .find(|(_, cert)| is_signer_certificate(&signer_info.sid, cert))
+        else {
+            result.fail("Signer certificate not found");
+            continue;
+        };
+
+        // Verify the message digest
+        let Some(digest_alg) = digest_algorithm(&signer_info.digest_algorithm.algorithm) else {
+            result.unsupported("Unsupported digest algorithm");
+            continue;
+        };
+        let content_digest = digest::digest(digest_alg, content);
+        let signed_message =
+            if let (Some(attrs), Some(signed_attrs)) = (&signer_info.signed_attrs, signed_attrs) {
+                let message_digest = attrs
+                    .iter()
+                    .find(|attr| attr.r#type == *MESSAGE_DIGEST)
+                    .and_then(|attr| attr.values.iter().next())
+                    .and_then(|value| rasn::der::decode::<OctetString>(value.as_bytes()).ok());
+                if message_digest.as_deref() != Some(content_digest.as_ref()) {
+                    result.fail("Message digest mismatch");
+                    continue;
+                }
+
+                // Signed attributes are signed using their SET OF encoding
+                let mut signed_attrs = signed_attrs.to_vec();
+                signed_attrs[0] = 0x31;
+                signed_attrs
+            } else {
+                content.to_vec()
+            };
+
+        // Verify the signature
+        let Ok(end_entity) = EndEntityCert::try_from(cert_der) else {
+            result.fail("Failed to parse signer certificate");
+            continue;
+        };
+        let algorithms = signature_algorithms(
+            &signer_info.signature_algorithm.algorithm,
+            &signer_info.digest_algorithm.algorithm,
+        );
+        if algorithms.is_empty() {
+            result.unsupported("Unsupported signature algorithm");
+            continue;
+        }
+        if !algorithms.iter().any(|alg| {
+            end_entity
+                .verify_signature(*alg, &signed_message, signer_info.signature.as_ref())
+                .is_ok()
+        }) {
+            result.fail("Signature verification failed");
+            continue;
+        }
+
+        // Validate the certificate chain, without trust anchors the signature
+        // is reported as signed but not verified (RFC 9219, section 3.1)
+        if anchors.is_empty() {
+            result.unsupported("No trust anchors configured");
+        } else if let Err(err) = end_entity.verify_for_usage(
+            webpki::ALL_VERIFICATION_ALGS,
+            &anchors,
+            &intermediates,
+            UnixTime::since_unix_epoch(Duration::from_secs(now)),
+            KeyUsage::required_if_present(EKU_EMAIL_PROTECTION),
+            None,
+            None,
+        ) {
+            result.fail(format!("Certificate validation failed: {err}"));
+            continue;
+        }
+
+        // Make sure the certificate belongs to the sender
+        if let Some(sender) = sender
+            && !certificate_addresses(cert)
+                .iter()
+                .any(|addr| addr.eq_ignore_ascii_case(sender))
+        {
+            result.fail("Signer certificate does not match the sender address");
+        }
+    }
+
+    // Signed content that is itself encrypted (encrypt-then-sign)
+    if is_encrypted {
+        result.status = result.status.into_encrypted();
+    }
+
+    Ok(result)
+}
+
+impl SmimeVerification {
+    // Messages encrypted at rest by the server can no longer be verified,
+    // these report the status obtained at delivery time instead.
+    pub fn or_at_delivery(live: Option<Self>, at_delivery: Option<SmimeStatus>) -> Option<Self> {
+        match (live, at_delivery) {
+            (live, Some(status))
+                if status != SmimeStatus::Unknown
+                    && live
+                        .as_ref()
+                        .is_none_or(|live| live.status == SmimeStatus::Unknown) =>
+            {
+                Some(SmimeVerification {
+                    status,
+                    errors: vec![],
+                })
+            }
+            (live, _) => live,
+        }
+    }
+
+    fn failed(error: impl Into<String>) -> Self {
+        SmimeVerification {
+            status: SmimeStatus::SignedFailed,
+            errors: vec![error.into()],
+        }
+    }
+
+    fn fail(&mut self, error: impl Into<String>) {
+        self.status = SmimeStatus::SignedFailed;
+        self.errors.push(error.into());
+    }
+
+    fn unsupported(&mut self, error: impl Into<String>) {
+        if self.status == SmimeStatus::SignedVerified {
+            self.status = SmimeStatus::Signed;
+        }
+        self.errors.push(error.into());
+    }
+}
+
+impl SmimeStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SmimeStatus::Unknown => "unknown",
+            SmimeStatus::Signed => "signed",
+            SmimeStatus::SignedVerified => "signed/verified",
+            SmimeStatus::SignedFailed => "signed/failed",
+            SmimeStatus::EncryptedSigned => "encrypted+signed",
+            SmimeStatus::EncryptedSignedVerified => "encrypted+signed/verified",
+            SmimeStatus::EncryptedSignedFailed => "encrypted+signed/failed",
+        }
+    }
+
+    pub fn is_verified(&self) -> bool {
+        matches!(
+            self,
+            SmimeStatus::SignedVerified | SmimeStatus::EncryptedSignedVerified
+        )
+    }
+
+    fn into_encrypted(self) -> Self {
+        match self {
+            SmimeStatus::Signed => SmimeStatus::EncryptedSigned,
+            SmimeStatus::SignedVerified => SmimeStatus::EncryptedSignedVerified,
+            SmimeStatus::SignedFailed => SmimeStatus::EncryptedSignedFailed,
+            status => status,
+        }
+    }
+
+    fn to_u8(self) -> u8 {
+        match self {
+            SmimeStatus::Unknown => 0,
+            SmimeStatus::Signed => 1,
+            SmimeStatus::SignedVerified => 2,
+            SmimeStatus::SignedFailed => 3,
+            SmimeStatus::EncryptedSigned => 4,
+            SmimeStatus::EncryptedSignedVerified => 5,
+            SmimeStatus::EncryptedSignedFailed => 6,
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => SmimeStatus::Signed,
+            2 => SmimeStatus::SignedVerified,
+            3 => SmimeStatus::SignedFailed,
+            4 => SmimeStatus::EncryptedSigned,
+            5 => SmimeStatus::EncryptedSignedVerified,
+            6 => SmimeStatus::EncryptedSignedFailed,
+            _ => SmimeStatus::Unknown,
+        }
+    }
+}
+
+impl SerializeInfallible for SmimeStatus {
+    fn serialize(&self) -> Vec<u8> {
+        vec![self.to_u8()]
+    }
+}
+
+impl Deserialize for SmimeStatus {
+    fn deserialize(bytes: &[u8]) -> trc::Result<Self> {
+        bytes
+            .first()
+            .map(|value| SmimeStatus::from_u8(*value))
+            .ok_or_else(|| trc::StoreEvent::DataCorruption.caused_by(trc::location!()))
+    }
+}
+
+fn is_pkcs7_signature(protocol: &str) -> bool {
+    protocol.eq_ignore_ascii_case("application/pkcs7-signature")
+        || protocol.eq_ignore_ascii_case("application/x-pkcs7-signature")
+}
+
+fn is_enveloped_data(part: &MessagePart<'_>) -> bool {
+    part.content_type().is_some_and(|content_type| {
+        content_type.ctype().eq_ignore_ascii_case("application")
+            && content_type.subtype().is_some_and(|subtype| {
+                subtype.eq_ignore_ascii_case("pkcs7-mime")
+                    || subtype.eq_ignore_ascii_case("x-pkcs7-mime")
+            })
+            && content_type
+                .attribute("smime-type")
+                .is_some_and(|smime_type| {
+                    smime_type.eq_ignore_ascii_case("enveloped-data")
+                        || smime_type.eq_ignore_ascii_case("authEnveloped-data")
+                })
+    })
+}
+
+fn is_signer_certificate(sid: &SignerIdentifier, cert: &Certificate) -> bool {
+    match sid {
+        SignerIdentifier::IssuerAndSerialNumber(issuer) => {
+            cert.tbs_certificate.issuer == issuer.issuer
+                && cert.tbs_certificate.serial_number == issuer.serial_number
+        }
+        SignerIdentifier::SubjectKeyIdentifier(key_id) => cert
+            .tbs_certificate
+            .extensions
+            .iter()
+            .flat_map(|extensions| extensions.iter())
+            .filter(|ext| ext.extn_id == *OID_SUBJECT_KEY_ID)
+            .filter_map(|ext| rasn::der::decode::<OctetString>(&ext.extn_value).ok())
+            .any(|cert_key_id| cert_key_id == *key_id),
+    }
+}
+
+fn certificate_addresses(cert: &Certificate) -> Vec<String> {
+    let mut addresses = cert
+        .tbs_certificate
+        .extensions
+        .iter()
+        .flat_map(|extensions| extensions.iter())
+        .filter(|ext| ext.extn_id == *OID_SUBJECT_ALT_NAME)
+        .filter_map(|ext| rasn::der::decode::<SubjectAltName>(&ext.extn_value).ok())
+        .flatten()
+        .filter_map(|name| match name {
+            GeneralName::Rfc822Name(addr) => Some(addr.to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let Name::RdnSequence(rdns) = &cert.tbs_certificate.subject;
+    addresses.extend(
+        rdns.iter()
+            .flat_map(|rdn| rdn.iter())
+            .filter(|attr| attr.r#type == *OID_EMAIL_ADDRESS)
+            .filter_map(|attr| {
+                rasn::der::decode::<rasn::types::Ia5String>(attr.value.as_bytes()).ok()
+            })
+            .map(|addr| addr.to_string()),
+    );
+    addresses
+}
+
+fn digest_algorithm(oid: &ObjectIdentifier) -> Option<&'static digest::Algorithm> {
+    if oid == OID_SHA256 {
+        Some(&digest::SHA256)
+    } else if oid == OID_SHA384 {
+        Some(&digest::SHA384)
+    } else if oid == OID_SHA512 {
+        Some(&digest::SHA512)
+    } else {
+        None
+    }
+}
+
+fn signature_algorithms(
+    signature: &ObjectIdentifier,
+    digest: &ObjectIdentifier,
+) -> Vec<&'static dyn SignatureVerificationAlgorithm> {
+    use webpki::ring::*;
+
+    let is_rsa = signature == OID_RSA;
+    let is_ec = signature == OID_EC_PUBLIC_KEY;
+    if signature == OID_RSA_SHA256 || (is_rsa && digest == OID_SHA256) {
+        vec![RSA_PKCS1_2048_8192_SHA256]
+    } else if signature == OID_RSA_SHA384 || (is_rsa && digest == OID_SHA384) {
+        vec![RSA_PKCS1_2048_8192_SHA384]
+    } else if signature == OID_RSA_SHA512 || (is_rsa && digest == OID_SHA512) {
+        vec![RSA_PKCS1_2048_8192_SHA512]
+    } else if signature == OID_ECDSA_SHA256 || (is_ec && digest == OID_SHA256) {
+        vec![ECDSA_P256_SHA256, ECDSA_P384_SHA256]
+    } else if signature == OID_ECDSA_SHA384 || (is_ec && digest == OID_SHA384) {
+        vec![ECDSA_P384_SHA384, ECDSA_P256_SHA384]
+    } else if signature == OID_ED25519 {
+        vec![ED25519]
+    } else {
+        vec![]
+    }
+}
+
+// Raw encodings of the SignedData fields that have to be verified byte by byte,
+// re-encoding the decoded structures does not preserve the original DER ordering.
+struct RawSignedData<'x> {
+    certificates: Vec<&'x [u8]>,
+    signer_infos: Vec<(&'x [u8], Option<&'x [u8]>)>,
+}
+
+impl<'x> RawSignedData<'x> {
+    fn parse(bytes: &'x [u8]) -> Option<Self> {
+        let (signed_data, _) = Tlv::parse(bytes)?;
+        let mut fields = signed_data.children();
+        let mut result = RawSignedData {
+            certificates: vec![],
+            signer_infos: vec![],
+        };
+
+        // Skip version, digestAlgorithms and encapContentInfo
+        for _ in 0..3 {
+            fields.next()?;
+        }
+
+        for field in fields {
+            match field.tag {
+                0xa0 => {
+                    result.certificates = field
+                        .children()
+                        .filter(|cert| cert.tag == 0x30)
+                        .map(|cert| cert.raw)
+                        .collect();
+                }
+                0x31 => {
+                    for signer_info in field.children() {
+                        let signed_attrs = signer_info
+                            .children()
+                            .nth(3)
+                            .filter(|attrs| attrs.tag == 0xa0)
+                            .map(|attrs| attrs.raw);
+                        result.signer_infos.push((signer_info.raw, signed_attrs));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Some(result)
+    }
+}
+
+const MAX_BER_DEPTH: usize = 32;
+
+struct Tlv<'x> {
+    tag: u8,
+    raw: &'x [u8],
+    contents: &'x [u8],
+}
+
+impl<'x> Tlv<'x> {
+    fn parse(bytes: &'x [u8]) -> Option<(Self, &'x [u8])> {
+        Tlv::parse_nested(bytes, 0)
+    }
+
+    fn parse_nested(bytes: &'x [u8], depth: usize) -> Option<(Self, &'x [u8])> {
+        let tag = *bytes.first()?;
+        let len_byte = *bytes.get(1)? as usize;
+        let (header_len, contents_len) = if len_byte < 0x80 {
+            (2, len_byte)
+        } else if len_byte == 0x80 {
+            // BER indefinite length, contents end at the end-of-contents marker
+            if tag & 0x20 == 0 || depth >= MAX_BER_DEPTH {
+                return None;
+            }
+            let mut rest = &bytes[2..];
+            while !rest.starts_with(&[0, 0]) {
+                rest = Tlv::parse_nested(rest, depth + 1)?.1;
+            }
+            let contents_len = bytes.len() - rest.len() - 2;
+
+            return Some((
+                Tlv {
+                    tag,
+                    raw: &bytes[..contents_len + 4],
+                    contents: &bytes[2..contents_len + 2],
+                },
+                &rest[2..],
+            ));
+        } else {
+            let num_bytes = len_byte & 0x7f;
+            if num_bytes > 4 {
+                return None;
+            }
+            let len = bytes
+                .get(2..2 + num_bytes)?
+                .iter()
+                .fold(0usize, |acc, &b| (acc << 8) | b as usize);
+            (2 + num_bytes, len)
+        };
+        let total_len = header_len.checked_add(contents_len)?;
+
+        Some((
+            Tlv {
+                tag,
+                raw: bytes.get(..total_len)?,
+                contents: bytes.get(header_len..total_len)?,
+            },
+            &bytes[total_len..],
+        ))
+    }
+
+    fn children(&self) -> impl Iterator<Item = Tlv<'x>> {
+        let mut bytes = self.contents;
+        std::iter::from_fn(move || {
+            let (tlv, rest) = Tlv::parse(bytes)?;
+            bytes = rest;
+            Some(tlv)
+        })
+    }
+}
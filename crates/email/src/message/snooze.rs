@@ -0,0 +1,110 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use common::Server;
+use std::future::Future;
+use store::{
+    Deserialize, SerializeInfallible, U32_LEN, U64_LEN, ValueKey,
+    write::{BatchBuilder, TaskEpoch, TaskQueueClass, ValueClass, key::DeserializeBigEndian},
+};
+use trc::AddContext;
+use types::{collection::Collection, field::EmailField, keyword::Keyword};
+
+// A snoozed message is stored along with the mailboxes it has to be
+// restored to, the task queue entry is keyed by the wake up time so
+// stale entries can be detected when the snooze is changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EmailSnooze {
+    pub until: u64,
+    pub move_to: Option<u32>,
+    pub restore_to: Vec<u32>,
+}
+
+impl EmailSnooze {
+    pub fn keyword() -> Keyword {
+        Keyword::Other("$snoozed".into())
+    }
+}
+
+pub trait SnoozeBatch {
+    fn set_snooze(&mut self, snooze: &EmailSnooze) -> &mut Self;
+    fn clear_snooze(&mut self, snooze: &EmailSnooze) -> &mut Self;
+}
+
+pub trait Snooze: Sync + Send {
+    fn get_snooze(
+        &self,
+        account_id: u32,
+        document_id: u32,
+    ) -> impl Future<Output = trc::Result<Option<EmailSnooze>>> + Send;
+}
+
+impl SnoozeBatch for BatchBuilder {
+    fn set_snooze(&mut self, snooze: &EmailSnooze) -> &mut Self {
+        self.set(EmailField::Snooze, snooze.serialize()).set(
+            ValueClass::TaskQueue(TaskQueueClass::UnsnoozeEmail {
+                due: TaskEpoch::new(snooze.until),
+            }),
+            vec![],
+        )
+    }
+
+    fn clear_snooze(&mut self, snooze: &EmailSnooze) -> &mut Self {
+        self.clear(EmailField::Snooze)
+            .clear(ValueClass::TaskQueue(TaskQueueClass::UnsnoozeEmail {
+                due: TaskEpoch::new(snooze.until),
+            }))
+    }
+}
+
+impl Snooze for Server {
+    async fn get_snooze(
+        &self,
+        account_id: u32,
+        document_id: u32,
+    ) -> trc::Result<Option<EmailSnooze>> {
+        self.store()
+            .get_value::<EmailSnooze>(ValueKey {
+                account_id,
+                collection: Collection::Email.into(),
+                document_id,
+                class: ValueClass::from(EmailField::Snooze),
+            })
+            .await
+            .caused_by(trc::location!())
+    }
+}
+
+impl SerializeInfallible for EmailSnooze {
+    fn serialize(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(U64_LEN + U32_LEN * (self.restore_to.len() + 1));
+        bytes.extend_from_slice(&self.until.to_be_bytes());
+        bytes.extend_from_slice(&self.move_to.unwrap_or(u32::MAX).to_be_bytes());
+        for mailbox_id in &self.restore_to {
+            bytes.extend_from_slice(&mailbox_id.to_be_bytes());
+        }
+        bytes
+    }
+}
+
+impl Deserialize for EmailSnooze {
+    fn deserialize(bytes: &[u8]) -> trc::Result<Self> {
+        let until = bytes.deserialize_be_u64(0)?;
+        let move_to = bytes.deserialize_be_u32(U64_LEN)?;
+        let mut restore_to = Vec::with_capacity((bytes.len() - U64_LEN) / U32_LEN);
+        let mut pos = U64_LEN + U32_LEN;
+        while pos < bytes.len() {
+            restore_to.push(bytes.deserialize_be_u32(pos)?);
+            pos += U32_LEN;
+        }
+
+        Ok(EmailSnooze {
+            until,
+            move_to: (move_to != u32::MAX).then_some(move_to),
+            restore_to,
+        })
+    }
+}
@@ -0,0 +1,730 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use super::metadata::MessageMetadata;
+use crate::cache::{MessageCacheFetch, email::MessageCacheAccess, mailbox::MailboxCacheAccess};
+use common::{Server, auth::AccessToken, sharing::EffectiveAcl};
+use directory::QueryParams;
+use mail_parser::{DateTime, Message, MessageParser, PartType};
+use rand::{Rng, distributions::Alphanumeric, thread_rng};
+use ring::hmac;
+use std::future::Future;
+use store::{
+    ValueKey,
+    write::{AlignedBytes, Archive, BatchBuilder, now},
+};
+use trc::AddContext;
+use types::{
+    acl::Acl,
+    blob::{BlobClass, BlobId},
+    collection::Collection,
+    field::{EmailField, MailboxField},
+};
+
+pub const URLAUTH_MECHANISM: &str = "INTERNAL";
+const URLAUTH_KEY_LEN: usize = 32;
+
+// An IMAP URL (RFC 5092) pointing to a message or one of its parts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImapUrl {
+    pub user: Option<String>,
+    pub mailbox: String,
+    pub uid_validity: Option<u32>,
+    pub uid: u32,
+    pub section: Option<String>,
+    pub partial: Option<(u32, Option<u32>)>,
+    pub expire: Option<i64>,
+    pub access: Option<UrlAccess>,
+    pub mechanism: Option<String>,
+    pub token: Option<String>,
+    pub rump: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UrlAccess {
+    Submit(String),
+    User(String),
+    AuthUser,
+    Anonymous,
+}
+
+pub trait UrlAuth: Sync + Send {
+    fn urlauth_key(
+        &self,
+        account_id: u32,
+        mailbox_id: u32,
+        create: bool,
+    ) -> impl Future<Output = trc::Result<Option<String>>> + Send;
+
+    fn urlauth_reset(
+        &self,
+        account_id: u32,
+        mailbox_ids: &[u32],
+    ) -> impl Future<Output = trc::Result<()>> + Send;
+
+    fn urlauth_generate(
+        &self,
+        account_id: u32,
+        mailbox_id: u32,
+        url: &ImapUrl,
+    ) -> impl Future<Output = trc::Result<String>> + Send;
+
+    fn urlauth_fetch(
+        &self,
+        url: &ImapUrl,
+        requester: Option<&str>,
+        is_submission: bool,
+    ) -> impl Future<Output = trc::Result<Option<Vec<u8>>>> + Send;
+
+    fn imap_url_fetch(
+        &self,
+        account_id: u32,
+        mailbox_id: u32,
+        url: &ImapUrl,
+    ) -> impl Future<Output = trc::Result<Option<Vec<u8>>>> + Send;
+
+    fn submission_fetch(
+        &self,
+        reference: &str,
+        access_token: &AccessToken,
+    ) -> impl Future<Output = trc::Result<Option<Vec<u8>>>> + Send;
+}
+
+impl UrlAuth for Server {
+    async fn urlauth_key(
+        &self,
+        account_id: u32,
+        mailbox_id: u32,
+        create: bool,
+    ) -> trc::Result<Option<String>> {
+        let key = ValueKey::property(
+            account_id,
+            Collection::Mailbox,
+            mailbox_id,
+            MailboxField::UrlAuthKey,
+        );
+        if let Some(value) = self
+            .store()
+            .get_value::<String>(key.clone())
+            .await
+            .caused_by(trc::location!())?
+        {
+            return Ok(Some(value));
+        } else if !create {
+            return Ok(None);
+        }
+
+        let value = thread_rng()
+            .sample_iter(Alphanumeric)
+            .take(URLAUTH_KEY_LEN)
+            .map(char::from)
+            .collect::<String>();
+        let mut batch = BatchBuilder::new();
+        batch
+            .with_account_id(account_id)
+            .with_collection(Collection::Mailbox)
+            .with_document(mailbox_id)
+            .assert_value(MailboxField::UrlAuthKey, ())
+            .set(MailboxField::UrlAuthKey, value.as_bytes().to_vec());
+        match self.store().write(batch.build_all()).await {
+            Ok(_) => Ok(Some(value)),
+            Err(err) if err.is_assertion_failure() => {
+                // Another session created the key first
+                self.store()
+                    .get_value::<String>(key)
+                    .await
+                    .caused_by(trc::location!())
+            }
+            Err(err) => Err(err.caused_by(trc::location!())),
+        }
+    }
+
+    async fn urlauth_reset(&self, account_id: u32, mailbox_ids: &[u32]) -> trc::Result<()> {
+        let mut batch = BatchBuilder::new();
+        batch
+            .with_account_id(account_id)
+            .with_collection(Collection::Mailbox);
+        for mailbox_id in mailbox_ids {
+            batch
+                .with_document(*mailbox_id)
+                .clear(MailboxField::UrlAuthKey);
+        }
+        if !batch.is_empty() {
+            self.store()
+                .write(batch.build_all())
+                .await
+                .caused_by(trc::location!())?;
+        }
+        Ok(())
+    }
+
+    async fn urlauth_generate(
+        &self,
+        account_id: u32,
+        mailbox_id: u32,
+        url: &ImapUrl,
+    ) -> trc::Result<String> {
+        let key = self
+            .urlauth_key(account_id, mailbox_id, true)
+            .await?
+            .unwrap_or_default();
+        let token = hmac::sign(
+            &hmac::Key::new(hmac::HMAC_SHA256, key.as_bytes()),
+            url.rump.as_bytes(),
+        );
+
+        let mut result = String::with_capacity(url.rump.len() + 74);
+        result.push_str(&url.rump);
+        result.push(':');
+        result.push_str(URLAUTH_MECHANISM);
+        result.push(':');
+        for byte in token.as_ref() {
+            result.push_str(&format!("{byte:02x}"));
+        }
+        Ok(result)
+    }
+
+    async fn urlauth_fetch(
+        &self,
+        url: &ImapUrl,
+        requester: Option<&str>,
+        is_submission: bool,
+    ) -> trc::Result<Option<Vec<u8>>> {
+        // Validate authorization
+        let (Some(user), Some(access), Some(token)) = (&url.user, &url.access, &url.token) else {
+            return Ok(None);
+        };
+        if !url
+            .mechanism
+            .as_ref()
+            .is_some_and(|mechanism| mechanism.eq_ignore_ascii_case(URLAUTH_MECHANISM))
+            || !access.is_allowed(requester, is_submission)
+            || url.expire.is_some_and(|expire| expire <= now() as i64)
+        {
+            return Ok(None);
+        }
+        let Some(token) = decode_hex(token) else {
+            return Ok(None);
+        };
+
+        // Obtain the mailbox of the user that generated the URL
+        let Some(account_id) = self
+            .directory()
+            .query(QueryParams::name(user).with_return_member_of(false))
+            .await
+            .caused_by(trc::location!())?
+            .map(|principal| principal.id())
+        else {
+            return Ok(None);
+        };
+        let Some(mailbox_id) = self
+            .get_cached_messages(account_id)
+            .await
+            .caused_by(trc::location!())?
+            .mailbox_by_path(&url.mailbox)
+            .map(|mailbox| mailbox.document_id)
+        else {
+            return Ok(None);
+        };
+
+        // Verify token
+        let Some(key) = self.urlauth_key(account_id, mailbox_id, false).await? else {
+            return Ok(None);
+        };
+        if hmac::verify(
+            &hmac::Key::new(hmac::HMAC_SHA256, key.as_bytes()),
+            url.rump.as_bytes(),
+            &token,
+        )
+        .is_err()
+        {
+            return Ok(None);
+        }
+
+        self.imap_url_fetch(account_id, mailbox_id, url).await
+    }
+
+    async fn imap_url_fetch(
+        &self,
+        account_id: u32,
+        mailbox_id: u32,
+        url: &ImapUrl,
+    ) -> trc::Result<Option<Vec<u8>>> {
+        // Obtain message
+        let cache = self
+            .get_cached_messages(account_id)
+            .await
+            .caused_by(trc::location!())?;
+        if url.uid_validity.is_some_and(|uid_validity| {
+            cache
+                .mailbox_by_id(&mailbox_id)
+                .is_none_or(|mailbox| mailbox.uid_validity != uid_validity)
+        }) {
+            return Ok(None);
+        }
+        let Some(document_id) = cache
+            .in_mailbox(mailbox_id)
+            .find(|message| {
+                message
+                    .mailboxes
+                    .iter()
+                    .any(|item| item.mailbox_id == mailbox_id && item.uid == url.uid)
+            })
+            .map(|message| message.document_id)
+        else {
+            return Ok(None);
+        };
+
+        // Fetch raw message
+        let Some(raw_message) = fetch_raw_message(self, account_id, document_id).await? else {
+            return Ok(None);
+        };
+
+        // Extract section
+        let contents = if let Some(section) = &url.section {
+            let Some(message) = MessageParser::new().parse(&raw_message) else {
+                return Ok(None);
+            };
+            let Some(contents) = message_section(&message, section) else {
+                return Ok(None);
+            };
+            contents.to_vec()
+        } else {
+            raw_message
+        };
+
+        Ok(Some(if let Some((offset, length)) = url.partial {
+            let start = (offset as usize).min(contents.len());
+            let end = length.map_or(contents.len(), |length| {
+                (start + length as usize).min(contents.len())
+            });
+            contents[start..end].to_vec()
+        } else {
+            contents
+        }))
+    }
+
+    async fn submission_fetch(
+        &self,
+        reference: &str,
+        access_token: &AccessToken,
+    ) -> trc::Result<Option<Vec<u8>>> {
+        if let Some(url) = ImapUrl::parse(reference) {
+            if url.has_token() {
+                return self
+                    .urlauth_fetch(&url, Some(access_token.name.as_str()), true)
+                    .await;
+            } else if url.access.is_some() {
+                return Ok(None);
+            }
+
+            // URLs without an authorization token are resolved against the requester's mailboxes
+            let account_id = if let Some(user) = &url.user {
+                let Some(account_id) = self
+                    .directory()
+                    .query(QueryParams::name(user).with_return_member_of(false))
+                    .await
+                    .caused_by(trc::location!())?
+                    .map(|principal| principal.id())
+                else {
+                    return Ok(None);
+                };
+                account_id
+            } else {
+                access_token.primary_id()
+            };
+            // Shared mailboxes require the same rights as an IMAP FETCH
+            let Some(mailbox_id) = self
+                .get_cached_messages(account_id)
+                .await
+                .caused_by(trc::location!())?
+                .mailbox_by_path(&url.mailbox)
+                .filter(|mailbox| {
+                    access_token.is_member(account_id)
+                        || mailbox
+                            .acls
+                            .as_slice()
+                            .effective_acl(access_token)
+                            .contains(Acl::ReadItems)
+                })
+                .map(|mailbox| mailbox.document_id)
+            else {
+                return Ok(None);
+            };
+
+            self.imap_url_fetch(account_id, mailbox_id, &url).await
+        } else if let Some(blob_id) = BlobId::from_base32(reference) {
+            // Blob ids follow the JMAP EmailSubmission rule: the email must belong
+            // to an account the requester is a member of
+            match &blob_id.class {
+                BlobClass::Linked {
+                    account_id,
+                    collection,
+                    document_id,
+                } if *collection == Collection::Email as u8
+                    && blob_id.section.is_none()
+                    && access_token.is_member(*account_id)
+                    && self
+                        .store()
+                        .blob_has_access(&blob_id.hash, &blob_id.class)
+                        .await
+                        .caused_by(trc::location!())? =>
+                {
+                    fetch_raw_message(self, *account_id, *document_id).await
+                }
+                _ => Ok(None),
+            }
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+async fn fetch_raw_message(
+    server: &Server,
+    account_id: u32,
+    document_id: u32,
+) -> trc::Result<Option<Vec<u8>>> {
+    let Some(metadata_) = server
+        .store()
+        .get_value::<Archive<AlignedBytes>>(ValueKey::property(
+            account_id,
+            Collection::Email,
+            document_id,
+            EmailField::Metadata,
+        ))
+        .await
+        .caused_by(trc::location!())?
+    else {
+        return Ok(None);
+    };
+    let metadata = metadata_
+        .unarchive::<MessageMetadata>()
+        .caused_by(trc::location!())?;
+    let Some(blob) = server
+        .blob_store()
+        .get_blob(metadata.blob_hash.0.as_slice(), 0..usize::MAX)
+        .await
+        .caused_by(trc::location!())?
+    else {
+        return Ok(None);
+    };
+    let raw_body = blob
+        .get(metadata.blob_body_offset.to_native() as usize..)
+        .unwrap_or_default();
+    let mut raw_message = Vec::with_capacity(metadata.raw_headers.len() + raw_body.len());
+    raw_message.extend_from_slice(metadata.raw_headers.as_ref());
+    raw_message.extend_from_slice(raw_body);
+
+    Ok(Some(raw_message))
+}
+
+impl ImapUrl {
+    pub fn parse(url: &str) -> Option<Self> {
+        let (user, path) = if url
+            .get(..7)
+            .is_some_and(|scheme| scheme.eq_ignore_ascii_case("imap://"))
+        {
+            let (authority, path) = url[7..].split_once('/')?;
+            let user = if let Some((userinfo, _)) = authority.rsplit_once('@') {
+                Some(percent_decode(
+                    userinfo.split_once(';').map_or(userinfo, |(user, _)| user),
+                )?)
+            } else {
+                None
+            };
+            (user, path)
+        } else {
+            (None, url.strip_prefix('/')?)
+        };
+
+        // Parse mailbox name
+        let (mailbox, params) = path.split_once(';')?;
+        let mailbox = percent_decode(mailbox.strip_suffix('/').unwrap_or(mailbox))?;
+        if mailbox.is_empty() {
+            return None;
+        }
+
+        // Parse parameters
+        let mut result = ImapUrl {
+            user,
+            mailbox,
+            uid_validity: None,
+            uid: 0,
+            section: None,
+            partial: None,
+            expire: None,
+            access: None,
+            mechanism: None,
+            token: None,
+            rump: url.to_string(),
+        };
+        let mut offset = url.len() - params.len();
+        for param in params.split(';') {
+            let param_offset = offset;
+            offset += param.len() + 1;
+            if result.access.is_some() {
+                // URLAUTH must be the last parameter
+                return None;
+            }
+
+            let (name, value) = param.split_once('=')?;
+            let value = value.strip_suffix('/').unwrap_or(value);
+            if name.eq_ignore_ascii_case("UIDVALIDITY") {
+                result.uid_validity = Some(value.parse().ok()?);
+            } else if name.eq_ignore_ascii_case("UID") {
+                result.uid = value.parse().ok().filter(|uid| *uid > 0)?;
+            } else if name.eq_ignore_ascii_case("SECTION") {
+                result.section = percent_decode(value)?.into();
+            } else if name.eq_ignore_ascii_case("PARTIAL") {
+                result.partial = if let Some((offset, length)) = value.split_once('.') {
+                    (
+                        offset.parse().ok()?,
+                        Some(length.parse().ok().filter(|length| *length > 0)?),
+                    )
+                } else {
+                    (value.parse().ok()?, None)
+                }
+                .into();
+            } else if name.eq_ignore_ascii_case("EXPIRE") {
+                result.expire = DateTime::parse_rfc3339(value)?.to_timestamp().into();
+            } else if name.eq_ignore_ascii_case("URLAUTH") {
+                let mut parts = value.splitn(3, ':');
+                let access = parts.next()?;
+                result.access = UrlAccess::parse(access)?.into();
+                result.rump = url[..param_offset + name.len() + 1 + access.len()].to_string();
+                match (parts.next(), parts.next()) {
+                    (Some(mechanism), Some(token)) => {
+                        result.mechanism = mechanism.to_string().into();
+                        result.token = token.to_string().into();
+                    }
+                    (None, None) => {}
+                    _ => return None,
+                }
+            } else {
+                return None;
+            }
+        }
+
+        if result.uid != 0 { Some(result) } else { None }
+    }
+
+    pub fn has_token(&self) -> bool {
+        self.token.is_some()
+    }
+}
+
+impl UrlAccess {
+    pub fn parse(value: &str) -> Option<Self> {
+        if value.eq_ignore_ascii_case("authuser") {
+            Some(UrlAccess::AuthUser)
+        } else if value.eq_ignore_ascii_case("anonymous") {
+            Some(UrlAccess::Anonymous)
+        } else {
+            let (access, user) = value.split_once('+')?;
+            let user = percent_decode(user).filter(|user| !user.is_empty())?;
+            if access.eq_ignore_ascii_case("submit") {
+                Some(UrlAccess::Submit(user))
+            } else if access.eq_ignore_ascii_case("user") {
+                Some(UrlAccess::User(user))
+            } else {
+                None
+            }
+        }
+    }
+
+    pub fn is_allowed(&self, requester: Option<&str>, is_submission: bool) -> bool {
+        match self {
+            UrlAccess::Anonymous => true,
+            UrlAccess::AuthUser => requester.is_some(),
+            UrlAccess::User(user) => requester == Some(user.as_str()),
+            UrlAccess::Submit(user) => is_submission && requester == Some(user.as_str()),
+        }
+    }
+}
+
+// Returns the contents of an IMAP section such as "1.2", "1.MIME" or "HEADER"
+fn message_section<'x>(message: &'x Message<'_>, section: &str) -> Option<&'x [u8]> {
+    let mut part_nums = Vec::new();
+    let mut suffix = None;
+    for item in section.split('.') {
+        if suffix.is_some() {
+            return None;
+        } else if let Ok(num) = item.parse::<usize>() {
+            part_nums.push(num.checked_sub(1)?);
+        } else if ["HEADER", "TEXT"]
+            .iter()
+            .any(|s| item.eq_ignore_ascii_case(s))
+            || (item.eq_ignore_ascii_case("MIME") && !part_nums.is_empty())
+        {
+            suffix = Some(item.to_ascii_uppercase());
+        } else {
+            return None;
+        }
+    }
+
+    let mut message = message;
+    let mut part = message.root_part();
+    let num_parts = part_nums.len();
+    for (pos, num) in part_nums.into_iter().enumerate() {
+        let is_last = pos == num_parts - 1;
+        part = if let PartType::Multipart(sub_parts) = &part.body {
+            message.parts.get(*sub_parts.get(num)? as usize)?
+        } else if num == 0 && (is_last || matches!(part.body, PartType::Message(_))) {
+            part
+        } else {
+            return None;
+        };
+
+        if let PartType::Message(nested_message) = &part.body
+            && (!is_last || matches!(suffix.as_deref(), Some("HEADER" | "TEXT")))
+        {
+            message = nested_message;
+            part = message.root_part();
+        }
+    }
+
+    let range = match suffix.as_deref() {
+        Some("HEADER" | "MIME") => part.offset_header..part.offset_body,
+        Some(_) => part.offset_body..part.offset_end,
+        None if num_parts > 0 => part.offset_body..part.offset_end,
+        None => part.offset_header..part.offset_end,
+    };
+    message
+        .raw_message
+        .get(range.start as usize..range.end as usize)
+}
+
+fn percent_decode(value: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut iter = value.bytes();
+    while let Some(ch) = iter.next() {
+        if ch == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(ch);
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|pos| u8::from_str_radix(value.get(pos..pos + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ImapUrl, UrlAccess, message_section};
+    use mail_parser::MessageParser;
+
+    #[test]
+    fn parse_imap_url() {
+        assert_eq!(
+            ImapUrl::parse(concat!(
+                "imap://joe@example.com/INBOX/;uid=20/;section=1.2;",
+                "urlauth=submit+fred:internal:91354a473744909de610943775f92038"
+            ))
+            .unwrap(),
+            ImapUrl {
+                user: Some("joe".into()),
+                mailbox: "INBOX".into(),
+                uid_validity: None,
+                uid: 20,
+                section: Some("1.2".into()),
+                partial: None,
+                expire: None,
+                access: Some(UrlAccess::Submit("fred".into())),
+                mechanism: Some("internal".into()),
+                token: Some("91354a473744909de610943775f92038".into()),
+                rump: "imap://joe@example.com/INBOX/;uid=20/;section=1.2;urlauth=submit+fred"
+                    .into(),
+            }
+        );
+        assert_eq!(
+            ImapUrl::parse("/Lists/Rust%20News;UIDVALIDITY=385759045/;UID=7/;PARTIAL=10.20")
+                .unwrap(),
+            ImapUrl {
+                user: None,
+                mailbox: "Lists/Rust News".into(),
+                uid_validity: Some(385759045),
+                uid: 7,
+                section: None,
+                partial: Some((10, Some(20))),
+                expire: None,
+                access: None,
+                mechanism: None,
+                token: None,
+                rump: "/Lists/Rust%20News;UIDVALIDITY=385759045/;UID=7/;PARTIAL=10.20".into(),
+            }
+        );
+        assert_eq!(
+            ImapUrl::parse(
+                "imap://joe@example.com/INBOX/;uid=1;expire=2010-01-01T00:00:00Z;urlauth=anonymous"
+            )
+            .unwrap()
+            .expire,
+            Some(1262304000)
+        );
+
+        for url in [
+            "INBOX/;UID=20",
+            "/INBOX",
+            "/INBOX/;UID=0",
+            "/INBOX/;UID=20;URLAUTH=nobody",
+            "/INBOX/;UID=20;URLAUTH=anonymous:internal",
+            "/INBOX/;UID=20;URLAUTH=anonymous;SECTION=1",
+            "/INBOX/;UID=20;FOO=bar",
+        ] {
+            assert_eq!(ImapUrl::parse(url), None, "{url}");
+        }
+    }
+
+    #[test]
+    fn imap_url_section() {
+        let raw_message = concat!(
+            "From: joe@example.com\r\n",
+            "Content-Type: multipart/mixed; boundary=\"b1\"\r\n\r\n",
+            "--b1\r\n",
+            "Content-Type: text/plain\r\n\r\n",
+            "Hello\r\n",
+            "--b1\r\n",
+            "Content-Type: message/rfc822\r\n\r\n",
+            "Subject: nested\r\n\r\n",
+            "Nested body\r\n",
+            "--b1--\r\n"
+        );
+        let message = MessageParser::new().parse(raw_message).unwrap();
+
+        for (section, expected) in [
+            ("1", Some("Hello")),
+            ("1.MIME", Some("Content-Type: text/plain\r\n\r\n")),
+            ("2.HEADER", Some("Subject: nested\r\n\r\n")),
+            ("2.TEXT", Some("Nested body")),
+            (
+                "HEADER",
+                Some(
+                    "From: joe@example.com\r\nContent-Type: multipart/mixed; boundary=\"b1\"\r\n\r\n",
+                ),
+            ),
+            ("3", None),
+            ("MIME", None),
+            ("1.HEADER.FIELDS", None),
+        ] {
+            assert_eq!(
+                message_section(&message, section).map(|v| std::str::from_utf8(v).unwrap()),
+                expected,
+                "{section}"
+            );
+        }
+    }
+}
@@ -0,0 +1,176 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use common::{Server, config::pop3::Pop3Policy};
+use std::future::Future;
+use store::{
+    Serialize, ValueKey,
+    write::{
+        AlignedBytes, Archive, Archiver, BatchBuilder,
+        assert::{AssertValue, ToAssertValue},
+    },
+};
+use trc::AddContext;
+use types::{collection::Collection, field::PrincipalField};
+
+// UIDs retrieved over POP3, used to hide or expire downloaded messages.
+#[derive(
+    rkyv::Archive, rkyv::Deserialize, rkyv::Serialize, Default, Debug, Clone, PartialEq, Eq,
+)]
+pub struct Pop3State {
+    pub mailbox_id: u32,
+    pub uid_validity: u32,
+    pub downloaded: Vec<Pop3Download>,
+}
+
+#[derive(rkyv::Archive, rkyv::Deserialize, rkyv::Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Pop3Download {
+    pub uid: u32,
+    pub downloaded_at: u64,
+}
+
+pub trait Pop3Store: Sync + Send {
+    fn get_pop3_policy(
+        &self,
+        account_id: u32,
+    ) -> impl Future<Output = trc::Result<Pop3Policy>> + Send;
+
+    fn set_pop3_policy(
+        &self,
+        account_id: u32,
+        policy: Option<Pop3Policy>,
+    ) -> impl Future<Output = trc::Result<()>> + Send;
+
+    fn get_pop3_state(
+        &self,
+        account_id: u32,
+    ) -> impl Future<Output = trc::Result<(Pop3State, AssertValue)>> + Send;
+
+    fn set_pop3_state(
+        &self,
+        account_id: u32,
+        current: AssertValue,
+        state: Pop3State,
+    ) -> impl Future<Output = trc::Result<()>> + Send;
+}
+
+impl Pop3Store for Server {
+    async fn get_pop3_policy(&self, account_id: u32) -> trc::Result<Pop3Policy> {
+        if let Some(archive) = self
+            .store()
+            .get_value::<Archive<AlignedBytes>>(ValueKey::property(
+                account_id,
+                Collection::Principal,
+                0,
+                PrincipalField::Pop3Policy,
+            ))
+            .await
+            .caused_by(trc::location!())?
+        {
+            archive
+                .deserialize::<Pop3Policy>()
+                .caused_by(trc::location!())
+        } else {
+            Ok(self.core.pop3.policy.clone())
+        }
+    }
+
+    async fn set_pop3_policy(
+        &self,
+        account_id: u32,
+        policy: Option<Pop3Policy>,
+    ) -> trc::Result<()> {
+        let mut batch = BatchBuilder::new();
+        batch
+            .with_account_id(account_id)
+            .with_collection(Collection::Principal)
+            .with_document(0);
+        if let Some(policy) = policy {
+            batch.set(
+                PrincipalField::Pop3Policy,
+                Archiver::new(policy)
+                    .serialize()
+                    .caused_by(trc::location!())?,
+            );
+        } else {
+            batch.clear(PrincipalField::Pop3Policy);
+        }
+
+        self.store()
+            .write(batch.build_all())
+            .await
+            .caused_by(trc::location!())
+            .map(|_| ())
+    }
+
+    async fn get_pop3_state(&self, account_id: u32) -> trc::Result<(Pop3State, AssertValue)> {
+        if let Some(archive) = self
+            .store()
+            .get_value::<Archive<AlignedBytes>>(ValueKey::property(
+                account_id,
+                Collection::Principal,
+                0,
+                PrincipalField::Pop3State,
+            ))
+            .await
+            .caused_by(trc::location!())?
+        {
+            archive
+                .deserialize::<Pop3State>()
+                .caused_by(trc::location!())
+                .map(|state| (state, archive.to_assert_value()))
+        } else {
+            Ok((Pop3State::default(), AssertValue::None))
+        }
+    }
+
+    async fn set_pop3_state(
+        &self,
+        account_id: u32,
+        current: AssertValue,
+        state: Pop3State,
+    ) -> trc::Result<()> {
+        // Concurrent sessions of the same account could otherwise drop each other's downloads
+        let mut batch = BatchBuilder::new();
+        batch
+            .with_account_id(account_id)
+            .with_collection(Collection::Principal)
+            .with_document(0)
+            .assert_value(PrincipalField::Pop3State, current);
+        if !state.downloaded.is_empty() {
+            batch.set(
+                PrincipalField::Pop3State,
+                Archiver::new(state)
+                    .serialize()
+                    .caused_by(trc::location!())?,
+            );
+        } else {
+            batch.clear(PrincipalField::Pop3State);
+        }
+
+        self.store()
+            .write(batch.build_all())
+            .await
+            .caused_by(trc::location!())
+            .map(|_| ())
+    }
+}
+
+impl Pop3State {
+    pub fn downloaded_at(&self, uid: u32) -> Option<u64> {
+        self.downloaded
+            .binary_search_by_key(&uid, |d| d.uid)
+            .ok()
+            .map(|idx| self.downloaded[idx].downloaded_at)
+    }
+
+    pub fn insert(&mut self, uid: u32, downloaded_at: u64) {
+        if let Err(idx) = self.downloaded.binary_search_by_key(&uid, |d| d.uid) {
+            self.downloaded
+                .insert(idx, Pop3Download { uid, downloaded_at });
+        }
+    }
+}
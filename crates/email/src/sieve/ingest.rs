@@ -534,6 +534,8 @@ impl SieveScriptIngest for Server {
                             is_spam: envelope_to.is_spam,
                         },
                         session_id,
+                        batch: None,
+                        released_quota: 0,
                     })
                     .await
                 {
@@ -9,11 +9,11 @@ use crate::{
     DavResourceName, RFC_3986,
     calendar::{
         ArchivedCalendar, ArchivedCalendarEvent, Calendar, CalendarEvent, SCHEDULE_INBOX_ID,
-        SCHEDULE_OUTBOX_ID, storage::ItipAutoExpunge,
+        SCHEDULE_OUTBOX_ID, SupportedComponent, storage::ItipAutoExpunge,
     },
     contact::{AddressBook, ArchivedAddressBook, ArchivedContactCard, ContactCard},
 };
-use calcard::common::timezone::Tz;
+use calcard::{common::timezone::Tz, icalendar::ArchivedICalendarComponentType};
 use common::{
     DavName, DavPath, DavResource, DavResourceMetadata, DavResources, Server,
     TinyCalendarPreferences, auth::AccessToken,
@@ -297,6 +297,11 @@ pub(super) fn resource_from_calendar(calendar: &ArchivedCalendar, document_id: u
                     tz: pref.time_zone.tz().unwrap_or(Tz::UTC),
                 })
                 .collect(),
+            supports_tasks: match calendar.supported_components.to_native() {
+                0 => true,
+                supported_components => Bitmap::<SupportedComponent>::from(supported_components)
+                    .contains(SupportedComponent::VTodo),
+            },
         },
     }
 }
@@ -316,6 +321,15 @@ pub(super) fn resource_from_event(event: &ArchivedCalendarEvent, document_id: u3
                 .collect(),
             start,
             duration,
+            is_task: event
+                .data
+                .event
+                .components
+                .iter()
+                .find(|comp| comp.component_type.is_scheduling_object())
+                .is_some_and(|comp| {
+                    matches!(comp.component_type, ArchivedICalendarComponentType::VTodo)
+                }),
         },
     }
 }
@@ -4,19 +4,14 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use std::str::FromStr;
-
-use common::{Server, auth::AccessToken, config::smtp::auth::simple_pem_parse};
+use common::{
+    Server,
+    auth::AccessToken,
+    config::smtp::auth::{DkimAlgorithm, generate_dkim_private_key, obtain_dkim_public_key},
+};
 use directory::{Permission, backend::internal::manage};
 use hyper::Method;
-use mail_auth::{
-    common::crypto::{Ed25519Key, RsaKey, Sha256},
-    dkim::generate::DkimKeyPair,
-};
-use mail_builder::encoders::base64::base64_encode;
 use mail_parser::DateTime;
-use pkcs8::Document;
-use rsa::pkcs1::DecodeRsaPublicKey;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use store::write::now;
@@ -24,16 +19,10 @@ use store::write::now;
 use http_proto::{request::decode_path_element, *};
 use std::future::Future;
 
-#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq)]
-pub enum Algorithm {
-    Rsa,
-    Ed25519,
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 struct DkimSignature {
     id: Option<String>,
-    algorithm: Algorithm,
+    algorithm: DkimAlgorithm,
     domain: String,
     selector: Option<String>,
 }
@@ -59,7 +48,7 @@ pub trait DkimManagement: Sync + Send {
 
     fn create_dkim_key(
         &self,
-        algo: Algorithm,
+        algo: DkimAlgorithm,
         id: impl AsRef<str> + Send,
         domain: impl Into<String> + Send,
         selector: impl Into<String> + Send,
@@ -79,7 +68,27 @@ impl DkimManagement for Server {
                 // Validate the access token
                 access_token.assert_has_permission(Permission::DkimSignatureGet)?;
 
-                self.handle_get_public_key(path).await
+                match (path.get(1), path.get(2).copied()) {
+                    (None, _) => Ok(JsonResponse::new(json!({
+                        "data": self.dkim_key_lifecycles().await?,
+                    }))
+                    .into_http_response()),
+                    (Some(signature_id), Some("rotation")) => {
+                        let signature_id = decode_path_element(signature_id);
+                        let lifecycle = self
+                            .dkim_key_lifecycles()
+                            .await?
+                            .into_iter()
+                            .find(|lifecycle| lifecycle.id == signature_id.as_ref())
+                            .ok_or_else(|| trc::ResourceEvent::NotFound.into_err())?;
+
+                        Ok(JsonResponse::new(json!({
+                            "data": lifecycle,
+                        }))
+                        .into_http_response())
+                    }
+                    _ => self.handle_get_public_key(path).await,
+                }
             }
             Method::POST => {
                 // Validate the access token
@@ -110,7 +119,7 @@ impl DkimManagement for Server {
                 .config
                 .get(&format!("signature.{signature_id}.algorithm"))
                 .await
-                .map(|algo| algo.and_then(|algo| algo.parse::<Algorithm>().ok())),
+                .map(|algo| algo.and_then(|algo| algo.parse::<DkimAlgorithm>().ok())),
         ) {
             (Ok(Some(pk)), Ok(Some(algorithm))) => (pk, algorithm),
             (Err(err), _) | (_, Err(err)) => return Err(err.caused_by(trc::location!())),
@@ -135,8 +144,8 @@ impl DkimManagement for Server {
             };
 
         let algo_str = match request.algorithm {
-            Algorithm::Rsa => "rsa",
-            Algorithm::Ed25519 => "ed25519",
+            DkimAlgorithm::Rsa => "rsa",
+            DkimAlgorithm::Ed25519 => "ed25519",
         };
         let id = request
             .id
@@ -147,7 +156,7 @@ impl DkimManagement for Server {
                 "{:04}{:02}{}",
                 dt.year,
                 dt.month,
-                if Algorithm::Rsa == request.algorithm {
+                if DkimAlgorithm::Rsa == request.algorithm {
                     "r"
                 } else {
                     "e"
@@ -181,55 +190,26 @@ impl DkimManagement for Server {
 
     async fn create_dkim_key(
         &self,
-        algo: Algorithm,
+        algo: DkimAlgorithm,
         id: impl AsRef<str>,
         domain: impl Into<String>,
         selector: impl Into<String>,
     ) -> trc::Result<()> {
         let id = id.as_ref();
-        let (algorithm, pk_type) = match algo {
-            Algorithm::Rsa => ("rsa-sha256", "RSA PRIVATE KEY"),
-            Algorithm::Ed25519 => ("ed25519-sha256", "PRIVATE KEY"),
-        };
-        let mut pk = format!("-----BEGIN {pk_type}-----\n").into_bytes();
-        let mut lf_count = 65;
-        for ch in base64_encode(
-            match algo {
-                Algorithm::Rsa => DkimKeyPair::generate_rsa(2048),
-                Algorithm::Ed25519 => DkimKeyPair::generate_ed25519(),
-            }
-            .map_err(|err| {
-                manage::error("Failed to generate key", err.to_string().into())
-                    .caused_by(trc::location!())
-            })?
-            .private_key(),
-        )
-        .unwrap_or_default()
-        {
-            pk.push(ch);
-            lf_count -= 1;
-            if lf_count == 0 {
-                pk.push(b'\n');
-                lf_count = 65;
-            }
-        }
-        if lf_count != 65 {
-            pk.push(b'\n');
-        }
-        pk.extend_from_slice(format!("-----END {pk_type}-----\n").as_bytes());
+        let pk = generate_dkim_private_key(algo)?;
 
         self.core
             .storage
             .config
             .set(
                 [
-                    (
-                        format!("signature.{id}.private-key"),
-                        String::from_utf8(pk).unwrap(),
-                    ),
+                    (format!("signature.{id}.private-key"), pk),
                     (format!("signature.{id}.domain"), domain.into()),
                     (format!("signature.{id}.selector"), selector.into()),
-                    (format!("signature.{id}.algorithm"), algorithm.to_string()),
+                    (
+                        format!("signature.{id}.algorithm"),
+                        algo.as_str().to_string(),
+                    ),
                     (
                         format!("signature.{id}.canonicalization"),
                         "relaxed/relaxed".to_string(),
@@ -249,47 +229,3 @@ impl DkimManagement for Server {
             .await
     }
 }
-
-pub fn obtain_dkim_public_key(algo: Algorithm, pk: &str) -> trc::Result<String> {
-    match simple_pem_parse(pk) {
-        Some(der) => match algo {
-            Algorithm::Rsa => match RsaKey::<Sha256>::from_der(&der).and_then(|key| {
-                Document::from_pkcs1_der(&key.public_key())
-                    .map_err(|err| mail_auth::Error::CryptoError(err.to_string()))
-            }) {
-                Ok(pk) => Ok(
-                    String::from_utf8(base64_encode(pk.as_bytes()).unwrap_or_default())
-                        .unwrap_or_default(),
-                ),
-                Err(err) => Err(manage::error(
-                    "Failed to read RSA DER",
-                    err.to_string().into(),
-                )),
-            },
-            Algorithm::Ed25519 => {
-                match Ed25519Key::from_pkcs8_maybe_unchecked_der(&der)
-                    .map_err(|err| mail_auth::Error::CryptoError(err.to_string()))
-                {
-                    Ok(pk) => Ok(String::from_utf8(
-                        base64_encode(&pk.public_key()).unwrap_or_default(),
-                    )
-                    .unwrap_or_default()),
-                    Err(err) => Err(manage::error("Crypto error", err.to_string().into())),
-                }
-            }
-        },
-        None => Err(manage::error("Failed to decode private key", None::<u32>)),
-    }
-}
-
-impl FromStr for Algorithm {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.split_once('-').map(|(algo, _)| algo) {
-            Some("rsa") => Ok(Algorithm::Rsa),
-            Some("ed25519") => Ok(Algorithm::Ed25519),
-            _ => Err(()),
-        }
-    }
-}
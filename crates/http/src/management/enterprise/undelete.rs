@@ -281,6 +281,8 @@ impl UndeleteApi for Server {
                                             received_at: request.time.into(),
                                             source: IngestSource::Restore,
                                             session_id: session.session_id,
+                                            batch: None,
+                                            released_quota: 0,
                                         })
                                         .await
                                     {
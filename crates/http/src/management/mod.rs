@@ -8,6 +8,7 @@ pub mod crypto;
 pub mod dkim;
 pub mod dns;
 pub mod log;
+pub mod pop3;
 pub mod principal;
 pub mod queue;
 pub mod reload;
@@ -16,6 +17,7 @@ pub mod settings;
 pub mod spam;
 pub mod stores;
 pub mod troubleshoot;
+pub mod virtual_mailbox;
 
 // SPDX-SnippetBegin
 // SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
@@ -39,6 +41,7 @@ use jmap::api::{ToJmapHttpResponse, ToRequestError};
 use jmap_proto::error::request::RequestError;
 use log::LogManagement;
 use mail_parser::DateTime;
+use pop3::Pop3PolicyManagement;
 use principal::PrincipalManager;
 use queue::QueueManagement;
 use reload::ManageReload;
@@ -51,6 +54,7 @@ use std::{str::FromStr, sync::Arc};
 use store::write::now;
 use stores::ManageStore;
 use troubleshoot::TroubleshootApi;
+use virtual_mailbox::VirtualMailboxManagement;
 
 #[derive(Serialize)]
 #[serde(tag = "error")]
@@ -162,6 +166,20 @@ impl ManagementApi for Server {
 
                     self.handle_account_auth_post(req, access_token, body).await
                 }
+                ("pop3", _) => {
+                    // Validate the access token
+                    access_token.assert_has_permission(Permission::ManagePop3Policy)?;
+
+                    self.handle_pop3_policy_request(req, path, &access_token, body)
+                        .await
+                }
+                ("virtual-mailboxes", _) => {
+                    // Validate the access token
+                    access_token.assert_has_permission(Permission::ManageVirtualMailboxes)?;
+
+                    self.handle_virtual_mailbox_request(req, path, &access_token, body)
+                        .await
+                }
                 _ => Err(trc::ResourceEvent::NotFound.into_err()),
             },
             "troubleshoot" => {
@@ -0,0 +1,110 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use common::{Server, auth::AccessToken, config::pop3::Pop3Policy};
+use directory::{
+    Permission, Type,
+    backend::internal::manage::{self, ManageDirectory},
+};
+use email::{
+    cache::{MessageCacheFetch, mailbox::MailboxCacheAccess},
+    pop3::Pop3Store,
+};
+use http_proto::{request::decode_path_element, *};
+use hyper::Method;
+use serde_json::json;
+use std::future::Future;
+use trc::AddContext;
+
+pub trait Pop3PolicyManagement: Sync + Send {
+    fn handle_pop3_policy_request(
+        &self,
+        req: &HttpRequest,
+        path: Vec<&str>,
+        access_token: &AccessToken,
+        body: Option<Vec<u8>>,
+    ) -> impl Future<Output = trc::Result<HttpResponse>> + Send;
+}
+
+impl Pop3PolicyManagement for Server {
+    async fn handle_pop3_policy_request(
+        &self,
+        req: &HttpRequest,
+        path: Vec<&str>,
+        access_token: &AccessToken,
+        body: Option<Vec<u8>>,
+    ) -> trc::Result<HttpResponse> {
+        // Administrators can manage the policy of other accounts
+        let account_id = if let Some(name) = path.get(2) {
+            let name = decode_path_element(name);
+            let account_id = self
+                .core
+                .storage
+                .data
+                .get_principal_info(name.as_ref())
+                .await?
+                .filter(|p| {
+                    p.typ == Type::Individual
+                        && p.has_tenant_access(access_token.tenant.map(|t| t.id))
+                })
+                .map(|p| p.id)
+                .ok_or_else(|| manage::not_found(name.to_string()))?;
+
+            if account_id != access_token.primary_id() {
+                access_token.assert_has_permission(if req.method() == Method::GET {
+                    Permission::IndividualGet
+                } else {
+                    Permission::IndividualUpdate
+                })?;
+            }
+
+            account_id
+        } else {
+            access_token.primary_id()
+        };
+
+        match *req.method() {
+            Method::GET => Ok(JsonResponse::new(json!({
+                "data": self.get_pop3_policy(account_id).await?,
+            }))
+            .into_http_response()),
+            Method::POST => {
+                let policy =
+                    serde_json::from_slice::<Pop3Policy>(body.as_deref().unwrap_or_default())
+                        .map_err(|err| trc::ResourceEvent::BadParameters.into_err().reason(err))?;
+
+                // Make sure the mailbox exists
+                if let Some(mailbox) = &policy.mailbox
+                    && self
+                        .get_cached_messages(account_id)
+                        .await
+                        .caused_by(trc::location!())?
+                        .mailbox_by_path(mailbox)
+                        .is_none()
+                {
+                    return Err(manage::not_found(mailbox.clone()));
+                }
+
+                self.set_pop3_policy(account_id, Some(policy)).await?;
+
+                Ok(JsonResponse::new(json!({
+                    "data": (),
+                }))
+                .into_http_response())
+            }
+            Method::DELETE => {
+                // Revert to the server defaults
+                self.set_pop3_policy(account_id, None).await?;
+
+                Ok(JsonResponse::new(json!({
+                    "data": (),
+                }))
+                .into_http_response())
+            }
+            _ => Err(trc::ResourceEvent::NotFound.into_err()),
+        }
+    }
+}
@@ -24,8 +24,10 @@ use mail_parser::DateTime;
 use serde::{Deserializer, Serializer};
 use serde_json::json;
 use smtp::{
+    outbound::adaptive::{reset_throttled_domain, throttled_domains},
     queue::{
-        self, ArchivedMessage, ArchivedStatus, ErrorDetails, QueueId, Status, spool::SmtpSpool,
+        self, ArchivedMessage, ArchivedStatus, ErrorDetails, QueueId, RCPT_HELD, Status,
+        spool::SmtpSpool,
     },
     reporting::{dmarc::DmarcReporting, tls::TlsReporting},
 };
@@ -87,6 +89,10 @@ pub struct Recipient {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub orcpt: Option<String>,
+
+    #[serde(skip_serializing_if = "is_false")]
+    #[serde(default)]
+    pub held: bool,
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
@@ -175,7 +181,9 @@ impl QueueManagement for Server {
                 // Validate the access token
                 access_token.assert_has_permission(Permission::MessageQueueList)?;
 
-                let result = fetch_queued_messages(self, &params, &tenant_domains).await?;
+                let result =
+                    fetch_queued_messages(self, &params, &tenant_domains, params.parse("held"))
+                        .await?;
 
                 let queue_status = self.inner.data.queue_status.load(Ordering::Relaxed);
 
@@ -222,7 +230,9 @@ impl QueueManagement for Server {
                     .parse::<FutureTimestamp>("at")
                     .map(|t| t.into_inner())
                     .unwrap_or_else(now);
-                let result = fetch_queued_messages(self, &params, &tenant_domains).await?;
+                let result =
+                    fetch_queued_messages(self, &params, &tenant_domains, params.parse("held"))
+                        .await?;
 
                 let found = !result.ids.is_empty();
                 if found {
@@ -238,7 +248,8 @@ impl QueueManagement for Server {
                                     if matches!(
                                         recipient.status,
                                         Status::Scheduled | Status::TemporaryFailure(_)
-                                    ) {
+                                    ) && !recipient.is_waiting()
+                                    {
                                         recipient.retry.due = time;
                                         if recipient
                                             .expiration_time(message.message.created)
@@ -291,9 +302,10 @@ impl QueueManagement for Server {
                         if matches!(
                             recipient.status,
                             Status::Scheduled | Status::TemporaryFailure(_)
-                        ) && item
-                            .as_ref()
-                            .is_none_or(|item| recipient.address().contains(item))
+                        ) && !recipient.is_waiting()
+                            && item
+                                .as_ref()
+                                .is_none_or(|item| recipient.address().contains(item))
                         {
                             recipient.retry.due = time;
                             if recipient
@@ -324,7 +336,9 @@ impl QueueManagement for Server {
                 // Validate the access token
                 access_token.assert_has_permission(Permission::MessageQueueDelete)?;
 
-                let result = fetch_queued_messages(self, &params, &tenant_domains).await?;
+                let result =
+                    fetch_queued_messages(self, &params, &tenant_domains, params.parse("held"))
+                        .await?;
 
                 let found = !result.ids.is_empty();
                 if found {
@@ -416,6 +430,133 @@ impl QueueManagement for Server {
                     Err(trc::ResourceEvent::NotFound.into_err())
                 }
             }
+            ("held", None, &Method::GET) => {
+                // Validate the access token
+                access_token.assert_has_permission(Permission::MessageQueueList)?;
+
+                let result =
+                    fetch_queued_messages(self, &params, &tenant_domains, true.into()).await?;
+
+                Ok(if !result.values.is_empty() {
+                    JsonResponse::new(json!({
+                            "data":{
+                                "items": result.values,
+                                "total": result.total,
+                            },
+                    }))
+                } else {
+                    JsonResponse::new(json!({
+                            "data": {
+                                "items": result.ids,
+                                "total":  result.total,
+                            },
+                    }))
+                }
+                .into_http_response())
+            }
+            ("held", Some(queue_id), &Method::POST) => {
+                // Validate the access token
+                access_token.assert_has_permission(Permission::MessageQueueUpdate)?;
+
+                if let Some(message) = self
+                    .read_message(queue_id.parse().unwrap_or_default(), QueueName::default())
+                    .await
+                    .filter(|message| {
+                        tenant_domains
+                            .as_ref()
+                            .is_none_or(|domains| message.has_domain(domains))
+                    })
+                {
+                    let reason = params.get("reason").unwrap_or("Held by administrator");
+
+                    Ok(JsonResponse::new(json!({
+                            "data": message.hold(self, reason).await,
+                    }))
+                    .into_http_response())
+                } else {
+                    Err(trc::ResourceEvent::NotFound.into_err())
+                }
+            }
+            ("held", None, &Method::PATCH | &Method::DELETE) => {
+                // Validate the access token
+                let is_release = req.method() == Method::PATCH;
+                access_token.assert_has_permission(if is_release {
+                    Permission::MessageQueueUpdate
+                } else {
+                    Permission::MessageQueueDelete
+                })?;
+
+                let result =
+                    fetch_queued_messages(self, &params, &tenant_domains, true.into()).await?;
+                let reason = params
+                    .get("reason")
+                    .unwrap_or("Message rejected by administrator")
+                    .to_string();
+
+                let found = !result.ids.is_empty();
+                if found {
+                    let server = self.clone();
+                    tokio::spawn(async move {
+                        for id in result.ids {
+                            if let Some(message) =
+                                server.read_message(id, QueueName::default()).await
+                            {
+                                if is_release {
+                                    message.release(&server, None).await;
+                                } else {
+                                    message.reject(&server, None, &reason).await;
+                                }
+                            }
+                        }
+                    });
+                }
+
+                Ok(JsonResponse::new(json!({
+                        "data": found,
+                }))
+                .into_http_response())
+            }
+            ("held", Some(queue_id), &Method::PATCH | &Method::DELETE) => {
+                // Validate the access token
+                let is_release = req.method() == Method::PATCH;
+                access_token.assert_has_permission(if is_release {
+                    Permission::MessageQueueUpdate
+                } else {
+                    Permission::MessageQueueDelete
+                })?;
+
+                if let Some(message) = self
+                    .read_message(queue_id.parse().unwrap_or_default(), QueueName::default())
+                    .await
+                    .filter(|message| {
+                        tenant_domains
+                            .as_ref()
+                            .is_none_or(|domains| message.has_domain(domains))
+                    })
+                {
+                    let filter = params.get("filter");
+                    let found = if is_release {
+                        message.release(self, filter).await
+                    } else {
+                        message
+                            .reject(
+                                self,
+                                filter,
+                                params
+                                    .get("reason")
+                                    .unwrap_or("Message rejected by administrator"),
+                            )
+                            .await
+                    };
+
+                    Ok(JsonResponse::new(json!({
+                            "data": found,
+                    }))
+                    .into_http_response())
+                } else {
+                    Err(trc::ResourceEvent::NotFound.into_err())
+                }
+            }
             ("reports", None, &Method::GET) => {
                 // Validate the access token
                 access_token.assert_has_permission(Permission::OutgoingReportList)?;
@@ -569,6 +710,34 @@ impl QueueManagement for Server {
                 }))
                 .into_http_response())
             }
+            ("throttle", None, &Method::GET) => {
+                // Validate the access token
+                access_token.assert_has_permission(Permission::MessageQueueGet)?;
+
+                let config = self.core.smtp.queue.adaptive_throttle.as_ref();
+                Ok(JsonResponse::new(json!({
+                        "data": {
+                            "enabled": config.is_some(),
+                            "domains": config
+                                .map(|config| throttled_domains(self, config))
+                                .unwrap_or_default(),
+                        },
+                }))
+                .into_http_response())
+            }
+            ("throttle", Some(domain), &Method::DELETE) => {
+                // Validate the access token
+                access_token.assert_has_permission(Permission::MessageQueueUpdate)?;
+
+                if reset_throttled_domain(self, &domain.to_lowercase()) {
+                    Ok(JsonResponse::new(json!({
+                            "data": true,
+                    }))
+                    .into_http_response())
+                } else {
+                    Err(trc::ResourceEvent::NotFound.into_err())
+                }
+            }
             _ => Err(trc::ResourceEvent::NotFound.into_err()),
         }
     }
@@ -616,6 +785,7 @@ impl Message {
                         None
                     },
                     orcpt: rcpt.orcpt.as_ref().map(|orcpt| orcpt.to_string()),
+                    held: rcpt.flags.to_native() & RCPT_HELD != 0,
                 })
                 .collect(),
 
@@ -634,6 +804,7 @@ async fn fetch_queued_messages(
     server: &Server,
     params: &UrlParams<'_>,
     tenant_domains: &Option<Vec<String>>,
+    held: Option<bool>,
 ) -> trc::Result<QueuedMessages> {
     let queue = params.get("queue").and_then(QueueName::new);
     let text = params.get("text");
@@ -665,7 +836,8 @@ async fn fetch_queued_messages(
         || to.is_some()
         || before.is_some()
         || after.is_some()
-        || queue.is_some();
+        || queue.is_some()
+        || held.is_some();
     let mut offset = page.saturating_sub(1) * limit;
     let mut total_returned = 0;
 
@@ -713,7 +885,14 @@ async fn fetch_queued_messages(
                             })
                             && queue
                                 .as_ref()
-                                .is_none_or(|q| message.recipients.iter().any(|r| &r.queue == q))));
+                                .is_none_or(|q| message.recipients.iter().any(|r| &r.queue == q))
+                            && held.is_none_or(|held| {
+                                message
+                                    .recipients
+                                    .iter()
+                                    .any(|r| r.flags.to_native() & RCPT_HELD != 0)
+                                    == held
+                            })));
 
                 if matches {
                     if offset == 0 {
@@ -938,6 +1117,10 @@ fn is_zero(num: &i16) -> bool {
     *num == 0
 }
 
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
 trait IsTenantDomain {
     fn is_tenant_domain(&self, tenant_domains: &Option<Vec<String>>) -> bool;
 }
@@ -76,7 +76,7 @@ impl ManageReload for Server {
                 if !UrlParams::new(req.uri().query()).has_key("dry-run") {
                     if let Some(core) = result.new_core {
                         // Update core
-                        self.inner.shared_core.store(core.into());
+                        self.inner.replace_core(core);
 
                         self.cluster_broadcast(BroadcastEvent::ReloadSettings).await;
                     }
@@ -0,0 +1,175 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use common::{Server, auth::AccessToken};
+use directory::backend::internal::manage;
+use email::{
+    cache::MessageCacheFetch,
+    mailbox::virtual_mailbox::{VirtualMailboxStore, validate_virtual_query},
+};
+use http_proto::*;
+use hyper::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::future::Future;
+use trc::AddContext;
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct VirtualMailboxRequest {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub query: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VirtualMailboxResponse {
+    pub id: u32,
+    pub name: String,
+    pub query: String,
+}
+
+pub trait VirtualMailboxManagement: Sync + Send {
+    fn handle_virtual_mailbox_request(
+        &self,
+        req: &HttpRequest,
+        path: Vec<&str>,
+        access_token: &AccessToken,
+        body: Option<Vec<u8>>,
+    ) -> impl Future<Output = trc::Result<HttpResponse>> + Send;
+}
+
+impl VirtualMailboxManagement for Server {
+    async fn handle_virtual_mailbox_request(
+        &self,
+        req: &HttpRequest,
+        path: Vec<&str>,
+        access_token: &AccessToken,
+        body: Option<Vec<u8>>,
+    ) -> trc::Result<HttpResponse> {
+        let account_id = access_token.primary_id();
+        let (mut mailboxes, current) = self.get_virtual_mailboxes_for_update(account_id).await?;
+        let id = path
+            .get(2)
+            .map(|id| {
+                id.parse::<u32>().map_err(|_| {
+                    trc::ResourceEvent::BadParameters
+                        .into_err()
+                        .details(id.to_string())
+                })
+            })
+            .transpose()?;
+
+        match (id, req.method()) {
+            (None, &Method::GET) => Ok(JsonResponse::new(json!({
+                "data": mailboxes
+                    .items
+                    .iter()
+                    .map(|m| VirtualMailboxResponse {
+                        id: m.id,
+                        name: m.name.clone(),
+                        query: m.query.clone(),
+                    })
+                    .collect::<Vec<_>>(),
+            }))
+            .into_http_response()),
+            (Some(id), &Method::GET) => {
+                let mailbox = mailboxes.get(id).ok_or_else(|| manage::not_found(id))?;
+
+                Ok(JsonResponse::new(json!({
+                    "data": VirtualMailboxResponse {
+                        id: mailbox.id,
+                        name: mailbox.name.clone(),
+                        query: mailbox.query.clone(),
+                    },
+                }))
+                .into_http_response())
+            }
+            (None, &Method::POST) => {
+                if mailboxes.items.len() >= self.core.jmap.mailbox_max_virtual {
+                    return Err(manage::error(
+                        "Virtual mailbox limit reached",
+                        "Delete some virtual mailboxes before adding a new one.".into(),
+                    ));
+                }
+                let request = parse_request(body)?;
+                let name = request.name.ok_or_else(|| manage::err_missing("name"))?;
+                let query = request.query.ok_or_else(|| manage::err_missing("query"))?;
+                validate_virtual_query(&query).map_err(|err| manage::error(err, None::<u32>))?;
+                let cache = self
+                    .get_cached_messages(account_id)
+                    .await
+                    .caused_by(trc::location!())?;
+                mailboxes
+                    .validate_name(&name, None, &cache, self.core.jmap.mailbox_name_max_len)
+                    .map_err(|err| manage::error(err, None::<u32>))?;
+
+                let id = mailboxes.insert(name, query);
+                self.set_virtual_mailboxes(account_id, current, mailboxes)
+                    .await
+                    .caused_by(trc::location!())?;
+
+                Ok(JsonResponse::new(json!({
+                    "data": id,
+                }))
+                .into_http_response())
+            }
+            (Some(id), &Method::PATCH) => {
+                let request = parse_request(body)?;
+                if mailboxes.get(id).is_none() {
+                    return Err(manage::not_found(id));
+                }
+                if let Some(query) = &request.query {
+                    validate_virtual_query(query).map_err(|err| manage::error(err, None::<u32>))?;
+                }
+                if let Some(name) = &request.name {
+                    let cache = self
+                        .get_cached_messages(account_id)
+                        .await
+                        .caused_by(trc::location!())?;
+                    mailboxes
+                        .validate_name(name, Some(id), &cache, self.core.jmap.mailbox_name_max_len)
+                        .map_err(|err| manage::error(err, None::<u32>))?;
+                }
+
+                let mailbox = mailboxes.get_mut(id).unwrap();
+                if let Some(name) = request.name {
+                    mailbox.name = name;
+                }
+                if let Some(query) = request.query {
+                    mailbox.set_query(query);
+                }
+                self.set_virtual_mailboxes(account_id, current, mailboxes)
+                    .await
+                    .caused_by(trc::location!())?;
+
+                Ok(JsonResponse::new(json!({
+                    "data": (),
+                }))
+                .into_http_response())
+            }
+            (Some(id), &Method::DELETE) => {
+                if !mailboxes.remove(id) {
+                    return Err(manage::not_found(id));
+                }
+                self.set_virtual_mailboxes(account_id, current, mailboxes)
+                    .await
+                    .caused_by(trc::location!())?;
+
+                Ok(JsonResponse::new(json!({
+                    "data": (),
+                }))
+                .into_http_response())
+            }
+            _ => Err(trc::ResourceEvent::NotFound.into_err()),
+        }
+    }
+}
+
+fn parse_request(body: Option<Vec<u8>>) -> trc::Result<VirtualMailboxRequest> {
+    serde_json::from_slice::<VirtualMailboxRequest>(body.as_deref().unwrap_or_default())
+        .map_err(|err| trc::ResourceEvent::BadParameters.into_err().reason(err))
+}
@@ -19,6 +19,7 @@ use crate::{
         ManagementApi, ToManageHttpResponse, UnauthorizedResponse, troubleshoot::TroubleshootApi,
     },
 };
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
 use common::{
     Inner, KV_ACME, Server,
     auth::{AccessToken, oauth::GrantType},
@@ -53,7 +54,7 @@ use jmap_proto::request::{Request, capability::Session};
 use std::{net::IpAddr, str::FromStr, sync::Arc};
 use store::dispatch::lookup::KeyValue;
 use trc::SecurityEvent;
-use types::{blob::BlobId, id::Id};
+use types::{blob::BlobId, blob_hash::BlobHash, id::Id};
 use utils::url_params::UrlParams;
 
 pub trait ParseHttp: Sync + Send {
@@ -504,6 +505,35 @@ impl ParseHttp for Server {
                         });
                 }
             }
+            "webhook" => {
+                if req.method() == Method::GET && path.next().unwrap_or_default() == "blob" {
+                    // Limit anonymous requests
+                    self.is_http_anonymous_request_allowed(&session.remote_ip)
+                        .await?;
+
+                    // Attachments linked from HTTP queue route payloads
+                    let params = UrlParams::new(req.uri().query());
+                    if let Some(token) = params.get("i")
+                        && let Ok(token) = self
+                            .validate_access_token(GrantType::Blob.into(), token)
+                            .await
+                        && let Some(hash) = URL_SAFE_NO_PAD
+                            .decode(token.client_id.as_bytes())
+                            .ok()
+                            .and_then(|hash| BlobHash::try_from_hash_slice(&hash).ok())
+                        && let Some(contents) = self
+                            .blob_store()
+                            .get_blob(hash.as_slice(), 0..usize::MAX)
+                            .await?
+                    {
+                        return Ok(Resource::new("application/octet-stream", contents)
+                            .into_http_response()
+                            .with_no_store());
+                    }
+
+                    return Err(trc::ResourceEvent::NotFound.into_err());
+                }
+            }
             "autodiscover" | "Autodiscover" => {
                 if req.method() == Method::POST
                     && path
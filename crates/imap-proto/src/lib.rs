@@ -74,6 +74,24 @@ pub enum Command {
     // RFC 9208
     GetQuota,
     GetQuotaRoot,
+
+    // RFC 4978
+    Compress,
+
+    // RFC 5464
+    GetMetadata,
+    SetMetadata,
+
+    // RFC 5465
+    Notify,
+
+    // RFC 8508
+    Replace(bool),
+
+    // RFC 4467
+    GenUrlAuth,
+    ResetKey,
+    UrlFetch,
 }
 
 impl Command {
@@ -88,6 +106,7 @@ impl Command {
                 | Command::Expunge(true)
                 | Command::Sort(true)
                 | Command::Thread(true)
+                | Command::Replace(true)
         )
     }
 }
@@ -152,6 +171,33 @@ pub enum ResponseCode {
 
     // USEATTR
     UseAttr,
+
+    // COMPRESS
+    CompressionActive,
+
+    // METADATA
+    MetadataLongEntries {
+        size: usize,
+    },
+    MetadataMaxSize {
+        size: usize,
+    },
+    MetadataTooMany,
+    MetadataNoPrivate,
+
+    // NOTIFY
+    BadEvent {
+        events: Vec<protocol::notify::Event>,
+    },
+    NotificationOverflow,
+
+    // CATENATE
+    BadUrl {
+        url: String,
+    },
+    TooBig,
+    // UIDONLY
+    UidRequired,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -9,20 +9,22 @@ use compact_str::ToCompactString;
 use crate::{
     Command,
     protocol::{
-        Flag,
-        append::{self, Message},
+        Flag, Sequence,
+        append::{self, CatenatePart, Message},
     },
     receiver::{Request, Token, bad},
     utf7::utf7_maybe_decode,
 };
 
-use super::parse_datetime;
+use super::{parse_datetime, parse_sequence_set};
 
 enum State {
     None,
     Flags,
     UTF8,
     UTF8Data,
+    Catenate,
+    CatenateData,
 }
 
 impl Request<Command> {
@@ -48,6 +50,7 @@ impl Request<Command> {
                         message: vec![],
                         flags: vec![],
                         received_at: None,
+                        catenate: vec![],
                     };
                     let mut state = State::None;
                     let mut seen_flags = false;
@@ -61,6 +64,7 @@ impl Request<Command> {
                                         State::Flags
                                     }
                                     State::UTF8 => State::UTF8Data,
+                                    State::Catenate => State::CatenateData,
                                     _ => {
                                         return Err(bad(
                                             self.tag.to_compact_string(),
@@ -70,7 +74,7 @@ impl Request<Command> {
                                 };
                             }
                             Token::ParenthesisClose => match state {
-                                State::None | State::UTF8 => {
+                                State::None | State::UTF8 | State::Catenate => {
                                     return Err(bad(
                                         self.tag.to_compact_string(),
                                         "Invalid closing parenthesis found.",
@@ -82,11 +86,23 @@ impl Request<Command> {
                                 State::UTF8Data => {
                                     break;
                                 }
+                                State::CatenateData => {
+                                    if !message.catenate.is_empty() {
+                                        break;
+                                    } else {
+                                        return Err(bad(
+                                            self.tag.to_compact_string(),
+                                            "Expected at least one CATENATE part.",
+                                        ));
+                                    }
+                                }
                             },
                             Token::Argument(value) => match state {
                                 State::None => {
                                     if value.eq_ignore_ascii_case(b"utf8") {
                                         state = State::UTF8;
+                                    } else if value.eq_ignore_ascii_case(b"catenate") {
+                                        state = State::Catenate;
                                     } else if matches!(tokens.peek(), Some(Token::Argument(_)))
                                         && value.len() <= 28
                                         && !value.contains(&b'\n')
@@ -116,6 +132,34 @@ impl Request<Command> {
                                         "Expected parenthesis after UTF8.",
                                     ));
                                 }
+                                State::Catenate => {
+                                    return Err(bad(
+                                        self.tag.to_compact_string(),
+                                        "Expected parenthesis after CATENATE.",
+                                    ));
+                                }
+                                State::CatenateData => {
+                                    let part = if value.eq_ignore_ascii_case(b"url") {
+                                        match tokens.next() {
+                                            Some(token @ (Token::Argument(_) | Token::Nil)) => {
+                                                token.unwrap_string().map(CatenatePart::Url)
+                                            }
+                                            _ => Err("Expected URL.".into()),
+                                        }
+                                    } else if value.eq_ignore_ascii_case(b"text") {
+                                        match tokens.next() {
+                                            Some(Token::Argument(value)) => {
+                                                Ok(CatenatePart::Text(value))
+                                            }
+                                            _ => Err("Expected literal after TEXT.".into()),
+                                        }
+                                    } else {
+                                        Err("Expected URL or TEXT.".into())
+                                    };
+                                    message.catenate.push(
+                                        part.map_err(|v| bad(self.tag.to_compact_string(), v))?,
+                                    );
+                                }
                                 State::UTF8Data => {
                                     if message.message.is_empty() {
                                         message.message = value;
@@ -147,6 +191,45 @@ impl Request<Command> {
             }
         }
     }
+
+    pub fn parse_replace(mut self, is_utf8: bool) -> trc::Result<append::ReplaceArguments> {
+        if self.tokens.len() < 3 {
+            return Err(self.into_error("Missing arguments."));
+        }
+
+        // Obtain message to replace
+        let sequence = parse_sequence_set(&self.tokens.remove(0).unwrap_bytes())
+            .map_err(|v| bad(self.tag.to_compact_string(), v))?;
+        if !matches!(
+            sequence,
+            Sequence::Number { .. }
+                | Sequence::Range {
+                    start: None,
+                    end: None
+                }
+        ) {
+            return Err(bad(
+                self.tag.to_compact_string(),
+                "Expected a single message number.",
+            ));
+        }
+
+        // Parse replacement message
+        let arguments = self.parse_append(is_utf8)?;
+        if arguments.messages.len() == 1 {
+            Ok(append::ReplaceArguments {
+                tag: arguments.tag,
+                sequence,
+                mailbox_name: arguments.mailbox_name,
+                message: arguments.messages.into_iter().next().unwrap(),
+            })
+        } else {
+            Err(bad(
+                arguments.tag.to_compact_string(),
+                "Only one message can be replaced at a time.",
+            ))
+        }
+    }
 }
 
 #[cfg(test)]
@@ -154,8 +237,8 @@ mod tests {
 
     use crate::{
         protocol::{
-            Flag,
-            append::{self, Message},
+            Flag, Sequence,
+            append::{self, CatenatePart, Message},
         },
         receiver::{Error, Receiver},
     };
@@ -174,6 +257,7 @@ mod tests {
                         message: vec![b'a'],
                         flags: vec![Flag::Seen],
                         received_at: None,
+                        catenate: vec![],
                     }],
                 },
             ),
@@ -186,6 +270,7 @@ mod tests {
                         message: vec![b'a'],
                         flags: vec![Flag::Seen, Flag::Draft, Flag::MDNSent],
                         received_at: None,
+                        catenate: vec![],
                     }],
                 },
             ),
@@ -198,6 +283,7 @@ mod tests {
                         message: vec![b'a'],
                         flags: vec![Flag::Junk],
                         received_at: Some(760689784),
+                        catenate: vec![],
                     }],
                 },
             ),
@@ -210,6 +296,7 @@ mod tests {
                         message: vec![b'a'],
                         flags: vec![],
                         received_at: Some(1668977999),
+                        catenate: vec![],
                     }],
                 },
             ),
@@ -222,6 +309,7 @@ mod tests {
                         message: vec![b'a'],
                         flags: vec![],
                         received_at: Some(1668977999),
+                        catenate: vec![],
                     }],
                 },
             ),
@@ -234,6 +322,7 @@ mod tests {
                         message: vec![b'h', b'e', b'l', b'l', b'o'],
                         flags: vec![Flag::Draft],
                         received_at: None,
+                        catenate: vec![],
                     }],
                 },
             ),
@@ -246,6 +335,7 @@ mod tests {
                         message: vec![b'h', b'e', b'l', b'l', b'o'],
                         flags: vec![Flag::Draft],
                         received_at: Some(1668977999),
+                        catenate: vec![],
                     }],
                 },
             ),
@@ -258,6 +348,32 @@ mod tests {
                         message: vec![b'a'],
                         flags: vec![Flag::Seen],
                         received_at: Some(760689784),
+                        catenate: vec![],
+                    }],
+                },
+            ),
+            (
+                concat!(
+                    "A004 APPEND Drafts (\\Seen \\Draft) CATENATE (URL ",
+                    "\"/Drafts;UIDVALIDITY=385759045/;UID=20/;section=HEADER\" ",
+                    "TEXT {7+}\r\n\r\nhello URL \"/Drafts;UIDVALIDITY=385759045/;UID=20/;section=1\")\r\n"
+                ),
+                append::Arguments {
+                    tag: "A004".into(),
+                    mailbox_name: "Drafts".into(),
+                    messages: vec![Message {
+                        message: vec![],
+                        flags: vec![Flag::Seen, Flag::Draft],
+                        received_at: None,
+                        catenate: vec![
+                            CatenatePart::Url(
+                                "/Drafts;UIDVALIDITY=385759045/;UID=20/;section=HEADER".into(),
+                            ),
+                            CatenatePart::Text(b"\r\nhello".to_vec()),
+                            CatenatePart::Url(
+                                "/Drafts;UIDVALIDITY=385759045/;UID=20/;section=1".into(),
+                            ),
+                        ],
                     }],
                 },
             ),
@@ -320,6 +436,7 @@ mod tests {
                                     .to_vec(),
                                     flags: vec![Flag::Seen],
                                     received_at: None,
+                                    catenate: vec![],
                                 },
                                 Message {
                                     message: concat!(
@@ -336,6 +453,7 @@ mod tests {
                                     .to_vec(),
                                     flags: vec![Flag::Seen],
                                     received_at: Some(760689784),
+                                    catenate: vec![],
                                 }
                             ],
                         },
@@ -347,5 +465,78 @@ mod tests {
                 },
             }
         }
+        for command in [
+            "A005 APPEND Drafts CATENATE ()\r\n",
+            "A006 APPEND Drafts CATENATE (URL)\r\n",
+            "A007 APPEND Drafts CATENATE (FILE \"/INBOX/;UID=1\")\r\n",
+        ] {
+            assert!(
+                receiver
+                    .parse(&mut command.as_bytes().iter())
+                    .unwrap()
+                    .parse_append(false)
+                    .is_err(),
+                "{command}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_replace() {
+        let mut receiver = Receiver::new();
+
+        for (command, arguments) in [
+            (
+                "A003 REPLACE 4 Drafts (\\Seen \\Draft) {1+}\r\na\r\n",
+                append::ReplaceArguments {
+                    tag: "A003".into(),
+                    sequence: Sequence::number(4),
+                    mailbox_name: "Drafts".into(),
+                    message: Message {
+                        message: vec![b'a'],
+                        flags: vec![Flag::Seen, Flag::Draft],
+                        received_at: None,
+                        catenate: vec![],
+                    },
+                },
+            ),
+            (
+                "A004 UID REPLACE * \"Other Drafts\" {1+}\r\nb\r\n",
+                append::ReplaceArguments {
+                    tag: "A004".into(),
+                    sequence: Sequence::range(None, None),
+                    mailbox_name: "Other Drafts".into(),
+                    message: Message {
+                        message: vec![b'b'],
+                        flags: vec![],
+                        received_at: None,
+                        catenate: vec![],
+                    },
+                },
+            ),
+        ] {
+            assert_eq!(
+                receiver
+                    .parse(&mut command.as_bytes().iter())
+                    .unwrap()
+                    .parse_replace(true)
+                    .unwrap(),
+                arguments
+            );
+        }
+
+        for command in [
+            "A005 REPLACE 1:3 Drafts {1+}\r\na\r\n",
+            "A006 REPLACE 1 Drafts {1+}\r\na {1+}\r\nb\r\n",
+        ] {
+            assert!(
+                receiver
+                    .parse(&mut command.as_bytes().iter())
+                    .unwrap()
+                    .parse_replace(true)
+                    .is_err(),
+                "{command}"
+            );
+        }
     }
 }
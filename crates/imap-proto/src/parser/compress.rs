@@ -0,0 +1,91 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use compact_str::ToCompactString;
+
+use crate::{
+    Command,
+    protocol::compress::{self, CompressionAlgorithm},
+    receiver::{Request, bad},
+};
+
+impl Request<Command> {
+    pub fn parse_compress(self) -> trc::Result<compress::Arguments> {
+        match self.tokens.len() {
+            1 => Ok(compress::Arguments {
+                algorithm: CompressionAlgorithm::parse(
+                    &self.tokens.into_iter().next().unwrap().unwrap_bytes(),
+                )
+                .map_err(|v| bad(self.tag.to_compact_string(), v))?,
+                tag: self.tag,
+            }),
+            0 => Err(self.into_error("Missing compression algorithm.")),
+            _ => Err(self.into_error("Too many arguments.")),
+        }
+    }
+}
+
+impl CompressionAlgorithm {
+    pub fn parse(value: &[u8]) -> super::Result<Self> {
+        hashify::tiny_map_ignore_case!(value,
+            "DEFLATE" => Self::Deflate,
+        )
+        .ok_or_else(|| {
+            format!(
+                "Unsupported compression algorithm '{}'.",
+                String::from_utf8_lossy(value)
+            )
+            .into()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        protocol::compress::{self, CompressionAlgorithm},
+        receiver::Receiver,
+    };
+
+    #[test]
+    fn parse_compress() {
+        let mut receiver = Receiver::new();
+
+        for (command, arguments) in [
+            (
+                "a001 COMPRESS DEFLATE\r\n",
+                compress::Arguments {
+                    tag: "a001".into(),
+                    algorithm: CompressionAlgorithm::Deflate,
+                },
+            ),
+            (
+                "a002 COMPRESS deflate\r\n",
+                compress::Arguments {
+                    tag: "a002".into(),
+                    algorithm: CompressionAlgorithm::Deflate,
+                },
+            ),
+        ] {
+            assert_eq!(
+                receiver
+                    .parse(&mut command.as_bytes().iter())
+                    .unwrap()
+                    .parse_compress()
+                    .unwrap(),
+                arguments
+            );
+        }
+
+        assert!(
+            receiver
+                .parse(&mut "a003 COMPRESS LZ4\r\n".as_bytes().iter())
+                .unwrap()
+                .parse_compress()
+                .is_err()
+        );
+    }
+}
@@ -42,6 +42,7 @@ impl Capability {
             "CONDSTORE" => Self::CondStore,
             "QRESYNC" => Self::QResync,
             "UTF8=ACCEPT" => Self::Utf8Accept,
+            "UIDONLY" => Self::UidOnly,
         )
         .ok_or_else(|| {
             format!(
@@ -66,13 +67,21 @@ mod tests {
 
         assert_eq!(
             receiver
-                .parse(&mut "t2 ENABLE IMAP4rev2 CONDSTORE\r\n".as_bytes().iter())
+                .parse(
+                    &mut "t2 ENABLE IMAP4rev2 CONDSTORE UIDONLY\r\n"
+                        .as_bytes()
+                        .iter()
+                )
                 .unwrap()
                 .parse_enable()
                 .unwrap(),
             enable::Arguments {
                 tag: "t2".into(),
-                capabilities: vec![Capability::IMAP4rev2, Capability::CondStore],
+                capabilities: vec![
+                    Capability::IMAP4rev2,
+                    Capability::CondStore,
+                    Capability::UidOnly
+                ],
             }
         );
     }
@@ -16,7 +16,7 @@ use crate::{
     receiver::{Request, Token, bad},
 };
 
-use super::{PushUnique, parse_number, parse_sequence_set};
+use super::{PushUnique, parse_number, parse_partial_range, parse_sequence_set};
 
 impl Request<Command> {
     #[allow(clippy::while_let_on_iterator)]
@@ -25,6 +25,7 @@ impl Request<Command> {
             return Err(self.into_error("Missing parameters."));
         }
 
+        let is_uid = matches!(self.command, Command::Fetch(true));
         let mut tokens = self.tokens.into_iter().peekable();
         let mut attributes = Vec::new();
         let sequence_set = parse_sequence_set(
@@ -336,6 +337,9 @@ impl Request<Command> {
                         "THREADID" => {
                             attributes.push_unique(Attribute::ThreadId);
                         },
+                        "SAVEDATE" => {
+                            attributes.push_unique(Attribute::SaveDate);
+                        },
                         _ => {
                             return Err(bad(
                                 CompactString::from_string_buffer(self.tag),
@@ -377,9 +381,10 @@ impl Request<Command> {
             }
         }
 
-        // CONDSTORE parameters
+        // Fetch modifiers
         let mut changed_since = None;
         let mut include_vanished = false;
+        let mut partial = None;
         if let Some(Token::ParenthesisOpen) = tokens.peek() {
             tokens.next();
             while let Some(token) = tokens.next() {
@@ -402,6 +407,25 @@ impl Request<Command> {
                     Token::Argument(param) if param.eq_ignore_ascii_case(b"VANISHED") => {
                         include_vanished = true;
                     }
+                    Token::Argument(param) if param.eq_ignore_ascii_case(b"PARTIAL") => {
+                        // The PARTIAL modifier is only valid in UID FETCH (RFC 9394)
+                        if !is_uid {
+                            return Err(bad(
+                                self.tag.to_compact_string(),
+                                "PARTIAL requires UID FETCH.",
+                            ));
+                        }
+                        partial = parse_partial_range(
+                            &tokens
+                                .next()
+                                .ok_or_else(|| {
+                                    bad(self.tag.to_compact_string(), "Missing PARTIAL range.")
+                                })?
+                                .unwrap_bytes(),
+                        )
+                        .map_err(|v| bad(self.tag.to_compact_string(), v))?
+                        .into();
+                    }
                     Token::ParenthesisClose => {
                         break;
                     }
@@ -422,6 +446,7 @@ impl Request<Command> {
                 attributes,
                 changed_since,
                 include_vanished,
+                partial,
             })
         } else {
             Err(bad(
@@ -513,7 +538,7 @@ pub fn parse_partial(tokens: &mut Peekable<IntoIter<Token>>) -> super::Result<Op
 mod tests {
     use crate::{
         protocol::{
-            Sequence,
+            PartialRange, Sequence,
             fetch::{self, Attribute, Section},
         },
         receiver::Receiver,
@@ -542,6 +567,7 @@ mod tests {
                     ],
                     changed_since: None,
                     include_vanished: false,
+                    partial: None,
                 },
             ),
             (
@@ -556,6 +582,7 @@ mod tests {
                     }],
                     changed_since: None,
                     include_vanished: false,
+                    partial: None,
                 },
             ),
             (
@@ -570,6 +597,7 @@ mod tests {
                     }],
                     changed_since: None,
                     include_vanished: false,
+                    partial: None,
                 },
             ),
             (
@@ -590,6 +618,7 @@ mod tests {
                     ],
                     changed_since: None,
                     include_vanished: false,
+                    partial: None,
                 },
             ),
             (
@@ -607,6 +636,7 @@ mod tests {
                     }],
                     changed_since: None,
                     include_vanished: false,
+                    partial: None,
                 },
             ),
             (
@@ -629,6 +659,7 @@ mod tests {
                     ],
                     changed_since: None,
                     include_vanished: false,
+                    partial: None,
                 },
             ),
             (
@@ -645,6 +676,7 @@ mod tests {
                     ],
                     changed_since: None,
                     include_vanished: false,
+                    partial: None,
                 },
             ),
             (
@@ -660,6 +692,7 @@ mod tests {
                     ],
                     changed_since: None,
                     include_vanished: false,
+                    partial: None,
                 },
             ),
             (
@@ -733,6 +766,7 @@ mod tests {
                     ],
                     changed_since: None,
                     include_vanished: false,
+                    partial: None,
                 },
             ),
             (
@@ -748,6 +782,7 @@ mod tests {
                     ],
                     changed_since: None,
                     include_vanished: false,
+                    partial: None,
                 },
             ),
             (
@@ -764,6 +799,7 @@ mod tests {
                     ],
                     changed_since: None,
                     include_vanished: false,
+                    partial: None,
                 },
             ),
             (
@@ -778,6 +814,7 @@ mod tests {
                     ],
                     changed_since: None,
                     include_vanished: false,
+                    partial: None,
                 },
             ),
             (
@@ -788,6 +825,7 @@ mod tests {
                     attributes: vec![Attribute::Flags, Attribute::ModSeq],
                     changed_since: 12345.into(),
                     include_vanished: true,
+                    partial: None,
                 },
             ),
             (
@@ -798,6 +836,22 @@ mod tests {
                     attributes: vec![Attribute::Uid],
                     changed_since: 1.into(),
                     include_vanished: true,
+                    partial: None,
+                },
+            ),
+            (
+                "10 UID FETCH 1:* (FLAGS) (PARTIAL -1:-30)\r\n",
+                fetch::Arguments {
+                    tag: "10".into(),
+                    sequence_set: Sequence::range(1.into(), None),
+                    attributes: vec![Attribute::Flags],
+                    changed_since: None,
+                    include_vanished: false,
+                    partial: Some(PartialRange {
+                        start: 1,
+                        end: 30,
+                        from_end: true,
+                    }),
                 },
             ),
         ] {
@@ -812,5 +866,14 @@ mod tests {
                 command
             );
         }
+
+        // PARTIAL is not allowed in plain FETCH
+        assert!(
+            receiver
+                .parse(&mut "11 FETCH 1:* (FLAGS) (PARTIAL 1:10)\r\n".as_bytes().iter())
+                .unwrap()
+                .parse_fetch()
+                .is_err()
+        );
     }
 }
@@ -0,0 +1,337 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use compact_str::ToCompactString;
+
+use crate::{
+    Command,
+    protocol::metadata::{self, Depth},
+    receiver::{Request, Token, bad},
+    utf7::utf7_maybe_decode,
+};
+
+use super::parse_number;
+
+/*
+
+   getmetadata     = "GETMETADATA" [SP getmetadata-options]
+                     SP mailbox SP getmetadata-entries
+
+   getmetadata-options = "(" getmetadata-option
+                         *(SP getmetadata-option) ")"
+
+   getmetadata-option  = "MAXSIZE" SP number / "DEPTH" SP ("0" / "1" / "infinity")
+
+   getmetadata-entries = "(" entry *(SP entry) ")" / entry
+
+   setmetadata     = "SETMETADATA" SP mailbox SP "(" entry-value *(SP entry-value) ")"
+
+   entry-value     = entry SP value
+
+   value           = nstring / literal8
+
+*/
+
+impl Request<Command> {
+    pub fn parse_get_metadata(self, is_utf8: bool) -> trc::Result<metadata::GetArguments> {
+        if self.tokens.len() < 2 {
+            return Err(self.into_error("Missing arguments."));
+        }
+
+        let mut tokens = self.tokens.into_iter().peekable();
+        let mut max_size = None;
+        let mut depth = Depth::Zero;
+
+        // Parse options
+        if tokens
+            .peek()
+            .is_some_and(|token| token.is_parenthesis_open())
+        {
+            tokens.next();
+            loop {
+                match tokens.next() {
+                    Some(Token::Argument(option)) => {
+                        let value = tokens
+                            .next()
+                            .ok_or_else(|| {
+                                bad(self.tag.to_compact_string(), "Missing option value.")
+                            })?
+                            .unwrap_bytes();
+                        match option.to_ascii_uppercase().as_slice() {
+                            b"MAXSIZE" => {
+                                max_size = parse_number::<usize>(&value)
+                                    .map_err(|v| bad(self.tag.to_compact_string(), v))?
+                                    .into();
+                            }
+                            b"DEPTH" => {
+                                depth = Depth::parse(&value)
+                                    .map_err(|v| bad(self.tag.to_compact_string(), v))?;
+                            }
+                            _ => {
+                                return Err(bad(
+                                    self.tag.to_compact_string(),
+                                    format!(
+                                        "Unsupported option '{}'.",
+                                        String::from_utf8_lossy(&option)
+                                    ),
+                                ));
+                            }
+                        }
+                    }
+                    Some(Token::ParenthesisClose) => break,
+                    _ => {
+                        return Err(bad(
+                            self.tag.to_compact_string(),
+                            "Invalid GETMETADATA options.",
+                        ));
+                    }
+                }
+            }
+        }
+
+        // Parse mailbox name
+        let mailbox_name = utf7_maybe_decode(
+            tokens
+                .next()
+                .ok_or_else(|| bad(self.tag.to_compact_string(), "Missing mailbox name."))?
+                .unwrap_string()
+                .map_err(|v| bad(self.tag.to_compact_string(), v))?,
+            is_utf8,
+        );
+
+        // Parse entries
+        let mut entries = Vec::new();
+        match tokens.next() {
+            Some(Token::ParenthesisOpen) => {
+                for token in tokens.by_ref() {
+                    match token {
+                        Token::Argument(value) => {
+                            entries.push(
+                                parse_entry(value)
+                                    .map_err(|v| bad(self.tag.to_compact_string(), v))?,
+                            );
+                        }
+                        Token::ParenthesisClose => break,
+                        _ => {
+                            return Err(bad(self.tag.to_compact_string(), "Invalid entry name."));
+                        }
+                    }
+                }
+            }
+            Some(Token::Argument(value)) => {
+                entries.push(parse_entry(value).map_err(|v| bad(self.tag.to_compact_string(), v))?);
+            }
+            _ => {
+                return Err(bad(self.tag.to_compact_string(), "Missing entry names."));
+            }
+        }
+
+        if entries.is_empty() {
+            Err(bad(self.tag.to_compact_string(), "Missing entry names."))
+        } else if tokens.next().is_some() {
+            Err(bad(self.tag.to_compact_string(), "Too many arguments."))
+        } else {
+            Ok(metadata::GetArguments {
+                tag: self.tag,
+                mailbox_name,
+                entries,
+                max_size,
+                depth,
+            })
+        }
+    }
+
+    pub fn parse_set_metadata(self, is_utf8: bool) -> trc::Result<metadata::SetArguments> {
+        if self.tokens.len() < 4 {
+            return Err(self.into_error("Missing arguments."));
+        }
+
+        let mut tokens = self.tokens.into_iter();
+        let mailbox_name = utf7_maybe_decode(
+            tokens
+                .next()
+                .unwrap()
+                .unwrap_string()
+                .map_err(|v| bad(self.tag.to_compact_string(), v))?,
+            is_utf8,
+        );
+
+        if tokens
+            .next()
+            .is_none_or(|token| !token.is_parenthesis_open())
+        {
+            return Err(bad(
+                self.tag.to_compact_string(),
+                "Expected parenthesis after mailbox name.",
+            ));
+        }
+
+        let mut entries: Vec<(String, Option<Vec<u8>>)> = Vec::new();
+        loop {
+            match tokens.next() {
+                Some(Token::Argument(name)) => {
+                    let name =
+                        parse_entry(name).map_err(|v| bad(self.tag.to_compact_string(), v))?;
+                    let value = match tokens.next() {
+                        Some(Token::Argument(value)) if !value.eq_ignore_ascii_case(b"NIL") => {
+                            Some(value)
+                        }
+                        Some(Token::Argument(_) | Token::Nil) => None,
+                        _ => {
+                            return Err(bad(
+                                self.tag.to_compact_string(),
+                                format!("Missing value for entry '{name}'."),
+                            ));
+                        }
+                    };
+                    if let Some(entry) = entries.iter_mut().find(|(entry, _)| entry == &name) {
+                        entry.1 = value;
+                    } else {
+                        entries.push((name, value));
+                    }
+                }
+                Some(Token::ParenthesisClose) => break,
+                _ => {
+                    return Err(bad(
+                        self.tag.to_compact_string(),
+                        "Invalid SETMETADATA arguments.",
+                    ));
+                }
+            }
+        }
+
+        if entries.is_empty() {
+            Err(bad(self.tag.to_compact_string(), "Missing entries."))
+        } else if tokens.next().is_some() {
+            Err(bad(self.tag.to_compact_string(), "Too many arguments."))
+        } else {
+            Ok(metadata::SetArguments {
+                tag: self.tag,
+                mailbox_name,
+                entries,
+            })
+        }
+    }
+}
+
+impl Depth {
+    pub fn parse(value: &[u8]) -> super::Result<Self> {
+        hashify::tiny_map_ignore_case!(value,
+            "0" => Depth::Zero,
+            "1" => Depth::One,
+            "infinity" => Depth::Infinity,
+        )
+        .ok_or_else(|| format!("Invalid depth value '{}'.", String::from_utf8_lossy(value)).into())
+    }
+}
+
+pub fn parse_entry(value: Vec<u8>) -> super::Result<String> {
+    let name = String::from_utf8(value)
+        .map_err(|_| "Invalid UTF-8 in entry name.")?
+        .to_ascii_lowercase();
+    let path = name
+        .strip_prefix("/private/")
+        .or_else(|| name.strip_prefix("/shared/"))
+        .ok_or_else(|| format!("Entry name '{name}' must begin with /private or /shared."))?;
+
+    if !path.is_empty()
+        && !path.ends_with('/')
+        && !path.contains("//")
+        && path
+            .bytes()
+            .all(|ch| ch.is_ascii_graphic() && ch != b'*' && ch != b'%')
+    {
+        Ok(name)
+    } else {
+        Err(format!("Invalid entry name '{name}'.").into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        protocol::metadata::{self, Depth},
+        receiver::Receiver,
+    };
+
+    #[test]
+    fn parse_get_metadata() {
+        let mut receiver = Receiver::new();
+
+        for (command, arguments) in [
+            (
+                "a GETMETADATA \"\" /private/comment\r\n",
+                metadata::GetArguments {
+                    tag: "a".into(),
+                    mailbox_name: "".into(),
+                    entries: vec!["/private/comment".into()],
+                    max_size: None,
+                    depth: Depth::Zero,
+                },
+            ),
+            (
+                "a GETMETADATA (MAXSIZE 1024 DEPTH infinity) INBOX (/Shared/Comment /private/comment)\r\n",
+                metadata::GetArguments {
+                    tag: "a".into(),
+                    mailbox_name: "INBOX".into(),
+                    entries: vec!["/shared/comment".into(), "/private/comment".into()],
+                    max_size: Some(1024),
+                    depth: Depth::Infinity,
+                },
+            ),
+        ] {
+            assert_eq!(
+                receiver
+                    .parse(&mut command.as_bytes().iter())
+                    .unwrap()
+                    .parse_get_metadata(true)
+                    .unwrap(),
+                arguments
+            );
+        }
+
+        for command in [
+            "a GETMETADATA INBOX /comment\r\n",
+            "a GETMETADATA INBOX /private/comment/\r\n",
+            "a GETMETADATA INBOX /private/*\r\n",
+            "a GETMETADATA (DEPTH 2) INBOX /private/comment\r\n",
+        ] {
+            assert!(
+                receiver
+                    .parse(&mut command.as_bytes().iter())
+                    .unwrap()
+                    .parse_get_metadata(true)
+                    .is_err(),
+                "{command}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_set_metadata() {
+        let mut receiver = Receiver::new();
+
+        let (command, arguments) = (
+            "a SETMETADATA INBOX (/private/comment {11+}\r\nMy\r\ncomment /shared/comment NIL)\r\n",
+            metadata::SetArguments {
+                tag: "a".into(),
+                mailbox_name: "INBOX".into(),
+                entries: vec![
+                    ("/private/comment".into(), Some(b"My\r\ncomment".to_vec())),
+                    ("/shared/comment".into(), None),
+                ],
+            },
+        );
+        assert_eq!(
+            receiver
+                .parse(&mut command.as_bytes().iter())
+                .unwrap()
+                .parse_set_metadata(true)
+                .unwrap(),
+            arguments
+        );
+    }
+}
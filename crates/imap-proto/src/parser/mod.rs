@@ -7,6 +7,7 @@
 pub mod acl;
 pub mod append;
 pub mod authenticate;
+pub mod compress;
 pub mod copy_move;
 pub mod create;
 pub mod delete;
@@ -15,6 +16,8 @@ pub mod fetch;
 pub mod list;
 pub mod login;
 pub mod lsub;
+pub mod metadata;
+pub mod notify;
 pub mod quota;
 pub mod rename;
 pub mod search;
@@ -24,6 +27,7 @@ pub mod status;
 pub mod store;
 pub mod subscribe;
 pub mod thread;
+pub mod urlauth;
 
 use std::{borrow::Cow, str::FromStr};
 
@@ -31,7 +35,7 @@ use chrono::{DateTime, NaiveDate};
 
 use crate::{
     Command,
-    protocol::{Flag, Sequence},
+    protocol::{Flag, PartialRange, Sequence},
     receiver::CommandParser,
 };
 
@@ -80,6 +84,14 @@ impl CommandParser for Command {
             "ID" => Command::Id,
             "GETQUOTA" => Command::GetQuota,
             "GETQUOTAROOT" => Command::GetQuotaRoot,
+            "COMPRESS" => Command::Compress,
+            "GETMETADATA" => Command::GetMetadata,
+            "SETMETADATA" => Command::SetMetadata,
+            "NOTIFY" => Command::Notify,
+            "REPLACE" => Command::Replace(uid),
+            "GENURLAUTH" => Command::GenUrlAuth,
+            "RESETKEY" => Command::ResetKey,
+            "URLFETCH" => Command::UrlFetch,
         )
     }
 
@@ -399,6 +411,36 @@ pub fn parse_sequence_set(value: &[u8]) -> Result<Sequence> {
     }
 }
 
+pub fn parse_partial_range(value: &[u8]) -> Result<PartialRange> {
+    let invalid = || {
+        Cow::from(format!(
+            "Invalid partial range {:?}.",
+            String::from_utf8_lossy(value)
+        ))
+    };
+    let (start, end) = value
+        .iter()
+        .position(|&ch| ch == b':')
+        .map(|pos| (&value[..pos], &value[pos + 1..]))
+        .ok_or_else(invalid)?;
+    let (start, end, from_end) = match (start.strip_prefix(b"-"), end.strip_prefix(b"-")) {
+        (Some(start), Some(end)) => (start, end, true),
+        (None, None) => (start, end, false),
+        _ => return Err(invalid()),
+    };
+    let start = parse_number::<u32>(start)?;
+    let end = parse_number::<u32>(end)?;
+    if start == 0 || end == 0 {
+        return Err(invalid());
+    }
+
+    Ok(PartialRange {
+        start: start.min(end),
+        end: start.max(end),
+        from_end,
+    })
+}
+
 pub trait PushUnique<T> {
     fn push_unique(&mut self, value: T);
 }
@@ -413,7 +455,7 @@ impl<T: PartialEq> PushUnique<T> for Vec<T> {
 
 #[cfg(test)]
 mod tests {
-    use crate::protocol::Sequence;
+    use crate::protocol::{PartialRange, Sequence};
 
     #[test]
     fn parse_sequence_set() {
@@ -487,4 +529,47 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn parse_partial_range() {
+        let items = (1..=10).collect::<Vec<u32>>();
+
+        for (range, expected_range, expected_items) in [
+            ("1:3", (1, 3, false), vec![1, 2, 3]),
+            ("5:2", (2, 5, false), vec![2, 3, 4, 5]),
+            ("8:20", (8, 20, false), vec![8, 9, 10]),
+            ("11:20", (11, 20, false), vec![]),
+            ("-1:-3", (1, 3, true), vec![8, 9, 10]),
+            ("-4:-2", (2, 4, true), vec![7, 8, 9]),
+            ("-5:-50", (5, 50, true), vec![1, 2, 3, 4, 5, 6]),
+            ("-11:-20", (11, 20, true), vec![]),
+        ] {
+            let partial = super::parse_partial_range(range.as_bytes()).unwrap();
+            assert_eq!(
+                partial,
+                PartialRange {
+                    start: expected_range.0,
+                    end: expected_range.1,
+                    from_end: expected_range.2,
+                },
+                "{range}"
+            );
+            assert_eq!(partial.apply(&items), expected_items, "{range}");
+
+            let mut buf = Vec::new();
+            partial.serialize(&mut buf);
+            assert_eq!(
+                super::parse_partial_range(&buf).unwrap(),
+                partial,
+                "{range}"
+            );
+        }
+
+        for range in ["0:10", "-1:10", "1:-10", "1", "a:b", "-0:-1"] {
+            assert!(
+                super::parse_partial_range(range.as_bytes()).is_err(),
+                "{range}"
+            );
+        }
+    }
 }
@@ -0,0 +1,351 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::iter::Peekable;
+
+use compact_str::ToCompactString;
+
+use crate::{
+    Command,
+    protocol::notify::{self, Event, EventGroup, Filter, NotifySet},
+    receiver::{Request, Token, bad},
+    utf7::utf7_maybe_decode,
+};
+
+/*
+
+   notify          = "NOTIFY" SP (notify-set / notify-none)
+
+   notify-none     = "NONE"
+
+   notify-set      = "SET" [status-indicator] SP event-groups
+
+   status-indicator = SP "STATUS"
+
+   event-groups    = event-group *(SP event-group)
+
+   event-group     = "(" filter-mailboxes SP events ")"
+
+   filter-mailboxes = "selected" / "selected-delayed" / "inboxes" /
+                      "personal" / "subscribed" /
+                      ( "subtree" SP one-or-more-mailbox ) /
+                      ( "mailboxes" SP one-or-more-mailbox )
+
+   events          = ( "(" event *(SP event) ")" ) / "NONE"
+
+   message-event   = ( "MessageNew" [SP "(" fetch-att *(SP fetch-att) ")" ] )
+                     / "MessageExpunge" / "FlagChange"
+
+*/
+
+impl Request<Command> {
+    pub fn parse_notify(self, is_utf8: bool) -> trc::Result<notify::Arguments> {
+        let mut tokens = self.tokens.into_iter().peekable();
+
+        match tokens.next().map(|token| token.unwrap_bytes()) {
+            Some(value) if value.eq_ignore_ascii_case(b"NONE") => {
+                return if tokens.next().is_none() {
+                    Ok(notify::Arguments {
+                        tag: self.tag,
+                        request: None,
+                    })
+                } else {
+                    Err(bad(self.tag.to_compact_string(), "Too many arguments."))
+                };
+            }
+            Some(value) if value.eq_ignore_ascii_case(b"SET") => (),
+            _ => {
+                return Err(bad(self.tag.to_compact_string(), "Expected SET or NONE."));
+            }
+        }
+
+        let status = if tokens
+            .peek()
+            .is_some_and(|token| token.eq_ignore_ascii_case(b"STATUS"))
+        {
+            tokens.next();
+            true
+        } else {
+            false
+        };
+
+        let mut groups: Vec<EventGroup> = Vec::new();
+        while let Some(token) = tokens.next() {
+            if !token.is_parenthesis_open() {
+                return Err(bad(self.tag.to_compact_string(), "Expected event group."));
+            }
+
+            let filter = parse_filter(&mut tokens, is_utf8)
+                .map_err(|v| bad(self.tag.to_compact_string(), v))?;
+            let events =
+                parse_events(&mut tokens).map_err(|v| bad(self.tag.to_compact_string(), v))?;
+
+            if tokens
+                .next()
+                .is_none_or(|token| !token.is_parenthesis_close())
+            {
+                return Err(bad(
+                    self.tag.to_compact_string(),
+                    "Expected parenthesis after events.",
+                ));
+            }
+
+            // FlagChange requires both MessageNew and MessageExpunge
+            if events.contains(&Event::FlagChange)
+                && !(events.contains(&Event::MessageNew) && events.contains(&Event::MessageExpunge))
+            {
+                return Err(bad(
+                    self.tag.to_compact_string(),
+                    "FlagChange requires MessageNew and MessageExpunge.",
+                ));
+            } else if events.contains(&Event::MessageNew) != events.contains(&Event::MessageExpunge)
+            {
+                return Err(bad(
+                    self.tag.to_compact_string(),
+                    "MessageNew and MessageExpunge must be specified together.",
+                ));
+            } else if groups.iter().any(|group| {
+                group.filter == filter || (group.filter.is_selected() && filter.is_selected())
+            }) {
+                return Err(bad(
+                    self.tag.to_compact_string(),
+                    "Duplicate mailbox filter.",
+                ));
+            }
+
+            groups.push(EventGroup { filter, events });
+        }
+
+        if !groups.is_empty() {
+            Ok(notify::Arguments {
+                tag: self.tag,
+                request: Some(NotifySet { status, groups }),
+            })
+        } else {
+            Err(bad(self.tag.to_compact_string(), "Missing event groups."))
+        }
+    }
+}
+
+fn parse_filter(
+    tokens: &mut Peekable<impl Iterator<Item = Token>>,
+    is_utf8: bool,
+) -> super::Result<Filter> {
+    let value = match tokens.next() {
+        Some(Token::Argument(value)) => value,
+        _ => return Err("Expected mailbox filter.".into()),
+    };
+
+    hashify::tiny_map_ignore_case!(value.as_slice(),
+        "selected" => Filter::Selected,
+        "selected-delayed" => Filter::SelectedDelayed,
+        "inboxes" => Filter::Inboxes,
+        "personal" => Filter::Personal,
+        "subscribed" => Filter::Subscribed,
+    )
+    .map(Ok)
+    .unwrap_or_else(|| {
+        let is_subtree = if value.eq_ignore_ascii_case(b"subtree") {
+            true
+        } else if value.eq_ignore_ascii_case(b"mailboxes") {
+            false
+        } else {
+            return Err(format!(
+                "Invalid mailbox filter '{}'.",
+                String::from_utf8_lossy(&value)
+            )
+            .into());
+        };
+
+        let mut mailboxes = Vec::new();
+        match tokens.next() {
+            Some(Token::ParenthesisOpen) => loop {
+                match tokens.next() {
+                    Some(Token::ParenthesisClose) => break,
+                    Some(token @ (Token::Argument(_) | Token::Nil)) => {
+                        mailboxes.push(utf7_maybe_decode(token.unwrap_string()?, is_utf8));
+                    }
+                    _ => return Err("Invalid mailbox name.".into()),
+                }
+            },
+            Some(token @ (Token::Argument(_) | Token::Nil)) => {
+                mailboxes.push(utf7_maybe_decode(token.unwrap_string()?, is_utf8));
+            }
+            _ => return Err("Expected mailbox name.".into()),
+        }
+
+        if mailboxes.is_empty() {
+            Err("Expected mailbox name.".into())
+        } else if is_subtree {
+            Ok(Filter::Subtree(mailboxes))
+        } else {
+            Ok(Filter::Mailboxes(mailboxes))
+        }
+    })
+}
+
+fn parse_events(tokens: &mut Peekable<impl Iterator<Item = Token>>) -> super::Result<Vec<Event>> {
+    let mut events = Vec::new();
+
+    match tokens.next() {
+        Some(Token::ParenthesisOpen) => loop {
+            match tokens.next() {
+                Some(Token::ParenthesisClose) if !events.is_empty() => break,
+                Some(Token::Argument(value)) => {
+                    let event = Event::parse(&value)?;
+                    if event == Event::MessageNew
+                        && tokens.peek().is_some_and(|t| t.is_parenthesis_open())
+                    {
+                        // Fetch attributes are accepted but not sent with notifications
+                        skip_parenthesized(tokens)?;
+                    }
+                    if !events.contains(&event) {
+                        events.push(event);
+                    }
+                }
+                _ => return Err("Invalid event.".into()),
+            }
+        },
+        Some(Token::Argument(value)) if value.eq_ignore_ascii_case(b"NONE") => {}
+        _ => return Err("Expected events.".into()),
+    }
+
+    Ok(events)
+}
+
+fn skip_parenthesized(tokens: &mut Peekable<impl Iterator<Item = Token>>) -> super::Result<()> {
+    let mut depth = 0;
+    for token in tokens.by_ref() {
+        match token {
+            Token::ParenthesisOpen => depth += 1,
+            Token::ParenthesisClose => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(());
+                }
+            }
+            _ => {}
+        }
+    }
+    Err("Unterminated fetch attributes.".into())
+}
+
+impl Event {
+    pub fn parse(value: &[u8]) -> super::Result<Self> {
+        hashify::tiny_map_ignore_case!(value,
+            "MessageNew" => Event::MessageNew,
+            "MessageExpunge" => Event::MessageExpunge,
+            "FlagChange" => Event::FlagChange,
+            "AnnotationChange" => Event::AnnotationChange,
+            "MailboxName" => Event::MailboxName,
+            "SubscriptionChange" => Event::SubscriptionChange,
+            "MailboxMetadataChange" => Event::MailboxMetadataChange,
+            "ServerMetadataChange" => Event::ServerMetadataChange,
+        )
+        .ok_or_else(|| format!("Unsupported event '{}'.", String::from_utf8_lossy(value)).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        protocol::notify::{self, Event, EventGroup, Filter, NotifySet},
+        receiver::Receiver,
+    };
+
+    #[test]
+    fn parse_notify() {
+        let mut receiver = Receiver::new();
+
+        for (command, arguments) in [
+            (
+                "a NOTIFY NONE\r\n",
+                notify::Arguments {
+                    tag: "a".into(),
+                    request: None,
+                },
+            ),
+            (
+                concat!(
+                    "a NOTIFY SET STATUS (selected (MessageNew (uid body.peek[header.fields (from to subject)]) ",
+                    "MessageExpunge FlagChange)) (subtree (INBOX \"Lists/Rust\") (MessageNew MessageExpunge)) ",
+                    "(personal (MailboxName SubscriptionChange))\r\n"
+                ),
+                notify::Arguments {
+                    tag: "a".into(),
+                    request: Some(NotifySet {
+                        status: true,
+                        groups: vec![
+                            EventGroup {
+                                filter: Filter::Selected,
+                                events: vec![
+                                    Event::MessageNew,
+                                    Event::MessageExpunge,
+                                    Event::FlagChange,
+                                ],
+                            },
+                            EventGroup {
+                                filter: Filter::Subtree(vec!["INBOX".into(), "Lists/Rust".into()]),
+                                events: vec![Event::MessageNew, Event::MessageExpunge],
+                            },
+                            EventGroup {
+                                filter: Filter::Personal,
+                                events: vec![Event::MailboxName, Event::SubscriptionChange],
+                            },
+                        ],
+                    }),
+                },
+            ),
+            (
+                "a NOTIFY SET (mailboxes Drafts NONE) (inboxes (MessageExpunge MessageNew))\r\n",
+                notify::Arguments {
+                    tag: "a".into(),
+                    request: Some(NotifySet {
+                        status: false,
+                        groups: vec![
+                            EventGroup {
+                                filter: Filter::Mailboxes(vec!["Drafts".into()]),
+                                events: vec![],
+                            },
+                            EventGroup {
+                                filter: Filter::Inboxes,
+                                events: vec![Event::MessageExpunge, Event::MessageNew],
+                            },
+                        ],
+                    }),
+                },
+            ),
+        ] {
+            assert_eq!(
+                receiver
+                    .parse(&mut command.as_bytes().iter())
+                    .unwrap()
+                    .parse_notify(true)
+                    .unwrap(),
+                arguments
+            );
+        }
+
+        for command in [
+            "a NOTIFY SET\r\n",
+            "a NOTIFY SET (selected (FlagChange))\r\n",
+            "a NOTIFY SET (selected (MessageNew))\r\n",
+            "a NOTIFY SET (selected (MessageNew MessageExpunge)) (selected-delayed NONE)\r\n",
+            "a NOTIFY SET (everything (MailboxName))\r\n",
+            "a NOTIFY SET (personal (MailboxName Unknown))\r\n",
+            "a NOTIFY NONE extra\r\n",
+        ] {
+            assert!(
+                receiver
+                    .parse(&mut command.as_bytes().iter())
+                    .unwrap()
+                    .parse_notify(true)
+                    .is_err(),
+                "{command}"
+            );
+        }
+    }
+}
@@ -16,9 +16,9 @@ use crate::Command;
 use crate::protocol::search::{self, Filter};
 use crate::protocol::search::{ModSeqEntry, ResultOption};
 use crate::protocol::{Flag, ProtocolVersion};
-use crate::receiver::{Request, Token, bad};
+use crate::receiver::{Receiver, Request, Token, bad};
 
-use super::{parse_date, parse_number, parse_sequence_set};
+use super::{parse_date, parse_number, parse_partial_range, parse_sequence_set};
 
 impl Request<Command> {
     #[allow(clippy::while_let_on_iterator)]
@@ -72,6 +72,35 @@ impl Request<Command> {
     }
 }
 
+/// Parses a standalone list of search keys that does not depend on a selected
+/// mailbox, such as the criteria backing a virtual mailbox.
+pub fn parse_search_criteria(criteria: &str) -> super::Result<Vec<Filter>> {
+    if criteria.contains(['\r', '\n']) {
+        return Err(Cow::from("Search criteria cannot contain line breaks."));
+    }
+
+    let request = format!("0 SEARCH {}\r\n", criteria.trim());
+    let mut tokens = match Receiver::<Command>::new().parse(&mut request.as_bytes().iter()) {
+        Ok(request) => request.tokens.into_iter().peekable(),
+        Err(_) => return Err(Cow::from("Invalid search criteria.")),
+    };
+    let filters = parse_filters(&mut tokens, None)?;
+
+    if filters.is_empty() {
+        Err(Cow::from("Missing search criteria."))
+    } else if filters
+        .iter()
+        .any(|filter| matches!(filter, Filter::Sequence(..)))
+    {
+        Err(Cow::from(
+            "Message sequence numbers are not allowed in search criteria.",
+        ))
+    } else {
+        Ok(filters)
+    }
+}
+
+#[allow(clippy::while_let_on_iterator)]
 pub fn parse_result_options(
     tokens: &mut Peekable<IntoIter<Token>>,
 ) -> super::Result<Vec<ResultOption>> {
@@ -83,9 +112,17 @@ pub fn parse_result_options(
         return Err(Cow::from("Invalid result option, expected parenthesis."));
     }
 
-    for token in tokens {
+    while let Some(token) = tokens.next() {
         match token {
             Token::ParenthesisClose => break,
+            Token::Argument(value) if value.eq_ignore_ascii_case(b"partial") => {
+                result_options.push(ResultOption::Partial(parse_partial_range(
+                    &tokens
+                        .next()
+                        .ok_or_else(|| Cow::from("Missing partial range."))?
+                        .unwrap_bytes(),
+                )?));
+            }
             Token::Argument(value) => {
                 result_options.push(ResultOption::parse(&value)?);
             }
@@ -93,6 +130,16 @@ pub fn parse_result_options(
         }
     }
 
+    if result_options.contains(&ResultOption::All)
+        && result_options
+            .iter()
+            .any(|option| matches!(option, ResultOption::Partial(_)))
+    {
+        return Err(Cow::from(
+            "PARTIAL and ALL result options are mutually exclusive.",
+        ));
+    }
+
     Ok(result_options)
 }
 
@@ -420,6 +467,37 @@ pub fn parse_filters(
                                 .unwrap_string()?,
                         ));
 
+                    },
+                    "SAVEDBEFORE" => {
+                        filters.push(Filter::SavedBefore(parse_date(
+                            &tokens
+                                .next()
+                                .ok_or_else(|| Cow::from("Expected date"))?
+                                .unwrap_bytes(),
+                        )?));
+
+                    },
+                    "SAVEDON" => {
+                        filters.push(Filter::SavedOn(parse_date(
+                            &tokens
+                                .next()
+                                .ok_or_else(|| Cow::from("Expected date"))?
+                                .unwrap_bytes(),
+                        )?));
+
+                    },
+                    "SAVEDSINCE" => {
+                        filters.push(Filter::SavedSince(parse_date(
+                            &tokens
+                                .next()
+                                .ok_or_else(|| Cow::from("Expected date"))?
+                                .unwrap_bytes(),
+                        )?));
+
+                    },
+                    "SAVEDATESUPPORTED" => {
+                        filters.push(Filter::SaveDateSupported);
+
                     },
                     "OR" => {
                         if filters_stack.len() > 10 {
@@ -541,7 +619,7 @@ impl ResultOption {
 mod tests {
     use crate::{
         protocol::{
-            Flag, ProtocolVersion, Sequence,
+            Flag, PartialRange, ProtocolVersion, Sequence,
             search::{self, Filter, ModSeqEntry, ResultOption},
         },
         receiver::Receiver,
@@ -826,6 +904,21 @@ mod tests {
                     sort: None,
                 },
             ),
+            (
+                b"a SEARCH SAVEDATESUPPORTED SAVEDSINCE 1-Feb-1994 SAVEDBEFORE 1-Feb-1994\r\n"
+                    .to_vec(),
+                search::Arguments {
+                    tag: "a".into(),
+                    result_options: vec![],
+                    filter: vec![
+                        Filter::SaveDateSupported,
+                        Filter::SavedSince(760060800),
+                        Filter::SavedBefore(760060800),
+                    ],
+                    is_esearch: true,
+                    sort: None,
+                },
+            ),
             (
                 b"t SEARCH OR NOT MODSEQ 720162338 LARGER 50000\r\n".to_vec(),
                 search::Arguments {
@@ -843,6 +936,23 @@ mod tests {
                     sort: None,
                 },
             ),
+            (
+                b"A04 UID SEARCH RETURN (COUNT PARTIAL -1:-100) UNDELETED\r\n".to_vec(),
+                search::Arguments {
+                    tag: "A04".into(),
+                    result_options: vec![
+                        ResultOption::Count,
+                        ResultOption::Partial(PartialRange {
+                            start: 1,
+                            end: 100,
+                            from_end: true,
+                        }),
+                    ],
+                    filter: vec![Filter::Undeleted],
+                    is_esearch: true,
+                    sort: None,
+                },
+            ),
             (
                 b"5 UID SEARCH BEFORE 1-Dec-2023\r\n".to_vec(),
                 search::Arguments {
@@ -866,5 +976,44 @@ mod tests {
                 command_str
             );
         }
+
+        for command in [
+            "A05 SEARCH RETURN (PARTIAL) ALL\r\n",
+            "A06 SEARCH RETURN (PARTIAL 0:10) ALL\r\n",
+            "A07 SEARCH RETURN (ALL PARTIAL 1:10) ALL\r\n",
+        ] {
+            assert!(
+                receiver
+                    .parse(&mut command.as_bytes().iter())
+                    .unwrap()
+                    .parse_search(ProtocolVersion::Rev2)
+                    .is_err(),
+                "{command}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_search_criteria() {
+        assert_eq!(
+            super::parse_search_criteria("UNSEEN YOUNGER 604800").unwrap(),
+            vec![Filter::Unseen, Filter::Younger(604800)]
+        );
+        assert_eq!(
+            super::parse_search_criteria("OR FLAGGED KEYWORD $Important").unwrap(),
+            vec![
+                Filter::Or,
+                Filter::Flagged,
+                Filter::Keyword(Flag::Important),
+                Filter::End
+            ]
+        );
+
+        for criteria in ["", "1:5 FLAGGED", "UID 1:*", "$", "FLAGGED\r\nA1 LOGOUT"] {
+            assert!(
+                super::parse_search_criteria(criteria).is_err(),
+                "{criteria}"
+            );
+        }
     }
 }
@@ -0,0 +1,187 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use compact_str::ToCompactString;
+
+use crate::{
+    Command,
+    protocol::urlauth,
+    receiver::{Request, bad},
+    utf7::utf7_maybe_decode,
+};
+
+/*
+
+   genurlauth      = "GENURLAUTH" 1*(SP url-rump SP mechanism)
+
+   resetkey        = "RESETKEY" [SP mailbox *(SP mechanism)]
+
+   urlfetch        = "URLFETCH" 1*(SP url-full)
+
+*/
+
+impl Request<Command> {
+    pub fn parse_genurlauth(self) -> trc::Result<urlauth::GenArguments> {
+        if self.tokens.len() < 2 || !self.tokens.len().is_multiple_of(2) {
+            return Err(self.into_error("Expected URL and mechanism pairs."));
+        }
+
+        let mut tokens = self.tokens.into_iter();
+        let mut urls = Vec::new();
+        while let (Some(url), Some(mechanism)) = (tokens.next(), tokens.next()) {
+            urls.push((
+                url.unwrap_string()
+                    .map_err(|v| bad(self.tag.to_compact_string(), v))?,
+                mechanism
+                    .unwrap_string()
+                    .map_err(|v| bad(self.tag.to_compact_string(), v))?,
+            ));
+        }
+
+        Ok(urlauth::GenArguments {
+            tag: self.tag,
+            urls,
+        })
+    }
+
+    pub fn parse_resetkey(self, is_utf8: bool) -> trc::Result<urlauth::ResetArguments> {
+        let mut tokens = self.tokens.into_iter();
+        let mailbox_name = tokens
+            .next()
+            .map(|token| {
+                token
+                    .unwrap_string()
+                    .map(|name| utf7_maybe_decode(name, is_utf8))
+            })
+            .transpose()
+            .map_err(|v| bad(self.tag.to_compact_string(), v))?;
+        let mechanisms = tokens
+            .map(|token| token.unwrap_string())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|v| bad(self.tag.to_compact_string(), v))?;
+
+        Ok(urlauth::ResetArguments {
+            tag: self.tag,
+            mailbox_name,
+            mechanisms,
+        })
+    }
+
+    pub fn parse_urlfetch(self) -> trc::Result<urlauth::FetchArguments> {
+        if self.tokens.is_empty() {
+            return Err(self.into_error("Missing URLs."));
+        }
+
+        Ok(urlauth::FetchArguments {
+            urls: self
+                .tokens
+                .into_iter()
+                .map(|token| token.unwrap_string())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|v| bad(self.tag.to_compact_string(), v))?,
+            tag: self.tag,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{protocol::urlauth, receiver::Receiver};
+
+    #[test]
+    fn parse_urlauth() {
+        let mut receiver = Receiver::new();
+
+        assert_eq!(
+            receiver
+                .parse(
+                    &mut concat!(
+                        "a GENURLAUTH \"imap://joe@example.com/INBOX/;uid=20/;section=1.2;",
+                        "urlauth=submit+fred\" INTERNAL \"imap://joe@example.com/INBOX/;uid=21;",
+                        "urlauth=anonymous\" INTERNAL\r\n"
+                    )
+                    .as_bytes()
+                    .iter()
+                )
+                .unwrap()
+                .parse_genurlauth()
+                .unwrap(),
+            urlauth::GenArguments {
+                tag: "a".into(),
+                urls: vec![
+                    (
+                        "imap://joe@example.com/INBOX/;uid=20/;section=1.2;urlauth=submit+fred"
+                            .into(),
+                        "INTERNAL".into()
+                    ),
+                    (
+                        "imap://joe@example.com/INBOX/;uid=21;urlauth=anonymous".into(),
+                        "INTERNAL".into()
+                    ),
+                ],
+            }
+        );
+
+        for (command, arguments) in [
+            (
+                "a RESETKEY\r\n",
+                urlauth::ResetArguments {
+                    tag: "a".into(),
+                    mailbox_name: None,
+                    mechanisms: vec![],
+                },
+            ),
+            (
+                "a RESETKEY \"Drafts\" INTERNAL\r\n",
+                urlauth::ResetArguments {
+                    tag: "a".into(),
+                    mailbox_name: Some("Drafts".into()),
+                    mechanisms: vec!["INTERNAL".into()],
+                },
+            ),
+        ] {
+            assert_eq!(
+                receiver
+                    .parse(&mut command.as_bytes().iter())
+                    .unwrap()
+                    .parse_resetkey(true)
+                    .unwrap(),
+                arguments
+            );
+        }
+
+        assert_eq!(
+            receiver
+                .parse(
+                    &mut "a URLFETCH \"/INBOX/;uid=20\" \"/INBOX/;uid=21\"\r\n"
+                        .as_bytes()
+                        .iter()
+                )
+                .unwrap()
+                .parse_urlfetch()
+                .unwrap(),
+            urlauth::FetchArguments {
+                tag: "a".into(),
+                urls: vec!["/INBOX/;uid=20".into(), "/INBOX/;uid=21".into()],
+            }
+        );
+
+        for command in [
+            "a GENURLAUTH \"imap://joe@example.com/INBOX/;uid=20;urlauth=anonymous\"\r\n",
+            "a URLFETCH\r\n",
+        ] {
+            let request = receiver.parse(&mut command.as_bytes().iter()).unwrap();
+            assert!(
+                if command.contains("GENURLAUTH") {
+                    request.parse_genurlauth().is_err()
+                } else {
+                    request.parse_urlfetch().is_err()
+                },
+                "{command}"
+            );
+        }
+    }
+}
@@ -4,7 +4,7 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use super::{ImapResponse, authenticate::Mechanism};
+use super::{ImapResponse, authenticate::Mechanism, compress::CompressionAlgorithm};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Response {
@@ -53,6 +53,16 @@ pub enum Capability {
     QuotaResource(QuotaResourceName),
     QuotaSet,
     JmapAccess,
+    Compress(CompressionAlgorithm),
+    Metadata,
+    Notify,
+    Replace,
+    Catenate,
+    UrlAuth,
+    Partial,
+    UidOnly,
+    SaveDate,
+    MessageLimit(usize),
 }
 
 /*
@@ -128,10 +138,32 @@ impl Capability {
             }
             Capability::QuotaSet => b"QUOTA=SET",
             Capability::JmapAccess => b"JMAPACCESS",
+            Capability::Metadata => b"METADATA",
+            Capability::Notify => b"NOTIFY",
+            Capability::Replace => b"REPLACE",
+            Capability::Catenate => b"CATENATE",
+            Capability::UrlAuth => b"URLAUTH",
+            Capability::Partial => b"PARTIAL",
+            Capability::UidOnly => b"UIDONLY",
+            Capability::SaveDate => b"SAVEDATE",
+            Capability::MessageLimit(limit) => {
+                buf.extend_from_slice(b"MESSAGELIMIT=");
+                buf.extend_from_slice(limit.to_string().as_bytes());
+                return;
+            }
+            Capability::Compress(algorithm) => {
+                buf.extend_from_slice(b"COMPRESS=");
+                algorithm.serialize(buf);
+                return;
+            }
         });
     }
 
-    pub fn all_capabilities(is_authenticated: bool, offer_tls: bool) -> Vec<Capability> {
+    pub fn all_capabilities(
+        is_authenticated: bool,
+        offer_tls: bool,
+        message_limit: Option<usize>,
+    ) -> Vec<Capability> {
         let mut capabilities = vec![
             Capability::IMAP4rev2,
             Capability::IMAP4rev1,
@@ -173,7 +205,19 @@ impl Capability {
                 Capability::Preview,
                 Capability::Quota,
                 Capability::QuotaResource(QuotaResourceName::Storage),
+                Capability::Compress(CompressionAlgorithm::Deflate),
+                Capability::Metadata,
+                Capability::Notify,
+                Capability::Replace,
+                Capability::Catenate,
+                Capability::UrlAuth,
+                Capability::Partial,
+                Capability::UidOnly,
+                Capability::SaveDate,
             ]);
+            if let Some(message_limit) = message_limit {
+                capabilities.push(Capability::MessageLimit(message_limit));
+            }
         } else {
             capabilities.extend([
                 Capability::Auth(Mechanism::Plain),
@@ -216,11 +260,12 @@ mod tests {
                 capabilities: vec![
                     Capability::IMAP4rev2,
                     Capability::StartTLS,
-                    Capability::LoginDisabled
+                    Capability::LoginDisabled,
+                    Capability::MessageLimit(1000)
                 ],
             }
             .serialize(),
-            "* CAPABILITY IMAP4rev2 STARTTLS LOGINDISABLED\r\n".as_bytes()
+            "* CAPABILITY IMAP4rev2 STARTTLS LOGINDISABLED MESSAGELIMIT=1000\r\n".as_bytes()
         );
     }
 }
@@ -12,7 +12,7 @@ use utils::chained_bytes::SliceRange;
 use crate::protocol::literal_string_slice;
 
 use super::{
-    Flag, ImapResponse, Sequence, literal_string, quoted_or_literal_string,
+    Flag, ImapResponse, PartialRange, Sequence, literal_string, quoted_or_literal_string,
     quoted_or_literal_string_or_nil, quoted_rfc2822_or_nil, quoted_timestamp,
 };
 
@@ -23,10 +23,12 @@ pub struct Arguments {
     pub attributes: Vec<Attribute>,
     pub changed_since: Option<u64>,
     pub include_vanished: bool,
+    pub partial: Option<PartialRange>,
 }
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Response<'x> {
     pub is_uid: bool,
+    pub is_uidonly: bool,
     pub items: Vec<FetchItem<'x>>,
 }
 
@@ -67,6 +69,7 @@ pub enum Attribute {
     ModSeq,
     EmailId,
     ThreadId,
+    SaveDate,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -136,6 +139,9 @@ pub enum DataItem<'x> {
     ThreadId {
         thread_id: String,
     },
+    SaveDate {
+        date: Option<i64>,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -847,15 +853,27 @@ impl DataItem<'_> {
                 buf.extend_from_slice(thread_id.as_bytes());
                 buf.push(b')');
             }
+            DataItem::SaveDate { date } => {
+                buf.extend_from_slice(b"SAVEDATE ");
+                if let Some(date) = date {
+                    quoted_timestamp(buf, *date);
+                } else {
+                    buf.extend_from_slice(b"NIL");
+                }
+            }
         }
     }
 }
 
 impl FetchItem<'_> {
-    pub fn serialize(&self, buf: &mut Vec<u8>) {
+    pub fn serialize(&self, buf: &mut Vec<u8>, is_uidonly: bool) {
         buf.extend_from_slice(b"* ");
         buf.extend_from_slice(self.id.to_string().as_bytes());
-        buf.extend_from_slice(b" FETCH (");
+        if !is_uidonly {
+            buf.extend_from_slice(b" FETCH (");
+        } else {
+            buf.extend_from_slice(b" UIDFETCH (");
+        }
         for (pos, item) in self.items.iter().enumerate() {
             if pos > 0 {
                 buf.push(b' ');
@@ -870,7 +888,7 @@ impl ImapResponse for Response<'_> {
     fn serialize(self) -> Vec<u8> {
         let mut buf = Vec::with_capacity(128);
         for item in &self.items {
-            item.serialize(&mut buf);
+            item.serialize(&mut buf, self.is_uidonly);
         }
         buf
     }
@@ -1362,6 +1380,13 @@ mod tests {
                 super::DataItem::InternalDate { date: 482374938 },
                 "INTERNALDATE \"15-Apr-1985 01:02:18 +0000\"",
             ),
+            (
+                super::DataItem::SaveDate {
+                    date: Some(482374938),
+                },
+                "SAVEDATE \"15-Apr-1985 01:02:18 +0000\"",
+            ),
+            (super::DataItem::SaveDate { date: None }, "SAVEDATE NIL"),
         ] {
             let mut buf = Vec::with_capacity(100);
 
@@ -1377,6 +1402,7 @@ mod tests {
             String::from_utf8(
                 Response {
                     is_uid: false,
+                    is_uidonly: false,
                     items: vec![FetchItem {
                         id: 123,
                         items: vec![
@@ -1405,5 +1431,25 @@ mod tests {
                 "RFC822.HEADER {6}\r\nheader)\r\n",
             )
         );
+        assert_eq!(
+            String::from_utf8(
+                Response {
+                    is_uid: true,
+                    is_uidonly: true,
+                    items: vec![FetchItem {
+                        id: 983,
+                        items: vec![
+                            super::DataItem::Flags {
+                                flags: vec![Flag::Seen],
+                            },
+                            super::DataItem::ModSeq { modseq: 12 },
+                        ],
+                    }],
+                }
+                .serialize(),
+            )
+            .unwrap(),
+            "* 983 UIDFETCH (FLAGS (\\Seen) MODSEQ (12))\r\n"
+        );
     }
 }
@@ -0,0 +1,191 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use super::{literal_string, quoted_string};
+use crate::utf7::utf7_encode;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GetArguments {
+    pub tag: String,
+    pub mailbox_name: String,
+    pub entries: Vec<String>,
+    pub max_size: Option<usize>,
+    pub depth: Depth,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetArguments {
+    pub tag: String,
+    pub mailbox_name: String,
+    pub entries: Vec<(String, Option<Vec<u8>>)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Depth {
+    #[default]
+    Zero,
+    One,
+    Infinity,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub mailbox_name: String,
+    pub entries: Vec<(String, Option<Vec<u8>>)>,
+}
+
+// Unsolicited response listing the entries that changed (RFC 5464, Section 4.4.2)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangeResponse {
+    pub mailbox_name: String,
+    pub entries: Vec<String>,
+}
+
+impl Depth {
+    pub fn matches(&self, requested: &str, entry: &str) -> bool {
+        if requested == entry {
+            true
+        } else if let Some(child) = entry
+            .strip_prefix(requested)
+            .and_then(|child| child.strip_prefix('/'))
+        {
+            match self {
+                Depth::Zero => false,
+                Depth::One => !child.contains('/'),
+                Depth::Infinity => true,
+            }
+        } else {
+            false
+        }
+    }
+}
+
+impl Response {
+    pub fn into_bytes(self, is_utf8: bool) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(
+            self.mailbox_name.len()
+                + 16
+                + self
+                    .entries
+                    .iter()
+                    .map(|(name, value)| name.len() + value.as_ref().map_or(3, |v| v.len() + 8))
+                    .sum::<usize>(),
+        );
+        buf.extend_from_slice(b"* METADATA ");
+        if is_utf8 {
+            quoted_string(&mut buf, &self.mailbox_name);
+        } else {
+            quoted_string(&mut buf, &utf7_encode(&self.mailbox_name));
+        }
+        buf.extend_from_slice(b" (");
+        for (pos, (name, value)) in self.entries.iter().enumerate() {
+            if pos > 0 {
+                buf.push(b' ');
+            }
+            buf.extend_from_slice(name.as_bytes());
+            buf.push(b' ');
+            match value {
+                Some(value) if value.contains(&0) => {
+                    buf.push(b'~');
+                    literal_string(&mut buf, value);
+                }
+                Some(value)
+                    if value.len() > 1024
+                        || value
+                            .iter()
+                            .any(|ch| [b'\\', b'"', b'\r', b'\n'].contains(ch) || *ch >= 0x80) =>
+                {
+                    literal_string(&mut buf, value);
+                }
+                Some(value) => {
+                    buf.push(b'"');
+                    buf.extend_from_slice(value);
+                    buf.push(b'"');
+                }
+                None => {
+                    buf.extend_from_slice(b"NIL");
+                }
+            }
+        }
+        buf.extend_from_slice(b")\r\n");
+        buf
+    }
+}
+
+impl ChangeResponse {
+    pub fn serialize(&self, buf: &mut Vec<u8>, is_utf8: bool) {
+        buf.extend_from_slice(b"* METADATA ");
+        if is_utf8 {
+            quoted_string(buf, &self.mailbox_name);
+        } else {
+            quoted_string(buf, &utf7_encode(&self.mailbox_name));
+        }
+        for name in &self.entries {
+            buf.push(b' ');
+            buf.extend_from_slice(name.as_bytes());
+        }
+        buf.extend_from_slice(b"\r\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChangeResponse, Depth, Response};
+
+    #[test]
+    fn serialize_metadata() {
+        assert_eq!(
+            String::from_utf8(
+                Response {
+                    mailbox_name: "INBOX".into(),
+                    entries: vec![
+                        ("/shared/comment".into(), Some(b"Shared comment".to_vec())),
+                        ("/private/comment".into(), Some(b"My\r\ncomment".to_vec())),
+                        ("/private/vendor/foo".into(), None),
+                    ],
+                }
+                .into_bytes(true)
+            )
+            .unwrap(),
+            concat!(
+                "* METADATA \"INBOX\" (/shared/comment \"Shared comment\" ",
+                "/private/comment {11}\r\nMy\r\ncomment /private/vendor/foo NIL)\r\n"
+            )
+        );
+    }
+
+    #[test]
+    fn serialize_metadata_change() {
+        let mut buf = Vec::new();
+        ChangeResponse {
+            mailbox_name: "".into(),
+            entries: vec!["/private/comment".into(), "/private/vendor/foo".into()],
+        }
+        .serialize(&mut buf, true);
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "* METADATA \"\" /private/comment /private/vendor/foo\r\n"
+        );
+    }
+
+    #[test]
+    fn metadata_depth() {
+        for (depth, entry, expected) in [
+            (Depth::Zero, "/private/comment", true),
+            (Depth::Zero, "/private/comment/child", false),
+            (Depth::One, "/private/comment/child", true),
+            (Depth::One, "/private/comment/child/grandchild", false),
+            (Depth::Infinity, "/private/comment/child/grandchild", true),
+            (Depth::Infinity, "/private/commentary", false),
+        ] {
+            assert_eq!(
+                depth.matches("/private/comment", entry),
+                expected,
+                "{depth:?} {entry}"
+            );
+        }
+    }
+}
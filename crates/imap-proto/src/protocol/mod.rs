@@ -16,6 +16,7 @@ pub mod acl;
 pub mod append;
 pub mod authenticate;
 pub mod capability;
+pub mod compress;
 pub mod copy_move;
 pub mod create;
 pub mod delete;
@@ -24,7 +25,9 @@ pub mod expunge;
 pub mod fetch;
 pub mod list;
 pub mod login;
+pub mod metadata;
 pub mod namespace;
+pub mod notify;
 pub mod quota;
 pub mod rename;
 pub mod search;
@@ -33,6 +36,7 @@ pub mod status;
 pub mod store;
 pub mod subscribe;
 pub mod thread;
+pub mod urlauth;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ProtocolVersion {
@@ -148,6 +152,38 @@ impl Sequence {
     }
 }
 
+// RFC 9394 - PARTIAL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartialRange {
+    pub start: u32,
+    pub end: u32,
+    pub from_end: bool,
+}
+
+impl PartialRange {
+    pub fn apply<'x, T>(&self, items: &'x [T]) -> &'x [T] {
+        let (start, end) = (self.start as usize, self.end as usize);
+        let len = items.len();
+        if !self.from_end {
+            &items[(start - 1).min(len)..end.min(len)]
+        } else {
+            &items[len.saturating_sub(end)..len.saturating_sub(start - 1)]
+        }
+    }
+
+    pub fn serialize(&self, buf: &mut Vec<u8>) {
+        if self.from_end {
+            buf.push(b'-');
+        }
+        buf.extend_from_slice(self.start.to_string().as_bytes());
+        buf.push(b':');
+        if self.from_end {
+            buf.push(b'-');
+        }
+        buf.extend_from_slice(self.end.to_string().as_bytes());
+    }
+}
+
 pub trait ImapResponse {
     fn serialize(self) -> Vec<u8>;
 }
@@ -492,6 +528,38 @@ impl ResponseCode {
                 return;
             }
             ResponseCode::UseAttr => b"USEATTR",
+            ResponseCode::CompressionActive => b"COMPRESSIONACTIVE",
+            ResponseCode::MetadataLongEntries { size } => {
+                buf.extend_from_slice(b"METADATA LONGENTRIES ");
+                buf.extend_from_slice(size.to_string().as_bytes());
+                return;
+            }
+            ResponseCode::MetadataMaxSize { size } => {
+                buf.extend_from_slice(b"METADATA MAXSIZE ");
+                buf.extend_from_slice(size.to_string().as_bytes());
+                return;
+            }
+            ResponseCode::MetadataTooMany => b"METADATA TOOMANY",
+            ResponseCode::MetadataNoPrivate => b"METADATA NOPRIVATE",
+            ResponseCode::BadEvent { events } => {
+                buf.extend_from_slice(b"BADEVENT (");
+                for (pos, event) in events.iter().enumerate() {
+                    if pos > 0 {
+                        buf.push(b' ');
+                    }
+                    event.serialize(buf);
+                }
+                buf.push(b')');
+                return;
+            }
+            ResponseCode::NotificationOverflow => b"NOTIFICATIONOVERFLOW",
+            ResponseCode::BadUrl { url } => {
+                buf.extend_from_slice(b"BADURL ");
+                buf.extend_from_slice(url.as_bytes());
+                return;
+            }
+            ResponseCode::TooBig => b"TOOBIG",
+            ResponseCode::UidRequired => b"UIDREQUIRED",
         });
     }
 
@@ -535,6 +603,16 @@ impl ResponseCode {
             ResponseCode::MailboxId { .. } => "MAILBOXID",
             ResponseCode::HighestModseq { .. } => "HIGHESTMODSEQ",
             ResponseCode::UseAttr => "USEATTR",
+            ResponseCode::CompressionActive => "COMPRESSIONACTIVE",
+            ResponseCode::MetadataLongEntries { .. }
+            | ResponseCode::MetadataMaxSize { .. }
+            | ResponseCode::MetadataTooMany
+            | ResponseCode::MetadataNoPrivate => "METADATA",
+            ResponseCode::BadEvent { .. } => "BADEVENT",
+            ResponseCode::NotificationOverflow => "NOTIFICATIONOVERFLOW",
+            ResponseCode::BadUrl { .. } => "BADURL",
+            ResponseCode::TooBig => "TOOBIG",
+            ResponseCode::UidRequired => "UIDREQUIRED",
         }
     }
 }
@@ -726,6 +804,15 @@ impl Display for Command {
             Command::Id => write!(f, "ID"),
             Command::GetQuota => write!(f, "GETQUOTA"),
             Command::GetQuotaRoot => write!(f, "GETQUOTAROOT"),
+            Command::Compress => write!(f, "COMPRESS"),
+            Command::GetMetadata => write!(f, "GETMETADATA"),
+            Command::SetMetadata => write!(f, "SETMETADATA"),
+            Command::Notify => write!(f, "NOTIFY"),
+            Command::Replace(false) => write!(f, "REPLACE"),
+            Command::Replace(true) => write!(f, "UID REPLACE"),
+            Command::GenUrlAuth => write!(f, "GENURLAUTH"),
+            Command::ResetKey => write!(f, "RESETKEY"),
+            Command::UrlFetch => write!(f, "URLFETCH"),
         }
     }
 }
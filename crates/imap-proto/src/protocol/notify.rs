@@ -0,0 +1,84 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Arguments {
+    pub tag: String,
+    pub request: Option<NotifySet>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotifySet {
+    pub status: bool,
+    pub groups: Vec<EventGroup>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventGroup {
+    pub filter: Filter,
+    pub events: Vec<Event>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    Selected,
+    SelectedDelayed,
+    Inboxes,
+    Personal,
+    Subscribed,
+    Subtree(Vec<String>),
+    Mailboxes(Vec<String>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    MessageNew,
+    MessageExpunge,
+    FlagChange,
+    AnnotationChange,
+    MailboxName,
+    SubscriptionChange,
+    MailboxMetadataChange,
+    ServerMetadataChange,
+}
+
+impl Filter {
+    pub fn is_selected(&self) -> bool {
+        matches!(self, Filter::Selected | Filter::SelectedDelayed)
+    }
+}
+
+impl EventGroup {
+    pub fn has_message_events(&self) -> bool {
+        self.events.iter().any(|event| event.is_message_event())
+    }
+
+    pub fn has_event(&self, event: Event) -> bool {
+        self.events.contains(&event)
+    }
+}
+
+impl Event {
+    pub fn is_message_event(&self) -> bool {
+        matches!(
+            self,
+            Event::MessageNew | Event::MessageExpunge | Event::FlagChange
+        )
+    }
+
+    pub fn serialize(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(match self {
+            Event::MessageNew => b"MessageNew",
+            Event::MessageExpunge => b"MessageExpunge",
+            Event::FlagChange => b"FlagChange",
+            Event::AnnotationChange => b"AnnotationChange",
+            Event::MailboxName => b"MailboxName",
+            Event::SubscriptionChange => b"SubscriptionChange",
+            Event::MailboxMetadataChange => b"MailboxMetadataChange",
+            Event::ServerMetadataChange => b"ServerMetadataChange",
+        });
+    }
+}
@@ -0,0 +1,110 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use super::{literal_string, quoted_string};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenArguments {
+    pub tag: String,
+    pub urls: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResetArguments {
+    pub tag: String,
+    pub mailbox_name: Option<String>,
+    pub mechanisms: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FetchArguments {
+    pub tag: String,
+    pub urls: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenResponse {
+    pub urls: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FetchResponse {
+    pub items: Vec<(String, Option<Vec<u8>>)>,
+}
+
+impl GenResponse {
+    pub fn into_bytes(self) -> Vec<u8> {
+        let mut buf =
+            Vec::with_capacity(16 + self.urls.iter().map(|url| url.len() + 3).sum::<usize>());
+        buf.extend_from_slice(b"* GENURLAUTH");
+        for url in &self.urls {
+            buf.push(b' ');
+            quoted_string(&mut buf, url);
+        }
+        buf.extend_from_slice(b"\r\n");
+        buf
+    }
+}
+
+impl FetchResponse {
+    pub fn into_bytes(self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(
+            16 + self
+                .items
+                .iter()
+                .map(|(url, value)| url.len() + value.as_ref().map_or(4, |v| v.len() + 16))
+                .sum::<usize>(),
+        );
+        buf.extend_from_slice(b"* URLFETCH");
+        for (url, value) in &self.items {
+            buf.push(b' ');
+            quoted_string(&mut buf, url);
+            buf.push(b' ');
+            if let Some(value) = value {
+                literal_string(&mut buf, value);
+            } else {
+                buf.extend_from_slice(b"NIL");
+            }
+        }
+        buf.extend_from_slice(b"\r\n");
+        buf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FetchResponse, GenResponse};
+
+    #[test]
+    fn serialize_urlauth() {
+        assert_eq!(
+            String::from_utf8(
+                GenResponse {
+                    urls: vec![
+                        "imap://joe@example.com/INBOX/;uid=20;urlauth=anonymous:INTERNAL:abcd"
+                            .into()
+                    ],
+                }
+                .into_bytes()
+            )
+            .unwrap(),
+            "* GENURLAUTH \"imap://joe@example.com/INBOX/;uid=20;urlauth=anonymous:INTERNAL:abcd\"\r\n"
+        );
+        assert_eq!(
+            String::from_utf8(
+                FetchResponse {
+                    items: vec![
+                        ("/INBOX/;uid=20".into(), Some(b"hello\r\n".to_vec())),
+                        ("/INBOX/;uid=21".into(), None),
+                    ],
+                }
+                .into_bytes()
+            )
+            .unwrap(),
+            "* URLFETCH \"/INBOX/;uid=20\" {7}\r\nhello\r\n \"/INBOX/;uid=21\" NIL\r\n"
+        );
+    }
+}
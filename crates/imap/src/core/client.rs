@@ -11,7 +11,7 @@ use common::{
     listener::{SessionResult, SessionStream},
 };
 use imap_proto::{
-    Command, ResponseType, StatusResponse,
+    Command, ResponseCode, ResponseType, StatusResponse,
     receiver::{self, Request},
 };
 use trc::SecurityEvent;
@@ -247,6 +247,38 @@ impl<T: SessionStream> Session<T> {
                     .handle_id(request)
                     .await
                     .map(|_| SessionResult::Continue),
+                Command::Compress => self
+                    .handle_compress(request)
+                    .await
+                    .map(|_| SessionResult::UpgradeCompression),
+                Command::GetMetadata => self
+                    .handle_get_metadata(request)
+                    .await
+                    .map(|_| SessionResult::Continue),
+                Command::SetMetadata => self
+                    .handle_set_metadata(request)
+                    .await
+                    .map(|_| SessionResult::Continue),
+                Command::Notify => self
+                    .handle_notify(request)
+                    .await
+                    .map(|_| SessionResult::Continue),
+                Command::Replace(is_uid) => self
+                    .handle_replace(request, is_uid)
+                    .await
+                    .map(|_| SessionResult::Continue),
+                Command::GenUrlAuth => self
+                    .handle_genurlauth(request)
+                    .await
+                    .map(|_| SessionResult::Continue),
+                Command::ResetKey => self
+                    .handle_resetkey(request)
+                    .await
+                    .map(|_| SessionResult::Continue),
+                Command::UrlFetch => self
+                    .handle_urlfetch(request)
+                    .await
+                    .map(|_| SessionResult::Continue),
             };
 
             match result {
@@ -315,7 +347,12 @@ impl<T: SessionStream> Session<T> {
         match &request.command {
             Command::Capability | Command::Noop | Command::Logout | Command::Id => Ok(request),
             Command::StartTls => {
-                if !self.is_tls {
+                if self.is_compressed {
+                    Err(trc::ImapEvent::Error
+                        .into_err()
+                        .details("STARTTLS is not allowed after COMPRESS.")
+                        .id(request.tag))
+                } else if !self.is_tls {
                     if self.instance.acceptor.is_tls() {
                         Ok(request)
                     } else {
@@ -379,7 +416,14 @@ impl<T: SessionStream> Session<T> {
             | Command::MyRights
             | Command::Unauthenticate
             | Command::GetQuota
-            | Command::GetQuotaRoot => {
+            | Command::GetQuotaRoot
+            | Command::Compress
+            | Command::GetMetadata
+            | Command::SetMetadata
+            | Command::Notify
+            | Command::GenUrlAuth
+            | Command::ResetKey
+            | Command::UrlFetch => {
                 if let State::Authenticated { .. } | State::Selected { .. } = state {
                     Ok(request)
                 } else {
@@ -396,12 +440,32 @@ impl<T: SessionStream> Session<T> {
             | Command::Fetch(_)
             | Command::Store(_)
             | Command::Copy(_)
+            | Command::Replace(_)
             | Command::Move(_)
             | Command::Check
             | Command::Sort(_)
             | Command::Thread(_) => match state {
                 State::Selected { mailbox, .. } => {
-                    if mailbox.is_select
+                    if mailbox.is_uidonly
+                        && matches!(
+                            request.command,
+                            Command::Search(false)
+                                | Command::Fetch(false)
+                                | Command::Store(false)
+                                | Command::Copy(false)
+                                | Command::Replace(false)
+                                | Command::Move(false)
+                                | Command::Sort(false)
+                                | Command::Thread(false)
+                        )
+                    {
+                        Err(trc::ImapEvent::Error
+                            .into_err()
+                            .details("Message sequence numbers are not allowed in UIDONLY mode.")
+                            .code(ResponseCode::UidRequired)
+                            .ctx(trc::Key::Type, ResponseType::Bad)
+                            .id(request.tag))
+                    } else if mailbox.is_select
                         || !matches!(
                             request.command,
                             Command::Store(_) | Command::Expunge(_) | Command::Move(_),
@@ -4,7 +4,7 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use super::{Account, MailboxId, MailboxSync, Session, SessionData};
+use super::{Account, MailboxId, MailboxSync, Session, SessionData, VIRTUAL_MAILBOX_ID};
 use crate::core::Mailbox;
 use ahash::AHashMap;
 use common::{
@@ -15,7 +15,10 @@ use common::{
 use directory::backend::internal::manage::ManageDirectory;
 use email::{
     cache::{MessageCacheFetch, email::MessageCacheAccess, mailbox::MailboxCacheAccess},
-    mailbox::INBOX_ID,
+    mailbox::{
+        INBOX_ID,
+        virtual_mailbox::{VirtualMailboxStore, VirtualMailboxes},
+    },
 };
 use imap_proto::protocol::list::Attribute;
 use parking_lot::Mutex;
@@ -25,6 +28,7 @@ use std::{
 };
 use store::{
     ValueKey,
+    roaring::RoaringBitmap,
     write::{AlignedBytes, Archive},
 };
 use trc::AddContext;
@@ -45,6 +49,7 @@ impl<T: SessionStream> SessionData<T> {
             state: access_token.state().into(),
             access_token,
             in_flight,
+            virtual_uids: Default::default(),
         };
         let access_token = session.access_token.clone();
 
@@ -89,14 +94,26 @@ impl<T: SessionStream> SessionData<T> {
         account_id: u32,
         mailbox_prefix: Option<String>,
         access_token: &AccessToken,
-        current_state: Option<u64>,
+        current_state: Option<(u64, u64)>,
     ) -> trc::Result<Option<Account>> {
         let cache = self
             .server
             .get_cached_messages(account_id)
             .await
             .caused_by(trc::location!())?;
-        if current_state.is_some_and(|state| state == cache.last_change_id) {
+
+        // Virtual mailboxes are only available on the primary account
+        let virtual_mailboxes = if mailbox_prefix.is_none() {
+            self.server
+                .get_virtual_mailboxes(account_id)
+                .await
+                .caused_by(trc::location!())?
+        } else {
+            VirtualMailboxes::default()
+        };
+        if current_state.is_some_and(|(change_id, virtual_revision)| {
+            change_id == cache.last_change_id && virtual_revision == virtual_mailboxes.revision
+        }) {
             return Ok(None);
         }
 
@@ -127,6 +144,7 @@ impl<T: SessionStream> SessionData<T> {
             mailbox_names: BTreeMap::new(),
             mailbox_state: AHashMap::with_capacity(cache.mailboxes.items.len()),
             last_change_id: cache.last_change_id,
+            virtual_revision: virtual_mailboxes.revision,
         };
 
         for mailbox in &cache.mailboxes.items {
@@ -202,6 +220,47 @@ impl<T: SessionStream> SessionData<T> {
             );
         }
 
+        // Add virtual mailboxes
+        for virtual_mailbox in &virtual_mailboxes.items {
+            let virtual_uids = self
+                .virtual_mailbox_uids(account_id, &virtual_mailboxes, virtual_mailbox, &cache)
+                .await
+                .caused_by(trc::location!())?;
+            let message_ids = RoaringBitmap::from_iter(virtual_uids.uids.iter().map(|(_, id)| *id));
+            let mailbox_id = VIRTUAL_MAILBOX_ID | virtual_mailbox.id;
+            let mut mailbox = Mailbox {
+                is_subscribed: true,
+                uid_validity: virtual_mailbox.uid_validity as u64,
+                uid_next: virtual_uids.uid_next as u64,
+                total_deleted_storage: Some(0),
+                size: Some(0),
+                ..Default::default()
+            };
+            for message in cache
+                .emails
+                .items
+                .iter()
+                .filter(|m| message_ids.contains(m.document_id))
+            {
+                mailbox.total_messages += 1;
+                mailbox.size = mailbox.size.map(|size| size + message.size as u64);
+                if !cache.has_keyword(message, &Keyword::Seen) {
+                    mailbox.total_unseen += 1;
+                }
+                if cache.has_keyword(message, &Keyword::Deleted) {
+                    mailbox.total_deleted += 1;
+                    mailbox.total_deleted_storage = mailbox
+                        .total_deleted_storage
+                        .map(|size| size + message.size as u64);
+                }
+            }
+
+            account
+                .mailbox_names
+                .insert(virtual_mailbox.name.clone(), mailbox_id);
+            account.mailbox_state.insert(mailbox_id, mailbox);
+        }
+
         Ok(account.into())
     }
 
@@ -292,7 +351,13 @@ impl<T: SessionStream> SessionData<T> {
             .mailboxes
             .lock()
             .iter()
-            .map(|m| (m.account_id, m.prefix.clone(), m.last_change_id))
+            .map(|m| {
+                (
+                    m.account_id,
+                    m.prefix.clone(),
+                    (m.last_change_id, m.virtual_revision),
+                )
+            })
             .collect::<Vec<_>>();
         for (account_id, prefix, last_state) in account_states {
             if let Some(changed_account) = self
@@ -317,15 +382,33 @@ impl<T: SessionStream> SessionData<T> {
                     if let Some(changes) = &mut changes {
                         let old_account = &mailboxes[pos];
                         let new_account = &changed_account;
+                        let old_names = old_account
+                            .mailbox_names
+                            .iter()
+                            .map(|(mailbox_name, mailbox_id)| (*mailbox_id, mailbox_name))
+                            .collect::<AHashMap<_, _>>();
 
                         // Add new mailboxes
                         for (mailbox_name, mailbox_id) in new_account.mailbox_names.iter() {
                             if let Some(old_mailbox) = old_account.mailbox_state.get(mailbox_id) {
-                                if let Some(mailbox) = new_account.mailbox_state.get(mailbox_id)
-                                    && (mailbox.total_messages != old_mailbox.total_messages
-                                        || mailbox.total_unseen != old_mailbox.total_unseen)
+                                if let Some(mailbox) = new_account.mailbox_state.get(mailbox_id) {
+                                    if mailbox.total_messages != old_mailbox.total_messages
+                                        || mailbox.total_unseen != old_mailbox.total_unseen
+                                    {
+                                        changes.changed.push(mailbox_name.clone());
+                                    }
+                                    if mailbox.is_subscribed != old_mailbox.is_subscribed {
+                                        changes.subscription_changed.push(mailbox_name.clone());
+                                    }
+                                }
+
+                                // Add renamed mailboxes
+                                if let Some(old_name) = old_names.get(mailbox_id)
+                                    && *old_name != mailbox_name
                                 {
-                                    changes.changed.push(mailbox_name.clone());
+                                    changes
+                                        .renamed
+                                        .push((old_name.to_string(), mailbox_name.clone()));
                                 }
                             } else {
                                 changes.added.push(mailbox_name.clone());
@@ -10,8 +10,11 @@ use super::{
 use crate::core::ImapId;
 use ahash::AHashMap;
 use common::listener::SessionStream;
-use email::cache::MessageCacheFetch;
-use imap_proto::protocol::{Sequence, expunge, select::Exists};
+use email::{cache::MessageCacheFetch, mailbox::virtual_mailbox::VirtualMailboxStore};
+use imap_proto::{
+    ResponseCode,
+    protocol::{Sequence, expunge, select::Exists},
+};
 use std::collections::BTreeMap;
 use store::{ValueKey, write::ValueClass};
 use trc::AddContext;
@@ -22,6 +25,7 @@ impl<T: SessionStream> SessionData<T> {
         &self,
         mailbox: &MailboxId,
         current_state: Option<u64>,
+        is_uidonly: bool,
     ) -> trc::Result<Option<MailboxState>> {
         let cached_messages = self
             .server
@@ -34,36 +38,70 @@ impl<T: SessionStream> SessionData<T> {
         }
 
         // Obtain UID next and assign UIDs
-        let uid_map = cached_messages
-            .emails
-            .items
-            .iter()
-            .filter_map(|item| {
-                item.mailboxes.iter().find_map(|m| {
-                    if m.mailbox_id == mailbox.mailbox_id {
-                        Some((m.uid, item.document_id))
-                    } else {
-                        None
-                    }
-                })
-            })
-            .collect::<BTreeMap<u32, u32>>();
+        let uids: Box<dyn Iterator<Item = (u32, u32)> + Send> =
+            if let Some(virtual_id) = mailbox.virtual_id() {
+                // Messages in virtual mailboxes are numbered by a per-mailbox UID counter
+                let virtual_mailboxes = self
+                    .server
+                    .get_virtual_mailboxes(mailbox.account_id)
+                    .await
+                    .caused_by(trc::location!())?;
+                let uids = match virtual_mailboxes.get(virtual_id) {
+                    Some(virtual_mailbox) => self
+                        .virtual_mailbox_uids(
+                            mailbox.account_id,
+                            &virtual_mailboxes,
+                            virtual_mailbox,
+                            &cached_messages,
+                        )
+                        .await?
+                        .uids
+                        .clone(),
+                    None => Vec::new(),
+                };
+                Box::new(uids.into_iter())
+            } else {
+                Box::new(cached_messages.emails.items.iter().filter_map(|item| {
+                    item.mailboxes.iter().find_map(|m| {
+                        if m.mailbox_id == mailbox.mailbox_id {
+                            Some((m.uid, item.document_id))
+                        } else {
+                            None
+                        }
+                    })
+                }))
+            };
         let mut uid_max = 0;
-        let mut id_to_imap = AHashMap::with_capacity(uid_map.len());
-        let mut uid_to_id = AHashMap::with_capacity(uid_map.len());
+        let mut id_to_imap = AHashMap::new();
+        let mut uid_to_id = AHashMap::new();
+
+        if !is_uidonly {
+            let uid_map = uids.collect::<BTreeMap<u32, u32>>();
+            id_to_imap.reserve(uid_map.len());
+            uid_to_id.reserve(uid_map.len());
 
-        for (seqnum, (uid, message_id)) in uid_map.into_iter().enumerate() {
-            if uid > uid_max {
-                uid_max = uid;
+            for (seqnum, (uid, message_id)) in uid_map.into_iter().enumerate() {
+                if uid > uid_max {
+                    uid_max = uid;
+                }
+                id_to_imap.insert(
+                    message_id,
+                    ImapId {
+                        uid,
+                        seqnum: seqnum as u32 + 1,
+                    },
+                );
+                uid_to_id.insert(uid, message_id);
+            }
+        } else {
+            // Sequence numbers are not used in UIDONLY mode, skip sorting and
+            // the UID index (see MailboxState::uid_index)
+            for (uid, message_id) in uids {
+                if uid > uid_max {
+                    uid_max = uid;
+                }
+                id_to_imap.insert(message_id, ImapId { uid, seqnum: 0 });
             }
-            id_to_imap.insert(
-                message_id,
-                ImapId {
-                    uid,
-                    seqnum: seqnum as u32 + 1,
-                },
-            );
-            uid_to_id.insert(uid, message_id);
         }
 
         Ok(Some(MailboxState {
@@ -80,7 +118,7 @@ impl<T: SessionStream> SessionData<T> {
         // Obtain current modseq
         let mut current_modseq = mailbox.state.lock().modseq;
         if let Some(new_state) = self
-            .fetch_messages(&mailbox.id, current_modseq.into())
+            .fetch_messages(&mailbox.id, current_modseq.into(), mailbox.is_uidonly)
             .await?
         {
             // Synchronize messages
@@ -95,7 +133,7 @@ impl<T: SessionStream> SessionData<T> {
                 .unwrap_or_default();
             let mut id_to_imap = AHashMap::with_capacity(current_state.id_to_imap.len());
             for (id, imap_id) in std::mem::take(&mut current_state.id_to_imap) {
-                if !new_state.uid_to_id.contains_key(&imap_id.uid) {
+                if !new_state.has_message(id, imap_id.uid) {
                     // Add to deletions
                     deletions.push(imap_id);
 
@@ -118,12 +156,43 @@ impl<T: SessionStream> SessionData<T> {
         Ok(current_modseq)
     }
 
+    // Returns whether messages were removed from the mailbox since the last
+    // time expunges were reported, without updating the mailbox state.
+    pub async fn has_pending_expunges(&self, mailbox: &SelectedMailbox) -> trc::Result<bool> {
+        let modseq = {
+            let state = mailbox.state.lock();
+            if state
+                .next_state
+                .as_ref()
+                .is_some_and(|next_state| !next_state.deletions.is_empty())
+            {
+                return Ok(true);
+            }
+            state.modseq
+        };
+
+        if let Some(new_state) = self
+            .fetch_messages(&mailbox.id, modseq.into(), mailbox.is_uidonly)
+            .await?
+        {
+            Ok(mailbox
+                .state
+                .lock()
+                .id_to_imap
+                .iter()
+                .any(|(id, imap_id)| !new_state.has_message(*id, imap_id.uid)))
+        } else {
+            Ok(false)
+        }
+    }
+
     pub async fn write_mailbox_changes(
         &self,
         mailbox: &SelectedMailbox,
         is_qresync: bool,
     ) -> trc::Result<u64> {
-        // Resync mailbox
+        // Resync mailbox, UIDONLY clients always receive VANISHED responses
+        let is_qresync = is_qresync || mailbox.is_uidonly;
         let modseq = self.synchronize_messages(mailbox).await?;
         let mut buf = Vec::new();
         {
@@ -183,6 +252,34 @@ impl<T: SessionStream> SessionData<T> {
             .and_then(|m| m.mailbox_state.get(&mailbox.mailbox_id))
             .cloned()
     }
+
+    // Enforces the maximum number of messages a command can operate on (RFC 9738).
+    // UID commands are truncated to the lowest UIDs, other commands are rejected.
+    pub fn apply_message_limit(
+        &self,
+        ids: &mut AHashMap<u32, ImapId>,
+        is_uid: bool,
+    ) -> trc::Result<bool> {
+        match self.server.core.imap.message_limit {
+            Some(limit) if ids.len() > limit => {
+                if is_uid {
+                    let mut sorted_ids = ids.drain().collect::<Vec<_>>();
+                    sorted_ids.sort_unstable_by_key(|(_, imap_id)| imap_id.uid);
+                    sorted_ids.truncate(limit);
+                    ids.extend(sorted_ids);
+                    Ok(true)
+                } else {
+                    Err(trc::ImapEvent::Error
+                        .into_err()
+                        .details(format!(
+                            "Too many messages in request, the maximum is {limit}."
+                        ))
+                        .code(ResponseCode::Limit))
+                }
+            }
+            _ => Ok(false),
+        }
+    }
 }
 
 impl SelectedMailbox {
@@ -225,9 +322,10 @@ impl SelectedMailbox {
             })?;
             let mut ids = AHashMap::with_capacity(saved_ids.len());
             let state = self.state.lock();
+            let uid_to_id = state.uid_index();
 
             for imap_id in saved_ids.iter() {
-                if let Some(id) = state.uid_to_id.get(&imap_id.uid) {
+                if let Some(id) = uid_to_id.get(&imap_id.uid) {
                     ids.insert(*id, *imap_id);
                 }
             }
@@ -241,8 +339,9 @@ impl SelectedMailbox {
         if !sequence.is_saved_search() {
             let state = self.state.lock();
             if is_uid {
+                let uid_to_id = state.uid_index();
                 for uid in sequence.expand(state.uid_max) {
-                    if !state.uid_to_id.contains_key(&uid) {
+                    if !uid_to_id.contains_key(&uid) {
                         deleted_ids.push(uid);
                     }
                 }
@@ -255,8 +354,9 @@ impl SelectedMailbox {
             }
         } else if let Some(saved_ids) = self.get_saved_search().await {
             let state = self.state.lock();
+            let uid_to_id = state.uid_index();
             for id in saved_ids.iter() {
-                if !state.uid_to_id.contains_key(&id.uid) {
+                if !uid_to_id.contains_key(&id.uid) {
                     deleted_ids.push(if is_uid { id.uid } else { id.seqnum });
                 }
             }
@@ -271,8 +371,12 @@ impl SelectedMailbox {
             let mut uid_max = 0;
             for id in ids {
                 mailbox.total_messages += 1;
-                let seqnum = mailbox.total_messages as u32;
-                mailbox.uid_to_id.insert(id.uid, id.uid);
+                let seqnum = if !self.is_uidonly {
+                    mailbox.uid_to_id.insert(id.uid, id.id);
+                    mailbox.total_messages as u32
+                } else {
+                    0
+                };
                 mailbox.id_to_imap.insert(
                     id.id,
                     ImapId {
@@ -5,6 +5,7 @@
  */
 
 use std::{
+    borrow::Cow,
     collections::BTreeMap,
     net::IpAddr,
     sync::{Arc, atomic::AtomicU32},
@@ -14,17 +15,18 @@ use ahash::AHashMap;
 use common::{
     Inner, Server,
     auth::AccessToken,
+    ipc::PushNotification,
     listener::{ServerInstance, SessionStream, limiter::InFlight},
 };
 
 use imap_proto::{
     Command,
-    protocol::{ProtocolVersion, list::Attribute},
+    protocol::{ProtocolVersion, list::Attribute, notify::EventGroup},
     receiver::Receiver,
 };
 use tokio::{
     io::{ReadHalf, WriteHalf},
-    sync::watch,
+    sync::{mpsc, watch},
 };
 use trc::AddContext;
 
@@ -53,12 +55,22 @@ pub struct Session<T: SessionStream> {
     pub is_tls: bool,
     pub is_condstore: bool,
     pub is_qresync: bool,
+    pub is_uidonly: bool,
     pub is_utf8: bool,
+    pub is_compressed: bool,
     pub stream_rx: ReadHalf<T>,
     pub stream_tx: Arc<tokio::sync::Mutex<WriteHalf<T>>>,
     pub in_flight: InFlight,
     pub remote_addr: IpAddr,
     pub session_id: u64,
+    pub notify: Option<NotifyState>,
+}
+
+pub struct NotifyState {
+    pub groups: Vec<EventGroup>,
+    pub push_rx: mpsc::Receiver<PushNotification>,
+    // Last reported metadata entries, server entries have no mailbox id
+    pub metadata: AHashMap<Option<MailboxId>, Vec<(String, Vec<u8>)>>,
 }
 
 pub struct SessionData<T: SessionStream> {
@@ -70,6 +82,7 @@ pub struct SessionData<T: SessionStream> {
     pub stream_tx: Arc<tokio::sync::Mutex<WriteHalf<T>>>,
     pub state: AtomicU32,
     pub in_flight: Option<InFlight>,
+    pub virtual_uids: parking_lot::Mutex<AHashMap<u32, Arc<VirtualUids>>>,
 }
 
 pub struct SelectedMailbox {
@@ -78,6 +91,7 @@ pub struct SelectedMailbox {
     pub saved_search: parking_lot::Mutex<SavedSearch>,
     pub is_select: bool,
     pub is_condstore: bool,
+    pub is_uidonly: bool,
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
@@ -92,6 +106,18 @@ pub struct MailboxId {
     pub mailbox_id: u32,
 }
 
+// Virtual mailboxes are mapped to mailbox ids above this value
+pub const VIRTUAL_MAILBOX_ID: u32 = 1 << 31;
+
+// Result set of a virtual mailbox, valid as long as no messages are modified
+#[derive(Debug, Default)]
+pub struct VirtualUids {
+    pub change_id: u64,
+    pub uid_validity: u32,
+    pub uid_next: u32,
+    pub uids: Vec<(u32, u32)>,
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct Account {
     pub account_id: u32,
@@ -99,6 +125,7 @@ pub struct Account {
     pub mailbox_names: BTreeMap<String, u32>,
     pub mailbox_state: AHashMap<u32, Mailbox>,
     pub last_change_id: u64,
+    pub virtual_revision: u64,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -142,6 +169,8 @@ pub struct MailboxSync {
     pub added: Vec<String>,
     pub changed: Vec<String>,
     pub deleted: Vec<String>,
+    pub renamed: Vec<(String, String)>,
+    pub subscription_changed: Vec<String>,
 }
 
 pub enum SavedSearch {
@@ -218,11 +247,43 @@ impl<T: SessionStream> SessionData<T> {
             state: self.state,
             in_flight: self.in_flight,
             access_token: self.access_token,
+            virtual_uids: self.virtual_uids,
         }
     }
 }
 
+impl MailboxId {
+    pub fn is_virtual(&self) -> bool {
+        self.mailbox_id & VIRTUAL_MAILBOX_ID != 0
+    }
+
+    pub fn virtual_id(&self) -> Option<u32> {
+        self.is_virtual()
+            .then_some(self.mailbox_id & !VIRTUAL_MAILBOX_ID)
+    }
+}
+
 impl MailboxState {
+    pub fn has_message(&self, document_id: u32, uid: u32) -> bool {
+        self.id_to_imap
+            .get(&document_id)
+            .is_some_and(|imap_id| imap_id.uid == uid)
+    }
+
+    // UIDONLY sessions do not keep a UID index, it is built on demand
+    pub fn uid_index(&self) -> Cow<'_, AHashMap<u32, u32>> {
+        if self.uid_to_id.is_empty() && !self.id_to_imap.is_empty() {
+            Cow::Owned(
+                self.id_to_imap
+                    .iter()
+                    .map(|(id, imap_id)| (imap_id.uid, *id))
+                    .collect(),
+            )
+        } else {
+            Cow::Borrowed(&self.uid_to_id)
+        }
+    }
+
     pub fn map_result_id(&self, document_id: u32, is_uid: bool) -> Option<(u32, ImapId)> {
         if let Some(imap_id) = self.id_to_imap.get(&document_id) {
             Some((if is_uid { imap_id.uid } else { imap_id.seqnum }, *imap_id))
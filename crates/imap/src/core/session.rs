@@ -8,7 +8,11 @@ use std::sync::Arc;
 
 use common::{
     core::BuildServer,
-    listener::{SessionData, SessionManager, SessionResult, SessionStream, stream::NullIo},
+    ipc::PushNotification,
+    listener::{
+        SessionData, SessionManager, SessionResult, SessionStream, compress::DeflateStream,
+        stream::NullIo,
+    },
 };
 use imap_proto::{
     protocol::{ProtocolVersion, SerializeResponse},
@@ -19,7 +23,7 @@ use tokio_rustls::server::TlsStream;
 
 use crate::{GREETING_WITH_TLS, GREETING_WITHOUT_TLS};
 
-use super::{ImapSessionManager, Session, State};
+use super::{ImapSessionManager, NotifyState, Session, State};
 
 impl SessionManager for ImapSessionManager {
     #[allow(clippy::manual_async_fn)]
@@ -28,12 +32,23 @@ impl SessionManager for ImapSessionManager {
         session: SessionData<T>,
     ) -> impl std::future::Future<Output = ()> + Send {
         async move {
-            if let Ok(mut session) = Session::new(session, self).await
-                && session.handle_conn().await
-                && session.instance.acceptor.is_tls()
-                && let Ok(mut session) = session.into_tls().await
-            {
-                session.handle_conn().await;
+            if let Ok(mut session) = Session::new(session, self).await {
+                match session.handle_conn().await {
+                    SessionResult::UpgradeTls if session.instance.acceptor.is_tls() => {
+                        if let Ok(mut session) = session.into_tls().await
+                            && session.handle_conn().await == SessionResult::UpgradeCompression
+                            && let Ok(mut session) = session.into_compressed().await
+                        {
+                            session.handle_conn().await;
+                        }
+                    }
+                    SessionResult::UpgradeCompression => {
+                        if let Ok(mut session) = session.into_compressed().await {
+                            session.handle_conn().await;
+                        }
+                    }
+                    _ => {}
+                }
             }
         }
     }
@@ -45,7 +60,7 @@ impl SessionManager for ImapSessionManager {
 }
 
 impl<T: SessionStream> Session<T> {
-    pub async fn handle_conn(&mut self) -> bool {
+    pub async fn handle_conn(&mut self) -> SessionResult {
         let mut buf = vec![0; 8192];
         let mut shutdown_rx = self.instance.shutdown_rx.clone();
 
@@ -63,12 +78,12 @@ impl<T: SessionStream> Session<T> {
                             if bytes_read > 0 {
                                 match self.ingest(&buf[..bytes_read]).await {
                                     SessionResult::Continue => (),
-                                    SessionResult::UpgradeTls => {
-                                        return true;
-                                    }
                                     SessionResult::Close => {
                                         break;
                                     }
+                                    result => {
+                                        return result;
+                                    }
                                 }
                             } else {
                                 trc::event!(
@@ -99,6 +114,17 @@ impl<T: SessionStream> Session<T> {
                         }
                     }
                 },
+                notification = next_notification(&mut self.notify) => {
+                    if let Some(notification) = notification {
+                        if let Err(err) = self.handle_notification(notification).await
+                            && !self.write_error(err).await
+                        {
+                            break;
+                        }
+                    } else {
+                        self.notify = None;
+                    }
+                },
                 _ = shutdown_rx.changed() => {
                     trc::event!(
                         Network(trc::NetworkEvent::Closed),
@@ -112,7 +138,7 @@ impl<T: SessionStream> Session<T> {
             };
         }
 
-        false
+        SessionResult::Close
     }
 
     pub async fn new(
@@ -149,7 +175,9 @@ impl<T: SessionStream> Session<T> {
             is_tls,
             is_condstore: false,
             is_qresync: false,
+            is_uidonly: false,
             is_utf8: false,
+            is_compressed: false,
             server,
             instance: session.instance,
             session_id: session.session_id,
@@ -157,10 +185,33 @@ impl<T: SessionStream> Session<T> {
             remote_addr: session.remote_ip,
             stream_rx,
             stream_tx: Arc::new(tokio::sync::Mutex::new(stream_tx)),
+            notify: None,
         })
     }
 
     pub async fn into_tls(self) -> Result<Session<TlsStream<T>>, ()> {
+        let instance = self.instance.clone();
+        let session_id = self.session_id;
+
+        self.upgrade_stream(|stream| async move { instance.tls_accept(stream, session_id).await })
+            .await
+    }
+
+    pub async fn into_compressed(self) -> Result<Session<DeflateStream<T>>, ()> {
+        self.upgrade_stream(|stream| std::future::ready(Ok(DeflateStream::new(stream))))
+            .await
+            .map(|mut session| {
+                session.is_compressed = true;
+                session
+            })
+    }
+
+    async fn upgrade_stream<U, F, R>(self, upgrade: F) -> Result<Session<U>, ()>
+    where
+        U: SessionStream,
+        F: FnOnce(T) -> R,
+        R: std::future::Future<Output = Result<U, ()>>,
+    {
         // Drop references to write half from state
         let state = if let Some(state) =
             self.state
@@ -192,9 +243,10 @@ impl<T: SessionStream> Session<T> {
             return Err(());
         };
 
-        // Upgrade to TLS
-        let (stream_rx, stream_tx) =
-            tokio::io::split(self.instance.tls_accept(stream, self.session_id).await?);
+        // Upgrade stream
+        let stream = upgrade(stream).await?;
+        let is_tls = stream.is_tls();
+        let (stream_rx, stream_tx) = tokio::io::split(stream);
         let stream_tx = Arc::new(tokio::sync::Mutex::new(stream_tx));
 
         Ok(Session {
@@ -203,19 +255,30 @@ impl<T: SessionStream> Session<T> {
             receiver: self.receiver,
             version: self.version,
             state: state.try_replace_stream_tx(stream_tx.clone()).unwrap(),
-            is_tls: true,
+            is_tls,
             is_condstore: self.is_condstore,
             is_qresync: self.is_qresync,
+            is_uidonly: self.is_uidonly,
             is_utf8: self.is_utf8,
+            is_compressed: self.is_compressed,
             session_id: self.session_id,
             in_flight: self.in_flight,
             remote_addr: self.remote_addr,
             stream_rx,
             stream_tx,
+            notify: self.notify,
         })
     }
 }
 
+async fn next_notification(notify: &mut Option<NotifyState>) -> Option<PushNotification> {
+    if let Some(notify) = notify {
+        notify.push_rx.recv().await
+    } else {
+        std::future::pending().await
+    }
+}
+
 impl<T: SessionStream> Session<T> {
     pub async fn write_bytes(&self, bytes: impl AsRef<[u8]>) -> trc::Result<()> {
         let bytes = bytes.as_ref();
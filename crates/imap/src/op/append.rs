@@ -6,21 +6,32 @@
 
 use super::{ImapContext, ToModSeq};
 use crate::{
-    core::{ImapUidToId, MailboxId, SelectedMailbox, Session, SessionData},
+    core::{ImapUidToId, MailboxId, SavedSearch, SelectedMailbox, Session, SessionData},
     spawn_op,
 };
 use common::{ipc::PushNotification, listener::SessionStream};
 use directory::Permission;
-use email::message::ingest::{EmailIngest, IngestEmail, IngestSource};
+use email::{
+    cache::{MessageCacheFetch, email::MessageCacheAccess},
+    message::{
+        ingest::{EmailIngest, IngestEmail, IngestSource},
+        urlauth::{ImapUrl, UrlAuth},
+    },
+};
 use imap_proto::{
-    Command, ResponseCode, StatusResponse,
-    protocol::{append::Arguments, select::HighestModSeq},
+    Command, ResponseCode, ResponseType, StatusResponse,
+    protocol::{
+        append::{Arguments, CatenatePart, ReplaceArguments},
+        select::HighestModSeq,
+    },
     receiver::Request,
 };
 use mail_parser::MessageParser;
 use std::{sync::Arc, time::Instant};
+use store::{roaring::RoaringBitmap, write::BatchBuilder};
 use types::{
     acl::Acl,
+    collection::SyncCollection,
     keyword::Keyword,
     type_state::{DataType, StateChange},
 };
@@ -40,14 +51,22 @@ impl<T: SessionStream> Session<T> {
             .imap_ctx(&arguments.tag, trc::location!())?;
 
         // Obtain mailbox
-        let mailbox = if let Some(mailbox) = data.get_mailbox_by_name(&arguments.mailbox_name) {
-            mailbox
-        } else {
-            return Err(trc::ImapEvent::Error
-                .into_err()
-                .details("Mailbox does not exist.")
-                .code(ResponseCode::TryCreate)
-                .id(arguments.tag));
+        let mailbox = match data.get_mailbox_by_name(&arguments.mailbox_name) {
+            Some(mailbox) if !mailbox.is_virtual() => mailbox,
+            Some(_) => {
+                return Err(trc::ImapEvent::Error
+                    .into_err()
+                    .details("Messages cannot be added to a virtual mailbox.")
+                    .code(ResponseCode::Cannot)
+                    .id(arguments.tag));
+            }
+            None => {
+                return Err(trc::ImapEvent::Error
+                    .into_err()
+                    .details("Mailbox does not exist.")
+                    .code(ResponseCode::TryCreate)
+                    .id(arguments.tag));
+            }
         };
         let is_qresync = self.is_qresync;
 
@@ -65,7 +84,7 @@ impl<T: SessionStream> Session<T> {
 impl<T: SessionStream> SessionData<T> {
     async fn append_messages(
         &self,
-        arguments: Arguments,
+        mut arguments: Arguments,
         selected_mailbox: Option<Arc<SelectedMailbox>>,
         mailbox: MailboxId,
         is_qresync: bool,
@@ -95,6 +114,20 @@ impl<T: SessionStream> SessionData<T> {
             .await
             .imap_ctx(&arguments.tag, trc::location!())?;
 
+        // Assemble CATENATE messages
+        for message in &mut arguments.messages {
+            if !message.catenate.is_empty() {
+                match self
+                    .catenate_message(std::mem::take(&mut message.catenate))
+                    .await
+                    .imap_ctx(&arguments.tag, trc::location!())?
+                {
+                    Ok(raw_message) => message.message = raw_message,
+                    Err(response) => return Ok(response.with_tag(arguments.tag)),
+                }
+            }
+        }
+
         // Append messages
         let mut response = StatusResponse::completed(Command::Append);
         let mut created_ids = Vec::with_capacity(arguments.messages.len());
@@ -114,6 +147,8 @@ impl<T: SessionStream> SessionData<T> {
                         train_classifier: true,
                     },
                     session_id: self.session_id,
+                    batch: None,
+                    released_quota: 0,
                 })
                 .await
             {
@@ -125,18 +160,7 @@ impl<T: SessionStream> SessionData<T> {
                     last_change_id = Some(email.change_id);
                 }
                 Err(err) => {
-                    return Err(
-                        if err.matches(trc::EventType::Limit(trc::LimitEvent::Quota)) {
-                            err.details("Disk quota exceeded.")
-                                .code(ResponseCode::OverQuota)
-                        } else if err.matches(trc::EventType::Limit(trc::LimitEvent::TenantQuota)) {
-                            err.details("Organization disk quota exceeded.")
-                                .code(ResponseCode::OverQuota)
-                        } else {
-                            err
-                        }
-                        .id(arguments.tag),
-                    );
+                    return Err(map_ingest_error(err).id(arguments.tag));
                 }
             }
         }
@@ -195,3 +219,355 @@ impl<T: SessionStream> SessionData<T> {
         Ok(response.with_tag(arguments.tag))
     }
 }
+
+impl<T: SessionStream> Session<T> {
+    pub async fn handle_replace(
+        &mut self,
+        request: Request<Command>,
+        is_uid: bool,
+    ) -> trc::Result<()> {
+        // Validate access
+        self.assert_has_permission(Permission::ImapAppend)?;
+        self.assert_has_permission(Permission::ImapExpunge)?;
+
+        let op_start = Instant::now();
+        let arguments = request.parse_replace(self.is_utf8)?;
+        let (data, selected_mailbox) = self.state.mailbox_state();
+
+        // Obtain message to replace, the set must resolve to exactly one message
+        let replaced_ids = selected_mailbox
+            .sequence_to_ids(&arguments.sequence, is_uid)
+            .await
+            .map_err(|err| err.id(arguments.tag.clone()))?;
+        let replaced_id = match replaced_ids.len() {
+            1 => replaced_ids.into_keys().next().unwrap(),
+            0 => {
+                return Err(trc::ImapEvent::Error
+                    .into_err()
+                    .details("Message does not exist.")
+                    .ctx(trc::Key::Type, ResponseType::Bad)
+                    .id(arguments.tag));
+            }
+            _ => {
+                return Err(trc::ImapEvent::Error
+                    .into_err()
+                    .details("REPLACE requires a single message.")
+                    .ctx(trc::Key::Type, ResponseType::Bad)
+                    .id(arguments.tag));
+            }
+        };
+
+        // Refresh mailboxes
+        data.synchronize_mailboxes(false)
+            .await
+            .imap_ctx(&arguments.tag, trc::location!())?;
+
+        // Obtain mailbox
+        let mailbox = match data.get_mailbox_by_name(&arguments.mailbox_name) {
+            Some(mailbox) if !mailbox.is_virtual() => mailbox,
+            Some(_) => {
+                return Err(trc::ImapEvent::Error
+                    .into_err()
+                    .details("Messages cannot be added to a virtual mailbox.")
+                    .code(ResponseCode::Cannot)
+                    .id(arguments.tag));
+            }
+            None => {
+                return Err(trc::ImapEvent::Error
+                    .into_err()
+                    .details("Mailbox does not exist.")
+                    .code(ResponseCode::TryCreate)
+                    .id(arguments.tag));
+            }
+        };
+        let is_qresync = self.is_qresync;
+
+        spawn_op!(data, {
+            data.replace_message(
+                arguments,
+                selected_mailbox,
+                replaced_id,
+                mailbox,
+                is_uid,
+                is_qresync,
+                op_start,
+            )
+            .await
+        })
+    }
+}
+
+impl<T: SessionStream> SessionData<T> {
+    #[allow(clippy::too_many_arguments)]
+    async fn replace_message(
+        &self,
+        mut arguments: ReplaceArguments,
+        selected_mailbox: Arc<SelectedMailbox>,
+        replaced_id: u32,
+        mailbox: MailboxId,
+        is_uid: bool,
+        is_qresync: bool,
+        op_start: Instant,
+    ) -> trc::Result<()> {
+        // Verify ACLs
+        let account_id = mailbox.account_id;
+        let mailbox_id = mailbox.mailbox_id;
+        if !self
+            .check_mailbox_acl(account_id, mailbox_id, Acl::AddItems)
+            .await
+            .imap_ctx(&arguments.tag, trc::location!())?
+        {
+            return Err(trc::ImapEvent::Error
+                .into_err()
+                .details(
+                    "You do not have the required permissions to append messages to this mailbox.",
+                )
+                .code(ResponseCode::NoPerm)
+                .id(arguments.tag));
+        }
+        if !self
+            .check_mailbox_acl(
+                selected_mailbox.id.account_id,
+                selected_mailbox.id.mailbox_id,
+                Acl::RemoveItems,
+            )
+            .await
+            .imap_ctx(&arguments.tag, trc::location!())?
+        {
+            return Err(trc::ImapEvent::Error
+                .into_err()
+                .details(concat!(
+                    "You do not have the required permissions ",
+                    "to remove messages from this mailbox."
+                ))
+                .code(ResponseCode::NoPerm)
+                .id(arguments.tag));
+        }
+
+        // Assemble CATENATE message
+        if !arguments.message.catenate.is_empty() {
+            match self
+                .catenate_message(std::mem::take(&mut arguments.message.catenate))
+                .await
+                .imap_ctx(&arguments.tag, trc::location!())?
+            {
+                Ok(raw_message) => arguments.message.message = raw_message,
+                Err(response) => {
+                    return self
+                        .write_bytes(response.with_tag(arguments.tag).into_bytes())
+                        .await;
+                }
+            }
+        }
+
+        // Obtain access token
+        let access_token = self
+            .server
+            .get_access_token(account_id)
+            .await
+            .imap_ctx(&arguments.tag, trc::location!())?;
+
+        // Expunge the replaced message
+        let src_account_id = selected_mailbox.id.account_id;
+        let mut batch = BatchBuilder::new();
+        self.email_untag_or_delete(
+            src_account_id,
+            selected_mailbox.id.mailbox_id,
+            &RoaringBitmap::from_iter([replaced_id]),
+            &mut batch,
+        )
+        .await
+        .imap_ctx(&arguments.tag, trc::location!())?;
+
+        // The quota used by the replaced message is released in the same account,
+        // unless it still belongs to other mailboxes
+        let is_same_account = src_account_id == account_id;
+        let released_quota = if is_same_account {
+            self.server
+                .get_cached_messages(account_id)
+                .await
+                .imap_ctx(&arguments.tag, trc::location!())?
+                .email_by_id(&replaced_id)
+                .filter(|email| email.mailboxes.len() == 1)
+                .map_or(0, |email| email.size as u64)
+        } else {
+            0
+        };
+
+        // Append the new message, both changes are written in the same batch
+        // so that a failure never leaves both copies behind
+        let message = arguments.message;
+        let email = self
+            .server
+            .email_ingest(IngestEmail {
+                raw_message: &message.message,
+                message: MessageParser::new().parse(&message.message),
+                blob_hash: None,
+                access_token: &access_token,
+                mailbox_ids: vec![mailbox_id],
+                keywords: message.flags.into_iter().map(Keyword::from).collect(),
+                received_at: message.received_at.map(|d| d as u64),
+                source: IngestSource::Imap {
+                    train_classifier: true,
+                },
+                session_id: self.session_id,
+                batch: Some(batch),
+                released_quota,
+            })
+            .await
+            .map_err(|err| map_ingest_error(err).id(arguments.tag.clone()))?;
+        self.server.notify_task_queue();
+
+        // Broadcast changes on the source account
+        if !is_same_account
+            && let Some(change_id) = self
+                .server
+                .store()
+                .get_last_change_id(src_account_id, SyncCollection::Email.into())
+                .await
+                .imap_ctx(&arguments.tag, trc::location!())?
+        {
+            self.server
+                .broadcast_push_notification(PushNotification::StateChange(
+                    StateChange::new(src_account_id)
+                        .with_change_id(change_id)
+                        .with_change(DataType::Email)
+                        .with_change(DataType::Mailbox)
+                        .with_change(DataType::Thread),
+                ))
+                .await;
+        }
+
+        // Broadcast changes
+        self.server
+            .broadcast_push_notification(PushNotification::StateChange(
+                StateChange::new(account_id)
+                    .with_change_id(email.change_id)
+                    .with_change(DataType::Email)
+                    .with_change(DataType::Mailbox)
+                    .with_change(DataType::Thread),
+            ))
+            .await;
+
+        trc::event!(
+            Imap(trc::ImapEvent::Replace),
+            SpanId = self.session_id,
+            MailboxName = arguments.mailbox_name,
+            AccountId = account_id,
+            MailboxId = mailbox_id,
+            DocumentId = email.document_id,
+            Id = replaced_id,
+            Elapsed = op_start.elapsed()
+        );
+
+        // Send APPENDUID
+        let uid = email.imap_uids[0];
+        let uid_validity = self
+            .mailbox_state(&mailbox)
+            .map(|m| m.uid_validity as u32)
+            .unwrap_or_default();
+        self.write_bytes(
+            StatusResponse::ok("Replacement message ready.")
+                .with_code(ResponseCode::AppendUid {
+                    uid_validity,
+                    uids: vec![uid],
+                })
+                .into_bytes(),
+        )
+        .await?;
+
+        // Write EXISTS and EXPUNGE responses
+        if selected_mailbox.id == mailbox {
+            selected_mailbox.append_messages(
+                vec![ImapUidToId {
+                    uid,
+                    id: email.document_id,
+                }],
+                Some(email.change_id),
+            );
+        }
+        *selected_mailbox.saved_search.lock() = SavedSearch::None;
+        self.write_mailbox_changes(&selected_mailbox, is_qresync)
+            .await
+            .imap_ctx(&arguments.tag, trc::location!())?;
+
+        self.write_bytes(
+            StatusResponse::completed(Command::Replace(is_uid))
+                .with_tag(arguments.tag)
+                .into_bytes(),
+        )
+        .await
+    }
+}
+
+impl<T: SessionStream> SessionData<T> {
+    async fn catenate_message(
+        &self,
+        parts: Vec<CatenatePart>,
+    ) -> trc::Result<Result<Vec<u8>, StatusResponse>> {
+        let max_size = self.server.core.imap.max_request_size;
+        let mut raw_message = Vec::new();
+
+        for part in parts {
+            match part {
+                CatenatePart::Text(text) => {
+                    raw_message.extend_from_slice(&text);
+                }
+                CatenatePart::Url(url) => {
+                    if let Some(contents) = self.fetch_catenate_url(&url).await? {
+                        raw_message.extend_from_slice(&contents);
+                    } else {
+                        return Ok(Err(StatusResponse::no("Unable to fetch URL.")
+                            .with_code(ResponseCode::BadUrl { url })));
+                    }
+                }
+            }
+
+            if raw_message.len() > max_size {
+                return Ok(Err(
+                    StatusResponse::no("Message is too large.").with_code(ResponseCode::TooBig)
+                ));
+            }
+        }
+
+        Ok(Ok(raw_message))
+    }
+
+    async fn fetch_catenate_url(&self, url: &str) -> trc::Result<Option<Vec<u8>>> {
+        let Some(url) = ImapUrl::parse(url) else {
+            return Ok(None);
+        };
+
+        if url.has_token() {
+            self.server
+                .urlauth_fetch(&url, Some(&self.access_token.name), false)
+                .await
+        } else if url
+            .user
+            .as_ref()
+            .is_none_or(|user| user == &self.access_token.name)
+            && let Some(mailbox) = self.get_mailbox_by_name(&url.mailbox)
+            && self
+                .check_mailbox_acl(mailbox.account_id, mailbox.mailbox_id, Acl::ReadItems)
+                .await?
+        {
+            self.server
+                .imap_url_fetch(mailbox.account_id, mailbox.mailbox_id, &url)
+                .await
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+fn map_ingest_error(err: trc::Error) -> trc::Error {
+    if err.matches(trc::EventType::Limit(trc::LimitEvent::Quota)) {
+        err.details("Disk quota exceeded.")
+            .code(ResponseCode::OverQuota)
+    } else if err.matches(trc::EventType::Limit(trc::LimitEvent::TenantQuota)) {
+        err.details("Organization disk quota exceeded.")
+            .code(ResponseCode::OverQuota)
+    } else {
+        err
+    }
+}
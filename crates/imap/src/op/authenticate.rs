@@ -130,6 +130,7 @@ impl<T: SessionStream> Session<T> {
                     capabilities: Capability::all_capabilities(
                         true,
                         !self.is_tls && self.instance.acceptor.is_tls(),
+                        self.server.core.imap.message_limit,
                     ),
                 })
                 .with_tag(tag)
@@ -140,6 +141,7 @@ impl<T: SessionStream> Session<T> {
 
     pub async fn handle_unauthenticate(&mut self, request: Request<Command>) -> trc::Result<()> {
         self.state = State::NotAuthenticated { auth_failures: 0 };
+        self.notify = None;
 
         self.write_bytes(
             StatusResponse::completed(Command::Unauthenticate)
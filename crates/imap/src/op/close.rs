@@ -16,7 +16,7 @@ impl<T: SessionStream> Session<T> {
         let op_start = Instant::now();
         let (data, mailbox) = self.state.select_data();
 
-        if mailbox.is_select {
+        if mailbox.is_select && !mailbox.id.is_virtual() {
             data.expunge(mailbox.clone(), None, op_start)
                 .await
                 .caused_by(trc::location!())?;
@@ -18,6 +18,7 @@ use email::{
         copy::{CopyMessageError, EmailCopy},
         ingest::EmailIngest,
         metadata::MessageData,
+        savedate::SaveDateBatch,
     },
 };
 use imap_proto::{
@@ -28,7 +29,7 @@ use std::{sync::Arc, time::Instant};
 use store::{
     ValueKey,
     roaring::RoaringBitmap,
-    write::{AlignedBytes, Archive, BatchBuilder},
+    write::{AlignedBytes, Archive, BatchBuilder, now},
 };
 use trc::AddContext;
 use types::{
@@ -74,6 +75,21 @@ impl<T: SessionStream> Session<T> {
                         .id(arguments.tag));
                 };
 
+            // Virtual mailboxes are read-only views over other mailboxes
+            if dest_mailbox.is_virtual() {
+                return Err(trc::ImapEvent::Error
+                    .into_err()
+                    .details("Messages cannot be added to a virtual mailbox.")
+                    .code(ResponseCode::Cannot)
+                    .id(arguments.tag));
+            } else if is_move && src_mailbox.id.is_virtual() {
+                return Err(trc::ImapEvent::Error
+                    .into_err()
+                    .details("Messages cannot be moved out of a virtual mailbox.")
+                    .code(ResponseCode::Cannot)
+                    .id(arguments.tag));
+            }
+
             // Check that the destination mailbox is not the same as the source mailbox.
             if src_mailbox.id.account_id == dest_mailbox.account_id
                 && src_mailbox.id.mailbox_id == dest_mailbox.mailbox_id
@@ -116,7 +132,7 @@ impl<T: SessionStream> SessionData<T> {
             .imap_ctx(&arguments.tag, trc::location!())?;
 
         // Convert IMAP ids to JMAP ids.
-        let ids = src_mailbox
+        let mut ids = src_mailbox
             .sequence_to_ids(&arguments.sequence_set, is_uid)
             .await
             .imap_ctx(&arguments.tag, trc::location!())?;
@@ -150,6 +166,11 @@ impl<T: SessionStream> SessionData<T> {
                 .await;
         }
 
+        // Enforce message limit
+        let is_limited = self
+            .apply_message_limit(&mut ids, is_uid)
+            .imap_ctx(&arguments.tag, trc::location!())?;
+
         // Verify that the user can delete messages from the source mailbox.
         if is_move
             && !self
@@ -225,11 +246,12 @@ impl<T: SessionStream> SessionData<T> {
                     .imap_ctx(&arguments.tag, trc::location!())?;
 
                 // Make sure the message still belongs to this mailbox
-                if !data
-                    .inner
-                    .mailboxes
-                    .iter()
-                    .any(|mailbox| mailbox.mailbox_id == src_mailbox.id.mailbox_id)
+                if !src_mailbox.id.is_virtual()
+                    && !data
+                        .inner
+                        .mailboxes
+                        .iter()
+                        .any(|mailbox| mailbox.mailbox_id == src_mailbox.id.mailbox_id)
                 {
                     continue;
                 }
@@ -256,6 +278,7 @@ impl<T: SessionStream> SessionData<T> {
                                     .with_changes(new_data.seal()),
                             )
                             .imap_ctx(&arguments.tag, trc::location!())?
+                            .clear_save_date(src_mailbox.id.mailbox_id, imap_id.uid)
                             .log_vanished_item(
                                 VanishedCollection::Email,
                                 (src_mailbox.id.mailbox_id, imap_id.uid),
@@ -291,6 +314,7 @@ impl<T: SessionStream> SessionData<T> {
                     .await
                     .caused_by(trc::location!())?;
 
+                let mut assigned_uids = Vec::with_capacity(1);
                 for (uid_mailbox, uid) in new_data
                     .mailboxes
                     .iter_mut()
@@ -298,6 +322,7 @@ impl<T: SessionStream> SessionData<T> {
                     .zip(ids)
                 {
                     copied_ids.push((imap_id.uid, uid));
+                    assigned_uids.push((uid_mailbox.mailbox_id, uid));
                     uid_mailbox.uid = uid;
                 }
 
@@ -312,11 +337,17 @@ impl<T: SessionStream> SessionData<T> {
                             .with_changes(new_data.seal()),
                     )
                     .imap_ctx(&arguments.tag, trc::location!())?;
+                let saved_at = now();
+                for (mailbox_id, uid) in assigned_uids {
+                    batch.set_save_date(mailbox_id, uid, saved_at);
+                }
                 if is_move {
-                    batch.log_vanished_item(
-                        VanishedCollection::Email,
-                        (src_mailbox.id.mailbox_id, imap_id.uid),
-                    );
+                    batch
+                        .clear_save_date(src_mailbox.id.mailbox_id, imap_id.uid)
+                        .log_vanished_item(
+                            VanishedCollection::Email,
+                            (src_mailbox.id.mailbox_id, imap_id.uid),
+                        );
                 }
 
                 // Add message to training queue
@@ -509,7 +540,7 @@ impl<T: SessionStream> SessionData<T> {
             Elapsed = op_start.elapsed()
         );
 
-        let response = if is_move {
+        let response = if is_move || is_limited {
             self.write_bytes(
                 StatusResponse::ok("Copied UIDs")
                     .with_code(ResponseCode::CopyUid {
@@ -528,6 +559,10 @@ impl<T: SessionStream> SessionData<T> {
                     .imap_ctx(&arguments.tag, trc::location!())?;
             }
 
+            if is_limited {
+                response = response.with_code(ResponseCode::Limit);
+            }
+
             response.with_tag(arguments.tag).into_bytes()
         } else {
             response
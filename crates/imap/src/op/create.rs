@@ -5,7 +5,7 @@
  */
 
 use crate::{
-    core::{Session, SessionData},
+    core::{Session, SessionData, VIRTUAL_MAILBOX_ID},
     op::ImapContext,
     spawn_op,
 };
@@ -260,7 +260,12 @@ impl<T: SessionStream> SessionData<T> {
 
         // Validate ACLs
         if let Some(parent_mailbox_id) = parent_mailbox_id {
-            if !self
+            if parent_mailbox_id & VIRTUAL_MAILBOX_ID != 0 {
+                return Err(trc::ImapEvent::Error
+                    .into_err()
+                    .details("Virtual mailboxes cannot have sub mailboxes.")
+                    .code(ResponseCode::Cannot));
+            } else if !self
                 .check_mailbox_acl(account_id, parent_mailbox_id, Acl::CreateChild)
                 .await?
             {
@@ -57,6 +57,13 @@ impl<T: SessionStream> SessionData<T> {
         // Validate mailbox
         let (account_id, mailbox_id) =
             if let Some(mailbox) = self.get_mailbox_by_name(&arguments.mailbox_name) {
+                if mailbox.is_virtual() {
+                    return Err(trc::ImapEvent::Error
+                        .into_err()
+                        .details("Virtual mailboxes cannot be deleted over IMAP.")
+                        .code(ResponseCode::Cannot)
+                        .id(arguments.tag));
+                }
                 (mailbox.account_id, mailbox.mailbox_id)
             } else {
                 return Err(trc::ImapEvent::Error
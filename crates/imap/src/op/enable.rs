@@ -6,7 +6,7 @@
 
 use std::time::Instant;
 
-use crate::core::Session;
+use crate::core::{Session, State};
 use common::listener::SessionStream;
 use directory::Permission;
 use imap_proto::{
@@ -46,6 +46,13 @@ impl<T: SessionStream> Session<T> {
                 Capability::Utf8Accept => {
                     self.is_utf8 = true;
                 }
+                Capability::UidOnly => {
+                    // Sequence number state is built when the mailbox is selected
+                    if matches!(self.state, State::Selected { .. }) {
+                        continue;
+                    }
+                    self.is_uidonly = true;
+                }
                 _ => {
                     continue;
                 }
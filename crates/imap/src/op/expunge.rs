@@ -11,7 +11,7 @@ use common::{listener::SessionStream, storage::index::ObjectIndexBuilder};
 use directory::Permission;
 use email::{
     cache::{MessageCacheFetch, email::MessageCacheAccess},
-    message::metadata::MessageData,
+    message::{metadata::MessageData, savedate::SaveDateBatch},
 };
 use imap_proto::{
     Command, ResponseCode, ResponseType, StatusResponse,
@@ -43,6 +43,15 @@ impl<T: SessionStream> Session<T> {
         let op_start = Instant::now();
         let (data, mailbox) = self.state.select_data();
 
+        // Messages can only be expunged from their underlying mailboxes
+        if mailbox.id.is_virtual() {
+            return Err(trc::ImapEvent::Error
+                .into_err()
+                .details("Messages cannot be expunged from a virtual mailbox.")
+                .code(ResponseCode::Cannot)
+                .id(request.tag));
+        }
+
         // Validate ACL
         if !data
             .check_mailbox_acl(
@@ -183,11 +192,13 @@ impl<T: SessionStream> SessionData<T> {
 
                     if let Some(message_uid) = metadata.inner.message_uid(mailbox_id) {
                         // Add vanished items
-                        batch.with_document(document_id);
-                        batch.log_vanished_item(
-                            VanishedCollection::Email,
-                            (mailbox_id, message_uid),
-                        );
+                        batch
+                            .with_document(document_id)
+                            .clear_save_date(mailbox_id, message_uid)
+                            .log_vanished_item(
+                                VanishedCollection::Email,
+                                (mailbox_id, message_uid),
+                            );
 
                         if metadata.inner.mailboxes.len() == 1 {
                             // Delete message
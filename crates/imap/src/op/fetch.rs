@@ -19,6 +19,7 @@ use email::{
         ArchivedMetadataPartType, DecodedParts, MESSAGE_RECEIVED_MASK, MessageData,
         MessageMetadata, MetadataHeaderName, PART_ENCODING_PROBLEM,
     },
+    message::savedate::SaveDate,
 };
 use imap_proto::{
     Command, ResponseCode, ResponseType, StatusResponse,
@@ -249,6 +250,18 @@ impl<T: SessionStream> SessionData<T> {
             arguments.attributes.push_unique(Attribute::ModSeq);
         }
 
+        // Apply partial range
+        if let Some(partial) = &arguments.partial {
+            let mut sorted_ids = ids.into_iter().collect::<Vec<_>>();
+            sorted_ids.sort_unstable_by_key(|(_, imap_id)| imap_id.uid);
+            ids = partial.apply(&sorted_ids).iter().copied().collect();
+        }
+
+        // Enforce message limit
+        let is_limited = self
+            .apply_message_limit(&mut ids, is_uid)
+            .imap_ctx(&arguments.tag, trc::location!())?;
+
         // Build properties list
         let mut set_seen_flags = false;
         let mut needs_blobs = false;
@@ -301,7 +314,7 @@ impl<T: SessionStream> SessionData<T> {
         if is_uid {
             if arguments.attributes.is_empty() {
                 arguments.attributes.push(Attribute::Flags);
-            } else if !arguments.attributes.contains(&Attribute::Uid) {
+            } else if !mailbox.is_uidonly && !arguments.attributes.contains(&Attribute::Uid) {
                 arguments.attributes.insert(0, Attribute::Uid);
             }
         }
@@ -312,7 +325,7 @@ impl<T: SessionStream> SessionData<T> {
             .into_iter()
             .map(|(id, imap_id)| (imap_id.seqnum, imap_id.uid, id))
             .collect::<Vec<_>>();
-        ids.sort_unstable_by_key(|(seqnum, _, _)| *seqnum);
+        ids.sort_unstable_by_key(|(_, uid, _)| *uid);
         let fetched_ids = ids
             .iter()
             .map(|id| trc::Value::from(id.2))
@@ -528,6 +541,19 @@ impl<T: SessionStream> SessionData<T> {
                             thread_id: Id::from_parts(account_id, data.thread_id).to_string(),
                         });
                     }
+                    Attribute::SaveDate => {
+                        // Messages without a save date report their internal date,
+                        // which is also what SEARCH matches them by
+                        let saved_at = self
+                            .server
+                            .get_save_date(account_id, id, mailbox.id.mailbox_id, uid)
+                            .await
+                            .imap_ctx(&arguments.tag, trc::location!())?
+                            .unwrap_or(metadata.rcvd_attach.to_native() & MESSAGE_RECEIVED_MASK);
+                        items.push(DataItem::SaveDate {
+                            date: Some(saved_at as i64),
+                        });
+                    }
                 }
             }
 
@@ -543,7 +569,11 @@ impl<T: SessionStream> SessionData<T> {
 
             // Serialize fetch item
             let mut buf = Vec::with_capacity(128);
-            FetchItem { id: seqnum, items }.serialize(&mut buf);
+            FetchItem {
+                id: if !mailbox.is_uidonly { seqnum } else { uid },
+                items,
+            }
+            .serialize(&mut buf, mailbox.is_uidonly);
             self.write_bytes(buf).await?;
 
             // Add to set flags
@@ -623,7 +653,12 @@ impl<T: SessionStream> SessionData<T> {
             .await?;
         }
 
-        Ok(StatusResponse::completed(Command::Fetch(is_uid)).with_tag(arguments.tag))
+        let response = StatusResponse::completed(Command::Fetch(is_uid)).with_tag(arguments.tag);
+        Ok(if !is_limited {
+            response
+        } else {
+            response.with_code(ResponseCode::Limit)
+        })
     }
 }
 
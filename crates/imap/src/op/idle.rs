@@ -249,6 +249,7 @@ impl<T: SessionStream> SessionData<T> {
                                 attributes: vec![fetch::Attribute::Flags, fetch::Attribute::Uid],
                                 changed_since: None,
                                 include_vanished: false,
+                                partial: None,
                             },
                             mailbox.clone(),
                             true,
@@ -7,7 +7,7 @@
 use std::time::Instant;
 
 use crate::{
-    core::{Session, SessionData},
+    core::{Session, SessionData, VIRTUAL_MAILBOX_ID},
     spawn_op,
 };
 use common::listener::SessionStream;
@@ -241,6 +241,9 @@ impl<T: SessionStream> SessionData<T> {
                     }
                     if !filter_subscribed || mailbox.is_subscribed || has_recursive_match {
                         let mut attributes = Vec::with_capacity(2);
+                        if *mailbox_id & VIRTUAL_MAILBOX_ID != 0 {
+                            attributes.push(Attribute::NoInferiors);
+                        }
                         if include_children {
                             attributes.push(if mailbox.has_children {
                                 Attribute::HasChildren
@@ -0,0 +1,447 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::Instant;
+
+use crate::{
+    core::{MailboxId, Session, SessionData},
+    op::ImapContext,
+    spawn_op,
+};
+use common::{ipc::PushNotification, listener::SessionStream};
+use directory::Permission;
+use email::mailbox::metadata::{MailboxMetadata, SPECIAL_USE_ENTRY};
+use imap_proto::{
+    Command, ResponseCode, StatusResponse,
+    protocol::metadata::{GetArguments, Response, SetArguments},
+    receiver::Request,
+};
+use store::{
+    Serialize, ValueKey,
+    write::{AlignedBytes, Archive, Archiver, BatchBuilder, ValueClass},
+};
+use trc::AddContext;
+use types::{
+    acl::Acl,
+    collection::{Collection, SyncCollection},
+    field::{MailboxField, PrincipalField},
+    type_state::{DataType, StateChange},
+};
+
+impl<T: SessionStream> Session<T> {
+    pub async fn handle_get_metadata(&mut self, request: Request<Command>) -> trc::Result<()> {
+        // Validate access
+        self.assert_has_permission(Permission::ImapMetadataGet)?;
+
+        let arguments = request.parse_get_metadata(self.is_utf8)?;
+        let is_utf8 = self.version.is_rev2() || self.is_utf8;
+        let data = self.state.session_data();
+
+        spawn_op!(data, {
+            let response = data.get_metadata(arguments, is_utf8).await?;
+            data.write_bytes(response).await
+        })
+    }
+
+    pub async fn handle_set_metadata(&mut self, request: Request<Command>) -> trc::Result<()> {
+        // Validate access
+        self.assert_has_permission(Permission::ImapMetadataSet)?;
+
+        let arguments = request.parse_set_metadata(self.is_utf8)?;
+        let data = self.state.session_data();
+
+        spawn_op!(data, {
+            let response = data.set_metadata(arguments).await?;
+            data.write_bytes(response).await
+        })
+    }
+}
+
+impl<T: SessionStream> SessionData<T> {
+    pub async fn get_metadata(
+        &self,
+        arguments: GetArguments,
+        is_utf8: bool,
+    ) -> trc::Result<Vec<u8>> {
+        let op_start = Instant::now();
+        let mailbox_id = self
+            .get_metadata_mailbox(&arguments.tag, &arguments.mailbox_name)
+            .await?;
+
+        // Validate ACLs
+        let account_id = self.access_token.primary_id();
+        let (can_read_private, can_read_shared) = if let Some(mailbox_id) = &mailbox_id {
+            (
+                self.check_mailbox_acl(mailbox_id.account_id, mailbox_id.mailbox_id, Acl::Read)
+                    .await
+                    .imap_ctx(&arguments.tag, trc::location!())?,
+                self.check_mailbox_acl(
+                    mailbox_id.account_id,
+                    mailbox_id.mailbox_id,
+                    Acl::ReadItems,
+                )
+                .await
+                .imap_ctx(&arguments.tag, trc::location!())?,
+            )
+        } else {
+            (true, true)
+        };
+        if !can_read_private {
+            return Err(trc::ImapEvent::Error
+                .into_err()
+                .details("Mailbox does not exist.")
+                .code(ResponseCode::NonExistent)
+                .id(arguments.tag));
+        }
+
+        let metadata = self
+            .load_metadata(mailbox_id.as_ref())
+            .await
+            .imap_ctx(&arguments.tag, trc::location!())?
+            .unwrap_or_default();
+        let special_use = mailbox_id
+            .as_ref()
+            .and_then(|mailbox_id| self.get_special_use(mailbox_id));
+
+        // Collect matching entries
+        let mut entries: Vec<(String, Option<Vec<u8>>)> = Vec::new();
+        let mut long_entries = 0;
+        for requested in &arguments.entries {
+            if requested.starts_with("/shared/") && !can_read_shared {
+                continue;
+            }
+
+            let mut found = false;
+            for (name, value) in special_use
+                .iter()
+                .map(|value| (SPECIAL_USE_ENTRY, value.as_slice()))
+                .chain(
+                    metadata
+                        .visible_to(account_id)
+                        .map(|entry| (entry.name.as_str(), entry.value.as_slice())),
+                )
+                .filter(|(name, _)| arguments.depth.matches(requested, name))
+            {
+                found = true;
+                if arguments
+                    .max_size
+                    .is_some_and(|max_size| value.len() > max_size)
+                {
+                    long_entries = long_entries.max(value.len());
+                } else if !entries.iter().any(|(entry, _)| entry == name) {
+                    entries.push((name.to_string(), Some(value.to_vec())));
+                }
+            }
+
+            if !found && !entries.iter().any(|(entry, _)| entry == requested) {
+                entries.push((requested.to_string(), None));
+            }
+        }
+
+        trc::event!(
+            Imap(trc::ImapEvent::GetMetadata),
+            SpanId = self.session_id,
+            MailboxName = arguments.mailbox_name.clone(),
+            Total = entries.len(),
+            Elapsed = op_start.elapsed()
+        );
+
+        let mut response = StatusResponse::completed(Command::GetMetadata).with_tag(arguments.tag);
+        if long_entries > 0 {
+            response = response.with_code(ResponseCode::MetadataLongEntries { size: long_entries });
+        }
+
+        Ok(response.serialize(if !entries.is_empty() {
+            Response {
+                mailbox_name: arguments.mailbox_name,
+                entries,
+            }
+            .into_bytes(is_utf8)
+        } else {
+            Vec::new()
+        }))
+    }
+
+    pub async fn set_metadata(&self, arguments: SetArguments) -> trc::Result<Vec<u8>> {
+        let op_start = Instant::now();
+        let mailbox_id = self
+            .get_metadata_mailbox(&arguments.tag, &arguments.mailbox_name)
+            .await?;
+        let account_id = self.access_token.primary_id();
+
+        // Validate ACLs
+        let has_shared = arguments
+            .entries
+            .iter()
+            .any(|(name, _)| name.starts_with("/shared/"));
+        let has_private = arguments
+            .entries
+            .iter()
+            .any(|(name, _)| name.starts_with("/private/"));
+        if let Some(mailbox_id) = &mailbox_id {
+            if mailbox_id.is_virtual() {
+                return Err(trc::ImapEvent::Error
+                    .into_err()
+                    .details("Virtual mailboxes do not support annotations.")
+                    .code(ResponseCode::Cannot)
+                    .id(arguments.tag));
+            }
+
+            if has_private
+                && !self
+                    .check_mailbox_acl(mailbox_id.account_id, mailbox_id.mailbox_id, Acl::Read)
+                    .await
+                    .imap_ctx(&arguments.tag, trc::location!())?
+            {
+                return Err(trc::ImapEvent::Error
+                    .into_err()
+                    .details("Mailbox does not exist.")
+                    .code(ResponseCode::NonExistent)
+                    .id(arguments.tag));
+            }
+
+            if has_shared
+                && !self
+                    .check_mailbox_acl(
+                        mailbox_id.account_id,
+                        mailbox_id.mailbox_id,
+                        Acl::ModifyItems,
+                    )
+                    .await
+                    .imap_ctx(&arguments.tag, trc::location!())?
+            {
+                return Err(trc::ImapEvent::Error
+                    .into_err()
+                    .details("You do not have enough permissions to modify shared entries.")
+                    .code(ResponseCode::NoPerm)
+                    .id(arguments.tag));
+            }
+        } else if has_shared {
+            return Err(trc::ImapEvent::Error
+                .into_err()
+                .details("Shared server entries cannot be modified.")
+                .code(ResponseCode::NoPerm)
+                .id(arguments.tag));
+        }
+
+        // Validate entries
+        let max_size = self.server.core.imap.metadata_max_size;
+        for (name, value) in &arguments.entries {
+            if name == SPECIAL_USE_ENTRY {
+                return Err(trc::ImapEvent::Error
+                    .into_err()
+                    .details("The /private/specialuse entry is read-only.")
+                    .code(ResponseCode::Cannot)
+                    .id(arguments.tag));
+            } else if value.as_ref().is_some_and(|value| value.len() > max_size) {
+                return Ok(StatusResponse::no("Entry value is too large.")
+                    .with_tag(arguments.tag)
+                    .with_code(ResponseCode::MetadataMaxSize { size: max_size })
+                    .into_bytes());
+            }
+        }
+
+        // Apply changes
+        let current = self
+            .load_metadata_archive(mailbox_id.as_ref())
+            .await
+            .imap_ctx(&arguments.tag, trc::location!())?;
+        let mut metadata = if let Some(current) = &current {
+            current
+                .deserialize::<MailboxMetadata>()
+                .imap_ctx(&arguments.tag, trc::location!())?
+        } else {
+            MailboxMetadata::default()
+        };
+        let mut has_changes = false;
+        for (name, value) in arguments.entries {
+            let owner_id = name.starts_with("/private/").then_some(account_id);
+            has_changes |= metadata.set(name, owner_id, value);
+        }
+
+        let max_entries = self.server.core.imap.metadata_max_entries;
+        if metadata.count(Some(account_id)) > max_entries || metadata.count(None) > max_entries {
+            return Ok(StatusResponse::no("Too many metadata entries.")
+                .with_tag(arguments.tag)
+                .with_code(ResponseCode::MetadataTooMany)
+                .into_bytes());
+        }
+
+        // Write changes
+        if has_changes {
+            let (account_id, collection, document_id, field) =
+                metadata_location(account_id, mailbox_id.as_ref());
+            let mut batch = BatchBuilder::new();
+            batch
+                .with_account_id(account_id)
+                .with_collection(collection)
+                .with_document(document_id);
+            if let Some(current) = current {
+                batch.assert_value(field.clone(), current);
+            }
+            if !metadata.is_empty() {
+                batch.set(
+                    field,
+                    Archiver::new(metadata)
+                        .serialize()
+                        .imap_ctx(&arguments.tag, trc::location!())?,
+                );
+            } else {
+                batch.clear(field);
+            }
+            if mailbox_id.is_some() {
+                // Metadata is a mailbox property in JMAP, report it as a mailbox update
+                batch.log_container_update(SyncCollection::Email);
+            }
+            self.server
+                .commit_batch(batch)
+                .await
+                .imap_ctx(&arguments.tag, trc::location!())?;
+
+            if mailbox_id.is_none() {
+                // Server entries are stored in the principal, notify other sessions directly
+                self.server
+                    .broadcast_push_notification(PushNotification::StateChange(
+                        StateChange::new(account_id)
+                            .with_change_id(self.server.generate_snowflake_id())
+                            .with_change(DataType::Principal),
+                    ))
+                    .await;
+            }
+        }
+
+        trc::event!(
+            Imap(trc::ImapEvent::SetMetadata),
+            SpanId = self.session_id,
+            MailboxName = arguments.mailbox_name,
+            Elapsed = op_start.elapsed()
+        );
+
+        Ok(StatusResponse::completed(Command::SetMetadata)
+            .with_tag(arguments.tag)
+            .into_bytes())
+    }
+
+    async fn get_metadata_mailbox(
+        &self,
+        tag: &str,
+        mailbox_name: &str,
+    ) -> trc::Result<Option<MailboxId>> {
+        // An empty mailbox name refers to server entries
+        if mailbox_name.is_empty() {
+            return Ok(None);
+        }
+
+        // Refresh mailboxes
+        self.synchronize_mailboxes(false)
+            .await
+            .imap_ctx(tag, trc::location!())?;
+
+        if let Some(mailbox_id) = self.get_mailbox_by_name(mailbox_name) {
+            Ok(Some(mailbox_id))
+        } else {
+            Err(trc::ImapEvent::Error
+                .into_err()
+                .details("Mailbox does not exist.")
+                .code(ResponseCode::NonExistent)
+                .id(tag.to_string()))
+        }
+    }
+
+    async fn load_metadata_archive(
+        &self,
+        mailbox_id: Option<&MailboxId>,
+    ) -> trc::Result<Option<Archive<AlignedBytes>>> {
+        let (account_id, collection, document_id, field) =
+            metadata_location(self.access_token.primary_id(), mailbox_id);
+        self.server
+            .store()
+            .get_value::<Archive<AlignedBytes>>(ValueKey {
+                account_id,
+                collection: collection.into(),
+                document_id,
+                class: field,
+            })
+            .await
+            .caused_by(trc::location!())
+    }
+
+    async fn load_metadata(
+        &self,
+        mailbox_id: Option<&MailboxId>,
+    ) -> trc::Result<Option<MailboxMetadata>> {
+        if let Some(archive) = self.load_metadata_archive(mailbox_id).await? {
+            archive
+                .deserialize::<MailboxMetadata>()
+                .caused_by(trc::location!())
+                .map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    // Returns the entries visible to the user, used to detect changes for NOTIFY
+    pub async fn visible_metadata(
+        &self,
+        mailbox_id: Option<&MailboxId>,
+    ) -> trc::Result<Vec<(String, Vec<u8>)>> {
+        let can_read_shared = if let Some(mailbox_id) = mailbox_id {
+            self.check_mailbox_acl(mailbox_id.account_id, mailbox_id.mailbox_id, Acl::ReadItems)
+                .await?
+        } else {
+            true
+        };
+        let account_id = self.access_token.primary_id();
+
+        Ok(self
+            .load_metadata(mailbox_id)
+            .await?
+            .map(|metadata| {
+                metadata
+                    .visible_to(account_id)
+                    .filter(|entry| entry.owner_id.is_some() || can_read_shared)
+                    .map(|entry| (entry.name.clone(), entry.value.clone()))
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    fn get_special_use(&self, mailbox_id: &MailboxId) -> Option<Vec<u8>> {
+        self.mailboxes
+            .lock()
+            .iter()
+            .find(|account| account.account_id == mailbox_id.account_id)
+            .and_then(|account| account.mailbox_state.get(&mailbox_id.mailbox_id))
+            .and_then(|mailbox| mailbox.special_use.as_ref())
+            .map(|special_use| {
+                let mut buf = Vec::with_capacity(10);
+                special_use.serialize(&mut buf);
+                buf
+            })
+    }
+}
+
+// Server entries are stored in the principal, mailbox entries in the mailbox document
+fn metadata_location(
+    account_id: u32,
+    mailbox_id: Option<&MailboxId>,
+) -> (u32, Collection, u32, ValueClass) {
+    if let Some(mailbox_id) = mailbox_id {
+        (
+            mailbox_id.account_id,
+            Collection::Mailbox,
+            mailbox_id.mailbox_id,
+            ValueClass::from(MailboxField::Metadata),
+        )
+    } else {
+        (
+            account_id,
+            Collection::Principal,
+            0,
+            ValueClass::from(PrincipalField::Metadata),
+        )
+    }
+}
@@ -0,0 +1,452 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::Instant;
+
+use crate::{
+    core::{MailboxId, NotifyState, Session, SessionData, State},
+    op::ImapContext,
+};
+use ahash::AHashMap;
+use common::{ipc::PushNotification, listener::SessionStream};
+use directory::Permission;
+use imap_proto::{
+    Command, ResponseCode, StatusResponse,
+    protocol::{
+        list::{Attribute, ListItem, Tag},
+        metadata,
+        notify::{Arguments, Event, EventGroup, Filter},
+        status::Status,
+    },
+    receiver::Request,
+};
+use trc::AddContext;
+use types::type_state::DataType;
+use utils::map::bitmap::Bitmap;
+
+const STATUS_ITEMS: &[Status] = &[
+    Status::Messages,
+    Status::Unseen,
+    Status::UidNext,
+    Status::UidValidity,
+];
+
+impl<T: SessionStream> Session<T> {
+    pub async fn handle_notify(&mut self, request: Request<Command>) -> trc::Result<()> {
+        // Validate access
+        self.assert_has_permission(Permission::ImapNotify)?;
+
+        let op_start = Instant::now();
+        let Arguments { tag, request } = request.parse_notify(self.is_utf8)?;
+
+        // NOTIFY NONE
+        let Some(request) = request else {
+            self.notify = None;
+
+            trc::event!(
+                Imap(trc::ImapEvent::Notify),
+                SpanId = self.session_id,
+                Total = 0,
+                Elapsed = op_start.elapsed()
+            );
+
+            return self
+                .write_bytes(
+                    StatusResponse::completed(Command::Notify)
+                        .with_tag(tag)
+                        .into_bytes(),
+                )
+                .await;
+        };
+
+        // Per-message annotations (RFC 5257) are not supported
+        let mut unsupported = Vec::new();
+        for event in request.groups.iter().flat_map(|group| group.events.iter()) {
+            if matches!(event, Event::AnnotationChange) && !unsupported.contains(event) {
+                unsupported.push(*event);
+            }
+        }
+        if !unsupported.is_empty() {
+            return self
+                .write_bytes(
+                    StatusResponse::no("Unsupported notification events.")
+                        .with_tag(tag)
+                        .with_code(ResponseCode::BadEvent {
+                            events: unsupported,
+                        })
+                        .into_bytes(),
+                )
+                .await;
+        }
+
+        // Refresh mailboxes so that later notifications only report new changes
+        let data = self.state.session_data();
+        data.synchronize_mailboxes(false)
+            .await
+            .imap_ctx(&tag, trc::location!())?;
+
+        // Register with push manager
+        let push_rx = self
+            .server
+            .subscribe_push_manager(
+                &data.access_token,
+                Bitmap::from_iter([
+                    DataType::Email,
+                    DataType::Mailbox,
+                    DataType::EmailDelivery,
+                    DataType::Principal,
+                ]),
+            )
+            .await
+            .imap_ctx(&tag, trc::location!())?;
+
+        // Snapshot metadata entries so that later notifications only report changes
+        let selected_id = self.selected_mailbox_id();
+        let mut metadata = AHashMap::new();
+        for (mailbox_id, _) in data.metadata_targets(&request.groups, selected_id.as_ref()) {
+            let entries = data
+                .visible_metadata(mailbox_id.as_ref())
+                .await
+                .imap_ctx(&tag, trc::location!())?;
+            metadata.insert(mailbox_id, entries);
+        }
+
+        // Send the status of all monitored mailboxes
+        let mut buf = Vec::new();
+        if request.status {
+            let mailbox_names = data
+                .mailboxes
+                .lock()
+                .iter()
+                .flat_map(|account| account.mailbox_names.keys().cloned())
+                .collect::<Vec<_>>();
+
+            for mailbox_name in mailbox_names {
+                if data
+                    .notify_group(&request.groups, &mailbox_name, selected_id.as_ref())
+                    .is_some_and(|group| !group.filter.is_selected() && group.has_message_events())
+                    && let Ok(status) = data.status(mailbox_name, STATUS_ITEMS).await
+                {
+                    status.serialize(&mut buf, self.is_utf8);
+                }
+            }
+        }
+
+        trc::event!(
+            Imap(trc::ImapEvent::Notify),
+            SpanId = self.session_id,
+            Total = request.groups.len(),
+            Elapsed = op_start.elapsed()
+        );
+
+        self.notify = Some(NotifyState {
+            groups: request.groups,
+            push_rx,
+            metadata,
+        });
+
+        self.write_bytes(
+            StatusResponse::completed(Command::Notify)
+                .with_tag(tag)
+                .serialize(buf),
+        )
+        .await
+    }
+
+    pub async fn handle_notification(&mut self, notification: PushNotification) -> trc::Result<()> {
+        let is_rev2 = self.version.is_rev2();
+        let is_utf8 = self.is_utf8;
+        let is_qresync = self.is_qresync;
+        let (Some(notify), State::Authenticated { data } | State::Selected { data, .. }) =
+            (&mut self.notify, &self.state)
+        else {
+            return Ok(());
+        };
+        let mailbox = match &self.state {
+            State::Selected { mailbox, .. } => Some(mailbox.clone()),
+            _ => None,
+        };
+
+        let mut has_email_changes = false;
+        let mut has_mailbox_changes = false;
+        let mut has_server_changes = false;
+        match notification {
+            PushNotification::StateChange(state_change) => {
+                for type_state in state_change.types {
+                    match type_state {
+                        DataType::Email | DataType::EmailDelivery => {
+                            has_email_changes = true;
+                        }
+                        DataType::Mailbox => {
+                            has_mailbox_changes = true;
+                        }
+                        DataType::Principal => {
+                            has_server_changes = true;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            PushNotification::EmailPush(_) => {
+                has_email_changes = true;
+            }
+            PushNotification::CalendarAlert(_) => return Ok(()),
+        }
+
+        // Report changes to the selected mailbox
+        if has_email_changes
+            && let Some(selected) = &mailbox
+            && let Some(group) = notify
+                .groups
+                .iter()
+                .find(|group| group.filter.is_selected())
+            && group.has_message_events()
+        {
+            // SELECTED-DELAYED holds back all changes while there are pending expunges,
+            // these are reported by the next command that allows them (RFC 5465)
+            let is_delayed = matches!(group.filter, Filter::SelectedDelayed)
+                && data
+                    .has_pending_expunges(selected)
+                    .await
+                    .caused_by(trc::location!())?;
+
+            if !is_delayed {
+                if group.has_event(Event::FlagChange) {
+                    data.write_changes(&mailbox, false, true, is_qresync, is_rev2, is_utf8)
+                        .await?;
+                } else {
+                    data.write_mailbox_changes(selected, is_qresync)
+                        .await
+                        .caused_by(trc::location!())?;
+                }
+            }
+        }
+
+        // Report changes to all other mailboxes
+        let changes = data
+            .synchronize_mailboxes(true)
+            .await
+            .caused_by(trc::location!())?
+            .unwrap();
+        let selected_id = mailbox.as_ref().map(|mailbox| mailbox.id);
+        let group_has = |mailbox_name: &str, event: Event| {
+            data.notify_group(&notify.groups, mailbox_name, selected_id.as_ref())
+                .is_some_and(|group| group.has_event(event))
+        };
+        let mut buf = Vec::with_capacity(64);
+
+        for mailbox_name in changes.deleted {
+            if group_has(&mailbox_name, Event::MailboxName) {
+                ListItem {
+                    mailbox_name,
+                    attributes: vec![Attribute::NonExistent],
+                    tags: vec![],
+                }
+                .serialize(&mut buf, is_rev2, is_utf8, false);
+            }
+        }
+
+        for mailbox_name in changes.added {
+            if group_has(&mailbox_name, Event::MailboxName) {
+                ListItem {
+                    mailbox_name,
+                    attributes: vec![],
+                    tags: vec![],
+                }
+                .serialize(&mut buf, is_rev2, is_utf8, false);
+            }
+        }
+
+        for (old_name, mailbox_name) in changes.renamed {
+            if group_has(&mailbox_name, Event::MailboxName) {
+                ListItem {
+                    mailbox_name,
+                    attributes: vec![],
+                    tags: vec![Tag::OldName(old_name)],
+                }
+                .serialize(&mut buf, is_rev2, is_utf8, false);
+            }
+        }
+
+        for mailbox_name in changes.subscription_changed {
+            if group_has(&mailbox_name, Event::SubscriptionChange) {
+                let attributes = if data.is_subscribed(&mailbox_name) {
+                    vec![Attribute::Subscribed]
+                } else {
+                    vec![]
+                };
+                ListItem {
+                    mailbox_name,
+                    attributes,
+                    tags: vec![],
+                }
+                .serialize(&mut buf, is_rev2, is_utf8, false);
+            }
+        }
+
+        for mailbox_name in changes.changed {
+            // The selected mailbox is reported using EXISTS and EXPUNGE responses
+            if data
+                .notify_group(&notify.groups, &mailbox_name, selected_id.as_ref())
+                .is_some_and(|group| !group.filter.is_selected() && group.has_message_events())
+                && (selected_id.is_none() || data.get_mailbox_by_name(&mailbox_name) != selected_id)
+                && let Ok(status) = data.status(mailbox_name, STATUS_ITEMS).await
+            {
+                status.serialize(&mut buf, is_utf8);
+            }
+        }
+
+        // Report metadata changes using unsolicited METADATA responses (RFC 5465)
+        if has_mailbox_changes || has_server_changes {
+            for (mailbox_id, mailbox_name) in
+                data.metadata_targets(&notify.groups, selected_id.as_ref())
+            {
+                if (mailbox_id.is_some() && !has_mailbox_changes)
+                    || (mailbox_id.is_none() && !has_server_changes)
+                {
+                    continue;
+                }
+
+                let entries = data
+                    .visible_metadata(mailbox_id.as_ref())
+                    .await
+                    .caused_by(trc::location!())?;
+                let previous = notify
+                    .metadata
+                    .insert(mailbox_id, entries.clone())
+                    .unwrap_or_default();
+                let changed = changed_entries(&previous, &entries);
+                if !changed.is_empty() {
+                    metadata::ChangeResponse {
+                        mailbox_name,
+                        entries: changed,
+                    }
+                    .serialize(&mut buf, is_utf8);
+                }
+            }
+        }
+
+        if !buf.is_empty() {
+            data.write_bytes(buf).await
+        } else {
+            Ok(())
+        }
+    }
+
+    fn selected_mailbox_id(&self) -> Option<MailboxId> {
+        match &self.state {
+            State::Selected { mailbox, .. } => Some(mailbox.id),
+            _ => None,
+        }
+    }
+}
+
+impl<T: SessionStream> SessionData<T> {
+    // Returns the event group that applies to a mailbox, the selected mailbox
+    // filter always takes precedence over any other filter.
+    pub fn notify_group<'x>(
+        &self,
+        groups: &'x [EventGroup],
+        mailbox_name: &str,
+        selected_id: Option<&MailboxId>,
+    ) -> Option<&'x EventGroup> {
+        let mailbox_id = self.get_mailbox_by_name(mailbox_name);
+        if selected_id.is_some()
+            && mailbox_id.as_ref() == selected_id
+            && let Some(group) = groups.iter().find(|group| group.filter.is_selected())
+        {
+            return Some(group);
+        }
+
+        let is_personal = mailbox_name
+            .strip_prefix(self.server.core.jmap.shared_folder.as_str())
+            .is_none_or(|name| !name.is_empty() && !name.starts_with('/'));
+        groups.iter().find(|group| match &group.filter {
+            Filter::Selected | Filter::SelectedDelayed => false,
+            Filter::Personal => is_personal,
+            Filter::Inboxes => is_personal && mailbox_name.eq_ignore_ascii_case("INBOX"),
+            Filter::Subscribed => self.is_subscribed(mailbox_name),
+            Filter::Subtree(roots) => roots.iter().any(|root| {
+                is_same_mailbox(root, mailbox_name)
+                    || mailbox_name
+                        .strip_prefix(root.as_str())
+                        .is_some_and(|name| name.starts_with('/'))
+            }),
+            Filter::Mailboxes(names) => {
+                names.iter().any(|name| is_same_mailbox(name, mailbox_name))
+            }
+        })
+    }
+
+    // Returns the mailboxes monitored for metadata changes, server entries
+    // are returned with no mailbox id and an empty name.
+    pub fn metadata_targets(
+        &self,
+        groups: &[EventGroup],
+        selected_id: Option<&MailboxId>,
+    ) -> Vec<(Option<MailboxId>, String)> {
+        let mut targets = Vec::new();
+        if groups
+            .iter()
+            .any(|group| group.has_event(Event::ServerMetadataChange))
+        {
+            targets.push((None, String::new()));
+        }
+
+        let mailbox_names = self
+            .mailboxes
+            .lock()
+            .iter()
+            .flat_map(|account| account.mailbox_names.keys().cloned())
+            .collect::<Vec<_>>();
+        for mailbox_name in mailbox_names {
+            if self
+                .notify_group(groups, &mailbox_name, selected_id)
+                .is_some_and(|group| group.has_event(Event::MailboxMetadataChange))
+                && let Some(mailbox_id) = self.get_mailbox_by_name(&mailbox_name)
+                && !mailbox_id.is_virtual()
+            {
+                targets.push((Some(mailbox_id), mailbox_name));
+            }
+        }
+
+        targets
+    }
+
+    pub fn is_subscribed(&self, mailbox_name: &str) -> bool {
+        self.get_mailbox_by_name(mailbox_name)
+            .and_then(|mailbox_id| {
+                self.mailboxes
+                    .lock()
+                    .iter()
+                    .find(|account| account.account_id == mailbox_id.account_id)
+                    .and_then(|account| account.mailbox_state.get(&mailbox_id.mailbox_id))
+                    .map(|mailbox| mailbox.is_subscribed)
+            })
+            .unwrap_or(false)
+    }
+}
+
+fn changed_entries(previous: &[(String, Vec<u8>)], current: &[(String, Vec<u8>)]) -> Vec<String> {
+    let mut changed = current
+        .iter()
+        .filter(|entry| !previous.contains(entry))
+        .chain(
+            previous
+                .iter()
+                .filter(|(name, _)| !current.iter().any(|(current, _)| current == name)),
+        )
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<_>>();
+    changed.sort_unstable();
+    changed.dedup();
+    changed
+}
+
+fn is_same_mailbox(name: &str, mailbox_name: &str) -> bool {
+    name == mailbox_name
+        || (name.eq_ignore_ascii_case("INBOX") && mailbox_name.eq_ignore_ascii_case("INBOX"))
+}
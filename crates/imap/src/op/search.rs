@@ -6,14 +6,23 @@
 
 use super::{FromModSeq, ToModSeq};
 use crate::{
-    core::{ImapId, SavedSearch, SelectedMailbox, Session, SessionData},
+    core::{
+        ImapId, MailboxId, SavedSearch, SelectedMailbox, Session, SessionData, VIRTUAL_MAILBOX_ID,
+        VirtualUids,
+    },
     spawn_op,
 };
-use common::listener::SessionStream;
+use common::{MessageStoreCache, listener::SessionStream};
 use directory::Permission;
-use email::cache::{MessageCacheFetch, email::MessageCacheAccess};
+use email::{
+    cache::{MessageCacheFetch, email::MessageCacheAccess},
+    mailbox::virtual_mailbox::{
+        VirtualMailbox, VirtualMailboxStore, VirtualMailboxes, virtual_mailbox_candidates,
+    },
+    message::savedate::SaveDate,
+};
 use imap_proto::{
-    Command, StatusResponse,
+    Command, ResponseCode, ResponseType, StatusResponse,
     protocol::{
         Sequence,
         search::{self, Arguments, Comparator, Filter, Response, ResultOption},
@@ -36,6 +45,8 @@ use trc::AddContext;
 use types::{collection::SyncCollection, id::Id, keyword::Keyword};
 use utils::map::vec_map::VecMap;
 
+const MAX_RETRIES: usize = 5;
+
 impl<T: SessionStream> Session<T> {
     pub async fn handle_search(
         &mut self,
@@ -136,6 +147,15 @@ impl<T: SessionStream> SessionData<T> {
             None
         };
 
+        // Partial results are windowed over the full sorted result
+        let partial = arguments
+            .result_options
+            .iter()
+            .find_map(|option| match option {
+                ResultOption::Partial(partial) => Some(*partial),
+                _ => None,
+            });
+
         // Sort and map ids
         let mut min: Option<(u32, ImapId)> = None;
         let mut max: Option<(u32, ImapId)> = None;
@@ -152,6 +172,7 @@ impl<T: SessionStream> SessionData<T> {
             is_uid,
             arguments.result_options.contains(&ResultOption::Min),
             arguments.result_options.contains(&ResultOption::Max),
+            partial.is_some(),
             &mut min,
             &mut max,
             &mut total,
@@ -160,6 +181,17 @@ impl<T: SessionStream> SessionData<T> {
         );
         if !is_sort {
             imap_ids.sort_unstable();
+            if let (Some(saved_results), Some(_)) = (&mut saved_results, &partial) {
+                saved_results.sort_unstable_by_key(|id| if is_uid { id.uid } else { id.seqnum });
+            }
+        }
+
+        // Apply partial range, the saved result only includes the returned window
+        if let Some(partial) = &partial {
+            imap_ids = partial.apply(&imap_ids).to_vec();
+            if let Some(saved_results) = &mut saved_results {
+                *saved_results = partial.apply(saved_results).to_vec();
+            }
         }
 
         // Save results
@@ -196,6 +228,7 @@ impl<T: SessionStream> SessionData<T> {
             },
             ids: if arguments.result_options.is_empty()
                 || arguments.result_options.contains(&ResultOption::All)
+                || partial.is_some()
             {
                 imap_ids
             } else {
@@ -203,6 +236,7 @@ impl<T: SessionStream> SessionData<T> {
             },
             is_sort,
             is_esearch: arguments.is_esearch,
+            partial,
             highest_modseq,
         })
     }
@@ -215,31 +249,165 @@ impl<T: SessionStream> SessionData<T> {
         prev_saved_search: &Option<Option<Arc<Vec<ImapId>>>>,
     ) -> trc::Result<(Vec<u32>, bool)> {
         // Obtain message ids
-        let mut filters = Vec::with_capacity(imap_filter.len() + 1);
         let cache = self
             .server
             .get_cached_messages(mailbox.id.account_id)
             .await
             .caused_by(trc::location!())?;
-        let message_ids = RoaringBitmap::from_iter(
-            cache
-                .in_mailbox(mailbox.id.mailbox_id)
-                .map(|m| m.document_id),
-        );
+        let message_ids = if mailbox.id.is_virtual() {
+            RoaringBitmap::from_iter(mailbox.state.lock().id_to_imap.keys().copied())
+        } else {
+            RoaringBitmap::from_iter(
+                cache
+                    .in_mailbox(mailbox.id.mailbox_id)
+                    .map(|m| m.document_id),
+            )
+        };
+
+        self.query_messages(
+            imap_filter,
+            imap_comparator,
+            &cache,
+            message_ids,
+            mailbox.id,
+            Some(mailbox),
+            prev_saved_search,
+        )
+        .await
+    }
+
+    pub async fn query_virtual_mailbox(
+        &self,
+        account_id: u32,
+        virtual_mailbox: &VirtualMailbox,
+        cache: &MessageStoreCache,
+    ) -> trc::Result<RoaringBitmap> {
+        let imap_filter = virtual_mailbox.filters().map_err(|err| {
+            trc::ImapEvent::Error
+                .into_err()
+                .details(err)
+                .ctx(trc::Key::MailboxName, virtual_mailbox.name.clone())
+        })?;
+
+        self.query_messages(
+            imap_filter,
+            vec![],
+            cache,
+            virtual_mailbox_candidates(cache),
+            MailboxId {
+                account_id,
+                mailbox_id: VIRTUAL_MAILBOX_ID | virtual_mailbox.id,
+            },
+            None,
+            &None,
+        )
+        .await
+        .map(|(ids, _)| RoaringBitmap::from_iter(ids))
+    }
+
+    pub async fn virtual_mailbox_uids(
+        &self,
+        account_id: u32,
+        virtual_mailboxes: &VirtualMailboxes,
+        virtual_mailbox: &VirtualMailbox,
+        cache: &MessageStoreCache,
+    ) -> trc::Result<Arc<VirtualUids>> {
+        if let Some(result) = self.virtual_uids.lock().get(&virtual_mailbox.id)
+            && result.change_id == cache.emails.change_id
+            && result.uid_validity == virtual_mailbox.uid_validity
+        {
+            return Ok(result.clone());
+        }
+
+        let message_ids = self
+            .query_virtual_mailbox(account_id, virtual_mailbox, cache)
+            .await?;
+
+        // Assign UIDs to the messages that entered the result set
+        let mut try_count = 0;
+        let uids = loop {
+            let (mut uids, current) = self
+                .server
+                .get_virtual_mailbox_uids(account_id)
+                .await
+                .caused_by(trc::location!())?;
+            if !uids.synchronize(virtual_mailboxes, virtual_mailbox, &message_ids, cache) {
+                break uids;
+            }
+
+            match self
+                .server
+                .set_virtual_mailbox_uids(account_id, current, uids.clone())
+                .await
+            {
+                Ok(_) => break uids,
+                Err(err) if err.is_assertion_failure() && try_count < MAX_RETRIES => {
+                    // Another session updated the UIDs first
+                    try_count += 1;
+                }
+                Err(err) => return Err(err.caused_by(trc::location!())),
+            }
+        };
+
+        let uid_map = uids.get(virtual_mailbox.id).unwrap();
+        let result = Arc::new(VirtualUids {
+            change_id: cache.emails.change_id,
+            uid_validity: virtual_mailbox.uid_validity,
+            uid_next: uid_map.uid_next,
+            uids: uid_map
+                .messages
+                .iter()
+                .map(|message| (message.uid, message.document_id))
+                .collect(),
+        });
+        self.virtual_uids
+            .lock()
+            .insert(virtual_mailbox.id, result.clone());
+
+        Ok(result)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn query_messages(
+        &self,
+        imap_filter: Vec<Filter>,
+        imap_comparator: Vec<Comparator>,
+        cache: &MessageStoreCache,
+        message_ids: RoaringBitmap,
+        mailbox_id: MailboxId,
+        mailbox: Option<&SelectedMailbox>,
+        prev_saved_search: &Option<Option<Arc<Vec<ImapId>>>>,
+    ) -> trc::Result<(Vec<u32>, bool)> {
+        let mut filters = Vec::with_capacity(imap_filter.len() + 1);
 
         // Convert query
         let mut include_highest_modseq = false;
+        let mut save_dates = None;
         for filter in imap_filter {
             match filter {
                 Filter::Sequence(sequence, uid_filter) => {
+                    let Some(mailbox) = mailbox else {
+                        return Err(trc::ImapEvent::Error
+                            .into_err()
+                            .details("Message sequence numbers are not allowed in this context."));
+                    };
+                    if mailbox.is_uidonly && !uid_filter && !sequence.is_saved_search() {
+                        return Err(trc::ImapEvent::Error
+                            .into_err()
+                            .details("Message sequence numbers are not allowed in UIDONLY mode.")
+                            .code(ResponseCode::UidRequired)
+                            .ctx(trc::Key::Type, ResponseType::Bad));
+                    }
+
                     let mut set = RoaringBitmap::new();
                     if let (Sequence::SavedSearch, Some(prev_saved_search)) =
                         (&sequence, &prev_saved_search)
                     {
                         if let Some(prev_saved_search) = prev_saved_search {
                             let state = mailbox.state.lock();
+                            let uid_to_id = state.uid_index();
                             for imap_id in prev_saved_search.iter() {
-                                if let Some(id) = state.uid_to_id.get(&imap_id.uid) {
+                                if let Some(id) = uid_to_id.get(&imap_id.uid) {
                                     set.insert(*id);
                                 }
                             }
@@ -399,7 +567,7 @@ impl<T: SessionStream> SessionData<T> {
                         .server
                         .store()
                         .changes(
-                            mailbox.id.account_id,
+                            mailbox_id.account_id,
                             SyncCollection::Email.into(),
                             Query::from_modseq(modseq),
                         )
@@ -438,6 +606,52 @@ impl<T: SessionStream> SessionData<T> {
                             .details(format!("Failed to parse thread id '{id}'.",)));
                     }
                 }
+                Filter::SavedBefore(date) | Filter::SavedOn(date) | Filter::SavedSince(date) => {
+                    let save_dates = match &save_dates {
+                        Some(save_dates) => save_dates,
+                        None => save_dates.insert(
+                            self.server
+                                .get_save_dates(mailbox_id.account_id, mailbox_id.mailbox_id)
+                                .await
+                                .caused_by(trc::location!())?,
+                        ),
+                    };
+                    let (from, to) = match filter {
+                        Filter::SavedBefore(_) => (None, Some(date)),
+                        Filter::SavedOn(_) => (Some(date), Some(date + 86400)),
+                        _ => (Some(date), None),
+                    };
+
+                    // Messages without a save date are matched by their internal date
+                    filters.push(SearchFilter::Or);
+                    filters.push(SearchFilter::is_in_set(RoaringBitmap::from_iter(
+                        save_dates
+                            .iter()
+                            .filter(|(_, saved_at)| {
+                                let saved_at = **saved_at as i64;
+                                from.is_none_or(|from| saved_at >= from)
+                                    && to.is_none_or(|to| saved_at < to)
+                            })
+                            .map(|(document_id, _)| *document_id),
+                    )));
+                    filters.push(SearchFilter::And);
+                    filters.push(SearchFilter::Not);
+                    filters.push(SearchFilter::is_in_set(RoaringBitmap::from_iter(
+                        save_dates.keys().copied(),
+                    )));
+                    filters.push(SearchFilter::End);
+                    if let Some(from) = from {
+                        filters.push(SearchFilter::ge(EmailSearchField::ReceivedAt, from));
+                    }
+                    if let Some(to) = to {
+                        filters.push(SearchFilter::lt(EmailSearchField::ReceivedAt, to));
+                    }
+                    filters.push(SearchFilter::End);
+                    filters.push(SearchFilter::End);
+                }
+                Filter::SaveDateSupported => {
+                    filters.push(SearchFilter::is_in_set(message_ids.clone()));
+                }
                 Filter::Bcc(text) => {
                     filters.push(SearchFilter::has_text(
                         EmailSearchField::Bcc,
@@ -599,7 +813,7 @@ impl<T: SessionStream> SessionData<T> {
                 SearchQuery::new(SearchIndex::Email)
                     .with_filters(filters)
                     .with_comparators(comparators)
-                    .with_account_id(mailbox.id.account_id)
+                    .with_account_id(mailbox_id.account_id)
                     .with_mask(message_ids),
             )
             .await
@@ -631,6 +845,7 @@ impl SelectedMailbox {
         is_uid: bool,
         find_min: bool,
         find_max: bool,
+        keep_all: bool,
         min: &mut Option<(u32, ImapId)>,
         max: &mut Option<(u32, ImapId)>,
         total: &mut u32,
@@ -639,6 +854,7 @@ impl SelectedMailbox {
     ) {
         let state = self.state.lock();
         let find_min_or_max = find_min || find_max;
+        let only_min_or_max = find_min_or_max && !keep_all;
         for document_id in ids {
             if let Some((id, imap_id)) = state.map_result_id(document_id, is_uid) {
                 if find_min_or_max {
@@ -660,7 +876,8 @@ impl SelectedMailbox {
                             *max = Some((id, imap_id));
                         }
                     }
-                } else {
+                }
+                if !only_min_or_max {
                     imap_ids.push(id);
                     if let Some(r) = saved_results.as_mut() {
                         r.push(imap_id)
@@ -669,7 +886,7 @@ impl SelectedMailbox {
                 *total += 1;
             }
         }
-        if find_min || find_max {
+        if only_min_or_max {
             for (id, imap_id) in [min, max].into_iter().flatten() {
                 imap_ids.push(*id);
                 if let Some(r) = saved_results.as_mut() {
@@ -9,7 +9,7 @@ use crate::core::{SavedSearch, SelectedMailbox, Session, State};
 use common::listener::SessionStream;
 use directory::Permission;
 use imap_proto::{
-    Command, ResponseCode, StatusResponse,
+    Command, ResponseCode, ResponseType, StatusResponse,
     protocol::{
         ImapResponse, Sequence, fetch,
         list::ListItem,
@@ -43,7 +43,7 @@ impl<T: SessionStream> Session<T> {
         if let Some(mailbox) = data.get_mailbox_by_name(&arguments.mailbox_name) {
             // Try obtaining the mailbox from the cache
             let state = data
-                .fetch_messages(&mailbox, None)
+                .fetch_messages(&mailbox, None, self.is_uidonly)
                 .await
                 .imap_ctx(&arguments.tag, trc::location!())?
                 .unwrap();
@@ -68,6 +68,7 @@ impl<T: SessionStream> Session<T> {
                 saved_search: parking_lot::Mutex::new(SavedSearch::None),
                 is_select,
                 is_condstore,
+                is_uidonly: self.is_uidonly,
             });
 
             // Validate QRESYNC arguments
@@ -77,6 +78,13 @@ impl<T: SessionStream> Session<T> {
                         .into_err()
                         .details("QRESYNC is not enabled.")
                         .id(arguments.tag));
+                } else if self.is_uidonly && qresync.seq_match.is_some() {
+                    return Err(trc::ImapEvent::Error
+                        .into_err()
+                        .details("Message sequence numbers are not allowed in UIDONLY mode.")
+                        .code(ResponseCode::UidRequired)
+                        .ctx(trc::Key::Type, ResponseType::Bad)
+                        .id(arguments.tag));
                 }
                 if qresync.uid_validity == mailbox_state.uid_validity as u32 {
                     // Send flags for changed messages
@@ -93,6 +101,7 @@ impl<T: SessionStream> Session<T> {
                             attributes: vec![fetch::Attribute::Flags],
                             changed_since: qresync.modseq.into(),
                             include_vanished: true,
+                            partial: None,
                         },
                         mailbox.clone(),
                         true,
@@ -88,6 +88,11 @@ impl<T: SessionStream> SessionData<T> {
                 .into_bytes());
         }
 
+        // Enforce message limit
+        let is_limited = self
+            .apply_message_limit(&mut ids, is_uid)
+            .imap_ctx(&arguments.tag, trc::location!())?;
+
         // Verify that the user can modify messages in this mailbox.
         if !self
             .check_mailbox_acl(
@@ -164,6 +169,8 @@ impl<T: SessionStream> SessionData<T> {
         .with_tag(arguments.tag);
         if let Some(response_code) = response_code {
             response = response.with_code(response_code)
+        } else if is_limited {
+            response = response.with_code(ResponseCode::Limit)
         }
         if ids.is_empty() {
             trc::event!(
@@ -183,6 +190,7 @@ impl<T: SessionStream> SessionData<T> {
             return Ok(response.into_bytes());
         }
         let mut items = Response {
+            is_uidonly: mailbox.is_uidonly,
             items: Vec::with_capacity(ids.len()),
         };
 
@@ -319,18 +327,24 @@ impl<T: SessionStream> SessionData<T> {
             batch.commit_point();
 
             // Add item to response
+            // UIDFETCH responses are keyed by UID
+            let (id, is_uid) = if !mailbox.is_uidonly {
+                (imap_id.seqnum, is_uid)
+            } else {
+                (imap_id.uid, false)
+            };
             if !arguments.is_silent {
                 let mut data_items = vec![DataItem::Flags { flags }];
                 if is_uid {
                     data_items.push(DataItem::Uid { uid: imap_id.uid });
                 }
                 items.items.push(FetchItem {
-                    id: imap_id.seqnum,
+                    id,
                     items: data_items,
                 });
             } else if is_condstore {
                 items.items.push(FetchItem {
-                    id: imap_id.seqnum,
+                    id,
                     items: if is_uid {
                         vec![DataItem::Uid { uid: imap_id.uid }]
                     } else {
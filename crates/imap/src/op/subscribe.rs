@@ -62,6 +62,13 @@ impl<T: SessionStream> SessionData<T> {
 
         // Validate mailbox
         let (account_id, mailbox_id) = match self.get_mailbox_by_name(&mailbox_name) {
+            Some(mailbox) if mailbox.is_virtual() => {
+                return Err(trc::ImapEvent::Error
+                    .into_err()
+                    .details("Virtual mailboxes are always subscribed.")
+                    .code(ResponseCode::Cannot)
+                    .id(tag));
+            }
             Some(mailbox) => (mailbox.account_id, mailbox.mailbox_id),
             None => {
                 return Err(trc::ImapEvent::Error
@@ -0,0 +1,230 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::Instant;
+
+use crate::{
+    core::{MailboxId, Session, SessionData},
+    op::ImapContext,
+    spawn_op,
+};
+use common::listener::SessionStream;
+use directory::Permission;
+use email::{
+    cache::MessageCacheFetch,
+    message::urlauth::{ImapUrl, URLAUTH_MECHANISM, UrlAuth},
+};
+use imap_proto::{
+    Command, ResponseCode, StatusResponse,
+    protocol::urlauth::{FetchArguments, FetchResponse, GenArguments, GenResponse, ResetArguments},
+    receiver::Request,
+};
+
+impl<T: SessionStream> Session<T> {
+    pub async fn handle_genurlauth(&mut self, request: Request<Command>) -> trc::Result<()> {
+        // Validate access
+        self.assert_has_permission(Permission::ImapGenUrlAuth)?;
+
+        let arguments = request.parse_genurlauth()?;
+        let data = self.state.session_data();
+
+        spawn_op!(data, {
+            let response = data.genurlauth(arguments).await?;
+            data.write_bytes(response).await
+        })
+    }
+
+    pub async fn handle_resetkey(&mut self, request: Request<Command>) -> trc::Result<()> {
+        // Validate access
+        self.assert_has_permission(Permission::ImapResetKey)?;
+
+        let arguments = request.parse_resetkey(self.is_utf8)?;
+        let data = self.state.session_data();
+
+        spawn_op!(data, {
+            let response = data.resetkey(arguments).await?;
+            data.write_bytes(response).await
+        })
+    }
+
+    pub async fn handle_urlfetch(&mut self, request: Request<Command>) -> trc::Result<()> {
+        // Validate access
+        self.assert_has_permission(Permission::ImapUrlFetch)?;
+
+        let arguments = request.parse_urlfetch()?;
+        let data = self.state.session_data();
+
+        spawn_op!(data, {
+            let response = data.urlfetch(arguments).await?;
+            data.write_bytes(response).await
+        })
+    }
+}
+
+impl<T: SessionStream> SessionData<T> {
+    pub async fn genurlauth(&self, arguments: GenArguments) -> trc::Result<Vec<u8>> {
+        let op_start = Instant::now();
+
+        // Refresh mailboxes
+        self.synchronize_mailboxes(false)
+            .await
+            .imap_ctx(&arguments.tag, trc::location!())?;
+
+        let mut urls = Vec::with_capacity(arguments.urls.len());
+        for (url, mechanism) in &arguments.urls {
+            if !mechanism.eq_ignore_ascii_case(URLAUTH_MECHANISM) {
+                return Err(trc::ImapEvent::Error
+                    .into_err()
+                    .details(format!("Unsupported URLAUTH mechanism {mechanism:?}."))
+                    .id(arguments.tag));
+            }
+
+            // Only URLs to mailboxes owned by the current user can be authorized
+            let url = ImapUrl::parse(url)
+                .filter(|url| {
+                    url.access.is_some()
+                        && !url.has_token()
+                        && url
+                            .user
+                            .as_ref()
+                            .is_some_and(|user| user == &self.access_token.name)
+                })
+                .ok_or_else(|| {
+                    trc::ImapEvent::Error
+                        .into_err()
+                        .details(format!("Invalid URL {url:?}."))
+                        .id(arguments.tag.clone())
+                })?;
+            let mailbox = self.get_urlauth_mailbox(&arguments.tag, &url.mailbox)?;
+            if url.uid_validity.is_some_and(|uid_validity| {
+                self.mailbox_state(&mailbox)
+                    .is_none_or(|state| state.uid_validity as u32 != uid_validity)
+            }) {
+                return Err(trc::ImapEvent::Error
+                    .into_err()
+                    .details("UIDVALIDITY does not match.")
+                    .id(arguments.tag));
+            }
+
+            urls.push(
+                self.server
+                    .urlauth_generate(mailbox.account_id, mailbox.mailbox_id, &url)
+                    .await
+                    .imap_ctx(&arguments.tag, trc::location!())?,
+            );
+        }
+
+        trc::event!(
+            Imap(trc::ImapEvent::GenUrlAuth),
+            SpanId = self.session_id,
+            Total = urls.len(),
+            Elapsed = op_start.elapsed()
+        );
+
+        Ok(StatusResponse::completed(Command::GenUrlAuth)
+            .with_tag(arguments.tag)
+            .serialize(GenResponse { urls }.into_bytes()))
+    }
+
+    pub async fn resetkey(&self, arguments: ResetArguments) -> trc::Result<Vec<u8>> {
+        let op_start = Instant::now();
+        let account_id = self.access_token.primary_id();
+
+        if let Some(mechanism) = arguments
+            .mechanisms
+            .iter()
+            .find(|mechanism| !mechanism.eq_ignore_ascii_case(URLAUTH_MECHANISM))
+        {
+            return Err(trc::ImapEvent::Error
+                .into_err()
+                .details(format!("Unsupported URLAUTH mechanism {mechanism:?}."))
+                .id(arguments.tag));
+        }
+
+        // Without a mailbox name, the keys of all mailboxes are reset
+        let mailbox_ids = if let Some(mailbox_name) = &arguments.mailbox_name {
+            self.synchronize_mailboxes(false)
+                .await
+                .imap_ctx(&arguments.tag, trc::location!())?;
+            vec![
+                self.get_urlauth_mailbox(&arguments.tag, mailbox_name)?
+                    .mailbox_id,
+            ]
+        } else {
+            self.server
+                .get_cached_messages(account_id)
+                .await
+                .imap_ctx(&arguments.tag, trc::location!())?
+                .mailboxes
+                .items
+                .iter()
+                .map(|mailbox| mailbox.document_id)
+                .collect()
+        };
+
+        self.server
+            .urlauth_reset(account_id, &mailbox_ids)
+            .await
+            .imap_ctx(&arguments.tag, trc::location!())?;
+
+        trc::event!(
+            Imap(trc::ImapEvent::ResetKey),
+            SpanId = self.session_id,
+            MailboxName = arguments.mailbox_name,
+            Total = mailbox_ids.len(),
+            Elapsed = op_start.elapsed()
+        );
+
+        Ok(StatusResponse::completed(Command::ResetKey)
+            .with_tag(arguments.tag)
+            .into_bytes())
+    }
+
+    pub async fn urlfetch(&self, arguments: FetchArguments) -> trc::Result<Vec<u8>> {
+        let op_start = Instant::now();
+
+        // Unauthorized or invalid URLs are returned as NIL
+        let mut items = Vec::with_capacity(arguments.urls.len());
+        for url in arguments.urls {
+            let contents =
+                if let Some(imap_url) = ImapUrl::parse(&url).filter(|url| url.has_token()) {
+                    self.server
+                        .urlauth_fetch(&imap_url, Some(&self.access_token.name), false)
+                        .await
+                        .imap_ctx(&arguments.tag, trc::location!())?
+                } else {
+                    None
+                };
+            items.push((url, contents));
+        }
+
+        trc::event!(
+            Imap(trc::ImapEvent::UrlFetch),
+            SpanId = self.session_id,
+            Total = items
+                .iter()
+                .filter(|(_, contents)| contents.is_some())
+                .count(),
+            Elapsed = op_start.elapsed()
+        );
+
+        Ok(StatusResponse::completed(Command::UrlFetch)
+            .with_tag(arguments.tag)
+            .serialize(FetchResponse { items }.into_bytes()))
+    }
+
+    fn get_urlauth_mailbox(&self, tag: &str, mailbox_name: &str) -> trc::Result<MailboxId> {
+        self.get_mailbox_by_name(mailbox_name)
+            .filter(|mailbox| mailbox.account_id == self.access_token.primary_id())
+            .ok_or_else(|| {
+                trc::ImapEvent::Error
+                    .into_err()
+                    .details("Mailbox does not exist.")
+                    .code(ResponseCode::NonExistent)
+                    .id(tag.to_string())
+            })
+    }
+}
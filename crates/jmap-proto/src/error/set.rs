@@ -89,6 +89,10 @@ pub enum SetErrorType {
     NodeHasChildren,
     #[serde(rename = "calendarHasEvent")]
     CalendarHasEvent,
+    #[serde(rename = "mdnAlreadySent")]
+    MdnAlreadySent,
+    #[serde(rename = "taskListHasTask")]
+    TaskListHasTask,
 }
 
 impl SetErrorType {
@@ -122,6 +126,8 @@ impl SetErrorType {
             SetErrorType::AddressBookHasContents => "addressBookHasContents",
             SetErrorType::NodeHasChildren => "nodeHasChildren",
             SetErrorType::CalendarHasEvent => "calendarHasEvent",
+            SetErrorType::MdnAlreadySent => "mdnAlreadySent",
+            SetErrorType::TaskListHasTask => "taskListHasTask",
         }
     }
 }
@@ -207,6 +213,10 @@ impl<T: Property> SetError<T> {
     pub fn calendar_has_event() -> Self {
         Self::new(SetErrorType::CalendarHasEvent).with_description("Calendar is not empty.")
     }
+
+    pub fn task_list_has_task() -> Self {
+        Self::new(SetErrorType::TaskListHasTask).with_description("Task list is not empty.")
+    }
 }
 
 impl<T: Property> From<T> for InvalidProperty<T> {
@@ -309,7 +309,7 @@ impl Default for CalendarEventComparator {
     }
 }
 
-struct LocalTime(JSCalendarDateTime);
+pub(crate) struct LocalTime(pub JSCalendarDateTime);
 
 impl<'de> serde::Deserialize<'de> for LocalTime {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
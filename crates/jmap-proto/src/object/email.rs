@@ -30,6 +30,17 @@ pub enum EmailProperty {
     Size,
     ReceivedAt,
 
+    // Snooze
+    Snoozed,
+    Until,
+    MoveToMailboxId,
+
+    // S/MIME
+    SmimeStatus,
+    SmimeErrors,
+    SmimeVerifiedAt,
+    SmimeStatusAtDelivery,
+
     // Address
     Name,
     Email,
@@ -167,6 +178,13 @@ impl Property for EmailProperty {
             EmailProperty::Value => "value",
             EmailProperty::IsEncodingProblem => "isEncodingProblem",
             EmailProperty::IsTruncated => "isTruncated",
+            EmailProperty::Snoozed => "snoozed",
+            EmailProperty::Until => "until",
+            EmailProperty::MoveToMailboxId => "moveToMailboxId",
+            EmailProperty::SmimeStatus => "smimeStatus",
+            EmailProperty::SmimeErrors => "smimeErrors",
+            EmailProperty::SmimeVerifiedAt => "smimeVerifiedAt",
+            EmailProperty::SmimeStatusAtDelivery => "smimeStatusAtDelivery",
             EmailProperty::Header(header) => return header.to_string().into(),
             EmailProperty::Keyword(keyword) => return keyword.to_string().into(),
             EmailProperty::IdValue(id) => return id.to_string().into(),
@@ -183,13 +201,14 @@ impl Element for EmailValue {
     fn try_parse<P>(key: &Key<'_, Self::Property>, value: &str) -> Option<Self> {
         if let Key::Property(prop) = key {
             match prop.patch_or_prop() {
-                EmailProperty::Id | EmailProperty::ThreadId | EmailProperty::MailboxIds => {
-                    match parse_ref(value) {
-                        MaybeReference::Value(v) => Some(EmailValue::Id(v)),
-                        MaybeReference::Reference(v) => Some(EmailValue::IdReference(v)),
-                        MaybeReference::ParseError => None,
-                    }
-                }
+                EmailProperty::Id
+                | EmailProperty::ThreadId
+                | EmailProperty::MailboxIds
+                | EmailProperty::MoveToMailboxId => match parse_ref(value) {
+                    MaybeReference::Value(v) => Some(EmailValue::Id(v)),
+                    MaybeReference::Reference(v) => Some(EmailValue::IdReference(v)),
+                    MaybeReference::ParseError => None,
+                },
                 EmailProperty::BlobId => match parse_ref(value) {
                     MaybeReference::Value(v) => Some(EmailValue::BlobId(v)),
                     MaybeReference::Reference(v) => Some(EmailValue::IdReference(v)),
@@ -200,7 +219,8 @@ impl Element for EmailValue {
                     ..
                 })
                 | EmailProperty::ReceivedAt
-                | EmailProperty::SentAt => UTCDate::from_str(value).ok().map(EmailValue::Date),
+                | EmailProperty::SentAt
+                | EmailProperty::Until => UTCDate::from_str(value).ok().map(EmailValue::Date),
                 _ => None,
             }
         } else {
@@ -260,7 +280,14 @@ impl EmailProperty {
                 "isEncodingProblem" => EmailProperty::IsEncodingProblem,
                 "isTruncated" => EmailProperty::IsTruncated,
                 "hasAttachment" => EmailProperty::HasAttachment,
-                "preview" => EmailProperty::Preview
+                "preview" => EmailProperty::Preview,
+                "snoozed" => EmailProperty::Snoozed,
+                "until" => EmailProperty::Until,
+                "moveToMailboxId" => EmailProperty::MoveToMailboxId,
+                "smimeStatus" => EmailProperty::SmimeStatus,
+                "smimeErrors" => EmailProperty::SmimeErrors,
+                "smimeVerifiedAt" => EmailProperty::SmimeVerifiedAt,
+                "smimeStatusAtDelivery" => EmailProperty::SmimeStatusAtDelivery
         )
         .or_else(|| {
             if let Some(header) = value.strip_prefix("header:") {
@@ -536,6 +563,9 @@ pub enum EmailFilter {
     SentAfter(UTCDate),
     InThread(Id),
     Id(Vec<Id>),
+    HasSmime(bool),
+    HasVerifiedSmime(bool),
+    HasVerifiedSmimeAtDelivery(bool),
     _T(String),
 }
 
@@ -633,6 +663,15 @@ impl<'de> DeserializeArguments<'de> for EmailFilter {
             b"id" => {
                 *self = EmailFilter::Id(map.next_value()?);
             },
+            b"hasSmime" => {
+                *self = EmailFilter::HasSmime(map.next_value()?);
+            },
+            b"hasVerifiedSmime" => {
+                *self = EmailFilter::HasVerifiedSmime(map.next_value()?);
+            },
+            b"hasVerifiedSmimeAtDelivery" => {
+                *self = EmailFilter::HasVerifiedSmimeAtDelivery(map.next_value()?);
+            },
             _ => {
                 *self = EmailFilter::_T(key.to_string());
                 let _ = map.next_value::<serde::de::IgnoredAny>()?;
@@ -766,6 +805,9 @@ impl Display for EmailFilter {
             EmailFilter::SentAfter(_) => "sentAfter",
             EmailFilter::InThread(_) => "inThread",
             EmailFilter::Id(_) => "id",
+            EmailFilter::HasSmime(_) => "hasSmime",
+            EmailFilter::HasVerifiedSmime(_) => "hasVerifiedSmime",
+            EmailFilter::HasVerifiedSmimeAtDelivery(_) => "hasVerifiedSmimeAtDelivery",
             EmailFilter::_T(v) => v.as_str(),
         })
     }
@@ -834,6 +876,8 @@ impl EmailFilter {
                 | EmailFilter::Id(_)
                 | EmailFilter::SentBefore(_)
                 | EmailFilter::SentAfter(_)
+                | EmailFilter::HasSmime(_)
+                | EmailFilter::HasVerifiedSmimeAtDelivery(_)
         )
     }
 }
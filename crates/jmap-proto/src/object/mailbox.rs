@@ -31,6 +31,7 @@ pub enum MailboxProperty {
     ShareWith,
     MyRights,
     IsSubscribed,
+    Metadata,
 
     // Other
     IdValue(Id),
@@ -88,6 +89,7 @@ impl Property for MailboxProperty {
             MailboxProperty::UnreadEmails => "unreadEmails",
             MailboxProperty::UnreadThreads => "unreadThreads",
             MailboxProperty::ShareWith => "shareWith",
+            MailboxProperty::Metadata => "metadata",
             MailboxProperty::Rights(mailbox_right) => mailbox_right.as_str(),
             MailboxProperty::Pointer(json_pointer) => return json_pointer.to_string().into(),
             MailboxProperty::IdValue(id) => return id.to_string().into(),
@@ -166,6 +168,7 @@ impl MailboxProperty {
             b"mayDelete" => MailboxProperty::Rights(MailboxRight::MayDelete),
             b"mayShare" => MailboxProperty::Rights(MailboxRight::MayShare),
             b"isSubscribed" => MailboxProperty::IsSubscribed,
+            b"metadata" => MailboxProperty::Metadata,
         )
         .or_else(|| {
             if allow_patch && value.contains('/') {
@@ -0,0 +1,197 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use jmap_tools::{Key, Property};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use types::id::Id;
+use utils::map::vec_map::VecMap;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Mdn {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub for_email_id: Option<Id>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text_body: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_original_message: Option<bool>,
+
+    #[serde(rename = "reportingUA")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reporting_ua: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disposition: Option<MdnDisposition>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mdn_gateway: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_recipient: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub final_recipient: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_message_id: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<Vec<String>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extension_fields: Option<VecMap<String, String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MdnDisposition {
+    pub action_mode: MdnActionMode,
+    pub sending_mode: MdnSendingMode,
+    #[serde(rename = "type")]
+    pub type_: MdnDispositionType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MdnActionMode {
+    #[serde(rename = "manual-action")]
+    Manual,
+    #[serde(rename = "automatic-action")]
+    Automatic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MdnSendingMode {
+    #[serde(rename = "mdn-sent-manually")]
+    Manual,
+    #[serde(rename = "mdn-sent-automatically")]
+    Automatic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MdnDispositionType {
+    Deleted,
+    Dispatched,
+    Displayed,
+    Processed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MdnProperty {
+    ForEmailId,
+    Subject,
+    TextBody,
+    IncludeOriginalMessage,
+    ReportingUA,
+    Disposition,
+    MdnGateway,
+    OriginalRecipient,
+    FinalRecipient,
+    OriginalMessageId,
+    Error,
+    ExtensionFields,
+}
+
+impl Property for MdnProperty {
+    fn try_parse(_: Option<&Key<'_, Self>>, value: &str) -> Option<Self> {
+        MdnProperty::parse(value)
+    }
+
+    fn to_cow(&self) -> Cow<'static, str> {
+        match self {
+            MdnProperty::ForEmailId => "forEmailId",
+            MdnProperty::Subject => "subject",
+            MdnProperty::TextBody => "textBody",
+            MdnProperty::IncludeOriginalMessage => "includeOriginalMessage",
+            MdnProperty::ReportingUA => "reportingUA",
+            MdnProperty::Disposition => "disposition",
+            MdnProperty::MdnGateway => "mdnGateway",
+            MdnProperty::OriginalRecipient => "originalRecipient",
+            MdnProperty::FinalRecipient => "finalRecipient",
+            MdnProperty::OriginalMessageId => "originalMessageId",
+            MdnProperty::Error => "error",
+            MdnProperty::ExtensionFields => "extensionFields",
+        }
+        .into()
+    }
+}
+
+impl MdnProperty {
+    fn parse(value: &str) -> Option<Self> {
+        hashify::tiny_map!(value.as_bytes(),
+            b"forEmailId" => MdnProperty::ForEmailId,
+            b"subject" => MdnProperty::Subject,
+            b"textBody" => MdnProperty::TextBody,
+            b"includeOriginalMessage" => MdnProperty::IncludeOriginalMessage,
+            b"reportingUA" => MdnProperty::ReportingUA,
+            b"disposition" => MdnProperty::Disposition,
+            b"mdnGateway" => MdnProperty::MdnGateway,
+            b"originalRecipient" => MdnProperty::OriginalRecipient,
+            b"finalRecipient" => MdnProperty::FinalRecipient,
+            b"originalMessageId" => MdnProperty::OriginalMessageId,
+            b"error" => MdnProperty::Error,
+            b"extensionFields" => MdnProperty::ExtensionFields,
+        )
+    }
+}
+
+impl MdnActionMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MdnActionMode::Manual => "manual-action",
+            MdnActionMode::Automatic => "automatic-action",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        hashify::tiny_map_ignore_case!(value.as_bytes(),
+            "manual-action" => MdnActionMode::Manual,
+            "automatic-action" => MdnActionMode::Automatic,
+        )
+    }
+}
+
+impl MdnSendingMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MdnSendingMode::Manual => "MDN-sent-manually",
+            MdnSendingMode::Automatic => "MDN-sent-automatically",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        hashify::tiny_map_ignore_case!(value.as_bytes(),
+            "mdn-sent-manually" => MdnSendingMode::Manual,
+            "mdn-sent-automatically" => MdnSendingMode::Automatic,
+        )
+    }
+}
+
+impl MdnDispositionType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MdnDispositionType::Deleted => "deleted",
+            MdnDispositionType::Dispatched => "dispatched",
+            MdnDispositionType::Displayed => "displayed",
+            MdnDispositionType::Processed => "processed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        hashify::tiny_map_ignore_case!(value.as_bytes(),
+            "deleted" => MdnDispositionType::Deleted,
+            "dispatched" => MdnDispositionType::Dispatched,
+            "displayed" => MdnDispositionType::Displayed,
+            "processed" => MdnDispositionType::Processed,
+        )
+    }
+}
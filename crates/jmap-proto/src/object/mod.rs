@@ -21,6 +21,7 @@ pub mod email_submission;
 pub mod file_node;
 pub mod identity;
 pub mod mailbox;
+pub mod mdn;
 pub mod participant_identity;
 pub mod principal;
 pub mod push_subscription;
@@ -28,8 +29,10 @@ pub mod quota;
 pub mod search_snippet;
 pub mod share_notification;
 pub mod sieve;
+pub mod task;
 pub mod thread;
 pub mod vacation_response;
+pub mod virtual_mailbox;
 
 pub trait JmapObject: std::fmt::Debug {
     type Property: Property + JmapObjectId + FromStr + Debug + Sync + Send;
@@ -0,0 +1,286 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use crate::{
+    method::set::SetRequest,
+    object::{
+        JmapObject,
+        calendar::{CalendarProperty, CalendarValue},
+        calendar_event::{CalendarEventSetArguments, LocalTime},
+    },
+    request::{MaybeInvalid, deserialize::DeserializeArguments},
+};
+use calcard::{
+    common::timezone::Tz,
+    jscalendar::{JSCalendarDateTime, JSCalendarProperty, JSCalendarValue},
+};
+use jmap_tools::{Key, Value};
+use std::{borrow::Cow, str::FromStr};
+use types::{blob::BlobId, id::Id};
+
+pub const TASK_LIST_ID: &str = "taskListId";
+
+#[derive(Debug, Clone, Default)]
+pub struct TaskList;
+
+#[derive(Debug, Clone, Default)]
+pub struct Task;
+
+impl JmapObject for TaskList {
+    type Property = CalendarProperty;
+
+    type Element = CalendarValue;
+
+    type Id = Id;
+
+    type Filter = ();
+
+    type Comparator = ();
+
+    type GetArguments = ();
+
+    type SetArguments<'de> = TaskListSetArguments;
+
+    type QueryArguments = ();
+
+    type CopyArguments = ();
+
+    type ParseArguments = ();
+
+    const ID_PROPERTY: Self::Property = CalendarProperty::Id;
+}
+
+impl JmapObject for Task {
+    type Property = JSCalendarProperty<Id>;
+
+    type Element = JSCalendarValue<Id, BlobId>;
+
+    type Id = Id;
+
+    type Filter = TaskFilter;
+
+    type Comparator = TaskComparator;
+
+    type GetArguments = ();
+
+    type SetArguments<'de> = CalendarEventSetArguments;
+
+    type QueryArguments = TaskQueryArguments;
+
+    type CopyArguments = ();
+
+    type ParseArguments = ();
+
+    const ID_PROPERTY: Self::Property = JSCalendarProperty::Id;
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TaskListSetArguments {
+    pub on_destroy_remove_tasks: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskFilter {
+    InTaskList(MaybeInvalid<Id>),
+    After(JSCalendarDateTime),
+    Before(JSCalendarDateTime),
+    Text(String),
+    Title(String),
+    Description(String),
+    Uid(String),
+    _T(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskComparator {
+    Start,
+    Uid,
+    _T(String),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TaskQueryArguments {
+    pub time_zone: Option<Tz>,
+}
+
+impl<'de> DeserializeArguments<'de> for TaskListSetArguments {
+    fn deserialize_argument<A>(&mut self, key: &str, map: &mut A) -> Result<(), A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        if key == "onDestroyRemoveTasks" {
+            self.on_destroy_remove_tasks = map.next_value()?;
+        } else {
+            let _ = map.next_value::<serde::de::IgnoredAny>()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'de> DeserializeArguments<'de> for TaskFilter {
+    fn deserialize_argument<A>(&mut self, key: &str, map: &mut A) -> Result<(), A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        hashify::fnc_map!(key.as_bytes(),
+            b"inTaskList" => {
+                *self = TaskFilter::InTaskList(map.next_value()?);
+            },
+            b"after" => {
+                *self = TaskFilter::After(map.next_value::<LocalTime>()?.0);
+            },
+            b"before" => {
+                *self = TaskFilter::Before(map.next_value::<LocalTime>()?.0);
+            },
+            b"text" => {
+                *self = TaskFilter::Text(map.next_value::<Cow<str>>()?.to_lowercase());
+            },
+            b"title" => {
+                *self = TaskFilter::Title(map.next_value::<Cow<str>>()?.to_lowercase());
+            },
+            b"description" => {
+                *self = TaskFilter::Description(map.next_value::<Cow<str>>()?.to_lowercase());
+            },
+            b"uid" => {
+                *self = TaskFilter::Uid(map.next_value()?);
+            },
+            _ => {
+                *self = TaskFilter::_T(key.to_string());
+                let _ = map.next_value::<serde::de::IgnoredAny>()?;
+            }
+        );
+        Ok(())
+    }
+}
+
+impl<'de> DeserializeArguments<'de> for TaskComparator {
+    fn deserialize_argument<A>(&mut self, key: &str, map: &mut A) -> Result<(), A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        if key == "property" {
+            let value = map.next_value::<Cow<str>>()?;
+            hashify::fnc_map!(value.as_bytes(),
+                b"start" => {
+                    *self = TaskComparator::Start;
+                },
+                b"uid" => {
+                    *self = TaskComparator::Uid;
+                },
+                _ => {
+                    *self = TaskComparator::_T(value.to_string());
+                }
+            );
+        } else {
+            let _ = map.next_value::<serde::de::IgnoredAny>()?;
+        }
+        Ok(())
+    }
+}
+
+impl<'de> DeserializeArguments<'de> for TaskQueryArguments {
+    fn deserialize_argument<A>(&mut self, key: &str, map: &mut A) -> Result<(), A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        if key == "timeZone" {
+            self.time_zone = map
+                .next_value::<Option<&str>>()?
+                .and_then(|s| Tz::from_str(s).ok());
+        } else {
+            let _ = map.next_value::<serde::de::IgnoredAny>()?;
+        }
+        Ok(())
+    }
+}
+
+impl TaskFilter {
+    pub fn into_string(self) -> Cow<'static, str> {
+        match self {
+            TaskFilter::InTaskList(_) => "inTaskList",
+            TaskFilter::After(_) => "after",
+            TaskFilter::Before(_) => "before",
+            TaskFilter::Text(_) => "text",
+            TaskFilter::Title(_) => "title",
+            TaskFilter::Description(_) => "description",
+            TaskFilter::Uid(_) => "uid",
+            TaskFilter::_T(s) => return Cow::Owned(s),
+        }
+        .into()
+    }
+}
+
+impl TaskComparator {
+    pub fn into_string(self) -> Cow<'static, str> {
+        match self {
+            TaskComparator::Start => "start",
+            TaskComparator::Uid => "uid",
+            TaskComparator::_T(s) => return Cow::Owned(s),
+        }
+        .into()
+    }
+}
+
+impl Default for TaskFilter {
+    fn default() -> Self {
+        TaskFilter::_T(String::new())
+    }
+}
+
+impl Default for TaskComparator {
+    fn default() -> Self {
+        TaskComparator::_T(String::new())
+    }
+}
+
+impl SetRequest<'_, Task> {
+    // Tasks belong to exactly one task list, which is stored as the
+    // calendarIds set of the underlying calendar object. The "taskListId"
+    // property is rewritten before id references are resolved so that
+    // creation ids (e.g. "#list") are supported.
+    pub(crate) fn expand_task_list_ids(&mut self) {
+        for obj in self
+            .create
+            .iter_mut()
+            .flat_map(|create| create.values_mut())
+            .chain(
+                self.update
+                    .iter_mut()
+                    .flat_map(|update| update.values_mut()),
+            )
+        {
+            let Value::Object(obj) = obj else {
+                continue;
+            };
+
+            for (key, value) in obj.as_mut_vec() {
+                if *key != TASK_LIST_ID {
+                    continue;
+                }
+
+                let id = match value {
+                    Value::Str(id) => {
+                        if let Some(id_ref) = id.strip_prefix('#') {
+                            Some(JSCalendarProperty::IdReference(id_ref.to_string()))
+                        } else {
+                            Id::from_str(id).ok().map(JSCalendarProperty::IdValue)
+                        }
+                    }
+                    Value::Element(JSCalendarValue::Id(id)) => {
+                        Some(JSCalendarProperty::IdValue(*id))
+                    }
+                    _ => None,
+                };
+
+                if let Some(id) = id {
+                    *key = Key::Property(JSCalendarProperty::CalendarIds);
+                    *value = Value::Object(vec![(Key::Property(id), Value::Bool(true))].into());
+                }
+            }
+        }
+    }
+}
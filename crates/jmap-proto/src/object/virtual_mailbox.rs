@@ -0,0 +1,151 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use crate::object::{AnyId, JmapObject, JmapObjectId};
+use jmap_tools::{Element, Key, Property};
+use std::{borrow::Cow, str::FromStr};
+use types::id::Id;
+
+#[derive(Debug, Clone, Default)]
+pub struct VirtualMailbox;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum VirtualMailboxProperty {
+    Id,
+    Name,
+    Query,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum VirtualMailboxValue {
+    Id(Id),
+}
+
+impl Property for VirtualMailboxProperty {
+    fn try_parse(_: Option<&Key<'_, Self>>, value: &str) -> Option<Self> {
+        VirtualMailboxProperty::parse(value)
+    }
+
+    fn to_cow(&self) -> Cow<'static, str> {
+        match self {
+            VirtualMailboxProperty::Id => "id",
+            VirtualMailboxProperty::Name => "name",
+            VirtualMailboxProperty::Query => "query",
+        }
+        .into()
+    }
+}
+
+impl Element for VirtualMailboxValue {
+    type Property = VirtualMailboxProperty;
+
+    fn try_parse<P>(key: &Key<'_, Self::Property>, value: &str) -> Option<Self> {
+        if let Key::Property(VirtualMailboxProperty::Id) = key {
+            Id::from_str(value).ok().map(VirtualMailboxValue::Id)
+        } else {
+            None
+        }
+    }
+
+    fn to_cow(&self) -> Cow<'static, str> {
+        match self {
+            VirtualMailboxValue::Id(id) => id.to_string().into(),
+        }
+    }
+}
+
+impl VirtualMailboxProperty {
+    fn parse(value: &str) -> Option<Self> {
+        hashify::tiny_map!(value.as_bytes(),
+            b"id" => VirtualMailboxProperty::Id,
+            b"name" => VirtualMailboxProperty::Name,
+            b"query" => VirtualMailboxProperty::Query,
+        )
+    }
+}
+
+impl FromStr for VirtualMailboxProperty {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        VirtualMailboxProperty::parse(s).ok_or(())
+    }
+}
+
+impl JmapObject for VirtualMailbox {
+    type Property = VirtualMailboxProperty;
+
+    type Element = VirtualMailboxValue;
+
+    type Id = Id;
+
+    type Filter = ();
+
+    type Comparator = ();
+
+    type GetArguments = ();
+
+    type SetArguments<'de> = ();
+
+    type QueryArguments = ();
+
+    type CopyArguments = ();
+
+    type ParseArguments = ();
+
+    const ID_PROPERTY: Self::Property = VirtualMailboxProperty::Id;
+}
+
+impl From<Id> for VirtualMailboxValue {
+    fn from(id: Id) -> Self {
+        VirtualMailboxValue::Id(id)
+    }
+}
+
+impl JmapObjectId for VirtualMailboxValue {
+    fn as_id(&self) -> Option<Id> {
+        match self {
+            VirtualMailboxValue::Id(id) => Some(*id),
+        }
+    }
+
+    fn as_any_id(&self) -> Option<AnyId> {
+        match self {
+            VirtualMailboxValue::Id(id) => Some(AnyId::Id(*id)),
+        }
+    }
+
+    fn as_id_ref(&self) -> Option<&str> {
+        None
+    }
+
+    fn try_set_id(&mut self, new_id: AnyId) -> bool {
+        if let AnyId::Id(id) = new_id {
+            *self = VirtualMailboxValue::Id(id);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl JmapObjectId for VirtualMailboxProperty {
+    fn as_id(&self) -> Option<Id> {
+        None
+    }
+
+    fn as_any_id(&self) -> Option<AnyId> {
+        None
+    }
+
+    fn as_id_ref(&self) -> Option<&str> {
+        None
+    }
+
+    fn try_set_id(&mut self, _: AnyId) -> bool {
+        false
+    }
+}
@@ -10,6 +10,7 @@ use crate::{
         copy::CopyRequest,
         get::GetRequest,
         import::ImportEmailRequest,
+        mdn::MdnParseRequest,
         parse::ParseRequest,
         search_snippet::GetSearchSnippetRequest,
         set::{SetRequest, SetResponse},
@@ -41,6 +42,7 @@ impl Response<'_> {
                 GetRequestMethod::PushSubscription(request) => request.resolve_references(self)?,
                 GetRequestMethod::Sieve(request) => request.resolve_references(self)?,
                 GetRequestMethod::VacationResponse(request) => request.resolve_references(self)?,
+                GetRequestMethod::VirtualMailbox(request) => request.resolve_references(self)?,
                 GetRequestMethod::Principal(request) => request.resolve_references(self)?,
                 GetRequestMethod::Quota(request) => request.resolve_references(self)?,
                 GetRequestMethod::Blob(request) => request.resolve_references(self)?,
@@ -56,6 +58,8 @@ impl Response<'_> {
                 GetRequestMethod::ParticipantIdentity(request) => {
                     request.resolve_references(self)?
                 }
+                GetRequestMethod::TaskList(request) => request.resolve_references(self)?,
+                GetRequestMethod::Task(request) => request.resolve_references(self)?,
                 GetRequestMethod::PrincipalAvailability(_) => (),
             },
             RequestMethod::Set(request) => match request {
@@ -66,6 +70,7 @@ impl Response<'_> {
                 SetRequestMethod::PushSubscription(request) => request.resolve_references(self)?,
                 SetRequestMethod::Sieve(request) => request.resolve_references(self)?,
                 SetRequestMethod::VacationResponse(request) => request.resolve_references(self)?,
+                SetRequestMethod::VirtualMailbox(request) => request.resolve_references(self)?,
                 SetRequestMethod::AddressBook(request) => request.resolve_references(self)?,
                 SetRequestMethod::ContactCard(request) => request.resolve_references(self)?,
                 SetRequestMethod::FileNode(request) => request.resolve_references(self)?,
@@ -78,6 +83,11 @@ impl Response<'_> {
                 SetRequestMethod::ParticipantIdentity(request) => {
                     request.resolve_references(self)?
                 }
+                SetRequestMethod::TaskList(request) => request.resolve_references(self)?,
+                SetRequestMethod::Task(request) => {
+                    request.expand_task_list_ids();
+                    request.resolve_references(self)?
+                }
             },
             RequestMethod::Copy(request) => match request {
                 CopyRequestMethod::Email(request) => request.resolve_references(self)?,
@@ -88,6 +98,7 @@ impl Response<'_> {
             RequestMethod::ImportEmail(request) => request.resolve_references(self)?,
             RequestMethod::SearchSnippet(request) => request.resolve_references(self)?,
             RequestMethod::UploadBlob(request) => request.resolve_references(self)?,
+            RequestMethod::ParseMdn(request) => request.resolve_references(self)?,
             RequestMethod::Parse(request) => match request {
                 ParseRequestMethod::Email(request) => request.resolve_references(self)?,
                 ParseRequestMethod::ContactCard(request) => request.resolve_references(self)?,
@@ -261,6 +272,19 @@ impl<T: JmapObject> ResolveReference for ParseRequest<T> {
     }
 }
 
+impl ResolveReference for MdnParseRequest {
+    fn resolve_references(&mut self, response: &Response<'_>) -> trc::Result<()> {
+        // Resolve blobId references
+        for id in self.blob_ids.iter_mut() {
+            if let MaybeIdReference::Reference(ir) = id {
+                *id = MaybeIdReference::Id(response.eval_blob_id_reference(ir)?);
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl ResolveReference for ImportEmailRequest {
     fn resolve_references(&mut self, response: &Response<'_>) -> trc::Result<()> {
         // Resolve email mailbox references
@@ -87,12 +87,28 @@ pub enum Capability {
     PrincipalsAvailability = 1 << 14,
     #[serde(rename(serialize = "urn:ietf:params:jmap:filenode"))]
     FileNode = 1 << 15,
+    #[serde(rename(serialize = "urn:ietf:params:jmap:mdn"))]
+    Mdn = 1 << 16,
+    #[serde(rename(serialize = "urn:ietf:params:jmap:smimeverify"))]
+    SmimeVerify = 1 << 17,
+    #[serde(rename(serialize = "urn:ietf:params:jmap:tasks"))]
+    Tasks = 1 << 18,
+    #[serde(rename(serialize = "urn:stalwart:jmap:metadata"))]
+    Metadata = 1 << 19,
+    #[serde(rename(serialize = "urn:stalwart:jmap:virtualmailbox"))]
+    VirtualMailbox = 1 << 20,
 }
 
 #[derive(Debug, Clone, Copy, Default)]
 #[repr(transparent)]
 pub struct CapabilityIds(pub u32);
 
+impl CapabilityIds {
+    pub fn contains(&self, capability: Capability) -> bool {
+        self.0 & capability as u32 != 0
+    }
+}
+
 #[derive(Debug, Clone, serde::Serialize)]
 #[serde(untagged)]
 #[allow(dead_code)]
@@ -108,6 +124,7 @@ pub enum Capabilities {
     Principals(PrincipalCapabilities),
     PrincipalsAvailability(PrincipalAvailabilityCapabilities),
     Calendar(CalendarCapabilities),
+    Tasks(TasksCapabilities),
     FileNode(FileNodeCapabilities),
     Empty(EmptyCapabilities),
 }
@@ -216,6 +233,16 @@ pub struct CalendarCapabilities {
     pub may_create_calendar: bool,
 }
 
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TasksCapabilities {
+    #[serde(rename(serialize = "minDateTime"))]
+    pub min_date_time: UTCDate,
+    #[serde(rename(serialize = "maxDateTime"))]
+    pub max_date_time: UTCDate,
+    #[serde(rename(serialize = "mayCreateTaskList"))]
+    pub may_create_task_list: bool,
+}
+
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct ContactsCapabilities {
     #[serde(rename(serialize = "maxAddressBooksPerCard"))]
@@ -297,6 +324,11 @@ impl Capability {
             Capability::PrincipalsOwner => "urn:ietf:params:jmap:principals:owner",
             Capability::PrincipalsAvailability => "urn:ietf:params:jmap:principals:availability",
             Capability::FileNode => "urn:ietf:params:jmap:filenode",
+            Capability::Mdn => "urn:ietf:params:jmap:mdn",
+            Capability::SmimeVerify => "urn:ietf:params:jmap:smimeverify",
+            Capability::Tasks => "urn:ietf:params:jmap:tasks",
+            Capability::Metadata => "urn:stalwart:jmap:metadata",
+            Capability::VirtualMailbox => "urn:stalwart:jmap:virtualmailbox",
         }
     }
 
@@ -317,6 +349,11 @@ impl Capability {
             Capability::Principals,
             Capability::PrincipalsAvailability,
             Capability::FileNode,
+            Capability::Mdn,
+            Capability::SmimeVerify,
+            Capability::Tasks,
+            Capability::Metadata,
+            Capability::VirtualMailbox,
         ]
     }
 }
@@ -405,6 +442,10 @@ impl Capabilities {
                     ..calendar_capabilities.clone()
                 })
             }
+            Capabilities::Tasks(tasks_capabilities) => Capabilities::Tasks(TasksCapabilities {
+                may_create_task_list: may_create,
+                ..tasks_capabilities.clone()
+            }),
             Capabilities::FileNode(file_node_capabilities) => {
                 Capabilities::FileNode(FileNodeCapabilities {
                     may_create_top_level_file_node: may_create,
@@ -435,6 +476,11 @@ impl Capability {
             "urn:ietf:params:jmap:principals:availability" => Capability::PrincipalsAvailability,
             "urn:ietf:params:jmap:contacts:parse" => Capability::ContactsParse,
             "urn:ietf:params:jmap:calendars:parse" => Capability::CalendarsParse,
+            "urn:ietf:params:jmap:mdn" => Capability::Mdn,
+            "urn:ietf:params:jmap:smimeverify" => Capability::SmimeVerify,
+            "urn:ietf:params:jmap:tasks" => Capability::Tasks,
+            "urn:stalwart:jmap:metadata" => Capability::Metadata,
+            "urn:stalwart:jmap:virtualmailbox" => Capability::VirtualMailbox,
         )
     }
 }
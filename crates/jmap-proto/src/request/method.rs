@@ -35,6 +35,10 @@ pub enum MethodObject {
     FileNode,
     ParticipantIdentity,
     ShareNotification,
+    VirtualMailbox,
+    Mdn,
+    TaskList,
+    Task,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -52,6 +56,7 @@ pub enum MethodFunction {
     Upload,
     Echo,
     GetAvailability,
+    Send,
 }
 
 impl Display for MethodName {
@@ -112,6 +117,10 @@ impl MethodName {
             (MethodFunction::Get, MethodObject::VacationResponse) => "VacationResponse/get",
             (MethodFunction::Set, MethodObject::VacationResponse) => "VacationResponse/set",
 
+            (MethodFunction::Get, MethodObject::VirtualMailbox) => "VirtualMailbox/get",
+            (MethodFunction::Changes, MethodObject::VirtualMailbox) => "VirtualMailbox/changes",
+            (MethodFunction::Set, MethodObject::VirtualMailbox) => "VirtualMailbox/set",
+
             (MethodFunction::Get, MethodObject::SieveScript) => "SieveScript/get",
             (MethodFunction::Set, MethodObject::SieveScript) => "SieveScript/set",
             (MethodFunction::Query, MethodObject::SieveScript) => "SieveScript/query",
@@ -180,6 +189,19 @@ impl MethodName {
             (MethodFunction::Changes, MethodObject::ParticipantIdentity) => "ParticipantIdentity/changes",
             (MethodFunction::Set, MethodObject::ParticipantIdentity) => "ParticipantIdentity/set",
 
+            (MethodFunction::Send, MethodObject::Mdn) => "MDN/send",
+            (MethodFunction::Parse, MethodObject::Mdn) => "MDN/parse",
+
+            (MethodFunction::Get, MethodObject::TaskList) => "TaskList/get",
+            (MethodFunction::Changes, MethodObject::TaskList) => "TaskList/changes",
+            (MethodFunction::Set, MethodObject::TaskList) => "TaskList/set",
+
+            (MethodFunction::Get, MethodObject::Task) => "Task/get",
+            (MethodFunction::Changes, MethodObject::Task) => "Task/changes",
+            (MethodFunction::Query, MethodObject::Task) => "Task/query",
+            (MethodFunction::QueryChanges, MethodObject::Task) => "Task/queryChanges",
+            (MethodFunction::Set, MethodObject::Task) => "Task/set",
+
             (MethodFunction::Echo, MethodObject::Core) => "Core/echo",
             _ => "error",
         }
@@ -223,6 +245,10 @@ impl MethodName {
             "VacationResponse/get" => (MethodObject::VacationResponse, MethodFunction::Get),
             "VacationResponse/set" => (MethodObject::VacationResponse, MethodFunction::Set),
 
+            "VirtualMailbox/get" => (MethodObject::VirtualMailbox, MethodFunction::Get),
+            "VirtualMailbox/changes" => (MethodObject::VirtualMailbox, MethodFunction::Changes),
+            "VirtualMailbox/set" => (MethodObject::VirtualMailbox, MethodFunction::Set),
+
             "SieveScript/get" => (MethodObject::SieveScript, MethodFunction::Get),
             "SieveScript/set" => (MethodObject::SieveScript, MethodFunction::Set),
             "SieveScript/query" => (MethodObject::SieveScript, MethodFunction::Query),
@@ -291,6 +317,17 @@ impl MethodName {
             "ParticipantIdentity/changes" => (MethodObject::ParticipantIdentity, MethodFunction::Changes),
             "ParticipantIdentity/set" => (MethodObject::ParticipantIdentity, MethodFunction::Set),
 
+            "MDN/send" => (MethodObject::Mdn, MethodFunction::Send),
+            "MDN/parse" => (MethodObject::Mdn, MethodFunction::Parse),
+            "TaskList/get" => (MethodObject::TaskList, MethodFunction::Get),
+            "TaskList/changes" => (MethodObject::TaskList, MethodFunction::Changes),
+            "TaskList/set" => (MethodObject::TaskList, MethodFunction::Set),
+            "Task/get" => (MethodObject::Task, MethodFunction::Get),
+            "Task/changes" => (MethodObject::Task, MethodFunction::Changes),
+            "Task/query" => (MethodObject::Task, MethodFunction::Query),
+            "Task/queryChanges" => (MethodObject::Task, MethodFunction::QueryChanges),
+            "Task/set" => (MethodObject::Task, MethodFunction::Set),
+
             "Core/echo" => (MethodObject::Core, MethodFunction::Echo),
 
         ).map(|(obj, fnc)| MethodName { obj, fnc })
@@ -322,6 +359,10 @@ impl Display for MethodObject {
             MethodObject::CalendarEvent => "CalendarEvent",
             MethodObject::CalendarEventNotification => "CalendarEventNotification",
             MethodObject::ShareNotification => "ShareNotification",
+            MethodObject::VirtualMailbox => "VirtualMailbox",
+            MethodObject::Mdn => "MDN",
+            MethodObject::TaskList => "TaskList",
+            MethodObject::Task => "Task",
         })
     }
 }
@@ -20,6 +20,7 @@ use crate::{
         get::GetRequest,
         import::ImportEmailRequest,
         lookup::BlobLookupRequest,
+        mdn::{MdnParseRequest, MdnSendRequest},
         parse::ParseRequest,
         query::QueryRequest,
         query_changes::QueryChangesRequest,
@@ -29,13 +30,28 @@ use crate::{
         validate::ValidateSieveScriptRequest,
     },
     object::{
-        AnyId, addressbook::AddressBook, blob::Blob, calendar::Calendar,
-        calendar_event::CalendarEvent, calendar_event_notification::CalendarEventNotification,
-        contact::ContactCard, email::Email, email_submission::EmailSubmission, file_node::FileNode,
-        identity::Identity, mailbox::Mailbox, participant_identity::ParticipantIdentity,
-        principal::Principal, push_subscription::PushSubscription, quota::Quota,
-        share_notification::ShareNotification, sieve::Sieve, thread::Thread,
+        AnyId,
+        addressbook::AddressBook,
+        blob::Blob,
+        calendar::Calendar,
+        calendar_event::CalendarEvent,
+        calendar_event_notification::CalendarEventNotification,
+        contact::ContactCard,
+        email::Email,
+        email_submission::EmailSubmission,
+        file_node::FileNode,
+        identity::Identity,
+        mailbox::Mailbox,
+        participant_identity::ParticipantIdentity,
+        principal::Principal,
+        push_subscription::PushSubscription,
+        quota::Quota,
+        share_notification::ShareNotification,
+        sieve::Sieve,
+        task::{Task, TaskList},
+        thread::Thread,
         vacation_response::VacationResponse,
+        virtual_mailbox::VirtualMailbox,
     },
     request::{capability::CapabilityIds, reference::MaybeIdReference},
 };
@@ -71,6 +87,8 @@ pub enum RequestMethod<'x> {
     ValidateScript(ValidateSieveScriptRequest),
     LookupBlob(BlobLookupRequest),
     UploadBlob(BlobUploadRequest),
+    SendMdn(MdnSendRequest<'x>),
+    ParseMdn(MdnParseRequest),
     Echo(Value<'x, Null, Null>),
     Error(trc::Error),
 }
@@ -85,6 +103,7 @@ pub enum GetRequestMethod {
     PushSubscription(GetRequest<PushSubscription>),
     Sieve(GetRequest<Sieve>),
     VacationResponse(GetRequest<VacationResponse>),
+    VirtualMailbox(GetRequest<VirtualMailbox>),
     Principal(GetRequest<Principal>),
     PrincipalAvailability(GetAvailabilityRequest),
     Quota(GetRequest<Quota>),
@@ -97,6 +116,8 @@ pub enum GetRequestMethod {
     CalendarEventNotification(GetRequest<CalendarEventNotification>),
     ParticipantIdentity(GetRequest<ParticipantIdentity>),
     ShareNotification(GetRequest<ShareNotification>),
+    TaskList(GetRequest<TaskList>),
+    Task(GetRequest<Task>),
 }
 
 #[derive(Debug)]
@@ -108,6 +129,7 @@ pub enum SetRequestMethod<'x> {
     PushSubscription(SetRequest<'x, PushSubscription>),
     Sieve(SetRequest<'x, Sieve>),
     VacationResponse(SetRequest<'x, VacationResponse>),
+    VirtualMailbox(SetRequest<'x, VirtualMailbox>),
     AddressBook(SetRequest<'x, AddressBook>),
     ContactCard(SetRequest<'x, ContactCard>),
     FileNode(SetRequest<'x, FileNode>),
@@ -116,6 +138,8 @@ pub enum SetRequestMethod<'x> {
     CalendarEvent(SetRequest<'x, CalendarEvent>),
     CalendarEventNotification(SetRequest<'x, CalendarEventNotification>),
     ParticipantIdentity(SetRequest<'x, ParticipantIdentity>),
+    TaskList(SetRequest<'x, TaskList>),
+    Task(SetRequest<'x, Task>),
 }
 
 #[derive(Debug)]
@@ -139,6 +163,7 @@ pub enum QueryRequestMethod {
     CalendarEvent(QueryRequest<CalendarEvent>),
     CalendarEventNotification(QueryRequest<CalendarEventNotification>),
     ShareNotification(QueryRequest<ShareNotification>),
+    Task(QueryRequest<Task>),
 }
 
 #[derive(Debug)]
@@ -154,6 +179,7 @@ pub enum QueryChangesRequestMethod {
     CalendarEvent(QueryChangesRequest<CalendarEvent>),
     CalendarEventNotification(QueryChangesRequest<CalendarEventNotification>),
     ShareNotification(QueryChangesRequest<ShareNotification>),
+    Task(QueryChangesRequest<Task>),
 }
 
 #[derive(Debug)]
@@ -17,6 +17,7 @@ use crate::{
         get::GetResponse,
         import::ImportEmailResponse,
         lookup::BlobLookupResponse,
+        mdn::{MdnParseResponse, MdnSendResponse},
         parse::ParseResponse,
         query::QueryResponse,
         query_changes::QueryChangesResponse,
@@ -48,6 +49,7 @@ use crate::{
         sieve::Sieve,
         thread::Thread,
         vacation_response::VacationResponse,
+        virtual_mailbox::VirtualMailbox,
     },
     request::{Call, method::MethodName},
 };
@@ -69,6 +71,8 @@ pub enum ResponseMethod<'x> {
     ValidateScript(ValidateSieveScriptResponse),
     LookupBlob(BlobLookupResponse),
     UploadBlob(BlobUploadResponse),
+    SendMdn(MdnSendResponse),
+    ParseMdn(MdnParseResponse),
     Echo(Value<'x, Null, Null>),
     Error(MethodErrorWrapper),
 }
@@ -84,6 +88,7 @@ pub enum GetResponseMethod {
     PushSubscription(GetResponse<PushSubscription>),
     Sieve(GetResponse<Sieve>),
     VacationResponse(GetResponse<VacationResponse>),
+    VirtualMailbox(GetResponse<VirtualMailbox>),
     Principal(GetResponse<Principal>),
     PrincipalAvailability(GetAvailabilityResponse),
     Quota(GetResponse<Quota>),
@@ -108,6 +113,7 @@ pub enum SetResponseMethod {
     PushSubscription(SetResponse<PushSubscription>),
     Sieve(SetResponse<Sieve>),
     VacationResponse(SetResponse<VacationResponse>),
+    VirtualMailbox(SetResponse<VirtualMailbox>),
     AddressBook(SetResponse<AddressBook>),
     ContactCard(SetResponse<ContactCard>),
     FileNode(SetResponse<FileNode>),
@@ -134,6 +140,7 @@ pub enum ChangesResponseMethod {
     CalendarEvent(ChangesResponse<CalendarEvent>),
     CalendarEventNotification(ChangesResponse<CalendarEventNotification>),
     ShareNotification(ChangesResponse<ShareNotification>),
+    VirtualMailbox(ChangesResponse<VirtualMailbox>),
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -265,6 +272,12 @@ impl<'x> From<GetResponse<VacationResponse>> for ResponseMethod<'x> {
     }
 }
 
+impl<'x> From<GetResponse<VirtualMailbox>> for ResponseMethod<'x> {
+    fn from(value: GetResponse<VirtualMailbox>) -> Self {
+        ResponseMethod::Get(GetResponseMethod::VirtualMailbox(value))
+    }
+}
+
 impl<'x> From<GetResponse<Principal>> for ResponseMethod<'x> {
     fn from(value: GetResponse<Principal>) -> Self {
         ResponseMethod::Get(GetResponseMethod::Principal(value))
@@ -337,6 +350,12 @@ impl<'x> From<SetResponse<VacationResponse>> for ResponseMethod<'x> {
     }
 }
 
+impl<'x> From<SetResponse<VirtualMailbox>> for ResponseMethod<'x> {
+    fn from(value: SetResponse<VirtualMailbox>) -> Self {
+        ResponseMethod::Set(SetResponseMethod::VirtualMailbox(value))
+    }
+}
+
 impl<'x> From<SetResponse<AddressBook>> for ResponseMethod<'x> {
     fn from(value: SetResponse<AddressBook>) -> Self {
         ResponseMethod::Set(SetResponseMethod::AddressBook(value))
@@ -385,6 +404,12 @@ impl<'x> From<ChangesResponse<Quota>> for ResponseMethod<'x> {
     }
 }
 
+impl<'x> From<ChangesResponse<VirtualMailbox>> for ResponseMethod<'x> {
+    fn from(value: ChangesResponse<VirtualMailbox>) -> Self {
+        ResponseMethod::Changes(ChangesResponseMethod::VirtualMailbox(value))
+    }
+}
+
 impl<'x> From<ChangesResponse<AddressBook>> for ResponseMethod<'x> {
     fn from(value: ChangesResponse<AddressBook>) -> Self {
         ResponseMethod::Changes(ChangesResponseMethod::AddressBook(value))
@@ -451,6 +476,18 @@ impl<'x> From<ValidateSieveScriptResponse> for ResponseMethod<'x> {
     }
 }
 
+impl<'x> From<MdnSendResponse> for ResponseMethod<'x> {
+    fn from(value: MdnSendResponse) -> Self {
+        ResponseMethod::SendMdn(value)
+    }
+}
+
+impl<'x> From<MdnParseResponse> for ResponseMethod<'x> {
+    fn from(value: MdnParseResponse) -> Self {
+        ResponseMethod::ParseMdn(value)
+    }
+}
+
 impl<'x> From<BlobLookupResponse> for ResponseMethod<'x> {
     fn from(value: BlobLookupResponse) -> Self {
         ResponseMethod::LookupBlob(value)
@@ -64,6 +64,7 @@ impl JmapAuthorization for AccessToken {
                 GetRequestMethod::PushSubscription(_) => Permission::JmapPushSubscriptionGet,
                 GetRequestMethod::Sieve(_) => Permission::JmapSieveScriptGet,
                 GetRequestMethod::VacationResponse(_) => Permission::JmapVacationResponseGet,
+                GetRequestMethod::VirtualMailbox(_) => Permission::JmapVirtualMailboxGet,
                 GetRequestMethod::Principal(_) => Permission::JmapPrincipalGet,
                 GetRequestMethod::Quota(_) => Permission::JmapQuotaGet,
                 GetRequestMethod::Blob(_) => Permission::JmapBlobGet,
@@ -80,6 +81,8 @@ impl JmapAuthorization for AccessToken {
                 }
                 GetRequestMethod::ParticipantIdentity(_) => Permission::JmapParticipantIdentityGet,
                 GetRequestMethod::ShareNotification(_) => Permission::JmapShareNotificationGet,
+                GetRequestMethod::TaskList(_) => Permission::JmapTaskListGet,
+                GetRequestMethod::Task(_) => Permission::JmapTaskGet,
             },
             RequestMethod::Set(m) => match &m {
                 SetRequestMethod::Email(_) => Permission::JmapEmailSet,
@@ -89,6 +92,7 @@ impl JmapAuthorization for AccessToken {
                 SetRequestMethod::PushSubscription(_) => Permission::JmapPushSubscriptionSet,
                 SetRequestMethod::Sieve(_) => Permission::JmapSieveScriptSet,
                 SetRequestMethod::VacationResponse(_) => Permission::JmapVacationResponseSet,
+                SetRequestMethod::VirtualMailbox(_) => Permission::JmapVirtualMailboxSet,
                 SetRequestMethod::AddressBook(_) => Permission::JmapAddressBookSet,
                 SetRequestMethod::ContactCard(_) => Permission::JmapContactCardSet,
                 SetRequestMethod::FileNode(_) => Permission::JmapFileNodeSet,
@@ -99,6 +103,8 @@ impl JmapAuthorization for AccessToken {
                     Permission::JmapCalendarEventNotificationSet
                 }
                 SetRequestMethod::ParticipantIdentity(_) => Permission::JmapParticipantIdentitySet,
+                SetRequestMethod::TaskList(_) => Permission::JmapTaskListSet,
+                SetRequestMethod::Task(_) => Permission::JmapTaskSet,
             },
             RequestMethod::Changes(_) => match object {
                 MethodObject::Email => Permission::JmapEmailChanges,
@@ -117,12 +123,16 @@ impl JmapAuthorization for AccessToken {
                 MethodObject::ParticipantIdentity => Permission::JmapParticipantIdentityChanges,
                 MethodObject::ShareNotification => Permission::JmapShareNotificationChanges,
                 MethodObject::Principal => Permission::JmapPrincipalChanges,
+                MethodObject::TaskList => Permission::JmapTaskListChanges,
+                MethodObject::Task => Permission::JmapTaskChanges,
+                MethodObject::VirtualMailbox => Permission::JmapVirtualMailboxChanges,
                 MethodObject::Core
                 | MethodObject::Blob
                 | MethodObject::PushSubscription
                 | MethodObject::SearchSnippet
                 | MethodObject::VacationResponse
                 | MethodObject::SieveScript
+                | MethodObject::Mdn
                 | MethodObject::AddressBook => Permission::JmapEmailChanges,
             },
             RequestMethod::Copy(m) => match &m {
@@ -159,6 +169,7 @@ impl JmapAuthorization for AccessToken {
                 QueryChangesRequestMethod::ShareNotification(_) => {
                     Permission::JmapShareNotificationQueryChanges
                 }
+                QueryChangesRequestMethod::Task(_) => Permission::JmapTaskQueryChanges,
             },
             RequestMethod::Query(m) => match m {
                 QueryRequestMethod::Email(_) => Permission::JmapEmailQuery,
@@ -174,11 +185,14 @@ impl JmapAuthorization for AccessToken {
                     Permission::JmapCalendarEventNotificationQuery
                 }
                 QueryRequestMethod::ShareNotification(_) => Permission::JmapShareNotificationQuery,
+                QueryRequestMethod::Task(_) => Permission::JmapTaskQuery,
             },
             RequestMethod::SearchSnippet(_) => Permission::JmapSearchSnippet,
             RequestMethod::ValidateScript(_) => Permission::JmapSieveScriptValidate,
             RequestMethod::LookupBlob(_) => Permission::JmapBlobLookup,
             RequestMethod::UploadBlob(_) => Permission::JmapBlobUpload,
+            RequestMethod::SendMdn(_) => Permission::JmapMdnSend,
+            RequestMethod::ParseMdn(_) => Permission::JmapMdnParse,
             RequestMethod::Echo(_) => Permission::JmapEcho,
             RequestMethod::Error(_) => return Ok(()),
         };
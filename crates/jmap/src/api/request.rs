@@ -29,6 +29,7 @@ use crate::{
     file::{get::FileNodeGet, query::FileNodeQuery, set::FileNodeSet},
     identity::{get::IdentityGet, set::IdentitySet},
     mailbox::{get::MailboxGet, query::MailboxQuery, set::MailboxSet},
+    mdn::{parse::MdnParse, send::MdnSend},
     participant_identity::{get::ParticipantIdentityGet, set::ParticipantIdentitySet},
     principal::{availability::PrincipalGetAvailability, get::PrincipalGet, query::PrincipalQuery},
     push::{get::PushSubscriptionFetch, set::PushSubscriptionSet},
@@ -41,15 +42,22 @@ use crate::{
         validate::SieveScriptValidate,
     },
     submission::{get::EmailSubmissionGet, query::EmailSubmissionQuery, set::EmailSubmissionSet},
+    task::{get::TaskGet, query::TaskQuery, set::TaskSet},
+    task_list::{get::TaskListGet, set::TaskListSet},
     thread::get::ThreadGet,
     vacation::{get::VacationResponseGet, set::VacationResponseSet},
+    virtual_mailbox::{
+        changes::VirtualMailboxChanges, get::VirtualMailboxGet, set::VirtualMailboxSet,
+    },
 };
 use common::{Server, auth::AccessToken};
 use http_proto::HttpSessionData;
 use jmap_proto::{
     request::{
         Call, CopyRequestMethod, GetRequestMethod, ParseRequestMethod, QueryRequestMethod, Request,
-        RequestMethod, SetRequestMethod, method::MethodName,
+        RequestMethod, SetRequestMethod,
+        capability::{Capability, CapabilityIds},
+        method::{MethodName, MethodObject},
     },
     response::{Response, ResponseMethod, SetResponseMethod},
 };
@@ -70,6 +78,7 @@ pub trait RequestHandler: Sync + Send {
         &self,
         method: RequestMethod<'x>,
         method_name: MethodName,
+        using: CapabilityIds,
         access_token: &AccessToken,
         next_call: &mut Option<Call<RequestMethod<'x>>>,
         session: &HttpSessionData,
@@ -111,6 +120,7 @@ impl RequestHandler for Server {
                     .handle_method_call(
                         call.method,
                         call.name,
+                        request.using,
                         &access_token,
                         &mut next_call,
                         session,
@@ -143,6 +153,9 @@ impl RequestHandler for Server {
                                     SetResponseMethod::VacationResponse(set_response) => {
                                         set_response.update_created_ids(&mut response);
                                     }
+                                    SetResponseMethod::VirtualMailbox(set_response) => {
+                                        set_response.update_created_ids(&mut response);
+                                    }
                                     SetResponseMethod::AddressBook(set_response) => {
                                         set_response.update_created_ids(&mut response);
                                     }
@@ -216,6 +229,7 @@ impl RequestHandler for Server {
         &self,
         method: RequestMethod<'x>,
         method_name: MethodName,
+        using: CapabilityIds,
         access_token: &AccessToken,
         next_call: &mut Option<Call<RequestMethod<'x>>>,
         session: &HttpSessionData,
@@ -225,6 +239,17 @@ impl RequestHandler for Server {
         // Check permissions
         access_token.assert_has_jmap_permission(&method, method_name.obj)?;
 
+        // Virtual mailboxes are a vendor extension, only available when requested
+        if method_name.obj == MethodObject::VirtualMailbox
+            && !using.contains(Capability::VirtualMailbox)
+        {
+            return Err(trc::JmapEvent::UnknownMethod.into_err().details(format!(
+                "{} requires the {} capability.",
+                method_name.as_str(),
+                Capability::VirtualMailbox.as_str()
+            )));
+        }
+
         // Handle method
         let response = match method {
             RequestMethod::Get(req) => match req {
@@ -238,7 +263,7 @@ impl RequestHandler for Server {
                     set_account_id_if_missing(&mut req.account_id, access_token);
                     access_token.assert_has_access(req.account_id, Collection::Mailbox)?;
 
-                    self.mailbox_get(req, access_token).await?.into()
+                    self.mailbox_get(req, using, access_token).await?.into()
                 }
                 GetRequestMethod::Thread(mut req) => {
                     set_account_id_if_missing(&mut req.account_id, access_token);
@@ -274,6 +299,12 @@ impl RequestHandler for Server {
 
                     self.vacation_response_get(req).await?.into()
                 }
+                GetRequestMethod::VirtualMailbox(mut req) => {
+                    set_account_id_if_missing(&mut req.account_id, access_token);
+                    access_token.assert_is_member(req.account_id)?;
+
+                    self.virtual_mailbox_get(req).await?.into()
+                }
                 GetRequestMethod::Principal(req) => {
                     self.principal_get(req, access_token).await?.into()
                 }
@@ -323,6 +354,18 @@ impl RequestHandler for Server {
 
                     self.calendar_event_get(req, access_token).await?.into()
                 }
+                GetRequestMethod::TaskList(mut req) => {
+                    set_account_id_if_missing(&mut req.account_id, access_token);
+                    access_token.assert_has_access(req.account_id, Collection::Calendar)?;
+
+                    self.task_list_get(req, access_token).await?.into()
+                }
+                GetRequestMethod::Task(mut req) => {
+                    set_account_id_if_missing(&mut req.account_id, access_token);
+                    access_token.assert_has_access(req.account_id, Collection::CalendarEvent)?;
+
+                    self.task_get(req, access_token).await?.into()
+                }
                 GetRequestMethod::CalendarEventNotification(mut req) => {
                     set_account_id_if_missing(&mut req.account_id, access_token);
                     access_token.assert_is_member(req.account_id)?;
@@ -397,6 +440,12 @@ impl RequestHandler for Server {
 
                     self.calendar_event_query(req, access_token).await?.into()
                 }
+                QueryRequestMethod::Task(mut req) => {
+                    set_account_id_if_missing(&mut req.account_id, access_token);
+                    access_token.assert_has_access(req.account_id, Collection::CalendarEvent)?;
+
+                    self.task_query(req, access_token).await?.into()
+                }
                 QueryRequestMethod::CalendarEventNotification(mut req) => {
                     set_account_id_if_missing(&mut req.account_id, access_token);
                     access_token.assert_is_member(req.account_id)?;
@@ -423,7 +472,7 @@ impl RequestHandler for Server {
                     set_account_id_if_missing(&mut req.account_id, access_token);
                     access_token.assert_has_access(req.account_id, Collection::Mailbox)?;
 
-                    self.mailbox_set(req, access_token).await?.into()
+                    self.mailbox_set(req, using, access_token).await?.into()
                 }
                 SetRequestMethod::Identity(mut req) => {
                     set_account_id_if_missing(&mut req.account_id, access_token);
@@ -457,6 +506,12 @@ impl RequestHandler for Server {
 
                     self.vacation_response_set(req, access_token).await?.into()
                 }
+                SetRequestMethod::VirtualMailbox(mut req) => {
+                    set_account_id_if_missing(&mut req.account_id, access_token);
+                    access_token.assert_is_member(req.account_id)?;
+
+                    self.virtual_mailbox_set(req).await?.into()
+                }
                 SetRequestMethod::AddressBook(mut req) => {
                     set_account_id_if_missing(&mut req.account_id, access_token);
                     access_token.assert_has_access(req.account_id, Collection::AddressBook)?;
@@ -499,6 +554,18 @@ impl RequestHandler for Server {
                         .await?
                         .into()
                 }
+                SetRequestMethod::TaskList(mut req) => {
+                    set_account_id_if_missing(&mut req.account_id, access_token);
+                    access_token.assert_has_access(req.account_id, Collection::Calendar)?;
+
+                    self.task_list_set(req, access_token, session).await?.into()
+                }
+                SetRequestMethod::Task(mut req) => {
+                    set_account_id_if_missing(&mut req.account_id, access_token);
+                    access_token.assert_has_access(req.account_id, Collection::CalendarEvent)?;
+
+                    self.task_set(req, access_token, session).await?.into()
+                }
                 SetRequestMethod::CalendarEventNotification(mut req) => {
                     set_account_id_if_missing(&mut req.account_id, access_token);
                     access_token.assert_is_member(req.account_id)?;
@@ -516,6 +583,12 @@ impl RequestHandler for Server {
                         .into()
                 }
             },
+            RequestMethod::Changes(mut req) if method_name.obj == MethodObject::VirtualMailbox => {
+                set_account_id_if_missing(&mut req.account_id, access_token);
+                access_token.assert_is_member(req.account_id)?;
+
+                self.virtual_mailbox_changes(req).await?.into()
+            }
             RequestMethod::Changes(mut req) => {
                 set_account_id_if_missing(&mut req.account_id, access_token);
 
@@ -618,6 +691,20 @@ impl RequestHandler for Server {
 
                 self.blob_upload_many(req, access_token).await?.into()
             }
+            RequestMethod::SendMdn(mut req) => {
+                set_account_id_if_missing(&mut req.account_id, access_token);
+                access_token.assert_is_member(req.account_id)?;
+
+                self.mdn_send(req, &session.instance, next_call)
+                    .await?
+                    .into()
+            }
+            RequestMethod::ParseMdn(mut req) => {
+                set_account_id_if_missing(&mut req.account_id, access_token);
+                access_token.assert_has_access(req.account_id, Collection::Email)?;
+
+                self.mdn_parse(req, access_token).await?.into()
+            }
             RequestMethod::Echo(req) => req.into(),
             RequestMethod::Error(error) => return Err(error),
         };
@@ -111,6 +111,11 @@ impl AccountCapabilities for AccessToken {
                     Capability::Blob => Permission::JmapBlobGet,
                     Capability::Quota => Permission::JmapQuotaGet,
                     Capability::FileNode => Permission::JmapFileNodeGet,
+                    Capability::Mdn => Permission::JmapMdnSend,
+                    Capability::SmimeVerify => Permission::JmapEmailGet,
+                    Capability::Tasks => Permission::JmapTaskGet,
+                    Capability::Metadata => Permission::JmapMailboxGet,
+                    Capability::VirtualMailbox => Permission::JmapVirtualMailboxGet,
                     Capability::WebSocket
                     | Capability::Principals
                     | Capability::PrincipalsAvailability => return true,
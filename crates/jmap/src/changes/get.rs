@@ -5,7 +5,8 @@
  */
 
 use crate::api::auth::JmapAuthorization;
-use common::{Server, auth::AccessToken};
+use common::{DavResources, Server, auth::AccessToken};
+use groupware::cache::GroupwareCache;
 use jmap_proto::{
     method::changes::{ChangesRequest, ChangesResponse},
     object::{JmapObject, NullObject, mailbox::MailboxProperty},
@@ -100,6 +101,16 @@ impl ChangesLookup for Server {
 
                 (SyncCollection::ShareNotification, false)
             }
+            MethodObject::TaskList => {
+                access_token.assert_has_access(request.account_id, Collection::Calendar)?;
+
+                (SyncCollection::Calendar, true)
+            }
+            MethodObject::Task => {
+                access_token.assert_has_access(request.account_id, Collection::CalendarEvent)?;
+
+                (SyncCollection::Calendar, false)
+            }
             _ => {
                 return Err(trc::JmapEvent::CannotCalculateChanges.into_err());
             }
@@ -123,6 +134,15 @@ impl ChangesLookup for Server {
         };
         let account_id = request.account_id.document_id();
 
+        // Tasks and task lists share the calendar changelog with events and calendars
+        let task_cache = if matches!(object, MethodObject::Task | MethodObject::TaskList) {
+            self.fetch_dav_resources(access_token, account_id, SyncCollection::Calendar)
+                .await?
+                .into()
+        } else {
+            None
+        };
+
         let (items_sent, changelog) = match &request.since_state {
             State::Initial => {
                 let changelog = self
@@ -185,8 +205,11 @@ impl ChangesLookup for Server {
             .changes
             .into_iter()
             .filter(|change| {
-                (is_container && change.is_container_change())
-                    || (!is_container && change.is_item_change())
+                ((is_container && change.is_container_change())
+                    || (!is_container && change.is_item_change()))
+                    && task_cache
+                        .as_ref()
+                        .is_none_or(|cache| is_task_change(cache, change))
             })
             .skip(items_sent)
             .peekable();
@@ -236,6 +259,25 @@ impl ChangesLookup for Server {
     }
 }
 
+fn is_task_change(cache: &DavResources, change: &Change) -> bool {
+    match change {
+        Change::InsertContainer(id)
+        | Change::UpdateContainer(id)
+        | Change::UpdateContainerProperty(id) => cache.has_task_list_id(&(*id as u32)),
+        Change::InsertItem(id) | Change::UpdateItem(id) => cache.has_task_id(&(*id as u32)),
+        // Destroyed objects can no longer be classified, report them unless
+        // their id has been reused by a calendar or event.
+        Change::DeleteContainer(id) => {
+            let id = *id as u32;
+            !cache.has_container_id(&id) || cache.has_task_list_id(&id)
+        }
+        Change::DeleteItem(id) => {
+            let id = *id as u32;
+            !cache.has_item_id(&id) || cache.has_task_id(&id)
+        }
+    }
+}
+
 impl IntermediateChangesResponse {
     pub fn into_method_response(self) -> ResponseMethod<'static> {
         ResponseMethod::Changes(match self.object {
@@ -283,14 +325,22 @@ impl IntermediateChangesResponse {
             MethodObject::ShareNotification => {
                 ChangesResponseMethod::ShareNotification(transmute_response(self.response))
             }
+            MethodObject::TaskList => {
+                ChangesResponseMethod::Calendar(transmute_response(self.response))
+            }
+            MethodObject::Task => {
+                ChangesResponseMethod::CalendarEvent(transmute_response(self.response))
+            }
             MethodObject::ParticipantIdentity
             | MethodObject::Core
             | MethodObject::Blob
             | MethodObject::PushSubscription
             | MethodObject::SearchSnippet
             | MethodObject::VacationResponse
+            | MethodObject::VirtualMailbox
             | MethodObject::SieveScript
             | MethodObject::Principal
+            | MethodObject::Mdn
             | MethodObject::Quota => unreachable!(),
         })
     }
@@ -11,6 +11,7 @@ use crate::{
     contact::query::ContactCardQuery, email::query::EmailQuery, file::query::FileNodeQuery,
     mailbox::query::MailboxQuery, share_notification::query::ShareNotificationQuery,
     sieve::query::SieveScriptQuery, submission::query::EmailSubmissionQuery,
+    task::query::TaskQuery,
 };
 use common::{Server, auth::AccessToken};
 use jmap_proto::{
@@ -255,6 +256,28 @@ impl QueryChanges for Server {
                 up_to_id = request.up_to_id;
                 results = self.share_notification_query(request.into()).await?;
             }
+            QueryChangesRequestMethod::Task(mut request) => {
+                // Query changes
+                set_account_id_if_missing(&mut request.account_id, access_token);
+                changes = self
+                    .changes(
+                        build_changes_request(&request),
+                        MethodObject::Task,
+                        access_token,
+                    )
+                    .await?
+                    .response;
+                let calculate_total = request.calculate_total.unwrap_or(false);
+                has_changes = changes.has_changes();
+                response = build_query_changes_response(&request, &changes);
+
+                if !has_changes && !calculate_total {
+                    return Ok(response);
+                }
+
+                up_to_id = request.up_to_id;
+                results = self.task_query(request.into(), access_token).await?;
+            }
             QueryChangesRequestMethod::Principal(_) => {
                 return Err(trc::JmapEvent::CannotCalculateChanges.into_err());
             }
@@ -16,6 +16,8 @@ use email::{
         ArchivedMetadataPartType, MESSAGE_HAS_ATTACHMENT, MESSAGE_RECEIVED_MASK, MessageMetadata,
         MetadataHeaderName, PART_ENCODING_PROBLEM,
     },
+    message::smime::{SmimeStatus, SmimeVerification, SmimeVerify, verify_smime},
+    message::snooze::Snooze,
 };
 use jmap_proto::{
     method::get::{GetRequest, GetResponse},
@@ -24,11 +26,11 @@ use jmap_proto::{
     types::date::UTCDate,
 };
 use jmap_tools::{Key, Map, Value};
-use mail_parser::HeaderValue;
+use mail_parser::{HeaderValue, MessageParser};
 use std::future::Future;
 use store::{
     ValueKey,
-    write::{AlignedBytes, Archive},
+    write::{AlignedBytes, Archive, now},
 };
 use trc::{AddContext, StoreEvent};
 use types::{
@@ -136,17 +138,23 @@ impl EmailGet for Server {
 
         // Check if we need to fetch the raw headers or body
         let mut needs_body = false;
+        let mut needs_smime = false;
         for property in &properties {
-            if matches!(
-                property,
+            match property {
                 EmailProperty::BodyValues
-                    | EmailProperty::TextBody
-                    | EmailProperty::HtmlBody
-                    | EmailProperty::Attachments
-                    | EmailProperty::BodyStructure
-            ) {
-                needs_body = true;
-                break;
+                | EmailProperty::TextBody
+                | EmailProperty::HtmlBody
+                | EmailProperty::Attachments
+                | EmailProperty::BodyStructure => {
+                    needs_body = true;
+                }
+                EmailProperty::SmimeStatus
+                | EmailProperty::SmimeErrors
+                | EmailProperty::SmimeVerifiedAt => {
+                    needs_body = true;
+                    needs_smime = true;
+                }
+                _ => {}
             }
         }
 
@@ -226,6 +234,35 @@ impl EmailGet for Server {
                 section: None,
             };
 
+            // Verify S/MIME signatures, messages encrypted at rest report
+            // the status obtained at delivery time
+            let (smime, smime_verified_at) = if needs_smime {
+                let raw_message = raw_message.to_bytes();
+                let live = MessageParser::new()
+                    .parse(&raw_message)
+                    .and_then(|message| {
+                        verify_smime(&message, &self.core.jmap.smime_trust_anchors, now())
+                    });
+                if live
+                    .as_ref()
+                    .is_some_and(|live| live.status != SmimeStatus::Unknown)
+                {
+                    (live, now())
+                } else {
+                    (
+                        SmimeVerification::or_at_delivery(
+                            live,
+                            self.get_smime_status(account_id, id.document_id())
+                                .await
+                                .caused_by(trc::location!())?,
+                        ),
+                        metadata.rcvd_attach.to_native() & MESSAGE_RECEIVED_MASK,
+                    )
+                }
+            } else {
+                (None, 0)
+            };
+
             // Prepare response
             let mut email: Map<'_, EmailProperty, EmailValue> =
                 Map::with_capacity(properties.len());
@@ -274,6 +311,79 @@ impl EmailGet for Server {
                             )),
                         );
                     }
+                    EmailProperty::Snoozed => {
+                        let snooze = self
+                            .get_snooze(account_id, id.document_id())
+                            .await
+                            .caused_by(trc::location!())?;
+                        email.insert_unchecked(
+                            EmailProperty::Snoozed,
+                            snooze.map_or(Value::Null, |snooze| {
+                                Value::Object(
+                                    Map::with_capacity(2)
+                                        .with_key_value(
+                                            EmailProperty::Until,
+                                            EmailValue::Date(UTCDate::from_timestamp(
+                                                snooze.until as i64,
+                                            )),
+                                        )
+                                        .with_key_value(
+                                            EmailProperty::MoveToMailboxId,
+                                            snooze.move_to.map_or(Value::Null, |id| {
+                                                Value::Element(EmailValue::Id(Id::from(id)))
+                                            }),
+                                        ),
+                                )
+                            }),
+                        );
+                    }
+                    EmailProperty::SmimeStatus => {
+                        email.insert_unchecked(
+                            EmailProperty::SmimeStatus,
+                            smime.as_ref().map_or(Value::Null, |smime| {
+                                Value::Str(smime.status.as_str().into())
+                            }),
+                        );
+                    }
+                    EmailProperty::SmimeErrors => {
+                        email.insert_unchecked(
+                            EmailProperty::SmimeErrors,
+                            smime
+                                .as_ref()
+                                .filter(|smime| !smime.errors.is_empty())
+                                .map_or(Value::Null, |smime| {
+                                    Value::Array(
+                                        smime
+                                            .errors
+                                            .iter()
+                                            .map(|error| Value::Str(error.clone().into()))
+                                            .collect(),
+                                    )
+                                }),
+                        );
+                    }
+                    EmailProperty::SmimeVerifiedAt => {
+                        email.insert_unchecked(
+                            EmailProperty::SmimeVerifiedAt,
+                            smime
+                                .as_ref()
+                                .filter(|smime| smime.status != SmimeStatus::Unknown)
+                                .map_or(Value::Null, |_| {
+                                    Value::Element(EmailValue::Date(UTCDate::from_timestamp(
+                                        smime_verified_at as i64,
+                                    )))
+                                }),
+                        );
+                    }
+                    EmailProperty::SmimeStatusAtDelivery => {
+                        email.insert_unchecked(
+                            EmailProperty::SmimeStatusAtDelivery,
+                            self.get_smime_status(account_id, id.document_id())
+                                .await
+                                .caused_by(trc::location!())?
+                                .map_or(Value::Null, |status| Value::Str(status.as_str().into())),
+                        );
+                    }
                     EmailProperty::Preview => {
                         if !metadata.preview.is_empty() {
                             email.insert_unchecked(
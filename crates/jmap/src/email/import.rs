@@ -161,6 +161,8 @@ impl EmailImport for Server {
                     keywords: email.keywords,
                     received_at: email.received_at.map(|r| r.into()),
                     session_id: session.session_id,
+                    batch: None,
+                    released_quota: 0,
                 })
                 .await
             {
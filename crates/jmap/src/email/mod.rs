@@ -66,6 +66,13 @@ pub(crate) fn handle_email_patch<'x>(
             }
             _ => (),
         },
+        (Some(JsonPointerItem::Key(Key::Property(EmailProperty::Snoozed))), _) => {
+            return PatchResult::Invalid(
+                SetError::invalid_properties()
+                    .with_property(EmailProperty::Pointer(pointer.clone()))
+                    .with_description("The snoozed property has to be set as a whole."),
+            );
+        }
         _ => (),
     }
 
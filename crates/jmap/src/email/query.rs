@@ -6,7 +6,10 @@
 
 use crate::{api::query::QueryResponseBuilder, changes::state::JmapCacheState};
 use common::{MessageStoreCache, Server, auth::AccessToken};
-use email::cache::{MessageCacheFetch, email::MessageCacheAccess};
+use email::{
+    cache::{MessageCacheFetch, email::MessageCacheAccess},
+    message::smime::SmimeVerify,
+};
 use jmap_proto::{
     method::query::{Filter, QueryRequest, QueryResponse},
     object::email::{Email, EmailComparator, EmailFilter},
@@ -252,6 +255,49 @@ impl EmailQuery for Server {
                                 .map(|item| item.document_id),
                         )))
                     }
+
+                    // RFC 9219
+                    EmailFilter::HasSmime(has_smime) => {
+                        let set = RoaringBitmap::from_iter(
+                            self.get_smime_statuses(account_id)
+                                .await
+                                .caused_by(trc::location!())?
+                                .into_iter()
+                                .map(|(document_id, _)| document_id),
+                        );
+                        push_set_filter(&mut filters, set, has_smime);
+                    }
+                    EmailFilter::HasVerifiedSmime(is_verified) => {
+                        // Signatures are verified again to match the smimeStatus
+                        // property, only messages signed at delivery are checked.
+                        let mut set = RoaringBitmap::new();
+                        for (document_id, status) in self
+                            .get_smime_statuses(account_id)
+                            .await
+                            .caused_by(trc::location!())?
+                        {
+                            if self
+                                .verify_smime_status(account_id, document_id, status.into())
+                                .await
+                                .caused_by(trc::location!())?
+                                .is_some_and(|smime| smime.status.is_verified())
+                            {
+                                set.insert(document_id);
+                            }
+                        }
+                        push_set_filter(&mut filters, set, is_verified);
+                    }
+                    EmailFilter::HasVerifiedSmimeAtDelivery(is_verified) => {
+                        let set = RoaringBitmap::from_iter(
+                            self.get_smime_statuses(account_id)
+                                .await
+                                .caused_by(trc::location!())?
+                                .into_iter()
+                                .filter(|(_, status)| status.is_verified())
+                                .map(|(document_id, _)| document_id),
+                        );
+                        push_set_filter(&mut filters, set, is_verified);
+                    }
                     other => {
                         return Err(trc::JmapEvent::UnsupportedFilter
                             .into_err()
@@ -424,3 +470,13 @@ fn thread_keywords(cache: &MessageStoreCache, keyword: Keyword, match_all: bool)
 
     matched_ids
 }
+
+fn push_set_filter(filters: &mut Vec<SearchFilter>, set: RoaringBitmap, is_in_set: bool) {
+    if is_in_set {
+        filters.push(SearchFilter::is_in_set(set));
+    } else {
+        filters.push(SearchFilter::Not);
+        filters.push(SearchFilter::is_in_set(set));
+        filters.push(SearchFilter::End);
+    }
+}
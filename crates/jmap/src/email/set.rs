@@ -20,6 +20,8 @@ use email::{
         delete::EmailDeletion,
         ingest::{EmailIngest, IngestEmail, IngestSource},
         metadata::MessageData,
+        savedate::SaveDateBatch,
+        snooze::{EmailSnooze, Snooze, SnoozeBatch},
     },
 };
 use http_proto::HttpSessionData;
@@ -47,7 +49,7 @@ use store::{
     ValueKey,
     ahash::AHashMap,
     roaring::RoaringBitmap,
-    write::{AlignedBytes, Archive, BatchBuilder},
+    write::{AlignedBytes, Archive, BatchBuilder, now},
 };
 use trc::AddContext;
 use types::{
@@ -55,6 +57,7 @@ use types::{
     collection::{Collection, SyncCollection, VanishedCollection},
     id::Id,
     keyword::{ArchivedKeyword, Keyword},
+    special_use::SpecialUse,
     type_state::{DataType, StateChange},
 };
 
@@ -676,6 +679,18 @@ impl EmailSet for Server {
 
                     (_, Value::Null) => (),
 
+                    (EmailProperty::Snoozed, _) => {
+                        response.not_created.append(
+                            id,
+                            SetError::invalid_properties()
+                                .with_property(EmailProperty::Snoozed)
+                                .with_description(
+                                    "Messages can only be snoozed once they have been created.",
+                                ),
+                        );
+                        continue 'create;
+                    }
+
                     (property, _) => {
                         response.invalid_property_create(id, property);
                         continue 'create;
@@ -766,6 +781,8 @@ impl EmailSet for Server {
                         train_classifier: true,
                     },
                     session_id: session.session_id,
+                    batch: None,
+                    released_quota: 0,
                 })
                 .await
             {
@@ -790,6 +807,7 @@ impl EmailSet for Server {
         let mut batch = BatchBuilder::new();
         let mut changed_mailboxes: AHashMap<u32, Vec<u32>> = AHashMap::new();
         let mut will_update = Vec::with_capacity(request.update.as_ref().map_or(0, |u| u.len()));
+        let mut has_snooze_tasks = false;
         'update: for (id, object) in request.unwrap_update().into_valid() {
             // Make sure id won't be destroyed
             if will_destroy.contains(&id) {
@@ -818,6 +836,7 @@ impl EmailSet for Server {
                 .to_unarchived::<MessageData>()
                 .caused_by(trc::location!())?;
             let mut new_data = data.inner.to_builder();
+            let mut snooze_update = None;
 
             for (property, mut value) in object.into_expanded_object() {
                 if let Err(err) = response.resolve_self_references(&mut value) {
@@ -848,6 +867,65 @@ impl EmailSet for Server {
                                 .collect(),
                         );
                     }
+                    (Key::Property(EmailProperty::Snoozed), Value::Object(mut snooze)) => {
+                        let until = match snooze.remove(&Key::Property(EmailProperty::Until)) {
+                            Some(Value::Element(EmailValue::Date(until))) => {
+                                until.timestamp() as u64
+                            }
+                            _ => {
+                                response.not_updated.append(
+                                    id,
+                                    SetError::invalid_properties()
+                                        .with_property((
+                                            EmailProperty::Snoozed,
+                                            EmailProperty::Until,
+                                        ))
+                                        .with_description("Missing or invalid snooze date."),
+                                );
+                                continue 'update;
+                            }
+                        };
+                        let move_to =
+                            match snooze.remove(&Key::Property(EmailProperty::MoveToMailboxId)) {
+                                Some(Value::Element(EmailValue::Id(mailbox_id)))
+                                    if cache.has_mailbox_id(&mailbox_id.document_id()) =>
+                                {
+                                    Some(mailbox_id.document_id())
+                                }
+                                Some(Value::Null) | None => None,
+                                _ => {
+                                    response.not_updated.append(
+                                        id,
+                                        SetError::invalid_properties()
+                                            .with_property((
+                                                EmailProperty::Snoozed,
+                                                EmailProperty::MoveToMailboxId,
+                                            ))
+                                            .with_description("Invalid or unknown mailbox id."),
+                                    );
+                                    continue 'update;
+                                }
+                            };
+
+                        // Restore the message to its current mailboxes by default
+                        let snoozed_id = cache
+                            .mailbox_by_role(&SpecialUse::Snoozed)
+                            .map(|mailbox| mailbox.document_id);
+                        snooze_update = Some(Some(EmailSnooze {
+                            until,
+                            move_to,
+                            restore_to: data
+                                .inner
+                                .mailboxes
+                                .iter()
+                                .map(|m| m.mailbox_id.to_native())
+                                .filter(|mailbox_id| Some(*mailbox_id) != snoozed_id)
+                                .collect(),
+                        }));
+                    }
+                    (Key::Property(EmailProperty::Snoozed), Value::Null) => {
+                        snooze_update = Some(None);
+                    }
                     (Key::Property(EmailProperty::Pointer(pointer)), value) => {
                         match handle_email_patch(&pointer, value) {
                             PatchResult::SetKeyword(keyword) => {
@@ -875,9 +953,26 @@ impl EmailSet for Server {
                 }
             }
 
+            // Process snooze changes
+            let mut snooze_changes = None;
+            if let Some(snooze_update) = snooze_update {
+                let current_snooze = self
+                    .get_snooze(account_id, document_id)
+                    .await
+                    .caused_by(trc::location!())?;
+                if current_snooze != snooze_update {
+                    if snooze_update.is_some() {
+                        new_data.add_keyword(EmailSnooze::keyword());
+                    } else {
+                        new_data.remove_keyword(&EmailSnooze::keyword());
+                    }
+                    snooze_changes = Some((current_snooze, snooze_update));
+                }
+            }
+
             let has_keyword_changes = new_data.has_keyword_changes(data.inner);
             let has_mailbox_changes = new_data.has_mailbox_changes(data.inner);
-            if !has_keyword_changes && !has_mailbox_changes {
+            if !has_keyword_changes && !has_mailbox_changes && snooze_changes.is_none() {
                 response.updated.append(id, None);
                 continue 'update;
             }
@@ -937,6 +1032,8 @@ impl EmailSet for Server {
             }
 
             // Process mailboxes
+            let mut assigned_uids = Vec::new();
+            let mut removed_uids = Vec::new();
             if has_mailbox_changes {
                 // Make sure the message is at least in one mailbox
                 if new_data.mailboxes.is_empty() {
@@ -1006,6 +1103,10 @@ impl EmailSet for Server {
                             .entry(mailbox_id.mailbox_id.to_native())
                             .or_default()
                             .push(mailbox_id.uid.to_native());
+                        removed_uids.push((
+                            mailbox_id.mailbox_id.to_native(),
+                            mailbox_id.uid.to_native(),
+                        ));
                     } else {
                         response.not_updated.append(
                             id,
@@ -1038,6 +1139,7 @@ impl EmailSet for Server {
                     .zip(ids)
                 {
                     uid_mailbox.uid = uid;
+                    assigned_uids.push((uid_mailbox.mailbox_id, uid));
                 }
             }
 
@@ -1052,6 +1154,24 @@ impl EmailSet for Server {
                         .with_changes(new_data.seal()),
                 )
                 .caused_by(trc::location!())?;
+            if !assigned_uids.is_empty() {
+                let saved_at = now();
+                for (mailbox_id, uid) in assigned_uids {
+                    batch.set_save_date(mailbox_id, uid, saved_at);
+                }
+            }
+            for (mailbox_id, uid) in removed_uids {
+                batch.clear_save_date(mailbox_id, uid);
+            }
+            if let Some((current_snooze, new_snooze)) = snooze_changes {
+                if let Some(current_snooze) = current_snooze {
+                    batch.clear_snooze(&current_snooze);
+                }
+                if let Some(new_snooze) = new_snooze {
+                    batch.set_snooze(&new_snooze);
+                    has_snooze_tasks = true;
+                }
+            }
 
             if let Some(train_spam) = train_spam {
                 self.add_account_spam_sample(
@@ -1086,6 +1206,11 @@ impl EmailSet for Server {
                 Ok(change_id) => {
                     last_change_id = change_id.into();
 
+                    // Wake up the task manager to schedule snoozed messages
+                    if has_snooze_tasks {
+                        self.notify_task_queue();
+                    }
+
                     // Add to updated list
                     for id in will_update {
                         response.updated.append(id, None);
@@ -5,15 +5,27 @@
  */
 
 use common::{Server, auth::AccessToken, sharing::EffectiveAcl};
-use email::cache::{MessageCacheFetch, email::MessageCacheAccess, mailbox::MailboxCacheAccess};
+use email::{
+    cache::{MessageCacheFetch, email::MessageCacheAccess, mailbox::MailboxCacheAccess},
+    mailbox::metadata::MailboxMetadata,
+};
 use jmap_proto::{
     method::get::{GetRequest, GetResponse},
     object::mailbox::{Mailbox, MailboxProperty, MailboxValue},
+    request::capability::{Capability, CapabilityIds},
 };
-use jmap_tools::{Map, Value};
+use jmap_tools::{Key, Map, Value};
 use std::future::Future;
-use store::ahash::AHashSet;
-use types::{acl::Acl, keyword::Keyword, special_use::SpecialUse};
+use store::{
+    ValueKey,
+    ahash::AHashSet,
+    write::{AlignedBytes, Archive},
+};
+use trc::AddContext;
+use types::{
+    acl::Acl, collection::Collection, field::MailboxField, keyword::Keyword,
+    special_use::SpecialUse,
+};
 
 use crate::api::acl::JmapRights;
 
@@ -21,6 +33,7 @@ pub trait MailboxGet: Sync + Send {
     fn mailbox_get(
         &self,
         request: GetRequest<Mailbox>,
+        using: CapabilityIds,
         access_token: &AccessToken,
     ) -> impl Future<Output = trc::Result<GetResponse<Mailbox>>> + Send;
 }
@@ -29,6 +42,7 @@ impl MailboxGet for Server {
     async fn mailbox_get(
         &self,
         mut request: GetRequest<Mailbox>,
+        using: CapabilityIds,
         access_token: &AccessToken,
     ) -> trc::Result<GetResponse<Mailbox>> {
         let ids = request.unwrap_ids(self.core.jmap.get_max_objects)?;
@@ -45,6 +59,13 @@ impl MailboxGet for Server {
             MailboxProperty::UnreadThreads,
             MailboxProperty::MyRights,
         ]);
+        if !using.contains(Capability::Metadata) && properties.contains(&MailboxProperty::Metadata)
+        {
+            return Err(trc::JmapEvent::InvalidArguments.into_err().details(format!(
+                "The metadata property requires the {} capability.",
+                Capability::Metadata.as_str()
+            )));
+        }
         let account_id = request.account_id.document_id();
         let cache = self.get_cached_messages(account_id).await?;
         let shared_ids = if access_token.is_shared(account_id) {
@@ -151,6 +172,45 @@ impl MailboxGet for Server {
                         access_token,
                         &cached_mailbox.acls,
                     ),
+                    MailboxProperty::Metadata => {
+                        let can_read_shared = !access_token.is_shared(account_id)
+                            || cached_mailbox
+                                .acls
+                                .as_slice()
+                                .effective_acl(access_token)
+                                .contains(Acl::ReadItems);
+                        let metadata = self
+                            .store()
+                            .get_value::<Archive<AlignedBytes>>(ValueKey::property(
+                                account_id,
+                                Collection::Mailbox,
+                                document_id,
+                                MailboxField::Metadata,
+                            ))
+                            .await
+                            .caused_by(trc::location!())?
+                            .map(|archive| archive.deserialize::<MailboxMetadata>())
+                            .transpose()
+                            .caused_by(trc::location!())?
+                            .unwrap_or_default();
+
+                        Value::Object(
+                            metadata
+                                .visible_to(access_token.primary_id())
+                                .filter(|entry| entry.owner_id.is_some() || can_read_shared)
+                                .map(|entry| {
+                                    (
+                                        Key::Owned(entry.name.clone()),
+                                        Value::Str(
+                                            String::from_utf8_lossy(&entry.value)
+                                                .into_owned()
+                                                .into(),
+                                        ),
+                                    )
+                                })
+                                .collect(),
+                        )
+                    }
                     _ => Value::Null,
                 };
 
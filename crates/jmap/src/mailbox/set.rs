@@ -18,6 +18,7 @@ use email::{
     mailbox::{
         Mailbox,
         destroy::{MailboxDestroy, MailboxDestroyError},
+        metadata::{MailboxMetadata, parse_entry_name},
     },
 };
 use jmap_proto::{
@@ -25,15 +26,18 @@ use jmap_proto::{
     method::set::{SetRequest, SetResponse},
     object::mailbox::{self, MailboxProperty, MailboxValue},
     references::resolve::ResolveCreatedReference,
-    request::IntoValid,
+    request::{
+        IntoValid,
+        capability::{Capability, CapabilityIds},
+    },
     types::state::State,
 };
 use jmap_tools::{JsonPointerItem, Key, Map, Value};
 use std::future::Future;
 use store::{
-    ValueKey,
+    Serialize, ValueKey,
     roaring::RoaringBitmap,
-    write::{AlignedBytes, Archive, BatchBuilder, assert::AssertValue},
+    write::{AlignedBytes, Archive, Archiver, BatchBuilder, assert::AssertValue},
 };
 use trc::AddContext;
 use types::{
@@ -44,15 +48,23 @@ pub struct SetContext<'x> {
     account_id: u32,
     access_token: &'x AccessToken,
     is_shared: bool,
+    using: CapabilityIds,
     response: SetResponse<mailbox::Mailbox>,
     mailbox_ids: RoaringBitmap,
     will_destroy: Vec<Id>,
 }
 
+type MetadataChanges = (Option<Archive<AlignedBytes>>, MailboxMetadata);
+type MetadataSet<'x> = Vec<(
+    Key<'x, MailboxProperty>,
+    Value<'x, MailboxProperty, MailboxValue>,
+)>;
+
 pub trait MailboxSet: Sync + Send {
     fn mailbox_set(
         &self,
         request: SetRequest<'_, mailbox::Mailbox>,
+        using: CapabilityIds,
         access_token: &AccessToken,
     ) -> impl Future<Output = trc::Result<SetResponse<mailbox::Mailbox>>> + Send;
 
@@ -66,6 +78,14 @@ pub trait MailboxSet: Sync + Send {
             Result<ObjectIndexBuilder<Mailbox, Mailbox>, SetError<MailboxProperty>>,
         >,
     > + Send;
+
+    fn mailbox_set_metadata(
+        &self,
+        changes: MetadataSet<'_>,
+        document_id: Option<u32>,
+        can_modify_shared: bool,
+        ctx: &SetContext,
+    ) -> impl Future<Output = trc::Result<Result<MetadataChanges, SetError<MailboxProperty>>>> + Send;
 }
 
 impl MailboxSet for Server {
@@ -73,6 +93,7 @@ impl MailboxSet for Server {
     async fn mailbox_set(
         &self,
         mut request: SetRequest<'_, mailbox::Mailbox>,
+        using: CapabilityIds,
         access_token: &AccessToken,
     ) -> trc::Result<SetResponse<mailbox::Mailbox>> {
         // Prepare response
@@ -83,6 +104,7 @@ impl MailboxSet for Server {
             account_id,
             is_shared: access_token.is_shared(account_id),
             access_token,
+            using,
             response: SetResponse::from_request(&request, self.core.jmap.set_max_objects)?
                 .with_state(cache.assert_state(true, &request.if_in_state)?),
             mailbox_ids: RoaringBitmap::from_iter(cache.mailboxes.index.keys()),
@@ -109,6 +131,23 @@ impl MailboxSet for Server {
                 continue 'create;
             }
 
+            // Validate metadata
+            let (object, metadata) = take_metadata(object);
+            let metadata = if !metadata.is_empty() {
+                match self
+                    .mailbox_set_metadata(metadata, None, true, &ctx)
+                    .await?
+                {
+                    Ok(metadata) => Some(metadata),
+                    Err(err) => {
+                        ctx.response.not_created.append(id, err);
+                        continue 'create;
+                    }
+                }
+            } else {
+                None
+            };
+
             match self.mailbox_set_item(object, None, &ctx).await? {
                 Ok(builder) => {
                     batch
@@ -131,8 +170,11 @@ impl MailboxSet for Server {
                     batch
                         .with_document(document_id)
                         .custom(builder)
-                        .caused_by(trc::location!())?
-                        .commit_point();
+                        .caused_by(trc::location!())?;
+                    if let Some(metadata) = metadata {
+                        write_metadata(&mut batch, metadata)?;
+                    }
+                    batch.commit_point();
 
                     ctx.mailbox_ids.insert(document_id);
                     ctx.response.created(id, document_id);
@@ -183,8 +225,10 @@ impl MailboxSet for Server {
                 let mailbox = mailbox
                     .into_deserialized::<email::mailbox::Mailbox>()
                     .caused_by(trc::location!())?;
+                let mut can_modify_shared = true;
                 if ctx.is_shared {
                     let acl = mailbox.inner.acls.effective_acl(access_token);
+                    can_modify_shared = acl.contains(Acl::ModifyItems);
                     if !acl.contains(Acl::Modify) {
                         ctx.response.not_updated.append(
                             id,
@@ -205,6 +249,23 @@ impl MailboxSet for Server {
                     }
                 }
 
+                // Validate metadata
+                let (object, metadata) = take_metadata(object);
+                let metadata = if !metadata.is_empty() {
+                    match self
+                        .mailbox_set_metadata(metadata, Some(document_id), can_modify_shared, &ctx)
+                        .await?
+                    {
+                        Ok(metadata) => Some(metadata),
+                        Err(err) => {
+                            ctx.response.not_updated.append(id, err);
+                            continue 'update;
+                        }
+                    }
+                } else {
+                    None
+                };
+
                 match self
                     .mailbox_set_item(object, (document_id, mailbox).into(), &ctx)
                     .await?
@@ -224,8 +285,11 @@ impl MailboxSet for Server {
                         batch
                             .with_document(document_id)
                             .custom(builder)
-                            .caused_by(trc::location!())?
-                            .commit_point();
+                            .caused_by(trc::location!())?;
+                        if let Some(metadata) = metadata {
+                            write_metadata(&mut batch, metadata)?;
+                        }
+                        batch.commit_point();
                         will_update.push(id);
                     }
                     Err(err) => {
@@ -584,4 +648,186 @@ impl MailboxSet for Server {
             .with_changes(changes)
             .with_current_opt(current)))
     }
+
+    async fn mailbox_set_metadata(
+        &self,
+        changes: MetadataSet<'_>,
+        document_id: Option<u32>,
+        can_modify_shared: bool,
+        ctx: &SetContext<'_>,
+    ) -> trc::Result<Result<MetadataChanges, SetError<MailboxProperty>>> {
+        if !ctx.using.contains(Capability::Metadata) {
+            return Ok(Err(SetError::invalid_properties()
+                .with_property(MailboxProperty::Metadata)
+                .with_description(format!(
+                    "The metadata property requires the {} capability.",
+                    Capability::Metadata.as_str()
+                ))));
+        }
+
+        // Setting the property replaces the entries, patching "metadata/<entry>"
+        // sets or removes a single entry as with SETMETADATA
+        let mut replace = false;
+        let mut has_patches = false;
+        let mut entries = Vec::with_capacity(changes.len());
+        for (property, value) in changes {
+            match (property, value) {
+                (Key::Property(MailboxProperty::Metadata), Value::Object(value)) => {
+                    replace = true;
+                    for (name, value) in value.into_vec() {
+                        entries.push((
+                            name.as_string_key().and_then(parse_entry_name),
+                            name.to_string(),
+                            value,
+                        ));
+                    }
+                }
+                (Key::Property(MailboxProperty::Metadata), Value::Null) => {
+                    replace = true;
+                }
+                (Key::Property(MailboxProperty::Pointer(pointer)), value) => {
+                    let mut path = pointer.iter().skip(1);
+                    let name = path
+                        .next()
+                        .and_then(|item| item.as_string_key())
+                        .filter(|_| path.next().is_none());
+                    has_patches = true;
+                    entries.push((
+                        name.and_then(parse_entry_name),
+                        name.map_or_else(|| pointer.to_string(), |name| name.to_string()),
+                        value,
+                    ));
+                }
+                _ => {
+                    return Ok(Err(SetError::invalid_properties()
+                        .with_property(MailboxProperty::Metadata)
+                        .with_description("Metadata must be an object.")));
+                }
+            }
+        }
+        if replace && has_patches {
+            return Ok(Err(SetError::invalid_properties()
+                .with_property(MailboxProperty::Metadata)
+                .with_description(
+                    "Metadata cannot be replaced and patched at the same time.",
+                )));
+        }
+
+        let current = if let Some(document_id) = document_id {
+            self.store()
+                .get_value::<Archive<AlignedBytes>>(ValueKey::property(
+                    ctx.account_id,
+                    Collection::Mailbox,
+                    document_id,
+                    MailboxField::Metadata,
+                ))
+                .await
+                .caused_by(trc::location!())?
+        } else {
+            None
+        };
+        let mut metadata = current
+            .as_ref()
+            .map(|current| current.deserialize::<MailboxMetadata>())
+            .transpose()
+            .caused_by(trc::location!())?
+            .unwrap_or_default();
+
+        // Private entries of other accounts are not visible and therefore kept,
+        // as are shared entries the caller is not allowed to modify
+        let account_id = ctx.access_token.primary_id();
+        if replace {
+            metadata.entries.retain(|entry| match entry.owner_id {
+                Some(owner_id) => owner_id != account_id,
+                None => !can_modify_shared,
+            });
+        }
+
+        let max_size = self.core.imap.metadata_max_size;
+        for (name, display_name, value) in entries {
+            let Some(name) = name else {
+                return Ok(Err(SetError::invalid_properties()
+                    .with_property(MailboxProperty::Metadata)
+                    .with_description(format!(
+                        "Invalid metadata entry name '{display_name}'."
+                    ))));
+            };
+            let owner_id = name.starts_with("/private/").then_some(account_id);
+            if owner_id.is_none() && !can_modify_shared {
+                return Ok(Err(SetError::forbidden().with_description(
+                    "You do not have enough permissions to modify shared entries.",
+                )));
+            }
+
+            let value = match value {
+                Value::Str(value) if value.len() <= max_size => Some(value.as_bytes().to_vec()),
+                Value::Null => None,
+                _ => {
+                    return Ok(Err(SetError::invalid_properties()
+                        .with_property(MailboxProperty::Metadata)
+                        .with_description(format!(
+                            "Metadata entry '{name}' must be a string of at most {max_size} bytes or null."
+                        ))));
+                }
+            };
+            metadata.set(name, owner_id, value);
+        }
+
+        let max_entries = self.core.imap.metadata_max_entries;
+        if metadata.count(Some(account_id)) > max_entries || metadata.count(None) > max_entries {
+            return Ok(Err(SetError::new(SetErrorType::OverQuota)
+                .with_description("Too many metadata entries.")));
+        }
+
+        Ok(Ok((current, metadata)))
+    }
+}
+
+fn take_metadata(
+    object: Map<'_, MailboxProperty, MailboxValue>,
+) -> (Map<'_, MailboxProperty, MailboxValue>, MetadataSet<'_>) {
+    let mut changes = Map::new();
+    let mut metadata = Vec::new();
+    for (property, value) in object.into_vec() {
+        match &property {
+            Key::Property(MailboxProperty::Metadata) => {
+                metadata.push((property, value));
+            }
+            Key::Property(MailboxProperty::Pointer(pointer))
+                if matches!(
+                    pointer.first(),
+                    Some(JsonPointerItem::Key(Key::Property(
+                        MailboxProperty::Metadata
+                    )))
+                ) =>
+            {
+                metadata.push((property, value));
+            }
+            _ => {
+                changes.insert_unchecked(property, value);
+            }
+        }
+    }
+    (changes, metadata)
+}
+
+fn write_metadata(
+    batch: &mut BatchBuilder,
+    (current, metadata): MetadataChanges,
+) -> trc::Result<()> {
+    let has_current = current.is_some();
+    if let Some(current) = current {
+        batch.assert_value(MailboxField::Metadata, current);
+    }
+    if !metadata.is_empty() {
+        batch.set(
+            MailboxField::Metadata,
+            Archiver::new(metadata)
+                .serialize()
+                .caused_by(trc::location!())?,
+        );
+    } else if has_current {
+        batch.clear(MailboxField::Metadata);
+    }
+    Ok(())
 }
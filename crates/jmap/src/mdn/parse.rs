@@ -0,0 +1,158 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use crate::blob::download::BlobDownload;
+use common::{Server, auth::AccessToken};
+use jmap_proto::{
+    method::mdn::{MdnParseRequest, MdnParseResponse},
+    object::mdn::{Mdn, MdnActionMode, MdnDisposition, MdnDispositionType, MdnSendingMode},
+    request::IntoValid,
+};
+use mail_parser::{MessageParser, MimeHeaders};
+use std::future::Future;
+use utils::map::vec_map::VecMap;
+
+pub trait MdnParse: Sync + Send {
+    fn mdn_parse(
+        &self,
+        request: MdnParseRequest,
+        access_token: &AccessToken,
+    ) -> impl Future<Output = trc::Result<MdnParseResponse>> + Send;
+}
+
+impl MdnParse for Server {
+    async fn mdn_parse(
+        &self,
+        request: MdnParseRequest,
+        access_token: &AccessToken,
+    ) -> trc::Result<MdnParseResponse> {
+        if request.blob_ids.len() > self.core.jmap.mail_parse_max_items {
+            return Err(trc::JmapEvent::RequestTooLarge.into_err());
+        }
+
+        let mut response = MdnParseResponse {
+            account_id: request.account_id,
+            parsed: VecMap::with_capacity(request.blob_ids.len()),
+            not_parsable: vec![],
+            not_found: vec![],
+        };
+
+        for blob_id in request.blob_ids.into_valid() {
+            // Fetch raw message to parse
+            let raw_message = match self.blob_download(&blob_id, access_token).await? {
+                Some(raw_message) => raw_message,
+                None => {
+                    response.not_found.push(blob_id);
+                    continue;
+                }
+            };
+
+            // Locate the disposition notification part
+            let Some(message) = MessageParser::new().parse(&raw_message) else {
+                response.not_parsable.push(blob_id);
+                continue;
+            };
+            let Some(fields) = message
+                .parts
+                .iter()
+                .find(|part| {
+                    part.content_type().is_some_and(|ct| {
+                        ct.ctype().eq_ignore_ascii_case("message")
+                            && ct.subtype().is_some_and(|st| {
+                                st.eq_ignore_ascii_case("disposition-notification")
+                            })
+                    })
+                })
+                .and_then(|part| parse_mdn_fields(part.contents()))
+            else {
+                response.not_parsable.push(blob_id);
+                continue;
+            };
+
+            response.parsed.append(
+                blob_id,
+                Mdn {
+                    subject: message.subject().map(|subject| subject.to_string()),
+                    text_body: message.body_text(0).map(|text| text.into_owned()),
+                    ..fields
+                },
+            );
+        }
+
+        Ok(response)
+    }
+}
+
+fn parse_mdn_fields(contents: &[u8]) -> Option<Mdn> {
+    let contents = std::str::from_utf8(contents).ok()?;
+    let mut mdn = Mdn::default();
+    let mut fields: Vec<(&str, String)> = Vec::new();
+
+    // Unfold fields
+    for line in contents.lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = fields.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            fields.push((name.trim(), value.trim().to_string()));
+        } else if line.trim().is_empty() && !fields.is_empty() {
+            // Only the per-message fields are returned
+            break;
+        }
+    }
+
+    for (name, value) in fields {
+        match name.to_ascii_lowercase().as_str() {
+            "reporting-ua" => {
+                mdn.reporting_ua = value.into();
+            }
+            "mdn-gateway" => {
+                mdn.mdn_gateway = value.into();
+            }
+            "original-recipient" => {
+                mdn.original_recipient = value.into();
+            }
+            "final-recipient" => {
+                mdn.final_recipient = value.into();
+            }
+            "original-message-id" => {
+                mdn.original_message_id = value
+                    .trim_start_matches('<')
+                    .trim_end_matches('>')
+                    .to_string()
+                    .into();
+            }
+            "disposition" => {
+                mdn.disposition = parse_disposition(&value);
+            }
+            "error" => {
+                mdn.error.get_or_insert_with(Vec::new).push(value);
+            }
+            _ => {
+                mdn.extension_fields
+                    .get_or_insert_with(VecMap::new)
+                    .append(name.to_string(), value);
+            }
+        }
+    }
+
+    // Disposition is the only mandatory field
+    mdn.disposition.is_some().then_some(mdn)
+}
+
+fn parse_disposition(value: &str) -> Option<MdnDisposition> {
+    let (modes, type_) = value.split_once(';')?;
+    let (action_mode, sending_mode) = modes.split_once('/')?;
+    let type_ = type_.split_once('/').map_or(type_, |(type_, _)| type_);
+
+    Some(MdnDisposition {
+        action_mode: MdnActionMode::parse(action_mode.trim())?,
+        sending_mode: MdnSendingMode::parse(sending_mode.trim())?,
+        type_: MdnDispositionType::parse(type_.trim())?,
+    })
+}
@@ -0,0 +1,576 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use common::{
+    Server,
+    listener::{ServerInstance, stream::NullIo},
+    storage::index::ObjectIndexBuilder,
+};
+use email::{
+    identity::Identity,
+    message::metadata::{MessageData, MessageMetadata},
+};
+use jmap_proto::{
+    error::set::{SetError, SetErrorType},
+    method::{
+        mdn::{MdnSendRequest, MdnSendResponse},
+        set::SetRequest,
+    },
+    object::mdn::{Mdn, MdnProperty},
+    request::{
+        Call, MaybeInvalid, RequestMethod, SetRequestMethod,
+        method::{MethodFunction, MethodName, MethodObject},
+        reference::MaybeIdReference,
+    },
+};
+use mail_builder::{
+    MessageBuilder,
+    headers::{content_type::ContentType, message_id::MessageId},
+    mime::{BodyPart, MimePart, make_boundary},
+};
+use mail_parser::{HeaderForm, MessageParser};
+use smtp::core::{Session, SessionData};
+use smtp_proto::{MailFrom, RcptTo};
+use std::{borrow::Cow, collections::HashMap, fmt::Write, future::Future, sync::Arc};
+use store::{
+    ValueKey,
+    write::{AlignedBytes, Archive, BatchBuilder},
+};
+use trc::AddContext;
+use types::{
+    collection::Collection,
+    field::EmailField,
+    keyword::{ArchivedKeyword, Keyword},
+};
+use utils::{map::vec_map::VecMap, sanitize_email};
+
+pub trait MdnSend: Sync + Send {
+    fn mdn_send<'x>(
+        &self,
+        request: MdnSendRequest<'x>,
+        instance: &Arc<ServerInstance>,
+        next_call: &mut Option<Call<RequestMethod<'x>>>,
+    ) -> impl Future<Output = trc::Result<MdnSendResponse>> + Send;
+
+    fn send_mdn(
+        &self,
+        account_id: u32,
+        identity: &Identity,
+        instance: &Arc<ServerInstance>,
+        mdn: Mdn,
+    ) -> impl Future<Output = trc::Result<Result<Mdn, SetError<MdnProperty>>>> + Send;
+
+    fn unflag_mdn_sent(
+        &self,
+        account_id: u32,
+        document_id: u32,
+    ) -> impl Future<Output = trc::Result<()>> + Send;
+}
+
+impl MdnSend for Server {
+    async fn mdn_send<'x>(
+        &self,
+        request: MdnSendRequest<'x>,
+        instance: &Arc<ServerInstance>,
+        next_call: &mut Option<Call<RequestMethod<'x>>>,
+    ) -> trc::Result<MdnSendResponse> {
+        if request.send.len() > self.core.jmap.set_max_objects {
+            return Err(trc::JmapEvent::RequestTooLarge.into_err());
+        }
+
+        let account_id = request.account_id.document_id();
+        let mut response = MdnSendResponse {
+            account_id: request.account_id,
+            sent: VecMap::with_capacity(request.send.len()),
+            not_sent: VecMap::new(),
+        };
+
+        // Obtain identity
+        let identity = if let Some(identity) = self
+            .store()
+            .get_value::<Archive<AlignedBytes>>(ValueKey::archive(
+                account_id,
+                Collection::Identity,
+                request.identity_id.document_id(),
+            ))
+            .await?
+        {
+            identity
+                .deserialize::<Identity>()
+                .caused_by(trc::location!())?
+        } else {
+            return Err(trc::JmapEvent::InvalidArguments
+                .into_err()
+                .details("Identity not found."));
+        };
+
+        // Send MDNs
+        let mut success_email_ids = HashMap::new();
+        for (id, mdn) in request.send {
+            let email_id = mdn.for_email_id;
+            match self.send_mdn(account_id, &identity, instance, mdn).await {
+                Ok(Ok(mdn)) => {
+                    if let Some(email_id) = email_id {
+                        success_email_ids.insert(id.clone(), email_id);
+                    }
+                    response.sent.append(id, mdn);
+                }
+                Ok(Err(err)) => {
+                    response.not_sent.append(id, err);
+                }
+                Err(err) => {
+                    trc::error!(
+                        err.account_id(account_id)
+                            .details("Failed to send disposition notification.")
+                    );
+                    response.not_sent.append(
+                        id,
+                        SetError::forbidden()
+                            .with_description("Failed to send the disposition notification."),
+                    );
+                }
+            }
+        }
+
+        // On success
+        if request
+            .on_success_update_email
+            .as_ref()
+            .is_some_and(|p| !p.is_empty())
+            && !response.sent.is_empty()
+        {
+            *next_call = Call {
+                id: String::new(),
+                name: MethodName::new(MethodObject::Email, MethodFunction::Set),
+                method: RequestMethod::Set(SetRequestMethod::Email(SetRequest {
+                    account_id: request.account_id,
+                    if_in_state: None,
+                    create: None,
+                    update: request.on_success_update_email.map(|update| {
+                        update
+                            .into_iter()
+                            .filter_map(|(id, value)| {
+                                (
+                                    match id {
+                                        MaybeIdReference::Id(id) => MaybeInvalid::Value(id),
+                                        MaybeIdReference::Reference(id_ref) => {
+                                            MaybeInvalid::Value(*(success_email_ids.get(&id_ref)?))
+                                        }
+                                        MaybeIdReference::Invalid(id) => MaybeInvalid::Invalid(id),
+                                    },
+                                    value,
+                                )
+                                    .into()
+                            })
+                            .collect()
+                    }),
+                    destroy: None,
+                    arguments: Default::default(),
+                })),
+            }
+            .into();
+        }
+
+        Ok(response)
+    }
+
+    async fn send_mdn(
+        &self,
+        account_id: u32,
+        identity: &Identity,
+        instance: &Arc<ServerInstance>,
+        mdn: Mdn,
+    ) -> trc::Result<Result<Mdn, SetError<MdnProperty>>> {
+        // Validate properties
+        let (Some(email_id), Some(disposition)) = (mdn.for_email_id, &mdn.disposition) else {
+            return Ok(Err(SetError::invalid_properties()
+                .with_properties([MdnProperty::ForEmailId, MdnProperty::Disposition])
+                .with_description(
+                    "forEmailId and disposition properties are required.",
+                )));
+        };
+        if mdn.original_recipient.is_some() || mdn.original_message_id.is_some() {
+            return Ok(Err(SetError::invalid_properties()
+                .with_properties([
+                    MdnProperty::OriginalRecipient,
+                    MdnProperty::OriginalMessageId,
+                ])
+                .with_description(
+                    "originalRecipient and originalMessageId are set by the server.",
+                )));
+        }
+        for (property, value) in [
+            (MdnProperty::ReportingUA, &mdn.reporting_ua),
+            (MdnProperty::MdnGateway, &mdn.mdn_gateway),
+            (MdnProperty::FinalRecipient, &mdn.final_recipient),
+        ] {
+            if value
+                .as_deref()
+                .is_some_and(|value| !is_valid_field_value(value))
+            {
+                return Ok(Err(SetError::invalid_properties()
+                    .with_property(property)
+                    .with_description("Field contains invalid characters.")));
+            }
+        }
+        if mdn
+            .error
+            .iter()
+            .flatten()
+            .any(|value| !is_valid_field_value(value))
+        {
+            return Ok(Err(SetError::invalid_properties()
+                .with_property(MdnProperty::Error)
+                .with_description("Field contains invalid characters.")));
+        }
+        if mdn
+            .extension_fields
+            .iter()
+            .flat_map(|fields| fields.iter())
+            .any(|(name, value)| !is_valid_field_name(name) || !is_valid_field_value(value))
+        {
+            return Ok(Err(SetError::invalid_properties()
+                .with_property(MdnProperty::ExtensionFields)
+                .with_description("Invalid extension field name or value.")));
+        }
+
+        // Obtain message data
+        let document_id = email_id.document_id();
+        let data_ = if let Some(data) = self
+            .store()
+            .get_value::<Archive<AlignedBytes>>(ValueKey::archive(
+                account_id,
+                Collection::Email,
+                document_id,
+            ))
+            .await?
+        {
+            data
+        } else {
+            return Ok(Err(SetError::not_found()
+                .with_property(MdnProperty::ForEmailId)
+                .with_description("Email not found.")));
+        };
+        let data = data_
+            .to_unarchived::<MessageData>()
+            .caused_by(trc::location!())?;
+        if data
+            .inner
+            .keywords
+            .iter()
+            .any(|keyword| matches!(keyword, ArchivedKeyword::MdnSent))
+        {
+            return Ok(Err(SetError::new(SetErrorType::MdnAlreadySent)
+                .with_description("An MDN has already been sent for this email.")));
+        }
+
+        // Obtain raw message
+        let raw_message = if let Some(raw_message) = self
+            .store()
+            .get_value::<Archive<AlignedBytes>>(ValueKey::property(
+                account_id,
+                Collection::Email,
+                document_id,
+                EmailField::Metadata,
+            ))
+            .await?
+        {
+            let metadata = raw_message
+                .unarchive::<MessageMetadata>()
+                .caused_by(trc::location!())?;
+            self.blob_store()
+                .get_blob(metadata.blob_hash.0.as_slice(), 0..usize::MAX)
+                .await?
+        } else {
+            None
+        };
+        let Some(raw_message) = raw_message else {
+            return Ok(Err(SetError::not_found()
+                .with_property(MdnProperty::ForEmailId)
+                .with_description("Blob for email not found.")));
+        };
+        let Some(message) = MessageParser::new().parse_headers(&raw_message) else {
+            return Ok(Err(SetError::invalid_properties()
+                .with_property(MdnProperty::ForEmailId)
+                .with_description("Failed to parse email headers.")));
+        };
+
+        // Obtain the address requesting the notification
+        let Some(rcpt_to) = message
+            .header_as("Disposition-Notification-To", HeaderForm::Addresses)
+            .iter()
+            .filter_map(|value| value.as_address())
+            .flat_map(|address| address.iter())
+            .find_map(|address| address.address().and_then(sanitize_email))
+        else {
+            return Ok(Err(SetError::invalid_properties()
+                .with_property(MdnProperty::ForEmailId)
+                .with_description(
+                    "Email does not request a disposition notification.",
+                )));
+        };
+        let original_message_id = message.message_id().map(|id| id.to_string());
+        let original_recipient = message
+            .header_raw("Original-Recipient")
+            .map(|value| value.trim().to_string())
+            .filter(|value| is_valid_field_value(value));
+        let original_subject = message.subject().unwrap_or_default();
+
+        // Build disposition notification fields
+        let reporting_ua = mdn
+            .reporting_ua
+            .clone()
+            .unwrap_or_else(|| format!("{}; Stalwart", self.core.network.server_name));
+        let final_recipient = mdn
+            .final_recipient
+            .clone()
+            .unwrap_or_else(|| format!("rfc822; {}", identity.email));
+        let mut fields = String::with_capacity(256);
+        let _ = write!(fields, "Reporting-UA: {reporting_ua}\r\n");
+        if let Some(mdn_gateway) = &mdn.mdn_gateway {
+            let _ = write!(fields, "MDN-Gateway: {mdn_gateway}\r\n");
+        }
+        if let Some(original_recipient) = &original_recipient {
+            let _ = write!(fields, "Original-Recipient: {original_recipient}\r\n");
+        }
+        let _ = write!(fields, "Final-Recipient: {final_recipient}\r\n");
+        if let Some(original_message_id) = &original_message_id {
+            let _ = write!(fields, "Original-Message-ID: <{original_message_id}>\r\n");
+        }
+        let _ = write!(
+            fields,
+            "Disposition: {}/{}; {}\r\n",
+            disposition.action_mode.as_str(),
+            disposition.sending_mode.as_str(),
+            disposition.type_.as_str()
+        );
+        for error in mdn.error.iter().flatten() {
+            let _ = write!(fields, "Error: {error}\r\n");
+        }
+        for (name, value) in mdn.extension_fields.iter().flat_map(|fields| fields.iter()) {
+            let _ = write!(fields, "{name}: {value}\r\n");
+        }
+
+        // Build message
+        let subject = mdn.subject.clone().unwrap_or_else(|| {
+            format!(
+                "Return Receipt ({}) - {}",
+                disposition.type_.as_str(),
+                original_subject
+            )
+        });
+        let text_body = mdn.text_body.clone().unwrap_or_else(|| {
+            format!(
+                "The message sent to {} with subject \"{}\" has been {}.\r\n",
+                identity.email,
+                original_subject,
+                disposition.type_.as_str()
+            )
+        });
+        let mut parts = vec![
+            MimePart::new(
+                ContentType::new("text/plain").attribute("charset", "utf-8"),
+                BodyPart::Text(text_body.into()),
+            ),
+            MimePart::new(
+                ContentType::new("message/disposition-notification"),
+                BodyPart::Text(fields.into()),
+            ),
+        ];
+        if mdn.include_original_message.unwrap_or(false) {
+            parts.push(
+                MimePart::new(
+                    ContentType::new("message/rfc822"),
+                    BodyPart::Binary(raw_message.as_slice().into()),
+                )
+                .transfer_encoding("8bit"),
+            );
+        }
+        let mut builder = MessageBuilder::new()
+            .from((identity.name.as_str(), identity.email.as_str()))
+            .to(rcpt_to.as_str())
+            .message_id(format!(
+                "{}@{}",
+                make_boundary("."),
+                self.core.network.server_name
+            ))
+            .subject(subject)
+            .body(MimePart::new(
+                ContentType::new("multipart/report")
+                    .attribute("report-type", "disposition-notification"),
+                BodyPart::Multipart(parts),
+            ));
+        if let Some(original_message_id) = &original_message_id {
+            builder = builder.references(MessageId::new(original_message_id.as_str()));
+        }
+        let message = builder.write_to_vec().unwrap_or_default();
+
+        // Begin local SMTP session
+        let mut session = Session::<NullIo>::local(
+            self.clone(),
+            instance.clone(),
+            SessionData::local(
+                self.get_access_token(account_id)
+                    .await
+                    .caused_by(trc::location!())?,
+                None,
+                vec![],
+                vec![],
+                0,
+            ),
+        );
+        let mail_from = MailFrom {
+            address: Cow::Owned(identity.email.clone()),
+            ..Default::default()
+        };
+        let rcpt_to = RcptTo {
+            address: Cow::Owned(rcpt_to),
+            ..Default::default()
+        };
+
+        // Reserve the notification by flagging the original message with $MDNSent
+        // before queueing it, the write fails if the message was modified since it
+        // was read which prevents concurrent requests from sending it twice.
+        let mut new_data = data.inner.to_builder();
+        new_data.add_keyword(Keyword::MdnSent);
+        let mut batch = BatchBuilder::new();
+        batch
+            .with_account_id(account_id)
+            .with_collection(Collection::Email)
+            .with_document(document_id)
+            .custom(
+                ObjectIndexBuilder::new()
+                    .with_current(data)
+                    .with_changes(new_data.seal()),
+            )
+            .caused_by(trc::location!())?
+            .commit_point();
+        match self.commit_batch(batch).await {
+            Ok(_) => {}
+            Err(err) if err.is_assertion_failure() => {
+                return Ok(Err(SetError::forbidden().with_description(
+                    "Another process modified this message, please try again.",
+                )));
+            }
+            Err(err) => {
+                return Err(err.caused_by(trc::location!()));
+            }
+        }
+
+        // Spawn SMTP session to avoid overflowing the stack
+        let handle = tokio::spawn(async move {
+            // MAIL FROM
+            let _ = session.handle_mail_from(mail_from).await;
+            if let Some(error) = session.has_failed() {
+                return Err(SetError::new(SetErrorType::ForbiddenMailFrom)
+                    .with_description(format!("Server rejected MAIL-FROM: {}", error.trim())));
+            }
+
+            // RCPT TO
+            let _ = session.handle_rcpt_to(rcpt_to).await;
+            if let Some(error) = session.has_failed() {
+                return Err(SetError::new(SetErrorType::ForbiddenToSend)
+                    .with_description(format!("Server rejected RCPT-TO: {}", error.trim())));
+            }
+
+            // DATA
+            session.data.message = message;
+            let response = session.queue_message().await;
+            if let smtp::core::State::Accepted(_) = session.state {
+                Ok(())
+            } else {
+                Err(
+                    SetError::new(SetErrorType::ForbiddenToSend).with_description(format!(
+                        "Server rejected DATA: {}",
+                        std::str::from_utf8(&response).unwrap().trim()
+                    )),
+                )
+            }
+        });
+
+        let failure = match handle.await {
+            Ok(Ok(())) => None,
+            Ok(Err(err)) => Some(Ok(Err(err))),
+            Err(err) => Some(Err(trc::EventType::Server(trc::ServerEvent::ThreadError)
+                .reason(err)
+                .caused_by(trc::location!())
+                .details("Join Error"))),
+        };
+        if let Some(failure) = failure {
+            // Release the reservation so the notification can be sent again
+            if let Err(err) = self.unflag_mdn_sent(account_id, document_id).await {
+                trc::error!(
+                    err.account_id(account_id)
+                        .document_id(document_id)
+                        .details("Failed to remove $MDNSent keyword.")
+                );
+            }
+            return failure;
+        }
+
+        // Return server-set properties
+        Ok(Ok(Mdn {
+            reporting_ua: mdn.reporting_ua.is_none().then_some(reporting_ua),
+            final_recipient: mdn.final_recipient.is_none().then_some(final_recipient),
+            original_recipient,
+            original_message_id,
+            ..Default::default()
+        }))
+    }
+
+    async fn unflag_mdn_sent(&self, account_id: u32, document_id: u32) -> trc::Result<()> {
+        // Retry if the message is modified while the keyword is being removed
+        let mut attempts = 0;
+        loop {
+            let Some(data_) = self
+                .store()
+                .get_value::<Archive<AlignedBytes>>(ValueKey::archive(
+                    account_id,
+                    Collection::Email,
+                    document_id,
+                ))
+                .await?
+            else {
+                return Ok(());
+            };
+            let data = data_
+                .to_unarchived::<MessageData>()
+                .caused_by(trc::location!())?;
+            let mut new_data = data.inner.to_builder();
+            if !new_data.remove_keyword(&Keyword::MdnSent) {
+                return Ok(());
+            }
+
+            let mut batch = BatchBuilder::new();
+            batch
+                .with_account_id(account_id)
+                .with_collection(Collection::Email)
+                .with_document(document_id)
+                .custom(
+                    ObjectIndexBuilder::new()
+                        .with_current(data)
+                        .with_changes(new_data.seal()),
+                )
+                .caused_by(trc::location!())?
+                .commit_point();
+            match self.commit_batch(batch).await {
+                Ok(_) => return Ok(()),
+                Err(err) if err.is_assertion_failure() && attempts < 3 => {
+                    attempts += 1;
+                }
+                Err(err) => return Err(err.caused_by(trc::location!())),
+            }
+        }
+    }
+}
+
+fn is_valid_field_name(name: &str) -> bool {
+    !name.is_empty() && name.bytes().all(|ch| ch.is_ascii_graphic() && ch != b':')
+}
+
+fn is_valid_field_value(value: &str) -> bool {
+    !value.contains(['\r', '\n'])
+}
@@ -0,0 +1,142 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use crate::calendar_event::get::CalendarEventGet;
+use calcard::jscalendar::{JSCalendarProperty, JSCalendarValue};
+use common::{Server, auth::AccessToken};
+use groupware::cache::GroupwareCache;
+use jmap_proto::{
+    method::get::{GetRequest, GetResponse},
+    object::{
+        calendar_event::{CalendarEvent, CalendarEventGetArguments},
+        task::{TASK_LIST_ID, Task},
+    },
+    request::{
+        MaybeInvalid,
+        reference::{MaybeIdReference, MaybeResultReference},
+    },
+};
+use jmap_tools::{Key, Value};
+use store::roaring::RoaringBitmap;
+use types::{acl::Acl, collection::SyncCollection, id::Id};
+
+pub trait TaskGet: Sync + Send {
+    fn task_get(
+        &self,
+        request: GetRequest<Task>,
+        access_token: &AccessToken,
+    ) -> impl Future<Output = trc::Result<GetResponse<CalendarEvent>>> + Send;
+}
+
+impl TaskGet for Server {
+    async fn task_get(
+        &self,
+        mut request: GetRequest<Task>,
+        access_token: &AccessToken,
+    ) -> trc::Result<GetResponse<CalendarEvent>> {
+        let account_id = request.account_id.document_id();
+        let cache = self
+            .fetch_dav_resources(access_token, account_id, SyncCollection::Calendar)
+            .await?;
+
+        // Only VTODO components are returned
+        let mut not_found = vec![];
+        let ids = if let Some(ids) = request.unwrap_ids(self.core.jmap.get_max_objects)? {
+            ids.into_iter()
+                .filter(|id| {
+                    if cache.has_task_id(&id.document_id()) {
+                        true
+                    } else {
+                        not_found.push(*id);
+                        false
+                    }
+                })
+                .collect::<Vec<_>>()
+        } else {
+            let mut task_ids = cache.task_ids().collect::<RoaringBitmap>();
+            if !access_token.is_member(account_id) {
+                task_ids &= cache.shared_items(access_token, [Acl::ReadItems], true);
+            }
+            task_ids
+                .iter()
+                .take(self.core.jmap.get_max_objects)
+                .map(Id::from)
+                .collect::<Vec<_>>()
+        };
+
+        // Map taskListId to calendarIds
+        let mut return_task_list_id = true;
+        let properties = match request.properties.take().map(|p| p.unwrap()) {
+            Some(properties) if !properties.is_empty() => {
+                let mut task_properties = Vec::with_capacity(properties.len() + 1);
+                return_task_list_id = false;
+
+                for property in properties {
+                    match property {
+                        MaybeInvalid::Invalid(property) if property == TASK_LIST_ID => {
+                            return_task_list_id = true;
+                        }
+                        MaybeInvalid::Value(
+                            JSCalendarProperty::CalendarIds | JSCalendarProperty::IsOrigin,
+                        ) => {}
+                        property => {
+                            task_properties.push(property);
+                        }
+                    }
+                }
+
+                if return_task_list_id {
+                    task_properties.push(MaybeInvalid::Value(JSCalendarProperty::CalendarIds));
+                }
+
+                Some(MaybeResultReference::Value(task_properties))
+            }
+            properties => properties.map(MaybeResultReference::Value),
+        };
+
+        let mut response = self
+            .calendar_event_get(
+                GetRequest {
+                    account_id: request.account_id,
+                    ids: Some(MaybeResultReference::Value(
+                        ids.into_iter().map(MaybeIdReference::Id).collect(),
+                    )),
+                    properties,
+                    arguments: CalendarEventGetArguments::default(),
+                },
+                access_token,
+            )
+            .await?;
+
+        for task in &mut response.list {
+            let Value::Object(task) = task else {
+                continue;
+            };
+
+            task.remove(&Key::Property(JSCalendarProperty::IsOrigin));
+            if let Some(calendar_ids) = task.remove(&Key::Property(JSCalendarProperty::CalendarIds))
+                && return_task_list_id
+            {
+                let task_list_id = calendar_ids
+                    .into_object()
+                    .into_iter()
+                    .flat_map(|ids| ids.into_expanded_boolean_set())
+                    .find_map(|key| match key {
+                        Key::Property(JSCalendarProperty::IdValue(id)) => Some(id),
+                        _ => None,
+                    });
+
+                task.insert_unchecked(
+                    Key::Borrowed(TASK_LIST_ID),
+                    task_list_id.map_or(Value::Null, |id| Value::Element(JSCalendarValue::Id(id))),
+                );
+            }
+        }
+        response.not_found.extend(not_found);
+
+        Ok(response)
+    }
+}
@@ -0,0 +1,174 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use crate::calendar_event::set::CalendarEventSet;
+use calcard::jscalendar::{JSCalendarProperty, JSCalendarType, JSCalendarValue};
+use common::{DavResources, Server, auth::AccessToken};
+use groupware::cache::GroupwareCache;
+use http_proto::HttpSessionData;
+use jmap_proto::{
+    error::set::SetError,
+    method::set::{SetRequest, SetResponse},
+    object::{
+        calendar_event::CalendarEvent,
+        task::{TASK_LIST_ID, Task},
+    },
+    request::{MaybeInvalid, reference::MaybeResultReference},
+};
+use jmap_tools::{Key, Value};
+use types::{blob::BlobId, collection::SyncCollection, id::Id};
+use utils::map::vec_map::VecMap;
+
+pub trait TaskSet: Sync + Send {
+    fn task_set(
+        &self,
+        request: SetRequest<'_, Task>,
+        access_token: &AccessToken,
+        session: &HttpSessionData,
+    ) -> impl Future<Output = trc::Result<SetResponse<CalendarEvent>>> + Send;
+}
+
+impl TaskSet for Server {
+    async fn task_set(
+        &self,
+        mut request: SetRequest<'_, Task>,
+        access_token: &AccessToken,
+        session: &HttpSessionData,
+    ) -> trc::Result<SetResponse<CalendarEvent>> {
+        let cache = self
+            .fetch_dav_resources(
+                access_token,
+                request.account_id.document_id(),
+                SyncCollection::Calendar,
+            )
+            .await?;
+        let mut not_created = VecMap::new();
+        let mut not_updated = VecMap::new();
+        let mut not_destroyed = VecMap::new();
+
+        let create = request.create.take().map(|create| {
+            create
+                .into_iter()
+                .filter_map(
+                    |(id, mut object)| match validate_task(&cache, &mut object, true) {
+                        Ok(_) => Some((id, object)),
+                        Err(err) => {
+                            not_created.append(id, err);
+                            None
+                        }
+                    },
+                )
+                .collect()
+        });
+        let update = request.update.take().map(|update| {
+            update
+                .into_iter()
+                .filter_map(|(id, mut object)| {
+                    if let MaybeInvalid::Value(id) = &id {
+                        if !cache.has_task_id(&id.document_id()) {
+                            not_updated.append(*id, SetError::not_found());
+                            return None;
+                        } else if let Err(err) = validate_task(&cache, &mut object, false) {
+                            not_updated.append(*id, err);
+                            return None;
+                        }
+                    }
+                    Some((id, object))
+                })
+                .collect()
+        });
+        let destroy = request.destroy.take().map(|destroy| {
+            MaybeResultReference::Value(
+                destroy
+                    .unwrap()
+                    .into_iter()
+                    .filter(|id| match id {
+                        MaybeInvalid::Value(id) if !cache.has_task_id(&id.document_id()) => {
+                            not_destroyed.append(*id, SetError::not_found());
+                            false
+                        }
+                        _ => true,
+                    })
+                    .collect(),
+            )
+        });
+
+        let mut response = self
+            .calendar_event_set(
+                SetRequest {
+                    account_id: request.account_id,
+                    if_in_state: request.if_in_state,
+                    create,
+                    update,
+                    destroy,
+                    arguments: request.arguments,
+                },
+                access_token,
+                session,
+            )
+            .await?;
+
+        for (id, err) in not_created {
+            response.not_created.append(id, err);
+        }
+        for (id, err) in not_updated {
+            response.not_updated.append(id, err);
+        }
+        for (id, err) in not_destroyed {
+            response.not_destroyed.append(id, err);
+        }
+
+        Ok(response)
+    }
+}
+
+fn validate_task(
+    cache: &DavResources,
+    object: &mut Value<'_, JSCalendarProperty<Id>, JSCalendarValue<Id, BlobId>>,
+    is_create: bool,
+) -> Result<(), SetError<JSCalendarProperty<Id>>> {
+    let Value::Object(object) = object else {
+        return Ok(());
+    };
+
+    // Tasks are always stored as VTODO components
+    match object.get(&Key::Property(JSCalendarProperty::Type)) {
+        Some(Value::Element(JSCalendarValue::Type(JSCalendarType::Task))) => {}
+        None if is_create => {
+            object.insert_unchecked(
+                JSCalendarProperty::Type,
+                Value::Element(JSCalendarValue::Type(JSCalendarType::Task)),
+            );
+        }
+        None => {}
+        Some(_) => {
+            return Err(SetError::invalid_properties()
+                .with_property(JSCalendarProperty::Type)
+                .with_description("Only objects of type Task are supported."));
+        }
+    }
+
+    // Validate task list
+    let is_valid = match object.get(&Key::Property(JSCalendarProperty::CalendarIds)) {
+        Some(Value::Object(ids)) => {
+            let mut ids = ids.keys();
+            matches!((ids.next(), ids.next()), (
+                    Some(Key::Property(JSCalendarProperty::IdValue(id))),
+                    None,
+                ) if cache.has_task_list_id(&id.document_id()))
+        }
+        Some(_) => false,
+        None => !is_create && !object.contains_key(&Key::Borrowed(TASK_LIST_ID)),
+    };
+
+    if is_valid {
+        Ok(())
+    } else {
+        Err(SetError::invalid_properties()
+            .with_property(Key::Borrowed(TASK_LIST_ID))
+            .with_description("Task has to belong to an existing task list."))
+    }
+}
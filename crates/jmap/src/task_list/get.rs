@@ -0,0 +1,110 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use crate::calendar::get::CalendarGet;
+use common::{Server, auth::AccessToken};
+use groupware::cache::GroupwareCache;
+use jmap_proto::{
+    method::get::{GetRequest, GetResponse},
+    object::{
+        JmapObjectId,
+        calendar::{Calendar, CalendarProperty},
+        task::TaskList,
+    },
+    request::{MaybeInvalid, reference::MaybeResultReference},
+};
+use jmap_tools::Key;
+use types::collection::SyncCollection;
+
+pub trait TaskListGet: Sync + Send {
+    fn task_list_get(
+        &self,
+        request: GetRequest<TaskList>,
+        access_token: &AccessToken,
+    ) -> impl Future<Output = trc::Result<GetResponse<Calendar>>> + Send;
+}
+
+impl TaskListGet for Server {
+    async fn task_list_get(
+        &self,
+        request: GetRequest<TaskList>,
+        access_token: &AccessToken,
+    ) -> trc::Result<GetResponse<Calendar>> {
+        let has_ids = request.ids.is_some();
+
+        // Remove calendar-only properties
+        let properties = if let Some(properties) = request.properties {
+            properties
+                .unwrap()
+                .into_iter()
+                .filter(|property| {
+                    !matches!(
+                        property,
+                        MaybeInvalid::Value(
+                            CalendarProperty::IsVisible
+                                | CalendarProperty::IsDefault
+                                | CalendarProperty::IncludeInAvailability
+                        )
+                    )
+                })
+                .collect()
+        } else {
+            [
+                CalendarProperty::Id,
+                CalendarProperty::Name,
+                CalendarProperty::Description,
+                CalendarProperty::Color,
+                CalendarProperty::TimeZone,
+                CalendarProperty::SortOrder,
+                CalendarProperty::IsSubscribed,
+                CalendarProperty::MyRights,
+            ]
+            .into_iter()
+            .map(MaybeInvalid::Value)
+            .collect()
+        };
+
+        let mut response = self
+            .calendar_get(
+                GetRequest {
+                    account_id: request.account_id,
+                    ids: request.ids,
+                    properties: Some(MaybeResultReference::Value(properties)),
+                    arguments: (),
+                },
+                access_token,
+            )
+            .await?;
+
+        // Only return calendars that can hold VTODO components
+        let cache = self
+            .fetch_dav_resources(
+                access_token,
+                request.account_id.document_id(),
+                SyncCollection::Calendar,
+            )
+            .await?;
+        let not_found = &mut response.not_found;
+        response.list.retain(|calendar| {
+            match calendar
+                .as_object_and_get(&Key::Property(CalendarProperty::Id))
+                .and_then(|id| id.as_element())
+                .and_then(|id| id.as_id())
+            {
+                Some(id) if cache.has_task_list_id(&id.document_id()) => true,
+                Some(id) => {
+                    if has_ids {
+                        not_found.push(id);
+                    }
+                    false
+                }
+                None => false,
+            }
+        });
+
+        Ok(response)
+    }
+}
@@ -0,0 +1,110 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use crate::calendar::set::CalendarSet;
+use common::{Server, auth::AccessToken};
+use groupware::cache::GroupwareCache;
+use http_proto::HttpSessionData;
+use jmap_proto::{
+    error::set::{SetError, SetErrorType},
+    method::set::{SetRequest, SetResponse},
+    object::{
+        calendar::{Calendar, CalendarSetArguments},
+        task::TaskList,
+    },
+    request::{MaybeInvalid, reference::MaybeResultReference},
+};
+use types::collection::SyncCollection;
+use utils::map::vec_map::VecMap;
+
+pub trait TaskListSet: Sync + Send {
+    fn task_list_set(
+        &self,
+        request: SetRequest<'_, TaskList>,
+        access_token: &AccessToken,
+        session: &HttpSessionData,
+    ) -> impl Future<Output = trc::Result<SetResponse<Calendar>>> + Send;
+}
+
+impl TaskListSet for Server {
+    async fn task_list_set(
+        &self,
+        mut request: SetRequest<'_, TaskList>,
+        access_token: &AccessToken,
+        session: &HttpSessionData,
+    ) -> trc::Result<SetResponse<Calendar>> {
+        let cache = self
+            .fetch_dav_resources(
+                access_token,
+                request.account_id.document_id(),
+                SyncCollection::Calendar,
+            )
+            .await?;
+        let mut not_updated = VecMap::new();
+        let mut not_destroyed = VecMap::new();
+
+        // Calendars that do not support VTODO components are not task lists
+        let update = request.update.take().map(|update| {
+            update
+                .into_iter()
+                .filter(|(id, _)| match id {
+                    MaybeInvalid::Value(id) if !cache.has_task_list_id(&id.document_id()) => {
+                        not_updated.append(*id, SetError::not_found());
+                        false
+                    }
+                    _ => true,
+                })
+                .collect()
+        });
+        let destroy = request.destroy.take().map(|destroy| {
+            MaybeResultReference::Value(
+                destroy
+                    .unwrap()
+                    .into_iter()
+                    .filter(|id| match id {
+                        MaybeInvalid::Value(id) if !cache.has_task_list_id(&id.document_id()) => {
+                            not_destroyed.append(*id, SetError::not_found());
+                            false
+                        }
+                        _ => true,
+                    })
+                    .collect(),
+            )
+        });
+
+        let mut response = self
+            .calendar_set(
+                SetRequest {
+                    account_id: request.account_id,
+                    if_in_state: request.if_in_state,
+                    create: request.create,
+                    update,
+                    destroy,
+                    arguments: CalendarSetArguments {
+                        on_destroy_remove_events: request.arguments.on_destroy_remove_tasks,
+                        on_success_set_is_default: None,
+                    },
+                },
+                access_token,
+                session,
+            )
+            .await?;
+
+        for (id, err) in not_updated {
+            response.not_updated.append(id, err);
+        }
+        for (id, err) in not_destroyed {
+            response.not_destroyed.append(id, err);
+        }
+        for (_, err) in response.not_destroyed.iter_mut() {
+            if err.type_ == SetErrorType::CalendarHasEvent {
+                *err = SetError::task_list_has_task();
+            }
+        }
+
+        Ok(response)
+    }
+}
@@ -0,0 +1,70 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use common::Server;
+use email::mailbox::virtual_mailbox::VirtualMailboxStore;
+use jmap_proto::{
+    method::changes::{ChangesRequest, ChangesResponse},
+    object::virtual_mailbox::VirtualMailbox,
+    types::state::State,
+};
+use std::future::Future;
+use types::id::Id;
+
+pub trait VirtualMailboxChanges: Sync + Send {
+    fn virtual_mailbox_changes(
+        &self,
+        request: ChangesRequest,
+    ) -> impl Future<Output = trc::Result<ChangesResponse<VirtualMailbox>>> + Send;
+}
+
+impl VirtualMailboxChanges for Server {
+    async fn virtual_mailbox_changes(
+        &self,
+        request: ChangesRequest,
+    ) -> trc::Result<ChangesResponse<VirtualMailbox>> {
+        let mailboxes = self
+            .get_virtual_mailboxes(request.account_id.document_id())
+            .await?;
+
+        // States are the revision of the virtual mailbox definitions, which are
+        // always returned in full so intermediate states are never issued.
+        let since = match &request.since_state {
+            State::Initial => 0,
+            State::Exact(revision) => *revision,
+            State::Intermediate(_) => {
+                return Err(trc::JmapEvent::CannotCalculateChanges.into_err());
+            }
+        };
+        let Some(changes) = mailboxes.changes_since(since) else {
+            return Err(trc::JmapEvent::CannotCalculateChanges.into_err());
+        };
+
+        let max_changes = std::cmp::min(
+            request
+                .max_changes
+                .filter(|n| *n != 0)
+                .unwrap_or(usize::MAX),
+            self.core.jmap.changes_max_results.unwrap_or(usize::MAX),
+        );
+        if changes.created.len() + changes.updated.len() + changes.destroyed.len() > max_changes {
+            return Err(trc::JmapEvent::CannotCalculateChanges
+                .into_err()
+                .details("Too many changes"));
+        }
+
+        Ok(ChangesResponse {
+            account_id: request.account_id,
+            old_state: request.since_state,
+            new_state: State::new_exact(mailboxes.revision),
+            has_more_changes: false,
+            created: changes.created.into_iter().map(Id::from).collect(),
+            updated: changes.updated.into_iter().map(Id::from).collect(),
+            destroyed: changes.destroyed.into_iter().map(Id::from).collect(),
+            updated_properties: None,
+        })
+    }
+}
@@ -0,0 +1,86 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use common::Server;
+use email::mailbox::virtual_mailbox::VirtualMailboxStore;
+use jmap_proto::{
+    method::get::{GetRequest, GetResponse},
+    object::virtual_mailbox::{self, VirtualMailboxProperty, VirtualMailboxValue},
+    types::state::State,
+};
+use jmap_tools::Map;
+use std::future::Future;
+use types::id::Id;
+
+pub trait VirtualMailboxGet: Sync + Send {
+    fn virtual_mailbox_get(
+        &self,
+        request: GetRequest<virtual_mailbox::VirtualMailbox>,
+    ) -> impl Future<Output = trc::Result<GetResponse<virtual_mailbox::VirtualMailbox>>> + Send;
+}
+
+impl VirtualMailboxGet for Server {
+    async fn virtual_mailbox_get(
+        &self,
+        mut request: GetRequest<virtual_mailbox::VirtualMailbox>,
+    ) -> trc::Result<GetResponse<virtual_mailbox::VirtualMailbox>> {
+        let ids = request.unwrap_ids(self.core.jmap.get_max_objects)?;
+        let properties = request.unwrap_properties(&[
+            VirtualMailboxProperty::Id,
+            VirtualMailboxProperty::Name,
+            VirtualMailboxProperty::Query,
+        ]);
+        let account_id = request.account_id.document_id();
+        let mailboxes = self.get_virtual_mailboxes(account_id).await?;
+
+        let mut response = GetResponse {
+            account_id: request.account_id.into(),
+            state: State::new_exact(mailboxes.revision).into(),
+            list: Vec::with_capacity(mailboxes.items.len()),
+            not_found: vec![],
+        };
+
+        let ids = if let Some(ids) = ids {
+            ids
+        } else {
+            mailboxes
+                .items
+                .iter()
+                .take(self.core.jmap.get_max_objects)
+                .map(|m| Id::from(m.id))
+                .collect::<Vec<_>>()
+        };
+
+        for id in ids {
+            let Some(mailbox) = mailboxes.get(id.document_id()) else {
+                response.not_found.push(id);
+                continue;
+            };
+
+            let mut result = Map::with_capacity(properties.len());
+            for property in &properties {
+                match property {
+                    VirtualMailboxProperty::Id => {
+                        result.insert_unchecked(
+                            VirtualMailboxProperty::Id,
+                            VirtualMailboxValue::Id(id),
+                        );
+                    }
+                    VirtualMailboxProperty::Name => {
+                        result.insert_unchecked(VirtualMailboxProperty::Name, mailbox.name.clone());
+                    }
+                    VirtualMailboxProperty::Query => {
+                        result
+                            .insert_unchecked(VirtualMailboxProperty::Query, mailbox.query.clone());
+                    }
+                }
+            }
+            response.list.push(result.into());
+        }
+
+        Ok(response)
+    }
+}
@@ -0,0 +1,222 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use common::Server;
+use email::{
+    cache::MessageCacheFetch,
+    mailbox::virtual_mailbox::{VirtualMailboxStore, validate_virtual_query},
+};
+use jmap_proto::{
+    error::set::{SetError, SetErrorType},
+    method::set::{SetRequest, SetResponse},
+    object::virtual_mailbox::{self, VirtualMailboxProperty, VirtualMailboxValue},
+    references::resolve::ResolveCreatedReference,
+    request::IntoValid,
+    types::state::State,
+};
+use jmap_tools::{Key, Map, Value};
+use std::future::Future;
+use trc::AddContext;
+
+pub trait VirtualMailboxSet: Sync + Send {
+    fn virtual_mailbox_set(
+        &self,
+        request: SetRequest<'_, virtual_mailbox::VirtualMailbox>,
+    ) -> impl Future<Output = trc::Result<SetResponse<virtual_mailbox::VirtualMailbox>>> + Send;
+}
+
+impl VirtualMailboxSet for Server {
+    async fn virtual_mailbox_set(
+        &self,
+        mut request: SetRequest<'_, virtual_mailbox::VirtualMailbox>,
+    ) -> trc::Result<SetResponse<virtual_mailbox::VirtualMailbox>> {
+        let account_id = request.account_id.document_id();
+        let (mut mailboxes, current) = self.get_virtual_mailboxes_for_update(account_id).await?;
+        let old_state = State::new_exact(mailboxes.revision);
+        if let Some(if_in_state) = &request.if_in_state
+            && &old_state != if_in_state
+        {
+            return Err(trc::JmapEvent::StateMismatch.into_err());
+        }
+        let cache = self
+            .get_cached_messages(account_id)
+            .await
+            .caused_by(trc::location!())?;
+        let max_len = self.core.jmap.mailbox_name_max_len;
+        let mut has_changes = false;
+
+        // Prepare response
+        let mut response = SetResponse::from_request(&request, self.core.jmap.set_max_objects)?
+            .with_state(old_state);
+        let will_destroy = request.unwrap_destroy().into_valid().collect::<Vec<_>>();
+
+        // Process creates
+        'create: for (id, object) in request.unwrap_create() {
+            if mailboxes.items.len() >= self.core.jmap.mailbox_max_virtual {
+                response.not_created.append(
+                    id,
+                    SetError::new(SetErrorType::OverQuota).with_description(concat!(
+                        "There are too many virtual mailboxes, ",
+                        "please delete some before adding a new one."
+                    )),
+                );
+                continue 'create;
+            }
+
+            let mut name = None;
+            let mut query = None;
+
+            for (property, mut value) in object.into_expanded_object() {
+                if let Err(err) = response
+                    .resolve_self_references(&mut value)
+                    .and_then(|_| validate_virtual_value(&property, value, &mut name, &mut query))
+                {
+                    response.not_created.append(id, err);
+                    continue 'create;
+                }
+            }
+
+            let (Some(name), Some(query)) = (name, query) else {
+                response.not_created.append(
+                    id,
+                    SetError::invalid_properties()
+                        .with_properties([
+                            VirtualMailboxProperty::Name,
+                            VirtualMailboxProperty::Query,
+                        ])
+                        .with_description("Missing required properties"),
+                );
+                continue 'create;
+            };
+
+            if let Err(err) = mailboxes.validate_name(&name, None, &cache, max_len) {
+                response.not_created.append(
+                    id,
+                    SetError::invalid_properties()
+                        .with_property(VirtualMailboxProperty::Name)
+                        .with_description(err),
+                );
+                continue 'create;
+            }
+
+            let document_id = mailboxes.insert(name, query);
+            response.created.insert(
+                id,
+                Map::with_capacity(1)
+                    .with_key_value(
+                        VirtualMailboxProperty::Id,
+                        VirtualMailboxValue::Id(document_id.into()),
+                    )
+                    .into(),
+            );
+            has_changes = true;
+        }
+
+        // Process updates
+        'update: for (id, object) in request.unwrap_update().into_valid() {
+            // Make sure id won't be destroyed
+            if will_destroy.contains(&id) {
+                response.not_updated.append(id, SetError::will_destroy());
+                continue 'update;
+            }
+
+            let document_id = id.document_id();
+            if mailboxes.get(document_id).is_none() {
+                response.not_updated.append(id, SetError::not_found());
+                continue 'update;
+            }
+
+            let mut name = None;
+            let mut query = None;
+            for (property, mut value) in object.into_expanded_object() {
+                if let Err(err) = response
+                    .resolve_self_references(&mut value)
+                    .and_then(|_| validate_virtual_value(&property, value, &mut name, &mut query))
+                {
+                    response.not_updated.append(id, err);
+                    continue 'update;
+                }
+            }
+
+            if let Some(name) = &name
+                && let Err(err) = mailboxes.validate_name(name, Some(document_id), &cache, max_len)
+            {
+                response.not_updated.append(
+                    id,
+                    SetError::invalid_properties()
+                        .with_property(VirtualMailboxProperty::Name)
+                        .with_description(err),
+                );
+                continue 'update;
+            }
+
+            let mailbox = mailboxes.get_mut(document_id).unwrap();
+            if let Some(name) = name {
+                mailbox.name = name;
+            }
+            if let Some(query) = query {
+                mailbox.set_query(query);
+            }
+
+            has_changes = true;
+            response.updated.append(id, None);
+        }
+
+        // Process deletions
+        for id in will_destroy {
+            if mailboxes.remove(id.document_id()) {
+                has_changes = true;
+                response.destroyed.push(id);
+            } else {
+                response.not_destroyed.append(id, SetError::not_found());
+            }
+        }
+
+        if has_changes {
+            let new_state = State::new_exact(mailboxes.revision + 1);
+            self.set_virtual_mailboxes(account_id, current, mailboxes)
+                .await
+                .caused_by(trc::location!())?;
+            response.new_state = new_state.into();
+        }
+
+        Ok(response)
+    }
+}
+
+fn validate_virtual_value(
+    property: &Key<VirtualMailboxProperty>,
+    value: Value<'_, VirtualMailboxProperty, VirtualMailboxValue>,
+    name: &mut Option<String>,
+    query: &mut Option<String>,
+) -> Result<(), SetError<VirtualMailboxProperty>> {
+    let Key::Property(property) = property else {
+        return Err(SetError::invalid_properties()
+            .with_property(property.to_owned())
+            .with_description("Invalid property."));
+    };
+
+    match (property, value) {
+        (VirtualMailboxProperty::Name, Value::Str(value)) => {
+            *name = Some(value.into_owned());
+        }
+        (VirtualMailboxProperty::Query, Value::Str(value)) => {
+            if let Err(err) = validate_virtual_query(&value) {
+                return Err(SetError::invalid_properties()
+                    .with_property(property.clone())
+                    .with_description(err));
+            }
+            *query = Some(value.into_owned());
+        }
+        (property, _) => {
+            return Err(SetError::invalid_properties()
+                .with_property(property.clone())
+                .with_description("Field could not be set."));
+        }
+    }
+
+    Ok(())
+}
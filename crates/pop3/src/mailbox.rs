@@ -9,10 +9,14 @@ use common::listener::SessionStream;
 use email::{
     cache::{MessageCacheFetch, mailbox::MailboxCacheAccess},
     mailbox::INBOX_ID,
+    pop3::{Pop3State, Pop3Store},
 };
 use std::collections::BTreeMap;
+use store::{
+    roaring::RoaringBitmap,
+    write::{assert::AssertValue, now},
+};
 use trc::AddContext;
-use types::special_use::SpecialUse;
 
 #[derive(Default)]
 pub struct Mailbox {
@@ -21,6 +25,9 @@ pub struct Mailbox {
     pub uid_validity: u32,
     pub total: u32,
     pub size: u32,
+    pub expired: RoaringBitmap,
+    pub state: Option<(Pop3State, AssertValue)>,
+    pub state_changed: bool,
 }
 
 pub struct Message {
@@ -28,6 +35,7 @@ pub struct Message {
     pub uid: u32,
     pub size: u32,
     pub deleted: bool,
+    pub downloaded: bool,
 }
 
 impl<T: SessionStream> Session<T> {
@@ -43,10 +51,43 @@ impl<T: SessionStream> Session<T> {
             return Ok(Mailbox::default());
         }
 
-        let uid_validity = cache
-            .mailbox_by_role(&SpecialUse::Inbox)
-            .map(|x| x.uid_validity)
-            .unwrap_or_default();
+        // Obtain the mailbox exposed over POP3
+        let policy = self
+            .server
+            .get_pop3_policy(account_id)
+            .await
+            .caused_by(trc::location!())?;
+        let (mailbox_id, uid_validity) = policy
+            .mailbox
+            .as_deref()
+            .and_then(|path| cache.mailbox_by_path(path))
+            .or_else(|| cache.mailbox_by_id(&INBOX_ID))
+            .map(|m| (m.document_id, m.uid_validity))
+            .unwrap_or((INBOX_ID, 0));
+
+        // Obtain the UIDs downloaded in previous sessions
+        let state = if policy.is_tracking() {
+            let (state, current) = self
+                .server
+                .get_pop3_state(account_id)
+                .await
+                .caused_by(trc::location!())?;
+            if state.mailbox_id == mailbox_id && state.uid_validity == uid_validity {
+                (state, current)
+            } else {
+                (
+                    Pop3State {
+                        mailbox_id,
+                        uid_validity,
+                        downloaded: vec![],
+                    },
+                    current,
+                )
+            }
+            .into()
+        } else {
+            None
+        };
 
         // Sort by UID
         let message_map = cache
@@ -57,7 +98,7 @@ impl<T: SessionStream> Session<T> {
                 message
                     .mailboxes
                     .iter()
-                    .find(|m| m.mailbox_id == INBOX_ID)
+                    .find(|m| m.mailbox_id == mailbox_id)
                     .map(|m| (m.uid, (message.document_id, message.size)))
             })
             .collect::<BTreeMap<u32, (u32, u32)>>();
@@ -69,16 +110,40 @@ impl<T: SessionStream> Session<T> {
             account_id,
             ..Default::default()
         };
+        let now = now();
+        let message_uids = message_map.keys().copied().collect::<Vec<_>>();
         for (uid, (id, size)) in message_map {
+            if let Some(downloaded_at) = state.as_ref().and_then(|(s, _)| s.downloaded_at(uid)) {
+                if policy
+                    .expire_after
+                    .is_some_and(|expire_after| downloaded_at + expire_after <= now)
+                {
+                    mailbox.expired.insert(id);
+                    continue;
+                } else if policy.hide_downloaded {
+                    continue;
+                }
+            }
+
             mailbox.messages.push(Message {
                 id,
                 uid,
                 size,
                 deleted: false,
+                downloaded: false,
             });
             mailbox.total += 1;
             mailbox.size += size;
         }
+        // Discard the state of messages that no longer exist
+        if let Some((mut state, current)) = state {
+            let num_downloaded = state.downloaded.len();
+            state
+                .downloaded
+                .retain(|d| message_uids.binary_search(&d.uid).is_ok());
+            mailbox.state_changed = num_downloaded != state.downloaded.len();
+            mailbox.state = Some((state, current));
+        }
 
         Ok(mailbox)
     }
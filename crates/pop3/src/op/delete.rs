@@ -8,12 +8,17 @@ use std::time::Instant;
 
 use common::listener::SessionStream;
 use directory::Permission;
-use email::message::delete::EmailDeletion;
-use store::{roaring::RoaringBitmap, write::BatchBuilder};
+use email::{
+    message::delete::EmailDeletion,
+    pop3::{Pop3State, Pop3Store},
+};
+use store::write::{BatchBuilder, now};
 use trc::AddContext;
 
 use crate::{Session, State, protocol::response::Response};
 
+const MAX_RETRIES: usize = 5;
+
 impl<T: SessionStream> Session<T> {
     pub async fn handle_dele(&mut self, msgs: Vec<u32>) -> trc::Result<()> {
         // Validate access
@@ -76,7 +81,8 @@ impl<T: SessionStream> Session<T> {
         let mut deleted_docs = Vec::new();
 
         if let State::Authenticated { mailbox, .. } = &self.state {
-            let mut deleted = RoaringBitmap::new();
+            // Messages past their retention period are removed along with deleted ones
+            let mut deleted = mailbox.expired.clone();
             for message in &mailbox.messages {
                 if message.deleted {
                     deleted.insert(message.id);
@@ -84,6 +90,61 @@ impl<T: SessionStream> Session<T> {
                 }
             }
 
+            // Update the downloaded messages state
+            if let Some((state, current)) = &mailbox.state {
+                let current_time = now();
+                let downloaded = mailbox
+                    .messages
+                    .iter()
+                    .filter(|message| message.downloaded && !message.deleted)
+                    .map(|message| message.uid)
+                    .collect::<Vec<_>>();
+
+                if mailbox.state_changed || !downloaded.is_empty() {
+                    let mailbox_id = state.mailbox_id;
+                    let mut state = state.clone();
+                    let mut current = *current;
+                    let mut try_count = 0;
+
+                    loop {
+                        for uid in &downloaded {
+                            state.insert(*uid, current_time);
+                        }
+
+                        match self
+                            .server
+                            .set_pop3_state(mailbox.account_id, current, state)
+                            .await
+                        {
+                            Ok(_) => break,
+                            Err(err) if err.is_assertion_failure() && try_count < MAX_RETRIES => {
+                                // Another session updated the state first, add the
+                                // messages downloaded in this session to its version
+                                let (latest, latest_current) = self
+                                    .server
+                                    .get_pop3_state(mailbox.account_id)
+                                    .await
+                                    .caused_by(trc::location!())?;
+                                state = if latest.mailbox_id == mailbox_id
+                                    && latest.uid_validity == mailbox.uid_validity
+                                {
+                                    latest
+                                } else {
+                                    Pop3State {
+                                        mailbox_id,
+                                        uid_validity: mailbox.uid_validity,
+                                        downloaded: vec![],
+                                    }
+                                };
+                                current = latest_current;
+                                try_count += 1;
+                            }
+                            Err(err) => return Err(err.caused_by(trc::location!())),
+                        }
+                    }
+                }
+            }
+
             if !deleted.is_empty() {
                 let num_deleted = deleted.len();
                 let mut batch = BatchBuilder::new();
@@ -64,14 +64,19 @@ impl<T: SessionStream> Session<T> {
                         )
                         .get_full_range();
 
-                    self.write_bytes(
-                        Response::Message::<u32> {
-                            bytes,
-                            lines: lines.unwrap_or(0),
-                        }
-                        .serialize(),
-                    )
-                    .await
+                    let response = Response::Message::<u32> {
+                        bytes,
+                        lines: lines.unwrap_or(0),
+                    }
+                    .serialize();
+
+                    // Messages are considered downloaded once retrieved in full
+                    if lines.is_none() {
+                        self.state.mailbox_mut().messages[msg.saturating_sub(1) as usize]
+                            .downloaded = true;
+                    }
+
+                    self.write_bytes(response).await
                 } else {
                     Err(trc::Pop3Event::Error
                         .into_err()
@@ -156,7 +156,7 @@ pub fn spawn_broadcast_subscriber(inner: Arc<Inner>, mut shutdown_rx: watch::Rec
                                                     Ok(result) => {
                                                         if let Some(new_core) = result.new_core {
                                                             // Update core
-                                                            inner.shared_core.store(new_core.into());
+                                                            inner.replace_core(new_core);
 
                                                             if inner
                                                                 .ipc
@@ -44,6 +44,8 @@ enum ActionClass {
     Account,
     Store(usize),
     Acme(String),
+    DnsSync,
+    DkimRotation,
     OtelMetrics,
     CalculateMetrics,
     // SPDX-SnippetBegin
@@ -155,6 +157,18 @@ pub fn spawn_housekeeper(inner: Arc<Inner>, mut rx: mpsc::Receiver<HousekeeperEv
                 }
             }
 
+            // Managed DNS synchronization
+            if roles.sync_dns.is_enabled_or_sharded() && !server.core.dns.providers.is_empty() {
+                queue.schedule(Instant::now(), ActionClass::DnsSync);
+            }
+
+            // DKIM key rotation
+            if roles.rotate_dkim.is_enabled_or_sharded()
+                && server.core.smtp.mail_auth.dkim.rotation.is_some()
+            {
+                queue.schedule(Instant::now(), ActionClass::DkimRotation);
+            }
+
             // SPDX-SnippetBegin
             // SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
             // SPDX-License-Identifier: LicenseRef-SEL
@@ -215,6 +229,27 @@ pub fn spawn_housekeeper(inner: Arc<Inner>, mut rx: mpsc::Receiver<HousekeeperEv
                                 _ => {}
                             }
 
+                            // Reload managed DNS synchronization
+                            if server.core.network.roles.sync_dns.is_enabled_or_sharded()
+                                && !server.core.dns.providers.is_empty()
+                                && !queue.has_action(&ActionClass::DnsSync)
+                            {
+                                queue.schedule(Instant::now(), ActionClass::DnsSync);
+                            }
+
+                            // Reload DKIM key rotation
+                            if server
+                                .core
+                                .network
+                                .roles
+                                .rotate_dkim
+                                .is_enabled_or_sharded()
+                                && server.core.smtp.mail_auth.dkim.rotation.is_some()
+                                && !queue.has_action(&ActionClass::DkimRotation)
+                            {
+                                queue.schedule(Instant::now(), ActionClass::DkimRotation);
+                            }
+
                             // SPDX-SnippetBegin
                             // SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
                             // SPDX-License-Identifier: LicenseRef-SEL
@@ -336,6 +371,11 @@ pub fn spawn_housekeeper(inner: Arc<Inner>, mut rx: mpsc::Receiver<HousekeeperEv
                                                     )
                                                 );
 
+                                                // Publish the TLSA records of the new certificates
+                                                if !server.core.dns.providers.is_empty() {
+                                                    server.sync_managed_dns().await;
+                                                }
+
                                                 renew_at
                                             }
                                             Err(err) => {
@@ -364,6 +404,88 @@ pub fn spawn_housekeeper(inner: Arc<Inner>, mut rx: mpsc::Receiver<HousekeeperEv
                                     }
                                 });
                             }
+                            ActionClass::DnsSync => {
+                                if server.core.network.roles.sync_dns.is_enabled_or_sharded()
+                                    && !server.core.dns.providers.is_empty()
+                                {
+                                    trc::event!(
+                                        Housekeeper(trc::HousekeeperEvent::Run),
+                                        Type = "dns_sync"
+                                    );
+
+                                    queue.schedule(
+                                        Instant::now() + server.core.dns.sync_frequency,
+                                        ActionClass::DnsSync,
+                                    );
+
+                                    let server = server.clone();
+                                    tokio::spawn(async move {
+                                        server.sync_managed_dns().await;
+                                    });
+                                }
+                            }
+                            ActionClass::DkimRotation => {
+                                if let Some(rotation) = server
+                                    .core
+                                    .smtp
+                                    .mail_auth
+                                    .dkim
+                                    .rotation
+                                    .as_ref()
+                                    .filter(|_| {
+                                        server
+                                            .core
+                                            .network
+                                            .roles
+                                            .rotate_dkim
+                                            .is_enabled_or_sharded()
+                                    })
+                                {
+                                    trc::event!(
+                                        Housekeeper(trc::HousekeeperEvent::Run),
+                                        Type = "dkim_rotation"
+                                    );
+
+                                    queue.schedule(
+                                        Instant::now() + rotation.frequency,
+                                        ActionClass::DkimRotation,
+                                    );
+
+                                    let server = server.clone();
+                                    tokio::spawn(async move {
+                                        match server.rotate_dkim_keys().await {
+                                            Ok(true) => {
+                                                // Reload settings to start signing with the new keys
+                                                match server.reload().await {
+                                                    Ok(result) => {
+                                                        if let Some(new_core) = result.new_core {
+                                                            server.inner.replace_core(new_core);
+                                                            server
+                                                                .cluster_broadcast(
+                                                                    BroadcastEvent::ReloadSettings,
+                                                                )
+                                                                .await;
+                                                        }
+                                                    }
+                                                    Err(err) => {
+                                                        trc::error!(
+                                                            err.details(
+                                                                "Failed to reload settings"
+                                                            )
+                                                        );
+                                                    }
+                                                }
+                                            }
+                                            Ok(false) => {}
+                                            Err(err) => {
+                                                trc::error!(
+                                                    err.details("Failed to rotate DKIM keys")
+                                                );
+                                            }
+                                        }
+                                    });
+                                }
+                            }
                             ActionClass::Account => {
                                 trc::event!(
                                     Housekeeper(trc::HousekeeperEvent::Run),
@@ -695,7 +817,7 @@ pub fn spawn_housekeeper(inner: Arc<Inner>, mut rx: mpsc::Receiver<HousekeeperEv
                                             }
 
                                             // Update core
-                                            server.inner.shared_core.store(new_core.into());
+                                            server.inner.replace_core(new_core);
 
                                             server
                                                 .cluster_broadcast(BroadcastEvent::ReloadSettings)
@@ -206,6 +206,35 @@ impl TaskLock for Task<MergeThreadIds<AHashSet<u32>>> {
     }
 }
 
+impl TaskLock for Task<UnsnoozeAction> {
+    fn account_id(&self) -> u32 {
+        self.account_id
+    }
+
+    fn document_id(&self) -> u32 {
+        self.document_id
+    }
+
+    fn lock_key(&self) -> Vec<u8> {
+        KeySerializer::new((U32_LEN * 2) + U64_LEN + 1)
+            .write(5u8)
+            .write(self.due.inner())
+            .write_leb128(self.account_id)
+            .write_leb128(self.document_id)
+            .finalize()
+    }
+
+    fn lock_expiry(&self) -> u64 {
+        ALARM_EXPIRY
+    }
+
+    fn value_classes(&self) -> impl Iterator<Item = ValueClass> {
+        std::iter::once(ValueClass::TaskQueue(TaskQueueClass::UnsnoozeEmail {
+            due: self.due,
+        }))
+    }
+}
+
 impl Task<TaskAction> {
     pub(crate) fn lock_expiry(&self) -> u64 {
         match &self.action {
@@ -265,6 +294,7 @@ impl Task<TaskAction> {
                         || trc::Error::corrupted_key(key, value.into(), trc::location!()),
                     )?)
                 }
+                Some(10) => TaskAction::UnsnoozeEmail,
                 _ => return Err(trc::Error::corrupted_key(key, None, trc::location!())),
             },
         })
@@ -8,6 +8,7 @@ use crate::task_manager::imip::SendImipTask;
 use crate::task_manager::index::SearchIndexTask;
 use crate::task_manager::lock::{TaskLock, TaskLockManager};
 use crate::task_manager::merge_threads::MergeThreadsTask;
+use crate::task_manager::snooze::UnsnoozeEmailTask;
 use alarm::SendAlarmTask;
 use common::IPC_CHANNEL_BUFFER;
 use common::config::server::ServerProtocol;
@@ -42,6 +43,7 @@ pub mod imip;
 pub mod index;
 pub mod lock;
 pub mod merge_threads;
+pub mod snooze;
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct Task<T> {
@@ -57,6 +59,7 @@ pub enum TaskAction {
     SendAlarm(CalendarAlarm),
     SendImip,
     MergeThreads(MergeThreadIds<AHashSet<u32>>),
+    UnsnoozeEmail,
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
@@ -68,6 +71,9 @@ pub struct IndexAction {
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub(crate) struct ImipAction;
 
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub(crate) struct UnsnoozeAction;
+
 const INDEX_EXPIRY: u64 = 60 * 5; // 5 minutes
 const ALARM_EXPIRY: u64 = 60 * 2; // 2 minutes
 const QUEUE_REFRESH_INTERVAL: u64 = 60 * 5; // 5 minutes
@@ -77,6 +83,7 @@ pub(crate) struct TaskManagerIpc {
     tx_alarm: mpsc::Sender<Task<CalendarAlarm>>,
     tx_imip: mpsc::Sender<Task<ImipAction>>,
     tx_threads: mpsc::Sender<Task<MergeThreadIds<AHashSet<u32>>>>,
+    tx_snooze: mpsc::Sender<Task<UnsnoozeAction>>,
     locked: AHashMap<Vec<u8>, Locked>,
     revision: u64,
 }
@@ -93,6 +100,7 @@ pub fn spawn_task_manager(inner: Arc<Inner>) {
     let (tx_index_3, mut rx_index_3) = mpsc::channel::<Task<ImipAction>>(IPC_CHANNEL_BUFFER);
     let (tx_index_4, mut rx_index_4) =
         mpsc::channel::<Task<MergeThreadIds<AHashSet<u32>>>>(IPC_CHANNEL_BUFFER);
+    let (tx_index_5, mut rx_index_5) = mpsc::channel::<Task<UnsnoozeAction>>(IPC_CHANNEL_BUFFER);
 
     // Create dummy server instance for alarms
     let server_instance = Arc::new(ServerInstance {
@@ -294,12 +302,50 @@ pub fn spawn_task_manager(inner: Arc<Inner>) {
         });
     }
 
+    // Unsnooze e-mail worker
+    {
+        let inner = inner.clone();
+        tokio::spawn(async move {
+            while let Some(task) = rx_index_5.recv().await {
+                let server = inner.build_server();
+
+                // Lock task
+                if server
+                    .try_lock_task(
+                        task.account_id,
+                        task.document_id,
+                        task.lock_key(),
+                        task.lock_expiry(),
+                    )
+                    .await
+                {
+                    let success = server
+                        .unsnooze_email(task.account_id, task.document_id, task.due)
+                        .await;
+
+                    // Remove entry from queue
+                    if success {
+                        delete_tasks(&server, &[task]).await;
+                    } else {
+                        trc::event!(
+                            TaskQueue(TaskQueueEvent::TaskFailed),
+                            AccountId = task.account_id,
+                            DocumentId = task.document_id,
+                            Details = "Unsnoozing e-mail task failed",
+                        );
+                    }
+                }
+            }
+        });
+    }
+
     tokio::spawn(async move {
         let mut ipc = TaskManagerIpc {
             tx_fts: tx_index_1,
             tx_alarm: tx_index_2,
             tx_imip: tx_index_3,
             tx_threads: tx_index_4,
+            tx_snooze: tx_index_5,
             locked: Default::default(),
             revision: 0,
         };
@@ -492,6 +538,25 @@ impl TaskQueueManager for Server {
                         );
                     }
                 }
+                TaskAction::UnsnoozeEmail if roles.unsnooze_emails.is_enabled_for_hash(&event) => {
+                    if ipc
+                        .tx_snooze
+                        .send(Task {
+                            account_id: event.account_id,
+                            document_id: event.document_id,
+                            due: event.due,
+                            action: UnsnoozeAction,
+                        })
+                        .await
+                        .is_err()
+                    {
+                        trc::event!(
+                            Server(trc::ServerEvent::ThreadError),
+                            Details = "Error sending task.",
+                            CausedBy = trc::location!()
+                        );
+                    }
+                }
                 _ => {
                     trc::event!(
                         TaskQueue(TaskQueueEvent::TaskIgnored),
@@ -544,6 +609,7 @@ impl TaskAction {
             TaskAction::SendAlarm(_) => "SendAlarm",
             TaskAction::SendImip => "SendImip",
             TaskAction::MergeThreads(_) => "MergeThreads",
+            TaskAction::UnsnoozeEmail => "UnsnoozeEmail",
         }
     }
 }
@@ -0,0 +1,205 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use common::{Server, storage::index::ObjectIndexBuilder};
+use email::{
+    cache::{MessageCacheFetch, mailbox::MailboxCacheAccess},
+    mailbox::{INBOX_ID, UidMailbox},
+    message::{
+        ingest::EmailIngest,
+        metadata::MessageData,
+        savedate::SaveDateBatch,
+        snooze::{EmailSnooze, Snooze},
+    },
+};
+use std::time::Duration;
+use store::{
+    ValueKey,
+    rand::Rng,
+    write::{AlignedBytes, Archive, BatchBuilder, TaskEpoch, now},
+};
+use trc::AddContext;
+use types::{
+    collection::{Collection, SyncCollection, VanishedCollection},
+    field::EmailField,
+    special_use::SpecialUse,
+};
+
+const MAX_RETRIES: usize = 5;
+
+pub trait UnsnoozeEmailTask: Sync + Send {
+    fn unsnooze_email(
+        &self,
+        account_id: u32,
+        document_id: u32,
+        due: TaskEpoch,
+    ) -> impl Future<Output = bool> + Send;
+}
+
+impl UnsnoozeEmailTask for Server {
+    async fn unsnooze_email(&self, account_id: u32, document_id: u32, due: TaskEpoch) -> bool {
+        match unsnooze_email(self, account_id, document_id, due).await {
+            Ok(_) => true,
+            Err(err) => {
+                trc::error!(
+                    err.account_id(account_id)
+                        .document_id(document_id)
+                        .details("Failed to unsnooze e-mail")
+                );
+                false
+            }
+        }
+    }
+}
+
+async fn unsnooze_email(
+    server: &Server,
+    account_id: u32,
+    document_id: u32,
+    due: TaskEpoch,
+) -> trc::Result<()> {
+    let mut try_count = 0;
+
+    loop {
+        // Make sure the message is still snoozed until the task due date,
+        // otherwise the snooze was cancelled or rescheduled
+        let Some(snooze) = server
+            .get_snooze(account_id, document_id)
+            .await
+            .caused_by(trc::location!())?
+            .filter(|snooze| snooze.until == due.due())
+        else {
+            return Ok(());
+        };
+        let Some(data_) = server
+            .store()
+            .get_value::<Archive<AlignedBytes>>(ValueKey::archive(
+                account_id,
+                Collection::Email,
+                document_id,
+            ))
+            .await
+            .caused_by(trc::location!())?
+        else {
+            return Ok(());
+        };
+        let data = data_
+            .to_unarchived::<MessageData>()
+            .caused_by(trc::location!())?;
+        let mut new_data = data.inner.to_builder();
+
+        // Move the message out of the snoozed mailbox and into the target mailbox,
+        // the original mailboxes or the inbox, in that order
+        let cache = server
+            .get_cached_messages(account_id)
+            .await
+            .caused_by(trc::location!())?;
+        let snoozed_id = cache
+            .mailbox_by_role(&SpecialUse::Snoozed)
+            .map(|mailbox| mailbox.document_id);
+        let is_valid_target =
+            |mailbox_id: &u32| cache.has_mailbox_id(mailbox_id) && Some(*mailbox_id) != snoozed_id;
+        let mut target_ids = snooze
+            .move_to
+            .filter(is_valid_target)
+            .map(|mailbox_id| vec![mailbox_id])
+            .unwrap_or_else(|| {
+                snooze
+                    .restore_to
+                    .iter()
+                    .copied()
+                    .filter(is_valid_target)
+                    .collect()
+            });
+        if target_ids.is_empty() {
+            target_ids.push(INBOX_ID);
+        }
+        if let Some(snoozed_id) = snoozed_id {
+            new_data.remove_mailbox(snoozed_id);
+        }
+        for mailbox_id in target_ids {
+            if !new_data
+                .mailboxes
+                .iter()
+                .any(|m| m.mailbox_id == mailbox_id)
+            {
+                new_data.add_mailbox(UidMailbox::new_unassigned(mailbox_id));
+            }
+        }
+        new_data.remove_keyword(&EmailSnooze::keyword());
+
+        // Obtain IMAP UIDs for added mailboxes
+        let ids = server
+            .assign_email_ids(
+                account_id,
+                new_data
+                    .mailboxes
+                    .iter()
+                    .filter(|m| m.uid == 0)
+                    .map(|m| m.mailbox_id),
+                false,
+            )
+            .await
+            .caused_by(trc::location!())?;
+        let mut assigned_uids = Vec::new();
+        for (uid_mailbox, uid) in new_data
+            .mailboxes
+            .iter_mut()
+            .filter(|m| m.uid == 0)
+            .zip(ids)
+        {
+            uid_mailbox.uid = uid;
+            assigned_uids.push((uid_mailbox.mailbox_id, uid));
+        }
+
+        // Log mailbox changes
+        let mut batch = BatchBuilder::new();
+        batch
+            .with_account_id(account_id)
+            .with_collection(Collection::Email)
+            .with_document(document_id);
+        for mailbox in new_data.removed_mailboxes(data.inner) {
+            batch
+                .clear_save_date(mailbox.mailbox_id.to_native(), mailbox.uid.to_native())
+                .log_container_property_change(
+                    SyncCollection::Email,
+                    mailbox.mailbox_id.to_native(),
+                )
+                .log_vanished_item(
+                    VanishedCollection::Email,
+                    (mailbox.mailbox_id.to_native(), mailbox.uid.to_native()),
+                );
+        }
+        let saved_at = now();
+        for (mailbox_id, uid) in assigned_uids {
+            batch
+                .set_save_date(mailbox_id, uid, saved_at)
+                .log_container_property_change(SyncCollection::Email, mailbox_id);
+        }
+
+        // Write changes
+        batch
+            .custom(
+                ObjectIndexBuilder::new()
+                    .with_current(data)
+                    .with_changes(new_data.seal()),
+            )
+            .caused_by(trc::location!())?
+            .clear(EmailField::Snooze);
+
+        match server.commit_batch(batch).await {
+            Ok(_) => return Ok(()),
+            Err(err) if err.is_assertion_failure() && try_count < MAX_RETRIES => {
+                let backoff = store::rand::rng().random_range(50..=300);
+                tokio::time::sleep(Duration::from_millis(backoff)).await;
+                try_count += 1;
+            }
+            Err(err) => {
+                return Err(err.caused_by(trc::location!()));
+            }
+        }
+    }
+}
@@ -0,0 +1,102 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use crate::core::Session;
+use common::{config::server::ServerProtocol, listener::SessionStream};
+use email::message::urlauth::UrlAuth;
+use std::borrow::Cow;
+use trc::SmtpEvent;
+
+impl<T: SessionStream> Session<T> {
+    pub async fn handle_burl(&mut self, uri: Cow<'_, str>, is_last: bool) -> Result<(), ()> {
+        let Some(access_token) = self.data.authenticated_as.clone() else {
+            return self.write(b"530 5.7.0 Authentication required.\r\n").await;
+        };
+        if !self.params.can_burl {
+            trc::event!(
+                Smtp(SmtpEvent::BurlDisabled),
+                SpanId = self.data.session_id,
+                Url = uri.as_ref().to_string(),
+            );
+
+            return self.write(b"502 5.7.0 BURL is disabled.\r\n").await;
+        } else if !self.can_send_data().await? {
+            return Ok(());
+        }
+
+        // Resolve the referenced message using the requester's permissions
+        let contents = match self
+            .server
+            .submission_fetch(uri.as_ref(), &access_token)
+            .await
+        {
+            Ok(Some(contents)) => contents,
+            Ok(None) => {
+                trc::event!(
+                    Smtp(SmtpEvent::BurlFailed),
+                    SpanId = self.data.session_id,
+                    Url = uri.as_ref().to_string(),
+                );
+
+                return self
+                    .write(b"554 5.6.6 IMAP URL resolution failed.\r\n")
+                    .await;
+            }
+            Err(err) => {
+                trc::error!(
+                    err.span_id(self.data.session_id)
+                        .details("BURL resolution failed")
+                );
+
+                return self
+                    .write(b"454 4.6.5 Unable to retrieve message data.\r\n")
+                    .await;
+            }
+        };
+        if contents.len() + self.data.message.len() >= self.params.max_message_size {
+            trc::event!(
+                Smtp(SmtpEvent::MessageTooLarge),
+                SpanId = self.data.session_id,
+                Size = contents.len() + self.data.message.len(),
+                Limit = self.params.max_message_size,
+            );
+
+            self.data.message = Vec::with_capacity(0);
+            return self
+                .write(b"552 5.3.4 Message too big for system.\r\n")
+                .await;
+        }
+
+        trc::event!(
+            Smtp(SmtpEvent::Burl),
+            SpanId = self.data.session_id,
+            Url = uri.as_ref().to_string(),
+            Size = contents.len(),
+        );
+
+        self.data.message.extend_from_slice(&contents);
+        if is_last {
+            let message = self.queue_message().await;
+            if !message.is_empty() {
+                let num_responses = if self.instance.protocol == ServerProtocol::Smtp {
+                    1
+                } else {
+                    self.data.rcpt_oks
+                };
+                for _ in 0..num_responses {
+                    self.write(message.as_ref()).await?;
+                }
+                self.reset();
+                Ok(())
+            } else {
+                // Disconnect requested
+                Err(())
+            }
+        } else {
+            self.write(b"250 2.5.0 OK.\r\n").await
+        }
+    }
+}
@@ -15,6 +15,7 @@ use crate::{
     reporting::analysis::AnalyzeReport,
     scripts::ScriptResult,
 };
+use ahash::AHashSet;
 use common::{
     config::{
         smtp::{
@@ -29,7 +30,7 @@ use common::{
     scripts::ScriptModification,
 };
 use mail_auth::{
-    AuthenticatedMessage, AuthenticationResults, DkimResult, DmarcResult, ReceivedSpf,
+    ArcOutput, AuthenticatedMessage, AuthenticationResults, DkimResult, DmarcResult, ReceivedSpf,
     common::{headers::HeaderWriter, verify::VerifySignature},
     dmarc::{self, verify::DmarcParameters},
 };
@@ -214,6 +215,15 @@ impl<T: SessionStream> Session<T> {
             None
         };
 
+        // Check whether the message was sealed by a trusted forwarder
+        let arc_trusted_sealer = arc_output.as_ref().and_then(|arc_output| {
+            trusted_arc_sealer(
+                arc_output,
+                &ac.arc.trusted_sealers,
+                &auth_message.from().domain_part().to_lowercase(),
+            )
+        });
+
         // Build authentication results header
         let mail_from = self.data.mail_from.as_ref().unwrap();
         let mut auth_results = AuthenticationResults::new(&self.hostname);
@@ -270,7 +280,18 @@ impl<T: SessionStream> Session<T> {
                 let pass = matches!(dmarc_output.spf_result(), DmarcResult::Pass)
                     || matches!(dmarc_output.dkim_result(), DmarcResult::Pass);
                 let strict = dmarc.is_strict();
-                let rejected = strict && dmarc_output.policy() == dmarc::Policy::Reject && !pass;
+                let mut dmarc_policy = dmarc_output.policy();
+                let trusted_sealer = arc_trusted_sealer.filter(|_| {
+                    !pass
+                        && matches!(
+                            dmarc_policy,
+                            dmarc::Policy::Reject | dmarc::Policy::Quarantine
+                        )
+                });
+                let rejected = strict
+                    && dmarc_policy == dmarc::Policy::Reject
+                    && !pass
+                    && trusted_sealer.is_none();
                 let is_temp_fail = rejected
                     && matches!(dmarc_output.spf_result(), DmarcResult::TempError(_))
                     || matches!(dmarc_output.dkim_result(), DmarcResult::TempError(_));
@@ -286,7 +307,6 @@ impl<T: SessionStream> Session<T> {
                 } else {
                     DmarcResult::None
                 };
-                let dmarc_policy = dmarc_output.policy();
 
                 trc::event!(
                     Smtp(if pass {
@@ -302,6 +322,18 @@ impl<T: SessionStream> Session<T> {
                     Elapsed = time.elapsed(),
                 );
 
+                // Local policy override for trusted forwarders
+                if let Some(sealer) = trusted_sealer {
+                    trc::event!(
+                        Smtp(SmtpEvent::DmarcPolicyOverride),
+                        SpanId = self.data.session_id,
+                        Domain = sealer.to_string(),
+                        Policy = dmarc_policy.to_string(),
+                    );
+
+                    dmarc_policy = dmarc::Policy::None;
+                }
+
                 // Send DMARC report
                 if dmarc_output.requested_reports() && !is_report {
                     self.send_dmarc_report(
@@ -311,6 +343,7 @@ impl<T: SessionStream> Session<T> {
                         dmarc_output,
                         &dkim_output,
                         &arc_output,
+                        trusted_sealer,
                     )
                     .await;
                 }
@@ -426,6 +459,7 @@ impl<T: SessionStream> Session<T> {
 
         // Run SPAM filter
         let mut train_spam = None;
+        let mut hold_reason = None;
         if self.server.core.spam.enabled
             && self
                 .server
@@ -447,6 +481,11 @@ impl<T: SessionStream> Session<T> {
                     // Add headers
                     headers.extend_from_slice(score.headers.as_bytes());
                     train_spam = score.train_spam;
+                    let hold_threshold = self.server.core.spam.scores.hold_threshold;
+                    if hold_threshold > 0.0 && score.score >= hold_threshold {
+                        hold_reason =
+                            format!("Spam score {:.2} exceeds hold threshold", score.score).into();
+                    }
 
                     // Add scores for local recipients
                     for (is_spam, recipient) in
@@ -598,6 +637,9 @@ impl<T: SessionStream> Session<T> {
                     ScriptModification::SetEnvelope { name, value } => {
                         self.data.apply_envelope_modification(name, value);
                     }
+                    ScriptModification::Hold { reason } => {
+                        hold_reason = reason.to_string().into();
+                    }
                 }
             }
         }
@@ -672,6 +714,27 @@ impl<T: SessionStream> Session<T> {
         // Update size
         message.message.size = (raw_message.len() + headers.len()) as u64;
 
+        // Hold message for review
+        if hold_reason.is_none()
+            && self
+                .server
+                .eval_if(&dc.hold, self, self.data.session_id)
+                .await
+                .unwrap_or(false)
+        {
+            hold_reason = "Matched hold rule".to_string().into();
+        }
+        if let Some(reason) = hold_reason
+            && message.hold_recipients(&reason)
+        {
+            trc::event!(
+                Queue(trc::QueueEvent::MessageHeld),
+                SpanId = self.data.session_id,
+                QueueId = message.queue_id,
+                Reason = reason,
+            );
+        }
+
         // Verify queue quota
         if self.server.has_quota(&mut message).await {
             // Prepare webhook event
@@ -952,3 +1015,62 @@ impl<T: SessionStream> Session<T> {
         headers.extend_from_slice(b"\r\n");
     }
 }
+
+fn trusted_arc_sealer<'x>(
+    arc_output: &'x ArcOutput<'_>,
+    trusted_sealers: &AHashSet<String>,
+    from_domain: &str,
+) -> Option<&'x str> {
+    if trusted_sealers.is_empty() || !matches!(arc_output.result(), DkimResult::Pass) {
+        return None;
+    }
+
+    // Use the results recorded by the trusted sealer, as the
+    // original message may have been modified by the forwarder
+    arc_output.sets().iter().rev().find_map(|set| {
+        let sealer = set.seal.header.d.as_str();
+        if !trusted_sealers.contains(&sealer.to_lowercase()) {
+            return None;
+        }
+        let results = std::str::from_utf8(set.results.value).ok()?.to_lowercase();
+        let mut has_dmarc = false;
+        let mut has_aligned_pass = false;
+        for result in results.split(';') {
+            let result = result.trim();
+            if let Some(value) = result.strip_prefix("dmarc=") {
+                if value.starts_with("pass") {
+                    return Some(sealer);
+                }
+                has_dmarc = true;
+            } else if result.starts_with("dkim=pass") {
+                has_aligned_pass |= auth_result_domain(result, &["header.d=", "header.i="])
+                    .is_some_and(|domain| is_aligned(domain, from_domain));
+            } else if result.starts_with("spf=pass") {
+                has_aligned_pass |= auth_result_domain(result, &["smtp.mailfrom="])
+                    .is_some_and(|domain| is_aligned(domain, from_domain));
+            }
+        }
+
+        // Without a DMARC verdict, a DKIM or SPF pass only counts when it
+        // is aligned with the RFC5322.From domain
+        (!has_dmarc && has_aligned_pass).then_some(sealer)
+    })
+}
+
+fn auth_result_domain<'x>(result: &'x str, properties: &[&str]) -> Option<&'x str> {
+    result.split_ascii_whitespace().find_map(|token| {
+        properties
+            .iter()
+            .find_map(|property| token.strip_prefix(property))
+            .map(|value| value.rsplit_once('@').map_or(value, |(_, domain)| domain))
+            .filter(|domain| !domain.is_empty())
+    })
+}
+
+fn is_aligned(domain: &str, from_domain: &str) -> bool {
+    !from_domain.is_empty()
+        && (domain == from_domain
+            || psl::domain_str(domain)
+                .zip(psl::domain_str(from_domain))
+                .is_some_and(|(a, b)| a == b))
+}
@@ -192,6 +192,37 @@ impl<T: SessionStream> Session<T> {
             response.capabilities |= EXT_VRFY;
         }
 
+        // Remote Message Queue Starting
+        if self
+            .server
+            .eval_if(&ec.etrn, self, self.data.session_id)
+            .await
+            .unwrap_or(false)
+        {
+            response.capabilities |= EXT_ETRN;
+        }
+
+        // Authenticated TURN
+        if self.is_authenticated()
+            && self
+                .server
+                .eval_if(&ec.atrn, self, self.data.session_id)
+                .await
+                .unwrap_or(false)
+        {
+            response.capabilities |= EXT_ATRN;
+        }
+
+        // Submission by reference
+        if self
+            .server
+            .eval_if(&ec.burl, self, self.data.session_id)
+            .await
+            .unwrap_or(false)
+        {
+            response.capabilities |= EXT_BURL;
+        }
+
         // Require TLS
         if self
             .server
@@ -13,6 +13,7 @@ use mail_auth::{
 };
 
 pub mod auth;
+pub mod burl;
 pub mod data;
 pub mod ehlo;
 pub mod hooks;
@@ -22,6 +23,7 @@ pub mod rcpt;
 pub mod session;
 pub mod spam;
 pub mod spawn;
+pub mod turn;
 pub mod vrfy;
 
 #[derive(Debug, Default)]
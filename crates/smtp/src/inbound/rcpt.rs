@@ -6,6 +6,7 @@
 
 use crate::{
     core::{Session, SessionAddress},
+    queue::srs::SenderRewriting,
     scripts::ScriptResult,
 };
 use common::{
@@ -16,7 +17,7 @@ use smtp_proto::{
     RCPT_NOTIFY_DELAY, RCPT_NOTIFY_FAILURE, RCPT_NOTIFY_NEVER, RCPT_NOTIFY_SUCCESS, RcptTo,
 };
 use std::borrow::Cow;
-use store::dispatch::lookup::KeyValue;
+use store::{dispatch::lookup::KeyValue, write::now};
 use trc::{SecurityEvent, SmtpEvent};
 use utils::DomainPart;
 
@@ -68,7 +69,7 @@ impl<T: SessionStream> Session<T> {
 
         // Build RCPT
         let address_lcase = to.address.to_lowercase();
-        let rcpt = SessionAddress {
+        let mut rcpt = SessionAddress {
             domain: address_lcase.domain_part().into(),
             address_lcase,
             address: to.address.into_owned(),
@@ -76,6 +77,40 @@ impl<T: SessionStream> Session<T> {
             dsn_info: to.orcpt.map(|e| e.into_owned()),
         };
 
+        // Reverse SRS addresses so bounces are routed back to the original sender
+        let mut is_srs_bounce = false;
+        if let Some(srs) = &self.server.core.smtp.queue.srs
+            && let Some(result) = srs.srs_reverse(&rcpt.address, now())
+        {
+            match result {
+                Ok(address) => {
+                    trc::event!(
+                        Smtp(SmtpEvent::SrsReversed),
+                        SpanId = self.data.session_id,
+                        Details = rcpt.address_lcase,
+                        To = address.clone(),
+                    );
+
+                    rcpt.address_lcase = address.to_lowercase();
+                    rcpt.domain = rcpt.address_lcase.domain_part().into();
+                    rcpt.address = address;
+                    is_srs_bounce = true;
+                }
+                Err(reason) => {
+                    trc::event!(
+                        Smtp(SmtpEvent::SrsInvalid),
+                        SpanId = self.data.session_id,
+                        To = rcpt.address_lcase.clone(),
+                        Reason = reason,
+                    );
+
+                    return self
+                        .rcpt_error(b"550 5.1.1 Invalid SRS address.\r\n", rcpt.address_lcase)
+                        .await;
+                }
+            }
+        }
+
         if self.data.rcpt_to.contains(&rcpt) {
             trc::event!(
                 Smtp(SmtpEvent::RcptToDuplicate),
@@ -235,11 +270,12 @@ impl<T: SessionStream> Session<T> {
                     }
                 }
                 Ok(false) => {
-                    if !self
-                        .server
-                        .eval_if(&rcpt_config.relay, self, self.data.session_id)
-                        .await
-                        .unwrap_or(false)
+                    if !is_srs_bounce
+                        && !self
+                            .server
+                            .eval_if(&rcpt_config.relay, self, self.data.session_id)
+                            .await
+                            .unwrap_or(false)
                     {
                         trc::event!(
                             Smtp(SmtpEvent::RelayNotAllowed),
@@ -266,11 +302,12 @@ impl<T: SessionStream> Session<T> {
                         .await;
                 }
             }
-        } else if !self
-            .server
-            .eval_if(&rcpt_config.relay, self, self.data.session_id)
-            .await
-            .unwrap_or(false)
+        } else if !is_srs_bounce
+            && !self
+                .server
+                .eval_if(&rcpt_config.relay, self, self.data.session_id)
+                .await
+                .unwrap_or(false)
         {
             trc::event!(
                 Smtp(SmtpEvent::RelayNotAllowed),
@@ -222,17 +222,14 @@ impl<T: SessionStream> Session<T> {
                                         .await?;
                                 }
                             }
-                            cmd @ (Request::Etrn { .. }
-                            | Request::Atrn { .. }
-                            | Request::Burl { .. }) => {
-                                trc::event!(
-                                    Smtp(SmtpEvent::CommandNotImplemented),
-                                    SpanId = self.data.session_id,
-                                    Details = format!("{cmd:?}"),
-                                );
-
-                                self.write(b"502 5.5.1 Command not implemented.\r\n")
-                                    .await?;
+                            Request::Etrn { name } => {
+                                self.handle_etrn(name).await?;
+                            }
+                            Request::Atrn { domains } => {
+                                self.handle_atrn(domains).await?;
+                            }
+                            Request::Burl { uri, is_last } => {
+                                self.handle_burl(uri, is_last).await?;
                             }
                         },
                         Err(err) => match err {
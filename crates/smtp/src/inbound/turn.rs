@@ -0,0 +1,411 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use crate::{
+    core::Session,
+    outbound::client::{SmtpClient, from_error_status},
+    outbound::session::SessionParams,
+    queue::{RCPT_PARKED, RCPT_RELEASED, Status, dsn::SendDsn, spool::SmtpSpool},
+};
+use common::{config::smtp::queue::QueueName, ipc::QueueEvent, listener::SessionStream};
+use directory::Permission;
+use std::{borrow::Cow, time::Instant};
+use store::write::now;
+use trc::{DeliveryEvent, SmtpEvent};
+use utils::{DomainPart, config::Rate};
+
+impl<T: SessionStream> Session<T> {
+    pub async fn handle_etrn(&mut self, name: Cow<'_, str>) -> Result<(), ()> {
+        if !self.params.can_etrn {
+            trc::event!(
+                Smtp(SmtpEvent::EtrnDisabled),
+                SpanId = self.data.session_id,
+                Domain = name.as_ref().to_string(),
+            );
+
+            return self.write(b"502 5.7.0 ETRN is disabled.\r\n").await;
+        }
+
+        // RFC 1985 node names: "domain", "@domain" (including subdomains) or "#queue"
+        let node = name.trim().to_lowercase();
+        if node.starts_with('#') {
+            trc::event!(
+                Smtp(SmtpEvent::Etrn),
+                SpanId = self.data.session_id,
+                Domain = node.clone(),
+                Reason = "Queue names are not supported",
+            );
+
+            return self
+                .write(
+                    format!(
+                        "459 4.3.0 Node {node} not allowed: queue names are not supported.\r\n"
+                    )
+                    .as_bytes(),
+                )
+                .await;
+        }
+        let (domain, include_subdomains) = match node.strip_prefix('@') {
+            Some(domain) => (domain, true),
+            None => (node.as_str(), false),
+        };
+        if domain.is_empty() || domain.contains(|ch: char| ch.is_whitespace() || ch == '@') {
+            return self.write(b"501 5.5.4 Invalid node name.\r\n").await;
+        }
+        let subdomain_suffix = format!(".{domain}");
+        let is_match = |rcpt_domain: &str| {
+            rcpt_domain == domain
+                || (include_subdomains && rcpt_domain.ends_with(subdomain_suffix.as_str()))
+        };
+
+        // Finding pending messages scans the whole queue, limit how often it can be requested
+        if let Some(rate) = self
+            .server
+            .eval_if::<Rate, _>(
+                &self.server.core.smtp.session.extensions.etrn_rate,
+                self,
+                self.data.session_id,
+            )
+            .await
+        {
+            if !self
+                .throttle_rcpt(&self.data.remote_ip_str, &rate, "etrn")
+                .await
+            {
+                trc::event!(
+                    Smtp(SmtpEvent::RateLimitExceeded),
+                    SpanId = self.data.session_id,
+                    Domain = node.clone(),
+                    Limit = vec![
+                        trc::Value::from(rate.requests),
+                        trc::Value::from(rate.period)
+                    ],
+                );
+
+                return self
+                    .write(b"458 4.7.0 Too many ETRN requests, try again later.\r\n")
+                    .await;
+            }
+        }
+
+        // Find pending messages for the requested node
+        let ids = match self.server.pending_messages_for(&is_match).await {
+            Ok(ids) => ids,
+            Err(err) => {
+                trc::error!(err.span_id(self.data.session_id).details("ETRN failed"));
+
+                return self
+                    .write(
+                        format!("458 4.3.0 Unable to queue messages for node {node}.\r\n")
+                            .as_bytes(),
+                    )
+                    .await;
+            }
+        };
+
+        // Reschedule the recipients for immediate delivery
+        let due = now();
+        let mut total = 0;
+        for id in ids {
+            if let Some(mut message) = self.server.read_message(id, QueueName::default()).await {
+                let mut has_changes = false;
+
+                for rcpt in &mut message.message.recipients {
+                    if matches!(rcpt.status, Status::Scheduled | Status::TemporaryFailure(_))
+                        && !rcpt.is_held()
+                        && is_match(rcpt.domain_part())
+                    {
+                        rcpt.retry.due = due;
+                        rcpt.flags = (rcpt.flags & !RCPT_PARKED) | RCPT_RELEASED;
+                        has_changes = true;
+                    }
+                }
+
+                if has_changes && message.save_changes(&self.server, None).await {
+                    total += 1;
+                }
+            }
+        }
+
+        trc::event!(
+            Smtp(SmtpEvent::Etrn),
+            SpanId = self.data.session_id,
+            Domain = node.clone(),
+            Total = total,
+        );
+
+        if total > 0 {
+            let _ = self
+                .server
+                .inner
+                .ipc
+                .queue_tx
+                .send(QueueEvent::Refresh)
+                .await;
+
+            self.write(
+                format!("253 2.0.0 OK, {total} pending messages for node {node} started.\r\n")
+                    .as_bytes(),
+            )
+            .await
+        } else {
+            self.write(format!("251 2.0.0 OK, no messages waiting for node {node}.\r\n").as_bytes())
+                .await
+        }
+    }
+
+    pub async fn handle_atrn(&mut self, domains: Vec<Cow<'_, str>>) -> Result<(), ()> {
+        if !self.is_authenticated() {
+            return self.write(b"530 5.7.0 Authentication required.\r\n").await;
+        } else if !self.params.can_atrn {
+            trc::event!(
+                Smtp(SmtpEvent::AtrnDisabled),
+                SpanId = self.data.session_id,
+                Domain = domains
+                    .iter()
+                    .map(|d| trc::Value::String(d.as_ref().into()))
+                    .collect::<Vec<_>>(),
+            );
+
+            return self.write(b"502 5.7.0 ATRN is disabled.\r\n").await;
+        } else if !self
+            .data
+            .authenticated_as
+            .as_ref()
+            .is_some_and(|token| token.has_permission(Permission::SmtpAtrn))
+        {
+            trc::event!(
+                Smtp(SmtpEvent::Atrn),
+                SpanId = self.data.session_id,
+                Domain = domains
+                    .iter()
+                    .map(|d| trc::Value::String(d.as_ref().into()))
+                    .collect::<Vec<_>>(),
+                Reason = "Account is not authorized to use ATRN",
+            );
+
+            return self
+                .write(b"550 5.7.1 Not authorized to use ATRN.\r\n")
+                .await;
+        }
+
+        // Only the domains of the authorized account can be dequeued
+        let mut allowed: Vec<String> = Vec::new();
+        for domain in self
+            .authenticated_emails()
+            .iter()
+            .filter_map(|email| email.try_domain_part())
+        {
+            let domain = domain.to_lowercase();
+            if !allowed.contains(&domain) {
+                allowed.push(domain);
+            }
+        }
+        let mut requested: Vec<String> = Vec::with_capacity(domains.len());
+        for domain in domains {
+            let domain = domain.trim().to_lowercase();
+            if !allowed.contains(&domain) {
+                trc::event!(
+                    Smtp(SmtpEvent::Atrn),
+                    SpanId = self.data.session_id,
+                    Domain = domain.clone(),
+                    Reason = "Not authorized for domain",
+                );
+
+                return self
+                    .write(format!("550 5.7.1 Not authorized for domain {domain}.\r\n").as_bytes())
+                    .await;
+            } else if !requested.contains(&domain) {
+                requested.push(domain);
+            }
+        }
+        if requested.is_empty() {
+            requested = allowed;
+        }
+        let is_match = |rcpt_domain: &str| requested.iter().any(|domain| domain == rcpt_domain);
+
+        // Lock pending messages
+        let ids = match self.server.pending_messages_for(&is_match).await {
+            Ok(ids) => ids,
+            Err(err) => {
+                trc::error!(err.span_id(self.data.session_id).details("ATRN failed"));
+
+                return self
+                    .write(b"451 4.3.0 Unable to process ATRN request.\r\n")
+                    .await;
+            }
+        };
+        let mut messages = Vec::with_capacity(ids.len());
+        for id in ids {
+            let Some(message) = self.server.read_message(id, QueueName::default()).await else {
+                continue;
+            };
+            let mut locked: Vec<QueueName> = Vec::new();
+            for rcpt in &message.message.recipients {
+                if matches!(rcpt.status, Status::Scheduled | Status::TemporaryFailure(_))
+                    && !rcpt.is_held()
+                    && is_match(rcpt.domain_part())
+                    && !locked.contains(&rcpt.queue)
+                    && self.server.try_lock_event(id, rcpt.queue).await
+                {
+                    locked.push(rcpt.queue);
+                }
+            }
+
+            if !locked.is_empty() {
+                // Reload the message in case it was modified before it was locked
+                if let Some(mut message) = self.server.read_message(id, QueueName::default()).await
+                {
+                    message.span_id = self.data.session_id;
+                    messages.push((message, locked));
+                } else {
+                    for queue_name in locked {
+                        self.server.unlock_event(id, queue_name).await;
+                    }
+                }
+            }
+        }
+
+        trc::event!(
+            Smtp(SmtpEvent::Atrn),
+            SpanId = self.data.session_id,
+            Domain = requested
+                .iter()
+                .map(|d| trc::Value::String(d.as_str().into()))
+                .collect::<Vec<_>>(),
+            Total = messages.len(),
+        );
+
+        if messages.is_empty() {
+            return self.write(b"453 4.7.0 No messages waiting.\r\n").await;
+        }
+
+        // Reverse the connection and deliver the messages
+        let result = self
+            .write(b"250 2.0.0 OK now reversing the connection.\r\n")
+            .await;
+        let server = self.server.clone();
+        let session_id = self.data.session_id;
+        let conn_strategy = server.get_connection_or_default("default", session_id);
+        let params = SessionParams {
+            server: &server,
+            hostname: &self.data.helo_domain,
+            credentials: None,
+            capabilities: None,
+            is_smtp: true,
+            local_hostname: &self.hostname,
+            conn_strategy,
+            session_id,
+            pool_key: None,
+        };
+        let mut smtp_client = SmtpClient {
+            stream: &mut self.stream,
+            timeout: conn_strategy.timeout_greeting,
+            session_id,
+        };
+        let mut capabilities = None;
+        if result.is_ok() {
+            let time = Instant::now();
+            match smtp_client.read_greeting(params.hostname).await {
+                Ok(_) => match smtp_client.say_helo(&params).await {
+                    Ok(response) => {
+                        trc::event!(
+                            Delivery(DeliveryEvent::Ehlo),
+                            SpanId = session_id,
+                            Hostname = params.hostname.to_string(),
+                            Details = response.capabilities(),
+                            Elapsed = time.elapsed(),
+                        );
+
+                        capabilities = Some(response);
+                    }
+                    Err(status) => {
+                        trc::event!(
+                            Delivery(DeliveryEvent::EhloRejected),
+                            SpanId = session_id,
+                            Hostname = params.hostname.to_string(),
+                            CausedBy = from_error_status(&status),
+                            Elapsed = time.elapsed(),
+                        );
+                    }
+                },
+                Err(status) => {
+                    trc::event!(
+                        Delivery(DeliveryEvent::GreetingFailed),
+                        SpanId = session_id,
+                        Hostname = params.hostname.to_string(),
+                        Details = from_error_status(&status),
+                    );
+                }
+            }
+        }
+
+        for (mut message, locked) in messages {
+            let queue_id = message.queue_id;
+            if let Some(response) = &capabilities {
+                let rcpt_idxs = message
+                    .message
+                    .recipients
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, rcpt)| {
+                        matches!(rcpt.status, Status::Scheduled | Status::TemporaryFailure(_))
+                            && !rcpt.is_held()
+                            && is_match(rcpt.domain_part())
+                            && locked.contains(&rcpt.queue)
+                    })
+                    .map(|(rcpt_idx, _)| rcpt_idx)
+                    .collect::<Vec<_>>();
+                for rcpt_idx in &rcpt_idxs {
+                    message.message.recipients[*rcpt_idx].flags &= !RCPT_PARKED;
+                }
+
+                if !rcpt_idxs.is_empty() {
+                    let mut delivery_results = Vec::with_capacity(1);
+                    if !message
+                        .deliver_transaction(
+                            &mut smtp_client,
+                            response,
+                            rcpt_idxs,
+                            &mut delivery_results,
+                            &params,
+                        )
+                        .await
+                        && !smtp_client
+                            .cmd(b"RSET\r\n")
+                            .await
+                            .is_ok_and(|r| r.is_positive_completion())
+                    {
+                        // The connection is no longer usable
+                        capabilities = None;
+                    }
+
+                    message
+                        .apply_delivery_results(delivery_results, &server)
+                        .await;
+                    server.send_dsn(&mut message).await;
+                    if message.message.next_event(None).is_some() || message.is_held() {
+                        message.save_changes(&server, None).await;
+                    } else {
+                        message.remove(&server, None).await;
+                    }
+                }
+            }
+
+            for queue_name in locked {
+                server.unlock_event(queue_id, queue_name).await;
+            }
+        }
+        let _ = server.inner.ipc.queue_tx.send(QueueEvent::Refresh).await;
+
+        // The remote host is now the server, end the session
+        if capabilities.is_some() {
+            smtp_client.quit().await;
+        }
+
+        Err(())
+    }
+}
@@ -0,0 +1,229 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use super::DeliveryResult;
+use crate::queue::{Error, ErrorDetails, HostResponse, Status};
+use common::{
+    Server,
+    config::smtp::queue::AdaptiveThrottle,
+    outbound::{DomainThrottle, ThrottledDomains},
+    psl,
+};
+use serde::Serialize;
+use smtp_proto::Severity;
+use std::time::Instant;
+use store::write::now;
+use trc::{Collector, DeliveryEvent, MetricType};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DomainThrottleStatus {
+    pub domain: String,
+    pub concurrency: u64,
+    pub max_concurrency: u64,
+    pub rate: u64,
+    pub max_rate: u64,
+    pub period: u64,
+    pub in_flight: u64,
+    pub deferrals: u64,
+    pub deliveries: u64,
+    pub throttled_since: u64,
+    pub last_deferral: u64,
+}
+
+pub struct ThrottlePermit {
+    domains: ThrottledDomains,
+    domain: String,
+    config: AdaptiveThrottle,
+    acquired: Instant,
+    is_tracked: bool,
+    is_deferred: bool,
+    is_delivered: bool,
+    span_id: u64,
+}
+
+impl ThrottlePermit {
+    pub fn acquire(
+        server: &Server,
+        config: &AdaptiveThrottle,
+        mx: &str,
+        span_id: u64,
+    ) -> Result<ThrottlePermit, u64> {
+        let mut permit = ThrottlePermit {
+            domains: server.inner.data.smtp_throttled_domains.clone(),
+            domain: throttle_domain(mx),
+            config: config.clone(),
+            acquired: Instant::now(),
+            is_tracked: false,
+            is_deferred: false,
+            is_delivered: false,
+            span_id,
+        };
+
+        let mut domains = server.inner.data.smtp_throttled_domains.lock();
+        if let Some(throttle) = domains.get_mut(&permit.domain) {
+            throttle.recover(&permit.domain, config, span_id);
+
+            let retry_at = if throttle.in_flight >= throttle.concurrency {
+                // Retry once a message slot is expected to be available
+                Some(now() + (config.rate.period.as_secs() / throttle.rate).max(1))
+            } else {
+                if throttle.window_start.elapsed() >= config.rate.period {
+                    throttle.window_start = Instant::now();
+                    throttle.window_count = 0;
+                }
+                (throttle.window_count >= throttle.rate).then(|| {
+                    now()
+                        + config
+                            .rate
+                            .period
+                            .saturating_sub(throttle.window_start.elapsed())
+                            .as_secs()
+                            .max(1)
+                })
+            };
+
+            if let Some(retry_at) = retry_at {
+                trc::event!(
+                    Delivery(DeliveryEvent::ThrottleLimitExceeded),
+                    SpanId = span_id,
+                    Domain = permit.domain.clone(),
+                    Limit = vec![
+                        trc::Value::from(throttle.concurrency),
+                        trc::Value::from(throttle.rate),
+                    ],
+                );
+
+                return Err(retry_at);
+            }
+
+            throttle.in_flight += 1;
+            throttle.window_count += 1;
+            permit.is_tracked = true;
+        }
+
+        Ok(permit)
+    }
+
+    pub fn observe(&mut self, status: &Status<HostResponse<Box<str>>, ErrorDetails>) {
+        match status {
+            Status::Completed(_) => {
+                self.is_delivered = true;
+            }
+            Status::TemporaryFailure(ErrorDetails {
+                details: Error::UnexpectedResponse(response),
+                ..
+            }) if response.response.severity() == Severity::TransientNegativeCompletion => {
+                self.is_deferred = true;
+            }
+            _ => {}
+        }
+    }
+
+    pub(super) fn observe_results(&mut self, results: &[DeliveryResult]) {
+        for result in results {
+            match result {
+                DeliveryResult::Domain { status, .. } | DeliveryResult::Account { status, .. } => {
+                    self.observe(status);
+                }
+                DeliveryResult::RateLimited { .. } | DeliveryResult::Held { .. } => {}
+            }
+        }
+    }
+}
+
+impl Drop for ThrottlePermit {
+    fn drop(&mut self) {
+        let mut domains = self.domains.lock();
+        let num_domains = domains.len();
+
+        if self.is_deferred {
+            let throttle = domains
+                .entry(self.domain.clone())
+                .or_insert_with(|| DomainThrottle::new(&self.config, self.acquired));
+            throttle.deferrals += 1;
+            throttle.last_deferral = Instant::now();
+            throttle.last_deferral_at = now();
+
+            // Deferrals for attempts started before the last adjustment
+            // were caused by the previous limits and are not counted again.
+            if self.acquired >= throttle.last_change {
+                let concurrency = ((throttle.concurrency as f64 * self.config.decrease_factor)
+                    as u64)
+                    .max(self.config.min_concurrency);
+                let rate = ((throttle.rate as f64 * self.config.decrease_factor) as u64)
+                    .max(self.config.min_rate);
+
+                if concurrency != throttle.concurrency || rate != throttle.rate {
+                    throttle.concurrency = concurrency;
+                    throttle.rate = rate;
+                    throttle.last_change = Instant::now();
+
+                    trc::event!(
+                        Delivery(DeliveryEvent::ThrottleDecreased),
+                        SpanId = self.span_id,
+                        Domain = self.domain.clone(),
+                        Limit = vec![trc::Value::from(concurrency), trc::Value::from(rate)],
+                    );
+                }
+            }
+        }
+
+        if let Some(throttle) = domains.get_mut(&self.domain) {
+            if self.is_tracked {
+                throttle.in_flight = throttle.in_flight.saturating_sub(1);
+            }
+            if self.is_delivered {
+                throttle.deliveries += 1;
+            }
+            if throttle.recover(&self.domain, &self.config, self.span_id) {
+                domains.remove(&self.domain);
+            }
+        }
+
+        if domains.len() != num_domains {
+            Collector::update_gauge(MetricType::DeliveryThrottledDomains, domains.len() as u64);
+        }
+    }
+}
+
+pub(super) fn throttle_domain(mx: &str) -> String {
+    psl::domain_str(mx).unwrap_or(mx).to_lowercase()
+}
+
+pub fn throttled_domains(server: &Server, config: &AdaptiveThrottle) -> Vec<DomainThrottleStatus> {
+    let mut domains = server
+        .inner
+        .data
+        .smtp_throttled_domains
+        .lock()
+        .iter()
+        .map(|(domain, throttle)| DomainThrottleStatus {
+            domain: domain.clone(),
+            concurrency: throttle.concurrency,
+            max_concurrency: config.concurrency,
+            rate: throttle.rate,
+            max_rate: config.rate.requests,
+            period: config.rate.period.as_secs(),
+            in_flight: throttle.in_flight,
+            deferrals: throttle.deferrals,
+            deliveries: throttle.deliveries,
+            throttled_since: throttle.throttled_since,
+            last_deferral: throttle.last_deferral_at,
+        })
+        .collect::<Vec<_>>();
+    domains.sort_unstable_by(|a, b| a.domain.cmp(&b.domain));
+    domains
+}
+
+pub fn reset_throttled_domain(server: &Server, domain: &str) -> bool {
+    let mut domains = server.inner.data.smtp_throttled_domains.lock();
+    let found = domains.remove(domain).is_some();
+    if found {
+        Collector::update_gauge(MetricType::DeliveryThrottledDomains, domains.len() as u64);
+    }
+    found
+}
@@ -4,7 +4,9 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use super::{NextHop, lookup::ToNextHop, mta_sts, session::SessionParams};
+use super::{
+    NextHop, adaptive::ThrottlePermit, lookup::ToNextHop, mta_sts, session::SessionParams,
+};
 use crate::outbound::DeliveryResult;
 use crate::outbound::client::{
     SmtpClient, from_error_details, from_error_status, from_mail_send_error,
@@ -18,7 +20,8 @@ use crate::queue::dsn::SendDsn;
 use crate::queue::spool::SmtpSpool;
 use crate::queue::throttle::IsAllowed;
 use crate::queue::{
-    Error, FROM_REPORT, HostResponse, MessageWrapper, QueueEnvelope, QueuedMessage, Status,
+    Error, FROM_REPORT, HostResponse, MessageWrapper, QueueEnvelope, QueuedMessage, RCPT_PARKED,
+    RCPT_RELEASED, Status,
 };
 use crate::reporting::SmtpReporting;
 use crate::{queue::ErrorDetails, reporting::tls::TlsRptOptions};
@@ -27,6 +30,7 @@ use common::Server;
 use common::config::smtp::queue::RoutingStrategy;
 use common::config::{server::ServerProtocol, smtp::report::AggregateFrequency};
 use common::ipc::{PolicyType, QueueEvent, QueueEventStatus, TlsEvent};
+use common::outbound::PoolKey;
 use compact_str::ToCompactString;
 use mail_auth::{
     mta_sts::TlsRpt,
@@ -214,25 +218,45 @@ impl QueuedMessage {
             }
         }
 
-        // Group recipients by route
+        // Recipients released by ETRN bypass held routes once
         let queue_config = &server.core.smtp.queue;
         let now_ = now();
+        let mut released = Vec::new();
+        for (rcpt_idx, rcpt) in message.message.recipients.iter_mut().enumerate() {
+            if rcpt.flags & RCPT_RELEASED != 0
+                && matches!(
+                    &rcpt.status,
+                    Status::Scheduled | Status::TemporaryFailure(_)
+                )
+                && rcpt.retry.due <= now_
+                && rcpt.queue == message.queue_name
+            {
+                rcpt.flags &= !RCPT_RELEASED;
+                released.push(rcpt_idx);
+            }
+        }
+
+        // Group recipients by route
         let mut routes: AHashMap<(&str, &RoutingStrategy), Vec<usize>> = AHashMap::new();
         for (rcpt_idx, rcpt) in message.message.recipients.iter().enumerate() {
             if matches!(
                 &rcpt.status,
                 Status::Scheduled | Status::TemporaryFailure(_)
-            ) && rcpt.retry.due <= now_
+            ) && !rcpt.is_waiting()
+                && rcpt.retry.due <= now_
                 && rcpt.queue == message.queue_name
             {
                 let envelope = QueueEnvelope::new(&message.message, rcpt);
-                let route = server.get_route_or_default(
+                let mut route = server.get_route_or_default(
                     &server
                         .eval_if::<String, _>(&queue_config.route, &envelope, message.span_id)
                         .await
                         .unwrap_or_else(|| "default".to_string()),
                     message.span_id,
                 );
+                if matches!(route, RoutingStrategy::Hold) && released.contains(&rcpt_idx) {
+                    route = server.get_route_or_default("mx", message.span_id);
+                }
 
                 routes
                     .entry((rcpt.domain_part(), route))
@@ -244,6 +268,18 @@ impl QueuedMessage {
         let no_ip = IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0));
         let mut delivery_results: Vec<DeliveryResult> = Vec::new();
         'next_route: for ((domain, route), rcpt_idxs) in routes {
+            // Held messages wait until the remote host requests them with ETRN or ATRN
+            if matches!(route, RoutingStrategy::Hold) {
+                trc::event!(
+                    Delivery(DeliveryEvent::Held),
+                    SpanId = message.span_id,
+                    Domain = domain.to_string(),
+                );
+
+                delivery_results.push(DeliveryResult::held(rcpt_idxs));
+                continue 'next_route;
+            }
+
             trc::event!(
                 Delivery(DeliveryEvent::DomainDeliveryStart),
                 SpanId = message.span_id,
@@ -281,12 +317,20 @@ impl QueuedMessage {
                         .await;
                     continue 'next_route;
                 }
+                RoutingStrategy::Http(http_config) => {
+                    // Deliver message over HTTP
+                    message
+                        .deliver_http(http_config, rcpt_idxs, &mut delivery_results, &server)
+                        .await;
+                    continue 'next_route;
+                }
                 RoutingStrategy::Mx(mx_config) => (Vec::with_capacity(0), Some(mx_config), true),
                 RoutingStrategy::Relay(relay_config) => (
                     vec![NextHop::Relay(relay_config)],
                     None,
                     relay_config.protocol == ServerProtocol::Smtp,
                 ),
+                RoutingStrategy::Hold => unreachable!(),
             };
 
             // Prepare TLS strategy
@@ -853,6 +897,23 @@ impl QueuedMessage {
                         }
                     }
 
+                    // Adaptive throttling based on the deferrals received from this domain
+                    let mut throttle_permit = match queue_config
+                        .adaptive_throttle
+                        .as_ref()
+                        .map(|config| {
+                            ThrottlePermit::acquire(&server, config, envelope.mx, message.span_id)
+                        })
+                        .transpose()
+                    {
+                        Ok(throttle_permit) => throttle_permit,
+                        Err(retry_at) => {
+                            delivery_results
+                                .push(DeliveryResult::rate_limited(rcpt_idxs, retry_at));
+                            continue 'next_route;
+                        }
+                    };
+
                     // Obtain connection parameters
                     let conn_strategy = server.get_connection_or_default(
                         &server
@@ -868,11 +929,64 @@ impl QueuedMessage {
 
                     // Set source IP, if any
                     let ip_host = conn_strategy.source_ip(remote_ip.is_ipv4());
+                    envelope.local_ip = ip_host.map_or(no_ip, |ip_host| ip_host.ip);
+
+                    // Prepare TLS connector
+                    let is_strict_tls = tls_strategy.is_tls_required()
+                        || (message.message.flags & MAIL_REQUIRETLS) != 0
+                        || mta_sts_policy.is_some()
+                        || dane_policy.is_some();
+                    // As per RFC7671 Section 5.1, DANE-EE(3) allows name mismatch
+                    let is_pki_verified = !(tls_strategy.allow_invalid_certs
+                        || remote_host.allow_invalid_certs()
+                        || dane_policy.as_ref().is_some_and(|t| t.has_end_entities));
+                    let tls_connector = if is_pki_verified {
+                        &server.inner.data.smtp_connectors.pki_verify
+                    } else {
+                        &server.inner.data.smtp_connectors.dummy_verify
+                    };
+
+                    // Obtain session parameters
+                    let local_hostname = ip_host
+                        .and_then(|ip| ip.host.as_deref())
+                        .or(conn_strategy.ehlo_hostname.as_deref())
+                        .unwrap_or(server.core.network.server_name.as_str());
+                    let mut params = SessionParams {
+                        session_id: message.span_id,
+                        server: &server,
+                        credentials: remote_host.credentials(),
+                        is_smtp: remote_host.is_smtp(),
+                        hostname: envelope.mx,
+                        local_hostname,
+                        conn_strategy,
+                        capabilities: None,
+                        pool_key: (conn_strategy.max_messages > 1).then(|| PoolKey {
+                            hostname: envelope.mx.to_string(),
+                            remote_ip,
+                            remote_port: remote_host.port(),
+                            local_ip: envelope.local_ip,
+                            credentials: remote_host.credentials().cloned(),
+                            is_smtp: remote_host.is_smtp(),
+                            is_dane: dane_policy.is_some(),
+                            is_pki_verified,
+                        }),
+                    };
+
+                    // Reuse an idle connection to this host, if available
+                    let results_start = delivery_results.len();
+                    if message
+                        .deliver_pooled(is_strict_tls, &rcpt_idxs, &mut delivery_results, &params)
+                        .await
+                    {
+                        if let Some(throttle_permit) = &mut throttle_permit {
+                            throttle_permit.observe_results(&delivery_results[results_start..]);
+                        }
+                        continue 'next_route;
+                    }
 
                     // Connect
                     let time = Instant::now();
                     let mut smtp_client = match if let Some(ip_host) = ip_host {
-                        envelope.local_ip = ip_host.ip;
                         SmtpClient::connect_using(
                             ip_host.ip,
                             SocketAddr::new(remote_ip, remote_host.port()),
@@ -881,7 +995,6 @@ impl QueuedMessage {
                         )
                         .await
                     } else {
-                        envelope.local_ip = no_ip;
                         SmtpClient::connect(
                             SocketAddr::new(remote_ip, remote_host.port()),
                             conn_strategy.timeout_connect,
@@ -921,37 +1034,6 @@ impl QueuedMessage {
                         }
                     };
 
-                    // Obtain session parameters
-                    let local_hostname = ip_host
-                        .and_then(|ip| ip.host.as_deref())
-                        .or(conn_strategy.ehlo_hostname.as_deref())
-                        .unwrap_or(server.core.network.server_name.as_str());
-                    let mut params = SessionParams {
-                        session_id: message.span_id,
-                        server: &server,
-                        credentials: remote_host.credentials(),
-                        is_smtp: remote_host.is_smtp(),
-                        hostname: envelope.mx,
-                        local_hostname,
-                        conn_strategy,
-                        capabilities: None,
-                    };
-
-                    // Prepare TLS connector
-                    let is_strict_tls = tls_strategy.is_tls_required()
-                        || (message.message.flags & MAIL_REQUIRETLS) != 0
-                        || mta_sts_policy.is_some()
-                        || dane_policy.is_some();
-                    // As per RFC7671 Section 5.1, DANE-EE(3) allows name mismatch
-                    let tls_connector = if tls_strategy.allow_invalid_certs
-                        || remote_host.allow_invalid_certs()
-                        || dane_policy.as_ref().is_some_and(|t| t.has_end_entities)
-                    {
-                        &server.inner.data.smtp_connectors.dummy_verify
-                    } else {
-                        &server.inner.data.smtp_connectors.pki_verify
-                    };
-
                     if !remote_host.implicit_tls() {
                         // Read greeting
                         smtp_client.timeout = conn_strategy.timeout_greeting;
@@ -964,6 +1046,9 @@ impl QueuedMessage {
                                 Details = status.to_string(),
                             );
 
+                            if let Some(throttle_permit) = &mut throttle_permit {
+                                throttle_permit.observe(&status);
+                            }
                             last_status = status;
                             continue 'next_host;
                         }
@@ -993,6 +1078,9 @@ impl QueuedMessage {
                                     Elapsed = time.elapsed(),
                                 );
 
+                                if let Some(throttle_permit) = &mut throttle_permit {
+                                    throttle_permit.observe(&status);
+                                }
                                 last_status = status;
                                 continue 'next_host;
                             }
@@ -1227,6 +1315,9 @@ impl QueuedMessage {
                                 Details = from_error_status(&status),
                             );
 
+                            if let Some(throttle_permit) = &mut throttle_permit {
+                                throttle_permit.observe(&status);
+                            }
                             last_status = status;
                             continue 'next_host;
                         }
@@ -1238,6 +1329,9 @@ impl QueuedMessage {
                     }
 
                     // Continue with the next domain/route
+                    if let Some(throttle_permit) = &mut throttle_permit {
+                        throttle_permit.observe_results(&delivery_results[results_start..]);
+                    }
                     continue 'next_route;
                 }
             }
@@ -1247,34 +1341,15 @@ impl QueuedMessage {
         }
 
         // Apply status changes
-        for delivery_result in delivery_results {
-            match delivery_result {
-                DeliveryResult::Domain { status, rcpt_idxs } => {
-                    for rcpt_idx in rcpt_idxs {
-                        message
-                            .set_rcpt_status(status.clone(), rcpt_idx, &server)
-                            .await;
-                    }
-                }
-                DeliveryResult::Account { status, rcpt_idx } => {
-                    message.set_rcpt_status(status, rcpt_idx, &server).await;
-                }
-                DeliveryResult::RateLimited {
-                    rcpt_idxs,
-                    retry_at,
-                } => {
-                    for rcpt_idx in rcpt_idxs {
-                        message.set_rcpt_rate_limit(rcpt_idx, retry_at);
-                    }
-                }
-            }
-        }
+        message
+            .apply_delivery_results(delivery_results, &server)
+            .await;
 
         // Send Delivery Status Notifications
         server.send_dsn(&mut message).await;
 
         // Notify queue manager
-        if message.message.next_event(None).is_some() {
+        if message.message.next_event(None).is_some() || message.is_held() {
             trc::event!(
                 Queue(trc::QueueEvent::Rescheduled),
                 SpanId = span_id,
@@ -1320,6 +1395,39 @@ impl MessageWrapper {
 
         for rcpt in self.message.recipients.iter_mut() {
             match &rcpt.status {
+                Status::Scheduled | Status::TemporaryFailure(_) if rcpt.is_held() => {
+                    // Held recipients do not expire until released
+                    has_pending_delivery = true;
+                }
+                Status::Scheduled | Status::TemporaryFailure(_)
+                    if rcpt.is_parked()
+                        && rcpt.parked_expiration_time(self.message.created) <= now =>
+                {
+                    trc::event!(
+                        Delivery(DeliveryEvent::Failed),
+                        SpanId = self.span_id,
+                        QueueId = self.queue_id,
+                        QueueName = self.queue_name.as_str().to_string(),
+                        To = rcpt.address().to_string(),
+                        Reason = "Message expired while waiting for a relay request.",
+                        Details = trc::Value::Timestamp(now),
+                        Expires = trc::Value::Timestamp(
+                            rcpt.parked_expiration_time(self.message.created)
+                        ),
+                    );
+
+                    rcpt.flags &= !RCPT_PARKED;
+                    rcpt.status = Status::PermanentFailure(ErrorDetails {
+                        entity: rcpt.domain_part().into(),
+                        details: Error::Io(
+                            "Message expired while waiting for a relay request.".into(),
+                        ),
+                    });
+                }
+                Status::Scheduled | Status::TemporaryFailure(_) if rcpt.is_parked() => {
+                    // Parked recipients wait for ETRN or ATRN until they expire
+                    has_pending_delivery = true;
+                }
                 Status::TemporaryFailure(err) if rcpt.is_expired(self.message.created, now) => {
                     trc::event!(
                         Delivery(DeliveryEvent::Failed),
@@ -1377,6 +1485,40 @@ impl MessageWrapper {
         }
     }
 
+    pub(crate) async fn apply_delivery_results(
+        &mut self,
+        delivery_results: Vec<DeliveryResult>,
+        server: &Server,
+    ) {
+        for delivery_result in delivery_results {
+            match delivery_result {
+                DeliveryResult::Domain { status, rcpt_idxs } => {
+                    for rcpt_idx in rcpt_idxs {
+                        self.set_rcpt_status(status.clone(), rcpt_idx, server).await;
+                    }
+                }
+                DeliveryResult::Account { status, rcpt_idx } => {
+                    self.set_rcpt_status(status, rcpt_idx, server).await;
+                }
+                DeliveryResult::RateLimited {
+                    rcpt_idxs,
+                    retry_at,
+                } => {
+                    for rcpt_idx in rcpt_idxs {
+                        self.set_rcpt_rate_limit(rcpt_idx, retry_at);
+                    }
+                }
+                DeliveryResult::Held { rcpt_idxs } => {
+                    // Parked recipients do not count as a delivery attempt and
+                    // wait to be requested with ETRN or ATRN until they expire
+                    for rcpt_idx in rcpt_idxs {
+                        self.message.recipients[rcpt_idx].flags |= RCPT_PARKED;
+                    }
+                }
+            }
+        }
+    }
+
     pub async fn set_rcpt_status(
         &mut self,
         status: Status<HostResponse<Box<str>>, ErrorDetails>,
@@ -0,0 +1,447 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use super::DeliveryResult;
+use crate::queue::{Error, ErrorDetails, HostResponse, MessageWrapper, Status, UnexpectedResponse};
+use base64::{
+    Engine,
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+};
+use common::{
+    KV_HTTP_ROUTE_BLOBS, Server,
+    auth::oauth::GrantType,
+    config::smtp::queue::{HttpRouteConfig, HttpRouteFormat},
+};
+use mail_parser::{Address, MessageParser, MessagePart, MimeHeaders};
+use reqwest::header::{CONTENT_TYPE, HeaderMap, HeaderValue};
+use ring::hmac;
+use serde::Serialize;
+use smtp_proto::Response;
+use std::time::Instant;
+use store::{dispatch::lookup::KeyValue, write::now};
+use trc::{AddContext, DeliveryEvent};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HttpPayload<'x> {
+    queue_id: u64,
+    envelope: HttpEnvelope<'x>,
+    message: HttpMessage<'x>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HttpEnvelope<'x> {
+    from: &'x str,
+    to: Vec<&'x str>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HttpMessage<'x> {
+    size: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message_id: Option<&'x str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    subject: Option<&'x str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date: Option<String>,
+    from: Vec<HttpAddress<'x>>,
+    to: Vec<HttpAddress<'x>>,
+    cc: Vec<HttpAddress<'x>>,
+    headers: Vec<HttpHeader<'x>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text_body: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    html_body: Option<String>,
+    attachments: Vec<HttpAttachment<'x>>,
+}
+
+#[derive(Serialize)]
+struct HttpAddress<'x> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<&'x str>,
+    email: &'x str,
+}
+
+#[derive(Serialize)]
+struct HttpHeader<'x> {
+    name: &'x str,
+    value: &'x str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HttpAttachment<'x> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<&'x str>,
+    #[serde(rename = "type")]
+    content_type: String,
+    size: usize,
+    url: String,
+}
+
+impl MessageWrapper {
+    pub(super) async fn deliver_http(
+        &self,
+        config: &HttpRouteConfig,
+        rcpt_idxs: Vec<usize>,
+        statuses: &mut Vec<DeliveryResult>,
+        server: &Server,
+    ) {
+        let time = Instant::now();
+        let hostname = reqwest::Url::parse(&config.url)
+            .ok()
+            .and_then(|url| url.host_str().map(|host| host.to_string()))
+            .unwrap_or_else(|| config.url.clone());
+        let recipients = rcpt_idxs
+            .iter()
+            .map(|&rcpt_idx| self.message.recipients[rcpt_idx].address())
+            .collect::<Vec<_>>();
+
+        // Fetch message
+        let raw_message = match server
+            .blob_store()
+            .get_blob(self.message.blob_hash.as_slice(), 0..usize::MAX)
+            .await
+        {
+            Ok(Some(raw_message)) => raw_message,
+            Ok(None) => {
+                trc::event!(
+                    Queue(trc::QueueEvent::BlobNotFound),
+                    SpanId = self.span_id,
+                    BlobId = self.message.blob_hash.to_hex(),
+                    CausedBy = trc::location!()
+                );
+                statuses.push(DeliveryResult::domain(
+                    Status::TemporaryFailure(ErrorDetails {
+                        entity: "localhost".into(),
+                        details: Error::Io("Queue system error.".into()),
+                    }),
+                    rcpt_idxs,
+                ));
+                return;
+            }
+            Err(err) => {
+                trc::error!(
+                    err.span_id(self.span_id)
+                        .details("Failed to fetch blobId")
+                        .caused_by(trc::location!())
+                );
+                statuses.push(DeliveryResult::domain(
+                    Status::TemporaryFailure(ErrorDetails {
+                        entity: "localhost".into(),
+                        details: Error::Io("Queue system error.".into()),
+                    }),
+                    rcpt_idxs,
+                ));
+                return;
+            }
+        };
+
+        // Build request body
+        let mut headers = HeaderMap::new();
+        let body = match config.format {
+            HttpRouteFormat::Raw => {
+                headers.insert(CONTENT_TYPE, HeaderValue::from_static("message/rfc822"));
+                for (name, value) in [
+                    ("X-Envelope-From", self.message.return_path.to_string()),
+                    ("X-Envelope-To", recipients.join(", ")),
+                    ("X-Queue-Id", self.queue_id.to_string()),
+                ] {
+                    if let Ok(value) = HeaderValue::from_str(&value) {
+                        headers.insert(name, value);
+                    }
+                }
+                raw_message
+            }
+            HttpRouteFormat::Json => {
+                headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+                match self
+                    .build_http_payload(config, &raw_message, &recipients, server)
+                    .await
+                {
+                    Ok(body) => body,
+                    Err(err) => {
+                        trc::error!(
+                            err.span_id(self.span_id)
+                                .details("Failed to build HTTP payload")
+                                .caused_by(trc::location!())
+                        );
+                        statuses.push(DeliveryResult::domain(
+                            Status::TemporaryFailure(ErrorDetails {
+                                entity: "localhost".into(),
+                                details: Error::Io("Queue system error.".into()),
+                            }),
+                            rcpt_idxs,
+                        ));
+                        return;
+                    }
+                }
+            }
+        };
+
+        // Add HMAC-SHA256 signature over "<timestamp>.<body>" to prevent replays
+        if let Some(signature_key) = &config.signature_key {
+            let timestamp = now().to_string();
+            let mut ctx = hmac::Context::with_key(&hmac::Key::new(
+                hmac::HMAC_SHA256,
+                signature_key.as_bytes(),
+            ));
+            ctx.update(timestamp.as_bytes());
+            ctx.update(b".");
+            ctx.update(&body);
+            if let (Ok(signature), Ok(timestamp)) = (
+                HeaderValue::from_str(&STANDARD.encode(ctx.sign().as_ref())),
+                HeaderValue::from_str(&timestamp),
+            ) {
+                headers.insert("X-Signature", signature);
+                headers.insert("X-Signature-Timestamp", timestamp);
+            }
+        }
+
+        // Send request
+        let status = match config
+            .client
+            .post(&config.url)
+            .headers(headers)
+            .body(body)
+            .send()
+            .await
+        {
+            Ok(response) => {
+                let code = response.status().as_u16();
+                let message = format!(
+                    "HTTP {code} {}",
+                    response.status().canonical_reason().unwrap_or("Unknown")
+                );
+
+                if response.status().is_success() {
+                    trc::event!(
+                        Delivery(DeliveryEvent::Delivered),
+                        SpanId = self.span_id,
+                        Hostname = hostname.clone(),
+                        To = recipients
+                            .iter()
+                            .map(|rcpt| trc::Value::from(rcpt.to_string()))
+                            .collect::<Vec<_>>(),
+                        Code = code,
+                        Details = message.clone(),
+                        Elapsed = time.elapsed(),
+                    );
+                } else {
+                    trc::event!(
+                        Delivery(DeliveryEvent::MessageRejected),
+                        SpanId = self.span_id,
+                        Hostname = hostname.clone(),
+                        Code = code,
+                        Details = message.clone(),
+                        Elapsed = time.elapsed(),
+                    );
+                }
+
+                Status::from_http_response(&hostname, code, message)
+            }
+            Err(err) => {
+                trc::event!(
+                    Delivery(DeliveryEvent::ConnectError),
+                    SpanId = self.span_id,
+                    Hostname = hostname.clone(),
+                    Reason = err.to_string(),
+                    Elapsed = time.elapsed(),
+                );
+
+                Status::TemporaryFailure(ErrorDetails {
+                    entity: hostname.into_boxed_str(),
+                    details: Error::ConnectionError(err.to_string().into_boxed_str()),
+                })
+            }
+        };
+
+        statuses.push(DeliveryResult::domain(status, rcpt_idxs));
+    }
+
+    async fn build_http_payload(
+        &self,
+        config: &HttpRouteConfig,
+        raw_message: &[u8],
+        recipients: &[&str],
+        server: &Server,
+    ) -> trc::Result<Vec<u8>> {
+        let message = MessageParser::new().parse(raw_message).unwrap_or_default();
+
+        // Attachments are stored as temporary blobs and linked using signed URLs.
+        // The links are created on the first attempt and reused by retries while
+        // they still have at least half of their lifetime left.
+        let parts = message.attachments().collect::<Vec<_>>();
+        let urls = if !parts.is_empty() {
+            self.http_attachment_urls(config, &parts, server).await?
+        } else {
+            Vec::new()
+        };
+        let attachments = parts
+            .into_iter()
+            .zip(urls)
+            .map(|(part, url)| HttpAttachment {
+                name: part.attachment_name(),
+                content_type: part
+                    .content_type()
+                    .map(|ct| {
+                        if let Some(subtype) = ct.subtype() {
+                            format!("{}/{}", ct.ctype(), subtype)
+                        } else {
+                            ct.ctype().to_string()
+                        }
+                    })
+                    .unwrap_or_else(|| "application/octet-stream".to_string()),
+                size: part.contents().len(),
+                url,
+            })
+            .collect();
+
+        let payload = HttpPayload {
+            queue_id: self.queue_id,
+            envelope: HttpEnvelope {
+                from: self.message.return_path.as_ref(),
+                to: recipients.to_vec(),
+            },
+            message: HttpMessage {
+                size: raw_message.len(),
+                message_id: message.message_id(),
+                subject: message.subject(),
+                date: message.date().map(|date| date.to_rfc3339()),
+                from: http_addresses(message.from()),
+                to: http_addresses(message.to()),
+                cc: http_addresses(message.cc()),
+                headers: message
+                    .headers_raw()
+                    .map(|(name, value)| HttpHeader {
+                        name,
+                        value: value.trim(),
+                    })
+                    .collect(),
+                text_body: message.body_text(0).map(|text| text.into_owned()),
+                html_body: message.body_html(0).map(|html| html.into_owned()),
+                attachments,
+            },
+        };
+
+        serde_json::to_vec(&payload).map_err(|err| {
+            trc::StoreEvent::UnexpectedError
+                .reason(err)
+                .caused_by(trc::location!())
+        })
+    }
+
+    async fn http_attachment_urls(
+        &self,
+        config: &HttpRouteConfig,
+        parts: &[&MessagePart<'_>],
+        server: &Server,
+    ) -> trc::Result<Vec<String>> {
+        let mut key = Vec::with_capacity(config.id.len() + 9);
+        key.push(KV_HTTP_ROUTE_BLOBS);
+        key.extend_from_slice(&self.queue_id.to_be_bytes());
+        key.extend_from_slice(config.id.as_bytes());
+
+        if let Some(urls) = server
+            .in_memory_store()
+            .key_get::<String>(key.clone())
+            .await
+            .caused_by(trc::location!())?
+        {
+            let urls = urls.lines().map(String::from).collect::<Vec<_>>();
+            if urls.len() == parts.len() {
+                return Ok(urls);
+            }
+        }
+
+        let expiry = config.blob_expiry.as_secs();
+        let mut urls = Vec::with_capacity(parts.len());
+        for part in parts {
+            let (hash, _) = server
+                .put_temporary_blob(u32::MAX, part.contents(), expiry)
+                .await
+                .caused_by(trc::location!())?;
+            let token = server
+                .encode_access_token(
+                    GrantType::Blob,
+                    u32::MAX,
+                    &URL_SAFE_NO_PAD.encode(hash.as_slice()),
+                    expiry,
+                )
+                .await
+                .caused_by(trc::location!())?;
+            urls.push(format!(
+                "{}?i={}",
+                config.blob_url,
+                form_urlencoded::byte_serialize(token.as_bytes()).collect::<String>()
+            ));
+        }
+
+        server
+            .in_memory_store()
+            .key_set(KeyValue::new(key, urls.join("\n").into_bytes()).expires(expiry / 2))
+            .await
+            .caused_by(trc::location!())?;
+
+        Ok(urls)
+    }
+}
+
+fn http_addresses<'x>(address: Option<&'x Address<'x>>) -> Vec<HttpAddress<'x>> {
+    address
+        .map(|address| {
+            address
+                .iter()
+                .filter_map(|addr| {
+                    addr.address().map(|email| HttpAddress {
+                        name: addr.name(),
+                        email,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+impl Status<HostResponse<Box<str>>, ErrorDetails> {
+    pub fn from_http_response(hostname: &str, code: u16, message: String) -> Self {
+        match code {
+            200..=299 => Status::Completed(HostResponse {
+                hostname: hostname.into(),
+                response: Response {
+                    code: 250,
+                    esc: [2, 0, 0],
+                    message: message.into_boxed_str(),
+                },
+            }),
+            // Timeouts, throttling and server errors are retried
+            408 | 425 | 429 | 500..=599 => Status::TemporaryFailure(ErrorDetails {
+                entity: hostname.into(),
+                details: Error::UnexpectedResponse(UnexpectedResponse {
+                    command: "POST".into(),
+                    response: Response {
+                        code: 451,
+                        esc: [4, 3, 0],
+                        message: message.into_boxed_str(),
+                    },
+                }),
+            }),
+            _ => Status::PermanentFailure(ErrorDetails {
+                entity: hostname.into(),
+                details: Error::UnexpectedResponse(UnexpectedResponse {
+                    command: "POST".into(),
+                    response: Response {
+                        code: 550,
+                        esc: [5, 0, 0],
+                        message: message.into_boxed_str(),
+                    },
+                }),
+            }),
+        }
+    }
+}
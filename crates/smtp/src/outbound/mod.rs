@@ -17,12 +17,15 @@ use mail_send::Credentials;
 use smtp_proto::{Response, Severity};
 use std::borrow::Cow;
 
+pub mod adaptive;
 pub mod client;
 pub mod dane;
 pub mod delivery;
+pub mod http;
 pub mod local;
 pub mod lookup;
 pub mod mta_sts;
+pub mod pool;
 pub mod session;
 
 pub(super) enum DeliveryResult {
@@ -38,6 +41,9 @@ pub(super) enum DeliveryResult {
         rcpt_idxs: Vec<usize>,
         retry_at: u64,
     },
+    Held {
+        rcpt_idxs: Vec<usize>,
+    },
 }
 
 impl Status<HostResponse<Box<str>>, ErrorDetails> {
@@ -344,6 +350,10 @@ impl DeliveryResult {
         }
     }
 
+    pub fn held(rcpt_idxs: Vec<usize>) -> Self {
+        DeliveryResult::Held { rcpt_idxs }
+    }
+
     pub fn account(status: Status<HostResponse<Box<str>>, ErrorDetails>, rcpt_idx: usize) -> Self {
         DeliveryResult::Account { status, rcpt_idx }
     }
@@ -0,0 +1,278 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use super::{
+    DeliveryResult, adaptive::throttle_domain, client::SmtpClient, session::SessionParams,
+};
+use crate::queue::MessageWrapper;
+use common::{
+    Server,
+    outbound::{PoolKey, PooledConnection, PooledStream, ThrottleSlot},
+};
+use smtp_proto::EhloResponse;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio_rustls::client::TlsStream;
+use trc::DeliveryEvent;
+
+pub enum PooledClient {
+    Plain(SmtpClient<TcpStream>),
+    Tls(Box<SmtpClient<TlsStream<TcpStream>>>),
+}
+
+pub trait IntoPooledClient {
+    fn into_pooled(self) -> PooledClient;
+}
+
+impl IntoPooledClient for SmtpClient<TcpStream> {
+    fn into_pooled(self) -> PooledClient {
+        PooledClient::Plain(self)
+    }
+}
+
+impl IntoPooledClient for SmtpClient<TlsStream<TcpStream>> {
+    fn into_pooled(self) -> PooledClient {
+        PooledClient::Tls(Box::new(self))
+    }
+}
+
+impl PooledClient {
+    pub async fn quit(self) {
+        match self {
+            PooledClient::Plain(client) => client.quit().await,
+            PooledClient::Tls(client) => (*client).quit().await,
+        }
+    }
+
+    async fn reset(&mut self, timeout: Duration) -> bool {
+        match self {
+            PooledClient::Plain(client) => {
+                client.timeout = timeout;
+                client
+                    .cmd(b"RSET\r\n")
+                    .await
+                    .is_ok_and(|r| r.is_positive_completion())
+            }
+            PooledClient::Tls(client) => {
+                client.timeout = timeout;
+                client
+                    .cmd(b"RSET\r\n")
+                    .await
+                    .is_ok_and(|r| r.is_positive_completion())
+            }
+        }
+    }
+
+    fn from_stream(stream: PooledStream, timeout: Duration, session_id: u64) -> Self {
+        match stream {
+            PooledStream::Plain(stream) => PooledClient::Plain(SmtpClient {
+                stream,
+                timeout,
+                session_id,
+            }),
+            PooledStream::Tls(stream) => PooledClient::Tls(Box::new(SmtpClient {
+                stream: *stream,
+                timeout,
+                session_id,
+            })),
+        }
+    }
+
+    fn into_stream(self) -> (PooledStream, u64) {
+        match self {
+            PooledClient::Plain(client) => (PooledStream::Plain(client.stream), client.session_id),
+            PooledClient::Tls(client) => (
+                PooledStream::Tls(Box::new(client.stream)),
+                client.session_id,
+            ),
+        }
+    }
+}
+
+impl MessageWrapper {
+    pub(super) async fn deliver_pooled(
+        &self,
+        require_tls: bool,
+        rcpt_idxs: &[usize],
+        statuses: &mut Vec<DeliveryResult>,
+        params: &SessionParams<'_>,
+    ) -> bool {
+        let Some(pool_key) = &params.pool_key else {
+            return false;
+        };
+        let max_messages = params.conn_strategy.max_messages;
+        let timeout_idle = params.conn_strategy.timeout_idle;
+
+        while let Some(conn) = checkout(params.server, pool_key, require_tls, timeout_idle) {
+            let PooledConnection {
+                stream,
+                capabilities,
+                mut messages,
+                throttle,
+                ..
+            } = conn;
+
+            // The permit acquired for this delivery now accounts for the connection
+            drop(throttle);
+            let mut client = PooledClient::from_stream(
+                stream,
+                params.conn_strategy.timeout_greeting,
+                params.session_id,
+            );
+
+            // Reset the session, which also verifies that the connection is still alive
+            if !client.reset(params.conn_strategy.timeout_greeting).await {
+                client.quit().await;
+                continue;
+            }
+
+            trc::event!(
+                Delivery(DeliveryEvent::ConnectionReused),
+                SpanId = params.session_id,
+                Hostname = params.hostname.to_string(),
+                RemoteIp = pool_key.remote_ip,
+                Total = messages,
+            );
+
+            let success = match &mut client {
+                PooledClient::Plain(client) => {
+                    self.deliver_transaction(
+                        client,
+                        &capabilities,
+                        rcpt_idxs.to_vec(),
+                        statuses,
+                        params,
+                    )
+                    .await
+                }
+                PooledClient::Tls(client) => {
+                    self.deliver_transaction(
+                        client.as_mut(),
+                        &capabilities,
+                        rcpt_idxs.to_vec(),
+                        statuses,
+                        params,
+                    )
+                    .await
+                }
+            };
+
+            messages += 1;
+            if success && messages < max_messages {
+                checkin(
+                    params.server,
+                    pool_key.clone(),
+                    client,
+                    capabilities,
+                    messages,
+                    timeout_idle,
+                )
+                .await;
+            } else {
+                client.quit().await;
+            }
+
+            return true;
+        }
+
+        false
+    }
+}
+
+pub(super) async fn checkin(
+    server: &Server,
+    pool_key: PoolKey,
+    client: PooledClient,
+    capabilities: EhloResponse<String>,
+    messages: usize,
+    timeout_idle: Duration,
+) {
+    // Idle connections count towards the concurrency of throttled domains
+    let Some(throttle) = ThrottleSlot::acquire(
+        &server.inner.data.smtp_throttled_domains,
+        throttle_domain(&pool_key.hostname),
+    ) else {
+        client.quit().await;
+        return;
+    };
+
+    let (stream, session_id) = client.into_stream();
+    server
+        .inner
+        .data
+        .smtp_connection_pool
+        .lock()
+        .entry(pool_key.clone())
+        .or_default()
+        .push(PooledConnection {
+            stream,
+            capabilities,
+            session_id,
+            messages,
+            idle_since: Instant::now(),
+            throttle,
+        });
+
+    // Close the connection once it has been idle for too long
+    let server = server.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(timeout_idle).await;
+        let expired = {
+            let mut pool = server.inner.data.smtp_connection_pool.lock();
+            let mut expired = Vec::new();
+            if let Some(conns) = pool.get_mut(&pool_key) {
+                let mut idx = 0;
+                while idx < conns.len() {
+                    if conns[idx].idle_since.elapsed() >= timeout_idle {
+                        expired.push(conns.swap_remove(idx));
+                    } else {
+                        idx += 1;
+                    }
+                }
+                if conns.is_empty() {
+                    pool.remove(&pool_key);
+                }
+            }
+            expired
+        };
+
+        for conn in expired {
+            quit_pooled(conn).await;
+        }
+    });
+}
+
+fn checkout(
+    server: &Server,
+    pool_key: &PoolKey,
+    require_tls: bool,
+    timeout_idle: Duration,
+) -> Option<PooledConnection> {
+    let mut pool = server.inner.data.smtp_connection_pool.lock();
+    let conns = pool.get_mut(pool_key)?;
+    let mut result = None;
+    let mut idx = conns.len();
+    while idx > 0 {
+        idx -= 1;
+        let conn = &conns[idx];
+        if conn.idle_since.elapsed() >= timeout_idle {
+            tokio::spawn(quit_pooled(conns.swap_remove(idx)));
+        } else if result.is_none() && (!require_tls || matches!(conn.stream, PooledStream::Tls(_)))
+        {
+            result = Some(conns.swap_remove(idx));
+        }
+    }
+    if conns.is_empty() {
+        pool.remove(pool_key);
+    }
+    result
+}
+
+async fn quit_pooled(conn: PooledConnection) {
+    PooledClient::from_stream(conn.stream, Duration::from_secs(10), conn.session_id)
+        .quit()
+        .await;
+}
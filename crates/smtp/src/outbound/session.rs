@@ -5,12 +5,14 @@
  */
 
 use super::client::SmtpClient;
+use super::pool::{IntoPooledClient, checkin};
 use crate::outbound::DeliveryResult;
 use crate::outbound::client::{BoxResponse, from_error_status, from_mail_send_error};
 use crate::queue::{Error, MessageWrapper, Recipient, Status};
 use crate::queue::{ErrorDetails, HostResponse, UnexpectedResponse};
 use common::Server;
 use common::config::smtp::queue::ConnectionStrategy;
+use common::outbound::PoolKey;
 use mail_send::Credentials;
 use smtp_proto::{
     EXT_CHUNKING, EXT_DSN, EXT_REQUIRE_TLS, EXT_SIZE, EXT_SMTP_UTF8, EhloResponse, MAIL_REQUIRETLS,
@@ -30,6 +32,7 @@ pub struct SessionParams<'x> {
     pub local_hostname: &'x str,
     pub conn_strategy: &'x ConnectionStrategy,
     pub session_id: u64,
+    pub pool_key: Option<PoolKey>,
 }
 
 impl MessageWrapper {
@@ -39,7 +42,9 @@ impl MessageWrapper {
         rcpt_idxs: Vec<usize>,
         statuses: &mut Vec<DeliveryResult>,
         mut params: SessionParams<'_>,
-    ) {
+    ) where
+        SmtpClient<T>: IntoPooledClient,
+    {
         // Obtain capabilities
         let time = Instant::now();
         let capabilities = if let Some(capabilities) = params.capabilities.take() {
@@ -117,10 +122,45 @@ impl MessageWrapper {
             };*/
         }
 
+        // Deliver message
+        let success = self
+            .deliver_transaction(
+                &mut smtp_client,
+                &capabilities,
+                rcpt_idxs,
+                statuses,
+                &params,
+            )
+            .await;
+
+        // Keep the connection open for other queued messages
+        if success && let Some(pool_key) = params.pool_key {
+            checkin(
+                params.server,
+                pool_key,
+                smtp_client.into_pooled(),
+                capabilities,
+                1,
+                params.conn_strategy.timeout_idle,
+            )
+            .await;
+        } else {
+            smtp_client.quit().await;
+        }
+    }
+
+    pub(crate) async fn deliver_transaction<T: AsyncRead + AsyncWrite + Unpin>(
+        &self,
+        smtp_client: &mut SmtpClient<T>,
+        capabilities: &EhloResponse<String>,
+        rcpt_idxs: Vec<usize>,
+        statuses: &mut Vec<DeliveryResult>,
+        params: &SessionParams<'_>,
+    ) -> bool {
         // MAIL FROM
         let time = Instant::now();
         smtp_client.timeout = params.conn_strategy.timeout_mail;
-        let cmd = self.build_mail_from(&capabilities);
+        let cmd = self.build_mail_from(capabilities);
         match smtp_client.cmd(cmd.as_bytes()).await.and_then(|r| {
             if r.is_positive_completion() {
                 Ok(r)
@@ -148,12 +188,11 @@ impl MessageWrapper {
                     Elapsed = time.elapsed(),
                 );
 
-                smtp_client.quit().await;
                 statuses.push(DeliveryResult::domain(
                     Status::from_smtp_error(params.hostname, &cmd, err),
                     rcpt_idxs,
                 ));
-                return;
+                return false;
             }
         }
 
@@ -170,7 +209,7 @@ impl MessageWrapper {
                 continue;
             }
 
-            let cmd = self.build_rcpt_to(rcpt, &capabilities);
+            let cmd = self.build_rcpt_to(rcpt, capabilities);
             match smtp_client.cmd(cmd.as_bytes()).await {
                 Ok(response) => match response.severity() {
                     Severity::PositiveCompletion => {
@@ -232,12 +271,11 @@ impl MessageWrapper {
                     );
 
                     // Something went wrong, abort.
-                    smtp_client.quit().await;
                     statuses.push(DeliveryResult::domain(
                         Status::from_smtp_error(params.hostname, "", err),
                         rcpt_idxs,
                     ));
-                    return;
+                    return false;
                 }
             }
         }
@@ -249,7 +287,7 @@ impl MessageWrapper {
                 .has_capability(EXT_CHUNKING)
                 .then(|| format!("BDAT {} LAST\r\n", self.message.size));
 
-            if let Err(status) = smtp_client.send_message(self, &bdat_cmd, &params).await {
+            if let Err(status) = smtp_client.send_message(self, &bdat_cmd, params).await {
                 trc::event!(
                     Delivery(DeliveryEvent::MessageRejected),
                     SpanId = params.session_id,
@@ -258,9 +296,8 @@ impl MessageWrapper {
                     Elapsed = time.elapsed(),
                 );
 
-                smtp_client.quit().await;
                 statuses.push(DeliveryResult::domain(status, rcpt_idxs));
-                return;
+                return false;
             }
 
             if params.is_smtp {
@@ -295,7 +332,6 @@ impl MessageWrapper {
                                 Elapsed = time.elapsed(),
                             );
 
-                            smtp_client.quit().await;
                             statuses.push(DeliveryResult::domain(
                                 Status::from_smtp_error(
                                     params.hostname,
@@ -304,7 +340,7 @@ impl MessageWrapper {
                                 ),
                                 rcpt_idxs,
                             ));
-                            return;
+                            return false;
                         }
                     }
                     Err(status) => {
@@ -316,9 +352,8 @@ impl MessageWrapper {
                             Elapsed = time.elapsed(),
                         );
 
-                        smtp_client.quit().await;
                         statuses.push(DeliveryResult::domain(status, rcpt_idxs));
-                        return;
+                        return false;
                     }
                 }
             } else {
@@ -392,15 +427,14 @@ impl MessageWrapper {
                             Elapsed = time.elapsed(),
                         );
 
-                        smtp_client.quit().await;
                         statuses.push(DeliveryResult::domain(status, rcpt_idxs));
-                        return;
+                        return false;
                     }
                 }
             }
         }
 
-        smtp_client.quit().await;
+        true
     }
 
     fn build_mail_from(&self, capabilities: &EhloResponse<String>) -> String {
@@ -11,7 +11,7 @@ use super::{
 };
 use crate::queue::{MessageWrapper, UnexpectedResponse};
 use crate::reporting::SmtpReporting;
-use common::Server;
+use common::{Server, config::smtp::queue::DsnTemplateVariable, i18n};
 use mail_builder::MessageBuilder;
 use mail_builder::headers::HeaderType;
 use mail_builder::headers::content_type::ContentType;
@@ -23,6 +23,7 @@ use smtp_proto::{
 use std::fmt::Write;
 use std::future::Future;
 use store::write::now;
+use utils::template::Variables;
 
 pub trait SendDsn: Sync + Send {
     fn send_dsn(&self, message: &mut MessageWrapper) -> impl Future<Output = ()> + Send;
@@ -133,9 +134,9 @@ impl MessageWrapper {
         let config = &server.core.smtp.queue;
         let now = now();
 
-        let mut txt_success = String::new();
-        let mut txt_delay = String::new();
-        let mut txt_failed = String::new();
+        let mut delivered = Vec::new();
+        let mut delayed = Vec::new();
+        let mut failed = Vec::new();
         let mut dsn = String::new();
 
         for rcpt in &mut self.message.recipients {
@@ -150,7 +151,7 @@ impl MessageWrapper {
                     }
                     rcpt.write_dsn(&mut dsn);
                     rcpt.status.write_dsn(&mut dsn);
-                    response.write_dsn_text(&rcpt.address, &mut txt_success);
+                    delivered.push(rcpt.dsn_text(response.dsn_text()));
                 }
                 Status::TemporaryFailure(response)
                     if rcpt.notify.due <= now && rcpt.has_flag(RCPT_NOTIFY_DELAY) =>
@@ -158,7 +159,7 @@ impl MessageWrapper {
                     rcpt.write_dsn(&mut dsn);
                     rcpt.status.write_dsn(&mut dsn);
                     rcpt.write_dsn_will_retry_until(self.message.created, &mut dsn);
-                    response.write_dsn_text(&rcpt.address, &mut txt_delay);
+                    delayed.push(rcpt.dsn_text(response.dsn_text()));
                 }
                 Status::PermanentFailure(response) => {
                     rcpt.flags |= RCPT_DSN_SENT;
@@ -167,18 +168,22 @@ impl MessageWrapper {
                     }
                     rcpt.write_dsn(&mut dsn);
                     rcpt.status.write_dsn(&mut dsn);
-                    response.write_dsn_text(&rcpt.address, &mut txt_failed);
+                    failed.push(rcpt.dsn_text(response.dsn_text()));
                 }
                 Status::Scheduled if rcpt.notify.due <= now && rcpt.has_flag(RCPT_NOTIFY_DELAY) => {
                     // This case should not happen under normal circumstances
                     rcpt.write_dsn(&mut dsn);
                     rcpt.status.write_dsn(&mut dsn);
                     rcpt.write_dsn_will_retry_until(self.message.created, &mut dsn);
-                    ErrorDetails {
-                        entity: "localhost".into(),
-                        details: Error::ConcurrencyLimited,
-                    }
-                    .write_dsn_text(&rcpt.address, &mut txt_delay);
+                    delayed.push(
+                        rcpt.dsn_text(
+                            ErrorDetails {
+                                entity: "localhost".into(),
+                                details: Error::ConcurrencyLimited,
+                            }
+                            .dsn_text(),
+                        ),
+                    );
                 }
                 _ => continue,
             }
@@ -186,69 +191,13 @@ impl MessageWrapper {
             dsn.push_str("\r\n");
         }
 
-        // Build text response
-        let txt_len = txt_success.len() + txt_delay.len() + txt_failed.len();
-        if txt_len == 0 {
+        if delivered.is_empty() && delayed.is_empty() && failed.is_empty() {
             return None;
         }
 
-        let has_success = !txt_success.is_empty();
-        let has_delay = !txt_delay.is_empty();
-        let has_failure = !txt_failed.is_empty();
-
-        let mut txt = String::with_capacity(txt_len + 128);
-        let (subject, is_mixed) = if has_success && !has_delay && !has_failure {
-            txt.push_str(
-                "Your message has been successfully delivered to the following recipients:\r\n\r\n",
-            );
-            ("Successfully delivered message", false)
-        } else if has_delay && !has_success && !has_failure {
-            txt.push_str("There was a temporary problem delivering your message to the following recipients:\r\n\r\n");
-            ("Warning: Delay in message delivery", false)
-        } else if has_failure && !has_success && !has_delay {
-            txt.push_str(
-                "Your message could not be delivered to the following recipients:\r\n\r\n",
-            );
-            ("Failed to deliver message", false)
-        } else if has_success {
-            txt.push_str("Your message has been partially delivered:\r\n\r\n");
-            ("Partially delivered message", true)
-        } else {
-            txt.push_str("Your message could not be delivered to some recipients:\r\n\r\n");
-            (
-                "Warning: Temporary and permanent failures during message delivery",
-                true,
-            )
-        };
-
-        if has_success {
-            if is_mixed {
-                txt.push_str(
-                    "    ----- Delivery to the following addresses was successful -----\r\n",
-                );
-            }
-
-            txt.push_str(&txt_success);
-            txt.push_str("\r\n");
-        }
-
-        if has_delay {
-            if is_mixed {
-                txt.push_str(
-                    "    ----- There was a temporary problem delivering to these addresses -----\r\n",
-                );
-            }
-            txt.push_str(&txt_delay);
-            txt.push_str("\r\n");
-        }
-
-        if has_failure {
-            if is_mixed {
-                txt.push_str("    ----- Delivery to the following addresses failed -----\r\n");
-            }
-            txt.push_str(&txt_failed);
-            txt.push_str("\r\n");
-        }
+        let has_success = !delivered.is_empty();
+        let has_delay = !delayed.is_empty();
+        let has_failure = !failed.is_empty();
 
         // Update next delay notification time
         if has_delay {
@@ -306,6 +255,96 @@ impl MessageWrapper {
             .await
             .unwrap_or_else(|| String::from("localhost"));
 
+        // Select template and locale
+        let template = server
+            .eval_if::<String, _>(&config.dsn.template, &self.message, self.span_id)
+            .await
+            .and_then(|id| config.dsn.templates.get(&id))
+            .unwrap_or(&config.dsn.default_template);
+        let locale = self.sender_locale(server).await;
+        let locale = i18n::locale_or_default(
+            locale
+                .as_deref()
+                .or(template.locale.as_deref())
+                .unwrap_or("en"),
+        );
+        let (subject, header, is_mixed) = if has_success && !has_delay && !has_failure {
+            (
+                locale.dsn_subject_delivered,
+                locale.dsn_header_delivered,
+                false,
+            )
+        } else if has_delay && !has_success && !has_failure {
+            (locale.dsn_subject_delayed, locale.dsn_header_delayed, false)
+        } else if has_failure && !has_success && !has_delay {
+            (locale.dsn_subject_failed, locale.dsn_header_failed, false)
+        } else if has_success {
+            (locale.dsn_subject_partial, locale.dsn_header_partial, true)
+        } else {
+            (locale.dsn_subject_mixed, locale.dsn_header_mixed, true)
+        };
+
+        // Build human readable parts
+        let mut variables = Variables::new();
+        variables.insert_single(DsnTemplateVariable::Subject, subject.to_string());
+        variables.insert_single(DsnTemplateVariable::Header, header.to_string());
+        variables.insert_single(
+            DsnTemplateVariable::Sender,
+            self.message.return_path.to_string(),
+        );
+        variables.insert_single(DsnTemplateVariable::ReportingMta, reporting_mta.clone());
+        if is_mixed {
+            variables.insert_single(DsnTemplateVariable::IsMixed, "true".to_string());
+        }
+        for (block, title, entries) in [
+            (
+                DsnTemplateVariable::Delivered,
+                (
+                    DsnTemplateVariable::DeliveredTitle,
+                    locale.dsn_delivered_title,
+                ),
+                delivered,
+            ),
+            (
+                DsnTemplateVariable::Delayed,
+                (DsnTemplateVariable::DelayedTitle, locale.dsn_delayed_title),
+                delayed,
+            ),
+            (
+                DsnTemplateVariable::Failed,
+                (DsnTemplateVariable::FailedTitle, locale.dsn_failed_title),
+                failed,
+            ),
+        ] {
+            variables.insert_single(title.0, title.1.to_string());
+            if !entries.is_empty() {
+                variables.insert_block(block, entries);
+            }
+        }
+        let subject = template
+            .subject
+            .as_ref()
+            .map(|subject| subject.eval(&variables))
+            .unwrap_or_else(|| subject.to_string());
+        let txt = MimePart::new(
+            ContentType::new("text/plain"),
+            BodyPart::Text(template.text.eval(&variables).into()),
+        );
+        let txt = if let Some(html) = &template.html {
+            MimePart::new(
+                ContentType::new("multipart/alternative"),
+                BodyPart::Multipart(vec![
+                    txt,
+                    MimePart::new(
+                        ContentType::new("text/html"),
+                        BodyPart::Text(html.eval(&variables).into()),
+                    ),
+                ]),
+            )
+        } else {
+            txt
+        };
+
         // Prepare DSN
         let mut dsn_header = String::with_capacity(dsn.len() + 128);
         self.message
@@ -377,7 +416,7 @@ impl MessageWrapper {
             .body(MimePart::new(
                 ContentType::new("multipart/report").attribute("report-type", "delivery-status"),
                 BodyPart::Multipart(vec![
-                    MimePart::new(ContentType::new("text/plain"), BodyPart::Text(txt.into())),
+                    txt,
                     MimePart::new(
                         ContentType::new("message/delivery-status"),
                         BodyPart::Text(dsn.into()),
@@ -393,6 +432,38 @@ impl MessageWrapper {
             .into()
     }
 
+    async fn sender_locale(&self, server: &Server) -> Option<String> {
+        match server
+            .email_to_id(
+                &server.core.storage.directory,
+                &self.message.return_path,
+                self.span_id,
+            )
+            .await
+        {
+            Ok(Some(account_id)) => match server.get_access_token(account_id).await {
+                Ok(access_token) => access_token.locale.clone(),
+                Err(err) => {
+                    trc::error!(
+                        err.span_id(self.span_id)
+                            .details("Failed to obtain access token")
+                            .caused_by(trc::location!())
+                    );
+                    None
+                }
+            },
+            Ok(None) => None,
+            Err(err) => {
+                trc::error!(
+                    err.span_id(self.span_id)
+                        .details("Failed to lookup sender account")
+                        .caused_by(trc::location!())
+                );
+                None
+            }
+        }
+    }
+
     fn handle_double_bounce(&mut self) {
         let mut is_double_bounce = Vec::with_capacity(0);
         let now = now();
@@ -402,9 +473,7 @@ impl MessageWrapper {
                 && let Status::PermanentFailure(err) = &rcpt.status
             {
                 rcpt.flags |= RCPT_DSN_SENT;
-                let mut dsn = String::new();
-                err.write_dsn_text(&rcpt.address, &mut dsn);
-                is_double_bounce.push(dsn);
+                is_double_bounce.push(format!("<{}> ({})\r\n", rcpt.address, err.dsn_text()));
             }
 
             if rcpt.notify.due <= now {
@@ -426,25 +495,24 @@ impl MessageWrapper {
 }
 
 impl HostResponse<Box<str>> {
-    fn write_dsn_text(&self, addr: &str, dsn: &mut String) {
-        let _ = write!(
-            dsn,
-            "<{}> (delivered to '{}' with code {} ({}.{}.{}) '",
-            addr,
+    fn dsn_text(&self) -> String {
+        let mut dsn = format!(
+            "delivered to '{}' with code {} ({}.{}.{}) '",
             self.hostname,
             self.response.code,
             self.response.esc[0],
             self.response.esc[1],
             self.response.esc[2]
         );
-        self.response.write_response(dsn);
-        dsn.push_str("')\r\n");
+        self.response.write_response(&mut dsn);
+        dsn.push('\'');
+        dsn
     }
 }
 
 impl UnexpectedResponse {
-    fn write_dsn_text(&self, host: &str, addr: &str, dsn: &mut String) {
-        let _ = write!(dsn, "<{addr}> (host '{host}' rejected ");
+    fn write_dsn_text(&self, host: &str, dsn: &mut String) {
+        let _ = write!(dsn, "host '{host}' rejected ");
 
         if !self.command.is_empty() {
             let _ = write!(dsn, "command '{}'", self.command);
@@ -458,53 +526,35 @@ impl UnexpectedResponse {
             self.response.code, self.response.esc[0], self.response.esc[1], self.response.esc[2]
         );
         self.response.write_response(dsn);
-        dsn.push_str("')\r\n");
+        dsn.push('\'');
     }
 }
 
 impl ErrorDetails {
-    fn write_dsn_text(&self, addr: &str, dsn: &mut String) {
+    fn dsn_text(&self) -> String {
         let entity = self.entity.as_ref();
         match &self.details {
             Error::UnexpectedResponse(response) => {
-                response.write_dsn_text(entity, addr, dsn);
-            }
-            Error::DnsError(err) => {
-                let _ = write!(dsn, "<{addr}> (failed to lookup '{entity}': {err})\r\n",);
+                let mut dsn = String::new();
+                response.write_dsn_text(entity, &mut dsn);
+                dsn
             }
+            Error::DnsError(err) => format!("failed to lookup '{entity}': {err}"),
             Error::ConnectionError(details) => {
-                let _ = write!(
-                    dsn,
-                    "<{addr}> (connection to '{entity}' failed: {details})\r\n",
-                );
-            }
-            Error::TlsError(details) => {
-                let _ = write!(dsn, "<{addr}> (TLS error from '{entity}': {details})\r\n",);
+                format!("connection to '{entity}' failed: {details}")
             }
+            Error::TlsError(details) => format!("TLS error from '{entity}': {details}"),
             Error::DaneError(details) => {
-                let _ = write!(
-                    dsn,
-                    "<{addr}> (DANE failed to authenticate '{entity}': {details})\r\n",
-                );
+                format!("DANE failed to authenticate '{entity}': {details}")
             }
             Error::MtaStsError(details) => {
-                let _ = write!(
-                    dsn,
-                    "<{addr}> (MTA-STS failed to authenticate '{entity}': {details})\r\n",
-                );
-            }
-            Error::RateLimited => {
-                let _ = write!(dsn, "<{addr}> (rate limited)\r\n");
+                format!("MTA-STS failed to authenticate '{entity}': {details}")
             }
+            Error::RateLimited => "rate limited".to_string(),
             Error::ConcurrencyLimited => {
-                let _ = write!(
-                    dsn,
-                    "<{addr}> (too many concurrent connections to remote server)\r\n",
-                );
-            }
-            Error::Io(err) => {
-                let _ = write!(dsn, "<{addr}> (queue error: {err})\r\n");
+                "too many concurrent connections to remote server".to_string()
             }
+            Error::Io(err) => format!("queue error: {err}"),
         }
     }
 }
@@ -523,6 +573,13 @@ impl Message {
 }
 
 impl Recipient {
+    fn dsn_text(&self, details: String) -> [(DsnTemplateVariable, String); 2] {
+        [
+            (DsnTemplateVariable::Address, self.address.to_string()),
+            (DsnTemplateVariable::Details, details),
+        ]
+    }
+
     fn write_dsn(&self, dsn: &mut String) {
         if let Some(orcpt) = &self.orcpt {
             let _ = write!(dsn, "Original-Recipient: rfc822;{orcpt}\r\n");
@@ -0,0 +1,142 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use super::{Error, ErrorDetails, MessageWrapper, RCPT_HELD, RCPT_PARKED, Status, dsn::SendDsn};
+use common::{Server, config::smtp::queue::QueueExpiry, ipc::QueueEvent};
+use store::write::{BatchBuilder, QueueClass, ValueClass, now};
+
+impl MessageWrapper {
+    pub fn is_held(&self) -> bool {
+        self.message.recipients.iter().any(|rcpt| rcpt.is_held())
+    }
+
+    // The hold reason is kept as the status of the held recipients
+    pub fn hold_recipients(&mut self, reason: &str) -> bool {
+        let mut has_changes = false;
+        for rcpt in &mut self.message.recipients {
+            if matches!(rcpt.status, Status::Scheduled | Status::TemporaryFailure(_))
+                && !rcpt.is_held()
+            {
+                rcpt.flags = (rcpt.flags & !RCPT_PARKED) | RCPT_HELD;
+                rcpt.status = Status::TemporaryFailure(ErrorDetails {
+                    entity: "localhost".into(),
+                    details: Error::Io(reason.into()),
+                });
+                has_changes = true;
+            }
+        }
+        has_changes
+    }
+
+    pub async fn hold(mut self, server: &Server, reason: &str) -> bool {
+        // Held recipients have no queue events, remove the existing ones
+        // in the same batch that saves the message
+        let prev_events = self.message.next_events();
+        if !self.hold_recipients(reason) {
+            return false;
+        }
+        let mut batch = BatchBuilder::new();
+        for (queue_name, due) in prev_events {
+            batch.clear(ValueClass::Queue(QueueClass::MessageEvent(
+                store::write::QueueEvent {
+                    due,
+                    queue_id: self.queue_id,
+                    queue_name: queue_name.into_inner(),
+                },
+            )));
+        }
+
+        // Recipients from all queues were modified
+        let span_id = self.span_id;
+        let queue_id = self.queue_id;
+        self.is_multi_queue = false;
+        if self.save_changes_with(server, batch).await {
+            trc::event!(
+                Queue(trc::QueueEvent::MessageHeld),
+                SpanId = span_id,
+                QueueId = queue_id,
+                Reason = reason.to_string(),
+            );
+            true
+        } else {
+            false
+        }
+    }
+
+    pub async fn release(mut self, server: &Server, filter: Option<&str>) -> bool {
+        let now = now();
+        let mut released = Vec::new();
+        for rcpt in &mut self.message.recipients {
+            if rcpt.is_held() && filter.is_none_or(|filter| rcpt.address.contains(filter)) {
+                rcpt.flags &= !RCPT_HELD;
+                rcpt.status = Status::Scheduled;
+                rcpt.retry.due = now;
+                if rcpt
+                    .expiration_time(self.message.created)
+                    .is_some_and(|expires| expires <= now)
+                {
+                    // Allow one delivery attempt for recipients that expired while held
+                    rcpt.expires = QueueExpiry::Attempts(rcpt.retry.inner + 1);
+                }
+                released.push(trc::Value::String(rcpt.address.as_ref().into()));
+            }
+        }
+        if released.is_empty() {
+            return false;
+        }
+
+        trc::event!(
+            Queue(trc::QueueEvent::MessageReleased),
+            SpanId = self.span_id,
+            QueueId = self.queue_id,
+            To = released,
+        );
+
+        self.is_multi_queue = false;
+        if self.save_changes(server, None).await {
+            let _ = server.inner.ipc.queue_tx.send(QueueEvent::Refresh).await;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub async fn reject(mut self, server: &Server, filter: Option<&str>, reason: &str) -> bool {
+        let mut rejected = Vec::new();
+        for rcpt in &mut self.message.recipients {
+            if rcpt.is_held() && filter.is_none_or(|filter| rcpt.address.contains(filter)) {
+                rcpt.flags &= !RCPT_HELD;
+                rcpt.status = Status::PermanentFailure(ErrorDetails {
+                    entity: "localhost".into(),
+                    details: Error::Io(reason.into()),
+                });
+                rejected.push(trc::Value::String(rcpt.address.as_ref().into()));
+            }
+        }
+        if rejected.is_empty() {
+            return false;
+        }
+
+        trc::event!(
+            Queue(trc::QueueEvent::MessageRejected),
+            SpanId = self.span_id,
+            QueueId = self.queue_id,
+            To = rejected,
+            Reason = reason.to_string(),
+        );
+
+        // Notify the sender
+        server.send_dsn(&mut self).await;
+
+        // Delete message if there are no pending deliveries
+        self.is_multi_queue = false;
+        if self.message.next_event(None).is_some() || self.is_held() {
+            self.save_changes(server, None).await
+        } else {
+            self.remove(server, None).await
+        }
+    }
+}
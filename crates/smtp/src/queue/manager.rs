@@ -4,7 +4,7 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use super::{Message, QueueId, Status, spool::SmtpSpool};
+use super::{Message, PARKED_EXPIRY, QueueId, RCPT_HELD, RCPT_PARKED, Status, spool::SmtpSpool};
 use crate::queue::{Recipient, spool::LOCK_EXPIRY};
 use ahash::AHashMap;
 use common::{
@@ -228,13 +228,17 @@ impl Message {
 
         for rcpt in &self.recipients {
             if matches!(rcpt.status, Status::Scheduled | Status::TemporaryFailure(_))
+                && !rcpt.is_held()
                 && queue.is_none_or(|q| rcpt.queue == q)
             {
-                let mut earlier_event = std::cmp::min(rcpt.retry.due, rcpt.notify.due);
-
-                if let Some(expires) = rcpt.expiration_time(self.created) {
-                    earlier_event = std::cmp::min(earlier_event, expires);
-                }
+                // Parked recipients are only woken up to expire them
+                let earlier_event = if rcpt.is_parked() {
+                    rcpt.parked_expiration_time(self.created)
+                } else if let Some(expires) = rcpt.expiration_time(self.created) {
+                    std::cmp::min(std::cmp::min(rcpt.retry.due, rcpt.notify.due), expires)
+                } else {
+                    std::cmp::min(rcpt.retry.due, rcpt.notify.due)
+                };
 
                 if let Some(next_event) = &mut next_event {
                     if earlier_event < *next_event {
@@ -254,6 +258,7 @@ impl Message {
 
         for rcpt in self.recipients.iter().filter(|rcpt| {
             matches!(rcpt.status, Status::Scheduled | Status::TemporaryFailure(_))
+                && !rcpt.is_waiting()
                 && queue.is_none_or(|q| rcpt.queue == q)
         }) {
             if let Some(next_delivery) = &mut next_delivery {
@@ -273,6 +278,7 @@ impl Message {
 
         for rcpt in self.recipients.iter().filter(|rcpt| {
             matches!(rcpt.status, Status::Scheduled | Status::TemporaryFailure(_))
+                && !rcpt.is_waiting()
                 && queue.is_none_or(|q| rcpt.queue == q)
         }) {
             if let Some(next_dsn) = &mut next_dsn {
@@ -292,6 +298,7 @@ impl Message {
 
         for rcpt in self.recipients.iter().filter(|d| {
             matches!(d.status, Status::Scheduled | Status::TemporaryFailure(_))
+                && !d.is_waiting()
                 && queue.is_none_or(|q| d.queue == q)
         }) {
             if let Some(rcpt_expires) = rcpt.expiration_time(self.created) {
@@ -312,12 +319,17 @@ impl Message {
         let mut next_events = AHashMap::new();
 
         for rcpt in &self.recipients {
-            if matches!(rcpt.status, Status::Scheduled | Status::TemporaryFailure(_)) {
-                let mut earlier_event = std::cmp::min(rcpt.retry.due, rcpt.notify.due);
-
-                if let Some(expires) = rcpt.expiration_time(self.created) {
-                    earlier_event = std::cmp::min(earlier_event, expires);
-                }
+            if matches!(rcpt.status, Status::Scheduled | Status::TemporaryFailure(_))
+                && !rcpt.is_held()
+            {
+                // Parked recipients are only woken up to expire them
+                let earlier_event = if rcpt.is_parked() {
+                    rcpt.parked_expiration_time(self.created)
+                } else if let Some(expires) = rcpt.expiration_time(self.created) {
+                    std::cmp::min(std::cmp::min(rcpt.retry.due, rcpt.notify.due), expires)
+                } else {
+                    std::cmp::min(rcpt.retry.due, rcpt.notify.due)
+                };
 
                 match next_events.entry(rcpt.queue) {
                     Entry::Occupied(mut entry) => {
@@ -351,6 +363,23 @@ impl Recipient {
             QueueExpiry::Attempts(count) => self.retry.inner >= count,
         }
     }
+
+    pub fn parked_expiration_time(&self, created: u64) -> u64 {
+        self.expiration_time(created)
+            .unwrap_or(created + PARKED_EXPIRY)
+    }
+
+    pub fn is_held(&self) -> bool {
+        self.flags & RCPT_HELD != 0
+    }
+
+    pub fn is_parked(&self) -> bool {
+        self.flags & RCPT_PARKED != 0
+    }
+
+    pub fn is_waiting(&self) -> bool {
+        self.flags & (RCPT_HELD | RCPT_PARKED) != 0
+    }
 }
 
 pub trait SpawnQueue {
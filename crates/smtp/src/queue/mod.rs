@@ -20,9 +20,11 @@ use types::blob_hash::BlobHash;
 use utils::DomainPart;
 
 pub mod dsn;
+pub mod hold;
 pub mod manager;
 pub mod quota;
 pub mod spool;
+pub mod srs;
 pub mod throttle;
 
 pub type QueueId = u64;
@@ -128,6 +130,12 @@ pub const FROM_AUTOGENERATED: u64 = 1 << 37;
 pub const RCPT_DSN_SENT: u64 = 1 << 32;
 //pub const RCPT_STATUS_CHANGED: u64 = 1 << 33;
 pub const RCPT_SPAM_PAYLOAD: u64 = 1 << 34;
+pub const RCPT_RELEASED: u64 = 1 << 35;
+pub const RCPT_HELD: u64 = 1 << 36;
+pub const RCPT_PARKED: u64 = 1 << 37;
+
+// Parked recipients without a time-based expiration are kept for five days
+pub const PARKED_EXPIRY: u64 = 5 * 86400;
 
 #[derive(
     Debug,
@@ -11,7 +11,7 @@ use super::{
 use crate::queue::manager::{LockedMessage, Queue};
 use crate::queue::{
     FROM_AUTHENTICATED, FROM_AUTOGENERATED, FROM_DSN, FROM_REPORT, FROM_UNAUTHENTICATED,
-    FROM_UNAUTHENTICATED_DMARC, MessageWrapper,
+    FROM_UNAUTHENTICATED_DMARC, MessageWrapper, RCPT_HELD, RCPT_PARKED,
 };
 use common::config::smtp::queue::QueueName;
 use common::ipc::QueueEvent;
@@ -68,6 +68,11 @@ pub trait SmtpSpool: Sync + Send {
         &self,
         id: QueueId,
     ) -> impl Future<Output = trc::Result<Option<Archive<AlignedBytes>>>> + Send;
+
+    fn pending_messages_for(
+        &self,
+        domain_matches: impl Fn(&str) -> bool + Sync + Send,
+    ) -> impl Future<Output = trc::Result<Vec<QueueId>>> + Send;
 }
 
 impl SmtpSpool for Server {
@@ -283,6 +288,42 @@ impl SmtpSpool for Server {
             )))
             .await
     }
+
+    async fn pending_messages_for(
+        &self,
+        domain_matches: impl Fn(&str) -> bool + Sync + Send,
+    ) -> trc::Result<Vec<QueueId>> {
+        let mut ids = Vec::new();
+        self.store()
+            .iterate(
+                IterateParams::new(
+                    ValueKey::from(ValueClass::Queue(QueueClass::Message(0))),
+                    ValueKey::from(ValueClass::Queue(QueueClass::Message(u64::MAX))),
+                )
+                .ascending(),
+                |key, value| {
+                    let message_ = <Archive<AlignedBytes> as Deserialize>::deserialize(value)
+                        .add_context(|ctx| ctx.ctx(trc::Key::Key, key))?;
+                    let message = message_
+                        .unarchive::<Message>()
+                        .add_context(|ctx| ctx.ctx(trc::Key::Key, key))?;
+                    if message.recipients.iter().any(|rcpt| {
+                        matches!(
+                            rcpt.status,
+                            ArchivedStatus::Scheduled | ArchivedStatus::TemporaryFailure(_)
+                        ) && rcpt.flags.to_native() & RCPT_HELD == 0
+                            && domain_matches(rcpt.domain_part())
+                    }) {
+                        ids.push(key.deserialize_be_u64(0)?);
+                    }
+
+                    Ok(true)
+                },
+            )
+            .await
+            .caused_by(trc::location!())
+            .map(|_| ids)
+    }
 }
 
 fn lock_id(queue_id: QueueId, queue_name: QueueName) -> [u8; 16] {
@@ -301,6 +342,14 @@ impl MessageWrapper {
         server: &Server,
         source: MessageSource,
     ) -> bool {
+        // Rewrite the return path of forwarded messages
+        if matches!(
+            source,
+            MessageSource::Unauthenticated { .. } | MessageSource::Autogenerated
+        ) {
+            self.srs_rewrite(server, session_id).await;
+        }
+
         // Set flags
         let (flags, event, train_spam) = match source {
             MessageSource::Authenticated => (
@@ -556,12 +605,8 @@ impl MessageWrapper {
         recipient.queue = queue.virtual_queue;
     }
 
-    pub async fn save_changes(mut self, server: &Server, prev_event: Option<u64>) -> bool {
-        // Release quota for completed deliveries
+    pub async fn save_changes(self, server: &Server, prev_event: Option<u64>) -> bool {
         let mut batch = BatchBuilder::new();
-        self.release_quota(&mut batch);
-
-        // Update message queue
         if let Some(prev_event) = prev_event {
             batch.clear(ValueClass::Queue(QueueClass::MessageEvent(
                 store::write::QueueEvent {
@@ -571,6 +616,15 @@ impl MessageWrapper {
                 },
             )));
         }
+        self.save_changes_with(server, batch).await
+    }
+
+    // Saves the message along with the changes already present in the batch
+    pub async fn save_changes_with(mut self, server: &Server, mut batch: BatchBuilder) -> bool {
+        // Release quota for completed deliveries
+        self.release_quota(&mut batch);
+
+        // Update message queue
         for (queue_name, due) in self.message.next_events() {
             batch.set(
                 ValueClass::Queue(QueueClass::MessageEvent(store::write::QueueEvent {
@@ -753,7 +807,8 @@ impl ArchivedMessage {
             matches!(
                 d.status,
                 ArchivedStatus::Scheduled | ArchivedStatus::TemporaryFailure(_)
-            ) && queue.is_none_or(|q| d.queue == q)
+            ) && d.flags.to_native() & (RCPT_HELD | RCPT_PARKED) == 0
+                && queue.is_none_or(|q| d.queue == q)
         }) {
             let retry_due = rcpt.retry.due.to_native();
             if let Some(next_delivery) = &mut next_delivery {
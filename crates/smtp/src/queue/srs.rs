@@ -0,0 +1,205 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use super::MessageWrapper;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use common::{Server, config::smtp::queue::SrsConfig};
+use ring::hmac;
+use store::write::now;
+use utils::DomainPart;
+
+const HASH_LEN: usize = 4;
+const TIMESTAMP_PRECISION: u64 = 24 * 60 * 60;
+const TIMESTAMP_SLOTS: u64 = 1 << 10;
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+pub trait SenderRewriting {
+    fn srs_forward(&self, address: &str, now: u64) -> Option<String>;
+    fn srs_reverse(&self, address: &str, now: u64) -> Option<Result<String, &'static str>>;
+}
+
+impl SenderRewriting for SrsConfig {
+    fn srs_forward(&self, address: &str, now: u64) -> Option<String> {
+        let (local, domain) = address.rsplit_once('@')?;
+        if local.is_empty() || domain.is_empty() || domain.eq_ignore_ascii_case(&self.domain) {
+            return None;
+        }
+
+        if let Some(opaque) = strip_srs_prefix(local, "SRS1") {
+            // Re-sign the address keeping the original forwarder
+            let (_, opaque) = opaque[1..].split_once('=')?;
+            let (orig_domain, opaque) = opaque.split_once('=')?;
+            format!(
+                "SRS1={}={orig_domain}={opaque}@{}",
+                srs_hash(self.secrets.first()?, &[orig_domain, opaque]),
+                self.domain
+            )
+            .into()
+        } else if let Some(opaque) = strip_srs_prefix(local, "SRS0") {
+            // Wrap an address rewritten by a previous forwarder
+            format!(
+                "SRS1={}={domain}={opaque}@{}",
+                srs_hash(self.secrets.first()?, &[domain, opaque]),
+                self.domain
+            )
+            .into()
+        } else {
+            let timestamp = encode_timestamp(now);
+            format!(
+                "SRS0={}={timestamp}={domain}={local}@{}",
+                srs_hash(self.secrets.first()?, &[&timestamp, domain, local]),
+                self.domain
+            )
+            .into()
+        }
+    }
+
+    fn srs_reverse(&self, address: &str, now: u64) -> Option<Result<String, &'static str>> {
+        let (local, domain) = address.rsplit_once('@')?;
+        if !domain.eq_ignore_ascii_case(&self.domain) {
+            return None;
+        }
+
+        if let Some(opaque) = strip_srs_prefix(local, "SRS0") {
+            let mut parts = opaque[1..].splitn(4, '=');
+            let (Some(hash), Some(timestamp), Some(orig_domain), Some(orig_local)) =
+                (parts.next(), parts.next(), parts.next(), parts.next())
+            else {
+                return Some(Err("Malformed SRS0 address"));
+            };
+            if orig_domain.is_empty() || orig_local.is_empty() {
+                return Some(Err("Malformed SRS0 address"));
+            } else if !verify_hash(self, hash, &[timestamp, orig_domain, orig_local]) {
+                return Some(Err("Invalid SRS hash"));
+            }
+            match decode_timestamp(timestamp) {
+                Some(timestamp)
+                    if (encode_days(now) + TIMESTAMP_SLOTS - timestamp) % TIMESTAMP_SLOTS
+                        <= self.max_age / TIMESTAMP_PRECISION =>
+                {
+                    Some(Ok(format!("{orig_local}@{orig_domain}")))
+                }
+                Some(_) => Some(Err("Expired SRS address")),
+                None => Some(Err("Invalid SRS timestamp")),
+            }
+        } else if let Some(opaque) = strip_srs_prefix(local, "SRS1") {
+            let mut parts = opaque[1..].splitn(2, '=');
+            let (Some(hash), Some((orig_domain, opaque))) = (
+                parts.next(),
+                parts.next().and_then(|rest| rest.split_once('=')),
+            ) else {
+                return Some(Err("Malformed SRS1 address"));
+            };
+            if orig_domain.is_empty() || opaque.is_empty() {
+                Some(Err("Malformed SRS1 address"))
+            } else if !verify_hash(self, hash, &[orig_domain, opaque]) {
+                Some(Err("Invalid SRS hash"))
+            } else {
+                Some(Ok(format!("SRS0{opaque}@{orig_domain}")))
+            }
+        } else {
+            None
+        }
+    }
+}
+
+impl MessageWrapper {
+    pub(crate) async fn srs_rewrite(&mut self, server: &Server, session_id: u64) {
+        let Some(srs) = &server.core.smtp.queue.srs else {
+            return;
+        };
+        let directory = &server.core.storage.directory;
+
+        // Only messages from remote senders relayed to remote recipients are rewritten
+        let sender_domain = self.message.return_path.domain_part();
+        if sender_domain.is_empty()
+            || directory
+                .is_local_domain(sender_domain)
+                .await
+                .unwrap_or(true)
+        {
+            return;
+        }
+        let mut has_remote_rcpt = false;
+        for rcpt in &self.message.recipients {
+            if !directory
+                .is_local_domain(rcpt.domain_part())
+                .await
+                .unwrap_or(true)
+            {
+                has_remote_rcpt = true;
+                break;
+            }
+        }
+
+        if has_remote_rcpt
+            && let Some(return_path) = srs.srs_forward(&self.message.return_path, now())
+        {
+            trc::event!(
+                Queue(trc::QueueEvent::SrsRewritten),
+                SpanId = session_id,
+                From = self.message.return_path.to_string(),
+                Details = return_path.clone(),
+            );
+
+            self.message.return_path = return_path.into_boxed_str();
+        }
+    }
+}
+
+fn srs_hash(secret: &str, parts: &[&str]) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, secret.as_bytes());
+    let mut ctx = hmac::Context::with_key(&key);
+    for part in parts {
+        ctx.update(part.to_lowercase().as_bytes());
+    }
+    let mut hash = STANDARD.encode(ctx.sign().as_ref());
+    hash.truncate(HASH_LEN);
+    hash
+}
+
+fn verify_hash(config: &SrsConfig, hash: &str, parts: &[&str]) -> bool {
+    // Secrets other than the first one are accepted to allow key rotation
+    hash.len() == HASH_LEN
+        && config
+            .secrets
+            .iter()
+            .any(|secret| srs_hash(secret, parts).eq_ignore_ascii_case(hash))
+}
+
+fn strip_srs_prefix<'x>(local: &'x str, prefix: &str) -> Option<&'x str> {
+    local
+        .get(..prefix.len())
+        .filter(|p| p.eq_ignore_ascii_case(prefix))
+        .map(|_| &local[prefix.len()..])
+        .filter(|opaque| opaque.starts_with(['=', '+', '-']) && opaque.len() > 1)
+}
+
+fn encode_days(now: u64) -> u64 {
+    (now / TIMESTAMP_PRECISION) % TIMESTAMP_SLOTS
+}
+
+fn encode_timestamp(now: u64) -> String {
+    let days = encode_days(now) as usize;
+    [
+        BASE32_ALPHABET[(days >> 5) & 0x1f] as char,
+        BASE32_ALPHABET[days & 0x1f] as char,
+    ]
+    .into_iter()
+    .collect()
+}
+
+fn decode_timestamp(timestamp: &str) -> Option<u64> {
+    if timestamp.len() != 2 {
+        return None;
+    }
+    timestamp.bytes().try_fold(0u64, |acc, ch| {
+        BASE32_ALPHABET
+            .iter()
+            .position(|&b| b == ch.to_ascii_uppercase())
+            .map(|pos| (acc << 5) | pos as u64)
+    })
+}
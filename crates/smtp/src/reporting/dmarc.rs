@@ -19,7 +19,10 @@ use mail_auth::{
     SpfResult,
     common::verify::VerifySignature,
     dmarc::{self, URI},
-    report::{AuthFailureType, IdentityAlignment, PolicyPublished, Record, Report, SPFDomainScope},
+    report::{
+        ActionDisposition, AuthFailureType, IdentityAlignment, PolicyOverride,
+        PolicyOverrideReason, PolicyPublished, Record, Report, SPFDomainScope,
+    },
 };
 use std::{collections::hash_map::Entry, future::Future};
 use store::{
@@ -55,6 +58,7 @@ impl<T: SessionStream> Session<T> {
         dmarc_output: DmarcOutput,
         dkim_output: &[DkimOutput<'_>],
         arc_output: &Option<ArcOutput<'_>>,
+        trusted_sealer: Option<&str>,
     ) {
         let dmarc_record = dmarc_output.dmarc_record_cloned().unwrap();
         let config = &self.server.core.smtp.report.dmarc;
@@ -292,7 +296,14 @@ impl<T: SessionStream> Session<T> {
         if let Some(spf_mail_from) = &self.data.spf_mail_from {
             report_record = report_record.with_spf_output(spf_mail_from, SPFDomainScope::MailFrom);
         }
-        if let Some(arc_output) = arc_output {
+        if let Some(sealer) = trusted_sealer {
+            report_record = report_record
+                .with_action_disposition(ActionDisposition::None)
+                .with_policy_override_reason(
+                    PolicyOverrideReason::new(PolicyOverride::TrustedForwarder)
+                        .with_comment(format!("arc=pass trusted sealer d={sealer}")),
+                );
+        } else if let Some(arc_output) = arc_output {
             report_record = report_record.with_arc_output(arc_output);
         }
 
@@ -337,6 +337,11 @@ impl ValueClass {
                     .write(account_id)
                     .write(9u8)
                     .write(document_id),
+                TaskQueueClass::UnsnoozeEmail { due } => serializer
+                    .write(due.inner())
+                    .write(account_id)
+                    .write(10u8)
+                    .write(document_id),
             },
             ValueClass::Blob(op) => match op {
                 BlobOp::Commit { hash } => serializer.write::<&[u8]>(hash.as_ref()),
@@ -602,9 +607,9 @@ impl ValueClass {
             },
             ValueClass::TaskQueue(e) => match e {
                 TaskQueueClass::UpdateIndex { .. } => (U64_LEN * 2) + 2,
-                TaskQueueClass::SendAlarm { .. } | TaskQueueClass::MergeThreads { .. } => {
-                    U64_LEN + (U32_LEN * 3) + 1
-                }
+                TaskQueueClass::SendAlarm { .. }
+                | TaskQueueClass::MergeThreads { .. }
+                | TaskQueueClass::UnsnoozeEmail { .. } => U64_LEN + (U32_LEN * 3) + 1,
                 TaskQueueClass::SendImip { is_payload, .. } => {
                     if *is_payload {
                         (U64_LEN * 2) + (U32_LEN * 2) + 1
@@ -239,6 +239,9 @@ pub enum TaskQueueClass {
     MergeThreads {
         due: TaskEpoch,
     },
+    UnsnoozeEmail {
+        due: TaskEpoch,
+    },
 }
 
 #[derive(Debug, PartialEq, Clone, Copy, Eq, Hash)]
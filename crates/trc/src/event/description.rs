@@ -54,6 +54,7 @@ impl EventType {
             EventType::Ai(event) => event.description(),
             EventType::WebDav(event) => event.description(),
             EventType::Calendar(event) => event.description(),
+            EventType::Dns(event) => event.description(),
         }
     }
 
@@ -104,6 +105,7 @@ impl EventType {
             EventType::Ai(event) => event.explain(),
             EventType::WebDav(event) => event.explain(),
             EventType::Calendar(event) => event.explain(),
+            EventType::Dns(event) => event.explain(),
         }
     }
 }
@@ -252,6 +254,14 @@ impl ImapEvent {
             ImapEvent::ConnectionStart => "IMAP connection started",
             ImapEvent::ConnectionEnd => "IMAP connection ended",
             ImapEvent::GetQuota => "IMAP GETQUOTA command",
+            ImapEvent::Compress => "IMAP COMPRESS command",
+            ImapEvent::GetMetadata => "IMAP GETMETADATA command",
+            ImapEvent::SetMetadata => "IMAP SETMETADATA command",
+            ImapEvent::Notify => "IMAP NOTIFY command",
+            ImapEvent::Replace => "IMAP REPLACE command",
+            ImapEvent::GenUrlAuth => "IMAP GENURLAUTH command",
+            ImapEvent::ResetKey => "IMAP RESETKEY command",
+            ImapEvent::UrlFetch => "IMAP URLFETCH command",
         }
     }
 
@@ -294,6 +304,14 @@ impl ImapEvent {
             ImapEvent::ConnectionStart => "IMAP connection started",
             ImapEvent::ConnectionEnd => "IMAP connection ended",
             ImapEvent::GetQuota => "Client requested mailbox quota",
+            ImapEvent::Compress => "Client enabled stream compression",
+            ImapEvent::GetMetadata => "Client requested mailbox or server metadata",
+            ImapEvent::SetMetadata => "Client modified mailbox or server metadata",
+            ImapEvent::Notify => "Client changed the mailbox events it wants to be notified about",
+            ImapEvent::Replace => "Client replaced a message with a new version",
+            ImapEvent::GenUrlAuth => "Client requested URL authorization tokens",
+            ImapEvent::ResetKey => "Client reset its URL authorization keys",
+            ImapEvent::UrlFetch => "Client fetched message data using IMAP URLs",
         }
     }
 }
@@ -481,6 +499,16 @@ impl SmtpEvent {
             SmtpEvent::RequestTooLarge => "Request too large",
             SmtpEvent::ConnectionStart => "SMTP connection started",
             SmtpEvent::ConnectionEnd => "SMTP connection ended",
+            SmtpEvent::Etrn => "SMTP ETRN command",
+            SmtpEvent::EtrnDisabled => "ETRN command disabled",
+            SmtpEvent::Atrn => "SMTP ATRN command",
+            SmtpEvent::AtrnDisabled => "ATRN command disabled",
+            SmtpEvent::Burl => "SMTP BURL command",
+            SmtpEvent::BurlDisabled => "BURL command disabled",
+            SmtpEvent::BurlFailed => "BURL URL resolution failed",
+            SmtpEvent::SrsReversed => "SRS address reversed",
+            SmtpEvent::SrsInvalid => "Invalid SRS address",
+            SmtpEvent::DmarcPolicyOverride => "DMARC policy overridden",
         }
     }
 
@@ -602,6 +630,36 @@ impl SmtpEvent {
             SmtpEvent::ConnectionStart => "A new SMTP connection was started",
             SmtpEvent::ConnectionEnd => "The SMTP connection was ended",
             SmtpEvent::StartTlsAlready => "TLS is already active",
+            SmtpEvent::Etrn => {
+                "The remote client requested the delivery of queued messages for a domain"
+            }
+            SmtpEvent::EtrnDisabled => {
+                "The remote client sent an ETRN command but the command is disabled"
+            }
+            SmtpEvent::Atrn => {
+                "The remote client requested the turnaround of the connection to receive queued messages"
+            }
+            SmtpEvent::AtrnDisabled => {
+                "The remote client sent an ATRN command but the command is disabled"
+            }
+            SmtpEvent::Burl => {
+                "The remote client submitted message data by reference using the BURL command"
+            }
+            SmtpEvent::BurlDisabled => {
+                "The remote client sent a BURL command but the command is disabled"
+            }
+            SmtpEvent::BurlFailed => {
+                "The message referenced by a BURL command could not be retrieved"
+            }
+            SmtpEvent::SrsReversed => {
+                "A bounce addressed to an SRS address was routed back to the original sender"
+            }
+            SmtpEvent::SrsInvalid => {
+                "The recipient is an SRS address with an invalid hash or an expired timestamp"
+            }
+            SmtpEvent::DmarcPolicyOverride => {
+                "The DMARC policy was not enforced because the message was sealed by a trusted ARC sealer"
+            }
         }
     }
 }
@@ -647,6 +705,11 @@ impl DeliveryEvent {
             DeliveryEvent::DsnPermFail => "DSN permanent failure notification",
             DeliveryEvent::RawInput => "Raw SMTP input received",
             DeliveryEvent::RawOutput => "Raw SMTP output sent",
+            DeliveryEvent::Held => "Message held for pickup",
+            DeliveryEvent::ConnectionReused => "Reusing SMTP connection",
+            DeliveryEvent::ThrottleDecreased => "Adaptive throttle decreased",
+            DeliveryEvent::ThrottleRecovered => "Adaptive throttle recovered",
+            DeliveryEvent::ThrottleLimitExceeded => "Adaptive throttle limit exceeded",
         }
     }
 
@@ -704,6 +767,21 @@ impl DeliveryEvent {
             }
             DeliveryEvent::RawInput => "Raw SMTP input received",
             DeliveryEvent::RawOutput => "Raw SMTP output sent",
+            DeliveryEvent::Held => {
+                "The message is held in the queue until the remote host requests its delivery with ETRN or ATRN"
+            }
+            DeliveryEvent::ConnectionReused => {
+                "An idle connection to the remote host was reused to deliver the message"
+            }
+            DeliveryEvent::ThrottleDecreased => {
+                "The delivery rate to the remote domain was reduced after receiving temporary failures"
+            }
+            DeliveryEvent::ThrottleRecovered => {
+                "The delivery rate to the remote domain was increased after successful deliveries"
+            }
+            DeliveryEvent::ThrottleLimitExceeded => {
+                "The adaptive concurrency or rate limit for the remote domain was exceeded"
+            }
         }
     }
 }
@@ -723,6 +801,10 @@ impl QueueEvent {
             QueueEvent::QueueDsn => "Queued DSN for delivery",
             QueueEvent::QueueAutogenerated => "Queued autogenerated message for delivery",
             QueueEvent::BackPressure => "Queue backpressure detected",
+            QueueEvent::SrsRewritten => "Return path rewritten using SRS",
+            QueueEvent::MessageHeld => "Message held for review",
+            QueueEvent::MessageReleased => "Held message released",
+            QueueEvent::MessageRejected => "Held message rejected",
         }
     }
 
@@ -744,6 +826,16 @@ impl QueueEvent {
             QueueEvent::BackPressure => {
                 "Queue congested, processing can't keep up with incoming message rate"
             }
+            QueueEvent::SrsRewritten => {
+                "The return path of a forwarded message was rewritten using the Sender Rewriting Scheme"
+            }
+            QueueEvent::MessageHeld => {
+                "A queued message has been placed on hold and will not be delivered until released"
+            }
+            QueueEvent::MessageReleased => "A held message has been released for delivery",
+            QueueEvent::MessageRejected => {
+                "A held message has been rejected and a failure DSN sent to the sender"
+            }
         }
     }
 }
@@ -1399,6 +1491,14 @@ impl DkimEvent {
             DkimEvent::SignatureExpired => "DKIM signature expired",
             DkimEvent::SignatureLength => "DKIM signature length issue",
             DkimEvent::SignerNotFound => "DKIM signer not found",
+            DkimEvent::KeyGenerated => "DKIM key generated",
+            DkimEvent::KeyPublished => "DKIM key published",
+            DkimEvent::KeyPublicationRequired => "DKIM key publication required",
+            DkimEvent::KeyNotPropagated => "DKIM key not propagated",
+            DkimEvent::KeyActivated => "DKIM key activated",
+            DkimEvent::KeyRevoked => "DKIM key revoked",
+            DkimEvent::KeyRevocationRequired => "DKIM key revocation required",
+            DkimEvent::KeyRotationFailed => "DKIM key rotation failed",
         }
     }
 
@@ -1422,6 +1522,20 @@ impl DkimEvent {
             DkimEvent::SignatureExpired => "The DKIM signature has expired",
             DkimEvent::SignatureLength => "The DKIM signature length is incorrect",
             DkimEvent::SignerNotFound => "The DKIM signer was not found",
+            DkimEvent::KeyGenerated => "A new DKIM key has been generated for rotation",
+            DkimEvent::KeyPublished => "The DNS record of a new DKIM key has been published",
+            DkimEvent::KeyPublicationRequired => {
+                "The DNS record of a new DKIM key has to be published manually"
+            }
+            DkimEvent::KeyNotPropagated => {
+                "The DNS record of a new DKIM key could not be found yet"
+            }
+            DkimEvent::KeyActivated => "Messages are now signed using the new DKIM key",
+            DkimEvent::KeyRevoked => "The DNS record of a retired DKIM key has been revoked",
+            DkimEvent::KeyRevocationRequired => {
+                "The DNS record of a retired DKIM key has to be revoked manually"
+            }
+            DkimEvent::KeyRotationFailed => "An error occurred while rotating a DKIM key",
         }
     }
 }
@@ -1928,3 +2042,29 @@ impl CalendarEvent {
         }
     }
 }
+
+impl DnsEvent {
+    pub fn description(&self) -> &'static str {
+        match self {
+            DnsEvent::RecordCreated => "Managed DNS record created",
+            DnsEvent::RecordUpdated => "Managed DNS record updated",
+            DnsEvent::RecordDrift => "Managed DNS record drift",
+            DnsEvent::RecordUpdateFailed => "Managed DNS record update failed",
+            DnsEvent::LookupFailed => "Managed DNS lookup failed",
+            DnsEvent::SyncCompleted => "Managed DNS sync completed",
+        }
+    }
+
+    pub fn explain(&self) -> &'static str {
+        match self {
+            DnsEvent::RecordCreated => "A DNS record has been created at the DNS provider",
+            DnsEvent::RecordUpdated => "A DNS record has been updated at the DNS provider",
+            DnsEvent::RecordDrift => "A published DNS record does not match the expected value",
+            DnsEvent::RecordUpdateFailed => {
+                "Failed to create or update a DNS record at the DNS provider"
+            }
+            DnsEvent::LookupFailed => "Failed to look up a DNS record while checking for drift",
+            DnsEvent::SyncCompleted => "The DNS records of a managed domain have been synchronized",
+        }
+    }
+}
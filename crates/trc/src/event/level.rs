@@ -84,7 +84,15 @@ impl EventType {
                 | ImapEvent::Error
                 | ImapEvent::IdleStart
                 | ImapEvent::IdleStop
-                | ImapEvent::GetQuota => Level::Debug,
+                | ImapEvent::GetQuota
+                | ImapEvent::Compress
+                | ImapEvent::GetMetadata
+                | ImapEvent::SetMetadata
+                | ImapEvent::Notify
+                | ImapEvent::Replace
+                | ImapEvent::GenUrlAuth
+                | ImapEvent::ResetKey
+                | ImapEvent::UrlFetch => Level::Debug,
                 ImapEvent::RawInput | ImapEvent::RawOutput => Level::Trace,
             },
             EventType::ManageSieve(event) => match event {
@@ -137,6 +145,8 @@ impl EventType {
                 | SmtpEvent::MailFromNotAllowed
                 | SmtpEvent::RcptToDuplicate
                 | SmtpEvent::RcptToRewritten
+                | SmtpEvent::SrsReversed
+                | SmtpEvent::SrsInvalid
                 | SmtpEvent::RcptToMissing
                 | SmtpEvent::RequireTlsDisabled
                 | SmtpEvent::DeliverByDisabled
@@ -182,6 +192,7 @@ impl EventType {
                 | SmtpEvent::SpfFromFail
                 | SmtpEvent::DmarcPass
                 | SmtpEvent::DmarcFail
+                | SmtpEvent::DmarcPolicyOverride
                 | SmtpEvent::IprevPass
                 | SmtpEvent::IprevFail
                 | SmtpEvent::TooManyMessages
@@ -201,6 +212,13 @@ impl EventType {
                 | SmtpEvent::AuthNotAllowed
                 | SmtpEvent::AuthMechanismNotSupported
                 | SmtpEvent::ExpnDisabled
+                | SmtpEvent::Etrn
+                | SmtpEvent::EtrnDisabled
+                | SmtpEvent::Atrn
+                | SmtpEvent::AtrnDisabled
+                | SmtpEvent::Burl
+                | SmtpEvent::BurlDisabled
+                | SmtpEvent::BurlFailed
                 | SmtpEvent::RequestTooLarge
                 | SmtpEvent::TooManyRecipients => Level::Info,
                 SmtpEvent::RawInput | SmtpEvent::RawOutput => Level::Trace,
@@ -265,7 +283,14 @@ impl EventType {
                 ArcEvent::SealerNotFound => Level::Warn,
             },
             EventType::Dkim(event) => match event {
-                DkimEvent::SignerNotFound => Level::Warn,
+                DkimEvent::SignerNotFound
+                | DkimEvent::KeyPublicationRequired
+                | DkimEvent::KeyRevocationRequired
+                | DkimEvent::KeyRotationFailed => Level::Warn,
+                DkimEvent::KeyGenerated
+                | DkimEvent::KeyPublished
+                | DkimEvent::KeyActivated
+                | DkimEvent::KeyRevoked => Level::Info,
                 _ => Level::Debug,
             },
             EventType::MailAuth(_) => Level::Debug,
@@ -437,6 +462,9 @@ impl EventType {
                 | DeliveryEvent::NullMx
                 | DeliveryEvent::Connect
                 | DeliveryEvent::ConnectError
+                | DeliveryEvent::ConnectionReused
+                | DeliveryEvent::ThrottleDecreased
+                | DeliveryEvent::ThrottleRecovered
                 | DeliveryEvent::GreetingFailed
                 | DeliveryEvent::EhloRejected
                 | DeliveryEvent::AuthFailed
@@ -450,9 +478,11 @@ impl EventType {
                 | DeliveryEvent::StartTlsError
                 | DeliveryEvent::StartTlsDisabled
                 | DeliveryEvent::ImplicitTlsError
-                | DeliveryEvent::DoubleBounce => Level::Info,
+                | DeliveryEvent::DoubleBounce
+                | DeliveryEvent::Held => Level::Info,
                 DeliveryEvent::ConcurrencyLimitExceeded
                 | DeliveryEvent::RateLimitExceeded
+                | DeliveryEvent::ThrottleLimitExceeded
                 | DeliveryEvent::MissingOutboundHostname => Level::Warn,
                 DeliveryEvent::DsnSuccess
                 | DeliveryEvent::DsnTempFail
@@ -475,7 +505,11 @@ impl EventType {
                 | QueueEvent::RateLimitExceeded
                 | QueueEvent::ConcurrencyLimitExceeded
                 | QueueEvent::Rescheduled
-                | QueueEvent::QuotaExceeded => Level::Info,
+                | QueueEvent::QuotaExceeded
+                | QueueEvent::SrsRewritten
+                | QueueEvent::MessageHeld
+                | QueueEvent::MessageReleased
+                | QueueEvent::MessageRejected => Level::Info,
                 QueueEvent::Locked | QueueEvent::BlobNotFound => Level::Debug,
             },
             EventType::TlsRpt(event) => match event {
@@ -550,6 +584,11 @@ impl EventType {
                 | CalendarEvent::AlarmRecipientOverride
                 | CalendarEvent::ItipMessageError => Level::Debug,
             },
+            EventType::Dns(event) => match event {
+                DnsEvent::RecordCreated | DnsEvent::RecordUpdated => Level::Info,
+                DnsEvent::RecordDrift | DnsEvent::RecordUpdateFailed => Level::Warn,
+                DnsEvent::LookupFailed | DnsEvent::SyncCompleted => Level::Debug,
+            },
         }
     }
 }
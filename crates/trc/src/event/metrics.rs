@@ -36,6 +36,7 @@ impl MetricType {
             Self::QueueCount => "queue.count",
             Self::UserCount => "user.count",
             Self::DomainCount => "domain.count",
+            Self::DeliveryThrottledDomains => "delivery.throttled-domains",
         }
     }
 
@@ -68,6 +69,7 @@ impl MetricType {
             Self::QueueCount => "Total number of messages in the queue",
             Self::UserCount => "Total number of users",
             Self::DomainCount => "Total number of domains",
+            Self::DeliveryThrottledDomains => "Remote domains with adaptive throttling in effect",
         }
     }
 
@@ -99,7 +101,7 @@ impl MetricType {
             | Self::DeliveryActiveConnections => "connections",
             Self::QueueCount => "messages",
             Self::UserCount => "users",
-            Self::DomainCount => "domains",
+            Self::DomainCount | Self::DeliveryThrottledDomains => "domains",
         }
     }
 
@@ -132,6 +134,7 @@ impl MetricType {
             Self::QueueCount => 24,
             Self::UserCount => 25,
             Self::DomainCount => 26,
+            Self::DeliveryThrottledDomains => 27,
         }
     }
 
@@ -164,6 +167,7 @@ impl MetricType {
             24 => Some(Self::QueueCount),
             25 => Some(Self::UserCount),
             26 => Some(Self::DomainCount),
+            27 => Some(Self::DeliveryThrottledDomains),
             _ => None,
         }
     }
@@ -197,6 +201,7 @@ impl MetricType {
             "queue.count" => Some(Self::QueueCount),
             "user.count" => Some(Self::UserCount),
             "domain.count" => Some(Self::DomainCount),
+            "delivery.throttled-domains" => Some(Self::DeliveryThrottledDomains),
             _ => None,
         }
     }
@@ -230,6 +235,7 @@ impl MetricType {
             Self::QueueCount,
             Self::UserCount,
             Self::DomainCount,
+            Self::DeliveryThrottledDomains,
         ]
     }
 }
@@ -49,6 +49,8 @@ static SERVER_MEMORY: AtomicGauge = AtomicGauge::new(MetricType::ServerMemory);
 static QUEUE_COUNT: AtomicGauge = AtomicGauge::new(MetricType::QueueCount);
 static USER_COUNT: AtomicGauge = AtomicGauge::new(MetricType::UserCount);
 static DOMAIN_COUNT: AtomicGauge = AtomicGauge::new(MetricType::DomainCount);
+static DELIVERY_THROTTLED_DOMAINS: AtomicGauge =
+    AtomicGauge::new(MetricType::DeliveryThrottledDomains);
 
 const CONN_SMTP_IN: usize = 0;
 const CONN_SMTP_OUT: usize = 1;
@@ -226,9 +228,19 @@ impl Collector {
     }
 
     pub fn collect_gauges(is_enterprise: bool) -> impl Iterator<Item = &'static AtomicGauge> {
-        static E_GAUGES: &[&AtomicGauge] =
-            &[&SERVER_MEMORY, &QUEUE_COUNT, &USER_COUNT, &DOMAIN_COUNT];
-        static C_GAUGES: &[&AtomicGauge] = &[&SERVER_MEMORY, &USER_COUNT, &DOMAIN_COUNT];
+        static E_GAUGES: &[&AtomicGauge] = &[
+            &SERVER_MEMORY,
+            &QUEUE_COUNT,
+            &USER_COUNT,
+            &DOMAIN_COUNT,
+            &DELIVERY_THROTTLED_DOMAINS,
+        ];
+        static C_GAUGES: &[&AtomicGauge] = &[
+            &SERVER_MEMORY,
+            &USER_COUNT,
+            &DOMAIN_COUNT,
+            &DELIVERY_THROTTLED_DOMAINS,
+        ];
 
         if is_enterprise { E_GAUGES } else { C_GAUGES }
             .iter()
@@ -315,6 +327,7 @@ impl Collector {
             MetricType::SieveRequestTime => CONNECTION_METRICS[CONN_SIEVE].elapsed.average(),
             MetricType::UserCount => USER_COUNT.get() as f64,
             MetricType::DomainCount => DOMAIN_COUNT.get() as f64,
+            MetricType::DeliveryThrottledDomains => DELIVERY_THROTTLED_DOMAINS.get() as f64,
         }
     }
 
@@ -324,6 +337,7 @@ impl Collector {
             MetricType::QueueCount => QUEUE_COUNT.set(value),
             MetricType::UserCount => USER_COUNT.set(value),
             MetricType::DomainCount => DOMAIN_COUNT.set(value),
+            MetricType::DeliveryThrottledDomains => DELIVERY_THROTTLED_DOMAINS.set(value),
             _ => {}
         }
     }
@@ -190,6 +190,7 @@ pub enum EventType {
     Ai(AiEvent),
     WebDav(WebDavEvent),
     Calendar(CalendarEvent),
+    Dns(DnsEvent),
 }
 
 #[event_type]
@@ -283,6 +284,14 @@ pub enum ImapEvent {
     Unsubscribe,
     Thread,
     GetQuota,
+    Compress,
+    GetMetadata,
+    SetMetadata,
+    Notify,
+    Replace,
+    GenUrlAuth,
+    ResetKey,
+    UrlFetch,
 
     // Errors
     Error,
@@ -373,6 +382,7 @@ pub enum SmtpEvent {
     SpfFromFail,
     DmarcPass,
     DmarcFail,
+    DmarcPolicyOverride,
     IprevPass,
     IprevFail,
     TooManyMessages,
@@ -393,6 +403,8 @@ pub enum SmtpEvent {
     RcptTo,
     RcptToDuplicate,
     RcptToRewritten,
+    SrsReversed,
+    SrsInvalid,
     RcptToMissing,
     RcptToGreylisted,
     TooManyRecipients,
@@ -406,6 +418,13 @@ pub enum SmtpEvent {
     Expn,
     ExpnNotFound,
     ExpnDisabled,
+    Etrn,
+    EtrnDisabled,
+    Atrn,
+    AtrnDisabled,
+    Burl,
+    BurlDisabled,
+    BurlFailed,
     RequireTlsDisabled,
     DeliverByDisabled,
     DeliverByInvalid,
@@ -449,6 +468,10 @@ pub enum DeliveryEvent {
     NullMx,
     Connect,
     ConnectError,
+    ConnectionReused,
+    ThrottleDecreased,
+    ThrottleRecovered,
+    ThrottleLimitExceeded,
     MissingOutboundHostname,
     GreetingFailed,
     Ehlo,
@@ -469,6 +492,7 @@ pub enum DeliveryEvent {
     ImplicitTlsError,
     ConcurrencyLimitExceeded,
     RateLimitExceeded,
+    Held,
     DoubleBounce,
     DsnSuccess,
     DsnTempFail,
@@ -491,6 +515,10 @@ pub enum QueueEvent {
     ConcurrencyLimitExceeded,
     QuotaExceeded,
     BackPressure,
+    SrsRewritten,
+    MessageHeld,
+    MessageReleased,
+    MessageRejected,
 }
 
 #[event_type]
@@ -774,6 +802,14 @@ pub enum DkimEvent {
     SignatureExpired,
     SignatureLength,
     SignerNotFound,
+    KeyGenerated,
+    KeyPublished,
+    KeyPublicationRequired,
+    KeyNotPropagated,
+    KeyActivated,
+    KeyRevoked,
+    KeyRevocationRequired,
+    KeyRotationFailed,
 }
 
 #[event_type]
@@ -962,6 +998,16 @@ pub enum AiEvent {
     ApiError,
 }
 
+#[event_type]
+pub enum DnsEvent {
+    RecordCreated,
+    RecordUpdated,
+    RecordDrift,
+    RecordUpdateFailed,
+    LookupFailed,
+    SyncCompleted,
+}
+
 #[event_type]
 pub enum WebDavEvent {
     // Requests
@@ -1028,6 +1074,7 @@ pub enum MetricType {
     SieveRequestTime,
     UserCount,
     DomainCount,
+    DeliveryThrottledDomains,
 }
 
 pub const TOTAL_EVENT_COUNT: usize = total_event_count!();
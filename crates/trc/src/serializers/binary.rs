@@ -898,6 +898,47 @@ impl EventType {
             EventType::Spam(SpamEvent::TrainStarted) => 588,
             EventType::Spam(SpamEvent::ModelLoaded) => 589,
             EventType::Store(StoreEvent::MeilisearchError) => 590,
+            EventType::Imap(ImapEvent::Compress) => 591,
+            EventType::Imap(ImapEvent::GetMetadata) => 592,
+            EventType::Imap(ImapEvent::SetMetadata) => 593,
+            EventType::Imap(ImapEvent::Notify) => 594,
+            EventType::Imap(ImapEvent::Replace) => 595,
+            EventType::Imap(ImapEvent::GenUrlAuth) => 596,
+            EventType::Imap(ImapEvent::ResetKey) => 597,
+            EventType::Imap(ImapEvent::UrlFetch) => 598,
+            EventType::Smtp(SmtpEvent::Etrn) => 599,
+            EventType::Smtp(SmtpEvent::EtrnDisabled) => 600,
+            EventType::Smtp(SmtpEvent::Atrn) => 601,
+            EventType::Smtp(SmtpEvent::AtrnDisabled) => 602,
+            EventType::Delivery(DeliveryEvent::Held) => 603,
+            EventType::Smtp(SmtpEvent::Burl) => 604,
+            EventType::Smtp(SmtpEvent::BurlDisabled) => 605,
+            EventType::Smtp(SmtpEvent::BurlFailed) => 606,
+            EventType::Smtp(SmtpEvent::SrsReversed) => 607,
+            EventType::Smtp(SmtpEvent::SrsInvalid) => 608,
+            EventType::Queue(QueueEvent::SrsRewritten) => 609,
+            EventType::Smtp(SmtpEvent::DmarcPolicyOverride) => 610,
+            EventType::Delivery(DeliveryEvent::ConnectionReused) => 611,
+            EventType::Queue(QueueEvent::MessageHeld) => 612,
+            EventType::Queue(QueueEvent::MessageReleased) => 613,
+            EventType::Queue(QueueEvent::MessageRejected) => 614,
+            EventType::Dns(DnsEvent::RecordCreated) => 615,
+            EventType::Dns(DnsEvent::RecordUpdated) => 616,
+            EventType::Dns(DnsEvent::RecordDrift) => 617,
+            EventType::Dns(DnsEvent::RecordUpdateFailed) => 618,
+            EventType::Dns(DnsEvent::LookupFailed) => 619,
+            EventType::Dns(DnsEvent::SyncCompleted) => 620,
+            EventType::Dkim(DkimEvent::KeyGenerated) => 621,
+            EventType::Dkim(DkimEvent::KeyPublished) => 622,
+            EventType::Dkim(DkimEvent::KeyPublicationRequired) => 623,
+            EventType::Dkim(DkimEvent::KeyNotPropagated) => 624,
+            EventType::Dkim(DkimEvent::KeyActivated) => 625,
+            EventType::Dkim(DkimEvent::KeyRevoked) => 626,
+            EventType::Dkim(DkimEvent::KeyRevocationRequired) => 627,
+            EventType::Dkim(DkimEvent::KeyRotationFailed) => 628,
+            EventType::Delivery(DeliveryEvent::ThrottleDecreased) => 629,
+            EventType::Delivery(DeliveryEvent::ThrottleRecovered) => 630,
+            EventType::Delivery(DeliveryEvent::ThrottleLimitExceeded) => 631,
         }
     }
 
@@ -1534,6 +1575,47 @@ impl EventType {
             588 => Some(EventType::Spam(SpamEvent::TrainStarted)),
             589 => Some(EventType::Spam(SpamEvent::ModelLoaded)),
             590 => Some(EventType::Store(StoreEvent::MeilisearchError)),
+            591 => Some(EventType::Imap(ImapEvent::Compress)),
+            592 => Some(EventType::Imap(ImapEvent::GetMetadata)),
+            593 => Some(EventType::Imap(ImapEvent::SetMetadata)),
+            594 => Some(EventType::Imap(ImapEvent::Notify)),
+            595 => Some(EventType::Imap(ImapEvent::Replace)),
+            596 => Some(EventType::Imap(ImapEvent::GenUrlAuth)),
+            597 => Some(EventType::Imap(ImapEvent::ResetKey)),
+            598 => Some(EventType::Imap(ImapEvent::UrlFetch)),
+            599 => Some(EventType::Smtp(SmtpEvent::Etrn)),
+            600 => Some(EventType::Smtp(SmtpEvent::EtrnDisabled)),
+            601 => Some(EventType::Smtp(SmtpEvent::Atrn)),
+            602 => Some(EventType::Smtp(SmtpEvent::AtrnDisabled)),
+            603 => Some(EventType::Delivery(DeliveryEvent::Held)),
+            604 => Some(EventType::Smtp(SmtpEvent::Burl)),
+            605 => Some(EventType::Smtp(SmtpEvent::BurlDisabled)),
+            606 => Some(EventType::Smtp(SmtpEvent::BurlFailed)),
+            607 => Some(EventType::Smtp(SmtpEvent::SrsReversed)),
+            608 => Some(EventType::Smtp(SmtpEvent::SrsInvalid)),
+            609 => Some(EventType::Queue(QueueEvent::SrsRewritten)),
+            610 => Some(EventType::Smtp(SmtpEvent::DmarcPolicyOverride)),
+            611 => Some(EventType::Delivery(DeliveryEvent::ConnectionReused)),
+            612 => Some(EventType::Queue(QueueEvent::MessageHeld)),
+            613 => Some(EventType::Queue(QueueEvent::MessageReleased)),
+            614 => Some(EventType::Queue(QueueEvent::MessageRejected)),
+            615 => Some(EventType::Dns(DnsEvent::RecordCreated)),
+            616 => Some(EventType::Dns(DnsEvent::RecordUpdated)),
+            617 => Some(EventType::Dns(DnsEvent::RecordDrift)),
+            618 => Some(EventType::Dns(DnsEvent::RecordUpdateFailed)),
+            619 => Some(EventType::Dns(DnsEvent::LookupFailed)),
+            620 => Some(EventType::Dns(DnsEvent::SyncCompleted)),
+            621 => Some(EventType::Dkim(DkimEvent::KeyGenerated)),
+            622 => Some(EventType::Dkim(DkimEvent::KeyPublished)),
+            623 => Some(EventType::Dkim(DkimEvent::KeyPublicationRequired)),
+            624 => Some(EventType::Dkim(DkimEvent::KeyNotPropagated)),
+            625 => Some(EventType::Dkim(DkimEvent::KeyActivated)),
+            626 => Some(EventType::Dkim(DkimEvent::KeyRevoked)),
+            627 => Some(EventType::Dkim(DkimEvent::KeyRevocationRequired)),
+            628 => Some(EventType::Dkim(DkimEvent::KeyRotationFailed)),
+            629 => Some(EventType::Delivery(DeliveryEvent::ThrottleDecreased)),
+            630 => Some(EventType::Delivery(DeliveryEvent::ThrottleRecovered)),
+            631 => Some(EventType::Delivery(DeliveryEvent::ThrottleLimitExceeded)),
             _ => None,
         }
     }
@@ -42,12 +42,17 @@ pub enum EmailField {
     Metadata,
     Threading,
     DeletedAt,
+    SaveDate,
+    Snooze,
+    SmimeStatus,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u8)]
 pub enum MailboxField {
     UidCounter,
+    Metadata,
+    UrlAuthKey,
     Archive,
 }
 
@@ -83,6 +88,11 @@ pub enum PrincipalField {
     DefaultAddressBookId,
     ActiveScriptId,
     PushSubscriptions,
+    Metadata,
+    VirtualMailboxes,
+    VirtualMailboxUids,
+    Pop3Policy,
+    Pop3State,
 }
 
 impl From<ContactField> for u8 {
@@ -120,6 +130,9 @@ impl From<EmailField> for u8 {
             EmailField::Metadata => 71,
             EmailField::Threading => 90,
             EmailField::DeletedAt => 91,
+            EmailField::SaveDate => 92,
+            EmailField::Snooze => 93,
+            EmailField::SmimeStatus => 94,
             EmailField::Archive => ARCHIVE_FIELD,
         }
     }
@@ -129,6 +142,8 @@ impl From<MailboxField> for u8 {
     fn from(value: MailboxField) -> Self {
         match value {
             MailboxField::UidCounter => 84,
+            MailboxField::Metadata => 85,
+            MailboxField::UrlAuthKey => 86,
             MailboxField::Archive => ARCHIVE_FIELD,
         }
     }
@@ -162,6 +177,11 @@ impl From<PrincipalField> for u8 {
             PrincipalField::DefaultAddressBookId => 48,
             PrincipalField::ActiveScriptId => 49,
             PrincipalField::PushSubscriptions => 44,
+            PrincipalField::Metadata => 52,
+            PrincipalField::VirtualMailboxes => 53,
+            PrincipalField::Pop3Policy => 54,
+            PrincipalField::Pop3State => 55,
+            PrincipalField::VirtualMailboxUids => 56,
             PrincipalField::Archive => ARCHIVE_FIELD,
         }
     }
@@ -0,0 +1,78 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use imap_proto::ResponseType;
+
+use super::{AssertResult, ImapConnection, Type};
+
+pub async fn test(imap: &mut ImapConnection, imap_check: &mut ImapConnection) {
+    println!("Running METADATA tests...");
+
+    // Set and retrieve mailbox entries
+    imap.send(concat!(
+        "SETMETADATA INBOX (/private/comment \"My own comment\" ",
+        "/shared/comment \"Shared comment\" /private/vendor/test/color \"blue\")"
+    ))
+    .await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap_check
+        .send("GETMETADATA INBOX (/private/comment /shared/comment /private/missing)")
+        .await;
+    imap_check
+        .assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("/private/comment \"My own comment\"")
+        .assert_contains("/shared/comment \"Shared comment\"")
+        .assert_contains("/private/missing NIL");
+
+    // Depth and size limits
+    imap.send("GETMETADATA (DEPTH infinity MAXSIZE 4) INBOX /private/vendor")
+        .await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("/private/vendor/test/color \"blue\"");
+    imap.send("GETMETADATA (MAXSIZE 5) INBOX /private/comment")
+        .await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("[METADATA LONGENTRIES 14]");
+
+    // Remove entries
+    imap.send("SETMETADATA INBOX (/private/comment NIL)").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap.send("GETMETADATA INBOX /private/comment").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("/private/comment NIL");
+
+    // Server entries
+    imap.send("SETMETADATA \"\" (/private/comment \"Server comment\")")
+        .await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap.send("GETMETADATA \"\" /private/comment").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("* METADATA \"\" (/private/comment \"Server comment\")");
+    imap.send("SETMETADATA \"\" (/shared/comment \"Server comment\")")
+        .await;
+    imap.assert_read(Type::Tagged, ResponseType::No)
+        .await
+        .assert_response_code("NOPERM");
+
+    // Special use entries are read-only
+    imap.send("SETMETADATA INBOX (/private/specialuse \"\\\\Trash\")")
+        .await;
+    imap.assert_read(Type::Tagged, ResponseType::No).await;
+
+    // Invalid entry names and non-existent mailboxes
+    imap.send("GETMETADATA INBOX /comment").await;
+    imap.assert_read(Type::Tagged, ResponseType::Bad).await;
+    imap.send("GETMETADATA \"Does not exist\" /private/comment")
+        .await;
+    imap.assert_read(Type::Tagged, ResponseType::No)
+        .await
+        .assert_response_code("NONEXISTENT");
+}
@@ -15,10 +15,17 @@ pub mod fetch;
 pub mod idle;
 pub mod mailbox;
 pub mod managesieve;
+pub mod metadata;
+pub mod notify;
 pub mod pop;
+pub mod replace;
+pub mod savedate;
 pub mod search;
 pub mod store;
 pub mod thread;
+pub mod uidonly;
+pub mod urlauth;
+pub mod virtual_mailbox;
 
 use crate::{
     AssertConfig, add_test_certs,
@@ -102,6 +109,13 @@ pub async fn imap_tests() {
     idle::test(&mut imap, &mut imap_check, false).await;
     condstore::test(&mut imap, &mut imap_check).await;
     acl::test(&mut imap, &mut imap_check).await;
+    metadata::test(&mut imap, &mut imap_check).await;
+    notify::test(&mut imap, &mut imap_check).await;
+    replace::test(&mut imap, &mut imap_check).await;
+    urlauth::test(&mut imap, &mut imap_check).await;
+    uidonly::test(&mut imap, &mut imap_check).await;
+    savedate::test(&mut imap, &mut imap_check, &handle).await;
+    virtual_mailbox::test(&mut imap, &mut imap_check, &handle).await;
 
     // Logout
     for imap in [&mut imap, &mut imap_check] {
@@ -119,7 +133,7 @@ pub async fn imap_tests() {
     managesieve::test().await;
 
     // Run POP3 tests
-    pop::test().await;
+    pop::test(&handle).await;
 
     // Print elapsed time
     let elapsed = start_time.elapsed();
@@ -366,6 +380,14 @@ impl ImapConnection {
         }
     }
 
+    pub async fn assert_no_response(&mut self) {
+        if let Ok(result) =
+            tokio::time::timeout(Duration::from_millis(500), self.reader.next_line()).await
+        {
+            panic!("Expected no response from server but got: {:?}", result);
+        }
+    }
+
     pub async fn read(&mut self, t: Type) -> Vec<String> {
         let mut lines = Vec::new();
         loop {
@@ -0,0 +1,140 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use imap_proto::ResponseType;
+
+use super::{AssertResult, ImapConnection, Type};
+
+pub async fn test(imap: &mut ImapConnection, imap_check: &mut ImapConnection) {
+    println!("Running NOTIFY tests...");
+
+    // Message annotations are not supported
+    imap_check
+        .send("NOTIFY SET (selected (MessageNew MessageExpunge AnnotationChange))")
+        .await;
+    imap_check
+        .assert_read(Type::Tagged, ResponseType::No)
+        .await
+        .assert_contains("[BADEVENT (AnnotationChange)]");
+    imap_check.send("NOTIFY SET (selected (FlagChange))").await;
+    imap_check
+        .assert_read(Type::Tagged, ResponseType::Bad)
+        .await;
+
+    // Track the whole account from a single connection
+    imap_check
+        .send(concat!(
+            "NOTIFY SET (selected (MessageNew MessageExpunge FlagChange)) ",
+            "(personal (MessageNew MessageExpunge MailboxName SubscriptionChange ",
+            "MailboxMetadataChange ServerMetadataChange))"
+        ))
+        .await;
+    imap_check.assert_read(Type::Tagged, ResponseType::Ok).await;
+
+    // Mailbox creation
+    imap.send("CREATE Gorgonzola").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap_check
+        .assert_read(Type::Status, ResponseType::Ok)
+        .await
+        .assert_contains("LIST () \"/\" \"Gorgonzola\"");
+
+    // New messages in a non-selected mailbox
+    let message = "From: test@domain.com\nSubject: Test\n\nTest message\n";
+    imap.send(&format!("APPEND Gorgonzola {{{}}}", message.len()))
+        .await;
+    imap.assert_read(Type::Continuation, ResponseType::Ok).await;
+    imap.send_untagged(message).await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap_check
+        .assert_read(Type::Status, ResponseType::Ok)
+        .await
+        .assert_contains("STATUS \"Gorgonzola\"")
+        .assert_contains("MESSAGES 1")
+        .assert_contains("UNSEEN 1");
+
+    // Subscription changes
+    imap.send("SUBSCRIBE Gorgonzola").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap_check
+        .assert_read(Type::Status, ResponseType::Ok)
+        .await
+        .assert_contains("LIST (\\Subscribed) \"/\" \"Gorgonzola\"");
+
+    // Mailbox and server metadata changes
+    imap.send("SETMETADATA Gorgonzola (/shared/comment \"Blue cheese\")")
+        .await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap_check
+        .assert_read(Type::Status, ResponseType::Ok)
+        .await
+        .assert_contains("* METADATA \"Gorgonzola\" /shared/comment");
+    imap.send("SETMETADATA \"\" (/private/vendor/test/cheese \"Gorgonzola\")")
+        .await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap_check
+        .assert_read(Type::Status, ResponseType::Ok)
+        .await
+        .assert_contains("* METADATA \"\" /private/vendor/test/cheese");
+
+    // Renames
+    imap.send("RENAME Gorgonzola Taleggio").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap_check
+        .assert_read(Type::Status, ResponseType::Ok)
+        .await
+        .assert_contains("\"Taleggio\" (\"OLDNAME\" (\"Gorgonzola\"))");
+
+    // Changes to the selected mailbox
+    imap_check.send("SELECT Taleggio").await;
+    imap_check.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap.send(&format!("APPEND Taleggio {{{}}}", message.len()))
+        .await;
+    imap.assert_read(Type::Continuation, ResponseType::Ok).await;
+    imap.send_untagged(message).await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap_check
+        .assert_read(Type::Status, ResponseType::Ok)
+        .await
+        .assert_contains("* 2 EXISTS");
+
+    // Expunges are delayed with selected-delayed until a command allows them
+    imap_check
+        .send(concat!(
+            "NOTIFY SET (selected-delayed (MessageNew MessageExpunge)) ",
+            "(personal (MessageNew MessageExpunge MailboxName SubscriptionChange))"
+        ))
+        .await;
+    imap_check.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap.send("SELECT Taleggio").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap.send("STORE 1 +FLAGS (\\Deleted)").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap.send("EXPUNGE").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap_check.assert_no_response().await;
+    imap_check.send("NOOP").await;
+    imap_check
+        .assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("* 1 EXPUNGE");
+    imap.send("UNSELECT").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+
+    // Mailbox deletion
+    imap_check.send("UNSELECT").await;
+    imap_check.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap.send("DELETE Taleggio").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap_check
+        .assert_read(Type::Status, ResponseType::Ok)
+        .await
+        .assert_contains("LIST (\\NonExistent) \"/\" \"Taleggio\"");
+
+    // Disable notifications
+    imap_check.send("NOTIFY NONE").await;
+    imap_check.assert_read(Type::Tagged, ResponseType::Ok).await;
+}
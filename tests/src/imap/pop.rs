@@ -4,7 +4,10 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use crate::{jmap::mail::delivery::SmtpConnection, smtp::session::VerifyResponse};
+use crate::{imap::IMAPTest, jmap::mail::delivery::SmtpConnection, smtp::session::VerifyResponse};
+use common::config::pop3::Pop3Policy;
+use directory::backend::internal::manage::ManageDirectory;
+use email::pop3::Pop3Store;
 use mail_send::smtp::tls::build_tls_connector;
 use rustls_pki_types::ServerName;
 use std::time::Duration;
@@ -14,7 +17,7 @@ use tokio::{
 };
 use tokio_rustls::client::TlsStream;
 
-pub async fn test() {
+pub async fn test(handle: &IMAPTest) {
     println!("Running POP3 tests...");
 
     // Send 3 test emails
@@ -186,6 +189,150 @@ pub async fn test() {
         .await
         .assert_contains("+OK 0 0");
     pop3.send("QUIT").await;
+
+    // Hide downloaded messages
+    let account_id = handle
+        .server
+        .store()
+        .get_principal_id("popper@example.com")
+        .await
+        .unwrap()
+        .unwrap();
+    handle
+        .server
+        .set_pop3_policy(
+            account_id,
+            Some(Pop3Policy {
+                hide_downloaded: true,
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+    for i in 0..2 {
+        let mut lmtp = SmtpConnection::connect_port(11201).await;
+        lmtp.ingest(
+            "bill@example.com",
+            &["popper@example.com"],
+            &format!(
+                concat!(
+                    "From: bill@example.com\r\n",
+                    "To: popper@example.com\r\n",
+                    "Subject: Retention {}\r\n",
+                    "X-Spam-Status: No\r\n",
+                    "\r\n",
+                    "Retention test.\r\n",
+                ),
+                i
+            ),
+        )
+        .await;
+    }
+    let mut pop3 = Pop3Connection::connect_and_login().await;
+    pop3.send("STAT").await;
+    pop3.assert_read(ResponseType::Ok)
+        .await
+        .assert_contains("+OK 2 ");
+    pop3.send("RETR 1").await;
+    pop3.assert_read(ResponseType::Multiline)
+        .await
+        .assert_contains("Subject: Retention 0");
+    pop3.send("TOP 2 0").await;
+    pop3.assert_read(ResponseType::Multiline)
+        .await
+        .assert_contains("Subject: Retention 1");
+    pop3.send("QUIT").await;
+    pop3.assert_read(ResponseType::Ok).await;
+    let mut pop3 = Pop3Connection::connect_and_login().await;
+    pop3.send("STAT").await;
+    pop3.assert_read(ResponseType::Ok)
+        .await
+        .assert_contains("+OK 1 ");
+    pop3.send("TOP 1 0").await;
+    pop3.assert_read(ResponseType::Multiline)
+        .await
+        .assert_contains("Subject: Retention 1");
+    pop3.send("QUIT").await;
+    pop3.assert_read(ResponseType::Ok).await;
+
+    // Downloads from concurrent sessions are all recorded
+    for i in 2..4 {
+        let mut lmtp = SmtpConnection::connect_port(11201).await;
+        lmtp.ingest(
+            "bill@example.com",
+            &["popper@example.com"],
+            &format!(
+                concat!(
+                    "From: bill@example.com\r\n",
+                    "To: popper@example.com\r\n",
+                    "Subject: Retention {}\r\n",
+                    "X-Spam-Status: No\r\n",
+                    "\r\n",
+                    "Retention test.\r\n",
+                ),
+                i
+            ),
+        )
+        .await;
+    }
+    let mut pop3 = Pop3Connection::connect_and_login().await;
+    let mut pop3_other = Pop3Connection::connect_and_login().await;
+    pop3.send("RETR 2").await;
+    pop3.assert_read(ResponseType::Multiline)
+        .await
+        .assert_contains("Subject: Retention 2");
+    pop3_other.send("RETR 3").await;
+    pop3_other
+        .assert_read(ResponseType::Multiline)
+        .await
+        .assert_contains("Subject: Retention 3");
+    pop3.send("QUIT").await;
+    pop3.assert_read(ResponseType::Ok).await;
+    pop3_other.send("QUIT").await;
+    pop3_other.assert_read(ResponseType::Ok).await;
+    let mut pop3 = Pop3Connection::connect_and_login().await;
+    pop3.send("STAT").await;
+    pop3.assert_read(ResponseType::Ok)
+        .await
+        .assert_contains("+OK 1 ");
+    pop3.send("QUIT").await;
+    pop3.assert_read(ResponseType::Ok).await;
+
+    // Expire downloaded messages
+    handle
+        .server
+        .set_pop3_policy(
+            account_id,
+            Some(Pop3Policy {
+                expire_after: Some(0),
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+    let mut pop3 = Pop3Connection::connect_and_login().await;
+    pop3.send("STAT").await;
+    pop3.assert_read(ResponseType::Ok)
+        .await
+        .assert_contains("+OK 1 ");
+    pop3.send("QUIT").await;
+    pop3.assert_read(ResponseType::Ok).await;
+
+    // Expired messages are removed from the server
+    handle
+        .server
+        .set_pop3_policy(account_id, None)
+        .await
+        .unwrap();
+    let mut pop3 = Pop3Connection::connect_and_login().await;
+    pop3.send("STAT").await;
+    pop3.assert_read(ResponseType::Ok)
+        .await
+        .assert_contains("+OK 1 ");
+    pop3.send("DELE 1").await;
+    pop3.assert_read(ResponseType::Ok).await;
+    pop3.send("QUIT").await;
+    pop3.assert_read(ResponseType::Ok).await;
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
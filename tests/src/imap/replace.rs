@@ -0,0 +1,89 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use imap_proto::ResponseType;
+
+use super::{AssertResult, ImapConnection, Type};
+
+pub async fn test(imap: &mut ImapConnection, _imap_check: &mut ImapConnection) {
+    println!("Running REPLACE tests...");
+
+    // Create a draft
+    imap.send("CREATE Autosave").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap.send("SELECT Autosave").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    let draft = "From: test@domain.com\nSubject: Draft\n\nFirst version\n";
+    imap.send(&format!("APPEND Autosave (\\Draft) {{{}}}", draft.len()))
+        .await;
+    imap.assert_read(Type::Continuation, ResponseType::Ok).await;
+    imap.send_untagged(draft).await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+
+    // Replace the draft
+    let draft = "From: test@domain.com\nSubject: Draft\n\nSecond version\n";
+    imap.send(&format!("REPLACE 1 Autosave (\\Draft) {{{}}}", draft.len()))
+        .await;
+    imap.assert_read(Type::Continuation, ResponseType::Ok).await;
+    imap.send_untagged(draft).await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("[APPENDUID ")
+        .assert_contains("* 1 EXPUNGE");
+
+    // Only the new version must remain
+    imap.send("UID FETCH 1:* (UID FLAGS)").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_count("FETCH (", 1)
+        .assert_contains("UID 2 ")
+        .assert_contains("\\Draft");
+
+    // Replace using UIDs
+    imap.send(&format!("UID REPLACE 2 Autosave {{{}}}", draft.len()))
+        .await;
+    imap.assert_read(Type::Continuation, ResponseType::Ok).await;
+    imap.send_untagged(draft).await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("[APPENDUID ")
+        .assert_contains("* 1 EXPUNGE");
+
+    // Non-existent messages and mailboxes
+    imap.send(&format!("UID REPLACE 2 Autosave {{{}}}", draft.len()))
+        .await;
+    imap.assert_read(Type::Continuation, ResponseType::Ok).await;
+    imap.send_untagged(draft).await;
+    imap.assert_read(Type::Tagged, ResponseType::Bad).await;
+    imap.send(&format!("REPLACE 1 \"Does not exist\" {{{}}}", draft.len()))
+        .await;
+    imap.assert_read(Type::Continuation, ResponseType::Ok).await;
+    imap.send_untagged(draft).await;
+    imap.assert_read(Type::Tagged, ResponseType::No)
+        .await
+        .assert_response_code("TRYCREATE");
+
+    // Sets resolving to more than one message are rejected
+    imap.send(&format!("APPEND Autosave {{{}}}", draft.len()))
+        .await;
+    imap.assert_read(Type::Continuation, ResponseType::Ok).await;
+    imap.send_untagged(draft).await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap.send(&format!("REPLACE 1:* Autosave {{{}}}", draft.len()))
+        .await;
+    imap.assert_read(Type::Continuation, ResponseType::Ok).await;
+    imap.send_untagged(draft).await;
+    imap.assert_read(Type::Tagged, ResponseType::Bad).await;
+    imap.send("UID FETCH 1:* (UID)").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_count("FETCH (", 2);
+
+    imap.send("UNSELECT").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap.send("DELETE Autosave").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+}
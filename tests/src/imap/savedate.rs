@@ -0,0 +1,126 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use directory::backend::internal::manage::ManageDirectory;
+use email::{
+    cache::{MessageCacheFetch, mailbox::MailboxCacheAccess},
+    message::savedate::SaveDateBatch,
+};
+use imap_proto::ResponseType;
+use store::write::BatchBuilder;
+use types::collection::Collection;
+
+use super::{AssertResult, IMAPTest, ImapConnection, Type};
+
+pub async fn test(imap: &mut ImapConnection, _imap_check: &mut ImapConnection, handle: &IMAPTest) {
+    println!("Running SAVEDATE tests...");
+
+    // Append a message with an old internal date
+    imap.send("CREATE SaveDate").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    let message = "From: test@domain.com\r\nSubject: Old message\r\n\r\nTest\r\n";
+    imap.send(&format!(
+        "APPEND SaveDate \"01-Feb-1994 08:00:00 +0000\" {{{}+}}\r\n{message}",
+        message.len()
+    ))
+    .await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+
+    // The save date is the time the message was added to the mailbox
+    imap.send("SELECT SaveDate").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap.send("FETCH 1 (INTERNALDATE SAVEDATE)").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("INTERNALDATE \"01-Feb-1994 08:00:00 +0000\"")
+        .assert_contains("SAVEDATE \"")
+        .assert_count("SAVEDATE \"01-Feb-1994", 0);
+
+    // Search by save date
+    imap.send("UID SEARCH RETURN (COUNT) SAVEDSINCE 1-Feb-2020")
+        .await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("COUNT 1");
+    imap.send("UID SEARCH RETURN (COUNT) SAVEDBEFORE 1-Feb-2020")
+        .await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("COUNT 0");
+    imap.send("UID SEARCH RETURN (COUNT) SINCE 1-Feb-2020")
+        .await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("COUNT 0");
+    imap.send("UID SEARCH RETURN (COUNT) SAVEDATESUPPORTED")
+        .await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("COUNT 1");
+
+    // Messages stored without a save date fall back to their internal date
+    let account_id = handle
+        .server
+        .store()
+        .get_principal_id("jdoe@example.com")
+        .await
+        .unwrap()
+        .unwrap();
+    let cache = handle.server.get_cached_messages(account_id).await.unwrap();
+    let mailbox_id = cache.mailbox_by_path("SaveDate").unwrap().document_id;
+    let (document_id, uid) = cache
+        .emails
+        .items
+        .iter()
+        .find_map(|item| {
+            item.mailboxes
+                .iter()
+                .find(|m| m.mailbox_id == mailbox_id)
+                .map(|m| (item.document_id, m.uid))
+        })
+        .unwrap();
+    let mut batch = BatchBuilder::new();
+    batch
+        .with_account_id(account_id)
+        .with_collection(Collection::Email)
+        .with_document(document_id)
+        .clear_save_date(mailbox_id, uid);
+    handle
+        .server
+        .store()
+        .write(batch.build_all())
+        .await
+        .unwrap();
+    imap.send("FETCH 1 (SAVEDATE)").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("SAVEDATE \"01-Feb-1994 08:00:00 +0000\"");
+    imap.send("UID SEARCH RETURN (COUNT) SAVEDBEFORE 1-Feb-2020")
+        .await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("COUNT 1");
+
+    // Moved messages are assigned a new save date
+    imap.send("CREATE SaveDateCopy").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap.send("UID MOVE 1 SaveDateCopy").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap.send("SELECT SaveDateCopy").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap.send("UID FETCH 1 (SAVEDATE)").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("SAVEDATE \"");
+
+    // Clean up
+    imap.send("UNSELECT").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    for mailbox in ["SaveDate", "SaveDateCopy"] {
+        imap.send(&format!("DELETE {mailbox}")).await;
+        imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    }
+}
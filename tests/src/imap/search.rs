@@ -106,6 +106,61 @@ pub async fn test(imap: &mut ImapConnection, imap_check: &mut ImapConnection, ha
         .await
         .assert_contains("MIN 2 MAX 9");
 
+    // Paged results
+    imap_check
+        .send("UID SEARCH RETURN (COUNT PARTIAL 1:3) ALL")
+        .await;
+    imap_check
+        .assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("COUNT 10 PARTIAL (1:3 1:3)");
+    imap_check
+        .send("UID SEARCH RETURN (PARTIAL -1:-4) NOT $")
+        .await;
+    imap_check
+        .assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("PARTIAL (-1:-4 2,5,7,9)");
+    imap_check
+        .send("UID SEARCH RETURN (PARTIAL 11:20) ALL")
+        .await;
+    imap_check
+        .assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("PARTIAL (11:20 NIL)");
+    imap_check
+        .send("UID FETCH 1:* (FLAGS) (PARTIAL -1:-2)")
+        .await;
+    imap_check
+        .assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_count("FLAGS", 2)
+        .assert_contains("* 9 FETCH")
+        .assert_contains("* 10 FETCH");
+    imap_check.send("FETCH 1:* (FLAGS) (PARTIAL -1:-2)").await;
+    imap_check
+        .assert_read(Type::Tagged, ResponseType::Bad)
+        .await;
+    imap_check
+        .send("UID SEARCH RETURN (MIN MAX PARTIAL 1:2) NOT $")
+        .await;
+    imap_check
+        .assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("MIN 2 MAX 9 PARTIAL (1:2 2,5)");
+    imap_check
+        .send("UID SEARCH RETURN (SAVE PARTIAL -1:-2) NOT $")
+        .await;
+    imap_check
+        .assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("PARTIAL (-1:-2 7,9)");
+    imap_check.send("UID SEARCH $").await;
+    imap_check
+        .assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_equals("* SEARCH 7 9");
+
     // Sort
     imap_check
         .send("UID SORT (REVERSE SUBJECT REVERSE DATE) UTF-8 FROM Nathaniel")
@@ -124,4 +179,14 @@ pub async fn test(imap: &mut ImapConnection, imap_check: &mut ImapConnection, ha
         } else {
             "COUNT 10 ALL 9,3,7:8,2,6,4:5,1,10"
         }); //6,4:5,1,10,9,3,7:8,2");
+
+    imap.send("UID SORT RETURN (PARTIAL 1:2) (DATE SUBJECT) UTF-8 ALL")
+        .await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains(if !handle.server.search_store().is_mysql() {
+            "PARTIAL (1:2 6,4)"
+        } else {
+            "PARTIAL (1:2 9,3)"
+        });
 }
@@ -0,0 +1,88 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use imap_proto::ResponseType;
+
+use super::{AssertResult, ImapConnection, Type};
+
+pub async fn test(imap: &mut ImapConnection, _imap_check: &mut ImapConnection) {
+    println!("Running UIDONLY tests...");
+
+    // Create a mailbox with a few messages
+    imap.send("CREATE UidOnly").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    for num in 1..=3 {
+        let message = format!("From: test@domain.com\r\nSubject: Message {num}\r\n\r\nTest\r\n");
+        imap.send(&format!(
+            "APPEND UidOnly {{{}+}}\r\n{message}",
+            message.len()
+        ))
+        .await;
+        imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    }
+
+    // UIDONLY can only be enabled before selecting a mailbox
+    let mut imap_uid = ImapConnection::connect(b"_u ").await;
+    imap_uid.assert_read(Type::Untagged, ResponseType::Ok).await;
+    imap_uid.authenticate("jdoe@example.com", "secret").await;
+    imap_uid.send("ENABLE UIDONLY").await;
+    imap_uid
+        .assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("* ENABLED UIDONLY");
+    imap_uid.send("SELECT UidOnly").await;
+    imap_uid
+        .assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("3 EXISTS");
+
+    // Commands using sequence numbers are rejected
+    for command in [
+        "FETCH 1:* (FLAGS)",
+        "STORE 1 +FLAGS (\\Seen)",
+        "SEARCH ALL",
+        "COPY 1 INBOX",
+        "UID SEARCH 1:2",
+    ] {
+        imap_uid.send(command).await;
+        imap_uid
+            .assert_read(Type::Tagged, ResponseType::Bad)
+            .await
+            .assert_contains("[UIDREQUIRED]");
+    }
+
+    // FETCH responses are returned as UIDFETCH
+    imap_uid.send("UID FETCH 1:* (FLAGS)").await;
+    imap_uid
+        .assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_count("UIDFETCH", 3)
+        .assert_contains("* 1 UIDFETCH (FLAGS ())")
+        .assert_count(" FETCH ", 0);
+    imap_uid.send("UID STORE 2 +FLAGS (\\Deleted)").await;
+    imap_uid
+        .assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("* 2 UIDFETCH (FLAGS (\\Deleted))");
+    imap_uid.send("UID SEARCH UID 2:3").await;
+    imap_uid
+        .assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("2 3");
+
+    // Expunged messages are reported with VANISHED
+    imap_uid.send("UID EXPUNGE 2").await;
+    imap_uid
+        .assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("* VANISHED 2");
+
+    // Clean up
+    imap_uid.send("UNSELECT").await;
+    imap_uid.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap.send("DELETE UidOnly").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+}
@@ -0,0 +1,124 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use imap_proto::ResponseType;
+
+use super::{AssertResult, ImapConnection, Type};
+
+pub async fn test(imap: &mut ImapConnection, imap_check: &mut ImapConnection) {
+    println!("Running CATENATE and URLAUTH tests...");
+
+    // Append a message with an attachment
+    imap.send("CREATE Compose").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    let message = concat!(
+        "From: test@domain.com\r\n",
+        "Subject: Report\r\n",
+        "Content-Type: multipart/mixed; boundary=\"b1\"\r\n\r\n",
+        "--b1\r\n",
+        "Content-Type: text/plain\r\n\r\n",
+        "See attached.\r\n",
+        "--b1\r\n",
+        "Content-Type: application/octet-stream\r\n",
+        "Content-Transfer-Encoding: base64\r\n\r\n",
+        "QXR0YWNobWVudCBjb250ZW50cw==\r\n",
+        "--b1--\r\n"
+    );
+    imap.send(&format!(
+        "APPEND Compose {{{}+}}\r\n{message}",
+        message.len()
+    ))
+    .await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+
+    // Forward the attachment without downloading it
+    let text = concat!(
+        "From: test@domain.com\r\n",
+        "Subject: Fwd: Report\r\n",
+        "Content-Type: multipart/mixed; boundary=\"b2\"\r\n\r\n",
+        "--b2\r\n"
+    );
+    imap.send(&format!(
+        concat!(
+            "APPEND Compose CATENATE (TEXT {{{}+}}\r\n{} ",
+            "URL \"/Compose/;UID=1/;SECTION=2.MIME\" ",
+            "URL \"/Compose/;UID=1/;SECTION=2\" TEXT {{10+}}\r\n\r\n--b2--\r\n)"
+        ),
+        text.len(),
+        text
+    ))
+    .await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("[APPENDUID ");
+    imap.send("SELECT Compose").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap.send("UID FETCH 2 (BODY.PEEK[HEADER.FIELDS (SUBJECT)] BINARY.PEEK[1])")
+        .await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("Subject: Fwd: Report")
+        .assert_contains("Attachment contents");
+
+    // Invalid URLs are rejected
+    imap.send("APPEND Compose CATENATE (URL \"/Compose/;UID=100\")")
+        .await;
+    imap.assert_read(Type::Tagged, ResponseType::No)
+        .await
+        .assert_response_code("BADURL /Compose/;UID=100");
+
+    // Generate an authorized URL
+    let url = "imap://jdoe%40example.com@localhost/Compose/;UID=1/;SECTION=1;URLAUTH=authuser";
+    imap.send(&format!("GENURLAUTH \"{url}\" INTERNAL")).await;
+    let url_auth = imap
+        .assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .into_iter()
+        .find_map(|line| {
+            line.strip_prefix("* GENURLAUTH \"")
+                .and_then(|line| line.strip_suffix('"'))
+                .map(|line| line.to_string())
+        })
+        .expect("Missing GENURLAUTH response");
+    assert!(url_auth.starts_with(&format!("{url}:INTERNAL:")));
+
+    // Fetch the URL from another session
+    imap_check
+        .send(&format!(
+            "URLFETCH \"{url_auth}\" \"{url}:INTERNAL:0123456789abcdef\""
+        ))
+        .await;
+    imap_check
+        .assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("See attached.")
+        .assert_contains(":INTERNAL:0123456789abcdef\" NIL");
+
+    // Resetting the key invalidates the URL
+    imap.send("RESETKEY Compose INTERNAL").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap_check.send(&format!("URLFETCH \"{url_auth}\"")).await;
+    imap_check
+        .assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("\" NIL");
+
+    // URLs can only be generated for the current user
+    imap.send(
+        "GENURLAUTH \"imap://bill%40example.com@localhost/Compose/;UID=1;URLAUTH=anonymous\" INTERNAL",
+    )
+    .await;
+    imap.assert_read(Type::Tagged, ResponseType::No).await;
+    imap.send(&format!("GENURLAUTH \"{url}\" XSAMPLE")).await;
+    imap.assert_read(Type::Tagged, ResponseType::No).await;
+    imap.send("RESETKEY").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+
+    imap.send("UNSELECT").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap.send("DELETE Compose").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+}
@@ -0,0 +1,166 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use super::{AssertResult, ImapConnection, Type};
+use crate::imap::IMAPTest;
+use directory::backend::internal::manage::ManageDirectory;
+use email::mailbox::virtual_mailbox::VirtualMailboxStore;
+use imap_proto::ResponseType;
+
+pub async fn test(imap: &mut ImapConnection, imap_check: &mut ImapConnection, handle: &IMAPTest) {
+    println!("Running virtual mailbox tests...");
+
+    // Append a flagged and an unflagged message
+    imap.send("CREATE VirtualSource").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    for (flags, subject) in [("(\\Flagged)", "Flagged message"), ("()", "Plain message")] {
+        let message = format!("From: test@domain.com\r\nSubject: {subject}\r\n\r\nTest\r\n");
+        imap.send(&format!(
+            "APPEND VirtualSource {flags} {{{}+}}\r\n{message}",
+            message.len()
+        ))
+        .await;
+        imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    }
+
+    // Define a virtual mailbox for flagged messages
+    let account_id = handle
+        .server
+        .store()
+        .get_principal_id("jdoe@example.com")
+        .await
+        .unwrap()
+        .unwrap();
+    let (mut mailboxes, current) = handle
+        .server
+        .get_virtual_mailboxes_for_update(account_id)
+        .await
+        .unwrap();
+    let id = mailboxes.insert("Flagged Items".into(), "FLAGGED UNDELETED".into());
+    handle
+        .server
+        .set_virtual_mailboxes(account_id, current, mailboxes)
+        .await
+        .unwrap();
+
+    // The virtual mailbox is listed and can be selected
+    imap.send("LIST \"\" \"Flagged Items\"").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("\"Flagged Items\"")
+        .assert_contains("\\NoInferiors");
+    imap.send("STATUS \"Flagged Items\" (MESSAGES UNSEEN)")
+        .await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("MESSAGES 1")
+        .assert_contains("UNSEEN 1");
+    imap.send("SELECT \"Flagged Items\"").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("1 EXISTS");
+    imap.send("FETCH 1 (BODY.PEEK[HEADER.FIELDS (SUBJECT)])")
+        .await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("Flagged message")
+        .assert_count("Plain message", 0);
+    imap.send("SEARCH RETURN (COUNT) SUBJECT \"Plain\"").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("COUNT 0");
+
+    // Flag changes are applied to the underlying message
+    imap.send("STORE 1 +FLAGS (\\Seen)").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap.send("STATUS VirtualSource (UNSEEN)").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("UNSEEN 1");
+
+    // Messages that leave and re-enter the result set are assigned a new UID
+    imap.send("UID FETCH 1:* (FLAGS)").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("UID 1 ");
+    imap_check.send("SELECT VirtualSource").await;
+    imap_check.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap_check.send("STORE 1 -FLAGS (\\Flagged)").await;
+    imap_check.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap_check
+        .send("STATUS \"Flagged Items\" (MESSAGES UIDNEXT)")
+        .await;
+    imap_check
+        .assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("MESSAGES 0")
+        .assert_contains("UIDNEXT 2");
+    imap_check.send("STORE 1 +FLAGS (\\Flagged)").await;
+    imap_check.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap_check
+        .send("STATUS \"Flagged Items\" (MESSAGES UIDNEXT)")
+        .await;
+    imap_check
+        .assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("MESSAGES 1")
+        .assert_contains("UIDNEXT 3");
+    imap_check.send("UNSELECT").await;
+    imap_check.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap.send("NOOP").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap.send("UID FETCH 1:* (FLAGS)").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_contains("UID 2 ")
+        .assert_count("UID 1 ", 0);
+
+    // Virtual mailboxes are read-only
+    let message = "From: test@domain.com\r\nSubject: Rejected\r\n\r\nTest\r\n";
+    imap.send(&format!(
+        "APPEND \"Flagged Items\" {{{}+}}\r\n{message}",
+        message.len()
+    ))
+    .await;
+    imap.assert_read(Type::Tagged, ResponseType::No)
+        .await
+        .assert_response_code("CANNOT");
+    imap.send("EXPUNGE").await;
+    imap.assert_read(Type::Tagged, ResponseType::No)
+        .await
+        .assert_response_code("CANNOT");
+    imap.send("CREATE \"Flagged Items/Child\"").await;
+    imap.assert_read(Type::Tagged, ResponseType::No).await;
+    imap.send("UNSELECT").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+    imap.send("DELETE \"Flagged Items\"").await;
+    imap.assert_read(Type::Tagged, ResponseType::No)
+        .await
+        .assert_response_code("CANNOT");
+
+    // Removing the definition removes the mailbox
+    let (mut mailboxes, current) = handle
+        .server
+        .get_virtual_mailboxes_for_update(account_id)
+        .await
+        .unwrap();
+    assert!(mailboxes.remove(id));
+    assert_eq!(mailboxes.insert("Unread".into(), "UNSEEN".into()), id + 1);
+    assert!(mailboxes.remove(id + 1));
+    handle
+        .server
+        .set_virtual_mailboxes(account_id, current, mailboxes)
+        .await
+        .unwrap();
+    imap.send("LIST \"\" \"Flagged Items\"").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_count("Flagged Items", 0);
+
+    // Clean up
+    imap.send("DELETE VirtualSource").await;
+    imap.assert_read(Type::Tagged, ResponseType::Ok).await;
+}
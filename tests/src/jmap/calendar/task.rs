@@ -0,0 +1,413 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use crate::jmap::{ChangeType, IntoJmapSet, JMAPTest, JmapUtils};
+use calcard::common::timezone::Tz;
+use groupware::calendar::CalendarEvent;
+use jmap_proto::request::method::MethodObject;
+use mail_parser::DateTime;
+use serde_json::json;
+use std::str::FromStr;
+use store::{
+    ValueKey,
+    write::{AlignedBytes, Archive, now},
+};
+use types::{collection::Collection, id::Id};
+
+pub async fn test(params: &mut JMAPTest) {
+    println!("Running Task tests...");
+    let account = params.account("jdoe@example.com");
+    let change_id = account
+        .jmap_get(MethodObject::TaskList, ["id"], Vec::<&str>::new())
+        .await
+        .state()
+        .to_string();
+    let task_change_id = account
+        .jmap_get(MethodObject::Task, ["id"], Vec::<&str>::new())
+        .await
+        .state()
+        .to_string();
+    let task_query_state = account
+        .jmap_method_call("Task/query", json!({}))
+        .await
+        .pointer("/methodResponses/0/1/queryState")
+        .and_then(|v| v.as_str())
+        .unwrap()
+        .to_string();
+
+    // Create a task list and a task referencing it in the same request
+    let response = account
+        .jmap_method_calls(json!([
+            [
+                "TaskList/set",
+                {
+                    "create": {
+                        "l1": {
+                            "name": "Chores",
+                            "description": "Things to do around the house"
+                        }
+                    }
+                },
+                "0"
+            ],
+            [
+                "Task/set",
+                {
+                    "create": {
+                        "t1": {
+                            "taskListId": "#l1",
+                            "title": "Water the plants",
+                            "description": "Do not forget the ones on the balcony",
+                            "uid": "0d2b9a6e-7f3c-4b8a-9f0e-3c1d2a6b5e4f",
+                            "timeZone": "Europe/Vienna",
+                            "start": "2025-06-01T09:00:00",
+                            "due": "2025-06-01T10:00:00",
+                            "recurrenceRules": [{
+                                "@type": "RecurrenceRule",
+                                "frequency": "weekly",
+                                "count": 4
+                            }],
+                            "alerts": {
+                                "a1": {
+                                    "@type": "Alert",
+                                    "trigger": {
+                                        "@type": "OffsetTrigger",
+                                        "offset": "-PT15M"
+                                    },
+                                    "action": "display"
+                                }
+                            }
+                        }
+                    }
+                },
+                "1"
+            ]
+        ]))
+        .await;
+    let task_list_id = response
+        .pointer("/methodResponses/0/1/created/l1/id")
+        .and_then(|v| v.as_str())
+        .unwrap_or_else(|| panic!("Missing created task list: {response:?}"))
+        .to_string();
+    let task_id = response
+        .pointer("/methodResponses/1/1/created/t1/id")
+        .and_then(|v| v.as_str())
+        .unwrap_or_else(|| panic!("Missing created task: {response:?}"))
+        .to_string();
+
+    // Validate changes
+    assert_eq!(
+        account
+            .jmap_changes(MethodObject::TaskList, &change_id)
+            .await
+            .changes()
+            .collect::<Vec<_>>(),
+        [ChangeType::Created(&task_list_id)]
+    );
+
+    // Get task list
+    let response = account
+        .jmap_get(
+            MethodObject::TaskList,
+            ["id", "name", "description", "isVisible"],
+            [&task_list_id],
+        )
+        .await;
+    response.list()[0].assert_is_equal(json!({
+        "id": task_list_id,
+        "name": "Chores",
+        "description": "Things to do around the house"
+    }));
+
+    // Get task
+    let response = account
+        .jmap_get(
+            MethodObject::Task,
+            ["id", "@type", "taskListId", "title", "start", "due"],
+            [&task_id],
+        )
+        .await;
+    response.list()[0].assert_is_equal(json!({
+        "id": task_id,
+        "@type": "Task",
+        "taskListId": task_list_id,
+        "title": "Water the plants",
+        "start": "2025-06-01T09:00:00",
+        "due": "2025-06-01T10:00:00"
+    }));
+    let task = &account
+        .jmap_get(MethodObject::Task, Vec::<&str>::new(), [&task_id])
+        .await
+        .list()[0];
+    assert!(task.pointer("/recurrenceRules/0").is_some(), "{task:?}");
+    assert!(task.pointer("/alerts/a1").is_some(), "{task:?}");
+    assert!(task.pointer("/calendarIds").is_none(), "{task:?}");
+
+    // Tasks without a task list or of a different type are rejected
+    let response = account
+        .jmap_create(
+            MethodObject::Task,
+            [
+                json!({
+                    "title": "Orphaned task"
+                }),
+                json!({
+                    "@type": "Event",
+                    "taskListId": task_list_id,
+                    "title": "Not a task"
+                }),
+                json!({
+                    "taskListId": "zzzzzz",
+                    "title": "Unknown task list"
+                }),
+            ],
+            Vec::<(&str, &str)>::new(),
+        )
+        .await;
+    assert_eq!(
+        response.not_created(0).text_field("type"),
+        "invalidProperties"
+    );
+    assert_eq!(
+        response.not_created(1).text_field("type"),
+        "invalidProperties"
+    );
+    assert_eq!(
+        response.not_created(2).text_field("type"),
+        "invalidProperties"
+    );
+
+    // Calendar events are not visible as tasks
+    let event_id = account
+        .jmap_create(
+            MethodObject::CalendarEvent,
+            [json!({
+                "@type": "Event",
+                "calendarIds": ([task_list_id.as_str()].into_jmap_set()),
+                "title": "Water the plants party",
+                "start": "2025-06-02T09:00:00",
+                "duration": "PT1H"
+            })],
+            Vec::<(&str, &str)>::new(),
+        )
+        .await
+        .created(0)
+        .id()
+        .to_string();
+    assert_eq!(
+        account
+            .jmap_get(MethodObject::Task, ["id"], [&event_id])
+            .await
+            .not_found()
+            .collect::<Vec<_>>(),
+        [event_id.as_str()]
+    );
+    assert_eq!(
+        account
+            .jmap_query(
+                MethodObject::Task,
+                [("inTaskList", task_list_id.as_str())],
+                ["start"],
+                Vec::<(&str, &str)>::new(),
+            )
+            .await
+            .ids()
+            .collect::<Vec<_>>(),
+        [task_id.as_str()]
+    );
+    assert_eq!(
+        account
+            .jmap_query(
+                MethodObject::Task,
+                [("title", "plants")],
+                Vec::<&str>::new(),
+                Vec::<(&str, &str)>::new(),
+            )
+            .await
+            .ids()
+            .collect::<Vec<_>>(),
+        [task_id.as_str()]
+    );
+    assert_eq!(
+        account
+            .jmap_destroy(MethodObject::Task, [&event_id], Vec::<(&str, &str)>::new())
+            .await
+            .not_destroyed(&event_id)
+            .text_field("type"),
+        "notFound"
+    );
+
+    // Changes to calendar events are not reported as task changes
+    assert_eq!(
+        account
+            .jmap_changes(MethodObject::Task, &task_change_id)
+            .await
+            .changes()
+            .collect::<Vec<_>>(),
+        [ChangeType::Created(&task_id)]
+    );
+    let response = account
+        .jmap_method_call(
+            "Task/queryChanges",
+            json!({
+                "sinceQueryState": task_query_state
+            }),
+        )
+        .await;
+    assert_eq!(
+        response.pointer("/methodResponses/0/1/added"),
+        Some(&json!([{"id": task_id, "index": 0}])),
+        "{response:?}"
+    );
+    assert_eq!(
+        response.pointer("/methodResponses/0/1/removed"),
+        Some(&json!([])),
+        "{response:?}"
+    );
+    let task_change_id = account
+        .jmap_get(MethodObject::Task, ["id"], Vec::<&str>::new())
+        .await
+        .state()
+        .to_string();
+    account
+        .jmap_update(
+            MethodObject::CalendarEvent,
+            [(&event_id, json!({"title": "Water the plants festival"}))],
+            Vec::<(&str, &str)>::new(),
+        )
+        .await
+        .updated(&event_id);
+    assert_eq!(
+        account
+            .jmap_changes(MethodObject::Task, &task_change_id)
+            .await
+            .changes()
+            .count(),
+        0
+    );
+
+    // Update task
+    account
+        .jmap_update(
+            MethodObject::Task,
+            [(
+                &task_id,
+                json!({
+                    "title": "Water all the plants",
+                    "progress": "completed"
+                }),
+            )],
+            Vec::<(&str, &str)>::new(),
+        )
+        .await
+        .updated(&task_id);
+    account
+        .jmap_get(
+            MethodObject::Task,
+            ["id", "title", "progress", "taskListId"],
+            [&task_id],
+        )
+        .await
+        .list()[0]
+        .assert_is_equal(json!({
+            "id": task_id,
+            "title": "Water all the plants",
+            "progress": "completed",
+            "taskListId": task_list_id
+        }));
+
+    // Alerts relative to the due date are scheduled
+    let due = (now() as i64 / 60 + 120) * 60;
+    let local_time = |timestamp: i64| {
+        DateTime::from_timestamp(timestamp)
+            .to_rfc3339()
+            .trim_end_matches('Z')
+            .to_string()
+    };
+    let alarm_task_id = account
+        .jmap_create(
+            MethodObject::Task,
+            [json!({
+                "taskListId": task_list_id,
+                "title": "Take out the trash",
+                "timeZone": "Etc/UTC",
+                "start": local_time(due - 3600),
+                "due": local_time(due),
+                "alerts": {
+                    "a1": {
+                        "@type": "Alert",
+                        "trigger": {
+                            "@type": "OffsetTrigger",
+                            "offset": "-PT15M",
+                            "relativeTo": "end"
+                        },
+                        "action": "display"
+                    }
+                }
+            })],
+            Vec::<(&str, &str)>::new(),
+        )
+        .await
+        .created(0)
+        .id()
+        .to_string();
+    let archive = params
+        .server
+        .store()
+        .get_value::<Archive<AlignedBytes>>(ValueKey::archive(
+            account.id().document_id(),
+            Collection::CalendarEvent,
+            Id::from_str(&alarm_task_id).unwrap().document_id(),
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    let alarm = archive
+        .unarchive::<CalendarEvent>()
+        .unwrap()
+        .data
+        .next_alarm(now() as i64, Tz::UTC)
+        .expect("Missing alarm for task");
+    assert_eq!(alarm.alarm_time, due - 900);
+
+    // Task lists with tasks can only be destroyed with onDestroyRemoveTasks
+    assert_eq!(
+        account
+            .jmap_destroy(
+                MethodObject::TaskList,
+                [&task_list_id],
+                Vec::<(&str, &str)>::new(),
+            )
+            .await
+            .not_destroyed(&task_list_id)
+            .text_field("type"),
+        "taskListHasTask"
+    );
+    assert_eq!(
+        account
+            .jmap_destroy(
+                MethodObject::TaskList,
+                [&task_list_id],
+                [("onDestroyRemoveTasks", true)],
+            )
+            .await
+            .destroyed()
+            .collect::<Vec<_>>(),
+        [task_list_id.as_str()]
+    );
+    assert_eq!(
+        account
+            .jmap_get(MethodObject::Task, ["id"], [&task_id])
+            .await
+            .not_found()
+            .collect::<Vec<_>>(),
+        [task_id.as_str()]
+    );
+
+    // Cleanup
+    account.destroy_all_calendars().await;
+    params.assert_is_empty().await;
+}
@@ -4,7 +4,7 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use crate::jmap::{Account, JMAPTest, wait_for_index};
+use crate::jmap::{Account, JMAPTest, JmapResponse, wait_for_index};
 use jmap_client::{
     Error, Set,
     client::{Client, Credentials},
@@ -16,10 +16,17 @@ use jmap_client::{
 };
 use jmap_proto::types::state::State;
 use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
 use std::time::Duration;
 use store::ahash::AHashMap;
 use types::id::Id;
 
+const METADATA_USING: &[&str] = &[
+    "urn:ietf:params:jmap:core",
+    "urn:ietf:params:jmap:mail",
+    "urn:stalwart:jmap:metadata",
+];
+
 pub async fn test(params: &mut JMAPTest) {
     println!("Running Mailbox tests...");
     let account = params.account("admin");
@@ -607,10 +614,164 @@ pub async fn test(params: &mut JMAPTest) {
         ["inbox", "sent", "spam"]
     );
 
+    // Mailbox metadata (shared with IMAP METADATA)
+    let inbox_id = id_map
+        .iter()
+        .find_map(|(id, name)| (name == "inbox").then_some(id.as_str()))
+        .unwrap();
+    metadata_update(
+        account,
+        inbox_id,
+        json!({
+            "metadata": {
+                "/private/comment": "My own comment",
+                "/shared/comment": "Shared comment"
+            }
+        }),
+    )
+    .await
+    .updated(inbox_id);
+    metadata_update(
+        account,
+        inbox_id,
+        json!({
+            "metadata/~1private~1comment": null,
+            "metadata/~1private~1vendor~1test~1color": "blue"
+        }),
+    )
+    .await
+    .updated(inbox_id);
+    assert_eq!(
+        metadata_get(account, inbox_id).await,
+        json!({
+            "/shared/comment": "Shared comment",
+            "/private/vendor/test/color": "blue"
+        })
+    );
+
+    // Setting the whole property replaces all entries
+    metadata_update(
+        account,
+        inbox_id,
+        json!({
+            "metadata": {
+                "/private/comment": "Replaced comment"
+            }
+        }),
+    )
+    .await
+    .updated(inbox_id);
+    assert_eq!(
+        metadata_get(account, inbox_id).await,
+        json!({
+            "/private/comment": "Replaced comment"
+        })
+    );
+    assert_eq!(
+        metadata_update(
+            account,
+            inbox_id,
+            json!({
+                "metadata": {},
+                "metadata/~1private~1comment": "value"
+            }),
+        )
+        .await
+        .not_updated(inbox_id)["type"],
+        "invalidProperties"
+    );
+    metadata_update(account, inbox_id, json!({ "metadata": null }))
+        .await
+        .updated(inbox_id);
+    assert_eq!(metadata_get(account, inbox_id).await, json!({}));
+
+    // The metadata property is only available with the vendor capability
+    let response = account
+        .jmap_method_calls_using(
+            &["urn:ietf:params:jmap:core", "urn:ietf:params:jmap:mail"],
+            json!([[
+                "Mailbox/get",
+                {
+                    "accountId": account.id_string(),
+                    "properties": ["metadata"],
+                    "ids": [inbox_id]
+                },
+                "0"
+            ]]),
+        )
+        .await;
+    assert_eq!(response.method_response()["type"], "invalidArguments");
+    let response = account
+        .jmap_method_calls_using(
+            &["urn:ietf:params:jmap:core", "urn:ietf:params:jmap:mail"],
+            json!([[
+                "Mailbox/set",
+                {
+                    "accountId": account.id_string(),
+                    "update": {
+                        inbox_id: { "metadata": { "/private/comment": "value" } }
+                    }
+                },
+                "0"
+            ]]),
+        )
+        .await;
+    assert_eq!(response.not_updated(inbox_id)["type"], "invalidProperties");
+
+    for invalid in ["/comment", "/private/specialuse", "/private/a*b"] {
+        assert_eq!(
+            metadata_update(
+                account,
+                inbox_id,
+                json!({ "metadata": { invalid: "value" } })
+            )
+            .await
+            .not_updated(inbox_id)["type"],
+            "invalidProperties"
+        );
+    }
+
     destroy_all_mailboxes_no_wait(&client).await;
     params.assert_is_empty().await;
 }
 
+async fn metadata_update(account: &Account, id: &str, update: Value) -> JmapResponse {
+    account
+        .jmap_method_calls_using(
+            METADATA_USING,
+            json!([[
+                "Mailbox/set",
+                {
+                    "accountId": account.id_string(),
+                    "update": {
+                        id: update
+                    }
+                },
+                "0"
+            ]]),
+        )
+        .await
+}
+
+async fn metadata_get(account: &Account, id: &str) -> Value {
+    account
+        .jmap_method_calls_using(
+            METADATA_USING,
+            json!([[
+                "Mailbox/get",
+                {
+                    "accountId": account.id_string(),
+                    "properties": ["metadata"],
+                    "ids": [id]
+                },
+                "0"
+            ]]),
+        )
+        .await
+        .list()[0]["metadata"]
+        .clone()
+}
+
 async fn create_test_mailboxes(client: &Client) -> AHashMap<String, String> {
     let mut mailbox_map = AHashMap::default();
     let mut request = client.build();
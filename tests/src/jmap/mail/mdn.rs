@@ -0,0 +1,253 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use crate::jmap::{
+    JMAPTest,
+    mail::submission::{expect_message_delivery, expect_nothing, spawn_mock_smtp_server},
+};
+use jmap_client::mailbox::Role;
+use serde_json::json;
+use std::time::Instant;
+
+pub async fn test(params: &mut JMAPTest) {
+    println!("Running MDN tests...");
+    let server = params.server.clone();
+    let account = params.account("jane.smith@example.com");
+    let client = account.client();
+    let (mut smtp_rx, smtp_settings) = spawn_mock_smtp_server();
+    server.ipv4_add(
+        "localhost",
+        vec!["127.0.0.1".parse().unwrap()],
+        Instant::now() + std::time::Duration::from_secs(10),
+    );
+
+    // Create an identity and import test messages
+    let identity_id = client
+        .identity_create("Jane Smith", "jane.smith@example.com")
+        .await
+        .unwrap()
+        .take_id();
+    let mailbox_id = client
+        .mailbox_create("JMAP MDN", None::<String>, Role::None)
+        .await
+        .unwrap()
+        .take_id();
+    let email_id = client
+        .email_import(
+            concat!(
+                "From: Bill Foobar <bill@remote.org>\r\n",
+                "To: Jane Smith <jane.smith@example.com>\r\n",
+                "Subject: Please confirm\r\n",
+                "Message-ID: <mdn-test@remote.org>\r\n",
+                "Disposition-Notification-To: Bill Foobar <bill@remote.org>\r\n",
+                "\r\n",
+                "Please let me know when you have read this.\r\n"
+            )
+            .as_bytes()
+            .to_vec(),
+            [&mailbox_id],
+            None::<Vec<&str>>,
+            None,
+        )
+        .await
+        .unwrap()
+        .take_id();
+    let email_id_no_dnt = client
+        .email_import(
+            concat!(
+                "From: Bill Foobar <bill@remote.org>\r\n",
+                "To: Jane Smith <jane.smith@example.com>\r\n",
+                "Subject: No receipt\r\n",
+                "\r\n",
+                "No notification requested.\r\n"
+            )
+            .as_bytes()
+            .to_vec(),
+            [&mailbox_id],
+            None::<Vec<&str>>,
+            None,
+        )
+        .await
+        .unwrap()
+        .take_id();
+    let disposition = json!({
+        "actionMode": "manual-action",
+        "sendingMode": "mdn-sent-manually",
+        "type": "displayed"
+    });
+
+    // Send an MDN and mark the original message as seen, this is the
+    // last delivery so the mock server can be stopped afterwards
+    smtp_settings.lock().do_stop = true;
+    let response = account
+        .jmap_method_call(
+            "MDN/send",
+            json!({
+                "identityId": identity_id,
+                "send": {
+                    "k1": {
+                        "forEmailId": email_id,
+                        "subject": "Read receipt",
+                        "textBody": "Your message has been displayed.",
+                        "reportingUA": "joes-pc.example.org; Foomail 97.1",
+                        "disposition": disposition,
+                        "extensionFields": {
+                            "X-Extension-Example": "example.com"
+                        }
+                    },
+                    "k2": {
+                        "forEmailId": email_id_no_dnt,
+                        "disposition": disposition
+                    },
+                    "k3": {
+                        "forEmailId": email_id,
+                        "disposition": disposition,
+                        "extensionFields": {
+                            "X-Invalid": "line\r\nInjected: header"
+                        }
+                    }
+                },
+                "onSuccessUpdateEmail": {
+                    "#k1": {
+                        "keywords/$seen": true
+                    }
+                }
+            }),
+        )
+        .await;
+    let sent = response.pointer("/methodResponses/0/1/sent/k1").unwrap();
+    assert_eq!(sent["finalRecipient"], "rfc822; jane.smith@example.com");
+    assert_eq!(sent["originalMessageId"], "mdn-test@remote.org");
+    assert!(sent.get("reportingUA").is_none(), "{sent:?}");
+    for (id, error) in [("k2", "invalidProperties"), ("k3", "invalidProperties")] {
+        assert_eq!(
+            response
+                .pointer(&format!("/methodResponses/0/1/notSent/{id}/type"))
+                .unwrap(),
+            error,
+            "{response:?}"
+        );
+    }
+    assert_eq!(
+        response.pointer("/methodResponses/1/0").unwrap(),
+        "Email/set"
+    );
+    assert!(
+        response
+            .pointer(&format!("/methodResponses/1/1/updated/{email_id}"))
+            .is_some(),
+        "{response:?}"
+    );
+
+    // Make sure the MDN was delivered
+    let message = expect_message_delivery(&mut smtp_rx).await;
+    assert_eq!(message.mail_from, "jane.smith@example.com");
+    assert_eq!(message.rcpt_to, vec!["bill@remote.org".to_string()]);
+    for needle in [
+        "Subject: Read receipt",
+        "multipart/report",
+        "report-type=\"disposition-notification\"",
+        "message/disposition-notification",
+        "Reporting-UA: joes-pc.example.org; Foomail 97.1",
+        "Final-Recipient: rfc822; jane.smith@example.com",
+        "Original-Message-ID: <mdn-test@remote.org>",
+        "Disposition: manual-action/MDN-sent-manually; displayed",
+        "X-Extension-Example: example.com",
+    ] {
+        assert!(
+            message.message.contains(needle),
+            "{needle}: {}",
+            message.message
+        );
+    }
+    expect_nothing(&mut smtp_rx).await;
+
+    // $MDNSent should be set on the original message
+    let keywords = client
+        .email_get(&email_id, None::<Vec<_>>)
+        .await
+        .unwrap()
+        .unwrap()
+        .keywords()
+        .into_iter()
+        .map(|k| k.to_string())
+        .collect::<Vec<_>>();
+    assert!(keywords.contains(&"$mdnsent".to_string()), "{keywords:?}");
+    assert!(keywords.contains(&"$seen".to_string()), "{keywords:?}");
+
+    // A second MDN for the same message must be rejected
+    let response = account
+        .jmap_method_call(
+            "MDN/send",
+            json!({
+                "identityId": identity_id,
+                "send": {
+                    "k1": {
+                        "forEmailId": email_id,
+                        "disposition": disposition
+                    }
+                }
+            }),
+        )
+        .await;
+    assert_eq!(
+        response
+            .pointer("/methodResponses/0/1/notSent/k1/type")
+            .unwrap(),
+        "mdnAlreadySent"
+    );
+    expect_nothing(&mut smtp_rx).await;
+
+    // Parse the delivered MDN
+    let mdn_blob_id = client
+        .upload(None, message.message.as_bytes().to_vec(), None)
+        .await
+        .unwrap()
+        .take_blob_id();
+    let other_blob_id = client
+        .upload(None, b"Subject: not an MDN\r\n\r\nHello".to_vec(), None)
+        .await
+        .unwrap()
+        .take_blob_id();
+    let response = account
+        .jmap_method_call(
+            "MDN/parse",
+            json!({
+                "blobIds": [mdn_blob_id, other_blob_id]
+            }),
+        )
+        .await;
+    let parsed = response
+        .pointer(&format!("/methodResponses/0/1/parsed/{mdn_blob_id}"))
+        .unwrap_or_else(|| panic!("{response:?}"));
+    assert_eq!(parsed["subject"], "Read receipt");
+    assert_eq!(parsed["reportingUA"], "joes-pc.example.org; Foomail 97.1");
+    assert_eq!(parsed["finalRecipient"], "rfc822; jane.smith@example.com");
+    assert_eq!(parsed["originalMessageId"], "mdn-test@remote.org");
+    assert_eq!(parsed["disposition"], disposition);
+    assert_eq!(
+        parsed["extensionFields"]["X-Extension-Example"],
+        "example.com"
+    );
+    assert!(
+        parsed["textBody"]
+            .as_str()
+            .unwrap()
+            .contains("Your message has been displayed."),
+        "{parsed:?}"
+    );
+    assert_eq!(
+        response
+            .pointer("/methodResponses/0/1/notParsable/0")
+            .unwrap(),
+        &json!(other_blob_id)
+    );
+
+    // Clean up
+    client.identity_destroy(&identity_id).await.unwrap();
+    params.destroy_all_mailboxes(account).await;
+    params.assert_is_empty().await;
+}
@@ -12,13 +12,17 @@ pub mod crypto;
 pub mod delivery;
 pub mod get;
 pub mod mailbox;
+pub mod mdn;
 pub mod parse;
 pub mod query;
 pub mod query_changes;
 pub mod search_snippet;
 pub mod set;
 pub mod sieve_script;
+pub mod smime;
+pub mod snooze;
 pub mod submission;
 pub mod thread_get;
 pub mod thread_merge;
 pub mod vacation_response;
+pub mod virtual_mailbox;
@@ -0,0 +1,219 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use crate::jmap::{JMAPTest, wait_for_index};
+use ::email::{
+    mailbox::INBOX_ID,
+    message::smime::{SmimeStatus, SmimeVerification, verify_smime},
+};
+use mail_parser::MessageParser;
+use serde_json::{Value, json};
+use std::path::PathBuf;
+use store::write::now;
+use types::id::Id;
+
+pub async fn test(params: &mut JMAPTest) {
+    println!("Running S/MIME verification tests...");
+    let server = params.server.clone();
+    let account = params.account("jdoe@example.com");
+    let client = account.client();
+
+    // Import test messages
+    let inbox_id = Id::from(INBOX_ID).to_string();
+    let mut email_ids = Vec::new();
+    for file_name in [
+        "smime_signed.eml",
+        "smime_opaque.eml",
+        "smime_tampered.eml",
+        "smime_untrusted.eml",
+        "smime_ber.eml",
+        "smime_encrypted.eml",
+    ] {
+        let raw_message = std::fs::read(
+            PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                .join("resources")
+                .join("crypto")
+                .join(file_name),
+        )
+        .unwrap();
+        email_ids.push(
+            client
+                .email_import(raw_message, [&inbox_id], None::<Vec<&str>>, None)
+                .await
+                .unwrap()
+                .take_id(),
+        );
+    }
+    email_ids.push(
+        client
+            .email_import(
+                concat!(
+                    "From: bill@remote.org\r\n",
+                    "To: jdoe@example.com\r\n",
+                    "Subject: Not signed\r\n",
+                    "\r\n",
+                    "Test message"
+                )
+                .as_bytes()
+                .to_vec(),
+                [&inbox_id],
+                None::<Vec<&str>>,
+                None,
+            )
+            .await
+            .unwrap()
+            .take_id(),
+    );
+
+    // Verify S/MIME status
+    let response = account
+        .jmap_method_call(
+            "Email/get",
+            json!({
+                "ids": &email_ids,
+                "properties": [
+                    "smimeStatus",
+                    "smimeErrors",
+                    "smimeVerifiedAt",
+                    "smimeStatusAtDelivery"
+                ]
+            }),
+        )
+        .await;
+    for (pos, (status, error)) in [
+        ("signed/verified", None),
+        ("signed/verified", None),
+        ("signed/failed", Some("Message digest mismatch")),
+        (
+            "signed/failed",
+            Some("Certificate validation failed: UnknownIssuer"),
+        ),
+        ("signed/verified", None),
+        ("encrypted+signed/verified", None),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let email = response
+            .pointer(&format!("/methodResponses/0/1/list/{pos}"))
+            .unwrap_or_else(|| panic!("{response:?}"));
+        assert_eq!(email["smimeStatus"], status, "{email:?}");
+        assert_eq!(email["smimeStatusAtDelivery"], status, "{email:?}");
+        assert!(email["smimeVerifiedAt"].is_string(), "{email:?}");
+        assert_eq!(
+            email["smimeErrors"],
+            error.map_or(Value::Null, |error| json!([error])),
+            "{email:?}"
+        );
+    }
+    let email = response
+        .pointer("/methodResponses/0/1/list/6")
+        .unwrap_or_else(|| panic!("{response:?}"));
+    for property in [
+        "smimeStatus",
+        "smimeErrors",
+        "smimeVerifiedAt",
+        "smimeStatusAtDelivery",
+    ] {
+        assert_eq!(email[property], Value::Null, "{email:?}");
+    }
+
+    // Filter by S/MIME status
+    wait_for_index(&server).await;
+    for (filter, expected_ids) in [
+        (json!({"hasSmime": true}), vec![0, 1, 2, 3, 4, 5]),
+        (json!({"hasSmime": false}), vec![6]),
+        (json!({"hasVerifiedSmime": true}), vec![0, 1, 4, 5]),
+        (json!({"hasVerifiedSmime": false}), vec![2, 3, 6]),
+        (
+            json!({"hasVerifiedSmimeAtDelivery": true}),
+            vec![0, 1, 4, 5],
+        ),
+        (json!({"hasVerifiedSmimeAtDelivery": false}), vec![2, 3, 6]),
+    ] {
+        let response = account
+            .jmap_method_call(
+                "Email/query",
+                json!({
+                    "filter": filter,
+                    "sort": [{"property": "receivedAt", "isAscending": true}]
+                }),
+            )
+            .await;
+        let mut ids = response
+            .pointer("/methodResponses/0/1/ids")
+            .and_then(|ids| ids.as_array())
+            .unwrap_or_else(|| panic!("{response:?}"))
+            .iter()
+            .map(|id| id.as_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        let mut expected_ids = expected_ids
+            .into_iter()
+            .map(|pos| email_ids[pos].clone())
+            .collect::<Vec<_>>();
+        ids.sort();
+        expected_ids.sort();
+        assert_eq!(ids, expected_ids, "{filter:?}");
+    }
+
+    // Without trust anchors valid signatures are reported as signed
+    let raw_message = std::fs::read(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources")
+            .join("crypto")
+            .join("smime_signed.eml"),
+    )
+    .unwrap();
+    let message = MessageParser::new().parse(&raw_message).unwrap();
+    assert_eq!(
+        verify_smime(&message, &[], now()),
+        Some(SmimeVerification {
+            status: SmimeStatus::Signed,
+            errors: vec!["No trust anchors configured".to_string()],
+        })
+    );
+
+    // Messages encrypted at rest fall back to the status at delivery
+    for (live, at_delivery, expected) in [
+        (
+            None,
+            Some(SmimeStatus::SignedVerified),
+            Some(SmimeStatus::SignedVerified),
+        ),
+        (
+            Some(SmimeStatus::Unknown),
+            Some(SmimeStatus::EncryptedSignedVerified),
+            Some(SmimeStatus::EncryptedSignedVerified),
+        ),
+        (
+            Some(SmimeStatus::SignedFailed),
+            Some(SmimeStatus::SignedVerified),
+            Some(SmimeStatus::SignedFailed),
+        ),
+        (
+            Some(SmimeStatus::Unknown),
+            Some(SmimeStatus::Unknown),
+            Some(SmimeStatus::Unknown),
+        ),
+        (None, None, None),
+    ] {
+        assert_eq!(
+            SmimeVerification::or_at_delivery(
+                live.map(|status| SmimeVerification {
+                    status,
+                    errors: vec![]
+                }),
+                at_delivery
+            )
+            .map(|smime| smime.status),
+            expected
+        );
+    }
+
+    // Clean up
+    params.destroy_all_mailboxes(account).await;
+    params.assert_is_empty().await;
+}
@@ -0,0 +1,230 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use crate::jmap::{JMAPTest, wait_for_index};
+use ::email::mailbox::INBOX_ID;
+use chrono::{TimeDelta, Utc};
+use jmap_client::mailbox::Role;
+use serde_json::{Value, json};
+use types::id::Id;
+
+pub async fn test(params: &mut JMAPTest) {
+    println!("Running Email snooze tests...");
+    let server = params.server.clone();
+    let account = params.account("jdoe@example.com");
+    let client = account.client();
+
+    // Create test mailboxes
+    let inbox_id = Id::from(INBOX_ID).to_string();
+    let later_id = client
+        .mailbox_create("Read later", None::<String>, Role::None)
+        .await
+        .unwrap()
+        .take_id();
+    let response = account
+        .jmap_method_call(
+            "Mailbox/set",
+            json!({
+                "create": {
+                    "s1": {
+                        "name": "Snoozed",
+                        "role": "snoozed"
+                    }
+                }
+            }),
+        )
+        .await;
+    let snoozed_id = response
+        .pointer("/methodResponses/0/1/created/s1/id")
+        .unwrap_or_else(|| panic!("{response:?}"))
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    // Import test messages
+    let mut email_ids = Vec::new();
+    for subject in ["Snooze me", "Move me later", "Cancel me"] {
+        email_ids.push(
+            client
+                .email_import(
+                    format!(
+                        concat!(
+                            "From: bill@remote.org\r\n",
+                            "To: jdoe@example.com\r\n",
+                            "Subject: {}\r\n",
+                            "\r\n",
+                            "Test message"
+                        ),
+                        subject
+                    )
+                    .into_bytes(),
+                    [&inbox_id],
+                    None::<Vec<&str>>,
+                    None,
+                )
+                .await
+                .unwrap()
+                .take_id(),
+        );
+    }
+
+    // Snooze all messages, the last one for a long time
+    let until =
+        (Utc::now() + TimeDelta::seconds(2)).to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let until_later =
+        (Utc::now() + TimeDelta::days(1)).to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let response = account
+        .jmap_method_call(
+            "Email/set",
+            json!({
+                "update": {
+                    &email_ids[0]: {
+                        "mailboxIds": { &snoozed_id: true },
+                        "snoozed": { "until": until }
+                    },
+                    &email_ids[1]: {
+                        "mailboxIds": { &snoozed_id: true },
+                        "snoozed": { "until": until, "moveToMailboxId": later_id }
+                    },
+                    &email_ids[2]: {
+                        "mailboxIds": { &snoozed_id: true },
+                        "snoozed": { "until": until_later }
+                    }
+                }
+            }),
+        )
+        .await;
+    for email_id in &email_ids {
+        assert!(
+            response
+                .pointer(&format!("/methodResponses/0/1/updated/{email_id}"))
+                .is_some(),
+            "{response:?}"
+        );
+    }
+    let email = get_email(params, &email_ids[1]).await;
+    assert_eq!(email["mailboxIds"], json!({ &snoozed_id: true }));
+    assert_eq!(email["keywords"], json!({ "$snoozed": true }));
+    assert_eq!(
+        email["snoozed"],
+        json!({ "until": until, "moveToMailboxId": later_id })
+    );
+
+    // Invalid snooze values should be rejected
+    let response = account
+        .jmap_method_call(
+            "Email/set",
+            json!({
+                "update": {
+                    &email_ids[0]: {
+                        "snoozed": { "moveToMailboxId": later_id }
+                    }
+                }
+            }),
+        )
+        .await;
+    assert_eq!(
+        response
+            .pointer(&format!(
+                "/methodResponses/0/1/notUpdated/{}/type",
+                email_ids[0]
+            ))
+            .unwrap(),
+        "invalidProperties"
+    );
+
+    // Snoozing is not supported on create or through patches
+    let response = account
+        .jmap_method_call(
+            "Email/set",
+            json!({
+                "create": {
+                    "e1": {
+                        "mailboxIds": { &inbox_id: true },
+                        "subject": "Snoozed on create",
+                        "snoozed": { "until": until_later }
+                    }
+                },
+                "update": {
+                    &email_ids[2]: {
+                        "snoozed/until": until
+                    }
+                }
+            }),
+        )
+        .await;
+    assert_eq!(
+        response
+            .pointer("/methodResponses/0/1/notCreated/e1/type")
+            .unwrap(),
+        "invalidProperties"
+    );
+    assert_eq!(
+        response
+            .pointer(&format!(
+                "/methodResponses/0/1/notUpdated/{}/type",
+                email_ids[2]
+            ))
+            .unwrap(),
+        "invalidProperties"
+    );
+
+    // Cancel the last snooze
+    let response = account
+        .jmap_method_call(
+            "Email/set",
+            json!({
+                "update": {
+                    &email_ids[2]: {
+                        "snoozed": null
+                    }
+                }
+            }),
+        )
+        .await;
+    assert!(
+        response
+            .pointer(&format!("/methodResponses/0/1/updated/{}", email_ids[2]))
+            .is_some(),
+        "{response:?}"
+    );
+    let email = get_email(params, &email_ids[2]).await;
+    assert_eq!(email["snoozed"], Value::Null);
+    assert_eq!(email["keywords"], json!({}));
+    assert_eq!(email["mailboxIds"], json!({ &snoozed_id: true }));
+
+    // Wait for the messages to wake up
+    wait_for_index(&server).await;
+    let email = get_email(params, &email_ids[0]).await;
+    assert_eq!(email["mailboxIds"], json!({ &inbox_id: true }));
+    assert_eq!(email["keywords"], json!({}));
+    assert_eq!(email["snoozed"], Value::Null);
+    let email = get_email(params, &email_ids[1]).await;
+    assert_eq!(email["mailboxIds"], json!({ &later_id: true }));
+    assert_eq!(email["keywords"], json!({}));
+    assert_eq!(email["snoozed"], Value::Null);
+
+    // Clean up
+    params.destroy_all_mailboxes(account).await;
+    params.assert_is_empty().await;
+}
+
+async fn get_email(params: &JMAPTest, email_id: &str) -> Value {
+    let response = params
+        .account("jdoe@example.com")
+        .jmap_method_call(
+            "Email/get",
+            json!({
+                "ids": [email_id],
+                "properties": ["mailboxIds", "keywords", "snoozed"]
+            }),
+        )
+        .await;
+    response
+        .pointer("/methodResponses/0/1/list/0")
+        .unwrap_or_else(|| panic!("{response:?}"))
+        .clone()
+}
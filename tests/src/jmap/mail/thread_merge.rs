@@ -241,6 +241,8 @@ async fn test_multi_thread(params: &mut JMAPTest) {
                             is_spam: false,
                         },
                         session_id: 0,
+                        batch: None,
+                        released_quota: 0,
                     })
                     .await
                 {
@@ -0,0 +1,205 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use crate::jmap::{Account, ChangeType, JMAPTest, JmapResponse};
+use ahash::AHashSet;
+use serde_json::{Value, json};
+
+const USING: &[&str] = &[
+    "urn:ietf:params:jmap:core",
+    "urn:ietf:params:jmap:mail",
+    "urn:stalwart:jmap:virtualmailbox",
+];
+
+pub async fn test(params: &mut JMAPTest) {
+    println!("Running VirtualMailbox tests...");
+    let account = params.account("jdoe@example.com");
+
+    // Virtual mailboxes require the vendor capability
+    let response = account
+        .jmap_method_calls_using(
+            &["urn:ietf:params:jmap:core", "urn:ietf:params:jmap:mail"],
+            json!([["VirtualMailbox/get", {}, "0"]]),
+        )
+        .await;
+    assert_eq!(
+        response
+            .pointer("/methodResponses/0/1/type")
+            .and_then(|v| v.as_str()),
+        Some("unknownMethod"),
+        "{response:?}"
+    );
+    assert!(
+        account
+            .jmap_session_object()
+            .await
+            .pointer("/capabilities/urn:stalwart:jmap:virtualmailbox")
+            .is_some()
+    );
+
+    // Obtain the initial state
+    let response = account
+        .virtual_mailbox_call("VirtualMailbox/get", json!({}))
+        .await;
+    let initial_state = response.state().to_string();
+
+    // Create two virtual mailboxes
+    let response = account
+        .virtual_mailbox_call(
+            "VirtualMailbox/set",
+            json!({
+                "create": {
+                    "i0": {
+                        "name": "Virtual Flagged",
+                        "query": "FLAGGED"
+                    },
+                    "i1": {
+                        "name": "Virtual Unseen",
+                        "query": "UNSEEN"
+                    }
+                }
+            }),
+        )
+        .await;
+    let flagged_id = response
+        .created(0)
+        .get("id")
+        .unwrap()
+        .as_str()
+        .unwrap()
+        .to_string();
+    let unseen_id = response
+        .created(1)
+        .get("id")
+        .unwrap()
+        .as_str()
+        .unwrap()
+        .to_string();
+    let created_state = response.new_state().to_string();
+    assert_ne!(created_state, initial_state);
+    assert_eq!(
+        account
+            .virtual_mailbox_call("VirtualMailbox/get", json!({}))
+            .await
+            .state(),
+        created_state
+    );
+
+    // Both mailboxes are reported as created
+    let response = account
+        .virtual_mailbox_call(
+            "VirtualMailbox/changes",
+            json!({
+                "sinceState": initial_state
+            }),
+        )
+        .await;
+    assert_eq!(response.new_state(), created_state);
+    let changes = response.changes().collect::<AHashSet<_>>();
+    assert_eq!(
+        changes,
+        AHashSet::from_iter([
+            ChangeType::Created(&flagged_id),
+            ChangeType::Created(&unseen_id),
+        ])
+    );
+
+    // Rename one mailbox and delete the other one
+    let response = account
+        .virtual_mailbox_call(
+            "VirtualMailbox/set",
+            json!({
+                "update": {
+                    (&flagged_id): {
+                        "name": "Virtual Important"
+                    }
+                },
+                "destroy": [&unseen_id]
+            }),
+        )
+        .await;
+    response.updated(&flagged_id);
+    assert_eq!(
+        response.destroyed().collect::<Vec<_>>(),
+        vec![unseen_id.as_str()]
+    );
+    let updated_state = response.new_state().to_string();
+
+    let response = account
+        .virtual_mailbox_call(
+            "VirtualMailbox/changes",
+            json!({
+                "sinceState": created_state
+            }),
+        )
+        .await;
+    assert_eq!(response.new_state(), updated_state);
+    assert_eq!(
+        response.changes().collect::<Vec<_>>(),
+        vec![
+            ChangeType::Updated(&flagged_id),
+            ChangeType::Destroyed(&unseen_id)
+        ]
+    );
+
+    // Mailboxes created and deleted after the state are not reported
+    let response = account
+        .virtual_mailbox_call(
+            "VirtualMailbox/changes",
+            json!({
+                "sinceState": initial_state
+            }),
+        )
+        .await;
+    assert_eq!(
+        response.changes().collect::<Vec<_>>(),
+        vec![ChangeType::Created(&flagged_id)]
+    );
+
+    // Outdated states are rejected
+    let response = account
+        .virtual_mailbox_call(
+            "VirtualMailbox/set",
+            json!({
+                "ifInState": created_state,
+                "destroy": [&flagged_id]
+            }),
+        )
+        .await;
+    assert_eq!(
+        response
+            .pointer("/methodResponses/0/1/type")
+            .and_then(|v| v.as_str()),
+        Some("stateMismatch"),
+        "{response:?}"
+    );
+
+    // Clean up
+    let response = account
+        .virtual_mailbox_call(
+            "VirtualMailbox/set",
+            json!({
+                "ifInState": updated_state,
+                "destroy": [&flagged_id]
+            }),
+        )
+        .await;
+    assert_eq!(
+        response.destroyed().collect::<Vec<_>>(),
+        vec![flagged_id.as_str()]
+    );
+}
+
+trait VirtualMailboxCall {
+    async fn virtual_mailbox_call(&self, method_name: &str, body: Value) -> JmapResponse;
+}
+
+impl VirtualMailboxCall for Account {
+    async fn virtual_mailbox_call(&self, method_name: &str, body: Value) -> JmapResponse {
+        self.jmap_method_calls_using(USING, json!([[method_name, body, "0"]]))
+            .await
+    }
+}
@@ -93,6 +93,10 @@ async fn jmap_tests() {
     mail::acl::test(&mut params).await;
     mail::sieve_script::test(&mut params).await;
     mail::vacation_response::test(&mut params).await;
+    mail::virtual_mailbox::test(&mut params).await;
+    mail::snooze::test(&mut params).await;
+    mail::smime::test(&mut params).await;
+    mail::mdn::test(&mut params).await;
     mail::submission::test(&mut params).await;
     mail::crypto::test(&mut params).await;
     mail::antispam::test(&mut params).await;
@@ -118,6 +122,7 @@ async fn jmap_tests() {
     calendar::event::test(&mut params).await;
     calendar::notification::test(&mut params).await;
     calendar::alarm::test(&mut params).await;
+    calendar::task::test(&mut params).await;
 
     calendar::identity::test(&mut params).await;
     calendar::acl::test(&mut params).await;
@@ -716,6 +721,18 @@ impl Account {
     }
 
     pub async fn jmap_method_calls(&self, calls: Value) -> JmapResponse {
+        self.jmap_method_calls_using(
+            &[
+                "urn:ietf:params:jmap:core",
+                "urn:ietf:params:jmap:mail",
+                "urn:ietf:params:jmap:quota",
+            ],
+            calls,
+        )
+        .await
+    }
+
+    pub async fn jmap_method_calls_using(&self, using: &[&str], calls: Value) -> JmapResponse {
         let mut headers = header::HeaderMap::new();
 
         headers.insert(
@@ -728,7 +745,7 @@ impl Account {
         );
 
         let body = json!({
-          "using": [ "urn:ietf:params:jmap:core", "urn:ietf:params:jmap:mail", "urn:ietf:params:jmap:quota" ],
+          "using": using,
           "methodCalls": calls
         });
 
@@ -1504,6 +1521,7 @@ attempts.interval = "500ms"
 
 [email]
 auto-expunge = "1s"
+smime.trust-anchors = "%{file:{SMIME_CA}}%"
 
 [changes]
 max-history = "1"
@@ -0,0 +1,187 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use common::{Core, auth::AccessToken};
+
+use directory::QueryParams;
+use email::{
+    cache::MessageCacheFetch,
+    mailbox::INBOX_ID,
+    message::{
+        ingest::{EmailIngest, IngestEmail, IngestSource},
+        urlauth::{ImapUrl, UrlAuth},
+    },
+};
+use mail_parser::MessageParser;
+use store::Stores;
+use utils::config::Config;
+
+use crate::{
+    AssertConfig,
+    smtp::{
+        TempDir, TestSMTP,
+        inbound::TestMessage,
+        session::{TestSession, VerifyResponse},
+    },
+};
+use smtp::core::Session;
+
+const MESSAGE: &str = "From: john@example.org
+To: jane@example.org
+Subject: BURL test
+
+Submitted by reference.
+";
+
+const CONFIG: &str = r#"
+[storage]
+data = "rocksdb"
+lookup = "rocksdb"
+blob = "rocksdb"
+fts = "rocksdb"
+directory = "local"
+
+[store."rocksdb"]
+type = "rocksdb"
+path = "{TMP}/queue.db"
+
+[directory."local"]
+type = "memory"
+
+[[directory."local".principals]]
+name = "john"
+description = "John Doe"
+secret = "secret"
+email = ["john@example.org"]
+
+[[directory."local".principals]]
+name = "jane"
+description = "Jane Doe"
+secret = "p4ssw0rd"
+email = "jane@example.org"
+
+[session.auth]
+mechanisms = [{if = "is_tls", then = "[plain]"},
+              {else = 0}]
+directory = "'local'"
+
+[session.rcpt]
+relay = true
+
+[session.extensions]
+burl = [{if = "remote_ip = '10.0.0.1' && !is_empty(authenticated_as)", then = true},
+        {else = false}]
+"#;
+
+#[tokio::test]
+async fn burl() {
+    // Enable logging
+    crate::enable_logging();
+
+    let tmp_dir = TempDir::new("smtp_burl_test", true);
+    let mut config = Config::new(tmp_dir.update_config(CONFIG)).unwrap();
+    let stores = Stores::parse_all(&mut config, false).await;
+    let core = Core::parse(&mut config, stores, Default::default()).await;
+    config.assert_no_errors();
+
+    // Store a message in John's inbox
+    let test = TestSMTP::from_core(core);
+    let server = test.server.clone();
+    let account_id = server
+        .directory()
+        .query(QueryParams::name("john").with_return_member_of(false))
+        .await
+        .unwrap()
+        .unwrap()
+        .id();
+    server.get_cached_messages(account_id).await.unwrap();
+    let uid = server
+        .email_ingest(IngestEmail {
+            raw_message: MESSAGE.as_bytes(),
+            message: MessageParser::new().parse(MESSAGE.as_bytes()),
+            blob_hash: None,
+            access_token: &AccessToken::from_id(account_id),
+            mailbox_ids: vec![INBOX_ID],
+            keywords: vec![],
+            received_at: None,
+            source: IngestSource::Smtp {
+                deliver_to: "john@example.org",
+                is_sender_authenticated: true,
+                is_spam: false,
+            },
+            session_id: 0,
+            batch: None,
+            released_quota: 0,
+        })
+        .await
+        .unwrap()
+        .imap_uids[0];
+    let url = server
+        .urlauth_generate(
+            account_id,
+            INBOX_ID,
+            &ImapUrl::parse(&format!(
+                "imap://john@localhost/INBOX;UID={uid};URLAUTH=submit+john"
+            ))
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    // BURL requires authentication
+    let mut session = Session::test(server);
+    session.data.remote_ip_str = "10.0.0.1".into();
+    session.eval_session_params().await;
+    session.stream.tls = true;
+    session
+        .ehlo("mx.foobar.org")
+        .await
+        .assert_not_contains("BURL");
+    session
+        .cmd("BURL imap://john@localhost/INBOX;UID=1 LAST", "530 5.7.0")
+        .await;
+
+    // BURL should be advertised after authenticating
+    session
+        .cmd("AUTH PLAIN AGpvaG4Ac2VjcmV0", "235 2.7.0")
+        .await;
+    session.ehlo("mx.foobar.org").await.assert_contains("BURL");
+
+    // Recipients are required before submitting a message
+    session.mail_from("john@example.org", "250").await;
+    session
+        .cmd("BURL imap://john@localhost/INBOX;UID=1 LAST", "503 5.5.1")
+        .await;
+    session.rcpt_to("jane@example.org", "250").await;
+
+    // Messages of other accounts or invalid references cannot be submitted
+    session
+        .cmd("BURL imap://jane@localhost/INBOX;UID=1 LAST", "554 5.6.6")
+        .await;
+    session.cmd("BURL not-a-reference LAST", "554 5.6.6").await;
+
+    // Submit John's message using an URLAUTH-authorized URL
+    session.cmd(&format!("BURL {url} LAST"), "250").await;
+    let message = test
+        .queue_receiver
+        .expect_message()
+        .await
+        .read_message(&test.queue_receiver)
+        .await;
+    assert!(message.contains("Subject: BURL test"), "{message}");
+    assert!(message.contains("Submitted by reference."), "{message}");
+
+    // BURL should not be available to 10.0.0.2
+    session.data.remote_ip_str = "10.0.0.2".into();
+    session.eval_post_auth_params().await;
+    session
+        .ehlo("mx.foobar.org")
+        .await
+        .assert_not_contains("BURL");
+    session
+        .cmd("BURL imap://john@localhost/INBOX;UID=1 LAST", "502 5.7.0")
+        .await;
+}
@@ -12,7 +12,7 @@ use mail_auth::{
     common::{parse::TxtRecordParser, verify::DomainKey},
     dkim::DomainKeyReport,
     dmarc::Dmarc,
-    report::DmarcResult,
+    report::{ActionDisposition, DmarcResult, PolicyOverride},
     spf::Spf,
 };
 use store::Stores;
@@ -305,3 +305,323 @@ async fn dmarc() {
         .assert_contains("dmarc=pass")
         .assert_contains("Received-SPF: pass");
 }
+
+const ARC_CONFIG: &str = r#"
+[storage]
+data = "rocksdb"
+lookup = "rocksdb"
+blob = "rocksdb"
+fts = "rocksdb"
+
+[store."rocksdb"]
+type = "rocksdb"
+path = "{TMP}/queue.db"
+
+[directory."local"]
+type = "memory"
+
+[[directory."local".principals]]
+name = "john"
+description = "John Doe"
+secret = "secret"
+email = ["jdoe@example.com"]
+
+[session.rcpt]
+directory = "'local'"
+
+[report.dmarc.aggregate]
+send = "daily"
+
+[auth.spf.verify]
+ehlo = "relaxed"
+mail-from = "relaxed"
+
+[auth.dkim]
+verify = "relaxed"
+sign = "{SIGN}"
+
+[auth.arc]
+verify = "relaxed"
+seal = "{SEAL}"
+trusted-sealers = ["{SEALER}"]
+
+[auth.dmarc]
+verify = "{DMARC}"
+
+"#;
+
+async fn arc_test_server(name: &str, sealer: &str) -> (TestSMTP, TempDir) {
+    arc_test_server_with(name, sealer, "false", "'rsa'", "strict").await
+}
+
+async fn arc_test_server_with(
+    name: &str,
+    sealer: &str,
+    sign: &str,
+    seal: &str,
+    dmarc: &str,
+) -> (TestSMTP, TempDir) {
+    let tmp_dir = TempDir::new(name, true);
+    let mut config = Config::new(
+        tmp_dir.update_config(
+            ARC_CONFIG
+                .replace("{SEALER}", sealer)
+                .replace("{SIGN}", sign)
+                .replace("{SEAL}", seal)
+                .replace("{DMARC}", dmarc)
+                + SIGNATURES,
+        ),
+    )
+    .unwrap();
+    let stores = Stores::parse_all(&mut config, false).await;
+    let core = Core::parse(&mut config, stores, Default::default()).await;
+    let test = TestSMTP::from_core(core);
+    test.server.txt_add(
+        "_dmarc.example.com",
+        Dmarc::parse(b"v=DMARC1; p=reject; rua=mailto:dmarc-feedback@example.com").unwrap(),
+        Instant::now() + Duration::from_secs(5),
+    );
+    (test, tmp_dir)
+}
+
+#[tokio::test]
+async fn dmarc_arc_override() {
+    // Enable logging
+    crate::enable_logging();
+
+    // The forwarder verifies the original DKIM signatures and seals the message
+    let (forwarder, _tmp_forwarder) = arc_test_server("smtp_dmarc_arc_forwarder", "").await;
+    forwarder.server.txt_add(
+        "ed._domainkey.example.com",
+        DomainKey::parse(
+            concat!(
+                "v=DKIM1; k=ed25519; ",
+                "p=11qYAYKxCrfVS/7TyWQHOg7hcvPapiMlrwIaaPcHURo="
+            )
+            .as_bytes(),
+        )
+        .unwrap(),
+        Instant::now() + Duration::from_secs(5),
+    );
+    forwarder.server.txt_add(
+        "default._domainkey.example.com",
+        DomainKey::parse(
+            concat!(
+                "v=DKIM1; t=s; p=MIGfMA0GCSqGSIb3DQEBAQUAA4GNADCBiQ",
+                "KBgQDwIRP/UC3SBsEmGqZ9ZJW3/DkMoGeLnQg1fWn7/zYt",
+                "IxN2SnFCjxOCKG9v3b4jYfcTNh5ijSsq631uBItLa7od+v",
+                "/RtdC2UzJ1lWT947qR+Rcac2gbto/NMqJ0fzfVjH4OuKhi",
+                "tdY9tf6mcwGjaNBcWToIMmPSPDdQPNUYckcQ2QIDAQAB",
+            )
+            .as_bytes(),
+        )
+        .unwrap(),
+        Instant::now() + Duration::from_secs(5),
+    );
+    let mut qr = forwarder.queue_receiver;
+    let mut session = Session::test(forwarder.server);
+    session.data.remote_ip_str = "10.0.0.1".into();
+    session.data.remote_ip = session.data.remote_ip_str.parse().unwrap();
+    session.eval_session_params().await;
+    session.ehlo("mx.example.com").await;
+    session
+        .send_message("bill@foobar.org", &["jdoe@example.com"], "test:dkim", "250")
+        .await;
+    let sealed_message = qr.expect_message().await.read_message(&qr).await;
+    assert!(
+        sealed_message.contains("ARC-Seal: i=1; a=rsa-sha256; s=rsa; d=example.com; cv=none;"),
+        "{sealed_message}"
+    );
+
+    // The original DKIM signatures can no longer be verified by the final recipient
+    let sealer_key = DomainKey::parse(
+        concat!(
+            "v=DKIM1; p=MIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8AMIIBCgKCAQEAv9XYXG3uK95115mB",
+            "4nJ37nGeNe2CrARm1agrbcnSk5oIaEfMZLUR/X8gPzoiNHZcfMZEVR6bAytxUhc5EvZIZrjSu",
+            "EEeny+fFd/cTvcm3cOUUbIaUmSACj0dL2/KwW0LyUaza9z9zor7I5XdIl1M53qVd5GI62XBB7",
+            "6FH+Q0bWPZNkT4NclzTLspD/MTpNCCPhySM4Kdg5CuDczTH4aNzyS0TqgXdtw6A4Sdsp97VXT",
+            "9fkPW9rso3lrkpsl/9EQ1mR/DWK6PBmRfIuSFuqnLKY6v/z2hXHxF7IoojfZLa2kZr9Aed4l9",
+            "WheQOTA19k5r2BmlRw/W9CrgCBo0Sdj+KQIDAQAB",
+        )
+        .as_bytes(),
+    )
+    .unwrap();
+
+    // DMARC policy should be enforced for untrusted sealers
+    let (untrusted, _tmp_untrusted) =
+        arc_test_server("smtp_dmarc_arc_untrusted", "other.org").await;
+    untrusted.server.txt_add(
+        "rsa._domainkey.example.com",
+        sealer_key.clone(),
+        Instant::now() + Duration::from_secs(5),
+    );
+    let mut session = Session::test(untrusted.server);
+    session.data.remote_ip_str = "10.0.0.1".into();
+    session.data.remote_ip = session.data.remote_ip_str.parse().unwrap();
+    session.eval_session_params().await;
+    session.ehlo("mx.foobar.org").await;
+    session
+        .send_message(
+            "bill@foobar.org",
+            &["jdoe@example.com"],
+            &sealed_message,
+            "550 5.7.1",
+        )
+        .await;
+
+    // Trusted sealers override the DMARC policy
+    let (trusted, _tmp_trusted) = arc_test_server("smtp_dmarc_arc_trusted", "example.com").await;
+    trusted.server.txt_add(
+        "rsa._domainkey.example.com",
+        sealer_key,
+        Instant::now() + Duration::from_secs(5),
+    );
+    let mut rr = trusted.report_receiver;
+    let mut qr = trusted.queue_receiver;
+    let mut session = Session::test(trusted.server);
+    session.data.remote_ip_str = "10.0.0.1".into();
+    session.data.remote_ip = session.data.remote_ip_str.parse().unwrap();
+    session.eval_session_params().await;
+    session.ehlo("mx.foobar.org").await;
+    session
+        .send_message(
+            "bill@foobar.org",
+            &["jdoe@example.com"],
+            &sealed_message,
+            "250",
+        )
+        .await;
+    qr.expect_message().await;
+
+    // The override should be included in the aggregate report
+    let report = rr.read_report().await.unwrap_dmarc();
+    assert_eq!(report.domain, "example.com");
+    assert_eq!(
+        report.report_record.action_disposition(),
+        ActionDisposition::None
+    );
+    assert!(
+        report
+            .report_record
+            .policy_override_reason()
+            .iter()
+            .any(|r| r.policy_override() == PolicyOverride::TrustedForwarder)
+    );
+}
+
+#[tokio::test]
+async fn dmarc_arc_unaligned_pass() {
+    // Enable logging
+    crate::enable_logging();
+
+    let rsa_key = DomainKey::parse(
+        concat!(
+            "v=DKIM1; p=MIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8AMIIBCgKCAQEAv9XYXG3uK95115mB",
+            "4nJ37nGeNe2CrARm1agrbcnSk5oIaEfMZLUR/X8gPzoiNHZcfMZEVR6bAytxUhc5EvZIZrjSu",
+            "EEeny+fFd/cTvcm3cOUUbIaUmSACj0dL2/KwW0LyUaza9z9zor7I5XdIl1M53qVd5GI62XBB7",
+            "6FH+Q0bWPZNkT4NclzTLspD/MTpNCCPhySM4Kdg5CuDczTH4aNzyS0TqgXdtw6A4Sdsp97VXT",
+            "9fkPW9rso3lrkpsl/9EQ1mR/DWK6PBmRfIuSFuqnLKY6v/z2hXHxF7IoojfZLa2kZr9Aed4l9",
+            "WheQOTA19k5r2BmlRw/W9CrgCBo0Sdj+KQIDAQAB",
+        )
+        .as_bytes(),
+    )
+    .unwrap();
+
+    // The attacker signs a spoofed message with a DKIM key of its own domain
+    let (signer, _tmp_signer) =
+        arc_test_server_with("smtp_dmarc_arc_signer", "", "['rsa']", "false", "disable").await;
+    let mut qr = signer.queue_receiver;
+    let mut session = Session::test(signer.server);
+    session.data.remote_ip_str = "10.0.0.1".into();
+    session.data.remote_ip = session.data.remote_ip_str.parse().unwrap();
+    session.eval_session_params().await;
+    session.ehlo("mx.example.com").await;
+    session
+        .send_message(
+            "bill@foobar.org",
+            &["jdoe@example.com"],
+            concat!(
+                "From: bill@foobar.org\r\n",
+                "To: jdoe@example.com\r\n",
+                "Date: Thu, 1 Jan 2026 00:00:00 +0000\r\n",
+                "Message-ID: <spoofed@foobar.org>\r\n",
+                "Subject: Spoofed\r\n",
+                "\r\n",
+                "Please wire the funds.\r\n"
+            ),
+            "250",
+        )
+        .await;
+    let signed_message = qr.expect_message().await.read_message(&qr).await;
+    assert!(
+        signed_message.contains("DKIM-Signature: v=1; a=rsa-sha256; s=rsa; d=example.com;"),
+        "{signed_message}"
+    );
+
+    // The trusted forwarder does not evaluate DMARC, it only records an
+    // unaligned DKIM pass in its ARC-Authentication-Results
+    let (forwarder, _tmp_forwarder) = arc_test_server_with(
+        "smtp_dmarc_arc_unaligned_forwarder",
+        "",
+        "false",
+        "'rsa'",
+        "disable",
+    )
+    .await;
+    forwarder.server.txt_add(
+        "rsa._domainkey.example.com",
+        rsa_key.clone(),
+        Instant::now() + Duration::from_secs(5),
+    );
+    let mut qr = forwarder.queue_receiver;
+    let mut session = Session::test(forwarder.server);
+    session.data.remote_ip_str = "10.0.0.1".into();
+    session.data.remote_ip = session.data.remote_ip_str.parse().unwrap();
+    session.eval_session_params().await;
+    session.ehlo("mx.example.com").await;
+    session
+        .send_message(
+            "bill@foobar.org",
+            &["jdoe@example.com"],
+            &signed_message,
+            "250",
+        )
+        .await;
+    let sealed_message = qr.expect_message().await.read_message(&qr).await;
+    assert!(
+        sealed_message.contains("ARC-Seal: i=1; a=rsa-sha256; s=rsa; d=example.com; cv=none;"),
+        "{sealed_message}"
+    );
+    assert!(!sealed_message.contains("dmarc="), "{sealed_message}");
+    assert!(
+        sealed_message.contains("dkim=pass header.d=example.com"),
+        "{sealed_message}"
+    );
+
+    // An unaligned DKIM pass from a trusted sealer must not override DMARC
+    let (trusted, _tmp_trusted) =
+        arc_test_server("smtp_dmarc_arc_unaligned_trusted", "example.com").await;
+    trusted.server.txt_add(
+        "rsa._domainkey.example.com",
+        rsa_key,
+        Instant::now() + Duration::from_secs(5),
+    );
+    trusted.server.txt_add(
+        "_dmarc.foobar.org",
+        Dmarc::parse(b"v=DMARC1; p=reject").unwrap(),
+        Instant::now() + Duration::from_secs(5),
+    );
+    let mut session = Session::test(trusted.server);
+    session.data.remote_ip_str = "10.0.0.1".into();
+    session.data.remote_ip = session.data.remote_ip_str.parse().unwrap();
+    session.eval_session_params().await;
+    session.ehlo("mx.foobar.org").await;
+    session
+        .send_message(
+            "bill@foobar.org",
+            &["jdoe@example.com"],
+            &sealed_message,
+            "550 5.7.1",
+        )
+        .await;
+}
@@ -26,6 +26,7 @@ pub mod antispam;
 pub mod asn;
 pub mod auth;
 pub mod basic;
+pub mod burl;
 pub mod data;
 pub mod dmarc;
 pub mod ehlo;
@@ -36,6 +37,7 @@ pub mod rcpt;
 pub mod rewrite;
 pub mod scripts;
 pub mod sign;
+pub mod srs;
 pub mod throttle;
 pub mod vrfy;
 
@@ -0,0 +1,195 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use common::{Core, config::smtp::queue::SrsConfig};
+use store::{Stores, write::now};
+use utils::config::Config;
+
+use crate::{
+    AssertConfig,
+    smtp::{
+        TempDir, TestSMTP,
+        session::{TestSession, VerifyResponse},
+    },
+};
+use smtp::{core::Session, queue::srs::SenderRewriting};
+
+const CONFIG: &str = r#"
+[storage]
+data = "rocksdb"
+lookup = "rocksdb"
+blob = "rocksdb"
+fts = "rocksdb"
+directory = "local"
+
+[store."rocksdb"]
+type = "rocksdb"
+path = "{TMP}/queue.db"
+
+[spam-filter]
+enable = false
+
+[directory."local"]
+type = "memory"
+
+[[directory."local".principals]]
+name = "john"
+description = "John Doe"
+secret = "secret"
+email = ["john@example.org"]
+
+[session.rcpt]
+directory = "'local'"
+relay = [{if = "remote_ip = '10.0.0.1'", then = true},
+         {else = false}]
+
+[session.rcpt.errors]
+total = 10
+wait = "1ms"
+
+[queue.srs]
+enable = true
+domain = "srs.example.org"
+secret = ["new-secret", "old-secret"]
+max-age = "10d"
+"#;
+
+#[tokio::test]
+async fn srs() {
+    // Enable logging
+    crate::enable_logging();
+
+    let tmp_dir = TempDir::new("smtp_srs_test", true);
+    let mut config = Config::new(tmp_dir.update_config(CONFIG)).unwrap();
+    let stores = Stores::parse_all(&mut config, false).await;
+    let core = Core::parse(&mut config, stores, Default::default()).await;
+    config.assert_no_errors();
+    let srs = core.smtp.queue.srs.clone().unwrap();
+
+    // Forwarded messages should have their return path rewritten
+    let test = TestSMTP::from_core(core);
+    let mut qr = test.queue_receiver;
+    let mut session = Session::test(test.server.clone());
+    session.data.remote_ip_str = "10.0.0.1".into();
+    session.eval_session_params().await;
+    session.ehlo("mx.remote.org").await;
+    session
+        .send_message(
+            "bill@remote.org",
+            &["jane@foobar.net"],
+            "test:no_dkim",
+            "250",
+        )
+        .await;
+    let return_path = qr.expect_message().await.message.return_path.to_string();
+    assert!(return_path.starts_with("SRS0="), "{return_path}");
+    assert!(
+        return_path.ends_with("=remote.org=bill@srs.example.org"),
+        "{return_path}"
+    );
+
+    // Local senders and local recipients are not rewritten
+    session
+        .send_message(
+            "john@example.org",
+            &["jane@foobar.net"],
+            "test:no_dkim",
+            "250",
+        )
+        .await;
+    assert_eq!(
+        qr.expect_message().await.message.return_path.as_ref(),
+        "john@example.org"
+    );
+    session
+        .send_message(
+            "bill@remote.org",
+            &["john@example.org"],
+            "test:no_dkim",
+            "250",
+        )
+        .await;
+    assert_eq!(
+        qr.expect_message().await.message.return_path.as_ref(),
+        "bill@remote.org"
+    );
+
+    // Bounces to SRS addresses are routed back to the original sender,
+    // even when relaying is not allowed
+    let mut session = Session::test(test.server.clone());
+    session.data.remote_ip_str = "10.0.0.2".into();
+    session.eval_session_params().await;
+    session.ehlo("mx.foobar.net").await;
+    session.mail_from("<>", "250").await;
+    session.rcpt_to("bill@remote.org", "550 5.1.2").await;
+    session.rcpt_to(&return_path, "250").await;
+    session.data("test:no_dkim", "250").await;
+    let message = qr.expect_message().await;
+    assert_eq!(message.message.return_path.as_ref(), "");
+    assert_eq!(message.message.recipients[0].address(), "bill@remote.org");
+
+    // Tampered and expired addresses are rejected
+    let (hash, rest) = return_path
+        .strip_prefix("SRS0=")
+        .unwrap()
+        .split_once('=')
+        .unwrap();
+    let tampered = format!("SRS0={hash}={}", rest.replacen("bill", "jane", 1));
+    session.mail_from("<>", "250").await;
+    session.rcpt_to(&tampered, "550 5.1.1").await;
+    let expired = srs
+        .srs_forward("bill@remote.org", now() - 11 * 86400)
+        .unwrap();
+    session.rcpt_to(&expired, "550 5.1.1").await;
+    session
+        .rcpt_to("SRS0=abcd@srs.example.org", "550 5.1.1")
+        .await;
+
+    // Addresses signed with a previous secret are still accepted
+    let mut old_srs = srs.clone();
+    old_srs.secrets = vec!["old-secret".into()];
+    let old_address = old_srs.srs_forward("bill@remote.org", now()).unwrap();
+    assert_ne!(
+        old_address,
+        srs.srs_forward("bill@remote.org", now()).unwrap()
+    );
+    assert_eq!(
+        srs.srs_reverse(&old_address, now()),
+        Some(Ok("bill@remote.org".into()))
+    );
+    let unknown_srs = SrsConfig {
+        secrets: vec!["unknown-secret".into()],
+        ..srs.clone()
+    };
+    assert_eq!(
+        srs.srs_reverse(
+            &unknown_srs.srs_forward("bill@remote.org", now()).unwrap(),
+            now()
+        ),
+        Some(Err("Invalid SRS hash"))
+    );
+
+    // Addresses rewritten by other forwarders are wrapped using SRS1
+    let srs0 = "SRS0=HHHH=TT=orig.org=bill@forwarder.org";
+    let srs1 = srs.srs_forward(srs0, now()).unwrap();
+    assert!(
+        srs1.starts_with("SRS1=")
+            && srs1.ends_with("=forwarder.org==HHHH=TT=orig.org=bill@srs.example.org"),
+        "{srs1}"
+    );
+    assert_eq!(srs.srs_reverse(&srs1, now()), Some(Ok(srs0.into())));
+    let rewrapped = srs
+        .srs_forward(&srs1.replace("@srs.example.org", "@other.org"), now())
+        .unwrap();
+    assert_eq!(srs.srs_reverse(&rewrapped, now()), Some(Ok(srs0.into())));
+
+    // Non-SRS addresses are ignored
+    assert_eq!(srs.srs_reverse("john@srs.example.org", now()), None);
+    assert_eq!(
+        srs.srs_reverse(&expired.replace("srs.example.org", "example.org"), now()),
+        None
+    );
+}
@@ -0,0 +1,84 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use common::{config::server::ServerProtocol, manager::dns::DnsRecord};
+use reqwest::Method;
+
+use crate::{jmap::ManagementApi, smtp::TestSMTP};
+
+const CONFIG: &str = r#"
+[storage]
+directory = "local"
+
+[directory."local"]
+type = "memory"
+
+[[directory."local".principals]]
+name = "admin"
+type = "admin"
+description = "Superuser"
+secret = "secret"
+class = "admin"
+"#;
+
+#[tokio::test]
+#[serial_test::serial]
+async fn manage_dns() {
+    // Enable logging
+    crate::enable_logging();
+
+    // Start management service
+    let local = TestSMTP::new("smtp_manage_dns", CONFIG).await;
+    let _rx = local.start(&[ServerProtocol::Http]).await;
+    let api = ManagementApi::default();
+
+    // Build the expected records
+    let records = api
+        .request::<Vec<DnsRecord>>(Method::GET, "/api/dns/records/example.org")
+        .await
+        .unwrap()
+        .unwrap_data();
+    for (typ, name, content) in [
+        ("MX", "example.org.", "10 mx.example.org."),
+        ("CNAME", "mail.example.org.", "mx.example.org."),
+        ("TXT", "mx.example.org.", "v=spf1 a ra=postmaster -all"),
+        ("TXT", "example.org.", "v=spf1 mx ra=postmaster -all"),
+        (
+            "TXT",
+            "_smtp._tls.example.org.",
+            "v=TLSRPTv1; rua=mailto:postmaster@example.org",
+        ),
+    ] {
+        assert!(
+            records.iter().any(|record| record.typ == typ
+                && record.name == name
+                && record.content == content),
+            "missing {typ} {name} {content}: {records:?}"
+        );
+    }
+    assert!(
+        records
+            .iter()
+            .any(|record| record.typ == "TXT" && record.name == "_dmarc.example.org."),
+        "{records:?}"
+    );
+
+    // Domains without a DNS provider cannot be synchronized
+    api.request::<serde_json::Value>(Method::POST, "/api/dns/sync/example.org")
+        .await
+        .unwrap()
+        .expect_error("not managed by a DNS provider");
+
+    // Unknown actions are rejected
+    assert_eq!(
+        api.request::<serde_json::Value>(Method::GET, "/api/dns/unknown/example.org")
+            .await
+            .unwrap()
+            .unwrap_request_error()
+            .status,
+        404
+    );
+}
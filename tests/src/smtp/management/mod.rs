@@ -4,5 +4,6 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
+pub mod dns;
 pub mod queue;
 pub mod report;
@@ -0,0 +1,179 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::{Duration, Instant};
+
+use common::config::server::ServerProtocol;
+use mail_auth::MX;
+use smtp::{
+    outbound::adaptive::{reset_throttled_domain, throttled_domains},
+    queue::Status,
+};
+use store::write::now;
+
+use crate::smtp::{DnsCache, TestSMTP, inbound::TestQueueEvent, session::TestSession};
+
+const LOCAL: &str = r#"
+[session.rcpt]
+relay = true
+
+[queue.adaptive-throttle]
+enable = true
+concurrency = 1
+rate = "2/1m"
+min-rate = 1
+recovery-interval = "1h"
+
+[spam-filter]
+enable = false
+
+"#;
+
+const REMOTE: &str = r#"
+[session.rcpt]
+relay = true
+
+[session.ehlo]
+reject-non-fqdn = false
+
+[[queue.limiter.inbound]]
+key = 'sender'
+rate = '1/1m'
+enable = true
+
+[spam-filter]
+enable = false
+
+"#;
+
+#[tokio::test]
+#[serial_test::serial]
+async fn adaptive_throttle() {
+    // Enable logging
+    crate::enable_logging();
+
+    // Start test server, which only accepts one message per sender and minute
+    let mut remote = TestSMTP::new("smtp_adaptive_remote", REMOTE).await;
+    let _rx = remote.start(&[ServerProtocol::Smtp]).await;
+    let mut local = TestSMTP::new("smtp_adaptive_local", LOCAL).await;
+
+    // Add mock DNS entries
+    let core = local.build_smtp();
+    let config = core.core.smtp.queue.adaptive_throttle.clone().unwrap();
+    core.mx_add(
+        "foobar.org",
+        vec![MX {
+            exchanges: vec!["mx.foobar.org".to_string()],
+            preference: 10,
+        }],
+        Instant::now() + Duration::from_secs(10),
+    );
+    core.ipv4_add(
+        "mx.foobar.org",
+        vec!["127.0.0.1".parse().unwrap()],
+        Instant::now() + Duration::from_secs(10),
+    );
+
+    let mut session = local.new_session();
+    session.data.remote_ip_str = "10.0.0.1".into();
+    session.eval_session_params().await;
+    session.ehlo("mx.test.org").await;
+
+    // Successful deliveries do not create any throttling state
+    session
+        .send_message("john@test.org", &["bill@foobar.org"], "test:no_dkim", "250")
+        .await;
+    local
+        .queue_receiver
+        .expect_message_then_deliver()
+        .await
+        .try_deliver(core.clone());
+    assert_eq!(
+        remote
+            .queue_receiver
+            .expect_message()
+            .await
+            .message
+            .recipients[0]
+            .address(),
+        "bill@foobar.org"
+    );
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    local
+        .queue_receiver
+        .read_event()
+        .await
+        .assert_refresh_or_done();
+    assert!(
+        !throttled_domains(&core, &config)
+            .iter()
+            .any(|d| d.domain == "foobar.org")
+    );
+
+    // A temporary failure halves the delivery rate for the domain
+    for (num, rcpt) in ["jane@foobar.org", "mike@foobar.org"]
+        .into_iter()
+        .enumerate()
+    {
+        session
+            .send_message("john@test.org", &[rcpt], "test:no_dkim", "250")
+            .await;
+        local
+            .queue_receiver
+            .expect_message_then_deliver()
+            .await
+            .try_deliver(core.clone());
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        local.queue_receiver.read_event().await.assert_refresh();
+        remote.queue_receiver.assert_no_events();
+        let messages = local.queue_receiver.read_queued_messages().await;
+        assert_eq!(messages.len(), 1);
+        assert!(
+            matches!(
+                messages[0].message.recipients[0].status,
+                Status::TemporaryFailure(_)
+            ),
+            "{:?}",
+            messages[0].message.recipients[0].status
+        );
+        local.queue_receiver.clear_queue(&core).await;
+
+        let throttle = throttled_domains(&core, &config)
+            .into_iter()
+            .find(|d| d.domain == "foobar.org")
+            .unwrap();
+        assert_eq!(throttle.rate, 1);
+        assert_eq!(throttle.max_rate, 2);
+        assert_eq!(throttle.concurrency, 1);
+        assert_eq!(throttle.in_flight, 0);
+        assert_eq!(throttle.deferrals, num as u64 + 1);
+    }
+
+    // The reduced rate has been exhausted, the message is not delivered
+    session
+        .send_message("john@test.org", &["tom@foobar.org"], "test:no_dkim", "250")
+        .await;
+    local
+        .queue_receiver
+        .expect_message_then_deliver()
+        .await
+        .try_deliver(core.clone());
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    local.queue_receiver.read_event().await.assert_refresh();
+    remote.queue_receiver.assert_no_events();
+    let due = local.queue_receiver.last_queued_due().await - now();
+    assert!(due > 0, "Due: {}", due);
+    local.queue_receiver.clear_queue(&core).await;
+
+    // Resetting the domain removes the throttling state
+    assert!(reset_throttled_domain(&core, "foobar.org"));
+    assert!(!reset_throttled_domain(&core, "foobar.org"));
+    assert!(
+        !throttled_domains(&core, &config)
+            .iter()
+            .any(|d| d.domain == "foobar.org")
+    );
+}
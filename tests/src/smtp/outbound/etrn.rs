@@ -0,0 +1,273 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::{Duration, Instant};
+
+use common::config::{server::ServerProtocol, smtp::queue::QueueName};
+use mail_auth::MX;
+use smtp::queue::{RCPT_PARKED, RCPT_RELEASED, Status, spool::SmtpSpool};
+
+use crate::smtp::{
+    DnsCache, TestSMTP,
+    session::{TestSession, VerifyResponse},
+};
+
+const LOCAL: &str = r#"
+[queue.strategy]
+route = [{if = "rcpt_domain == 'foobar.org'", then = "'hold'"},
+         {else = "'mx'"}]
+
+[queue.route.hold]
+type = "hold"
+
+[session.rcpt]
+relay = true
+
+[session.extensions]
+etrn = [{if = "remote_ip = '10.0.0.1'", then = true},
+        {else = false}]
+etrn-rate = "[2, 1m]"
+
+[spam-filter]
+enable = false
+
+"#;
+
+const REMOTE: &str = r#"
+[session.rcpt]
+relay = true
+
+[session.ehlo]
+reject-non-fqdn = false
+
+[session.extensions]
+chunking = false
+
+[spam-filter]
+enable = false
+
+"#;
+
+#[tokio::test]
+#[serial_test::serial]
+async fn etrn_hold() {
+    // Enable logging
+    crate::enable_logging();
+
+    // Start test server
+    let mut remote = TestSMTP::new("smtp_etrn_remote", REMOTE).await;
+    let _rx = remote.start(&[ServerProtocol::Smtp]).await;
+    let mut local = TestSMTP::new("smtp_etrn_local", LOCAL).await;
+
+    // Add mock DNS entries
+    let core = local.build_smtp();
+    core.mx_add(
+        "foobar.org",
+        vec![MX {
+            exchanges: vec!["mx.foobar.org".to_string()],
+            preference: 10,
+        }],
+        Instant::now() + Duration::from_secs(10),
+    );
+    core.ipv4_add(
+        "mx.foobar.org",
+        vec!["127.0.0.1".parse().unwrap()],
+        Instant::now() + Duration::from_secs(10),
+    );
+
+    // ETRN should not be available to 10.0.0.2
+    let mut session = local.new_session();
+    session.data.remote_ip_str = "10.0.0.2".into();
+    session.eval_session_params().await;
+    session
+        .ehlo("mx.test.org")
+        .await
+        .assert_not_contains("ETRN");
+    session.cmd("ETRN foobar.org", "502 5.7.0").await;
+
+    // Messages routed to a held domain should stay in the queue
+    session
+        .send_message("john@test.org", &["bill@foobar.org"], "test:no_dkim", "250")
+        .await;
+    local
+        .queue_receiver
+        .expect_message_then_deliver()
+        .await
+        .try_deliver(core.clone());
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    remote.queue_receiver.assert_no_events();
+    let messages = local.queue_receiver.read_queued_messages().await;
+    assert_eq!(messages.len(), 1);
+    let queue_id = messages[0].queue_id;
+    assert_eq!(messages[0].message.recipients[0].status, Status::Scheduled);
+    assert_eq!(messages[0].message.recipients[0].retry.inner, 0);
+    assert_ne!(messages[0].message.recipients[0].flags & RCPT_PARKED, 0);
+
+    // ETRN from 10.0.0.1 should release the held message
+    let mut session = local.new_session();
+    session.data.remote_ip_str = "10.0.0.1".into();
+    session.eval_session_params().await;
+    session.ehlo("mx.test.org").await.assert_contains("ETRN");
+    session.cmd("ETRN #default", "459 4.3.0").await;
+    session.cmd("ETRN example.org", "251 2.0.0").await;
+    session
+        .cmd("ETRN @org", "253 2.0.0")
+        .await
+        .assert_contains("1 pending messages");
+    let messages = local.queue_receiver.read_queued_messages().await;
+    assert_ne!(messages[0].message.recipients[0].flags & RCPT_RELEASED, 0);
+    assert_eq!(messages[0].message.recipients[0].flags & RCPT_PARKED, 0);
+
+    // Released recipients bypass the held route
+    local
+        .queue_receiver
+        .delivery_attempt(queue_id)
+        .await
+        .try_deliver(core.clone());
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(
+        remote
+            .queue_receiver
+            .expect_message()
+            .await
+            .message
+            .recipients[0]
+            .address(),
+        "bill@foobar.org"
+    );
+
+    // Pending message scans are rate limited
+    session.cmd("ETRN foobar.org", "458 4.7.0").await;
+}
+
+const ATRN: &str = r#"
+[queue.strategy]
+route = [{if = "rcpt_domain == 'foobar.org'", then = "'hold'"},
+         {else = "'mx'"}]
+
+[queue.route.hold]
+type = "hold"
+
+[queue.connection.default]
+timeout.greeting = "1s"
+
+[directory."local"]
+type = "memory"
+
+[[directory."local".principals]]
+name = "john"
+description = "John Doe"
+secret = "secret"
+email = ["john@foobar.org"]
+
+[[directory."local".principals]]
+name = "relay"
+class = "admin"
+description = "Relay for foobar.org"
+secret = "secret"
+email = ["postmaster@foobar.org"]
+
+[session.auth]
+mechanisms = "[plain]"
+directory = "'local'"
+
+[session.rcpt]
+relay = true
+
+[session.extensions]
+atrn = true
+
+[spam-filter]
+enable = false
+
+"#;
+
+#[tokio::test]
+#[serial_test::serial]
+async fn atrn_authorization() {
+    // Enable logging
+    crate::enable_logging();
+
+    let mut local = TestSMTP::new("smtp_atrn_local", ATRN).await;
+    let core = local.build_smtp();
+
+    // Messages routed to a held domain should be parked
+    let mut session = local.new_session();
+    session.data.remote_ip_str = "10.0.0.1".into();
+    session.eval_session_params().await;
+    session
+        .ehlo("mx.test.org")
+        .await
+        .assert_not_contains("ATRN");
+    session
+        .send_message("jane@test.org", &["bill@foobar.org"], "test:no_dkim", "250")
+        .await;
+    local
+        .queue_receiver
+        .expect_message_then_deliver()
+        .await
+        .try_deliver(core.clone());
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let messages = local.queue_receiver.read_queued_messages().await;
+    assert_eq!(messages.len(), 1);
+    assert_ne!(messages[0].message.recipients[0].flags & RCPT_PARKED, 0);
+
+    // Parked recipients are only scheduled to expire
+    assert_eq!(
+        messages[0].message.next_event(None),
+        Some(messages[0].message.recipients[0].parked_expiration_time(messages[0].message.created))
+    );
+
+    // Parked messages are not part of the quarantine workflow
+    let message = messages.into_iter().next().unwrap();
+    assert!(!message.is_held());
+    assert!(!message.release(&core, None).await);
+    let messages = local.queue_receiver.read_queued_messages().await;
+    assert_ne!(messages[0].message.recipients[0].flags & RCPT_PARKED, 0);
+    assert_eq!(messages[0].message.recipients[0].flags & RCPT_RELEASED, 0);
+
+    // ATRN requires authentication
+    session.cmd("ATRN foobar.org", "530 5.7.0").await;
+
+    // Ordinary users of a hosted domain are not allowed to dequeue it
+    session.stream.tls = true;
+    session
+        .cmd("AUTH PLAIN AGpvaG4Ac2VjcmV0", "235 2.7.0")
+        .await;
+    session.ehlo("mx.test.org").await.assert_contains("ATRN");
+    session
+        .cmd("ATRN foobar.org", "550 5.7.1")
+        .await
+        .assert_contains("Not authorized to use ATRN");
+
+    // Accounts with the ATRN permission can only dequeue their own domains
+    let mut session = local.new_session();
+    session.data.remote_ip_str = "10.0.0.1".into();
+    session.eval_session_params().await;
+    session.stream.tls = true;
+    session.ehlo("mx.test.org").await;
+    session
+        .cmd("AUTH PLAIN AHJlbGF5AHNlY3JldA==", "235 2.7.0")
+        .await;
+    session
+        .cmd("ATRN example.org", "550 5.7.1")
+        .await
+        .assert_contains("Not authorized for domain");
+    session.ingest(b"ATRN foobar.org\r\n").await.unwrap_err();
+    session
+        .response()
+        .assert_code("250 2.0.0")
+        .assert_contains("reversing the connection");
+
+    // The remote host never sent a greeting, the message stays parked and unlocked
+    let messages = local.queue_receiver.read_queued_messages().await;
+    assert_eq!(messages.len(), 1);
+    assert_ne!(messages[0].message.recipients[0].flags & RCPT_PARKED, 0);
+    assert!(
+        core.try_lock_event(messages[0].queue_id, QueueName::default())
+            .await
+    );
+}
@@ -0,0 +1,463 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use common::config::server::ServerProtocol;
+use http_proto::HttpResponse;
+use hyper::{Method, StatusCode, server::conn::http1, service::service_fn};
+use hyper_util::rt::TokioIo;
+use ring::hmac;
+use rustls::{
+    DigitallySignedStruct, DistinguishedName, ServerConfig, SignatureScheme,
+    client::danger::HandshakeSignatureValid,
+    crypto::{
+        CryptoProvider, ring::default_provider, verify_tls12_signature, verify_tls13_signature,
+    },
+    server::danger::{ClientCertVerified, ClientCertVerifier},
+};
+use rustls_pki_types::{CertificateDer, UnixTime};
+use smtp::queue::Status;
+use store::write::now;
+use tokio::{net::TcpListener, sync::watch};
+use tokio_rustls::TlsAcceptor;
+
+use crate::{
+    http_server::{HttpMessage, spawn_mock_http_server},
+    smtp::{
+        TestSMTP, add_test_certs,
+        inbound::{TestMessage, TestQueueEvent},
+        session::TestSession,
+    },
+};
+
+const LOCAL: &str = r#"
+[session.rcpt]
+relay = true
+
+[queue.strategy]
+route = [{if = "rcpt_domain = 'raw.org'", then = "'raw'"},
+         {if = "rcpt_domain = 'json.org'", then = "'json'"},
+         {if = "rcpt_domain = 'defer.org'", then = "'defer'"},
+         {if = "rcpt_domain = 'reject.org'", then = "'reject'"},
+         {if = "rcpt_domain = 'mtls.org'", then = "'mtls'"},
+         {else = "'mx'"}]
+
+[queue.schedule.default]
+retry = "1s"
+notify = "1d"
+expire = "1d"
+queue-name = "default"
+
+[queue.route.raw]
+type = "http"
+url = "https://127.0.0.1:9090/raw"
+format = "raw"
+signature-key = "secret"
+tls.allow-invalid-certs = true
+
+[queue.route.json]
+type = "http"
+url = "https://127.0.0.1:9090/json"
+format = "json"
+blob.url = "https://127.0.0.1:9980/webhook/blob"
+tls.allow-invalid-certs = true
+
+[queue.route.defer]
+type = "http"
+url = "https://127.0.0.1:9090/defer"
+format = "json"
+blob.url = "https://127.0.0.1:9980/webhook/blob"
+tls.allow-invalid-certs = true
+
+[queue.route.reject]
+type = "http"
+url = "https://127.0.0.1:9090/reject"
+tls.allow-invalid-certs = true
+
+[queue.route.mtls]
+type = "http"
+url = "https://127.0.0.1:9091/mtls"
+tls.allow-invalid-certs = true
+tls.certificate = '%{file:{CERT}}%'
+tls.private-key = '%{file:{PK}}%'
+
+[spam-filter]
+enable = false
+
+"#;
+
+const MESSAGE: &str = concat!(
+    "From: John Doe <john@test.org>\r\n",
+    "To: Jane <jane@json.org>\r\n",
+    "Subject: Webhook test\r\n",
+    "Message-ID: <webhook@test.org>\r\n",
+    "MIME-Version: 1.0\r\n",
+    "Content-Type: multipart/mixed; boundary=\"boundary\"\r\n",
+    "\r\n",
+    "--boundary\r\n",
+    "Content-Type: text/plain; charset=\"utf-8\"\r\n",
+    "\r\n",
+    "Hello world\r\n",
+    "--boundary\r\n",
+    "Content-Type: application/pdf\r\n",
+    "Content-Disposition: attachment; filename=\"report.pdf\"\r\n",
+    "\r\n",
+    "%PDF-1.4\r\n",
+    "--boundary--\r\n",
+    "\r\n.\r\n"
+);
+
+type Requests = Arc<Mutex<Vec<(String, ahash::AHashMap<String, String>, Vec<u8>)>>>;
+
+#[tokio::test]
+#[serial_test::serial]
+async fn http_delivery() {
+    // Enable logging
+    crate::enable_logging();
+
+    // Spawn mock webhook server
+    let requests = Requests::default();
+    let requests_ = requests.clone();
+    let _tx = spawn_mock_http_server(Arc::new(move |req: HttpMessage| {
+        assert_eq!(req.method, Method::POST);
+        let path = req.uri.path().to_string();
+        let status = match path.as_str() {
+            "/raw" | "/json" => StatusCode::OK,
+            "/defer" => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::BAD_REQUEST,
+        };
+        requests_
+            .lock()
+            .unwrap()
+            .push((path, req.headers, req.body.unwrap_or_default()));
+        HttpResponse::new(status)
+    }))
+    .await;
+
+    // Spawn mock endpoint requiring client certificates
+    let peer_certs = Arc::new(Mutex::new(Vec::new()));
+    let _tx_mtls = spawn_mock_mtls_server(peer_certs.clone());
+
+    let mut local = TestSMTP::new("smtp_http_local", add_test_certs(LOCAL)).await;
+    let _rx = local.start(&[ServerProtocol::Http]).await;
+    let core = local.build_smtp();
+    let mut session = local.new_session();
+    session.data.remote_ip_str = "10.0.0.1".into();
+    session.eval_session_params().await;
+    session.ehlo("mx.test.org").await;
+
+    // Raw delivery with signature
+    session
+        .send_message("john@test.org", &["bill@raw.org"], "test:no_dkim", "250")
+        .await;
+    local
+        .queue_receiver
+        .expect_message_then_deliver()
+        .await
+        .try_deliver(core.clone());
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    local
+        .queue_receiver
+        .read_event()
+        .await
+        .assert_refresh_or_done();
+    local.queue_receiver.assert_queue_is_empty().await;
+    {
+        let (path, headers, body) = requests.lock().unwrap().pop().unwrap();
+        assert_eq!(path, "/raw");
+        assert_eq!(headers.get("content-type").unwrap(), "message/rfc822");
+        assert_eq!(headers.get("x-envelope-from").unwrap(), "john@test.org");
+        assert_eq!(headers.get("x-envelope-to").unwrap(), "bill@raw.org");
+        let timestamp = headers
+            .get("x-signature-timestamp")
+            .unwrap()
+            .parse::<u64>()
+            .unwrap();
+        assert!(timestamp.abs_diff(now()) < 60);
+        let mut signed = format!("{timestamp}.").into_bytes();
+        signed.extend_from_slice(&body);
+        let key = hmac::Key::new(hmac::HMAC_SHA256, b"secret");
+        assert_eq!(
+            headers.get("x-signature").unwrap(),
+            &STANDARD.encode(hmac::sign(&key, &signed).as_ref())
+        );
+        assert!(String::from_utf8(body).unwrap().contains("Subject:"));
+    }
+
+    // JSON delivery with attachment links
+    session
+        .send_message("john@test.org", &["jane@json.org"], MESSAGE, "250")
+        .await;
+    local
+        .queue_receiver
+        .expect_message_then_deliver()
+        .await
+        .try_deliver(core.clone());
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    local
+        .queue_receiver
+        .read_event()
+        .await
+        .assert_refresh_or_done();
+    local.queue_receiver.assert_queue_is_empty().await;
+    let attachment_url = {
+        let (path, headers, body) = requests.lock().unwrap().pop().unwrap();
+        assert_eq!(path, "/json");
+        assert_eq!(headers.get("content-type").unwrap(), "application/json");
+        assert!(!headers.contains_key("x-signature"));
+        let payload = serde_json::from_slice::<serde_json::Value>(&body).unwrap();
+        assert_eq!(payload["envelope"]["from"], "john@test.org");
+        assert_eq!(payload["envelope"]["to"][0], "jane@json.org");
+        assert_eq!(payload["message"]["subject"], "Webhook test");
+        assert_eq!(payload["message"]["messageId"], "webhook@test.org");
+        assert_eq!(payload["message"]["from"][0]["email"], "john@test.org");
+        assert_eq!(payload["message"]["textBody"], "Hello world");
+        let attachment = &payload["message"]["attachments"][0];
+        assert_eq!(attachment["name"], "report.pdf");
+        assert_eq!(attachment["type"], "application/pdf");
+        let url = attachment["url"].as_str().unwrap().to_string();
+        assert!(
+            url.starts_with("https://127.0.0.1:9980/webhook/blob?i="),
+            "{attachment:?}"
+        );
+        url
+    };
+
+    // Attachment links can be fetched without authentication
+    let client = reqwest::Client::builder()
+        .danger_accept_invalid_certs(true)
+        .build()
+        .unwrap();
+    let response = client.get(&attachment_url).send().await.unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.bytes().await.unwrap().as_ref(), b"%PDF-1.4");
+    let response = client
+        .get("https://127.0.0.1:9980/webhook/blob?i=invalid")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 404);
+
+    // 5xx responses are retried
+    session
+        .send_message("john@test.org", &["bill@defer.org"], MESSAGE, "250")
+        .await;
+    local
+        .queue_receiver
+        .expect_message_then_deliver()
+        .await
+        .try_deliver(core.clone());
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    local.queue_receiver.read_event().await.assert_refresh();
+    let messages = local.queue_receiver.read_queued_messages().await;
+    assert_eq!(messages.len(), 1);
+    assert!(
+        matches!(
+            messages[0].message.recipients[0].status,
+            Status::TemporaryFailure(_)
+        ),
+        "{:?}",
+        messages[0].message.recipients[0].status
+    );
+
+    // Retries reuse the attachment links created by the first attempt
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    local
+        .queue_receiver
+        .delivery_attempt(messages[0].queue_id)
+        .await
+        .try_deliver(core.clone());
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    local.queue_receiver.read_event().await.assert_refresh();
+    let urls = requests
+        .lock()
+        .unwrap()
+        .drain(..)
+        .map(|(path, _, body)| {
+            assert_eq!(path, "/defer");
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()["message"]["attachments"][0]
+                ["url"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(urls.len(), 2);
+    assert_eq!(urls[0], urls[1]);
+    local.queue_receiver.clear_queue(&core).await;
+
+    // 4xx responses are permanent failures and generate a DSN
+    session
+        .send_message("john@test.org", &["bill@reject.org"], "test:no_dkim", "250")
+        .await;
+    local
+        .queue_receiver
+        .expect_message_then_deliver()
+        .await
+        .try_deliver(core.clone());
+    let dsn = local
+        .queue_receiver
+        .expect_message()
+        .await
+        .read_message(&local.queue_receiver)
+        .await;
+    assert!(dsn.contains("HTTP 400 Bad Request"), "{dsn}");
+    assert_eq!(requests.lock().unwrap().pop().unwrap().0, "/reject");
+
+    // Client certificates are presented to endpoints requiring mutual TLS
+    session
+        .send_message("john@test.org", &["bill@mtls.org"], "test:no_dkim", "250")
+        .await;
+    local
+        .queue_receiver
+        .expect_message_then_deliver()
+        .await
+        .try_deliver(core.clone());
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    local
+        .queue_receiver
+        .read_event()
+        .await
+        .assert_refresh_or_done();
+    local.queue_receiver.assert_queue_is_empty().await;
+    let (cert, _) = test_cert_and_key();
+    assert_eq!(
+        peer_certs.lock().unwrap().first(),
+        Some(&cert[0].to_vec()),
+        "client certificate not presented"
+    );
+}
+
+fn test_cert_and_key() -> (
+    Vec<CertificateDer<'static>>,
+    rustls_pki_types::PrivateKeyDer<'static>,
+) {
+    let mut cert_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    cert_path.push("resources");
+    cert_path.push("smtp");
+    cert_path.push("certs");
+    let cert = std::fs::read(cert_path.join("tls_cert.pem")).unwrap();
+    let key = std::fs::read(cert_path.join("tls_privatekey.pem")).unwrap();
+
+    (
+        rustls_pemfile::certs(&mut cert.as_slice())
+            .map(|cert| cert.unwrap())
+            .collect(),
+        rustls_pemfile::private_key(&mut key.as_slice())
+            .unwrap()
+            .unwrap(),
+    )
+}
+
+fn spawn_mock_mtls_server(peer_certs: Arc<Mutex<Vec<Vec<u8>>>>) -> watch::Sender<bool> {
+    let (tx, mut rx) = watch::channel(true);
+    let (cert, key) = test_cert_and_key();
+    let acceptor = TlsAcceptor::from(Arc::new(
+        ServerConfig::builder()
+            .with_client_cert_verifier(Arc::new(AcceptAnyClientCert(Arc::new(default_provider()))))
+            .with_single_cert(cert, key)
+            .unwrap(),
+    ));
+
+    tokio::spawn(async move {
+        let listener = TcpListener::bind("127.0.0.1:9091")
+            .await
+            .unwrap_or_else(|e| {
+                panic!("Failed to bind mock mTLS server to 127.0.0.1:9091: {e}");
+            });
+        loop {
+            tokio::select! {
+                stream = listener.accept() => {
+                    let (stream, _) = stream.unwrap();
+                    let acceptor = acceptor.clone();
+                    let peer_certs = peer_certs.clone();
+                    tokio::spawn(async move {
+                        let Ok(stream) = acceptor.accept(stream).await else {
+                            return;
+                        };
+                        if let Some(certs) = stream.get_ref().1.peer_certificates() {
+                            peer_certs
+                                .lock()
+                                .unwrap()
+                                .extend(certs.iter().map(|cert| cert.to_vec()));
+                        }
+                        let _ = http1::Builder::new()
+                            .keep_alive(false)
+                            .serve_connection(
+                                TokioIo::new(stream),
+                                service_fn(|_| async {
+                                    Ok::<_, hyper::Error>(HttpResponse::new(StatusCode::OK).build())
+                                }),
+                            )
+                            .await;
+                    });
+                },
+                _ = rx.changed() => {
+                    break;
+                }
+            };
+        }
+    });
+
+    tx
+}
+
+// Requests a client certificate and accepts any that is presented
+#[derive(Debug)]
+struct AcceptAnyClientCert(Arc<CryptoProvider>);
+
+impl ClientCertVerifier for AcceptAnyClientCert {
+    fn root_hint_subjects(&self) -> &[DistinguishedName] {
+        &[]
+    }
+
+    fn verify_client_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _now: UnixTime,
+    ) -> Result<ClientCertVerified, rustls::Error> {
+        Ok(ClientCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
@@ -0,0 +1,136 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::{Duration, Instant};
+
+use common::config::server::ServerProtocol;
+use mail_auth::MX;
+use smtp::queue::Status;
+
+use crate::smtp::{DnsCache, TestSMTP, session::TestSession};
+
+const LOCAL: &str = r#"
+[session.rcpt]
+relay = true
+
+[queue.connection.default]
+max-messages = 2
+
+[queue.connection.default.timeout]
+idle = "5s"
+
+[spam-filter]
+enable = false
+
+"#;
+
+const REMOTE: &str = r#"
+[session.rcpt]
+relay = true
+
+[session.ehlo]
+reject-non-fqdn = false
+
+[session.extensions]
+chunking = false
+
+[[queue.limiter.inbound]]
+match = "is_empty(sender)"
+key = 'remote_ip'
+rate = '1/1m'
+enable = true
+
+[spam-filter]
+enable = false
+
+"#;
+
+#[tokio::test]
+#[serial_test::serial]
+async fn connection_pool() {
+    // Enable logging
+    crate::enable_logging();
+
+    // Start test server, which only accepts one connection per minute
+    let mut remote = TestSMTP::new("smtp_pool_remote", REMOTE).await;
+    let _rx = remote.start(&[ServerProtocol::Smtp]).await;
+    let mut local = TestSMTP::new("smtp_pool_local", LOCAL).await;
+
+    // Add mock DNS entries
+    let core = local.build_smtp();
+    core.mx_add(
+        "foobar.org",
+        vec![MX {
+            exchanges: vec!["mx.foobar.org".to_string()],
+            preference: 10,
+        }],
+        Instant::now() + Duration::from_secs(10),
+    );
+    core.ipv4_add(
+        "mx.foobar.org",
+        vec!["127.0.0.1".parse().unwrap()],
+        Instant::now() + Duration::from_secs(10),
+    );
+
+    // The first two messages should be delivered over the same connection
+    let mut session = local.new_session();
+    session.data.remote_ip_str = "10.0.0.1".into();
+    session.eval_session_params().await;
+    session.ehlo("mx.test.org").await;
+    for (num, rcpt) in ["bill@foobar.org", "jane@foobar.org"]
+        .into_iter()
+        .enumerate()
+    {
+        session
+            .send_message("john@test.org", &[rcpt], "test:no_dkim", "250")
+            .await;
+        local
+            .queue_receiver
+            .expect_message_then_deliver()
+            .await
+            .try_deliver(core.clone());
+        assert_eq!(
+            remote
+                .queue_receiver
+                .expect_message()
+                .await
+                .message
+                .recipients[0]
+                .address(),
+            rcpt
+        );
+
+        // The connection is kept idle until it reaches the message limit
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(
+            core.inner.data.smtp_connection_pool.lock().is_empty(),
+            num == 1
+        );
+    }
+
+    // The connection is closed after reaching the message limit,
+    // the new connection is rejected by the remote rate limiter
+    session
+        .send_message("john@test.org", &["mike@foobar.org"], "test:no_dkim", "250")
+        .await;
+    local
+        .queue_receiver
+        .expect_message_then_deliver()
+        .await
+        .try_deliver(core.clone());
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    remote.queue_receiver.assert_no_events();
+    let messages = local.queue_receiver.read_queued_messages().await;
+    assert_eq!(messages.len(), 1);
+    assert!(
+        matches!(
+            messages[0].message.recipients[0].status,
+            Status::TemporaryFailure(_)
+        ),
+        "{:?}",
+        messages[0].message.recipients[0].status
+    );
+}
@@ -6,6 +6,7 @@
 
 use crate::smtp::{QueueReceiver, TestSMTP, inbound::sign::SIGNATURES};
 use common::config::smtp::queue::{QueueExpiry, QueueName};
+use mail_parser::{MessageParser, MimeHeaders};
 use smtp::queue::{
     Error, ErrorDetails, HostResponse, Message, MessageWrapper, Recipient, Schedule, Status,
     UnexpectedResponse, dsn::SendDsn,
@@ -171,7 +172,150 @@ async fn generate_dsn() {
     assert_eq!(queue.len(), 4);
 }
 
+const CONFIG_TEMPLATES: &str = r#"
+[report]
+submitter = "'mx.example.org'"
+
+[session.rcpt]
+relay = true
+
+[report.dsn]
+from-name = "'Mail Delivery Subsystem'"
+from-address = "'MAILER-DAEMON@example.org'"
+sign = "['rsa']"
+template = [{if = "sender_domain == 'foobar.org'", then = "'foobar'"},
+            {else = "'default'"}]
+
+[report.dsn-template.foobar]
+locale = "es"
+subject = "{{!subject}} ({{!sender}})"
+html = "<p>{{header}}</p><ul>{{#each failed}}<li>{{address}}: {{details}}</li>{{/each failed}}</ul>"
+
+"#;
+
+#[tokio::test]
+async fn dsn_templates() {
+    // Enable logging
+    crate::enable_logging();
+
+    let flags = RCPT_NOTIFY_FAILURE | RCPT_NOTIFY_DELAY | RCPT_NOTIFY_SUCCESS;
+    let original = "From: sender@foobar.org\r\nSubject: Test\r\n\r\nTest\r\n";
+    let mut message = MessageWrapper {
+        queue_id: 0,
+        span_id: 0,
+        is_multi_queue: false,
+        queue_name: QueueName::default(),
+        message: Message {
+            size: original.len() as u64,
+            created: now(),
+            return_path: "sender@foobar.org".into(),
+            recipients: vec![Recipient {
+                address: "foobar@example.org".into(),
+                status: Status::PermanentFailure(ErrorDetails {
+                    entity: "mx.example.org".into(),
+                    details: Error::UnexpectedResponse(UnexpectedResponse {
+                        command: "RCPT TO:<foobar@example.org>".into(),
+                        response: Response {
+                            code: 550,
+                            esc: [5, 1, 2],
+                            message: "User does not exist".into(),
+                        },
+                    }),
+                }),
+                flags,
+                orcpt: None,
+                retry: Schedule::now(),
+                notify: Schedule::now(),
+                expires: QueueExpiry::Ttl(10),
+                queue: QueueName::default(),
+            }],
+            flags: 0,
+            env_id: None,
+            priority: 0,
+            blob_hash: BlobHash::generate(original.as_bytes()),
+            quota_keys: Default::default(),
+            received_from_ip: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            received_via_port: 0,
+        },
+    };
+
+    let mut local = TestSMTP::new(
+        "smtp_dsn_template_test",
+        CONFIG_TEMPLATES.to_string() + SIGNATURES,
+    )
+    .await;
+    let core = local.build_smtp();
+    let qr = &mut local.queue_receiver;
+    qr.blob_store
+        .put_blob(message.message.blob_hash.as_slice(), original.as_bytes())
+        .await
+        .unwrap();
+
+    // DSNs are localized and rendered using the sender domain's template
+    core.send_dsn(&mut message.clone()).await;
+    let dsn_message = qr.expect_message().await;
+    let raw_dsn = qr.read_dsn(dsn_message.message).await;
+    let dsn = MessageParser::new().parse(&raw_dsn).unwrap();
+    assert_eq!(
+        dsn.subject(),
+        Some("No se pudo entregar el mensaje (sender@foobar.org)")
+    );
+    assert!(
+        dsn.parts
+            .iter()
+            .any(|part| part.is_content_type("multipart", "alternative"))
+    );
+    let text = dsn.body_text(0).unwrap();
+    assert!(
+        text.starts_with("No se pudo entregar su mensaje a los siguientes destinatarios:"),
+        "{text}"
+    );
+    assert!(text.contains("<foobar@example.org> (host 'mx.example.org' rejected"));
+    let html = dsn.body_html(0).unwrap();
+    assert!(
+        html.contains("<li>foobar@example.org: host &#39;mx.example.org&#39; rejected"),
+        "{html}"
+    );
+
+    // The delivery status report is not localized
+    let status = dsn
+        .parts
+        .iter()
+        .find(|part| part.is_content_type("message", "delivery-status"))
+        .and_then(|part| part.text_contents())
+        .unwrap();
+    assert!(status.contains(
+        "Final-Recipient: rfc822;foobar@example.org\r\nAction: failed\r\nStatus: 5.1.2\r\n"
+    ));
+
+    // Other senders get the default English template
+    message.message.return_path = "sender@example.com".into();
+    core.send_dsn(&mut message).await;
+    let dsn_message = qr.expect_message().await;
+    let raw_dsn = qr.read_dsn(dsn_message.message).await;
+    let dsn = MessageParser::new().parse(&raw_dsn).unwrap();
+    assert_eq!(dsn.subject(), Some("Failed to deliver message"));
+    assert!(
+        !dsn.parts
+            .iter()
+            .any(|part| part.is_content_type("text", "html"))
+    );
+    assert!(
+        dsn.body_text(0)
+            .unwrap()
+            .starts_with("Your message could not be delivered to the following recipients:")
+    );
+}
+
 impl QueueReceiver {
+    async fn read_dsn(&self, message: Message) -> Vec<u8> {
+        self.blob_store
+            .get_blob(message.blob_hash.as_slice(), 0..usize::MAX)
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
     async fn compare_dsn(&self, message: Message, test: &str) {
         let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         path.push("resources");
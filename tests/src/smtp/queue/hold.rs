@@ -0,0 +1,112 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use common::config::smtp::queue::QueueName;
+use smtp::queue::{Error, ErrorDetails, Status, spool::SmtpSpool};
+
+use crate::smtp::{TestSMTP, session::TestSession};
+
+const CONFIG: &str = r#"
+[session.rcpt]
+relay = true
+
+[session.data]
+hold = [{if = "sender == 'review@test.org'", then = true},
+        {else = false}]
+
+[spam-filter]
+enable = false
+
+"#;
+
+#[tokio::test]
+async fn queue_hold() {
+    // Enable logging
+    crate::enable_logging();
+
+    let test = TestSMTP::new("smtp_queue_hold_test", CONFIG).await;
+    let server = test.server.clone();
+    let mut session = test.new_session();
+    let mut qr = test.queue_receiver;
+    session.data.remote_ip_str = "10.0.0.1".into();
+    session.eval_session_params().await;
+    session.ehlo("mx.test.org").await;
+
+    // Messages not matching the hold rule are scheduled for delivery
+    session
+        .send_message("john@test.org", &["bill@foobar.org"], "test:no_dkim", "250")
+        .await;
+    let message = qr.consume_message(&server).await;
+    assert!(!message.is_held());
+    qr.assert_queue_is_empty().await;
+
+    // Held messages are stored without any queue events
+    session
+        .send_message(
+            "review@test.org",
+            &["bill@foobar.org"],
+            "test:no_dkim",
+            "250",
+        )
+        .await;
+    let message = qr.expect_message().await;
+    let queue_id = message.queue_id;
+    assert!(message.is_held());
+    assert_eq!(message.message.next_event(None), None);
+    assert_eq!(qr.read_queued_events().await, vec![]);
+    assert_eq!(
+        message.message.recipients[0].status,
+        Status::TemporaryFailure(ErrorDetails {
+            entity: "localhost".into(),
+            details: Error::Io("Matched hold rule".into()),
+        })
+    );
+
+    // Filters not matching any held recipient are ignored
+    assert!(!message.clone().release(&server, Some("jane")).await);
+    assert_eq!(qr.read_queued_events().await, vec![]);
+
+    // Release the message
+    assert!(message.release(&server, None).await);
+    qr.read_event().await.assert_refresh();
+    let message = server
+        .read_message(queue_id, QueueName::default())
+        .await
+        .unwrap();
+    assert!(!message.is_held());
+    assert_eq!(message.message.recipients[0].status, Status::Scheduled);
+    assert_eq!(qr.read_queued_events().await.len(), 1);
+
+    // Hold the message again
+    assert!(message.hold(&server, "Manual review").await);
+    let message = server
+        .read_message(queue_id, QueueName::default())
+        .await
+        .unwrap();
+    assert!(message.is_held());
+    assert_eq!(qr.read_queued_events().await, vec![]);
+    assert_eq!(
+        message.message.recipients[0].status,
+        Status::TemporaryFailure(ErrorDetails {
+            entity: "localhost".into(),
+            details: Error::Io("Manual review".into()),
+        })
+    );
+    assert!(!message.clone().hold(&server, "Manual review").await);
+
+    // Reject the message, a DSN should be sent to the sender
+    assert!(message.reject(&server, None, "Message rejected.").await);
+    assert!(
+        server
+            .read_message(queue_id, QueueName::default())
+            .await
+            .is_none()
+    );
+    let dsn = qr.expect_message().await;
+    assert_eq!(dsn.message.return_path.as_ref(), "");
+    assert_eq!(dsn.message.recipients[0].address(), "review@test.org");
+    assert_eq!(dsn.message.recipients[0].status, Status::Scheduled);
+}